@@ -0,0 +1,208 @@
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any
+
+import tomli
+import tomli_w
+
+from tach.errors import TachConfigError
+from tach.extension import ProjectConfig, dump_project_config_to_toml
+
+MODULE_PATH_PATTERN = re.compile(r"^[A-Za-z_]\w*(\.[A-Za-z_]\w*)*$")
+ARROW = "->"
+# The keys of the project config which are generated, all others are kept as they are
+GENERATED_KEYS = ("source_roots", "layers", "modules", "forbid")
+
+
+@dataclass
+class CompiledConfig:
+    """A project config compiled from an architecture DSL file."""
+
+    config_toml: str
+    modules: list[str]
+    layers: list[str]
+    forbid: list[dict[str, str]] = field(default_factory=list)
+
+
+@dataclass
+class _Architecture:
+    source_roots: list[str] | None = None
+    layers: list[str] | None = None
+    # Module path -> the modules it depends on, in the order they were declared
+    dependencies: dict[str, list[str]] = field(default_factory=dict)
+    module_layers: dict[str, str] = field(default_factory=dict)
+    # Layer name -> the line it was first assigned modules on
+    assigned_layers: dict[str, int] = field(default_factory=dict)
+    utilities: set[str] = field(default_factory=set)
+    forbid: list[dict[str, str]] = field(default_factory=list)
+
+    def declare(self, module: str) -> None:
+        self.dependencies.setdefault(module, [])
+
+
+class _Parser:
+    def __init__(self, path: Path):
+        self.path = path
+        self.architecture = _Architecture()
+        self.line_number = 0
+
+    def error(self, message: str) -> TachConfigError:
+        return TachConfigError(f"{self.path}:{self.line_number}: {message}")
+
+    def split_list(self, value: str, prefix: str = "") -> list[str]:
+        items = [item.strip() for item in value.split(",")]
+        if not all(items):
+            raise self.error(f"Expected a comma-separated list, found '{value}'")
+        modules = [f"{prefix}{item}" for item in items]
+        for module in modules:
+            if not MODULE_PATH_PATTERN.match(module):
+                raise self.error(f"'{module}' is not a valid module path")
+        return modules
+
+    def parse_chain(self, value: str, prefix: str = "") -> None:
+        """Each group of modules in 'a -> b, c -> d' depends on the group to its right."""
+        groups = [self.split_list(part, prefix) for part in value.split(ARROW)]
+        for module in (module for group in groups for module in group):
+            self.architecture.declare(module)
+        for sources, targets in zip(groups, groups[1:]):
+            for source in sources:
+                dependencies = self.architecture.dependencies[source]
+                dependencies.extend(
+                    target
+                    for target in targets
+                    if target != source and target not in dependencies
+                )
+
+    def parse_line(self, line: str) -> None:
+        statement, colon, value = line.partition(":")
+        statement, value = statement.strip(), value.strip()
+        # e.g. 'layer web' or 'domain myapp.billing'
+        keyword, _, name = statement.partition(" ")
+        architecture = self.architecture
+        if not colon:
+            self.parse_chain(line)
+        elif statement == "source_roots":
+            architecture.source_roots = [
+                root.strip() for root in value.split(",") if root.strip()
+            ]
+        elif statement == "layers":
+            if architecture.layers is not None:
+                raise self.error("'layers' may only be declared once")
+            architecture.layers = [layer.strip() for layer in value.split(ARROW)]
+            if not all(architecture.layers):
+                raise self.error(f"Expected layers separated by '{ARROW}'")
+        elif statement == "utility":
+            for module in self.split_list(value):
+                architecture.declare(module)
+                architecture.utilities.add(module)
+        elif statement == "forbid":
+            rule, _, unless_through = value.partition(" unless ")
+            parts = [part.strip() for part in rule.split(ARROW)]
+            if len(parts) != 2 or not all(parts):
+                raise self.error(
+                    f"Expected 'forbid: <from> {ARROW} <to>', optionally followed by 'unless <through>'"
+                )
+            forbid = {"from": parts[0], "to": parts[1]}
+            if unless_through.strip():
+                forbid["unless_through"] = unless_through.strip()
+            architecture.forbid.append(forbid)
+        elif keyword == "layer":
+            layer = name.strip()
+            architecture.assigned_layers.setdefault(layer, self.line_number)
+            for module in self.split_list(value):
+                architecture.declare(module)
+                assigned = architecture.module_layers.setdefault(module, layer)
+                if assigned != layer:
+                    raise self.error(
+                        f"Module '{module}' is already in layer '{assigned}'"
+                    )
+        elif keyword == "domain":
+            domain = name.strip()
+            if not MODULE_PATH_PATTERN.match(domain):
+                raise self.error(f"'{domain}' is not a valid module path")
+            self.parse_chain(value, prefix=f"{domain}.")
+        else:
+            raise self.error(f"Unknown statement '{statement}'")
+
+    def parse(self, contents: str) -> _Architecture:
+        for line_number, line in enumerate(contents.splitlines(), start=1):
+            self.line_number = line_number
+            line = line.split("#", 1)[0].strip()
+            if line:
+                self.parse_line(line)
+
+        architecture = self.architecture
+        for layer, line_number in architecture.assigned_layers.items():
+            if layer not in (architecture.layers or []):
+                self.line_number = line_number
+                raise self.error(
+                    f"Layer '{layer}' is not declared in 'layers: ...'"
+                )
+        return architecture
+
+
+def compile_architecture(
+    project_root: Path, path: Path, config_path: Path
+) -> CompiledConfig:
+    """
+    Compile an architecture DSL file into the project config at 'config_path'.
+
+    Modules, layers, source roots and '[[forbid]]' rules are generated from the DSL,
+    while the rest of an existing project config is kept.
+    """
+    try:
+        contents = path.read_text()
+    except OSError as e:
+        raise TachConfigError(f"Failed to read '{path}': {e}")
+    architecture = _Parser(path).parse(contents)
+
+    if config_path.exists():
+        try:
+            config = tomli.loads(config_path.read_text())
+        except (OSError, tomli.TOMLDecodeError) as e:
+            raise TachConfigError(f"Failed to read '{config_path}': {e}")
+    else:
+        config = tomli.loads(dump_project_config_to_toml(ProjectConfig()))
+    for key in GENERATED_KEYS:
+        if key != "source_roots" or architecture.source_roots is not None:
+            config.pop(key, None)
+
+    if architecture.source_roots is not None:
+        config["source_roots"] = architecture.source_roots
+    if architecture.layers:
+        config["layers"] = architecture.layers
+    modules: list[dict[str, Any]] = []
+    for module in sorted(architecture.dependencies):
+        module_config: dict[str, Any] = {
+            "path": module,
+            "depends_on": sorted(architecture.dependencies[module]),
+        }
+        if module in architecture.module_layers:
+            module_config["layer"] = architecture.module_layers[module]
+        if module in architecture.utilities:
+            module_config["utility"] = True
+        modules.append(module_config)
+    config["modules"] = modules
+    if architecture.forbid:
+        config["forbid"] = architecture.forbid
+
+    try:
+        source = path.relative_to(project_root)
+    except ValueError:
+        source = path
+    header = (
+        f"# Modules, layers and forbid rules are generated from '{source.as_posix()}'.\n"
+        f"# Edit it instead, and run 'tach compile {source.as_posix()}' to update this file.\n\n"
+    )
+    return CompiledConfig(
+        config_toml=header + tomli_w.dumps(config),
+        modules=sorted(architecture.dependencies),
+        layers=architecture.layers or [],
+        forbid=architecture.forbid,
+    )
+
+
+__all__ = ["CompiledConfig", "compile_architecture"]
@@ -0,0 +1,78 @@
+from __future__ import annotations
+
+import json
+from dataclasses import dataclass
+from html import escape
+
+PASSING_COLOR = "brightgreen"
+FAILING_COLOR = "red"
+
+# Hex values of the named colors, as used by shields.io
+COLORS = {PASSING_COLOR: "#4c1", FAILING_COLOR: "#e05d44"}
+
+# Approximate width of a character in 11px Verdana, which shields.io badges are set in
+CHARACTER_WIDTH = 7
+HORIZONTAL_PADDING = 10
+
+
+@dataclass
+class Badge:
+    label: str
+    message: str
+    color: str
+
+    @classmethod
+    def from_violation_count(
+        cls, violations: int, label: str = "architecture"
+    ) -> Badge:
+        if violations == 0:
+            return cls(label=label, message="passing", color=PASSING_COLOR)
+        suffix = "" if violations == 1 else "s"
+        return cls(
+            label=label, message=f"{violations} violation{suffix}", color=FAILING_COLOR
+        )
+
+    def to_json(self) -> str:
+        """The badge in the shields.io endpoint format (https://shields.io/badges/endpoint-badge)."""
+        payload = {
+            "schemaVersion": 1,
+            "label": self.label,
+            "message": self.message,
+            "color": self.color,
+        }
+        return json.dumps(payload, indent=2) + "\n"
+
+    def to_svg(self) -> str:
+        """The badge as a flat shields.io-style SVG image."""
+        label_width = len(self.label) * CHARACTER_WIDTH + HORIZONTAL_PADDING
+        message_width = len(self.message) * CHARACTER_WIDTH + HORIZONTAL_PADDING
+        width = label_width + message_width
+        color = COLORS.get(self.color, self.color)
+        label = escape(self.label)
+        message = escape(self.message)
+        return f"""\
+<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r">
+    <rect width="{width}" height="20" rx="3" fill="#fff"/>
+  </clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_width / 2}" y="15" fill="#010101" fill-opacity=".3">{label}</text>
+    <text x="{label_width / 2}" y="14">{label}</text>
+    <text x="{label_width + message_width / 2}" y="15" fill="#010101" fill-opacity=".3">{message}</text>
+    <text x="{label_width + message_width / 2}" y="14">{message}</text>
+  </g>
+</svg>
+"""
+
+
+__all__ = ["Badge"]
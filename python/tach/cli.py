@@ -1,21 +1,28 @@
 from __future__ import annotations
 
 import argparse
+import difflib
 import json
+import os
 import sys
+import time
 from dataclasses import dataclass, field
+from datetime import datetime
 from enum import Enum
 from pathlib import Path
 from typing import TYPE_CHECKING, Any
 
 from tach import __version__, cache, extension, icons
 from tach import filesystem as fs
+from tach.arch_dsl import compile_architecture
+from tach.badge import Badge
 from tach.check_external import check_external
 from tach.colors import BCOLORS
 from tach.console import console, console_err
-from tach.constants import CONFIG_FILE_NAME, TOOL_NAME
+from tach.constants import CONFIG_FILE_NAME, LOG_ENV_VAR, RULES_ENV_VAR, TOOL_NAME
 from tach.errors import (
     TachCircularDependencyError,
+    TachCircularDomainDependencyError,
     TachClosedBetaError,
     TachConfigError,
     TachError,
@@ -24,20 +31,54 @@ from tach.errors import (
 )
 from tach.extension import ProjectConfig
 from tach.filesystem import install_pre_commit
+from tach.export_config import export_to_deptry, export_to_import_linter
+from tach.import_config import import_from_import_linter
 from tach.init import init_project
 from tach.logging import CallInfo, init_logging, logger
-from tach.modularity import export_report, upload_report_to_gauge
-from tach.parsing import extend_and_validate, parse_project_config
-from tach.report import external_dependency_report, report
+from tach.modularity import (
+    build_import_side_effects,
+    export_report,
+    upload_report_to_gauge,
+)
+from tach.parsing import extend_and_validate, parse_project_config_file
+from tach.pr_comment import github_link_base, render_pr_comment
+from tach.report import (
+    combined_report,
+    dependency_matrix,
+    dependency_record_to_dict,
+    dependency_records,
+    external_dependency_report,
+    interface_usage_report,
+    render_dependency_matrix,
+    render_dependency_matrix_csv,
+    render_dependency_matrix_html,
+    render_dependency_records_csv,
+    report,
+    side_effect_report,
+    unused_interface_members_report,
+    unused_modules_report,
+)
 from tach.show import (
+    build_graph,
+    generate_module_graph_d2,
     generate_module_graph_dot_file,
     generate_module_graph_mermaid,
+    generate_module_graph_plantuml,
     upload_show_report,
 )
 from tach.test import run_affected_tests
 
 if TYPE_CHECKING:
-    from tach.extension import UnusedDependencies
+    from tach.extension import (
+        CheckProgress,
+        ConfigDiff,
+        DependencyPath,
+        Diagnostic,
+        HistoryFinding,
+        HistoryRun,
+        SyncPlan,
+        UnusedDependencies,
+    )
 
 
 import signal
@@ -70,13 +111,14 @@ def print_unused_dependencies(
 
 
 def print_no_config_found(
-    output_format: str = "text", *, config_file_name: str = CONFIG_FILE_NAME
+    output_format: str = "text", *, config_path: Path | None = None
 ) -> None:
     if output_format == "json":
         json.dump({"error": "No config file found"}, sys.stdout)
     else:
+        missing = config_path if config_path is not None else f"{CONFIG_FILE_NAME}.toml"
         console_err.print(
-            f"{config_file_name}.toml not found. Do you need to run [cyan]'tach mod'[/]?",
+            f"{missing} not found. Do you need to run [cyan]'tach mod'[/]?",
             style="red",
         )
 
@@ -95,32 +137,43 @@ def print_no_dependencies_found() -> None:
     )
 
 
-def print_show_web_suggestion(is_mermaid: bool = False) -> None:
-    if is_mermaid:
-        console.print(
-            "NOTE: You are generating a Mermaid graph locally representing your module graph. For a remotely hosted visualization, use the '--web' argument.\nTo visualize your graph, you will need to use Mermaid.js: https://mermaid.js.org/config/usage.html\n",
-            style="cyan",
-        )
-    else:
-        console.print(
-            "NOTE: You are generating a DOT file locally representing your module graph. For a remotely hosted visualization, use the '--web' argument.\nTo visualize your graph, you will need a program like GraphViz: https://www.graphviz.org/download/\n",
-            style="cyan",
-        )
+# The name, viewer and default file extension for each local graph format
+GRAPH_FORMATS: dict[str, tuple[str, str, str]] = {
+    "dot": (
+        "DOT file",
+        "a program like GraphViz: https://www.graphviz.org/download/",
+        "dot",
+    ),
+    "mermaid": (
+        "Mermaid graph",
+        "Mermaid.js: https://mermaid.js.org/config/usage.html",
+        "mmd",
+    ),
+    "d2": ("D2 diagram", "the D2 CLI: https://d2lang.com/tour/install", "d2"),
+    "plantuml": (
+        "PlantUML component diagram",
+        "PlantUML: https://plantuml.com/starting",
+        "puml",
+    ),
+}
+
+
+def print_show_web_suggestion(graph_format: str = "dot") -> None:
+    name, viewer, _ = GRAPH_FORMATS[graph_format]
+    console.print(
+        f"NOTE: You are generating a {name} locally representing your module graph. For a remotely hosted visualization, use the '--web' argument.\nTo visualize your graph, you will need to use {viewer}\n",
+        style="cyan",
+    )
 
 
 def print_generated_module_graph_file(
-    output_filepath: Path, is_mermaid: bool = False
+    output_filepath: Path, graph_format: str = "dot"
 ) -> None:
-    if is_mermaid:
-        console.print(
-            f"Generated a Mermaid file containing your module graph at '{output_filepath}'",
-            style="green",
-        )
-    else:
-        console.print(
-            f"Generated a DOT file containing your module graph at '{output_filepath}'",
-            style="green",
-        )
+    name, _, _ = GRAPH_FORMATS[graph_format]
+    console.print(
+        f"Generated a {name} containing your module graph at '{output_filepath}'",
+        style="green",
+    )
 
 
 def print_circular_dependency_error(
@@ -144,6 +197,25 @@ def print_circular_dependency_error(
         )
 
 
+def print_circular_domain_dependency_error(
+    cycles: list[list[str]], output_format: str = "text"
+) -> None:
+    if output_format == "json":
+        json.dump({"error": "Circular domain dependency", "cycles": cycles}, sys.stdout)
+    else:
+        console_err.print(
+            "\n".join(
+                [
+                    f"{icons.FAIL} [red]Circular dependency detected between domains: [/]{' -> '.join(cycle)}"
+                    for cycle in cycles
+                ]
+            )
+            + f"\n\n[yellow]Resolve circular dependencies between domains.\n"
+            f"Remove or unset 'forbid_circular_domain_dependencies' from "
+            f"'{CONFIG_FILE_NAME}.toml' to allow circular dependencies between domains.[/]",
+        )
+
+
 def print_visibility_errors(
     visibility_errors: list[tuple[str, str, list[str]]], output_format: str = "text"
 ) -> None:
@@ -162,7 +234,44 @@ def print_visibility_errors(
             )
 
 
+def add_project_arguments(parser: argparse.ArgumentParser) -> None:
+    parser.add_argument(
+        "-c",
+        "--config",
+        type=Path,
+        default=None,
+        metavar="path",
+        help=f"Path to the project config file [default: ${fs.CONFIG_PATH_ENV_VAR}, or found by searching upwards from the current directory]",
+    )
+    parser.add_argument(
+        "--root",
+        type=Path,
+        default=None,
+        metavar="path",
+        help=f"Path to the project root [default: ${fs.PROJECT_ROOT_ENV_VAR}, or the directory containing the config file]",
+    )
+    parser.add_argument(
+        "--env",
+        default=None,
+        metavar="name",
+        help=f"Apply the rule settings for this environment from '[rules.<name>]' in the project config [default: ${RULES_ENV_VAR}]",
+    )
+    parser.add_argument(
+        "-v",
+        "--verbose",
+        action="count",
+        default=0,
+        help=f"Log what {TOOL_NAME} is doing to stderr; repeat for more detail (e.g. -vv) [filters can also be set with ${LOG_ENV_VAR}]",
+    )
+    parser.add_argument(
+        "--timings",
+        action="store_true",
+        help="Print a summary of where time was spent when the command finishes",
+    )
+
+
 def add_base_arguments(parser: argparse.ArgumentParser) -> None:
+    add_project_arguments(parser)
     parser.add_argument(
         "-e",
         "--exclude",
@@ -173,6 +282,46 @@ def add_base_arguments(parser: argparse.ArgumentParser) -> None:
     )
 
 
+def add_backup_argument(parser: argparse.ArgumentParser) -> None:
+    parser.add_argument(
+        "--no-backup",
+        action="store_true",
+        help="Do not back up the config files to '.tach/backups' before changing them.",
+    )
+
+
+def positive_int(value: str) -> int:
+    try:
+        number = int(value)
+    except ValueError:
+        number = 0
+    if number <= 0:
+        raise argparse.ArgumentTypeError(
+            f"expected a positive integer, got '{value}'"
+        )
+    return number
+
+
+def add_schema_version_argument(parser: argparse.ArgumentParser) -> None:
+    parser.add_argument(
+        "--schema-version",
+        type=int,
+        choices=range(
+            extension.MIN_OUTPUT_SCHEMA_VERSION, extension.OUTPUT_SCHEMA_VERSION + 1
+        ),
+        default=extension.OUTPUT_SCHEMA_VERSION,
+        metavar="N",
+        help=f"The layout of JSON output, to keep older consumers working (default: {extension.OUTPUT_SCHEMA_VERSION})",
+    )
+
+
+def versioned_json(payload: dict[str, Any], schema_version: int) -> dict[str, Any]:
+    # Version 1 predates the embedded 'schema_version' key
+    if schema_version < 2:
+        return payload
+    return {"schema_version": schema_version, **payload}
+
+
 def build_parser() -> argparse.ArgumentParser:
     parser = argparse.ArgumentParser(
         prog=TOOL_NAME,
@@ -199,6 +348,7 @@ def build_parser() -> argparse.ArgumentParser:
         default=None,
         help="The number of child directories to expand from the root",
     )
+    add_backup_argument(mod_parser)
     add_base_arguments(mod_parser)
 
     ## tach check
@@ -224,12 +374,86 @@ def build_parser() -> argparse.ArgumentParser:
         help="Check interface implementations. When present, all checks must be explicitly enabled.",
     )
     check_parser.add_argument(
+        "--output",
+        choices=["text", "json", "pr-comment"],
+        default="text",
+        help="Output format. 'pr-comment' is a markdown summary of the changes since a recorded run, "
+        "for posting on a pull request (default: text)",
+    )
+    check_parser.add_argument(
+        "--base",
+        metavar="RUN",
+        help="With '--output pr-comment', the recorded run to compare against: 'latest', '~N' or a run ID "
+        "(default: latest)",
+    )
+    check_parser.add_argument(
+        "--update-budgets",
+        action="store_true",
+        help="Lower each violation budget ('max_violations') to its current number of violations "
+        "after checking, like 'tach ratchet'.",
+    )
+    check_parser.add_argument(
+        "--group-by",
+        choices=["kind", "owner"],
+        default="kind",
+        help="Group text output by kind of error, or by the owners of each file in CODEOWNERS (default: kind)",
+    )
+    check_parser.add_argument(
+        "--shard",
+        metavar="INDEX/COUNT",
+        help="Only check the files in one of COUNT deterministic shards of the project (e.g. '3/8'). "
+        "Combine the JSON output of each shard with 'tach check-merge'.",
+    )
+    check_parser.add_argument(
+        "--max-memory",
+        type=positive_int,
+        metavar="MB",
+        help="Check fewer files at once, one source root at a time, to stay within roughly MB megabytes of memory. "
+        "Parsed imports are also kept in '.tach' to avoid parsing unchanged files again.",
+    )
+    check_parser.add_argument(
+        "-q",
+        "--quiet",
+        action="store_true",
+        help="Do not show a progress bar while checking.",
+    )
+    check_parser.add_argument(
+        "--profile",
+        type=Path,
+        metavar="PATH",
+        help="Write a timeline of the check to PATH in the Chrome trace format, "
+        "which can be opened in chrome://tracing or https://ui.perfetto.dev",
+    )
+    add_schema_version_argument(check_parser)
+    add_base_arguments(check_parser)
+
+    ## tach check-merge
+    check_merge_parser = subparsers.add_parser(
+        "check-merge",
+        prog=f"{TOOL_NAME} check-merge",
+        help="Combine the JSON output of each shard of 'tach check --shard'",
+        description="Combine the JSON output of each shard of 'tach check --shard' into a single result",
+    )
+    check_merge_parser.add_argument(
+        "files",
+        type=Path,
+        nargs="+",
+        help="The JSON output of each shard",
+    )
+    check_merge_parser.add_argument(
         "--output",
         choices=["text", "json"],
         default="text",
         help="Output format (default: text)",
     )
-    add_base_arguments(check_parser)
+    check_merge_parser.add_argument(
+        "--group-by",
+        choices=["kind", "owner"],
+        default="kind",
+        help="Group text output by kind of error, or by the owners of each file in CODEOWNERS (default: kind)",
+    )
+    add_schema_version_argument(check_merge_parser)
+    add_base_arguments(check_merge_parser)
 
     ## tach check-external
     check_parser_external = subparsers.add_parser(
@@ -252,8 +476,36 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Add any missing dependencies, but do not remove unused dependencies.",
     )
+    sync_parser.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="Show the planned changes without updating the configuration.",
+    )
+    sync_parser.add_argument(
+        "--output",
+        choices=["text", "json"],
+        default="text",
+        help="Output format of the sync plan (default: text)",
+    )
+    add_backup_argument(sync_parser)
+    add_schema_version_argument(sync_parser)
     add_base_arguments(sync_parser)
 
+    ## tach ratchet
+    ratchet_parser = subparsers.add_parser(
+        "ratchet",
+        prog=f"{TOOL_NAME} ratchet",
+        help="Lower violation budgets to the current number of violations.",
+        description="Lower the 'max_violations' budget of each module, and of the project, to its current number of violations, so that fixed violations cannot be reintroduced. Budgets are never raised.",
+    )
+    ratchet_parser.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="Print the budgets which would be lowered, without changing the config.",
+    )
+    add_backup_argument(ratchet_parser)
+    add_base_arguments(ratchet_parser)
+
     ## tach report
     report_parser = subparsers.add_parser(
         "report",
@@ -262,7 +514,11 @@ def build_parser() -> argparse.ArgumentParser:
         description="Create a report of dependencies and usages.",
     )
     report_parser.add_argument(
-        "path", help="The path or directory path used to generate the report."
+        "path",
+        nargs="+",
+        help="The paths or directory paths used to generate the report. When several "
+        "paths are given, their dependency and usage reports are combined, followed by "
+        "a matrix of the imports between them.",
     )
     # Report type flags
     report_parser.add_argument(
@@ -280,6 +536,31 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Generate external dependency report. When present, all reports must be explicitly enabled.",
     )
+    report_parser.add_argument(
+        "--side-effects",
+        action="store_true",
+        help="Generate a report of code which runs when modules are imported (network, filesystem, database and subprocess calls, and heavy imports). Only generated when requested.",
+    )
+    report_parser.add_argument(
+        "--interface-usage",
+        action="store_true",
+        help="Generate a report of how often each member exposed by the module's interfaces is imported by other modules. Only generated when requested.",
+    )
+    report_parser.add_argument(
+        "--unused-interface-members",
+        action="store_true",
+        help="Generate a report of members exposed by interfaces (or listed in '__all__') which no other module imports. Only generated when requested.",
+    )
+    report_parser.add_argument(
+        "--unused-modules",
+        action="store_true",
+        help="Generate a report of modules which are not imported from anywhere else in the project, excluding configured entrypoints. Only generated when requested.",
+    )
+    report_parser.add_argument(
+        "--matrix",
+        action="store_true",
+        help="Generate a matrix of the imports between the modules within the path, highlighting imports which violate the declared dependencies. Cannot be combined with other reports.",
+    )
     # Report options
     report_parser.add_argument(
         "-d",
@@ -302,8 +583,97 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Group lines by module and print each without any formatting.",
     )
+    report_parser.add_argument(
+        "--format",
+        choices=["text", "json", "csv", "html"],
+        default="text",
+        help="Output format. 'json' and 'csv' print a record for each import, and only support the dependency and usage reports. With '--matrix', every format prints the matrix, and 'html' is only supported with '--matrix' (default: text)",
+    )
+    add_schema_version_argument(report_parser)
     add_base_arguments(report_parser)
 
+    ## tach query
+    query_parser = subparsers.add_parser(
+        "query",
+        prog=f"{TOOL_NAME} query",
+        help="Query the transitive dependencies between modules.",
+        description="Query the transitive dependencies between modules, based on the imports in your project.",
+    )
+    query_subparsers = query_parser.add_subparsers(
+        title="query commands", dest="query_command", required=True
+    )
+    query_dependencies_parser = query_subparsers.add_parser(
+        "dependencies",
+        prog=f"{TOOL_NAME} query dependencies",
+        help="List the modules which a module transitively depends on",
+        description="List the modules which a module transitively depends on",
+    )
+    query_dependencies_parser.add_argument("module", help="The module path.")
+    query_dependents_parser = query_subparsers.add_parser(
+        "dependents",
+        prog=f"{TOOL_NAME} query dependents",
+        help="List the modules which transitively depend on a module",
+        description="List the modules which transitively depend on a module",
+    )
+    query_dependents_parser.add_argument("module", help="The module path.")
+    query_paths_parser = query_subparsers.add_parser(
+        "paths",
+        prog=f"{TOOL_NAME} query paths",
+        help="Show the paths through which one module depends on another",
+        description="Show the paths of imports through which one module depends on another, shortest first",
+    )
+    query_paths_parser.add_argument("source", help="The depending module path.")
+    query_paths_parser.add_argument("target", help="The module path depended on.")
+    query_paths_parser.add_argument(
+        "--max-paths",
+        type=int,
+        default=10,
+        help="The maximum number of paths to show (default: 10).",
+    )
+    for query_subparser in (
+        query_dependencies_parser,
+        query_dependents_parser,
+        query_paths_parser,
+    ):
+        query_subparser.add_argument(
+            "--output",
+            choices=["text", "json"],
+            default="text",
+            help="Output format (default: text)",
+        )
+        add_schema_version_argument(query_subparser)
+        add_base_arguments(query_subparser)
+
+    ## tach why
+    why_parser = subparsers.add_parser(
+        "why",
+        prog=f"{TOOL_NAME} why",
+        help="Explain why one module depends on another",
+        description="Show the shortest chain of modules through which one module depends on another, "
+        "along with the import statements which form each link.",
+    )
+    why_parser.add_argument(
+        "source",
+        help="The depending module path, or a glob of module paths (e.g. 'api.*').",
+    )
+    why_parser.add_argument(
+        "target",
+        help="The module path depended on, or a glob of module paths.",
+    )
+    why_parser.add_argument(
+        "--all-shortest",
+        action="store_true",
+        help="List every shortest path between each pair of modules, rather than only the first.",
+    )
+    why_parser.add_argument(
+        "--output",
+        choices=["text", "json"],
+        default="text",
+        help="Output format (default: text)",
+    )
+    add_schema_version_argument(why_parser)
+    add_base_arguments(why_parser)
+
     ## tach show
     show_parser = subparsers.add_parser(
         "show",
@@ -322,10 +692,44 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Open your dependency graph in a remote web viewer.",
     )
+    show_parser.add_argument(
+        "--format",
+        choices=list(GRAPH_FORMATS),
+        default=None,
+        help="The format of a locally generated module graph file (default: dot).",
+    )
     show_parser.add_argument(
         "--mermaid",
         action="store_true",
-        help="Generate a mermaid.js graph instead of a DOT file.",
+        help="Generate a mermaid.js graph instead of a DOT file. Equivalent to '--format mermaid'.",
+    )
+    show_parser.add_argument(
+        "--depth",
+        type=int,
+        default=None,
+        help="Collapse modules nested deeper than this many segments into their parent.",
+    )
+    show_parser.add_argument(
+        "--focus",
+        type=str,
+        default=None,
+        help="Only show modules near this module in the graph.",
+    )
+    show_parser.add_argument(
+        "--focus-depth",
+        type=int,
+        default=1,
+        help="How many dependencies away from '--focus' to include (default: 1).",
+    )
+    show_parser.add_argument(
+        "--only-violations",
+        action="store_true",
+        help="Only show dependencies which have violations.",
+    )
+    show_parser.add_argument(
+        "--exclude-utilities",
+        action="store_true",
+        help="Hide utility modules from the graph.",
     )
     show_parser.add_argument(
         "-o",
@@ -335,6 +739,7 @@ def build_parser() -> argparse.ArgumentParser:
         default=None,
         help="Specify an output path for a locally generated module graph file.",
     )
+    add_project_arguments(show_parser)
 
     ## tach install
     install_parser = subparsers.add_parser(
@@ -348,6 +753,7 @@ def build_parser() -> argparse.ArgumentParser:
         choices=InstallTarget.choices(),
         help="What kind of installation to perform (e.g. pre-commit)",
     )
+    add_project_arguments(install_parser)
 
     ## tach test
     test_parser = subparsers.add_parser(
@@ -375,6 +781,7 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Do not check cache for results, and do not push results to cache.",
     )
+    add_project_arguments(test_parser)
     test_parser.add_argument(
         "pytest_args",
         nargs=argparse.REMAINDER,
@@ -394,6 +801,7 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Ignore warnings and force the report to be generated.",
     )
+    add_project_arguments(upload_parser)
 
     ## tach export
     export_parser = subparsers.add_parser(
@@ -410,12 +818,59 @@ def build_parser() -> argparse.ArgumentParser:
         default=None,
         help="Specify an output path for the modularity report [DEFAULT: 'modularity_report.json']",
     )
+    export_parser.add_argument(
+        "--bundle",
+        type=Path,
+        metavar="DIRECTORY",
+        default=None,
+        help="Instead of a modularity report, write a versioned bundle of JSON artifacts "
+        "(modules, domains, edges, diagnostics, metrics and metadata) to the directory, for dashboards.",
+    )
     export_parser.add_argument(
         "-f",
         "--force",
         action="store_true",
         help="Ignore warnings and force the report to be generated.",
     )
+    add_project_arguments(export_parser)
+
+    ## tach warm
+    warm_parser = subparsers.add_parser(
+        "warm",
+        prog=f"{TOOL_NAME} warm",
+        help="Populate the per-file caches without running checks",
+        description="Parse the whole project to populate the per-file caches, so that later runs of 'tach check' hit a warm cache. Results are shared when a remote cache backend is configured. Intended for scheduled CI jobs.",
+    )
+    warm_parser.add_argument(
+        "--checks",
+        action="store_true",
+        help="Also cache the results of 'tach check' in the remote cache. Failures are not reported.",
+    )
+    add_base_arguments(warm_parser)
+
+    ## tach cache
+    cache_parser = subparsers.add_parser(
+        "cache",
+        prog=f"{TOOL_NAME} cache",
+        help="Manage the per-file caches",
+        description="Manage the per-file caches",
+    )
+    cache_subparsers = cache_parser.add_subparsers(
+        title="cache commands", dest="cache_command", required=True
+    )
+    cache_invalidate_parser = cache_subparsers.add_parser(
+        "invalidate",
+        prog=f"{TOOL_NAME} cache invalidate",
+        help="Remove the cache entries for the given paths or modules",
+        description="Remove the per-file cache entries (parsed imports and check results) for the given paths or modules, so that they are recomputed on the next run. Intended for build systems which already know what changed.",
+    )
+    cache_invalidate_parser.add_argument(
+        "targets",
+        nargs="+",
+        metavar="path_or_module",
+        help="Files or directories (relative to the project root), or module paths",
+    )
+    add_base_arguments(cache_invalidate_parser)
 
     ## tach server
     server_parser = subparsers.add_parser(
@@ -424,14 +879,194 @@ def build_parser() -> argparse.ArgumentParser:
         help="Start the Language Server Protocol (LSP) server",
         description="Start the Language Server Protocol (LSP) server",
     )
-    server_parser.add_argument(
-        "-c",
-        "--config",
-        type=Path,
+    add_project_arguments(server_parser)
+
+    ## tach serve
+    serve_parser = subparsers.add_parser(
+        "serve",
+        prog=f"{TOOL_NAME} serve",
+        help="Explore the module graph in a local web UI",
+        description="Build the module graph and serve a local web UI for exploring dependencies, violations and module metadata.",
+    )
+    serve_parser.add_argument(
+        "--host",
+        type=str,
+        default="127.0.0.1",
+        help="Host to bind the server to (default: 127.0.0.1)",
+    )
+    serve_parser.add_argument(
+        "--port",
+        type=int,
+        default=8000,
+        help="Port to serve on (default: 8000)",
+    )
+    serve_parser.add_argument(
+        "--open",
+        action="store_true",
+        help="Open the web UI in a browser once the server has started.",
+    )
+    add_project_arguments(serve_parser)
+    ## tach config
+    config_parser = subparsers.add_parser(
+        "config",
+        prog=f"{TOOL_NAME} config",
+        help="Inspect the project configuration",
+        description="Inspect the project configuration",
+    )
+    config_subparsers = config_parser.add_subparsers(
+        title="config commands", dest="config_command", required=True
+    )
+    config_validate_parser = config_subparsers.add_parser(
+        "validate",
+        prog=f"{TOOL_NAME} config validate",
+        help="Validate the project configuration",
+        description="Check the project configuration for unknown keys, undefined or duplicate modules, self-dependencies and modules which cannot be found",
+    )
+    config_validate_parser.add_argument(
+        "--output",
+        choices=["text", "json"],
+        default="text",
+        help="Output format (default: text)",
+    )
+    add_project_arguments(config_validate_parser)
+    config_migrate_parser = config_subparsers.add_parser(
+        "migrate",
+        prog=f"{TOOL_NAME} config migrate",
+        help="Upgrade the project configuration to the current schema version",
+        description="Upgrade the project configuration to the current schema version",
+    )
+    config_migrate_parser.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="Show the changes as a diff without writing them.",
+    )
+    add_backup_argument(config_migrate_parser)
+    add_project_arguments(config_migrate_parser)
+    ## tach stats
+    stats_parser = subparsers.add_parser(
+        "stats",
+        prog=f"{TOOL_NAME} stats",
+        help="Show locally recorded usage stats for tach commands",
+        description="Show locally recorded usage stats (run durations, file counts, cache hit rates) for tach commands",
+    )
+    add_project_arguments(stats_parser)
+    ## tach explain
+    explain_parser = subparsers.add_parser(
+        "explain",
+        prog=f"{TOOL_NAME} explain",
+        help="Explain the rule with the given code (e.g. TACH001)",
+        description="Explain what a rule checks, why, and how to fix its violations. Without a code, list every rule.",
+        formatter_class=argparse.RawDescriptionHelpFormatter,
+        epilog="rules:\n"
+        + "\n".join(
+            f"  {rule_id}  {summary}" for rule_id, summary in extension.list_rules()
+        ),
+    )
+    explain_parser.add_argument(
+        "rule",
+        nargs="?",
+        default=None,
+        help="The code of the rule, as shown before each error (e.g. TACH001)",
+    )
+    ## tach history
+    history_parser = subparsers.add_parser(
+        "history",
+        prog=f"{TOOL_NAME} history",
+        help="Inspect locally recorded 'tach check' runs",
+        description="Inspect locally recorded 'tach check' runs, and compare their findings",
+    )
+    history_subparsers = history_parser.add_subparsers(
+        title="history commands", dest="history_command", required=True
+    )
+    history_list_parser = history_subparsers.add_parser(
+        "list",
+        prog=f"{TOOL_NAME} history list",
+        help="List the recorded runs, most recent first",
+        description="List the recorded runs, most recent first",
+    )
+    history_list_parser.add_argument(
+        "-n",
+        "--limit",
+        type=positive_int,
+        default=10,
+        help="The number of runs to show (default: 10)",
+    )
+    history_show_parser = history_subparsers.add_parser(
+        "show",
+        prog=f"{TOOL_NAME} history show",
+        help="Show the findings of a recorded run",
+        description="Show the findings of a recorded run",
+    )
+    history_show_parser.add_argument(
+        "run",
+        nargs="?",
+        default="latest",
+        help="A run ID (or a prefix of one), 'latest', or '~N' for the Nth run before the latest (default: latest)",
+    )
+    history_diff_parser = history_subparsers.add_parser(
+        "diff",
+        prog=f"{TOOL_NAME} history diff",
+        help="Show the findings which are new or fixed between two recorded runs",
+        description="Show the findings which are new or fixed between two recorded runs. By default, the latest run is compared against the run before it.",
+    )
+    history_diff_parser.add_argument(
+        "base",
+        nargs="?",
+        default=None,
+        help="The run to compare against (default: the run before 'head')",
+    )
+    history_diff_parser.add_argument(
+        "head",
+        nargs="?",
+        default=None,
+        help="The run to compare (default: latest)",
+    )
+    history_diff_parser.add_argument(
+        "--since",
+        default=None,
+        metavar="DURATION",
+        help="Compare against the latest run recorded at least DURATION ago, such as '24h' or '7d'",
+    )
+    for parser in (history_list_parser, history_show_parser, history_diff_parser):
+        parser.add_argument(
+            "--output",
+            choices=["text", "json"],
+            default="text",
+            help="Output format (default: text)",
+        )
+        add_project_arguments(parser)
+    ## tach diff-config
+    diff_config_parser = subparsers.add_parser(
+        "diff-config",
+        prog=f"{TOOL_NAME} diff-config",
+        help="Preview the effect of a config change on modules, dependencies, rules and violations",
+        description="Compare two project configs, then check the project under each of them to show which violations would appear or disappear. Compares against the current config unless 'new' is given.",
+    )
+    diff_config_parser.add_argument(
+        "old",
+        nargs="?",
+        default=None,
+        help="The path of the config to compare against",
+    )
+    diff_config_parser.add_argument(
+        "new",
         nargs="?",
         default=None,
-        help="Path to the config file",
+        help=f"The path of the config to compare (default: the project's {CONFIG_FILE_NAME}.toml)",
+    )
+    diff_config_parser.add_argument(
+        "--against",
+        default=None,
+        metavar="REVISION",
+        help="Compare against the project config at a git revision instead, such as 'main' or 'git:main'",
+    )
+    diff_config_parser.add_argument(
+        "--output",
+        choices=["text", "json"],
+        default="text",
+        help="Output format (default: text)",
     )
+    add_project_arguments(diff_config_parser)
     ## tach init
     init_parser = subparsers.add_parser(
         "init",
@@ -444,36 +1079,176 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Force re-initialization if project is already configured.",
     )
-
-    return parser
-
-
-def parse_arguments(
-    args: list[str],
-) -> tuple[argparse.Namespace, argparse.ArgumentParser]:
-    parser = build_parser()
-    parsed_args = parser.parse_args(args)
-    return parsed_args, parser
-
-
-@dataclass
-class CachedOutput:
-    key: str
-    output: list[tuple[int, str]] = field(default_factory=list)
-    exit_code: int | None = None
-
-    @property
-    def exists(self) -> bool:
-        return self.exit_code is not None
-
-    def replay(self):
-        for fd, output in self.output:
+    init_parser.add_argument(
+        "--auto",
+        action="store_true",
+        help="Skip the guided setup, and make a module of each top-level package in the detected source roots.",
+    )
+    init_parser.add_argument(
+        "--no-sync",
+        action="store_true",
+        help="With --auto, do not fill in the dependencies of each module from its imports.",
+    )
+    add_project_arguments(init_parser)
+    ## tach import-config
+    import_config_parser = subparsers.add_parser(
+        "import-config",
+        prog=f"{TOOL_NAME} import-config",
+        help="Create the project configuration from the configuration of another tool",
+        description="Create the project configuration from the contracts of another tool (e.g. import-linter)",
+    )
+    import_config_parser.add_argument(
+        "--from",
+        dest="source",
+        choices=["importlinter"],
+        required=True,
+        help="The tool which the configuration is for",
+    )
+    import_config_parser.add_argument(
+        "path",
+        type=Path,
+        help="The configuration to import (e.g. setup.cfg, .importlinter or pyproject.toml)",
+    )
+    import_config_parser.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="Print the project configuration instead of writing it.",
+    )
+    import_config_parser.add_argument(
+        "--force",
+        action="store_true",
+        help="Overwrite the project configuration if it already exists.",
+    )
+    add_project_arguments(import_config_parser)
+    ## tach compile
+    compile_parser = subparsers.add_parser(
+        "compile",
+        prog=f"{TOOL_NAME} compile",
+        help="Generate the modules and layers of the project configuration from an architecture file",
+        description="Generate the modules, layers and forbid rules of the project configuration from a compact architecture file (e.g. 'web -> service -> data')",
+    )
+    compile_parser.add_argument(
+        "path",
+        type=Path,
+        help="The architecture file to compile (e.g. arch.dsl)",
+    )
+    compile_group = compile_parser.add_mutually_exclusive_group()
+    compile_group.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="Print the project configuration instead of writing it.",
+    )
+    compile_group.add_argument(
+        "--check",
+        action="store_true",
+        help="Exit with an error if the project configuration is not up to date with the architecture file.",
+    )
+    add_project_arguments(compile_parser)
+    ## tach export-config
+    export_config_parser = subparsers.add_parser(
+        "export-config",
+        prog=f"{TOOL_NAME} export-config",
+        help="Convert the project configuration into the configuration of another tool",
+        description="Convert the modules and dependencies of the project into the configuration of another tool (e.g. import-linter contracts or deptry settings)",
+    )
+    export_config_parser.add_argument(
+        "--to",
+        dest="target",
+        choices=["importlinter", "deptry"],
+        required=True,
+        help="The tool to write the configuration for",
+    )
+    export_config_parser.add_argument(
+        "-o",
+        "--output",
+        type=Path,
+        default=None,
+        help="Write the configuration to this file instead of printing it (e.g. .importlinter, or a TOML file for 'deptry --config')",
+    )
+    add_project_arguments(export_config_parser)
+    ## tach strictify
+    strictify_parser = subparsers.add_parser(
+        "strictify",
+        prog=f"{TOOL_NAME} strictify",
+        help="Find the imports which block strict mode for a module, and the interface which would allow them",
+        description="Find the imports of a module from elsewhere in the project which do not go through its '__all__' or an interface, and generate the interface which would expose every member in use",
+    )
+    strictify_parser.add_argument(
+        "path",
+        type=Path,
+        help="The path to a module (e.g. src/payments)",
+    )
+    strictify_parser.add_argument(
+        "--apply",
+        action="store_true",
+        help="Add the generated interface to the project configuration.",
+    )
+    add_project_arguments(strictify_parser)
+    ## tach badge
+    badge_parser = subparsers.add_parser(
+        "badge",
+        prog=f"{TOOL_NAME} badge",
+        help="Generate a badge reporting the result of 'tach check'",
+        description="Run 'tach check' and generate a shields.io-style badge reporting the number of violations, or that the architecture is passing",
+    )
+    badge_parser.add_argument(
+        "-o",
+        "--output",
+        type=Path,
+        default=None,
+        help="Write the badge to this file instead of printing it (e.g. badge.svg)",
+    )
+    badge_parser.add_argument(
+        "--format",
+        choices=["svg", "json"],
+        default="svg",
+        help="'svg' for an image, or 'json' for a shields.io endpoint (default: svg)",
+    )
+    badge_parser.add_argument(
+        "--label",
+        type=str,
+        default="architecture",
+        help="The text on the left side of the badge (default: architecture)",
+    )
+    add_project_arguments(badge_parser)
+
+    return parser
+
+
+def parse_arguments(
+    args: list[str],
+) -> tuple[argparse.Namespace, argparse.ArgumentParser]:
+    parser = build_parser()
+    parsed_args = parser.parse_args(args)
+    return parsed_args, parser
+
+
+@dataclass
+class CachedOutput:
+    key: str
+    output: list[tuple[int, str]] = field(default_factory=list)
+    exit_code: int | None = None
+
+    @property
+    def exists(self) -> bool:
+        return self.exit_code is not None
+
+    def replay(self):
+        for fd, output in self.output:
             if fd == 1:
                 print(output, file=sys.stdout)
             elif fd == 2:
                 print(output, file=sys.stderr)
 
 
+@dataclass
+class RunStats:
+    cache_hit: bool | None = None
+
+
+run_stats = RunStats()
+
+
 def check_cache_for_action(
     project_root: Path, project_config: ProjectConfig, action: str
 ) -> CachedOutput:
@@ -489,8 +1264,11 @@ def check_cache_for_action(
         backend=project_config.cache.backend,
     )
     cache_result = extension.check_computation_cache(
-        project_root=project_root, cache_key=cache_key
+        project_root=project_root,
+        cache_key=cache_key,
+        cache_config=project_config.cache,
     )
+    run_stats.cache_hit = cache_result is not None
     if cache_result:
         return CachedOutput(
             key=cache_key,
@@ -500,6 +1278,44 @@ def check_cache_for_action(
     return CachedOutput(key=cache_key)
 
 
+def check_with_progress_bar(
+    project_config: ProjectConfig,
+    project_root: Path,
+    dependencies: bool,
+    interfaces: bool,
+    shard: str | None,
+    max_memory: int | None = None,
+) -> list[Diagnostic]:
+    from rich.progress import BarColumn, MofNCompleteColumn, Progress, TextColumn
+
+    # The check holds the GIL, so the bar is only redrawn when progress is reported
+    with Progress(
+        TextColumn("Checking files"),
+        BarColumn(),
+        MofNCompleteColumn(),
+        console=console_err,
+        auto_refresh=False,
+        transient=True,
+    ) as progress_bar:
+        task = progress_bar.add_task("check", total=None)
+
+        def report_progress(progress: CheckProgress) -> None:
+            progress_bar.update(
+                task, completed=progress.checked_files, total=progress.total_files
+            )
+            progress_bar.refresh()
+
+        return extension.check(
+            project_root=project_root,
+            project_config=project_config,
+            dependencies=dependencies,
+            interfaces=interfaces,
+            shard=shard,
+            progress=report_progress,
+            max_memory=max_memory,
+        )
+
+
 def tach_check(
     project_config: ProjectConfig,
     project_root: Path,
@@ -507,31 +1323,118 @@ def tach_check(
     dependencies: bool = True,
     interfaces: bool = True,
     output_format: str = "text",
+    group_by: str = "kind",
+    shard: str | None = None,
+    quiet: bool = False,
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
+    max_memory: int | None = None,
+    base: str | None = None,
+    update_budgets: bool = False,
 ):
     logger.info(
         "tach check called",
         extra={
             "data": CallInfo(
                 function="tach_check",
-                parameters={"exact": exact, "output_format": output_format},
+                parameters={
+                    "exact": exact,
+                    "output_format": output_format,
+                    "group_by": group_by,
+                    "shard": shard,
+                    "quiet": quiet,
+                    "schema_version": schema_version,
+                    "max_memory": max_memory,
+                    "base": base,
+                    "update_budgets": update_budgets,
+                },
             ),
         },
     )
+    if base is not None and output_format != "pr-comment":
+        print(
+            f"{BCOLORS.FAIL}'--base' can only be used with '--output pr-comment'.{BCOLORS.ENDC}",
+            file=sys.stderr,
+        )
+        sys.exit(1)
+    if shard is not None and output_format == "pr-comment":
+        print(
+            f"{BCOLORS.FAIL}'--output pr-comment' cannot be combined with '--shard', "
+            f"since findings outside of the shard would look fixed.{BCOLORS.ENDC}",
+            file=sys.stderr,
+        )
+        sys.exit(1)
+    if update_budgets and (shard is not None or not (dependencies and interfaces)):
+        print(
+            f"{BCOLORS.FAIL}'--update-budgets' requires a full check, "
+            f"so it cannot be combined with '--shard', '--dependencies' or '--interfaces'.{BCOLORS.ENDC}",
+            file=sys.stderr,
+        )
+        sys.exit(1)
+    if shard is not None and exact:
+        print(
+            f"{BCOLORS.FAIL}'--exact' cannot be combined with '--shard', "
+            f"since unused dependencies can only be detected across the whole project.{BCOLORS.ENDC}",
+            file=sys.stderr,
+        )
+        sys.exit(1)
     try:
-        exact |= project_config.exact
+        # Each shard only sees part of the project, so it cannot find unused dependencies
+        exact = (exact or project_config.exact) and shard is None
 
-        diagnostics = extension.check(
-            project_root=project_root,
-            project_config=project_config,
-            dependencies=dependencies,
-            interfaces=interfaces,
-        )
+        if not quiet and output_format == "text" and console_err.is_terminal:
+            diagnostics = check_with_progress_bar(
+                project_config=project_config,
+                project_root=project_root,
+                dependencies=dependencies,
+                interfaces=interfaces,
+                shard=shard,
+                max_memory=max_memory,
+            )
+        else:
+            diagnostics = extension.check(
+                project_root=project_root,
+                project_config=project_config,
+                dependencies=dependencies,
+                interfaces=interfaces,
+                shard=shard,
+                max_memory=max_memory,
+            )
         has_errors = any(diagnostic.is_error() for diagnostic in diagnostics)
+        if update_budgets:
+            for update in extension.update_violation_budgets(
+                project_config=project_config, diagnostics=diagnostics
+            ):
+                console_err.print(
+                    f"[cyan]{update.path}[/]: max_violations {update.previous} -> {update.current}"
+                )
+        if output_format == "pr-comment":
+            # Compare before recording this check, which would otherwise become the latest run
+            diff = extension.diff_check_against_history(
+                project_root=project_root,
+                project_config=project_config,
+                diagnostics=diagnostics,
+                base=base,
+            )
+            record_check_history(project_root, project_config, diagnostics)
+            print(
+                render_pr_comment(
+                    diff,
+                    head_link_base=github_link_base(diff.head.commit, project_root),
+                    base_link_base=github_link_base(diff.base.commit, project_root),
+                ),
+                end="",
+            )
+            sys.exit(1 if has_errors else 0)
+        # Shards only see part of the project, so their findings would look fixed
+        if shard is None:
+            record_check_history(project_root, project_config, diagnostics)
 
         if output_format == "json":
             try:
                 print(
-                    extension.serialize_diagnostics_json(diagnostics, pretty_print=True)
+                    extension.serialize_diagnostics_json(
+                        diagnostics, pretty_print=True, schema_version=schema_version
+                    )
                 )
             except ValueError as e:
                 json.dump({"error": str(e)}, sys.stdout)
@@ -540,7 +1443,9 @@ def tach_check(
         if diagnostics:
             print(
                 extension.format_diagnostics(
-                    project_root=project_root, diagnostics=diagnostics
+                    project_root=project_root,
+                    diagnostics=diagnostics,
+                    group_by_owner=group_by == "owner",
                 ),
                 file=sys.stderr,
             )
@@ -559,6 +1464,9 @@ def tach_check(
     except TachCircularDependencyError as e:
         print_circular_dependency_error(e.dependencies, output_format)
         sys.exit(1)
+    except TachCircularDomainDependencyError as e:
+        print_circular_domain_dependency_error(e.cycles, output_format)
+        sys.exit(1)
     except TachVisibilityError as e:
         print_visibility_errors(e.visibility_errors, output_format)
         sys.exit(1)
@@ -576,6 +1484,65 @@ def tach_check(
     sys.exit(exit_code)
 
 
+def tach_check_merge(
+    project_config: ProjectConfig,
+    project_root: Path,
+    files: list[Path],
+    output_format: str = "text",
+    group_by: str = "kind",
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
+):
+    logger.info(
+        "tach check-merge called",
+        extra={
+            "data": CallInfo(
+                function="tach_check_merge",
+                parameters={
+                    "shards": len(files),
+                    "output_format": output_format,
+                    "group_by": group_by,
+                },
+            ),
+        },
+    )
+    try:
+        diagnostics = extension.merge_check_shards(
+            project_root=project_root,
+            project_config=project_config,
+            outputs=[file.read_text() for file in files],
+        )
+    except Exception as e:
+        if output_format == "json":
+            json.dump({"error": str(e)}, sys.stdout)
+        else:
+            print(f"{BCOLORS.FAIL}{e}{BCOLORS.ENDC}", file=sys.stderr)
+        sys.exit(1)
+
+    has_errors = any(diagnostic.is_error() for diagnostic in diagnostics)
+    if output_format == "json":
+        print(
+            extension.serialize_diagnostics_json(
+                diagnostics, pretty_print=True, schema_version=schema_version
+            )
+        )
+        sys.exit(1 if has_errors else 0)
+
+    if diagnostics:
+        print(
+            extension.format_diagnostics(
+                project_root=project_root,
+                diagnostics=diagnostics,
+                group_by_owner=group_by == "owner",
+            ),
+            file=sys.stderr,
+        )
+    if not has_errors:
+        console.print(
+            f"{icons.SUCCESS} [green]All modules validated![/]", style="green"
+        )
+    sys.exit(1 if has_errors else 0)
+
+
 def tach_check_external(
     project_config: ProjectConfig,
     project_root: Path,
@@ -620,6 +1587,8 @@ def tach_mod(
     project_root: Path,
     depth: int | None = 1,
     exclude_paths: list[str] | None = None,
+    config_path: Path | None = None,
+    backup: bool = True,
 ):
     logger.info(
         "tach mod called",
@@ -630,14 +1599,16 @@ def tach_mod(
             ),
         },
     )
-    # Local import because prompt_toolkit takes about ~80ms to load
-    from tach.mod import mod_edit_interactive
+    from tach.extension import mod_edit_interactive
 
     try:
-        project_config = parse_project_config(root=project_root) or ProjectConfig()
-        exclude_paths = extend_and_validate(
-            exclude_paths, project_config.exclude, project_config.use_regex_matching
+        project_config = (
+            parse_project_config_file(config_path)
+            if config_path is not None and config_path.exists()
+            else ProjectConfig()
         )
+        project_config.backup_edits = backup
+        exclude_paths = extend_and_validate(exclude_paths, project_config.exclude)
         saved_changes, warnings = mod_edit_interactive(
             project_root=project_root,
             project_config=project_config,
@@ -659,31 +1630,139 @@ def tach_mod(
     sys.exit(0)
 
 
+def sync_plan_to_dict(plan: SyncPlan) -> dict[str, Any]:
+    return {
+        "changes": [
+            {
+                "path": change.path,
+                "dependency": change.dependency,
+                "action": change.action,
+                "evidence": change.evidence,
+                "examples": [
+                    {"file": example.file, "line": example.line}
+                    for example in change.examples
+                ],
+            }
+            for change in plan.changes
+        ],
+        "created_modules": plan.created_modules,
+        "deleted_modules": plan.deleted_modules,
+    }
+
+
+def print_sync_plan(plan: SyncPlan) -> None:
+    for module_path in plan.created_modules:
+        console.print(f"[green]+ module '{module_path}'[/]")
+    for change in plan.changes:
+        edge = f"'{change.path}' -> '{change.dependency}'"
+        if change.action == "add":
+            examples = ", ".join(
+                f"{example.file}:{example.line}" for example in change.examples
+            )
+            console.print(
+                f"[green]+ {edge}[/] ({change.evidence} import(s), e.g. {examples})"
+            )
+        elif change.action == "deprecate":
+            console.print(f"[yellow]~ {edge} (deprecated, since it is annotated)[/]")
+        else:
+            console.print(f"[red]- {edge}[/]")
+    for module_path in plan.deleted_modules:
+        console.print(f"[red]- module '{module_path}'[/]")
+
+
 def tach_sync(
     project_config: ProjectConfig,
     project_root: Path,
     add: bool = False,
+    dry_run: bool = False,
+    output_format: str = "text",
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
 ):
     logger.info(
         "tach sync called",
         extra={
             "data": CallInfo(
                 function="tach_sync",
-                parameters={"add": add},
+                parameters={
+                    "add": add,
+                    "dry_run": dry_run,
+                    "output_format": output_format,
+                },
             ),
         },
     )
     try:
-        extension.sync_project(
+        plan = extension.sync_project(
             project_root=project_root,
             project_config=project_config,
             add=add,
+            dry_run=dry_run,
+        )
+    except Exception as e:
+        if output_format == "json":
+            json.dump({"error": str(e)}, sys.stdout)
+        else:
+            print(str(e))
+        sys.exit(1)
+
+    if output_format == "json":
+        json.dump(versioned_json(sync_plan_to_dict(plan), schema_version), sys.stdout)
+        sys.exit(0)
+
+    print_sync_plan(plan)
+    if dry_run:
+        console.print(
+            f"[yellow]{CONFIG_FILE_NAME}.toml was not updated. Run without '--dry-run' to apply these changes.[/]"
+        )
+    else:
+        console.print(f"{icons.SUCCESS} [green]Synced dependencies.[/]", style="green")
+    sys.exit(0)
+
+
+def tach_ratchet(
+    project_config: ProjectConfig,
+    project_root: Path,
+    dry_run: bool = False,
+):
+    logger.info(
+        "tach ratchet called",
+        extra={
+            "data": CallInfo(
+                function="tach_ratchet",
+                parameters={"dry_run": dry_run},
+            ),
+        },
+    )
+    try:
+        updates = extension.ratchet_budgets(
+            project_root=project_root,
+            project_config=project_config,
+            dry_run=dry_run,
         )
     except Exception as e:
         print(str(e))
         sys.exit(1)
 
-    console.print(f"{icons.SUCCESS} [green]Synced dependencies.[/]", style="green")
+    if not updates:
+        console.print(
+            f"{icons.SUCCESS} [green]All violation budgets are up to date.[/]",
+            style="green",
+        )
+        sys.exit(0)
+
+    for update in updates:
+        console.print(
+            f"[cyan]{update.path}[/]: max_violations {update.previous} -> {update.current}"
+        )
+    if dry_run:
+        console.print(
+            f"[yellow]{len(updates)} budget(s) can be lowered. Run '{TOOL_NAME} ratchet' to update {CONFIG_FILE_NAME}.toml.[/]"
+        )
+    else:
+        console.print(
+            f"{icons.SUCCESS} [green]Lowered {len(updates)} violation budget(s).[/]",
+            style="green",
+        )
     sys.exit(0)
 
 
@@ -730,13 +1809,20 @@ def tach_install(project_root: Path, target: InstallTarget) -> None:
 def tach_report(
     project_config: ProjectConfig,
     project_root: Path,
-    path: str,
+    paths: list[str],
     include_dependency_modules: list[str] | None = None,
     include_usage_modules: list[str] | None = None,
     dependencies: bool = False,
     usages: bool = False,
     external: bool = False,
+    side_effects: bool = False,
+    interface_usage: bool = False,
+    unused_interface_members: bool = False,
+    unused_modules: bool = False,
+    matrix: bool = False,
     raw: bool = False,
+    output_format: str = "text",
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
 ):
     logger.info(
         "tach report called",
@@ -747,13 +1833,82 @@ def tach_report(
                     "dependencies": dependencies,
                     "usages": usages,
                     "external": external,
+                    "side_effects": side_effects,
+                    "interface_usage": interface_usage,
+                    "unused_interface_members": unused_interface_members,
+                    "unused_modules": unused_modules,
+                    "matrix": matrix,
+                    "output_format": output_format,
+                    "paths": len(paths),
                 },
             ),
         },
     )
+    other_reports = (
+        external
+        or side_effects
+        or interface_usage
+        or unused_interface_members
+        or unused_modules
+    )
+    if matrix:
+        tach_report_matrix(
+            project_config=project_config,
+            project_root=project_root,
+            paths=paths,
+            other_reports=raw
+            or dependencies
+            or usages
+            or other_reports
+            or include_dependency_modules is not None
+            or include_usage_modules is not None,
+            output_format=output_format,
+            schema_version=schema_version,
+        )
+        return
+    if output_format == "html":
+        print("Report failed: '--format html' is only supported with '--matrix'.")
+        sys.exit(1)
+    if len(paths) > 1:
+        tach_combined_report(
+            project_config=project_config,
+            project_root=project_root,
+            paths=paths,
+            include_dependency_modules=include_dependency_modules,
+            include_usage_modules=include_usage_modules,
+            dependencies=dependencies,
+            usages=usages,
+            other_reports=other_reports,
+            raw=raw,
+            output_format=output_format,
+        )
+        return
+    path = paths[0]
+    if output_format != "text":
+        tach_report_records(
+            project_config=project_config,
+            project_root=project_root,
+            path=path,
+            include_dependency_modules=include_dependency_modules,
+            include_usage_modules=include_usage_modules,
+            dependencies=dependencies,
+            usages=usages,
+            other_reports=raw or other_reports,
+            output_format=output_format,
+            schema_version=schema_version,
+        )
+        return
     try:
         # Generate reports based on flags
-        generate_all = not (dependencies or usages or external)
+        generate_all = not (
+            dependencies
+            or usages
+            or external
+            or side_effects
+            or interface_usage
+            or unused_interface_members
+            or unused_modules
+        )
         generate_dependencies = generate_all or dependencies
         generate_usages = generate_all or usages
         generate_external = generate_all or external
@@ -783,34 +1938,233 @@ def tach_report(
                 )
             )
 
-        print("\n".join(reports))
-        sys.exit(0)
-    except TachError as e:
-        print(f"Report failed: {e}")
-        sys.exit(1)
+        if side_effects:
+            reports.append(
+                side_effect_report(
+                    project_root,
+                    Path(path),
+                    project_config=project_config,
+                    raw=raw,
+                )
+            )
 
+        if interface_usage:
+            reports.append(
+                interface_usage_report(
+                    project_root,
+                    Path(path),
+                    project_config=project_config,
+                    raw=raw,
+                )
+            )
 
-def tach_show(
-    project_config: ProjectConfig,
-    project_root: Path,
-    included_paths: list[Path] | None = None,
+        if unused_interface_members:
+            reports.append(
+                unused_interface_members_report(
+                    project_root,
+                    Path(path),
+                    project_config=project_config,
+                    raw=raw,
+                )
+            )
+
+        if unused_modules:
+            reports.append(
+                unused_modules_report(
+                    project_root,
+                    Path(path),
+                    project_config=project_config,
+                    raw=raw,
+                )
+            )
+
+        print("\n".join(reports))
+        sys.exit(0)
+    except TachError as e:
+        print(f"Report failed: {e}")
+        sys.exit(1)
+
+
+def tach_combined_report(
+    project_config: ProjectConfig,
+    project_root: Path,
+    paths: list[str],
+    include_dependency_modules: list[str] | None,
+    include_usage_modules: list[str] | None,
+    dependencies: bool,
+    usages: bool,
+    other_reports: bool,
+    raw: bool,
+    output_format: str,
+):
+    if other_reports or output_format != "text":
+        print(
+            "Report failed: Reports of several paths only support the dependency "
+            "and usage reports, in the 'text' format."
+        )
+        sys.exit(1)
+    try:
+        print(
+            combined_report(
+                project_root,
+                [Path(path) for path in paths],
+                project_config=project_config,
+                include_dependency_modules=include_dependency_modules,
+                include_usage_modules=include_usage_modules,
+                skip_dependencies=usages and not dependencies,
+                skip_usages=dependencies and not usages,
+                raw=raw,
+            )
+        )
+        sys.exit(0)
+    except TachError as e:
+        print(f"Report failed: {e}")
+        sys.exit(1)
+
+
+def tach_report_matrix(
+    project_config: ProjectConfig,
+    project_root: Path,
+    paths: list[str],
+    other_reports: bool,
+    output_format: str,
+    schema_version: int,
+):
+    if other_reports or len(paths) > 1:
+        print(
+            "Report failed: '--matrix' takes a single path, and cannot be combined "
+            "with other reports, '--raw', '-d' or '-u'."
+        )
+        sys.exit(1)
+    path = Path(paths[0])
+    try:
+        matrix = dependency_matrix(project_root, path, project_config=project_config)
+    except TachError as e:
+        print(f"Report failed: {e}")
+        sys.exit(1)
+
+    if output_format == "csv":
+        print(render_dependency_matrix_csv(matrix), end="")
+    elif output_format == "html":
+        print(render_dependency_matrix_html(matrix, path), end="")
+    elif output_format == "json":
+        json.dump(
+            versioned_json(
+                {
+                    "modules": matrix.modules,
+                    "counts": matrix.counts,
+                    "violations": matrix.violations,
+                },
+                schema_version,
+            ),
+            sys.stdout,
+        )
+    else:
+        print(render_dependency_matrix(matrix, path))
+    sys.exit(0)
+
+
+def tach_report_records(
+    project_config: ProjectConfig,
+    project_root: Path,
+    path: str,
+    include_dependency_modules: list[str] | None,
+    include_usage_modules: list[str] | None,
+    dependencies: bool,
+    usages: bool,
+    other_reports: bool,
+    output_format: str,
+    schema_version: int,
+):
+    if other_reports:
+        print(
+            f"Report failed: '--format {output_format}' only supports the dependency "
+            "and usage reports, and cannot be combined with '--raw'."
+        )
+        sys.exit(1)
+    try:
+        records = dependency_records(
+            project_root,
+            Path(path),
+            project_config=project_config,
+            include_dependency_modules=include_dependency_modules,
+            include_usage_modules=include_usage_modules,
+            # Both are included unless one is requested
+            skip_dependencies=usages and not dependencies,
+            skip_usages=dependencies and not usages,
+        )
+    except TachError as e:
+        print(f"Report failed: {e}")
+        sys.exit(1)
+
+    if output_format == "csv":
+        print(render_dependency_records_csv(records), end="")
+    else:
+        json.dump(
+            versioned_json(
+                {"records": [dependency_record_to_dict(record) for record in records]},
+                schema_version,
+            ),
+            sys.stdout,
+        )
+    sys.exit(0)
+
+
+def tach_show(
+    project_config: ProjectConfig,
+    project_root: Path,
+    included_paths: list[Path] | None = None,
     is_web: bool = False,
     is_mermaid: bool = False,
     output_filepath: Path | None = None,
+    graph_format: str | None = None,
+    depth: int | None = None,
+    focus: str | None = None,
+    focus_depth: int = 1,
+    only_violations: bool = False,
+    exclude_utilities: bool = False,
 ):
     logger.info(
         "tach show called",
         extra={
             "data": CallInfo(
                 function="tach_show",
-                parameters={"is_web": is_web, "is_mermaid": is_mermaid},
+                parameters={
+                    "is_web": is_web,
+                    "is_mermaid": is_mermaid,
+                    "graph_format": graph_format,
+                    "only_violations": only_violations,
+                    "exclude_utilities": exclude_utilities,
+                },
             ),
         },
     )
 
-    if is_web and is_mermaid:
+    if is_mermaid and graph_format not in (None, "mermaid"):
+        console_err.print(
+            "'--mermaid' cannot be combined with '--format'.", style="red"
+        )
+        sys.exit(1)
+    graph_format = "mermaid" if is_mermaid else graph_format or "dot"
+
+    if is_web and graph_format != "dot":
+        console.print(
+            "[yellow]Passing --web generates a remote graph; ignoring the graph format.[/]",
+            style="yellow",
+        )
+
+    if depth is not None and depth < 1:
+        console_err.print("'--depth' must be at least 1.", style="red")
+        sys.exit(1)
+    if focus_depth < 0:
+        console_err.print("'--focus-depth' must not be negative.", style="red")
+        sys.exit(1)
+    is_filtered = (
+        depth is not None or focus is not None or only_violations or exclude_utilities
+    )
+    if is_web and is_filtered:
         console.print(
-            "[yellow]Passing --web generates a remote graph; ignoring '--mermaid' flag.[/]",
+            "[yellow]Passing --web graphs the whole project; ignoring graph filters.[/]",
             style="yellow",
         )
 
@@ -838,29 +2192,68 @@ def tach_show(
             else:
                 sys.exit(1)
         else:
-            print_show_web_suggestion(is_mermaid=is_mermaid)
-            if is_mermaid:
-                output_filepath = output_filepath or Path(
-                    f"{TOOL_NAME}_module_graph.mmd"
+            print_show_web_suggestion(graph_format=graph_format)
+            import_side_effects = build_import_side_effects(
+                project_root, project_config
+            )
+            try:
+                graph = build_graph(
+                    project_root,
+                    project_config,
+                    included_paths,
+                    focus=focus,
+                    focus_depth=focus_depth,
+                    depth=depth,
+                    only_violations=only_violations,
+                    exclude_utilities=exclude_utilities,
                 )
+            except ValueError as e:
+                console_err.print(str(e), style="red")
+                sys.exit(1)
+            _, _, extension = GRAPH_FORMATS[graph_format]
+            output_filepath = output_filepath or Path(
+                f"{TOOL_NAME}_module_graph.{extension}"
+            )
+            if graph_format == "mermaid":
                 generate_module_graph_mermaid(
                     project_config,
                     included_paths=included_paths,
                     output_filepath=output_filepath,
+                    import_side_effects=import_side_effects,
+                    depth=depth,
+                    graph=graph,
                 )
-                print_generated_module_graph_file(output_filepath, is_mermaid=True)
-                sys.exit(0)
-            else:
-                output_filepath = output_filepath or Path(
-                    f"{TOOL_NAME}_module_graph.dot"
+            elif graph_format == "d2":
+                generate_module_graph_d2(
+                    project_config,
+                    included_paths=included_paths,
+                    output_filepath=output_filepath,
+                    import_side_effects=import_side_effects,
+                    depth=depth,
+                    graph=graph,
+                )
+            elif graph_format == "plantuml":
+                generate_module_graph_plantuml(
+                    project_config,
+                    included_paths=included_paths,
+                    output_filepath=output_filepath,
+                    import_side_effects=import_side_effects,
+                    depth=depth,
+                    graph=graph,
                 )
+            else:
                 generate_module_graph_dot_file(
                     project_config,
                     included_paths=included_paths,
                     output_filepath=output_filepath,
+                    import_side_effects=import_side_effects,
+                    depth=depth,
+                    graph=graph,
                 )
-                print_generated_module_graph_file(output_filepath)
-                sys.exit(0)
+            print_generated_module_graph_file(
+                output_filepath, graph_format=graph_format
+            )
+            sys.exit(0)
     except TachError as e:
         print(f"Failed to show module graph: {e}")
         sys.exit(1)
@@ -949,6 +2342,7 @@ def tach_test(
                     ],
                     results.exit_code,
                 ),
+                cache_config=project_config.cache,
             )
         sys.exit(results.exit_code)
     except TachError as e:
@@ -961,17 +2355,35 @@ def tach_export(
     project_root: Path,
     output_path: Path | None = None,
     force: bool = False,
+    bundle: Path | None = None,
 ):
     logger.info(
         "tach export called",
         extra={
             "data": CallInfo(
                 function="tach_export",
-                parameters={"force": force},
+                parameters={"force": force, "bundle": bundle is not None},
             ),
         },
     )
 
+    if bundle is not None:
+        if output_path is not None:
+            console_err.print(
+                "'--output' cannot be combined with '--bundle'.", style="red"
+            )
+            sys.exit(1)
+        try:
+            summary = extension.export_bundle(project_root, project_config, bundle)
+        except Exception as e:
+            console_err.print(f"Failed to export bundle: {e}", style="red")
+            sys.exit(1)
+        console.print(
+            f"{icons.SUCCESS} [green]Exported {len(summary.artifacts)} artifacts "
+            f"(schema version {summary.schema_version}) to '{summary.directory}'.[/]"
+        )
+        return
+
     try:
         export_report(
             project_root=project_root,
@@ -1013,93 +2425,1184 @@ def tach_upload(
         sys.exit(1)
 
 
-def tach_server(
+def tach_cache_invalidate(
     project_config: ProjectConfig,
     project_root: Path,
+    targets: list[str],
 ) -> None:
     logger.info(
-        "tach server called",
-        extra={
-            "data": CallInfo(function="tach_server"),
-        },
+        "tach cache invalidate called",
+        extra={"data": CallInfo(function="tach_cache_invalidate")},
     )
     try:
-        extension.run_server(project_root, project_config)
-    except TachSetupError as e:
-        print(f"Failed to setup LSP server: {e}")
+        summary = extension.invalidate_caches(project_root, project_config, targets)
+    except TachConfigError as e:
+        console_err.print(str(e), style="red")
+        sys.exit(1)
+    except Exception as e:
+        print(f"{BCOLORS.FAIL}Failed to invalidate caches: {e}{BCOLORS.ENDC}")
         sys.exit(1)
 
+    console.print(
+        f"{icons.SUCCESS} [green]Removed {summary.entries} cache entries for {summary.files} files.[/]"
+    )
+    sys.exit(0)
+
 
-def tach_init(project_root: Path, force: bool = False):
+def tach_warm(
+    project_config: ProjectConfig,
+    project_root: Path,
+    checks: bool = False,
+) -> None:
     logger.info(
-        "tach init called",
-        extra={"data": CallInfo(function="tach_init")},
+        "tach warm called",
+        extra={"data": CallInfo(function="tach_warm")},
     )
     try:
-        init_project(project_root, force=force)
-    except TachError as e:
-        # Error may contain rich markup
-        console_err.print(str(e))
+        summary = extension.warm_caches(project_root, project_config, checks=checks)
+    except TachConfigError as e:
+        console_err.print(str(e), style="red")
+        sys.exit(1)
+    except Exception as e:
+        print(f"{BCOLORS.FAIL}Failed to warm caches: {e}{BCOLORS.ENDC}")
         sys.exit(1)
 
-
-def current_version_is_behind(latest_version: str) -> bool:
-    try:
-        current_version_parts = list(map(int, __version__.split(".")[:3]))
-        latest_version_parts = list(map(int, latest_version.split(".")[:3]))
-        return current_version_parts < latest_version_parts
-    except Exception:
-        return False
+    cached = "imports and check results" if summary.checked else "imports"
+    location = "in the shared cache" if summary.shared else "in .tach"
+    console.print(
+        f"{icons.SUCCESS} [green]Cached {cached} for {summary.files} files {location}.[/]"
+    )
+    if summary.skipped:
+        console.print(
+            f"[yellow]Skipped {summary.skipped} files which could not be read or parsed.[/]"
+        )
 
 
-def try_parse_project_config(
+def tach_query(
+    project_config: ProjectConfig,
     project_root: Path,
-    *,
-    file_name: str = CONFIG_FILE_NAME,
-) -> ProjectConfig | None:
+    query_command: str,
+    module: str | None = None,
+    source: str | None = None,
+    target: str | None = None,
+    max_paths: int = 10,
+    output_format: str = "text",
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
+) -> None:
+    logger.info(
+        "tach query called",
+        extra={
+            "data": CallInfo(
+                function="tach_query",
+                parameters={
+                    "query_command": query_command,
+                    "output_format": output_format,
+                },
+            ),
+        },
+    )
+    if max_paths < 1:
+        console_err.print("'--max-paths' must be at least 1.", style="red")
+        sys.exit(1)
+    modules: list[str] = []
+    paths: list[DependencyPath] = []
     try:
-        return parse_project_config(project_root, file_name=file_name)
+        if query_command == "paths":
+            assert source is not None and target is not None
+            paths = extension.query_paths(
+                project_root, project_config, source, target, max_paths=max_paths
+            )
+        else:
+            assert module is not None
+            modules = extension.query_dependencies(
+                project_root,
+                project_config,
+                module,
+                dependents=query_command == "dependents",
+            )
     except Exception as e:
-        print(f"Failed to parse project config: {e}")
+        if output_format == "json":
+            json.dump({"error": str(e)}, sys.stdout)
+        else:
+            console_err.print(str(e), style="red")
         sys.exit(1)
 
+    if query_command != "paths":
+        if output_format == "json":
+            json.dump(
+                versioned_json(
+                    {"module": module, query_command: modules}, schema_version
+                ),
+                sys.stdout,
+            )
+        elif not modules:
+            console.print(f"[yellow]'{module}' has no {query_command}.[/]")
+        else:
+            for module_path in modules:
+                print(module_path)
+        sys.exit(0)
 
-def main(argv: list[str] = sys.argv[1:]) -> None:
-    args, parser = parse_arguments(argv)
-    project_root = fs.find_project_config_root() or Path.cwd()
-    using_custom_config = args.command == "server" and args.config
-    config_file_name = CONFIG_FILE_NAME if not using_custom_config else args.config.stem
-    if using_custom_config:
-        project_root = args.config.parent.resolve()
-        project_config = try_parse_project_config(
-            project_root, file_name=args.config.stem
+    if output_format == "json":
+        json.dump(
+            versioned_json(
+                {
+                    "source": source,
+                    "target": target,
+                    "paths": [
+                        [
+                            {
+                                "source": step.source,
+                                "target": step.target,
+                                "evidence": step.evidence,
+                                "examples": [
+                                    {"file": example.file, "line": example.line}
+                                    for example in step.examples
+                                ],
+                            }
+                            for step in path.steps
+                        ]
+                        for path in paths
+                    ],
+                },
+                schema_version,
+            ),
+            sys.stdout,
         )
-    else:
-        project_config = try_parse_project_config(project_root)
+        sys.exit(0)
 
-    if project_config is None or not project_config.disable_logging:
-        init_logging(project_root)
+    if not paths:
+        console.print(f"[yellow]'{source}' does not depend on '{target}'.[/]")
+        sys.exit(0)
+    for path in paths:
+        path_modules = [path.steps[0].source, *(step.target for step in path.steps)]
+        console.print(f"[cyan]{' -> '.join(path_modules)}[/]")
+        for step in path.steps:
+            examples = ", ".join(
+                f"{example.file}:{example.line}" for example in step.examples
+            )
+            console.print(
+                f"  {step.source} -> {step.target}: {step.evidence} import(s), e.g. {examples}"
+            )
+    sys.exit(0)
 
-    latest_version = cache.get_latest_version()
-    if latest_version and current_version_is_behind(latest_version):
-        console.print(
-            f"WARNING: there is a new {TOOL_NAME} version available"
-            f" ({__version__} -> {latest_version}). Upgrade to remove this warning.",
-            style="yellow",
+
+def read_source_line(project_root: Path, file: str, line: int) -> str | None:
+    try:
+        lines = (project_root / file).read_text().splitlines()
+    except (OSError, UnicodeDecodeError):
+        return None
+    if 0 < line <= len(lines):
+        return lines[line - 1].strip()
+    return None
+
+
+def tach_why(
+    project_config: ProjectConfig,
+    project_root: Path,
+    source: str,
+    target: str,
+    all_shortest: bool = False,
+    output_format: str = "text",
+    schema_version: int = extension.OUTPUT_SCHEMA_VERSION,
+) -> None:
+    logger.info(
+        "tach why called",
+        extra={
+            "data": CallInfo(
+                function="tach_why",
+                parameters={
+                    "all_shortest": all_shortest,
+                    "output_format": output_format,
+                },
+            ),
+        },
+    )
+    try:
+        explanations = extension.explain_dependencies(
+            project_root, project_config, source, target, all_shortest=all_shortest
         )
+    except Exception as e:
+        if output_format == "json":
+            json.dump({"error": str(e)}, sys.stdout)
+        else:
+            console_err.print(str(e), style="red")
+        sys.exit(1)
 
-    exclude_paths = args.exclude.split(",") if getattr(args, "exclude", None) else None
+    if output_format == "json":
 
-    # Some commands can run without project config
-    if args.command == "mod":
-        tach_mod(
-            project_root=project_root,
-            depth=args.depth,
-            exclude_paths=exclude_paths,
+        def path_json(path: extension.DependencyPath) -> list[dict[str, Any]]:
+            return [
+                {
+                    "source": step.source,
+                    "target": step.target,
+                    "evidence": step.evidence,
+                    "imports": [
+                        {
+                            "file": location.file,
+                            "line": location.line,
+                            "code": read_source_line(
+                                project_root, location.file, location.line
+                            ),
+                        }
+                        for location in step.examples
+                    ],
+                }
+                for step in path.steps
+            ]
+
+        json.dump(
+            versioned_json(
+                {
+                    "source": source,
+                    "target": target,
+                    "pairs": [
+                        {
+                            "source": explanation.source,
+                            "target": explanation.target,
+                            "paths": [path_json(path) for path in explanation.paths],
+                        }
+                        for explanation in explanations
+                    ],
+                },
+                schema_version,
+            ),
+            sys.stdout,
         )
-        return
+        sys.exit(0)
+
+    if not explanations:
+        console.print(f"[yellow]'{source}' does not depend on '{target}'.[/]")
+        sys.exit(0)
+    for index, explanation in enumerate(explanations):
+        if len(explanations) > 1:
+            if index > 0:
+                console.print()
+            console.print(
+                f"[bold magenta]{explanation.source} depends on {explanation.target}[/]"
+            )
+        for path_index, path in enumerate(explanation.paths):
+            if path_index > 0:
+                console.print()
+            path_modules = [path.steps[0].source, *(step.target for step in path.steps)]
+            console.print(f"[cyan]{' -> '.join(path_modules)}[/]")
+            for step in path.steps:
+                console.print(
+                    f"\n[bold]{step.source} -> {step.target}[/] ({step.evidence} import(s))"
+                )
+                for location in step.examples:
+                    code = read_source_line(project_root, location.file, location.line)
+                    console.print(
+                        f"  {location.file}:{location.line}"
+                        + (f": {code}" if code else ""),
+                        highlight=False,
+                        markup=False,
+                    )
+    sys.exit(0)
+
+
+def tach_server(
+    project_config: ProjectConfig,
+    project_root: Path,
+) -> None:
+    logger.info(
+        "tach server called",
+        extra={
+            "data": CallInfo(function="tach_server"),
+        },
+    )
+    try:
+        extension.run_server(project_root, project_config)
+    except TachSetupError as e:
+        print(f"Failed to setup LSP server: {e}")
+        sys.exit(1)
+
+
+def tach_serve(
+    project_config: ProjectConfig,
+    project_root: Path,
+    host: str = "127.0.0.1",
+    port: int = 8000,
+    open_browser: bool = False,
+) -> None:
+    logger.info(
+        "tach serve called",
+        extra={
+            "data": CallInfo(
+                function="tach_serve", parameters={"open_browser": open_browser}
+            ),
+        },
+    )
+    if project_config.has_no_modules():
+        print_no_modules_found()
+        sys.exit(1)
+
+    # Local import because the web server is rarely used
+    from tach.serve import serve
+
+    def on_ready(url: str) -> None:
+        console.print(f"Serving the module graph at [cyan]{url}[/] (press Ctrl+C to stop)")
+        if open_browser:
+            import webbrowser
+
+            webbrowser.open(url)
+
+    try:
+        serve(
+            project_root=project_root,
+            project_config=project_config,
+            host=host,
+            port=port,
+            on_ready=on_ready,
+        )
+    except KeyboardInterrupt:
+        sys.exit(0)
+    except OSError as e:
+        print(f"{BCOLORS.FAIL}Failed to start server: {e}{BCOLORS.ENDC}")
+        sys.exit(1)
+    except Exception as e:
+        print(str(e))
+        sys.exit(1)
+
+
+def tach_explain(rule: str | None) -> None:
+    if rule is None:
+        for rule_id, summary in extension.list_rules():
+            print(f"{rule_id}  {summary}")
+        return
+    try:
+        print(extension.explain_rule(rule), end="")
+    except ValueError as e:
+        print(f"{BCOLORS.FAIL}{e}{BCOLORS.ENDC}")
+        sys.exit(1)
+
+
+def tach_stats(project_root: Path):
+    logger.info(
+        "tach stats called",
+        extra={"data": CallInfo(function="tach_stats")},
+    )
+    try:
+        print(extension.format_usage_stats(project_root))
+    except OSError as e:
+        print(f"{BCOLORS.FAIL}Failed to read usage stats: {e}{BCOLORS.ENDC}")
+        sys.exit(1)
+
+
+def format_history_timestamp(timestamp: int) -> str:
+    return datetime.fromtimestamp(timestamp).strftime("%Y-%m-%d %H:%M:%S")
+
+
+def history_finding_to_dict(finding: HistoryFinding) -> dict[str, Any]:
+    return {
+        "fingerprint": finding.fingerprint,
+        "severity": finding.severity,
+        "file": finding.file_path,
+        "line": finding.line_number,
+        "message": finding.message,
+    }
+
+
+def history_run_to_dict(run: HistoryRun, findings: bool = True) -> dict[str, Any]:
+    result: dict[str, Any] = {
+        "id": run.id,
+        "timestamp": run.timestamp,
+        "commit": run.commit,
+        "errors": run.errors,
+        "warnings": run.warnings,
+        "suppressions": run.suppressions,
+        "module_coverage": run.module_coverage,
+    }
+    if findings:
+        result["findings"] = [
+            history_finding_to_dict(finding) for finding in run.findings
+        ]
+    return result
+
+
+def format_history_finding(finding: HistoryFinding) -> str:
+    location = finding.file_path or "(project)"
+    if finding.line_number is not None:
+        location += f":{finding.line_number}"
+    return f"{finding.severity:<8}{location}: {finding.message}"
+
+
+def format_history_run_header(run: HistoryRun) -> str:
+    commit = f", commit {run.commit[:10]}" if run.commit else ""
+    return f"Run {run.id} ({format_history_timestamp(run.timestamp)}{commit})"
+
+
+def tach_history(
+    project_root: Path,
+    history_command: str,
+    output_format: str = "text",
+    limit: int = 10,
+    run: str = "latest",
+    base: str | None = None,
+    head: str | None = None,
+    since: str | None = None,
+) -> None:
+    logger.info(
+        "tach history called",
+        extra={
+            "data": CallInfo(
+                function="tach_history",
+                parameters={
+                    "history_command": history_command,
+                    "output_format": output_format,
+                },
+            )
+        },
+    )
+    try:
+        if history_command == "list":
+            runs = extension.load_check_history(project_root)[::-1][:limit]
+            if output_format == "json":
+                json.dump(
+                    [history_run_to_dict(run, findings=False) for run in runs],
+                    sys.stdout,
+                )
+            elif not runs:
+                print(
+                    "No runs recorded. Set 'record_history = true' in your "
+                    f"{CONFIG_FILE_NAME}.toml to record each 'tach check'."
+                )
+            else:
+                print(
+                    f"{'ID':<10}{'Date':<21}{'Commit':<12}{'Errors':>8}{'Warnings':>10}"
+                )
+                for run in runs:
+                    print(
+                        f"{run.id:<10}{format_history_timestamp(run.timestamp):<21}"
+                        f"{(run.commit or '-')[:10]:<12}{run.errors:>8}{run.warnings:>10}"
+                    )
+        elif history_command == "show":
+            found = extension.find_check_history_run(project_root, run)
+            if output_format == "json":
+                json.dump(history_run_to_dict(found), sys.stdout)
+            else:
+                print(format_history_run_header(found))
+                print(f"{found.errors} error(s), {found.warnings} warning(s)")
+                for finding in found.findings:
+                    print(f"  {format_history_finding(finding)}")
+        elif history_command == "diff":
+            diff = extension.diff_check_history(
+                project_root, base=base, head=head, since=since
+            )
+            if output_format == "json":
+                json.dump(
+                    {
+                        "base": history_run_to_dict(diff.base, findings=False),
+                        "head": history_run_to_dict(diff.head, findings=False),
+                        "new": [history_finding_to_dict(f) for f in diff.new],
+                        "fixed": [history_finding_to_dict(f) for f in diff.fixed],
+                    },
+                    sys.stdout,
+                )
+                return
+            print(f"Base: {format_history_run_header(diff.base)}")
+            print(f"Head: {format_history_run_header(diff.head)}")
+            if not diff.new and not diff.fixed:
+                print("No findings changed.")
+                return
+            if diff.new:
+                print(f"{BCOLORS.FAIL}New ({len(diff.new)}):{BCOLORS.ENDC}")
+                for finding in diff.new:
+                    print(f"  + {format_history_finding(finding)}")
+            if diff.fixed:
+                print(
+                    f"{BCOLORS.OKGREEN}Fixed ({len(diff.fixed)}):{BCOLORS.ENDC}"
+                )
+                for finding in diff.fixed:
+                    print(f"  - {format_history_finding(finding)}")
+    except (OSError, ValueError) as e:
+        if output_format == "json":
+            json.dump({"error": str(e)}, sys.stdout)
+        else:
+            print(f"{BCOLORS.FAIL}{e}{BCOLORS.ENDC}", file=sys.stderr)
+        sys.exit(1)
+
+
+def config_diff_to_dict(diff: ConfigDiff) -> dict[str, Any]:
+    return {
+        "modules_added": diff.modules_added,
+        "modules_removed": diff.modules_removed,
+        "dependencies_added": [
+            {"module": change.module_path, "dependency": change.dependency_path}
+            for change in diff.dependencies_added
+        ],
+        "dependencies_removed": [
+            {"module": change.module_path, "dependency": change.dependency_path}
+            for change in diff.dependencies_removed
+        ],
+        "rules_changed": [
+            {"rule": change.rule, "old": change.old, "new": change.new}
+            for change in diff.rules_changed
+        ],
+        "new": [history_finding_to_dict(f) for f in diff.new],
+        "fixed": [history_finding_to_dict(f) for f in diff.fixed],
+    }
+
+
+def print_config_diff(diff: ConfigDiff) -> None:
+    if diff.modules_added or diff.modules_removed:
+        print("Modules:")
+        for path in diff.modules_added:
+            print(f"  + {path}")
+        for path in diff.modules_removed:
+            print(f"  - {path}")
+    if diff.dependencies_added or diff.dependencies_removed:
+        print("Dependencies:")
+        for change in diff.dependencies_added:
+            print(f"  + {change.module_path} -> {change.dependency_path}")
+        for change in diff.dependencies_removed:
+            print(f"  - {change.module_path} -> {change.dependency_path}")
+    if diff.rules_changed:
+        print("Rules:")
+        for change in diff.rules_changed:
+            print(f"  {change.rule}: {change.old} -> {change.new}")
+    if not diff.new and not diff.fixed:
+        print("No violations would change.")
+        return
+    if diff.new:
+        print(f"{BCOLORS.FAIL}New ({len(diff.new)}):{BCOLORS.ENDC}")
+        for finding in diff.new:
+            print(f"  + {format_history_finding(finding)}")
+    if diff.fixed:
+        print(f"{BCOLORS.OKGREEN}Fixed ({len(diff.fixed)}):{BCOLORS.ENDC}")
+        for finding in diff.fixed:
+            print(f"  - {format_history_finding(finding)}")
+
+
+def tach_diff_config(
+    project_root: Path,
+    config_path: Path | None,
+    old: str | None = None,
+    new: str | None = None,
+    against: str | None = None,
+    output_format: str = "text",
+    environment: str | None = None,
+) -> None:
+    logger.info(
+        "tach diff-config called",
+        extra={
+            "data": CallInfo(
+                function="tach_diff_config",
+                parameters={
+                    "against": against is not None,
+                    "output_format": output_format,
+                },
+            )
+        },
+    )
+
+    def fail(message: str) -> None:
+        if output_format == "json":
+            json.dump({"error": message}, sys.stdout)
+        else:
+            print(f"{BCOLORS.FAIL}{message}{BCOLORS.ENDC}", file=sys.stderr)
+        sys.exit(1)
+
+    if (old is None) == (against is None):
+        fail("Pass either the path of the old config, or '--against <revision>'.")
+    if config_path is None or not config_path.exists():
+        print_no_config_found(output_format, config_path=config_path)
+        sys.exit(1)
+    try:
+        # Both configs are parsed in place of the project config, so that domain configs
+        # are found in the project. Parsing directly also keeps a config needing migration
+        # from being rewritten.
+        if new is not None:
+            new_config = extension.parse_project_config_as(
+                Path(new).resolve(), config_path
+            )
+        else:
+            new_config, _ = extension.parse_project_config(config_path)
+        if against is not None:
+            old_config = extension.parse_project_config_at_revision(
+                config_path, against
+            )
+        else:
+            assert old is not None
+            old_config = extension.parse_project_config_as(
+                Path(old).resolve(), config_path
+            )
+        if environment:
+            new_config.apply_rules_environment(environment)
+            old_config.apply_rules_environment(environment)
+        diff = extension.diff_project_configs(project_root, old_config, new_config)
+    except (OSError, ValueError, TachError) as e:
+        fail(str(e))
+        return
+
+    if output_format == "json":
+        json.dump(config_diff_to_dict(diff), sys.stdout)
+    else:
+        print_config_diff(diff)
+    # New errors fail the command, so it can gate config changes in CI
+    if any(finding.severity == "error" for finding in diff.new):
+        sys.exit(1)
+
+
+def tach_config_validate(
+    project_root: Path, config_path: Path | None, output_format: str = "text"
+) -> None:
+    logger.info(
+        "tach config validate called",
+        extra={"data": CallInfo(function="tach_config_validate")},
+    )
+    if config_path is None or not config_path.exists():
+        print_no_config_found(output_format, config_path=config_path)
+        sys.exit(1)
+    try:
+        diagnostics = extension.validate_project_config(project_root, config_path)
+    except ValueError as e:
+        print(f"{BCOLORS.FAIL}Failed to validate config: {e}{BCOLORS.ENDC}")
+        sys.exit(1)
+
+    if output_format == "json":
+        json.dump(
+            [
+                {
+                    "kind": diagnostic.kind,
+                    "message": diagnostic.message,
+                    "suggestion": diagnostic.suggestion,
+                    "file": str(diagnostic.file),
+                    "line": diagnostic.line,
+                    "column": diagnostic.column,
+                }
+                for diagnostic in diagnostics
+            ],
+            sys.stdout,
+        )
+    else:
+        for diagnostic in diagnostics:
+            location = str(diagnostic.file.relative_to(project_root))
+            if diagnostic.line is not None:
+                location += f":{diagnostic.line}:{diagnostic.column}"
+            suggestion = (
+                f" (did you mean '{diagnostic.suggestion}'?)"
+                if diagnostic.suggestion
+                else ""
+            )
+            console_err.print(
+                f"{icons.FAIL} [red]{location}[/]: {diagnostic.message}{suggestion}"
+            )
+        if not diagnostics:
+            console.print(f"{icons.SUCCESS} [green]Config is valid![/]")
+
+    if diagnostics:
+        sys.exit(1)
+
+
+def tach_config_migrate(
+    config_path: Path | None, dry_run: bool = False, backup: bool = True
+) -> None:
+    logger.info(
+        "tach config migrate called",
+        extra={"data": CallInfo(function="tach_config_migrate")},
+    )
+    if config_path is None or not config_path.exists():
+        print_no_config_found(config_path=config_path)
+        sys.exit(1)
+    try:
+        result = extension.migrate_project_config(
+            config_path, dry_run=dry_run, backup=backup
+        )
+    except (ValueError, OSError) as e:
+        print(f"{BCOLORS.FAIL}Failed to migrate config: {e}{BCOLORS.ENDC}")
+        sys.exit(1)
+
+    if not result.has_changes():
+        console.print(
+            f"{icons.SUCCESS} [green]Config is already at schema version {result.to_version}.[/]"
+        )
+        return
+
+    if dry_run:
+        diff = difflib.unified_diff(
+            result.original.splitlines(keepends=True),
+            result.migrated.splitlines(keepends=True),
+            fromfile=f"{config_path.name} (schema version {result.from_version})",
+            tofile=f"{config_path.name} (schema version {result.to_version})",
+        )
+        print("".join(diff))
+
+    for description in result.applied:
+        console.print(f"- {description}")
+    if dry_run:
+        console.print(
+            f"[yellow]Dry run: {config_path.name} was not changed. Run '{TOOL_NAME} config migrate' to apply these changes.[/]"
+        )
+    else:
+        console.print(
+            f"{icons.SUCCESS} [green]Migrated {config_path.name} from schema version {result.from_version} to {result.to_version}.[/]"
+        )
+        if result.backup is not None:
+            console.print(f"The previous config was backed up to '{result.backup}'.")
+
+
+def record_run_stats(
+    project_root: Path, project_config: ProjectConfig, command: str, start_time: float
+):
+    if not project_config.record_usage_stats:
+        return
+    try:
+        extension.record_usage_stats(
+            project_root=project_root,
+            project_config=project_config,
+            command=command,
+            duration_ms=int((time.perf_counter() - start_time) * 1000),
+            cache_hit=run_stats.cache_hit,
+        )
+    except Exception:
+        # Usage stats are best-effort and should never affect the command result
+        pass
+
+
+def record_check_history(
+    project_root: Path, project_config: ProjectConfig, diagnostics: list[Diagnostic]
+):
+    if not project_config.record_history:
+        return
+    try:
+        extension.record_check_history(
+            project_root=project_root,
+            project_config=project_config,
+            diagnostics=diagnostics,
+        )
+    except Exception:
+        # Like usage stats, history is best-effort and never affects the check result
+        pass
+
+
+def tach_init(
+    project_root: Path, force: bool = False, auto: bool = False, sync: bool = True
+):
+    logger.info(
+        "tach init called",
+        extra={
+            "data": CallInfo(
+                function="tach_init", parameters={"auto": auto, "sync": sync}
+            )
+        },
+    )
+    if auto:
+        try:
+            summary = extension.init_project(project_root, force=force, sync=sync)
+        except (OSError, ValueError) as e:
+            print(f"{BCOLORS.FAIL}{e}{BCOLORS.ENDC}", file=sys.stderr)
+            sys.exit(1)
+        source_roots = ", ".join(f"'{root}'" for root in summary.source_roots)
+        console.print(
+            f"{icons.SUCCESS} [green]Wrote {summary.config_path} with "
+            f"{len(summary.modules)} module(s) in source root(s) {source_roots}"
+            + (f" and {summary.dependencies} dependencies" if sync else "")
+            + ".[/]"
+        )
+        console.print(f"Run '{TOOL_NAME} check' to check your project.")
+        return
+    try:
+        init_project(project_root, force=force)
+    except TachError as e:
+        # Error may contain rich markup
+        console_err.print(str(e))
+        sys.exit(1)
+
+
+def tach_import_config(
+    project_root: Path,
+    config_path: Path | None,
+    path: Path,
+    dry_run: bool = False,
+    force: bool = False,
+) -> None:
+    logger.info(
+        "tach import-config called",
+        extra={"data": CallInfo(function="tach_import_config")},
+    )
+    config_path = config_path or fs.build_project_config_path(project_root)
+    if config_path.exists() and not force and not dry_run:
+        console_err.print(
+            f"[red]{config_path} already exists. Use --force to overwrite it.[/]"
+        )
+        sys.exit(1)
+    try:
+        imported = import_from_import_linter(project_root, path)
+    except TachError as e:
+        console_err.print(f"[red]Failed to import '{path}': {e}[/]", markup=False)
+        sys.exit(1)
+
+    for warning in imported.warnings:
+        console_err.print(f"WARNING: {warning}", style="yellow", markup=False)
+    if dry_run:
+        print(imported.config_toml, end="")
+        return
+    fs.write_file_atomic(config_path, imported.config_toml)
+    console.print(
+        f"{icons.SUCCESS} [green]Wrote {len(imported.modules)} module(s) and "
+        f"{len(imported.layers)} layer(s) to {config_path}.[/]"
+    )
+    console.print(f"Run '{TOOL_NAME} check' to check your project.")
+
+
+def tach_compile(
+    project_root: Path,
+    config_path: Path | None,
+    path: Path,
+    dry_run: bool = False,
+    check: bool = False,
+) -> None:
+    logger.info(
+        "tach compile called",
+        extra={
+            "data": CallInfo(function="tach_compile", parameters={"check": check})
+        },
+    )
+    config_path = config_path or fs.build_project_config_path(project_root)
+    try:
+        compiled = compile_architecture(project_root, path, config_path)
+    except TachError as e:
+        console_err.print(
+            f"Failed to compile '{path}': {e}", style="red", markup=False
+        )
+        sys.exit(1)
+
+    if dry_run:
+        print(compiled.config_toml, end="")
+        return
+    if check:
+        current = config_path.read_text() if config_path.exists() else None
+        if current != compiled.config_toml:
+            console_err.print(
+                f"[red]{config_path} is out of date with '{path}'. "
+                f"Run '{TOOL_NAME} compile {path}' to update it.[/]"
+            )
+            sys.exit(1)
+        console.print(f"{icons.SUCCESS} [green]{config_path} is up to date.[/]")
+        return
+    fs.write_file_atomic(config_path, compiled.config_toml)
+    console.print(
+        f"{icons.SUCCESS} [green]Wrote {len(compiled.modules)} module(s), "
+        f"{len(compiled.layers)} layer(s) and {len(compiled.forbid)} forbid rule(s) "
+        f"to {config_path}.[/]"
+    )
+
+
+def tach_export_config(
+    project_config: ProjectConfig,
+    project_root: Path,
+    target: str,
+    output_path: Path | None = None,
+) -> None:
+    logger.info(
+        "tach export-config called",
+        extra={
+            "data": CallInfo(
+                function="tach_export_config", parameters={"target": target}
+            )
+        },
+    )
+    if target == "deptry":
+        exported = export_to_deptry(project_root, project_config)
+    else:
+        exported = export_to_import_linter(project_config)
+
+    for warning in exported.warnings:
+        console_err.print(f"WARNING: {warning}", style="yellow", markup=False)
+    if output_path is None:
+        print(exported.contents, end="")
+        return
+    fs.write_file_atomic(output_path, exported.contents)
+    console.print(
+        f"{icons.SUCCESS} [green]Wrote {target} configuration to {output_path}.[/]"
+    )
+
+
+def tach_strictify(
+    project_config: ProjectConfig,
+    project_root: Path,
+    path: Path,
+    apply: bool = False,
+) -> None:
+    logger.info(
+        "tach strictify called",
+        extra={
+            "data": CallInfo(function="tach_strictify", parameters={"apply": apply})
+        },
+    )
+    try:
+        strictify_report = extension.strictify_module(
+            project_root=project_root, project_config=project_config, path=path
+        )
+    except ValueError as e:
+        console_err.print(str(e), style="red", markup=False)
+        sys.exit(1)
+
+    module_path = strictify_report.module_path
+    console.print(
+        f"{strictify_report.covered_usages} import(s) of '{module_path}' already "
+        "go through '__all__' or an interface.",
+        markup=False,
+    )
+    if strictify_report.blocking_imports:
+        console.print(
+            f"{len(strictify_report.blocking_imports)} import(s) would be rejected "
+            "in strict mode:",
+            style="yellow",
+            markup=False,
+        )
+        for blocking_import in strictify_report.blocking_imports:
+            console.print(
+                f"  {blocking_import.file_path}:{blocking_import.line_number}: "
+                f"'{blocking_import.member}' (in '{blocking_import.usage_module}')",
+                markup=False,
+            )
+
+    # Modules with an interface which already exposes every member in use need no changes
+    if strictify_report.has_interface and not strictify_report.expose:
+        console.print(
+            f"{icons.SUCCESS} [green]Every import of '{module_path}' goes through "
+            "its interface.[/]"
+        )
+        return
+    expose = ", ".join(json.dumps(member) for member in strictify_report.expose)
+    interface_toml = f'[[interfaces]]\nexpose = [{expose}]\nfrom = ["{module_path}"]'
+    if not apply:
+        console.print("Add this interface to allow every import in use:")
+        print(interface_toml)
+        console.print(f"Run '{TOOL_NAME} strictify {path} --apply' to add it.")
+        return
+    project_config.add_interface(module_path, strictify_report.expose)
+    project_config.save_edits()
+    console.print(
+        f"{icons.SUCCESS} [green]Added an interface exposing "
+        f"{len(strictify_report.expose)} member(s) of '{module_path}'.[/]"
+    )
+
+
+def count_violations(project_config: ProjectConfig, project_root: Path) -> int:
+    try:
+        diagnostics = extension.check(
+            project_root=project_root,
+            project_config=project_config,
+            dependencies=True,
+            interfaces=True,
+        )
+    except TachCircularDependencyError as e:
+        return len(e.dependencies)
+    except TachCircularDomainDependencyError as e:
+        return len(e.cycles)
+    except TachVisibilityError as e:
+        return len(e.visibility_errors)
+    return sum(1 for diagnostic in diagnostics if diagnostic.is_error())
+
+
+def tach_badge(
+    project_config: ProjectConfig,
+    project_root: Path,
+    output_path: Path | None = None,
+    output_format: str = "svg",
+    label: str = "architecture",
+) -> None:
+    logger.info(
+        "tach badge called",
+        extra={
+            "data": CallInfo(
+                function="tach_badge", parameters={"output_format": output_format}
+            )
+        },
+    )
+    try:
+        violations = count_violations(project_config, project_root)
+    except Exception as e:
+        print(f"{BCOLORS.FAIL}Failed to check the project: {e}{BCOLORS.ENDC}")
+        sys.exit(1)
+
+    badge = Badge.from_violation_count(violations, label=label)
+    contents = badge.to_json() if output_format == "json" else badge.to_svg()
+    if output_path is None:
+        print(contents, end="")
+        return
+    fs.write_file_atomic(output_path, contents)
+    console.print(
+        f"{icons.SUCCESS} [green]Wrote badge ({label}: {badge.message}) to {output_path}.[/]"
+    )
+
+
+def current_version_is_behind(latest_version: str) -> bool:
+    try:
+        current_version_parts = list(map(int, __version__.split(".")[:3]))
+        latest_version_parts = list(map(int, latest_version.split(".")[:3]))
+        return current_version_parts < latest_version_parts
+    except Exception:
+        return False
+
+
+def print_timings(start_time: float) -> None:
+    timings = extension.take_timings()
+    console_err.print(
+        f"\n[bold]Timings[/] (total: {time.perf_counter() - start_time:.3f}s)"
+    )
+    if not timings:
+        return
+    name_width = max(len(timing.name) for timing in timings)
+    for timing in timings:
+        console_err.print(
+            f"  {timing.name:<{name_width}}  {timing.total_seconds:>9.3f}s  {timing.calls:>7} call(s)"
+        )
+    console_err.print(
+        "[dim]Steps which run in parallel (e.g. check_file) add up to more than the total.[/]"
+    )
+
+
+def write_profile(path: Path) -> None:
+    try:
+        events = extension.write_profile(path)
+    except OSError as e:
+        console_err.print(f"[red]Failed to write profile to '{path}': {e}[/]")
+        return
+    console_err.print(
+        f"Wrote {events} event(s) to '{path}'. "
+        "Open it in chrome://tracing or https://ui.perfetto.dev to view the timeline."
+    )
+
+
+def start_otlp_export(project_root: Path, project_config: ProjectConfig) -> None:
+    try:
+        extension.start_otlp_export(
+            project_root=project_root, project_config=project_config
+        )
+    except ValueError as e:
+        # Exporting is best-effort and should never affect the command result
+        console_err.print(f"WARNING: {e}", style="yellow", markup=False)
+
+
+def try_parse_project_config(config_path: Path | None) -> ProjectConfig | None:
+    if config_path is None or not config_path.exists():
+        return None
+    try:
+        return parse_project_config_file(config_path)
+    except Exception as e:
+        print(f"Failed to parse project config: {e}")
+        # Backups are kept next to the project config
+        backup = extension.latest_config_backup(config_path.parent)
+        if backup is not None:
+            print(
+                f"If a previous '{TOOL_NAME}' command was interrupted while writing the config, "
+                f"the config files from before the change were backed up to '{backup}'."
+            )
+        sys.exit(1)
+
+
+def main(argv: list[str] = sys.argv[1:]) -> None:
+    args, parser = parse_arguments(argv)
+    main_start_time = time.perf_counter()
+    timings = getattr(args, "timings", False)
+    profile_path: Path | None = getattr(args, "profile", None)
+    try:
+        extension.init_tracing(
+            verbosity=getattr(args, "verbose", 0),
+            timings=timings,
+            profile=profile_path is not None,
+        )
+    except ValueError as e:
+        print(e)
+        sys.exit(1)
+    location = fs.resolve_project_location(
+        config_path=getattr(args, "config", None), root=getattr(args, "root", None)
+    )
+    project_root, config_path = location.root, location.config_path
+
+    # Rules are described by the extension, so no project is needed
+    if args.command == "explain":
+        tach_explain(args.rule)
+        return
+    # Validating and migrating must not require the config to parse
+    if args.command == "config":
+        if args.config_command == "validate":
+            tach_config_validate(project_root, config_path, output_format=args.output)
+        elif args.config_command == "migrate":
+            tach_config_migrate(
+                config_path, dry_run=args.dry_run, backup=not args.no_backup
+            )
+        return
+    # An existing config is about to be replaced, so it does not need to parse either
+    if args.command == "import-config":
+        tach_import_config(
+            project_root,
+            config_path,
+            args.path,
+            dry_run=args.dry_run,
+            force=args.force,
+        )
+        return
+    # The generated parts of the config are replaced, so it does not need to parse
+    if args.command == "compile":
+        tach_compile(
+            project_root,
+            config_path,
+            args.path,
+            dry_run=args.dry_run,
+            check=args.check,
+        )
+        return
+    project_config = try_parse_project_config(config_path)
+
+    if project_config is None or not project_config.disable_logging:
+        init_logging(project_root)
+    if project_config is not None and project_config.otlp.enabled:
+        start_otlp_export(project_root, project_config)
+
+    latest_version = cache.get_latest_version()
+    if latest_version and current_version_is_behind(latest_version):
+        console.print(
+            f"WARNING: there is a new {TOOL_NAME} version available"
+            f" ({__version__} -> {latest_version}). Upgrade to remove this warning.",
+            style="yellow",
+        )
+
+    exclude_paths = args.exclude.split(",") if getattr(args, "exclude", None) else None
+
+    # Some commands can run without project config
+    if args.command == "mod":
+        tach_mod(
+            project_root=project_root,
+            depth=args.depth,
+            exclude_paths=exclude_paths,
+            config_path=config_path,
+            backup=not args.no_backup,
+        )
+        return
     elif args.command == "init":
-        tach_init(project_root, force=args.force)
+        tach_init(
+            project_root, force=args.force, auto=args.auto, sync=not args.no_sync
+        )
+        return
+    elif args.command == "stats":
+        tach_stats(project_root)
+        return
+    elif args.command == "history":
+        tach_history(
+            project_root,
+            args.history_command,
+            output_format=args.output,
+            limit=getattr(args, "limit", 10),
+            run=getattr(args, "run", "latest"),
+            base=getattr(args, "base", None),
+            head=getattr(args, "head", None),
+            since=getattr(args, "since", None),
+        )
+        return
+    elif args.command == "diff-config":
+        tach_diff_config(
+            project_root,
+            config_path,
+            old=args.old,
+            new=args.new,
+            against=args.against,
+            output_format=args.output,
+            environment=args.env or os.environ.get(RULES_ENV_VAR),
+        )
         return
     elif args.command == "install":
         try:
@@ -1112,17 +3615,10 @@ def main(argv: list[str] = sys.argv[1:]) -> None:
 
     # All other commands require project config
     if project_config is None:
-        print_no_config_found(config_file_name=config_file_name)
+        print_no_config_found(config_path=config_path)
         sys.exit(1)
 
     # Deprecation warnings
-    if project_config.use_regex_matching:
-        console.print(
-            "WARNING: regex matching for exclude paths is deprecated. "
-            + f"Update your exclude paths in {CONFIG_FILE_NAME}.toml to use glob patterns instead, and remove the 'use_regex_matching' setting."
-            + "\n",
-            style="yellow",
-        )
     if (
         project_config.root_module == "ignore"
         and project_config.has_root_module_reference()
@@ -1136,101 +3632,231 @@ def main(argv: list[str] = sys.argv[1:]) -> None:
             style="yellow",
         )
 
+    # Rule settings for the environment take precedence over the rest of the project's rules
+    environment = getattr(args, "env", None) or os.environ.get(RULES_ENV_VAR)
+    if environment and not project_config.apply_rules_environment(environment):
+        if getattr(args, "env", None):
+            console_err.print(
+                f"WARNING: '[rules.{environment}]' is not declared in your project config, so no environment rule settings were applied.",
+                style="yellow",
+            )
+
     # Exclude paths on the CLI extend those from the project config
     try:
         project_config.exclude = extend_and_validate(
-            exclude_paths, project_config.exclude, project_config.use_regex_matching
+            exclude_paths, project_config.exclude
         )
     except TachConfigError as e:
         print(f"{BCOLORS.FAIL}Failed to validate exclude paths: {e}{BCOLORS.ENDC}")
         sys.exit(1)
+    if getattr(args, "no_backup", False):
+        project_config.backup_edits = False
 
-    if args.command == "sync":
-        tach_sync(
-            project_config=project_config,
-            project_root=project_root,
-            add=args.add,
-        )
-    elif args.command == "check":
-        if args.dependencies or args.interfaces:
-            tach_check(
+    start_time = time.perf_counter()
+    try:
+        if args.command == "sync":
+            tach_sync(
+                project_config=project_config,
+                project_root=project_root,
+                add=args.add,
+                dry_run=args.dry_run,
+                output_format=args.output,
+                schema_version=args.schema_version,
+            )
+        elif args.command == "ratchet":
+            tach_ratchet(
+                project_config=project_config,
+                project_root=project_root,
+                dry_run=args.dry_run,
+            )
+        elif args.command == "check":
+            if args.dependencies or args.interfaces:
+                tach_check(
+                    project_config=project_config,
+                    project_root=project_root,
+                    dependencies=args.dependencies,
+                    interfaces=args.interfaces,
+                    exact=args.exact,
+                    output_format=args.output,
+                    group_by=args.group_by,
+                    shard=args.shard,
+                    quiet=args.quiet,
+                    schema_version=args.schema_version,
+                    max_memory=args.max_memory,
+                    base=args.base,
+                    update_budgets=args.update_budgets,
+                )
+            else:
+                tach_check(
+                    project_config=project_config,
+                    project_root=project_root,
+                    exact=args.exact,
+                    output_format=args.output,
+                    group_by=args.group_by,
+                    shard=args.shard,
+                    quiet=args.quiet,
+                    schema_version=args.schema_version,
+                    max_memory=args.max_memory,
+                    base=args.base,
+                    update_budgets=args.update_budgets,
+                )
+        elif args.command == "check-merge":
+            tach_check_merge(
+                project_config=project_config,
+                project_root=project_root,
+                files=args.files,
+                output_format=args.output,
+                group_by=args.group_by,
+                schema_version=args.schema_version,
+            )
+        elif args.command == "check-external":
+            tach_check_external(
+                project_config=project_config,
+                project_root=project_root,
+            )
+        elif args.command == "report":
+            include_dependency_modules = (
+                args.dependency_modules.split(",") if args.dependency_modules else None
+            )
+            include_usage_modules = (
+                args.usage_modules.split(",") if args.usage_modules else None
+            )
+            tach_report(
                 project_config=project_config,
                 project_root=project_root,
+                paths=args.path,
+                include_dependency_modules=include_dependency_modules,
+                include_usage_modules=include_usage_modules,
                 dependencies=args.dependencies,
-                interfaces=args.interfaces,
-                exact=args.exact,
+                usages=args.usages,
+                external=args.external,
+                side_effects=args.side_effects,
+                interface_usage=args.interface_usage,
+                unused_interface_members=args.unused_interface_members,
+                unused_modules=args.unused_modules,
+                matrix=args.matrix,
+                raw=args.raw,
+                output_format=args.format,
+                schema_version=args.schema_version,
+            )
+        elif args.command == "query":
+            tach_query(
+                project_config=project_config,
+                project_root=project_root,
+                query_command=args.query_command,
+                module=getattr(args, "module", None),
+                source=getattr(args, "source", None),
+                target=getattr(args, "target", None),
+                max_paths=getattr(args, "max_paths", 10),
                 output_format=args.output,
+                schema_version=args.schema_version,
             )
-        else:
-            tach_check(
+        elif args.command == "why":
+            tach_why(
                 project_config=project_config,
                 project_root=project_root,
-                exact=args.exact,
+                source=args.source,
+                target=args.target,
+                all_shortest=args.all_shortest,
                 output_format=args.output,
+                schema_version=args.schema_version,
             )
-    elif args.command == "check-external":
-        tach_check_external(
-            project_config=project_config,
-            project_root=project_root,
-        )
-    elif args.command == "report":
-        include_dependency_modules = (
-            args.dependency_modules.split(",") if args.dependency_modules else None
-        )
-        include_usage_modules = (
-            args.usage_modules.split(",") if args.usage_modules else None
-        )
-        tach_report(
-            project_config=project_config,
-            project_root=project_root,
-            path=args.path,
-            include_dependency_modules=include_dependency_modules,
-            include_usage_modules=include_usage_modules,
-            dependencies=args.dependencies,
-            usages=args.usages,
-            external=args.external,
-            raw=args.raw,
-        )
-    elif args.command == "show":
-        tach_show(
-            project_config=project_config,
-            project_root=project_root,
-            included_paths=args.included_paths,
-            output_filepath=args.out,
-            is_web=args.web,
-            is_mermaid=args.mermaid,
-        )
-    elif args.command == "test":
-        tach_test(
-            project_config=project_config,
-            project_root=project_root,
-            head=args.head,
-            base=args.base,
-            disable_cache=args.disable_cache,
-            pytest_args=args.pytest_args,
-        )
-    elif args.command == "export":
-        tach_export(
-            project_config=project_config,
-            project_root=project_root,
-            output_path=args.output,
-            force=args.force,
-        )
-    elif args.command == "upload":
-        tach_upload(
-            project_config=project_config,
-            project_root=project_root,
-            force=args.force,
-        )
-    elif args.command == "server":
-        tach_server(
-            project_config=project_config,
-            project_root=project_root,
-        )
-    else:
-        print("Unrecognized command")
-        parser.print_help()
-        sys.exit(1)
+        elif args.command == "show":
+            tach_show(
+                project_config=project_config,
+                project_root=project_root,
+                included_paths=args.included_paths,
+                output_filepath=args.out,
+                is_web=args.web,
+                is_mermaid=args.mermaid,
+                graph_format=args.format,
+                depth=args.depth,
+                focus=args.focus,
+                focus_depth=args.focus_depth,
+                only_violations=args.only_violations,
+                exclude_utilities=args.exclude_utilities,
+            )
+        elif args.command == "test":
+            tach_test(
+                project_config=project_config,
+                project_root=project_root,
+                head=args.head,
+                base=args.base,
+                disable_cache=args.disable_cache,
+                pytest_args=args.pytest_args,
+            )
+        elif args.command == "export":
+            tach_export(
+                project_config=project_config,
+                project_root=project_root,
+                output_path=args.output,
+                force=args.force,
+                bundle=args.bundle,
+            )
+        elif args.command == "export-config":
+            tach_export_config(
+                project_config=project_config,
+                project_root=project_root,
+                target=args.target,
+                output_path=args.output,
+            )
+        elif args.command == "strictify":
+            tach_strictify(
+                project_config=project_config,
+                project_root=project_root,
+                path=args.path,
+                apply=args.apply,
+            )
+        elif args.command == "badge":
+            tach_badge(
+                project_config=project_config,
+                project_root=project_root,
+                output_path=args.output,
+                output_format=args.format,
+                label=args.label,
+            )
+        elif args.command == "upload":
+            tach_upload(
+                project_config=project_config,
+                project_root=project_root,
+                force=args.force,
+            )
+        elif args.command == "cache":
+            tach_cache_invalidate(
+                project_config=project_config,
+                project_root=project_root,
+                targets=args.targets,
+            )
+        elif args.command == "warm":
+            tach_warm(
+                project_config=project_config,
+                project_root=project_root,
+                checks=args.checks,
+            )
+        elif args.command == "server":
+            tach_server(
+                project_config=project_config,
+                project_root=project_root,
+            )
+        elif args.command == "serve":
+            tach_serve(
+                project_config=project_config,
+                project_root=project_root,
+                host=args.host,
+                port=args.port,
+                open_browser=args.open,
+            )
+        else:
+            print("Unrecognized command")
+            parser.print_help()
+            sys.exit(1)
+    finally:
+        record_run_stats(project_root, project_config, args.command, start_time)
+        if timings:
+            print_timings(main_start_time)
+        if profile_path is not None:
+            write_profile(profile_path)
+        extension.shutdown_otlp_export()
 
 
 __all__ = ["main"]
@@ -15,6 +15,11 @@ DEFAULT_EXCLUDE_PATHS = [
     "**/venv",
 ]
 
+# Chooses the environment whose rule settings (e.g. '[rules.ci]') are applied
+RULES_ENV_VAR: str = "TACH_ENV"
+# Log filter directives for '--verbose' output (e.g. 'tach::processors=trace'), read by the extension
+LOG_ENV_VAR: str = "TACH_LOG"
+
 GAUGE_API_BASE_URL: str = os.getenv("GAUGE_API_BASE_URL", "https://app.gauge.sh")
 
 __all__ = [
@@ -24,5 +29,7 @@ __all__ = [
     "PACKAGE_FILE_NAME",
     "ROOT_MODULE_SENTINEL_TAG",
     "DEFAULT_EXCLUDE_PATHS",
+    "RULES_ENV_VAR",
+    "LOG_ENV_VAR",
     "GAUGE_API_BASE_URL",
 ]
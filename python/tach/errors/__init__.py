@@ -19,6 +19,12 @@ class TachCircularDependencyError(TachError):
         super().__init__("Circular dependency error")
 
 
+class TachCircularDomainDependencyError(TachError):
+    def __init__(self, cycles: list[list[str]]):
+        self.cycles = cycles
+        super().__init__("Circular domain dependency error")
+
+
 class TachVisibilityError(TachError):
     def __init__(self, visibility_errors: list[tuple[str, str, list[str]]]):
         self.visibility_errors = visibility_errors
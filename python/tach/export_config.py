@@ -0,0 +1,235 @@
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import TYPE_CHECKING, Any
+
+import tomli_w
+
+from tach.constants import ROOT_MODULE_SENTINEL_TAG
+
+if TYPE_CHECKING:
+    from tach.extension import ModuleConfig, ProjectConfig
+
+# Top-level rules of deptry which report imports by module name
+DEPTRY_MODULE_RULES = ("DEP001", "DEP003")
+
+
+@dataclass
+class ExportedConfig:
+    """The configuration of another tool converted from the project config, along with anything which could not be converted."""
+
+    contents: str
+    warnings: list[str] = field(default_factory=list)
+
+
+def _covers(parent: str, module: str) -> bool:
+    return module == parent or module.startswith(f"{parent}.")
+
+
+def _is_allowed(
+    module: ModuleConfig,
+    target: ModuleConfig,
+    layers: list[str],
+    utility_paths: set[str],
+) -> bool:
+    """Whether 'module' may import 'target', following the same rules as 'tach check'."""
+    if (
+        module.layer in layers
+        and target.layer in layers
+        and module.layer != target.layer
+    ):
+        # Higher layers can import lower layers, and never the other way around
+        return layers.index(module.layer) < layers.index(target.layer)
+    if module.depends_on is None or target.path in utility_paths:
+        return True
+    return any(dependency.path == target.path for dependency in module.depends_on)
+
+
+def export_to_import_linter(project_config: ProjectConfig) -> ExportedConfig:
+    """
+    Convert the modules of the project into import-linter contracts.
+
+    Each module which may not import some of the other modules gets a 'forbidden' contract,
+    which lists the modules it does not depend on (and those in higher layers).
+    """
+    warnings: list[str] = []
+    modules = sorted(
+        (
+            module
+            for module in project_config.all_modules()
+            if module.path != ROOT_MODULE_SENTINEL_TAG
+        ),
+        key=lambda module: module.path,
+    )
+    utility_paths = set(project_config.utility_paths())
+    layers = list(project_config.layers)
+
+    lines: list[str] = ["[importlinter]", "root_packages ="]
+    root_packages = sorted({module.path.split(".")[0] for module in modules})
+    lines.extend(f"    {package}" for package in root_packages)
+
+    for module in modules:
+        if module.unchecked:
+            continue
+        # import-linter also forbids the descendants of a forbidden module, and a module's own package
+        related = [
+            other
+            for other in modules
+            if _covers(other.path, module.path) or _covers(module.path, other.path)
+        ]
+        forbidden: list[str] = []
+        for target in modules:
+            if target in related or _is_allowed(module, target, layers, utility_paths):
+                continue
+            allowed_descendants = [
+                other.path
+                for other in modules
+                if other.path != target.path
+                and _covers(target.path, other.path)
+                and (
+                    other in related
+                    or _is_allowed(module, other, layers, utility_paths)
+                )
+            ]
+            if allowed_descendants:
+                warnings.append(
+                    f"'{module.path}' may not import '{target.path}', but this was not exported: "
+                    f"import-linter would also forbid {', '.join(repr(path) for path in allowed_descendants)}."
+                )
+                continue
+            forbidden.append(target.path)
+        if not forbidden:
+            continue
+        lines.extend(
+            [
+                "",
+                f"[importlinter:contract:tach-{module.path}]",
+                f"name = {module.path} only imports its dependencies",
+                "type = forbidden",
+                "source_modules =",
+                f"    {module.path}",
+                "forbidden_modules =",
+                *(f"    {path}" for path in forbidden),
+            ]
+        )
+
+    if any(module.visibility != ["*"] for module in modules):
+        warnings.append(
+            "Module 'visibility' was not exported: import-linter has no equivalent."
+        )
+    if project_config.all_interfaces():
+        warnings.append(
+            "Interfaces were not exported: import-linter has no equivalent."
+        )
+    return ExportedConfig(contents="\n".join(lines) + "\n", warnings=warnings)
+
+
+def _gitignore_pattern_to_regex(pattern: str) -> str:
+    """Convert a gitignore-style pattern into a regex matched from the start of a relative path."""
+    anchored = "/" in pattern.rstrip("/")
+    pattern = pattern.strip("/")
+    regex = ""
+    index = 0
+    while index < len(pattern):
+        if pattern.startswith("**/", index):
+            regex += "(.*/)?"
+            index += 3
+        elif pattern.startswith("**", index):
+            regex += ".*"
+            index += 2
+        elif pattern[index] == "*":
+            regex += "[^/]*"
+            index += 1
+        elif pattern[index] == "?":
+            regex += "[^/]"
+            index += 1
+        elif pattern[index] == "[" and "]" in pattern[index + 1 :]:
+            end = pattern.index("]", index + 1)
+            regex += pattern[index : end + 1]
+            index = end + 1
+        else:
+            regex += re.escape(pattern[index])
+            index += 1
+    return ("" if anchored else "(.*/)?") + regex + "(/|$)"
+
+
+def _first_party_packages(
+    project_root: Path, project_config: ProjectConfig
+) -> list[str]:
+    packages = {
+        module.path.split(".")[0]
+        for module in project_config.all_modules()
+        if module.path != ROOT_MODULE_SENTINEL_TAG
+    }
+    for source_root in project_config.source_roots:
+        source_root_path = project_root / source_root
+        if not source_root_path.is_dir():
+            continue
+        for entry in source_root_path.iterdir():
+            if entry.is_dir() and (entry / "__init__.py").is_file():
+                packages.add(entry.name)
+            elif entry.is_file() and entry.suffix == ".py":
+                packages.add(entry.stem)
+    return sorted(packages)
+
+
+def export_to_deptry(
+    project_root: Path, project_config: ProjectConfig
+) -> ExportedConfig:
+    """
+    Convert the project config into deptry settings, as a [tool.deptry] table.
+
+    - Packages in the source roots become 'known_first_party'.
+    - 'external.exclude' is ignored by the deptry rules which report imports by module name.
+    - 'external.rename' becomes 'package_module_name_map'.
+    - 'exclude' patterns become regexes in 'extend_exclude'.
+    """
+    warnings: list[str] = []
+    settings: dict[str, Any] = {}
+
+    first_party = _first_party_packages(project_root, project_config)
+    if first_party:
+        settings["known_first_party"] = first_party
+
+    extend_exclude: list[str] = []
+    for pattern in project_config.exclude:
+        if pattern.startswith("!"):
+            warnings.append(
+                f"Skipped exclude pattern '{pattern}': deptry cannot re-include excluded paths."
+            )
+            continue
+        extend_exclude.append(_gitignore_pattern_to_regex(pattern))
+    if extend_exclude:
+        settings["extend_exclude"] = extend_exclude
+    if not project_config.ignore.respect_gitignore:
+        settings["use_gitignore"] = False
+
+    if project_config.external.exclude:
+        settings["per_rule_ignores"] = {
+            rule: list(project_config.external.exclude)
+            for rule in DEPTRY_MODULE_RULES
+        }
+
+    package_module_name_map: dict[str, list[str]] = {}
+    for rename in project_config.external.rename:
+        module_name, separator, package_name = rename.partition(":")
+        if not separator or not module_name or not package_name:
+            warnings.append(
+                f"Skipped rename '{rename}': expected the format '[module name]:[package name]'."
+            )
+            continue
+        package_module_name_map.setdefault(package_name, []).append(module_name)
+    if package_module_name_map:
+        settings["package_module_name_map"] = {
+            package: modules[0] if len(modules) == 1 else modules
+            for package, modules in package_module_name_map.items()
+        }
+
+    return ExportedConfig(
+        contents=tomli_w.dumps({"tool": {"deptry": settings}}), warnings=warnings
+    )
+
+
+__all__ = ["ExportedConfig", "export_to_deptry", "export_to_import_linter"]
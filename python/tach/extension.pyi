@@ -1,5 +1,8 @@
 from pathlib import Path
-from typing import Literal
+from typing import Callable, Literal
+
+OUTPUT_SCHEMA_VERSION: int
+MIN_OUTPUT_SCHEMA_VERSION: int
 
 class PythonImport:
     module_path: str
@@ -10,12 +13,14 @@ def get_project_imports(
     source_roots: list[Path],
     file_path: Path,
     project_config: ProjectConfig,
+    overlay: dict[Path, str] | None = None,
 ) -> list[PythonImport]: ...
 def get_external_imports(
     project_root: Path,
     source_roots: list[Path],
     file_path: Path,
     project_config: ProjectConfig,
+    overlay: dict[Path, str] | None = None,
 ) -> list[PythonImport]: ...
 def create_dependency_report(
     project_root: Path,
@@ -27,6 +32,63 @@ def create_dependency_report(
     skip_usages: bool,
     raw: bool,
 ) -> str: ...
+def create_combined_dependency_report(
+    project_root: Path,
+    project_config: ProjectConfig,
+    paths: list[Path],
+    include_dependency_modules: list[str] | None,
+    include_usage_modules: list[str] | None,
+    skip_dependencies: bool,
+    skip_usages: bool,
+    raw: bool,
+) -> str: ...
+def create_dependency_records(
+    project_root: Path,
+    project_config: ProjectConfig,
+    path: Path,
+    include_dependency_modules: list[str] | None,
+    include_usage_modules: list[str] | None,
+    skip_dependencies: bool,
+    skip_usages: bool,
+) -> list[DependencyRecord]: ...
+def detect_import_side_effects(
+    project_root: Path, project_config: ProjectConfig
+) -> list[ImportSideEffect]: ...
+def query_dependencies(
+    project_root: Path,
+    project_config: ProjectConfig,
+    module: str,
+    dependents: bool = False,
+) -> list[str]: ...
+def query_paths(
+    project_root: Path,
+    project_config: ProjectConfig,
+    source: str,
+    target: str,
+    max_paths: int = 10,
+) -> list[DependencyPath]: ...
+def explain_dependencies(
+    project_root: Path,
+    project_config: ProjectConfig,
+    source: str,
+    target: str,
+    all_shortest: bool = False,
+) -> list[DependencyExplanation]: ...
+def create_interface_usage_report(
+    project_root: Path, project_config: ProjectConfig, path: Path
+) -> InterfaceUsageReport: ...
+def find_unused_modules(
+    project_root: Path, project_config: ProjectConfig, path: Path
+) -> list[str]: ...
+def create_dependency_matrix(
+    project_root: Path, project_config: ProjectConfig, path: Path
+) -> DependencyMatrix: ...
+def find_unused_interface_members(
+    project_root: Path, project_config: ProjectConfig, path: Path
+) -> list[UnusedInterfaceMembers]: ...
+def strictify_module(
+    project_root: Path, project_config: ProjectConfig, path: Path
+) -> StrictifyReport: ...
 def create_computation_cache_key(
     project_root: Path,
     source_roots: list[Path],
@@ -35,20 +97,81 @@ def create_computation_cache_key(
     file_dependencies: list[str],
     env_dependencies: list[str],
     backend: str,
+    overlay: dict[Path, str] | None = None,
 ) -> str: ...
 def check_computation_cache(
-    project_root: Path, cache_key: str
+    project_root: Path, cache_key: str, cache_config: CacheConfig | None = None
 ) -> tuple[list[tuple[int, str]], int] | None: ...
 def update_computation_cache(
-    project_root: Path, cache_key: str, value: tuple[list[tuple[int, str]], int]
+    project_root: Path,
+    cache_key: str,
+    value: tuple[list[tuple[int, str]], int],
+    cache_config: CacheConfig | None = None,
+) -> None: ...
+def record_usage_stats(
+    project_root: Path,
+    project_config: ProjectConfig,
+    command: str,
+    duration_ms: int,
+    cache_hit: bool | None = None,
 ) -> None: ...
+def format_usage_stats(project_root: Path) -> str: ...
+def explain_rule(rule_id: str) -> str: ...
+def list_rules() -> list[tuple[str, str]]: ...
+def record_check_history(
+    project_root: Path, project_config: ProjectConfig, diagnostics: list[Diagnostic]
+) -> HistoryRun | None: ...
+def load_check_history(project_root: Path) -> list[HistoryRun]: ...
+def find_check_history_run(project_root: Path, reference: str) -> HistoryRun: ...
+def diff_check_history(
+    project_root: Path,
+    base: str | None = None,
+    head: str | None = None,
+    since: str | None = None,
+) -> HistoryDiff: ...
+def diff_check_against_history(
+    project_root: Path,
+    project_config: ProjectConfig,
+    diagnostics: list[Diagnostic],
+    base: str | None = None,
+) -> HistoryDiff: ...
+def init_tracing(
+    verbosity: int = 0, timings: bool = False, profile: bool = False
+) -> None: ...
+def take_timings() -> list[SpanTiming]: ...
+def write_profile(path: Path) -> int: ...
+def start_otlp_export(project_root: Path, project_config: ProjectConfig) -> None: ...
+def shutdown_otlp_export() -> None: ...
 def parse_project_config(filepath: Path) -> tuple[ProjectConfig, bool]: ...
 def dump_project_config_to_toml(project_config: ProjectConfig) -> str: ...
+def dump_project_config_to_yaml(project_config: ProjectConfig) -> str: ...
 def check(
     project_root: Path,
     project_config: ProjectConfig,
     dependencies: bool,
     interfaces: bool,
+    overlay: dict[Path, str] | None = None,
+    shard: str | None = None,
+    progress: Callable[[CheckProgress], None] | None = None,
+    max_memory: int | None = None,
+) -> list[Diagnostic]: ...
+def check_config(
+    project_root: Path,
+    project_config: ProjectConfig,
+    dependencies: bool = True,
+    interfaces: bool = True,
+    overlay: dict[Path, str] | None = None,
+) -> list[Diagnostic]: ...
+def check_file(
+    project_root: Path,
+    project_config: ProjectConfig,
+    file_path: Path,
+    contents: str,
+) -> list[Diagnostic]: ...
+def merge_check_shards(
+    project_root: Path,
+    project_config: ProjectConfig,
+    outputs: list[str],
 ) -> list[Diagnostic]: ...
 def check_external_dependencies(
     project_root: Path,
@@ -57,6 +180,7 @@ def check_external_dependencies(
 def format_diagnostics(
     project_root: Path,
     diagnostics: list[Diagnostic],
+    group_by_owner: bool = False,
 ) -> str: ...
 def detect_unused_dependencies(
     project_root: Path,
@@ -66,9 +190,141 @@ def sync_project(
     project_root: Path,
     project_config: ProjectConfig,
     add: bool = False,
-) -> None: ...
+    dry_run: bool = False,
+) -> SyncPlan: ...
+def ratchet_budgets(
+    project_root: Path,
+    project_config: ProjectConfig,
+    dry_run: bool = False,
+) -> list[BudgetUpdate]: ...
+def update_violation_budgets(
+    project_config: ProjectConfig,
+    diagnostics: list[Diagnostic],
+    dry_run: bool = False,
+) -> list[BudgetUpdate]: ...
+def diff_project_configs(
+    project_root: Path,
+    old_config: ProjectConfig,
+    new_config: ProjectConfig,
+) -> ConfigDiff: ...
+def parse_project_config_as(path: Path, config_path: Path) -> ProjectConfig: ...
+def parse_project_config_at_revision(
+    config_path: Path, revision: str
+) -> ProjectConfig: ...
+def mod_edit_interactive(
+    project_root: Path,
+    project_config: ProjectConfig,
+    exclude_paths: list[str],
+    depth: int | None = 1,
+) -> tuple[bool, list[str]]: ...
 def run_server(project_root: Path, project_config: ProjectConfig) -> None: ...
+def get_module_tree(
+    project_root: Path, project_config: ProjectConfig
+) -> ModuleTreeNode: ...
+def validate_project_config(
+    project_root: Path, config_path: Path
+) -> list[ConfigDiagnostic]: ...
+def migrate_project_config(
+    config_path: Path, dry_run: bool = False, backup: bool = True
+) -> MigrationResult: ...
+def latest_config_backup(project_root: Path) -> Path | None: ...
+def warm_caches(
+    project_root: Path, project_config: ProjectConfig, checks: bool = False
+) -> WarmSummary: ...
+def invalidate_caches(
+    project_root: Path, project_config: ProjectConfig, targets: list[str]
+) -> InvalidateSummary: ...
 def serialize_modules_json(modules: list[ModuleConfig]) -> str: ...
+def build_module_graph(
+    project_config: ProjectConfig,
+    included_paths: list[Path],
+    violations: list[tuple[str, str]] = [],
+    focus: str | None = None,
+    focus_depth: int = 1,
+    depth: int | None = None,
+    only_violations: bool = False,
+    exclude_utilities: bool = False,
+) -> ModuleGraph: ...
+
+SyncAction = Literal["add", "remove", "deprecate"]
+
+class ImportLocation:
+    file: str
+    line: int
+
+class SyncChange:
+    path: str
+    dependency: str
+    action: SyncAction
+    # The number of imports which use the dependency (only for added dependencies)
+    evidence: int
+    examples: list[ImportLocation]
+
+class SyncPlan:
+    changes: list[SyncChange]
+    created_modules: list[str]
+    deleted_modules: list[str]
+
+def export_bundle(
+    project_root: Path, project_config: ProjectConfig, directory: Path
+) -> ExportSummary: ...
+def init_project(
+    project_root: Path, force: bool = False, sync: bool = True
+) -> InitSummary: ...
+
+class ExportSummary:
+    schema_version: int
+    directory: Path
+    artifacts: list[str]
+
+class InitSummary:
+    config_path: Path
+    source_roots: list[Path]
+    modules: list[str]
+    dependencies: int
+
+class GraphEdge:
+    source: str
+    target: str
+    declared: bool
+    deprecated: bool
+    violations: int
+
+class ModuleGraph:
+    nodes: list[str]
+    edges: list[GraphEdge]
+
+class CheckProgress:
+    checked_files: int
+    total_files: int
+
+class SpanTiming:
+    name: str
+    calls: int
+    total_seconds: float
+
+class HistoryFinding:
+    fingerprint: str
+    severity: Literal["error", "warning"]
+    file_path: str | None
+    line_number: int | None
+    message: str
+
+class HistoryRun:
+    id: str
+    timestamp: int
+    commit: str | None
+    errors: int
+    warnings: int
+    findings: list[HistoryFinding]
+    suppressions: int | None
+    module_coverage: float | None
+
+class HistoryDiff:
+    base: HistoryRun
+    head: HistoryRun
+    new: list[HistoryFinding]
+    fixed: list[HistoryFinding]
 
 class Diagnostic:
     def is_code(self) -> bool: ...
@@ -78,14 +334,23 @@ class Diagnostic:
     def is_warning(self) -> bool: ...
     def is_error(self) -> bool: ...
     def is_deprecated(self) -> bool: ...
+    def deprecated_member(self) -> str | None: ...
     def usage_module(self) -> str | None: ...
     def definition_module(self) -> str | None: ...
     def to_string(self) -> str: ...
     def pyfile_path(self) -> str | None: ...
     def pyline_number(self) -> int | None: ...
+    def rule_id(self) -> str | None: ...
+    def column(self) -> int | None: ...
+    def end_line_number(self) -> int | None: ...
+    def end_column(self) -> int | None: ...
+    def owners(self) -> list[str]: ...
+    def occurrences(self) -> int: ...
 
 def serialize_diagnostics_json(
-    diagnostics: list[Diagnostic], pretty_print: bool
+    diagnostics: list[Diagnostic],
+    pretty_print: bool = False,
+    schema_version: int | None = None,
 ) -> str: ...
 
 ErrorKind = Literal["DEPENDENCY", "INTERFACE"]
@@ -105,16 +370,138 @@ def into_usage_errors(diagnostics: list[Diagnostic]) -> list[UsageError]: ...
 class DependencyConfig:
     path: str
     deprecated: bool
+    deprecated_until: str | None
+    reason: str | None
+    ticket: str | None
+    members: list[str] | None
+    deprecated_members: list[str] | None
+
+Stability = Literal["stable", "beta", "experimental"]
 
 class ModuleConfig:
     path: str
     depends_on: list[DependencyConfig] | None
+    layer: str | None
+    stability: Stability | None
+    platforms: list[str]
     visibility: list[str]
     strict: bool
     unchecked: bool
+    display_name: str | None
+    max_violations: int | None
+    internal_structure: dict[str, list[str]]
+    rules: ModuleRulesConfig | None
+    implements: str | None
 
     def __new__(cls, path: str, strict: bool) -> ModuleConfig: ...
     def mod_path(self) -> str: ...
+    def label(self) -> str: ...
+
+class ModuleTreeNode:
+    name: str
+    full_path: str
+    is_module: bool
+    config: ModuleConfig | None
+    interface_members: list[str]
+    dependencies: list[str]
+    children: list[ModuleTreeNode]
+
+    def find(self, full_path: str) -> ModuleTreeNode | None: ...
+    def modules(self) -> list[ModuleTreeNode]: ...
+
+class WarmSummary:
+    files: int
+    skipped: int
+    checked: bool
+    shared: bool
+
+class InvalidateSummary:
+    files: int
+    entries: int
+
+ImportSideEffectKind = Literal[
+    "network", "filesystem", "database", "subprocess", "heavy_import"
+]
+
+class ImportSideEffect:
+    file_path: Path
+    line_number: int
+    module_path: str | None
+    kind: ImportSideEffectKind
+    detail: str
+
+class InterfaceMemberUsage:
+    member: str
+    usages: int
+    usage_modules: list[str]
+
+class DependencyRecord:
+    kind: Literal["dependency", "usage"]
+    file_path: Path
+    line_number: int
+    source_module: str
+    target_module: str
+    import_path: str
+
+class DependencyMatrix:
+    modules: list[str]
+    labels: list[str]
+    counts: list[list[int]]
+    violations: list[list[bool]]
+
+class InterfaceUsageReport:
+    module_path: str
+    members: list[InterfaceMemberUsage]
+
+class UnusedInterfaceMembers:
+    module_path: str
+    members: list[str]
+
+class BlockingImport:
+    file_path: Path
+    line_number: int
+    member: str
+    usage_module: str
+
+class StrictifyReport:
+    module_path: str
+    has_interface: bool
+    covered_usages: int
+    blocking_imports: list[BlockingImport]
+    expose: list[str]
+
+class PathStep:
+    source: str
+    target: str
+    # The number of imports which use this dependency
+    evidence: int
+    examples: list[ImportLocation]
+
+class DependencyPath:
+    steps: list[PathStep]
+
+class DependencyExplanation:
+    source: str
+    target: str
+    paths: list[DependencyPath]
+
+class MigrationResult:
+    from_version: int
+    to_version: int
+    applied: list[str]
+    original: str
+    migrated: str
+    backup: Path | None
+
+    def has_changes(self) -> bool: ...
+
+class ConfigDiagnostic:
+    kind: str
+    message: str
+    suggestion: str | None
+    file: Path
+    line: int | None
+    column: int | None
 
 InterfaceDataTypes = Literal["all", "primitive"]
 
@@ -123,14 +510,50 @@ class InterfaceConfig:
     # 'from' in tach.toml
     from_modules: list[str]
     data_types: InterfaceDataTypes
+    stability: Stability | None
 
-CacheBackend = Literal["disk"]
+CacheBackend = Literal["disk", "http", "redis"]
+
+class RemoteCacheConfig:
+    url: str
+    token_env: str | None
+
+class RedisCacheConfig:
+    url: str
+    key_prefix: str
+    ttl_seconds: int | None
 
 class CacheConfig:
     backend: CacheBackend
+    remote: RemoteCacheConfig | None
+    redis: RedisCacheConfig | None
     file_dependencies: list[str]
     env_dependencies: list[str]
 
+class IgnoreConfig:
+    respect_gitignore: bool
+
+class HealthConfig:
+    violations: float
+    cycles: float
+    instability: float
+    interface_coverage: float
+    suppressions: float
+
+class OtlpConfig:
+    enabled: bool
+    endpoint: str | None
+    resource_attributes: dict[str, str]
+
+class PathExclusions:
+    def __new__(
+        cls,
+        project_root: Path,
+        exclude_paths: list[str],
+        ignore: IgnoreConfig | None = None,
+    ) -> PathExclusions: ...
+    def is_path_excluded(self, path: Path) -> bool: ...
+
 class ExternalDependencyConfig:
     exclude: list[str]
     rename: list[str]
@@ -139,31 +562,237 @@ class UnusedDependencies:
     path: str
     dependencies: list[DependencyConfig]
 
+class BudgetUpdate:
+    path: str
+    previous: int
+    current: int
+
+class DependencyChange:
+    module_path: str
+    dependency_path: str
+
+class RuleChange:
+    rule: str
+    old: str
+    new: str
+
+class ConfigDiff:
+    modules_added: list[str]
+    modules_removed: list[str]
+    dependencies_added: list[DependencyChange]
+    dependencies_removed: list[DependencyChange]
+    rules_changed: list[RuleChange]
+    new: list[HistoryFinding]
+    fixed: list[HistoryFinding]
+
 RuleSetting = Literal["error", "warn", "off"]
 
 RootModuleTreatment = Literal["allow", "ignore", "dependenciesonly", "forbid"]
 
+ReexportResolution = Literal["nearest", "follow"]
+
+SymlinkPolicy = Literal["follow", "skip", "error-on-cycle"]
+ModulePathNormalizer = Literal["strip-src", "strip-version-suffix", "unit-tests"]
+
 class RulesConfig:
     unused_ignore_directives: RuleSetting
     require_ignore_directive_reasons: RuleSetting
+    reexport_resolution: ReexportResolution
+    max_reexport_depth: int
+    function_imports: RuleSetting
+    try_except_imports: RuleSetting
+    main_block_imports: RuleSetting
+    conftest_imports: RuleSetting
+    type_checking_imports: RuleSetting
+    cli_patterns: list[str]
+    undeclared_dependencies: RuleSetting
+    deprecated_dependencies: RuleSetting
+    interface_violations: RuleSetting
+    circular_dependencies: RuleSetting
+    root_module_usage: RuleSetting
+    unstable_dependencies: RuleSetting
+    unresolved_imports: RuleSetting
+    wildcard_imports: RuleSetting
+    relative_imports: RuleSetting
+    conditional_imports: RuleSetting
+    platform_imports: RuleSetting
+    domain_boundaries: RuleSetting
+    interface_conformance: RuleSetting
+    utility_dependencies: RuleSetting
+    environments: dict[str, EnvironmentRulesConfig]
+
+class EnvironmentRulesConfig:
+    unused_ignore_directives: RuleSetting | None
+    require_ignore_directive_reasons: RuleSetting | None
+    unused_external_dependencies: RuleSetting | None
+    function_imports: RuleSetting | None
+    try_except_imports: RuleSetting | None
+    main_block_imports: RuleSetting | None
+    conftest_imports: RuleSetting | None
+    type_checking_imports: RuleSetting | None
+    undeclared_dependencies: RuleSetting | None
+    deprecated_dependencies: RuleSetting | None
+    interface_violations: RuleSetting | None
+    circular_dependencies: RuleSetting | None
+    root_module_usage: RuleSetting | None
+    unstable_dependencies: RuleSetting | None
+    unresolved_imports: RuleSetting | None
+    wildcard_imports: RuleSetting | None
+    relative_imports: RuleSetting | None
+    conditional_imports: RuleSetting | None
+    platform_imports: RuleSetting | None
+    domain_boundaries: RuleSetting | None
+    interface_conformance: RuleSetting | None
+    utility_dependencies: RuleSetting | None
+
+class ModuleRulesConfig:
+    undeclared_dependencies: RuleSetting | None
+    deprecated_dependencies: RuleSetting | None
+    interface_violations: RuleSetting | None
+    root_module_usage: RuleSetting | None
+    unstable_dependencies: RuleSetting | None
+    function_imports: RuleSetting | None
+    try_except_imports: RuleSetting | None
+    main_block_imports: RuleSetting | None
+    conftest_imports: RuleSetting | None
+    type_checking_imports: RuleSetting | None
+    wildcard_imports: RuleSetting | None
+    relative_imports: RuleSetting | None
+    conditional_imports: RuleSetting | None
+    platform_imports: RuleSetting | None
+    domain_boundaries: RuleSetting | None
+
+class ForbidRuleConfig:
+    from_: str
+    to: str
+    unless_through: str | None
+    reason: str | None
+    severity: RuleSetting
+
+class NamingRuleConfig:
+    modules: str
+    pattern: str
+    reason: str | None
+    severity: RuleSetting
+
+class ConfigEdit:
+    class CreateModule(ConfigEdit):
+        path: str
+        def __init__(self, path: str) -> None: ...
+
+    class DeleteModule(ConfigEdit):
+        path: str
+        def __init__(self, path: str) -> None: ...
+
+    class MarkModuleAsUtility(ConfigEdit):
+        path: str
+        def __init__(self, path: str) -> None: ...
+
+    class UnmarkModuleAsUtility(ConfigEdit):
+        path: str
+        def __init__(self, path: str) -> None: ...
+
+    class AddDependency(ConfigEdit):
+        path: str
+        dependency: str
+        def __init__(self, path: str, dependency: str) -> None: ...
+
+    class RemoveDependency(ConfigEdit):
+        path: str
+        dependency: str
+        def __init__(self, path: str, dependency: str) -> None: ...
+
+    class MarkDependencyDeprecated(ConfigEdit):
+        path: str
+        dependency: str
+        def __init__(self, path: str, dependency: str) -> None: ...
+
+    class UnmarkDependencyDeprecated(ConfigEdit):
+        path: str
+        dependency: str
+        def __init__(self, path: str, dependency: str) -> None: ...
+
+    class SetModuleStrict(ConfigEdit):
+        path: str
+        strict: bool
+        def __init__(self, path: str, strict: bool) -> None: ...
+
+    class SetModuleLayer(ConfigEdit):
+        path: str
+        layer: str | None
+        def __init__(self, path: str, layer: str | None) -> None: ...
+
+    class SetModuleMaxViolations(ConfigEdit):
+        path: str
+        max_violations: int | None
+        def __init__(self, path: str, max_violations: int | None) -> None: ...
+
+    class AddSourceRoot(ConfigEdit):
+        filepath: Path
+        def __init__(self, filepath: Path) -> None: ...
+
+    class RemoveSourceRoot(ConfigEdit):
+        filepath: Path
+        def __init__(self, filepath: Path) -> None: ...
+
+    class AddInterface(ConfigEdit):
+        path: str
+        expose: list[str]
+        def __init__(self, path: str, expose: list[str]) -> None: ...
+
+    class SetMaxViolations(ConfigEdit):
+        max_violations: int | None
+        def __init__(self, max_violations: int | None) -> None: ...
+
+class ConfigEditPreview:
+    path: Path
+    original: str
+    edited: str
+
+    def diff(self) -> str: ...
 
 class ProjectConfig:
     cache: CacheConfig
     external: ExternalDependencyConfig
     exclude: list[str]
+    ignore: IgnoreConfig
+    health: HealthConfig
+    otlp: OtlpConfig
+    layers: list[str]
     source_roots: list[str]
+    python_version: str | None
+    source_root_python_versions: dict[Path, str]
     exact: bool
     disable_logging: bool
+    record_usage_stats: bool
+    record_history: bool
+    max_violations: int | None
     ignore_type_checking_imports: bool
     include_string_imports: bool
+    analyze_attribute_access: bool
+    namespace_packages: bool
+    implicit_modules: bool
+    inline_config: bool
+    include_stubs: bool
+    include_notebooks: bool
+    symlinks: SymlinkPolicy
+    module_path_normalizers: list[ModulePathNormalizer]
+    schema_version: int
     forbid_circular_dependencies: bool
+    forbid_circular_domain_dependencies: bool
     use_regex_matching: bool
     rules: RulesConfig
+    forbid: list[ForbidRuleConfig]
+    naming: list[NamingRuleConfig]
     root_module: RootModuleTreatment
+    module_mappings: dict[str, Path]
+    entrypoints: list[str]
+    backup_edits: bool
 
     def __new__(cls) -> ProjectConfig: ...
     def serialize_json(self) -> str: ...
     def set_location(self, location: Path) -> None: ...
+    def apply_rules_environment(self, environment: str) -> bool: ...
     def has_no_modules(self) -> bool: ...
     def has_no_dependencies(self) -> bool: ...
     def has_root_module_reference(self) -> bool: ...
@@ -175,8 +804,20 @@ class ProjectConfig:
     def unmark_module_as_utility(self, path: str) -> None: ...
     def add_dependency(self, path: str, dependency: str) -> None: ...
     def remove_dependency(self, path: str, dependency: str) -> None: ...
+    def mark_dependency_deprecated(self, path: str, dependency: str) -> None: ...
+    def unmark_dependency_deprecated(self, path: str, dependency: str) -> None: ...
+    def set_module_strict(self, path: str, strict: bool) -> None: ...
+    def set_module_layer(self, path: str, layer: str | None = None) -> None: ...
+    def set_module_max_violations(
+        self, path: str, max_violations: int | None = None
+    ) -> None: ...
+    def set_max_violations(self, max_violations: int | None = None) -> None: ...
     def add_source_root(self, path: Path) -> None: ...
     def remove_source_root(self, path: Path) -> None: ...
+    def add_interface(self, path: str, expose: list[str]) -> None: ...
+    def enqueue_edit(self, edit: ConfigEdit) -> None: ...
+    def pending_edits(self) -> list[ConfigEdit]: ...
+    def preview_edits(self) -> list[ConfigEditPreview]: ...
     def save_edits(self) -> None: ...
     def all_modules(self) -> list[ModuleConfig]: ...
     def all_interfaces(self) -> list[InterfaceConfig]: ...
@@ -2,10 +2,13 @@ from __future__ import annotations
 
 from tach.filesystem.install import install_pre_commit
 from tach.filesystem.project import (
+    CONFIG_PATH_ENV_VAR,
+    PROJECT_ROOT_ENV_VAR,
+    ProjectLocation,
     build_project_config_path,
     find_project_config_root,
-    get_deprecated_project_config_path,
     get_project_config_path,
+    resolve_project_location,
 )
 from tach.filesystem.service import (
     file_to_module_path,
@@ -13,17 +16,22 @@ from tach.filesystem.service import (
     walk,
     walk_pyfiles,
     write_file,
+    write_file_atomic,
 )
 
 __all__ = [
     "write_file",
+    "write_file_atomic",
     "walk",
     "walk_pyfiles",
     "file_to_module_path",
     "module_to_pyfile_or_dir_path",
     "get_project_config_path",
     "build_project_config_path",
-    "get_deprecated_project_config_path",
     "find_project_config_root",
+    "resolve_project_location",
+    "ProjectLocation",
+    "CONFIG_PATH_ENV_VAR",
+    "PROJECT_ROOT_ENV_VAR",
     "install_pre_commit",
 ]
@@ -1,9 +1,17 @@
 from __future__ import annotations
 
+import os
+from dataclasses import dataclass
 from pathlib import Path
 
 from tach.constants import CONFIG_FILE_NAME
 
+# TOML takes precedence when several config files are present
+PROJECT_CONFIG_EXTENSIONS = ("toml", "yml", "yaml")
+
+CONFIG_PATH_ENV_VAR = "TACH_CONFIG"
+PROJECT_ROOT_ENV_VAR = "TACH_ROOT"
+
 
 def build_project_config_path(root: Path, file_name: str = CONFIG_FILE_NAME) -> Path:
     return root / f"{file_name}.toml"
@@ -12,20 +20,10 @@ def build_project_config_path(root: Path, file_name: str = CONFIG_FILE_NAME) ->
 def get_project_config_path(
     root: Path, *, file_name: str = CONFIG_FILE_NAME
 ) -> Path | None:
-    file_path = build_project_config_path(root, file_name)
-    if file_path.exists():
-        return file_path
-    return None
-
-
-def get_deprecated_project_config_path(root: Path | None = None) -> Path | None:
-    root = root or Path.cwd()
-    file_path = root / f"{CONFIG_FILE_NAME}.yaml"
-    if file_path.exists():
-        return file_path
-    file_path = root / f"{CONFIG_FILE_NAME}.yml"
-    if file_path.exists():
-        return file_path
+    for extension in PROJECT_CONFIG_EXTENSIONS:
+        file_path = root / f"{file_name}.{extension}"
+        if file_path.exists():
+            return file_path
     return None
 
 
@@ -41,3 +39,41 @@ def find_project_config_root() -> Path | None:
             return parent
 
     return None
+
+
+@dataclass(frozen=True)
+class ProjectLocation:
+    root: Path
+    # None when no config file was found
+    config_path: Path | None
+
+
+def _path_from_env(env_var: str) -> Path | None:
+    value = os.environ.get(env_var)
+    return Path(value) if value else None
+
+
+def resolve_project_location(
+    *, config_path: Path | None = None, root: Path | None = None
+) -> ProjectLocation:
+    """
+    Resolve the project root and config file used by every command.
+
+    Explicit paths (from '--config' and '--root') take precedence over the
+    TACH_CONFIG and TACH_ROOT environment variables, which take precedence
+    over searching upwards from the current directory.
+    Unless a root is also given, the project root is the directory containing the config file.
+    """
+    if config_path is None and root is None:
+        config_path = _path_from_env(CONFIG_PATH_ENV_VAR)
+        root = _path_from_env(PROJECT_ROOT_ENV_VAR)
+
+    if config_path is not None:
+        config_path = config_path.resolve()
+        return ProjectLocation(
+            root=root.resolve() if root is not None else config_path.parent,
+            config_path=config_path,
+        )
+
+    root = (root or find_project_config_root() or Path.cwd()).resolve()
+    return ProjectLocation(root=root, config_path=get_project_config_path(root))
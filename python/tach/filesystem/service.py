@@ -2,12 +2,15 @@ from __future__ import annotations
 
 import os
 import stat
+import tempfile
 from functools import lru_cache
 from pathlib import Path
-from typing import Generator, Optional
+from typing import TYPE_CHECKING, Generator, Optional
 
 from tach.colors import BCOLORS
-from tach.utils.exclude import is_path_excluded
+
+if TYPE_CHECKING:
+    from tach.extension import PathExclusions
 
 
 def write_file(path: Path, content: str, root: Optional[Path] = None):
@@ -19,15 +22,28 @@ def write_file(path: Path, content: str, root: Optional[Path] = None):
     print(f"{BCOLORS.WARNING}Wrote '{display_path}'{BCOLORS.ENDC}")
 
 
+def write_file_atomic(path: Path, content: str):
+    # Write to a temporary file next to 'path' and rename it into place,
+    # so an interrupted write never leaves a partially written file behind
+    fd, tmp_path = tempfile.mkstemp(dir=path.parent, prefix=f".{path.name}.")
+    try:
+        with os.fdopen(fd, "w") as f:
+            f.write(content)
+        if path.exists():
+            os.chmod(tmp_path, stat.S_IMODE(path.stat().st_mode))
+        os.replace(tmp_path, path)
+    except BaseException:
+        Path(tmp_path).unlink(missing_ok=True)
+        raise
+
+
 def mark_executable(path: Path):
     path.chmod(path.stat().st_mode | stat.S_IXUSR | stat.S_IXGRP | stat.S_IXOTH)
 
 
 def walk(
     root: Path,
-    project_root: Optional[Path] = None,
-    exclude_paths: Optional[list[str]] = None,
-    use_regex_matching: bool = False,
+    exclusions: PathExclusions | None = None,
     depth: int | None = None,
 ) -> Generator[tuple[Path, list[Path]], None, None]:
     if depth is not None and depth <= 0:
@@ -41,15 +57,9 @@ def walk(
             del dirnames[:]
             continue
 
-        if exclude_paths:
-            project_dirpath = (
-                Path(dirpath).relative_to(project_root) if project_root else rel_dirpath
-            )
-            if is_path_excluded(
-                exclude_paths, project_dirpath, use_regex_matching=use_regex_matching
-            ):
-                del dirnames[:]
-                continue
+        if exclusions is not None and exclusions.is_path_excluded(Path(dirpath)):
+            del dirnames[:]
+            continue
 
         if depth:
             # Ignore anything past requested depth
@@ -58,8 +68,8 @@ def walk(
                 continue
 
         def filter_filename(filename: str) -> bool:
-            if exclude_paths and is_path_excluded(
-                exclude_paths, Path(filename), use_regex_matching=use_regex_matching
+            if exclusions is not None and exclusions.is_path_excluded(
+                Path(dirpath) / filename
             ):
                 return False
             return not filename.startswith(".")
@@ -69,18 +79,10 @@ def walk(
 
 def walk_pyfiles(
     root: Path,
-    project_root: Optional[Path] = None,
-    exclude_paths: Optional[list[str]] = None,
-    use_regex_matching: bool = False,
+    exclusions: PathExclusions | None = None,
     depth: int | None = None,
 ) -> Generator[Path, None, None]:
-    for dirpath, filepaths in walk(
-        root,
-        project_root=project_root,
-        exclude_paths=exclude_paths,
-        use_regex_matching=use_regex_matching,
-        depth=depth,
-    ):
+    for dirpath, filepaths in walk(root, exclusions=exclusions, depth=depth):
         for filepath in filepaths:
             if filepath.name.endswith(".py"):
                 yield dirpath / filepath
@@ -89,11 +91,13 @@ def walk_pyfiles(
 @lru_cache(maxsize=None)
 def file_to_module_path(source_roots: tuple[Path, ...], file_path: Path) -> str:
     # NOTE: source_roots are assumed to be absolute here
+    # When source roots are nested (e.g. '.' and 'src'), the most specific root wins
     matching_root: Path | None = None
     for root in source_roots:
-        if root in file_path.parents:
+        if root in file_path.parents and (
+            matching_root is None or len(root.parts) > len(matching_root.parts)
+        ):
             matching_root = root
-            break
 
     if matching_root is None:
         raise ValueError(f"File path: {file_path} not found in any source root.")
@@ -128,15 +132,22 @@ def module_to_pyfile_or_dir_path(
         return None
 
     base_path = module_path.replace(".", os.sep)
+    # A regular package or module in any source root takes precedence
+    # over a namespace package directory found in an earlier root (PEP 420)
+    namespace_dir: Path | None = None
     for source_root in source_roots:
         dir_path = source_root / base_path
         pyinterface_path = source_root / f"{base_path}.pyi"
         pyfile_path = source_root / f"{base_path}.py"
         if dir_path.is_dir():
-            return dir_path
+            if (dir_path / "__init__.py").is_file() or (
+                dir_path / "__init__.pyi"
+            ).is_file():
+                return dir_path
+            namespace_dir = namespace_dir or dir_path
         elif pyinterface_path.exists():
             return pyinterface_path
         elif pyfile_path.exists():
             return pyfile_path
 
-    return None
+    return namespace_dir
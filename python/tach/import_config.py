@@ -0,0 +1,303 @@
+from __future__ import annotations
+
+import configparser
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any
+
+import tomli
+import tomli_w
+
+from tach.errors import TachConfigError
+from tach.extension import ProjectConfig, dump_project_config_to_toml
+
+IMPORT_LINTER_SECTION = "importlinter"
+IMPORT_LINTER_CONTRACT_PREFIX = "importlinter:contract:"
+SUPPORTED_CONTRACT_TYPES = ("layers", "forbidden", "independence")
+# Root packages are looked for in these directories, relative to the project root
+CANDIDATE_SOURCE_ROOTS = (".", "src")
+
+
+@dataclass
+class ImportedConfig:
+    """A project config converted from another tool, along with anything which could not be converted."""
+
+    config_toml: str
+    modules: list[str]
+    layers: list[str]
+    warnings: list[str] = field(default_factory=list)
+
+
+@dataclass
+class _Contract:
+    name: str
+    type: str
+    options: dict[str, Any]
+
+    def list_option(self, key: str) -> list[str]:
+        return _as_list(self.options.get(key))
+
+
+def _as_list(value: Any) -> list[str]:
+    if value is None:
+        return []
+    if isinstance(value, str):
+        # Lists in INI files are written one item per line
+        lines = (line.strip() for line in value.splitlines())
+        return [line for line in lines if line and not line.startswith("#")]
+    return [str(item).strip() for item in value]
+
+
+def _read_import_linter_config(path: Path) -> tuple[list[str], list[_Contract]]:
+    """Read the root packages and contracts from an INI file (setup.cfg, .importlinter) or pyproject.toml."""
+    try:
+        contents = path.read_text()
+    except OSError as e:
+        raise TachConfigError(f"Failed to read '{path}': {e}")
+
+    if path.suffix == ".toml":
+        try:
+            data = tomli.loads(contents)
+        except tomli.TOMLDecodeError as e:
+            raise TachConfigError(f"Failed to parse '{path}': {e}")
+        section = data.get("tool", {}).get(IMPORT_LINTER_SECTION)
+        if section is None:
+            raise TachConfigError(f"No [tool.importlinter] table found in '{path}'")
+        contracts = [
+            _Contract(
+                name=str(contract.get("name") or contract.get("id") or index + 1),
+                type=str(contract.get("type", "")),
+                options=contract,
+            )
+            for index, contract in enumerate(section.get("contracts", []))
+        ]
+    else:
+        parser = configparser.ConfigParser(interpolation=None)
+        try:
+            parser.read_string(contents, source=str(path))
+        except configparser.Error as e:
+            raise TachConfigError(f"Failed to parse '{path}': {e}")
+        if not parser.has_section(IMPORT_LINTER_SECTION):
+            raise TachConfigError(
+                f"No [{IMPORT_LINTER_SECTION}] section found in '{path}'"
+            )
+        section = dict(parser[IMPORT_LINTER_SECTION])
+        contracts = [
+            _Contract(
+                name=parser[section_name].get(
+                    "name", section_name[len(IMPORT_LINTER_CONTRACT_PREFIX) :]
+                ),
+                type=parser[section_name].get("type", ""),
+                options=dict(parser[section_name]),
+            )
+            for section_name in parser.sections()
+            if section_name.startswith(IMPORT_LINTER_CONTRACT_PREFIX)
+        ]
+
+    root_packages = _as_list(section.get("root_packages")) or _as_list(
+        section.get("root_package")
+    )
+    return root_packages, contracts
+
+
+def _find_source_roots(project_root: Path, root_packages: list[str]) -> list[str]:
+    source_roots: list[str] = []
+    for package in root_packages:
+        for source_root in CANDIDATE_SOURCE_ROOTS:
+            package_path = project_root / source_root / package
+            if package_path.is_dir() or package_path.with_suffix(".py").is_file():
+                if source_root not in source_roots:
+                    source_roots.append(source_root)
+                break
+    return source_roots or ["."]
+
+
+def _module_exists(project_root: Path, source_roots: list[str], module: str) -> bool:
+    for source_root in source_roots:
+        module_path = project_root / source_root / module.replace(".", "/")
+        if module_path.is_dir() or module_path.with_suffix(".py").is_file():
+            return True
+    return False
+
+
+def _covers(parent: str, module: str) -> bool:
+    return module == parent or module.startswith(f"{parent}.")
+
+
+def _merge_layers(
+    layers: list[str], contract_layers: list[str], contract: _Contract
+) -> list[str]:
+    """Add the layers of a contract to the single ordering of layers which Tach supports."""
+    shared = [layer for layer in contract_layers if layer in layers]
+    if shared != sorted(shared, key=layers.index):
+        raise TachConfigError(
+            f"The layers in contract '{contract.name}' are ordered differently from an earlier contract, "
+            "but Tach supports a single ordering of layers."
+        )
+    merged = list(layers)
+    for index, layer in enumerate(contract_layers):
+        if layer in merged:
+            continue
+        # Keep the new layer above the next layer which is already known
+        following = [
+            known for known in contract_layers[index + 1 :] if known in merged
+        ]
+        position = merged.index(following[0]) if following else len(merged)
+        merged.insert(position, layer)
+    return merged
+
+
+def import_from_import_linter(project_root: Path, path: Path) -> ImportedConfig:
+    """
+    Convert the contracts of an import-linter config into Tach modules and layers.
+
+    - 'layers' contracts become layers, with a module for each layer in each container.
+      Layers of independent siblings ('a | b') also may not import each other.
+    - 'forbidden' contracts remove the forbidden modules from 'depends_on' of the source modules.
+    - 'independence' contracts remove the other independent modules from 'depends_on' of each module.
+    """
+    root_packages, contracts = _read_import_linter_config(path)
+    source_roots = _find_source_roots(project_root, root_packages)
+    warnings: list[str] = []
+
+    layers: list[str] = []
+    module_layers: dict[str, str] = {}
+    # Pairs of (sources, targets), where the sources may not import any of the targets
+    restrictions: list[tuple[list[str], list[str]]] = []
+    mentioned: list[str] = []
+    optional: set[str] = set()
+
+    def mention(module: str, contract: _Contract) -> bool:
+        if "*" in module:
+            warnings.append(
+                f"Skipped '{module}' in contract '{contract.name}': wildcards are not supported."
+            )
+            return False
+        if module not in mentioned:
+            mentioned.append(module)
+        return True
+
+    for contract in contracts:
+        if contract.type not in SUPPORTED_CONTRACT_TYPES:
+            warnings.append(
+                f"Skipped contract '{contract.name}': contracts of type '{contract.type}' are not supported."
+            )
+            continue
+        if contract.list_option("ignore_imports"):
+            warnings.append(
+                f"Ignored imports in contract '{contract.name}' were not converted; "
+                "add '# tach-ignore' comments to those imports instead."
+            )
+
+        if contract.type == "layers":
+            containers = contract.list_option("containers")
+            contract_layers: list[str] = []
+            for item in contract.list_option("layers"):
+                is_optional = item.startswith("(") and item.endswith(")")
+                layer = item.strip("()").strip()
+                independent = "|" in layer
+                siblings = [
+                    sibling.strip()
+                    for sibling in layer.replace("|", ":").split(":")
+                    if sibling.strip()
+                ]
+                contract_layers.append(layer)
+                for container in containers or [None]:
+                    modules = [
+                        f"{container}.{sibling}" if container else sibling
+                        for sibling in siblings
+                    ]
+                    modules = [
+                        module for module in modules if mention(module, contract)
+                    ]
+                    for module in modules:
+                        if is_optional:
+                            optional.add(module)
+                        if module_layers.setdefault(module, layer) != layer:
+                            warnings.append(
+                                f"Module '{module}' is in layer '{module_layers[module]}' and '{layer}'; "
+                                f"kept '{module_layers[module]}'."
+                            )
+                    if independent:
+                        restrictions.extend(
+                            ([module], [other for other in modules if other != module])
+                            for module in modules
+                        )
+            layers = _merge_layers(layers, contract_layers, contract)
+        elif contract.type == "forbidden":
+            sources = [
+                module
+                for module in contract.list_option("source_modules")
+                if mention(module, contract)
+            ]
+            forbidden = [
+                module
+                for module in contract.list_option("forbidden_modules")
+                if mention(module, contract)
+            ]
+            restrictions.append((sources, forbidden))
+        else:
+            modules = [
+                module
+                for module in contract.list_option("modules")
+                if mention(module, contract)
+            ]
+            restrictions.extend(
+                ([module], [other for other in modules if other != module])
+                for module in modules
+            )
+
+    declared: list[str] = []
+    for module in mentioned:
+        if _module_exists(project_root, source_roots, module):
+            declared.append(module)
+        elif module not in optional:
+            warnings.append(
+                f"Skipped '{module}': it was not found in the source roots ({', '.join(source_roots)}). "
+                "If it is a third-party package, see 'tach check-external'."
+            )
+    declared.sort()
+
+    restricted: dict[str, set[str]] = {}
+    for sources, targets in restrictions:
+        for module in declared:
+            if not any(_covers(source, module) for source in sources):
+                continue
+            restricted.setdefault(module, set()).update(
+                other
+                for other in declared
+                if any(_covers(target, other) for target in targets)
+            )
+
+    modules: list[dict[str, Any]] = []
+    for module in declared:
+        module_config: dict[str, Any] = {"path": module}
+        if module in restricted:
+            module_config["depends_on"] = [
+                other
+                for other in declared
+                if other != module and other not in restricted[module]
+            ]
+        if module in module_layers:
+            module_config["layer"] = module_layers[module]
+        modules.append(module_config)
+
+    used_layers = {
+        module_layers[module] for module in declared if module in module_layers
+    }
+    layers = [layer for layer in layers if layer in used_layers]
+
+    config = tomli.loads(dump_project_config_to_toml(ProjectConfig()))
+    config["source_roots"] = source_roots
+    if layers:
+        config["layers"] = layers
+    config["modules"] = modules
+    return ImportedConfig(
+        config_toml=tomli_w.dumps(config),
+        modules=declared,
+        layers=layers,
+        warnings=warnings,
+    )
+
+
+__all__ = ["ImportedConfig", "import_from_import_linter"]
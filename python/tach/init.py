@@ -9,8 +9,12 @@ from tach import errors
 from tach import filesystem as fs
 from tach.console import console
 from tach.constants import CONFIG_FILE_NAME, TOOL_NAME
-from tach.extension import ProjectConfig, parse_project_config, sync_project
-from tach.mod import mod_edit_interactive
+from tach.extension import (
+    ProjectConfig,
+    mod_edit_interactive,
+    parse_project_config,
+    sync_project,
+)
 from tach.show import upload_show_report
 
 if TYPE_CHECKING:
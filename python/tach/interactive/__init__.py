@@ -1,8 +0,0 @@
-from __future__ import annotations
-
-from tach.interactive.modules import (
-    InteractiveModuleConfiguration,
-    get_selected_modules_interactive,
-)
-
-__all__ = ["get_selected_modules_interactive", "InteractiveModuleConfiguration"]
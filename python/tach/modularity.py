@@ -13,8 +13,10 @@ from tach.colors import BCOLORS
 from tach.constants import GAUGE_API_BASE_URL
 from tach.errors import TachClosedBetaError, TachError
 from tach.extension import (
+    PathExclusions,
     ProjectConfig,
     check,
+    detect_import_side_effects,
     get_project_imports,
     into_usage_errors,
 )
@@ -138,9 +140,11 @@ class Module:
     interface_members: list[str] = field(default_factory=list)
     # [1.3] Adds 'depends_on'
     depends_on: list[Dependency] = field(default_factory=list)
+    # [1.5] Kinds of import-time side effects (e.g. 'network', 'heavy_import')
+    import_side_effects: list[str] = field(default_factory=list)
 
 
-REPORT_VERSION = "1.4"
+REPORT_VERSION = "1.5"
 
 
 @dataclass
@@ -192,6 +196,7 @@ class Report:
 def build_modules(
     project_config: ProjectConfig,
     included_paths: list[Path] | None = None,
+    import_side_effects: dict[str, list[str]] | None = None,
 ) -> list[Module]:
     config_modules = (
         project_config.filtered_modules(included_paths)
@@ -214,7 +219,11 @@ def build_modules(
                 has_interface = True
                 interface_members.update(interface.expose)
         dependencies = [
-            Dependency(path=dep.path, deprecated=dep.deprecated)
+            Dependency(
+                path=dep.path,
+                deprecated=dep.deprecated
+                or (dep.deprecated_until is not None and dep.deprecated_members is None),
+            )
             for dep in module.depends_on
             or []  # TODO: platform should differentiate None vs. []
         ]
@@ -224,17 +233,45 @@ def build_modules(
                 has_interface=has_interface,
                 interface_members=list(interface_members),
                 depends_on=dependencies,
+                import_side_effects=(import_side_effects or {}).get(module.path, []),
             )
         )
     return modules
 
 
+def build_import_side_effects(
+    project_root: Path,
+    project_config: ProjectConfig,
+) -> dict[str, list[str]]:
+    """Map each module path to the kinds of side effects detected on import."""
+    try:
+        side_effects = detect_import_side_effects(
+            project_root=project_root, project_config=project_config
+        )
+    except ValueError as e:
+        raise TachError(str(e))
+
+    kinds_by_module: dict[str, set[str]] = {}
+    for side_effect in side_effects:
+        if side_effect.module_path is None:
+            continue
+        kinds_by_module.setdefault(side_effect.module_path, set()).add(
+            side_effect.kind
+        )
+    return {
+        module_path: sorted(kinds) for module_path, kinds in kinds_by_module.items()
+    }
+
+
 def build_usages(
     project_root: Path,
     project_config: ProjectConfig,
     included_paths: list[Path] | None = None,
 ) -> list[Usage]:
     source_roots = [project_root / root for root in project_config.source_roots]
+    exclusions = PathExclusions(
+        project_root.resolve(), project_config.exclude, project_config.ignore
+    )
     modules = (
         project_config.filtered_modules(included_paths)
         if included_paths
@@ -258,12 +295,7 @@ def build_usages(
 
     usages: list[Usage] = []
     for source_root in source_roots:
-        for pyfile in fs.walk_pyfiles(
-            source_root,
-            project_root=project_root,
-            exclude_paths=project_config.exclude,
-            use_regex_matching=project_config.use_regex_matching,
-        ):
+        for pyfile in fs.walk_pyfiles(source_root, exclusions=exclusions):
             pyfile_mod_path = fs.file_to_module_path(
                 tuple(source_roots), source_root / pyfile
             )
@@ -301,9 +333,7 @@ def build_diagnostics(
     project_root: Path,
     project_config: ProjectConfig,
 ) -> list[UsageError]:
-    project_config.exclude = extend_and_validate(
-        None, project_config.exclude, project_config.use_regex_matching
-    )
+    project_config.exclude = extend_and_validate(None, project_config.exclude)
     check_diagnostics = check(
         project_root=project_root,
         project_config=project_config,
@@ -333,7 +363,10 @@ def generate_modularity_report(
         full_configuration=project_config.serialize_json(),
     )
 
-    report.modules = build_modules(project_config)
+    report.modules = build_modules(
+        project_config,
+        import_side_effects=build_import_side_effects(project_root, project_config),
+    )
     report.usages = build_usages(project_root, project_config)
     report.diagnostics = build_diagnostics(
         project_root=project_root,
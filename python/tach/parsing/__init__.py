@@ -4,6 +4,12 @@ from tach.parsing.config import (
     dump_project_config_to_toml,
     extend_and_validate,
     parse_project_config,
+    parse_project_config_file,
 )
 
-__all__ = ["parse_project_config", "dump_project_config_to_toml", "extend_and_validate"]
+__all__ = [
+    "parse_project_config",
+    "parse_project_config_file",
+    "dump_project_config_to_toml",
+    "extend_and_validate",
+]
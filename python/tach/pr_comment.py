@@ -0,0 +1,158 @@
+from __future__ import annotations
+
+import os
+import re
+from pathlib import Path
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from tach.extension import HistoryDiff, HistoryFinding
+
+# Bots can find their previous comment by this marker, and update it instead
+COMMENT_MARKER = "<!-- tach-pr-comment -->"
+
+# GitHub rejects comments longer than 65536 characters, so long lists are cut short
+MAX_LISTED_FINDINGS = 50
+
+MARKDOWN_SPECIAL_CHARACTERS = re.compile(r"([\\`*_\[\]<>|])")
+
+
+def escape_markdown(text: str) -> str:
+    return MARKDOWN_SPECIAL_CHARACTERS.sub(r"\\\1", text)
+
+
+def github_link_base(commit: str | None, project_root: Path) -> str | None:
+    """The URL of the project root at 'commit', when running in GitHub Actions."""
+    server = os.environ.get("GITHUB_SERVER_URL")
+    repository = os.environ.get("GITHUB_REPOSITORY")
+    if not (server and repository and commit):
+        return None
+    link_base = f"{server}/{repository}/blob/{commit}"
+    workspace = os.environ.get("GITHUB_WORKSPACE")
+    if workspace:
+        try:
+            prefix = project_root.resolve().relative_to(Path(workspace).resolve())
+        except ValueError:
+            return link_base
+        if prefix.parts:
+            link_base += "/" + prefix.as_posix()
+    return link_base
+
+
+def format_finding(finding: HistoryFinding, link_base: str | None) -> str:
+    severity = "**error**" if finding.severity == "error" else "warning"
+    if finding.file_path is None:
+        return f"- {severity}: {escape_markdown(finding.message)}"
+    location = finding.file_path
+    anchor = ""
+    if finding.line_number is not None:
+        location += f":{finding.line_number}"
+        anchor = f"#L{finding.line_number}"
+    location = f"`{location}`"
+    if link_base is not None:
+        location = f"[{location}]({link_base}/{finding.file_path}{anchor})"
+    return f"- {severity} {location}: {escape_markdown(finding.message)}"
+
+
+def format_findings(
+    title: str, findings: list[HistoryFinding], link_base: str | None
+) -> list[str]:
+    if not findings:
+        return []
+    lines = [f"### {title} ({len(findings)})", ""]
+    lines.extend(
+        format_finding(finding, link_base)
+        for finding in findings[:MAX_LISTED_FINDINGS]
+    )
+    if len(findings) > MAX_LISTED_FINDINGS:
+        lines.append(f"- ...and {len(findings) - MAX_LISTED_FINDINGS} more")
+    lines.append("")
+    return lines
+
+
+def format_change(base: float | None, head: float | None, unit: str = "") -> str:
+    if base is None or head is None:
+        return ""
+    change = round(head - base, 1)
+    if change == 0:
+        return "0"
+    return f"{change:+g}{unit}"
+
+
+def format_coverage(coverage: float | None) -> str:
+    return "-" if coverage is None else f"{coverage * 100:.1f}%"
+
+
+def render_pr_comment(
+    diff: HistoryDiff,
+    head_link_base: str | None = None,
+    base_link_base: str | None = None,
+) -> str:
+    """A markdown summary of the changes between two runs of 'tach check'."""
+    base, head = diff.base, diff.head
+    if diff.new:
+        suffix = "" if len(diff.new) == 1 else "s"
+        status = f":x: **{len(diff.new)} new violation{suffix}**"
+    else:
+        status = ":white_check_mark: **No new violations**"
+    if diff.fixed:
+        status += f", {len(diff.fixed)} fixed"
+
+    compared_with = f"run `{base.id}`"
+    if base.commit:
+        compared_with += f" (commit `{base.commit[:10]}`)"
+
+    def to_percent(coverage: float | None) -> float | None:
+        return None if coverage is None else coverage * 100
+
+    rows = [
+        (
+            "Errors",
+            str(base.errors),
+            str(head.errors),
+            format_change(base.errors, head.errors),
+        ),
+        (
+            "Warnings",
+            str(base.warnings),
+            str(head.warnings),
+            format_change(base.warnings, head.warnings),
+        ),
+        (
+            "Suppressions",
+            "-" if base.suppressions is None else str(base.suppressions),
+            "-" if head.suppressions is None else str(head.suppressions),
+            format_change(base.suppressions, head.suppressions),
+        ),
+        (
+            "Module coverage",
+            format_coverage(base.module_coverage),
+            format_coverage(head.module_coverage),
+            format_change(
+                to_percent(base.module_coverage),
+                to_percent(head.module_coverage),
+                unit=" pp",
+            ),
+        ),
+    ]
+
+    lines = [
+        COMMENT_MARKER,
+        "## Tach module boundaries",
+        "",
+        status,
+        "",
+        f"Compared with {compared_with}.",
+        "",
+        "| | Base | Head | Change |",
+        "| --- | ---: | ---: | ---: |",
+        *(f"| {' | '.join(row)} |" for row in rows),
+        "",
+    ]
+    lines.extend(format_findings("New violations", diff.new, head_link_base))
+    # Fixed violations link to the base commit, since their lines may no longer exist
+    lines.extend(format_findings("Fixed violations", diff.fixed, base_link_base))
+    return "\n".join(lines).rstrip() + "\n"
+
+
+__all__ = ["COMMENT_MARKER", "github_link_base", "render_pr_comment"]
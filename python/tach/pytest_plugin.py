@@ -10,7 +10,7 @@ from tach import filesystem as fs
 from tach.errors import TachSetupError
 from tach.extension import TachPytestPluginHandler
 from tach.filesystem.git_ops import get_changed_files
-from tach.parsing import parse_project_config
+from tach.parsing import parse_project_config_file
 
 
 class TachConfig(Protocol):
@@ -35,10 +35,11 @@ def pytest_addoption(parser: pytest.Parser):
 
 @pytest.hookimpl(tryfirst=True)
 def pytest_configure(config: TachConfig):
-    project_root = fs.find_project_config_root() or Path.cwd()
-    project_config = parse_project_config(root=project_root)
-    if project_config is None:
+    location = fs.resolve_project_location()
+    project_root = location.root
+    if location.config_path is None or not location.config_path.exists():
         raise TachSetupError("In Tach pytest plugin: No project config found")
+    project_config = parse_project_config_file(location.config_path)
 
     base = config.getoption("--tach-base")
     head = config.getoption("--tach-head")
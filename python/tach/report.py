@@ -1,18 +1,28 @@
 from __future__ import annotations
 
+import csv
+import html
+import io
 from dataclasses import dataclass
 from pathlib import Path
-from typing import TYPE_CHECKING
+from typing import TYPE_CHECKING, Any
 
 from tach import errors
 from tach.colors import BCOLORS
 from tach.extension import (
+    create_combined_dependency_report,
+    create_dependency_matrix,
+    create_dependency_records,
     create_dependency_report,
+    create_interface_usage_report,
+    detect_import_side_effects,
+    find_unused_interface_members,
+    find_unused_modules,
     get_external_imports,
 )
+from tach.extension import PathExclusions
 from tach.filesystem import walk_pyfiles
 from tach.utils.display import create_clickable_link
-from tach.utils.exclude import is_path_excluded
 from tach.utils.external import (
     get_package_name,
     is_stdlib_module,
@@ -20,7 +30,14 @@ from tach.utils.external import (
 )
 
 if TYPE_CHECKING:
-    from tach.extension import ProjectConfig
+    from tach.extension import (
+        DependencyMatrix,
+        DependencyRecord,
+        ImportSideEffect,
+        InterfaceUsageReport,
+        ProjectConfig,
+        UnusedInterfaceMembers,
+    )
 
 
 def report(
@@ -60,6 +77,243 @@ def report(
         raise errors.TachError(str(e))
 
 
+def combined_report(
+    project_root: Path,
+    paths: list[Path],
+    project_config: ProjectConfig,
+    include_dependency_modules: list[str] | None = None,
+    include_usage_modules: list[str] | None = None,
+    skip_dependencies: bool = False,
+    skip_usages: bool = False,
+    raw: bool = False,
+) -> str:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    for path in paths:
+        if not path.exists():
+            raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        return create_combined_dependency_report(
+            project_root=project_root,
+            project_config=project_config,
+            paths=[path.resolve().relative_to(project_root) for path in paths],
+            include_dependency_modules=include_dependency_modules,
+            include_usage_modules=include_usage_modules,
+            skip_dependencies=skip_dependencies,
+            skip_usages=skip_usages,
+            raw=raw,
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+
+DEPENDENCY_RECORD_FIELDS = [
+    "kind",
+    "file",
+    "line",
+    "source_module",
+    "target_module",
+    "import_path",
+]
+
+
+def dependency_records(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+    include_dependency_modules: list[str] | None = None,
+    include_usage_modules: list[str] | None = None,
+    skip_dependencies: bool = False,
+    skip_usages: bool = False,
+) -> list[DependencyRecord]:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    # Resolved in Python for the same reason as in 'report'
+    path = path.resolve().relative_to(project_root)
+    try:
+        return create_dependency_records(
+            project_root=project_root,
+            project_config=project_config,
+            path=path,
+            include_dependency_modules=include_dependency_modules,
+            include_usage_modules=include_usage_modules,
+            skip_dependencies=skip_dependencies,
+            skip_usages=skip_usages,
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+
+def dependency_record_to_dict(record: DependencyRecord) -> dict[str, Any]:
+    return {
+        "kind": record.kind,
+        "file": record.file_path.as_posix(),
+        "line": record.line_number,
+        "source_module": record.source_module,
+        "target_module": record.target_module,
+        "import_path": record.import_path,
+    }
+
+
+def render_dependency_records_csv(records: list[DependencyRecord]) -> str:
+    output = io.StringIO()
+    writer = csv.DictWriter(
+        output, fieldnames=DEPENDENCY_RECORD_FIELDS, lineterminator="\n"
+    )
+    writer.writeheader()
+    writer.writerows(dependency_record_to_dict(record) for record in records)
+    return output.getvalue()
+
+
+def dependency_matrix(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+) -> DependencyMatrix:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        return create_dependency_matrix(
+            project_root=project_root, project_config=project_config, path=path
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+
+def dependency_matrix_cell(matrix: DependencyMatrix, row: int, column: int) -> str:
+    """The text of a cell: '-' on the diagonal, empty without imports,
+    and marked with '!' when its imports violate the declared dependencies."""
+    if row == column:
+        return "-"
+    count = matrix.counts[row][column]
+    violation = "!" if matrix.violations[row][column] else ""
+    return f"{count}{violation}" if count or violation else ""
+
+
+def render_dependency_matrix(matrix: DependencyMatrix, path: Path) -> str:
+    if not matrix.modules:
+        return f"{BCOLORS.OKCYAN}No modules found in {BCOLORS.ENDC}{BCOLORS.OKGREEN}'{path}'.{BCOLORS.ENDC}"
+
+    size = len(matrix.modules)
+    # Modules are shown by their display names, while the CSV keeps their paths
+    labels = [f"{index} {label}" for index, label in enumerate(matrix.labels, start=1)]
+    label_width = max(len(label) for label in labels)
+    cell_width = max(
+        len(dependency_matrix_cell(matrix, row, column)) or 1
+        for row in range(size)
+        for column in range(size)
+    )
+    cell_width = max(cell_width, len(str(size)))
+
+    title = f"[ Dependency Matrix for '{path}' ]"
+    lines = [title, "-" * len(title)]
+    lines.append(
+        " " * label_width
+        + " | "
+        + " ".join(f"{index:>{cell_width}}" for index in range(1, size + 1))
+    )
+    for row, label in enumerate(labels):
+        cells: list[str] = []
+        for column in range(size):
+            cell = f"{dependency_matrix_cell(matrix, row, column) or '.':>{cell_width}}"
+            if matrix.violations[row][column]:
+                cell = f"{BCOLORS.FAIL}{cell}{BCOLORS.ENDC}"
+            cells.append(cell)
+        lines.append(f"{label:<{label_width}} | " + " ".join(cells))
+    lines.append(
+        f"{BCOLORS.OKCYAN}Each row counts the imports made by a module of the module in each column. "
+        f"Cells marked with '!' violate the declared dependencies.{BCOLORS.ENDC}"
+    )
+    return "\n".join(lines)
+
+
+def render_dependency_matrix_csv(matrix: DependencyMatrix) -> str:
+    output = io.StringIO()
+    writer = csv.writer(output, lineterminator="\n")
+    writer.writerow(["module", *matrix.modules])
+    for row, module in enumerate(matrix.modules):
+        writer.writerow(
+            [
+                module,
+                *(
+                    dependency_matrix_cell(matrix, row, column)
+                    for column in range(len(matrix.modules))
+                ),
+            ]
+        )
+    return output.getvalue()
+
+
+DEPENDENCY_MATRIX_HTML_STYLE = """
+body { font-family: sans-serif; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: center; }
+th.module { text-align: left; }
+td.diagonal { background: #eee; }
+td.violation { background: #f8d7da; color: #842029; font-weight: bold; }
+"""
+
+
+def render_dependency_matrix_html(matrix: DependencyMatrix, path: Path) -> str:
+    title = html.escape(f"Dependency Matrix for '{path}'")
+    header = "".join(
+        f'<th title="{html.escape(label)}">{index}</th>'
+        for index, label in enumerate(matrix.labels, start=1)
+    )
+    rows: list[str] = []
+    for row, source in enumerate(matrix.labels):
+        cells: list[str] = []
+        for column, target in enumerate(matrix.labels):
+            classes = []
+            if row == column:
+                classes.append("diagonal")
+            if matrix.violations[row][column]:
+                classes.append("violation")
+            cell = html.escape(dependency_matrix_cell(matrix, row, column))
+            tooltip = html.escape(
+                f"{source} -> {target}: {matrix.counts[row][column]} imports"
+            )
+            cells.append(
+                f'<td class="{" ".join(classes)}" title="{tooltip}">{cell}</td>'
+            )
+        rows.append(
+            f'<tr><th class="module">{row + 1} {html.escape(source)}</th>'
+            + "".join(cells)
+            + "</tr>"
+        )
+    return "\n".join(
+        [
+            "<!DOCTYPE html>",
+            '<html lang="en">',
+            f'<head><meta charset="utf-8"><title>{title}</title>',
+            f"<style>{DEPENDENCY_MATRIX_HTML_STYLE}</style></head>",
+            f"<body><h1>{title}</h1>",
+            "<p>Each row counts the imports made by a module of the module in each column. "
+            "Highlighted cells violate the declared dependencies.</p>",
+            f"<table><tr><th></th>{header}</tr>",
+            *rows,
+            "</table></body></html>",
+            "",
+        ]
+    )
+
+
 @dataclass
 class ExternalDependency:
     absolute_file_path: Path
@@ -163,11 +417,10 @@ def external_dependency_report(
     if not path.exists():
         raise errors.TachError(f"The path '{path}' does not exist.")
 
-    if project_config.exclude and is_path_excluded(
-        project_config.exclude,
-        path,
-        use_regex_matching=project_config.use_regex_matching,
-    ):
+    exclusions = PathExclusions(
+        project_root.resolve(), project_config.exclude, project_config.ignore
+    )
+    if exclusions.is_path_excluded(path.resolve()):
         raise errors.TachError(f"The path '{path}' is excluded.")
 
     source_roots = [
@@ -185,12 +438,7 @@ def external_dependency_report(
         return render_external_dependency_report(path, external_dependencies, raw=raw)
 
     all_external_dependencies: list[ExternalDependency] = []
-    for pyfile in walk_pyfiles(
-        path,
-        project_root=project_root,
-        exclude_paths=project_config.exclude,
-        use_regex_matching=project_config.use_regex_matching,
-    ):
+    for pyfile in walk_pyfiles(path, exclusions=exclusions):
         all_external_dependencies.extend(
             get_external_dependencies(
                 project_root=project_root,
@@ -204,4 +452,255 @@ def external_dependency_report(
     return render_external_dependency_report(path, all_external_dependencies, raw=raw)
 
 
-__all__ = ["report", "external_dependency_report"]
+def render_side_effect(side_effect: ImportSideEffect, project_root: Path) -> str:
+    clickable_link = create_clickable_link(
+        file_path=project_root / side_effect.file_path,
+        display_path=side_effect.file_path,
+        line=side_effect.line_number,
+    )
+    kind_label = side_effect.kind.replace("_", " ").capitalize()
+    module_label = (
+        f" (in '{side_effect.module_path}')" if side_effect.module_path else ""
+    )
+    return (
+        f"{BCOLORS.OKGREEN}{clickable_link}{BCOLORS.ENDC}{BCOLORS.OKCYAN}: "
+        f"{kind_label} '{side_effect.detail}'{module_label}{BCOLORS.ENDC}"
+    )
+
+
+def render_side_effect_report(
+    path: Path,
+    project_root: Path,
+    side_effects: list[ImportSideEffect],
+    raw: bool = False,
+) -> str:
+    if raw:
+        if not side_effects:
+            return ""
+        return "# Import-Time Side Effects\n" + "\n".join(
+            sorted(
+                {
+                    side_effect.module_path
+                    for side_effect in side_effects
+                    if side_effect.module_path
+                }
+            )
+        )
+
+    if not side_effects:
+        return f"{BCOLORS.OKCYAN}No import-time side effects found in {BCOLORS.ENDC}{BCOLORS.OKGREEN}'{path}'.{BCOLORS.ENDC}"
+
+    title = f"[ Import-Time Side Effects in '{path}' ]"
+    divider = "-" * len(title)
+    lines = [title, divider]
+    for side_effect in side_effects:
+        lines.append(render_side_effect(side_effect, project_root))
+    return "\n".join(lines)
+
+
+def side_effect_report(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+    raw: bool = False,
+) -> str:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        side_effects = detect_import_side_effects(
+            project_root=project_root, project_config=project_config
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+    absolute_path = path.resolve()
+    side_effects = [
+        side_effect
+        for side_effect in side_effects
+        if absolute_path == project_root / side_effect.file_path
+        or absolute_path in (project_root / side_effect.file_path).parents
+    ]
+    return render_side_effect_report(path, project_root, side_effects, raw=raw)
+
+
+def render_interface_usage_report(
+    path: Path, usage_report: InterfaceUsageReport, raw: bool = False
+) -> str:
+    if raw:
+        if not usage_report.members:
+            return ""
+        return "# Interface Usage\n" + "\n".join(
+            f"{member.member} {member.usages}" for member in usage_report.members
+        )
+
+    if not usage_report.members:
+        return f"{BCOLORS.OKCYAN}No exposed members found for {BCOLORS.ENDC}{BCOLORS.OKGREEN}'{usage_report.module_path}'.{BCOLORS.ENDC}"
+
+    title = f"[ Interface Usage of '{usage_report.module_path}' ]"
+    divider = "-" * len(title)
+    lines = [title, divider]
+    max_usages = max(member.usages for member in usage_report.members)
+    for member in usage_report.members:
+        if not member.usages:
+            lines.append(
+                f"{BCOLORS.WARNING}{member.member}: never used externally{BCOLORS.ENDC}"
+            )
+            continue
+        bar = "#" * max(1, round(20 * member.usages / max_usages))
+        usage_label = "usage" if member.usages == 1 else "usages"
+        lines.append(
+            f"{BCOLORS.OKGREEN}{member.member}{BCOLORS.ENDC}: {bar} "
+            f"{BCOLORS.OKCYAN}{member.usages} {usage_label} "
+            f"(from {', '.join(member.usage_modules)}){BCOLORS.ENDC}"
+        )
+    return "\n".join(lines)
+
+
+def interface_usage_report(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+    raw: bool = False,
+) -> str:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        usage_report = create_interface_usage_report(
+            project_root=project_root, project_config=project_config, path=path
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+    return render_interface_usage_report(path, usage_report, raw=raw)
+
+
+def render_unused_interface_members_report(
+    path: Path, unused_members: list[UnusedInterfaceMembers], raw: bool = False
+) -> str:
+    if raw:
+        if not unused_members:
+            return ""
+        return "# Unused Interface Members\n" + "\n".join(
+            f"{module.module_path} {member}"
+            for module in unused_members
+            for member in module.members
+        )
+
+    if not unused_members:
+        return f"{BCOLORS.OKCYAN}No unused interface members found in {BCOLORS.ENDC}{BCOLORS.OKGREEN}'{path}'.{BCOLORS.ENDC}"
+
+    title = f"[ Unused Interface Members in '{path}' ]"
+    divider = "-" * len(title)
+    lines = [title, divider]
+    for module in unused_members:
+        lines.append(f"{BCOLORS.OKGREEN}{module.module_path}{BCOLORS.ENDC}:")
+        lines.extend(
+            f"  {BCOLORS.WARNING}{member}{BCOLORS.ENDC}" for member in module.members
+        )
+    lines.append(
+        f"{BCOLORS.OKCYAN}These members are exposed by an interface, but not imported by any other module. "
+        f"Consider removing them from the interface.{BCOLORS.ENDC}"
+    )
+    return "\n".join(lines)
+
+
+def unused_interface_members_report(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+    raw: bool = False,
+) -> str:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        unused_members = find_unused_interface_members(
+            project_root=project_root, project_config=project_config, path=path
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+    return render_unused_interface_members_report(path, unused_members, raw=raw)
+
+
+def render_unused_modules_report(
+    path: Path, unused_modules: list[str], raw: bool = False
+) -> str:
+    if raw:
+        if not unused_modules:
+            return ""
+        return "# Unused Modules\n" + "\n".join(unused_modules)
+
+    if not unused_modules:
+        return f"{BCOLORS.OKCYAN}No unused modules found in {BCOLORS.ENDC}{BCOLORS.OKGREEN}'{path}'.{BCOLORS.ENDC}"
+
+    title = f"[ Unused Modules in '{path}' ]"
+    divider = "-" * len(title)
+    lines = [title, divider]
+    lines.extend(
+        f"{BCOLORS.WARNING}{module_path}{BCOLORS.ENDC}" for module_path in unused_modules
+    )
+    lines.append(
+        f"{BCOLORS.OKCYAN}These modules are not imported from anywhere else in the project. "
+        f"Add modules which are run directly to 'entrypoints' in your configuration.{BCOLORS.ENDC}"
+    )
+    return "\n".join(lines)
+
+
+def unused_modules_report(
+    project_root: Path,
+    path: Path,
+    project_config: ProjectConfig,
+    raw: bool = False,
+) -> str:
+    if not project_root.is_dir():
+        raise errors.TachSetupError(
+            f"The path '{project_root}' is not a valid directory."
+        )
+
+    if not path.exists():
+        raise errors.TachError(f"The path '{path}' does not exist.")
+
+    try:
+        unused_modules = find_unused_modules(
+            project_root=project_root, project_config=project_config, path=path
+        )
+    except ValueError as e:
+        raise errors.TachError(str(e))
+
+    return render_unused_modules_report(path, unused_modules, raw=raw)
+
+
+__all__ = [
+    "report",
+    "combined_report",
+    "dependency_records",
+    "dependency_record_to_dict",
+    "render_dependency_records_csv",
+    "dependency_matrix",
+    "render_dependency_matrix",
+    "render_dependency_matrix_csv",
+    "render_dependency_matrix_html",
+    "external_dependency_report",
+    "side_effect_report",
+    "interface_usage_report",
+    "unused_interface_members_report",
+    "unused_modules_report",
+]
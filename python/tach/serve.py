@@ -0,0 +1,242 @@
+from __future__ import annotations
+
+import json
+import threading
+from collections import Counter
+from http import HTTPStatus
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from pathlib import Path
+from typing import TYPE_CHECKING, Any
+from urllib.parse import parse_qs, unquote, urlparse
+
+from tach.modularity import (
+    build_diagnostics,
+    build_import_side_effects,
+    build_modules,
+    build_usages,
+)
+
+if TYPE_CHECKING:
+    from typing import Callable
+
+    from tach.extension import ProjectConfig
+
+
+STATIC_DIR = Path(__file__).parent / "static"
+CONTENT_TYPES = {
+    ".html": "text/html; charset=utf-8",
+    ".js": "text/javascript; charset=utf-8",
+    ".css": "text/css; charset=utf-8",
+}
+
+
+def build_graph(project_root: Path, project_config: ProjectConfig) -> dict[str, Any]:
+    """Build the module graph served by 'tach serve'.
+
+    Nodes are modules, and edges are declared dependencies along with any
+    undeclared dependencies which appear in violations.
+    """
+    modules = build_modules(
+        project_config=project_config,
+        import_side_effects=build_import_side_effects(project_root, project_config),
+    )
+    usages = build_usages(project_root=project_root, project_config=project_config)
+    violations = build_diagnostics(
+        project_root=project_root, project_config=project_config
+    )
+    config_modules = {module.path: module for module in project_config.all_modules()}
+
+    usage_counts = Counter(
+        (usage.containing_module_path, usage.module_path) for usage in usages
+    )
+    violation_counts = Counter(
+        (violation.usage_module, violation.definition_module)
+        for violation in violations
+    )
+    module_violation_counts = Counter(
+        violation.usage_module for violation in violations
+    )
+
+    nodes: list[dict[str, Any]] = []
+    edges: dict[tuple[str, str], dict[str, Any]] = {}
+    for module in modules:
+        config_module = config_modules.get(module.path)
+        nodes.append(
+            {
+                "id": module.path,
+                "label": config_module.label() if config_module else module.path,
+                "layer": config_module.layer if config_module else None,
+                "stability": config_module.stability if config_module else None,
+                "utility": config_module.utility if config_module else False,
+                "has_interface": module.has_interface,
+                "interface_members": sorted(module.interface_members),
+                "import_side_effects": module.import_side_effects,
+                "violations": module_violation_counts[module.path],
+            }
+        )
+        config_dependencies = {
+            dependency.path: dependency
+            for dependency in (config_module.depends_on if config_module else None)
+            or []
+        }
+        for dependency in module.depends_on:
+            config_dependency = config_dependencies.get(dependency.path)
+            edges[(module.path, dependency.path)] = {
+                "source": module.path,
+                "target": dependency.path,
+                "declared": True,
+                "deprecated": dependency.deprecated,
+                "reason": config_dependency.reason if config_dependency else None,
+                "ticket": config_dependency.ticket if config_dependency else None,
+            }
+
+    for source, target in violation_counts:
+        edges.setdefault(
+            (source, target),
+            {
+                "source": source,
+                "target": target,
+                "declared": False,
+                "deprecated": False,
+                "reason": None,
+                "ticket": None,
+            },
+        )
+    for (source, target), edge in edges.items():
+        edge["usages"] = usage_counts[(source, target)]
+        edge["violations"] = violation_counts[(source, target)]
+
+    node_ids = {node["id"] for node in nodes}
+    return {
+        "nodes": nodes,
+        # Edges to modules outside the graph (e.g. the root module) are dropped
+        "edges": [
+            edge
+            for edge in edges.values()
+            if edge["source"] in node_ids and edge["target"] in node_ids
+        ],
+        "violations": [
+            {
+                "file": violation.file,
+                "line_number": violation.line_number,
+                "member": violation.member,
+                "usage_module": violation.usage_module,
+                "definition_module": violation.definition_module,
+                "error_type": violation.error_type,
+            }
+            for violation in violations
+        ],
+    }
+
+
+def module_details(graph: dict[str, Any], module_path: str) -> dict[str, Any] | None:
+    node = next((node for node in graph["nodes"] if node["id"] == module_path), None)
+    if node is None:
+        return None
+    return {
+        **node,
+        "dependencies": [
+            edge for edge in graph["edges"] if edge["source"] == module_path
+        ],
+        "dependents": [edge for edge in graph["edges"] if edge["target"] == module_path],
+        "violations": [
+            violation
+            for violation in graph["violations"]
+            if module_path in (violation["usage_module"], violation["definition_module"])
+        ],
+    }
+
+
+class GraphState:
+    """Holds the computed graph, which is rebuilt on request."""
+
+    def __init__(self, project_root: Path, project_config: ProjectConfig):
+        self.project_root = project_root
+        self.project_config = project_config
+        self._graph: dict[str, Any] | None = None
+        self._lock = threading.Lock()
+
+    def graph(self, refresh: bool = False) -> dict[str, Any]:
+        with self._lock:
+            if self._graph is None or refresh:
+                self._graph = build_graph(self.project_root, self.project_config)
+            return self._graph
+
+
+def make_handler(state: GraphState) -> type[BaseHTTPRequestHandler]:
+    class GraphRequestHandler(BaseHTTPRequestHandler):
+        def log_message(self, format: str, *args: Any) -> None:
+            # Keep the terminal quiet while exploring
+            pass
+
+        def send_json(self, data: Any, status: HTTPStatus = HTTPStatus.OK) -> None:
+            body = json.dumps(data).encode("utf-8")
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def send_static(self, name: str) -> None:
+            file_path = (STATIC_DIR / name).resolve()
+            if (
+                file_path.parent != STATIC_DIR.resolve()
+                or file_path.suffix not in CONTENT_TYPES
+                or not file_path.is_file()
+            ):
+                self.send_json({"error": "Not found"}, HTTPStatus.NOT_FOUND)
+                return
+            body = file_path.read_bytes()
+            self.send_response(HTTPStatus.OK)
+            self.send_header("Content-Type", CONTENT_TYPES[file_path.suffix])
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def do_GET(self) -> None:
+            url = urlparse(self.path)
+            query = parse_qs(url.query)
+            try:
+                if url.path == "/api/graph":
+                    refresh = query.get("refresh", ["false"])[0] in ("1", "true")
+                    self.send_json(state.graph(refresh=refresh))
+                elif url.path.startswith("/api/modules/"):
+                    module_path = unquote(url.path[len("/api/modules/") :])
+                    details = module_details(state.graph(), module_path)
+                    if details is None:
+                        self.send_json(
+                            {"error": f"Module '{module_path}' not found"},
+                            HTTPStatus.NOT_FOUND,
+                        )
+                    else:
+                        self.send_json(details)
+                elif url.path == "/":
+                    self.send_static("index.html")
+                else:
+                    self.send_static(url.path.lstrip("/"))
+            except Exception as e:
+                self.send_json({"error": str(e)}, HTTPStatus.INTERNAL_SERVER_ERROR)
+
+    return GraphRequestHandler
+
+
+def serve(
+    project_root: Path,
+    project_config: ProjectConfig,
+    host: str,
+    port: int,
+    on_ready: Callable[[str], None] | None = None,
+) -> None:
+    state = GraphState(project_root, project_config)
+    # Build the graph up front, so that configuration errors surface immediately
+    state.graph()
+    server = ThreadingHTTPServer((host, port), make_handler(state))
+    try:
+        if on_ready is not None:
+            on_ready(f"http://{host}:{server.server_address[1]}")
+        server.serve_forever()
+    finally:
+        server.server_close()
+
+
+__all__ = ["build_graph", "module_details", "serve"]
@@ -7,11 +7,13 @@ from typing import TYPE_CHECKING
 from urllib import error, request
 
 from tach.constants import GAUGE_API_BASE_URL
+from tach.extension import build_module_graph
 from tach.modularity import (
     Module,
     Usage,
     UsageError,
     build_diagnostics,
+    build_import_side_effects,
     build_modules,
     build_usages,
 )
@@ -21,7 +23,7 @@ if TYPE_CHECKING:
 
     import pydot  # type: ignore
 
-    from tach.extension import ProjectConfig
+    from tach.extension import ModuleConfig, ModuleGraph, ProjectConfig
 
 
 @dataclass
@@ -37,7 +39,9 @@ def generate_show_report(
     included_paths: list[Path],
 ) -> ShowReport:
     modules = build_modules(
-        project_config=project_config, included_paths=included_paths
+        project_config=project_config,
+        included_paths=included_paths,
+        import_side_effects=build_import_side_effects(project_root, project_config),
     )
     usages = build_usages(
         project_root=project_root,
@@ -80,30 +84,108 @@ def upload_show_report(
         return None
 
 
+def graph_label(module: ModuleConfig) -> str:
+    """The label of a module in generated graphs, including its declared stability."""
+    if module.stability is None:
+        return module.label()
+    return f"{module.label()} [{module.stability}]"
+
+
+def collapse_module_path(path: str, depth: int | None) -> str:
+    """Collapse a module path to at most 'depth' segments (e.g. 'a.b.c' -> 'a.b' at depth 2)."""
+    if depth is None or path.startswith("<"):
+        return path
+    return ".".join(path.split(".")[:depth])
+
+
+def build_graph(
+    project_root: Path,
+    project_config: ProjectConfig,
+    included_paths: list[Path],
+    focus: str | None = None,
+    focus_depth: int = 1,
+    depth: int | None = None,
+    only_violations: bool = False,
+    exclude_utilities: bool = False,
+) -> ModuleGraph:
+    """Build the module graph drawn by 'tach show', reduced by the given filters.
+
+    Violations are only computed when they are needed to filter the graph,
+    since this requires checking the whole project.
+    """
+    violations: list[tuple[str, str]] = []
+    if only_violations:
+        violations = [
+            (diagnostic.usage_module, diagnostic.definition_module)
+            for diagnostic in build_diagnostics(
+                project_root=project_root, project_config=project_config
+            )
+        ]
+    return build_module_graph(
+        project_config,
+        included_paths,
+        violations=violations,
+        focus=focus,
+        focus_depth=focus_depth,
+        depth=depth,
+        only_violations=only_violations,
+        exclude_utilities=exclude_utilities,
+    )
+
+
+def collapsed_import_side_effects(
+    import_side_effects: dict[str, list[str]] | None, depth: int | None
+) -> dict[str, list[str]]:
+    collapsed: dict[str, list[str]] = {}
+    for path, side_effects in (import_side_effects or {}).items():
+        node_side_effects = collapsed.setdefault(collapse_module_path(path, depth), [])
+        node_side_effects.extend(
+            side_effect
+            for side_effect in side_effects
+            if side_effect not in node_side_effects
+        )
+    return collapsed
+
+
 def generate_module_graph_dot_file(
     project_config: ProjectConfig,
     output_filepath: Path,
     included_paths: list[Path],
+    import_side_effects: dict[str, list[str]] | None = None,
+    depth: int | None = None,
+    graph: ModuleGraph | None = None,
 ) -> None:
     # Local import because networkx takes about ~100ms to load
     import networkx as nx
 
-    graph = nx.DiGraph()  # type: ignore
-
-    def upsert_edge(graph: nx.DiGraph, module: str, dependency: str) -> None:  # type: ignore
-        if module not in graph:
-            graph.add_node(module)  # type: ignore
-        if dependency not in graph:
-            graph.add_node(dependency)  # type: ignore
-        graph.add_edge(module, dependency)  # type: ignore
-
-    modules = project_config.filtered_modules(included_paths)
+    module_graph = graph or build_module_graph(
+        project_config, included_paths, depth=depth
+    )
+    labels = {
+        module.path: graph_label(module) for module in project_config.all_modules()
+    }
+    side_effects = collapsed_import_side_effects(import_side_effects, depth)
 
-    for module in modules:
-        for dependency in module.depends_on or []:
-            upsert_edge(graph, module.path, dependency.path)  # type: ignore
+    dot_graph = nx.DiGraph()  # type: ignore
+    for node in module_graph.nodes:
+        dot_graph.add_node(node)  # type: ignore
+        if node in labels and labels[node] != node:
+            dot_graph.nodes[node]["label"] = f'"{labels[node]}"'  # type: ignore
+        if node in side_effects:
+            # Highlight modules which run risky or slow code when imported
+            dot_graph.nodes[node]["color"] = "orange"  # type: ignore
+            dot_graph.nodes[node]["tooltip"] = (  # type: ignore
+                f'"Import-time side effects: {", ".join(side_effects[node])}"'
+            )
+    for edge in module_graph.edges:
+        attributes: dict[str, str] = {}
+        if not edge.declared:
+            attributes["color"] = "red"
+        elif edge.deprecated:
+            attributes["style"] = "dashed"
+        dot_graph.add_edge(edge.source, edge.target, **attributes)  # type: ignore
 
-    pydot_graph: pydot.Dot = nx.nx_pydot.to_pydot(graph)  # type: ignore
+    pydot_graph: pydot.Dot = nx.nx_pydot.to_pydot(dot_graph)  # type: ignore
     dot_data: str = pydot_graph.to_string()  # type: ignore
 
     output_filepath.write_text(dot_data)  # type: ignore
@@ -113,25 +195,159 @@ def generate_module_graph_mermaid(
     project_config: ProjectConfig,
     output_filepath: Path,
     included_paths: list[Path],
+    import_side_effects: dict[str, list[str]] | None = None,
+    depth: int | None = None,
+    graph: ModuleGraph | None = None,
 ) -> None:
-    modules = project_config.filtered_modules(included_paths)
+    module_graph = graph or build_module_graph(
+        project_config, included_paths, depth=depth
+    )
+    labels = {
+        module.path: graph_label(module) for module in project_config.all_modules()
+    }
+    side_effects = collapsed_import_side_effects(import_side_effects, depth)
+
+    def mermaid_node(path: str) -> str:
+        node_id = path.strip("<>")
+        label = labels.get(path, path)
+        if label == path:
+            return node_id
+        return f'{node_id}["{label}"]'
+
     edges: list[str] = []
-    isolated: list[str] = []
-    for module in modules:
-        for dependency in module.depends_on or []:
-            edges.append(
-                f"    {module.path.strip('<>')} --> {dependency.path.strip('<>')}"
-            )
-        if not module.depends_on:
-            isolated.append(f"    {module.path.strip('<>')}")
+    connected: set[str] = set()
+    for edge in module_graph.edges:
+        if not edge.declared:
+            arrow = "-- undeclared -->"
+        elif edge.deprecated:
+            arrow = "-.->"
+        else:
+            arrow = "-->"
+        edges.append(
+            f"    {mermaid_node(edge.source)} {arrow} {mermaid_node(edge.target)}"
+        )
+        connected.update((edge.source, edge.target))
+    isolated = [
+        f"    {mermaid_node(node)}"
+        for node in module_graph.nodes
+        if node not in connected
+    ]
 
     mermaid_graph = "graph TD\n" + "\n".join(edges) + "\n" + "\n".join(isolated)
 
+    side_effect_nodes = sorted(
+        {node.strip("<>") for node in module_graph.nodes if node in side_effects}
+    )
+    if side_effect_nodes:
+        # Highlight modules which run risky or slow code when imported
+        mermaid_graph += (
+            "\n    classDef importSideEffects stroke:#f0ad4e,stroke-width:2px"
+            f"\n    class {','.join(side_effect_nodes)} importSideEffects"
+        )
+
     output_filepath.write_text(mermaid_graph)
 
 
+def d2_string(value: str) -> str:
+    escaped = value.replace("\\", "\\\\").replace('"', '\\"')
+    return f'"{escaped}"'
+
+
+def generate_module_graph_d2(
+    project_config: ProjectConfig,
+    output_filepath: Path,
+    included_paths: list[Path],
+    import_side_effects: dict[str, list[str]] | None = None,
+    depth: int | None = None,
+    graph: ModuleGraph | None = None,
+) -> None:
+    module_graph = graph or build_module_graph(
+        project_config, included_paths, depth=depth
+    )
+    labels = {
+        module.path: graph_label(module) for module in project_config.all_modules()
+    }
+    side_effects = collapsed_import_side_effects(import_side_effects, depth)
+
+    # Keys are quoted, since D2 otherwise treats dots in a key as nesting
+    lines: list[str] = ["direction: down", ""]
+    for node in module_graph.nodes:
+        label = labels.get(node, node)
+        if node in side_effects:
+            # Highlight modules which run risky or slow code when imported
+            tooltip = f"Import-time side effects: {', '.join(side_effects[node])}"
+            lines.extend(
+                [
+                    f"{d2_string(node)}: {d2_string(label)} {{",
+                    '  style.stroke: "#f0ad4e"',
+                    "  style.stroke-width: 3",
+                    f"  tooltip: {d2_string(tooltip)}",
+                    "}",
+                ]
+            )
+        else:
+            lines.append(f"{d2_string(node)}: {d2_string(label)}")
+    lines.append("")
+    for edge in module_graph.edges:
+        connection = f"{d2_string(edge.source)} -> {d2_string(edge.target)}"
+        if not edge.declared:
+            connection += ': undeclared {style.stroke: "#cf222e"}'
+        elif edge.deprecated:
+            connection += ": deprecated {style.stroke-dash: 3}"
+        lines.append(connection)
+
+    output_filepath.write_text("\n".join(lines) + "\n")
+
+
+def plantuml_alias(path: str) -> str:
+    return "".join(char if char.isalnum() else "_" for char in path)
+
+
+def generate_module_graph_plantuml(
+    project_config: ProjectConfig,
+    output_filepath: Path,
+    included_paths: list[Path],
+    import_side_effects: dict[str, list[str]] | None = None,
+    depth: int | None = None,
+    graph: ModuleGraph | None = None,
+) -> None:
+    module_graph = graph or build_module_graph(
+        project_config, included_paths, depth=depth
+    )
+    labels = {
+        module.path: graph_label(module) for module in project_config.all_modules()
+    }
+    side_effects = collapsed_import_side_effects(import_side_effects, depth)
+
+    lines: list[str] = ["@startuml"]
+    for node in module_graph.nodes:
+        label = labels.get(node, node).replace('"', "'")
+        component = f'component "{label}" as {plantuml_alias(node)}'
+        if node in side_effects:
+            # Highlight modules which run risky or slow code when imported
+            component += " #f0ad4e"
+        lines.append(component)
+    for edge in module_graph.edges:
+        # Red arrows mark undeclared dependencies, dotted arrows deprecated ones
+        if not edge.declared:
+            arrow = "-[#red]->"
+        elif edge.deprecated:
+            arrow = "..>"
+        else:
+            arrow = "-->"
+        lines.append(
+            f"{plantuml_alias(edge.source)} {arrow} {plantuml_alias(edge.target)}"
+        )
+    lines.append("@enduml")
+
+    output_filepath.write_text("\n".join(lines) + "\n")
+
+
 __all__ = [
     "upload_show_report",
+    "build_graph",
     "generate_module_graph_dot_file",
     "generate_module_graph_mermaid",
+    "generate_module_graph_d2",
+    "generate_module_graph_plantuml",
 ]
@@ -0,0 +1,240 @@
+// Interactive module graph for 'tach serve'.
+// The layout is a simple force simulation, so no external libraries are needed.
+"use strict";
+
+const SVG_NS = "http://www.w3.org/2000/svg";
+const svg = document.getElementById("graph");
+const details = document.getElementById("details");
+const search = document.getElementById("search");
+const violationsOnly = document.getElementById("violations-only");
+const status = document.getElementById("status");
+
+let graph = { nodes: [], edges: [], violations: [] };
+let positions = new Map();
+let selected = null;
+let view = { x: 0, y: 0, scale: 1 };
+
+function el(tag, attributes = {}, parent = null) {
+  const element = document.createElementNS(SVG_NS, tag);
+  for (const [key, value] of Object.entries(attributes)) {
+    element.setAttribute(key, value);
+  }
+  if (parent) parent.appendChild(element);
+  return element;
+}
+
+function layout(nodes, edges) {
+  const width = svg.clientWidth || 800;
+  const height = svg.clientHeight || 600;
+  const points = new Map();
+  nodes.forEach((node, index) => {
+    const previous = positions.get(node.id);
+    const angle = (2 * Math.PI * index) / Math.max(nodes.length, 1);
+    points.set(node.id, previous ? { ...previous } : {
+      x: width / 2 + (width / 3) * Math.cos(angle),
+      y: height / 2 + (height / 3) * Math.sin(angle),
+    });
+  });
+
+  const ideal = Math.sqrt((width * height) / Math.max(nodes.length, 1)) * 0.8;
+  for (let iteration = 0; iteration < 300; iteration++) {
+    const cooling = 1 - iteration / 300;
+    const forces = new Map(nodes.map((node) => [node.id, { x: 0, y: 0 }]));
+    for (let i = 0; i < nodes.length; i++) {
+      for (let j = i + 1; j < nodes.length; j++) {
+        const a = points.get(nodes[i].id);
+        const b = points.get(nodes[j].id);
+        const dx = a.x - b.x || 0.01;
+        const dy = a.y - b.y || 0.01;
+        const distance = Math.max(Math.hypot(dx, dy), 1);
+        const repulsion = (ideal * ideal) / distance;
+        forces.get(nodes[i].id).x += (dx / distance) * repulsion;
+        forces.get(nodes[i].id).y += (dy / distance) * repulsion;
+        forces.get(nodes[j].id).x -= (dx / distance) * repulsion;
+        forces.get(nodes[j].id).y -= (dy / distance) * repulsion;
+      }
+    }
+    for (const edge of edges) {
+      const a = points.get(edge.source);
+      const b = points.get(edge.target);
+      if (!a || !b) continue;
+      const dx = a.x - b.x;
+      const dy = a.y - b.y;
+      const distance = Math.max(Math.hypot(dx, dy), 1);
+      const attraction = (distance * distance) / ideal;
+      forces.get(edge.source).x -= (dx / distance) * attraction;
+      forces.get(edge.source).y -= (dy / distance) * attraction;
+      forces.get(edge.target).x += (dx / distance) * attraction;
+      forces.get(edge.target).y += (dy / distance) * attraction;
+    }
+    for (const node of nodes) {
+      const point = points.get(node.id);
+      const force = forces.get(node.id);
+      const magnitude = Math.max(Math.hypot(force.x, force.y), 1);
+      const step = Math.min(magnitude, ideal * cooling);
+      point.x += (force.x / magnitude) * step;
+      point.y += (force.y / magnitude) * step;
+      // Gentle pull towards the center keeps disconnected modules in view
+      point.x += (width / 2 - point.x) * 0.01;
+      point.y += (height / 2 - point.y) * 0.01;
+    }
+  }
+  positions = points;
+}
+
+function visibleGraph() {
+  const query = search.value.trim().toLowerCase();
+  const nodes = graph.nodes.filter(
+    (node) =>
+      (!query || node.id.toLowerCase().includes(query) || node.label.toLowerCase().includes(query)) &&
+      (!violationsOnly.checked || node.violations > 0)
+  );
+  const ids = new Set(nodes.map((node) => node.id));
+  const edges = graph.edges.filter((edge) => ids.has(edge.source) && ids.has(edge.target));
+  return { nodes, edges };
+}
+
+function render() {
+  const { nodes, edges } = visibleGraph();
+  layout(nodes, edges);
+  svg.replaceChildren();
+  const defs = el("defs", {}, svg);
+  const marker = el("marker", {
+    id: "arrow", viewBox: "0 0 10 10", refX: "18", refY: "5",
+    markerWidth: "6", markerHeight: "6", orient: "auto-start-reverse",
+  }, defs);
+  el("path", { d: "M 0 0 L 10 5 L 0 10 z", fill: "#8c959f" }, marker);
+
+  const root = el("g", { id: "viewport" }, svg);
+  applyView(root);
+
+  const neighbours = new Set();
+  if (selected) {
+    neighbours.add(selected);
+    for (const edge of edges) {
+      if (edge.source === selected) neighbours.add(edge.target);
+      if (edge.target === selected) neighbours.add(edge.source);
+    }
+  }
+
+  for (const edge of edges) {
+    const a = positions.get(edge.source);
+    const b = positions.get(edge.target);
+    const classes = ["edge"];
+    if (!edge.declared) classes.push("undeclared");
+    if (edge.deprecated) classes.push("deprecated");
+    if (selected && edge.source !== selected && edge.target !== selected) classes.push("faded");
+    const line = el("line", {
+      class: classes.join(" "), x1: a.x, y1: a.y, x2: b.x, y2: b.y, "marker-end": "url(#arrow)",
+    }, root);
+    el("title", {}, line).textContent =
+      `${edge.source} → ${edge.target}: ${edge.usages} imports, ${edge.violations} violations`;
+  }
+
+  for (const node of nodes) {
+    const point = positions.get(node.id);
+    const classes = ["node"];
+    if (node.violations > 0) classes.push("violations");
+    if (node.import_side_effects.length > 0) classes.push("side-effects");
+    if (node.id === selected) classes.push("selected");
+    if (selected && !neighbours.has(node.id)) classes.push("faded");
+    const group = el("g", { class: classes.join(" "), transform: `translate(${point.x},${point.y})` }, root);
+    el("circle", { r: 6 + Math.min(node.violations, 10) }, group);
+    el("text", { x: 10, y: 4 }, group).textContent = node.label;
+    group.addEventListener("click", (event) => {
+      event.stopPropagation();
+      selectModule(node.id);
+    });
+  }
+  status.textContent = `${nodes.length} modules, ${edges.length} dependencies, ${graph.violations.length} violations`;
+}
+
+function applyView(root = document.getElementById("viewport")) {
+  if (root) root.setAttribute("transform", `translate(${view.x},${view.y}) scale(${view.scale})`);
+}
+
+function list(title, items, format) {
+  const section = document.createElement("section");
+  const heading = document.createElement("h3");
+  heading.textContent = `${title} (${items.length})`;
+  section.appendChild(heading);
+  const ul = document.createElement("ul");
+  for (const item of items) {
+    const li = document.createElement("li");
+    li.textContent = format(item);
+    ul.appendChild(li);
+  }
+  section.appendChild(ul);
+  return section;
+}
+
+async function selectModule(modulePath) {
+  selected = modulePath;
+  render();
+  const response = await fetch(`/api/modules/${encodeURIComponent(modulePath)}`);
+  const module = await response.json();
+  details.replaceChildren();
+  const heading = document.createElement("h2");
+  heading.textContent = module.label === module.id ? module.id : `${module.label} (${module.id})`;
+  details.appendChild(heading);
+  const metadata = document.createElement("p");
+  metadata.textContent = [
+    module.layer && `Layer: ${module.layer}`,
+    module.stability && `Stability: ${module.stability}`,
+    module.utility && "Utility module",
+    module.has_interface && `Interface: ${module.interface_members.join(", ") || "(empty)"}`,
+    module.import_side_effects.length > 0 && `Import side effects: ${module.import_side_effects.join(", ")}`,
+  ].filter(Boolean).join(" · ");
+  details.appendChild(metadata);
+  const describeEdge = (other) => (edge) => {
+    const annotation = [edge.reason, edge.ticket].filter(Boolean).join(", ");
+    return `${edge[other]}${edge.declared ? "" : " (undeclared)"}${edge.deprecated ? " (deprecated)" : ""} - ${edge.usages} imports${annotation ? ` - ${annotation}` : ""}`;
+  };
+  details.appendChild(list("Dependencies", module.dependencies, describeEdge("target")));
+  details.appendChild(list("Dependents", module.dependents, describeEdge("source")));
+  details.appendChild(list("Violations", module.violations, (violation) =>
+    `${violation.file}:${violation.line_number} ${violation.error_type.toLowerCase()} '${violation.member}' (${violation.usage_module} → ${violation.definition_module})`
+  ));
+}
+
+async function load(refresh = false) {
+  status.textContent = "Loading...";
+  const response = await fetch(`/api/graph${refresh ? "?refresh=true" : ""}`);
+  graph = await response.json();
+  if (graph.error) {
+    status.textContent = graph.error;
+    return;
+  }
+  render();
+}
+
+let drag = null;
+svg.addEventListener("mousedown", (event) => {
+  drag = { x: event.clientX - view.x, y: event.clientY - view.y };
+});
+window.addEventListener("mousemove", (event) => {
+  if (!drag) return;
+  view.x = event.clientX - drag.x;
+  view.y = event.clientY - drag.y;
+  applyView();
+});
+window.addEventListener("mouseup", () => {
+  drag = null;
+});
+svg.addEventListener("wheel", (event) => {
+  event.preventDefault();
+  const factor = event.deltaY < 0 ? 1.1 : 1 / 1.1;
+  view.x = event.offsetX - (event.offsetX - view.x) * factor;
+  view.y = event.offsetY - (event.offsetY - view.y) * factor;
+  view.scale *= factor;
+  applyView();
+});
+svg.addEventListener("click", () => {
+  selected = null;
+  render();
+});
+search.addEventListener("input", render);
+violationsOnly.addEventListener("change", render);
+document.getElementById("refresh").addEventListener("click", () => load(true));
+
+load();
@@ -27,7 +27,19 @@ def is_stdlib_module(module: str) -> bool:
         return in_stdlib(module)  # type: ignore
 
 
-def get_stdlib_modules() -> list[str]:
+def get_stdlib_modules(python_version: str | None = None) -> list[str]:
+    """Modules in the standard library of 'python_version' (e.g. '3.8'), or of the running interpreter."""
+    if python_version is not None:
+        from stdlib_list import stdlib_list  # type: ignore
+
+        try:
+            versioned_modules: set[str] = set(stdlib_list(python_version))  # type: ignore
+        except ValueError:
+            # Versions unknown to stdlib-list fall back to the running interpreter
+            return get_stdlib_modules()
+        versioned_modules.update(KNOWN_MODULE_SPECIAL_CASES)
+        return sorted(versioned_modules)
+
     if sys.version_info >= (3, 10):
         modules = set(sys.builtin_module_names)
         modules.update(sys.stdlib_module_names)
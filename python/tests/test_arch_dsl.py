@@ -0,0 +1,97 @@
+from __future__ import annotations
+
+import pytest
+
+from tach import cli
+from tach.arch_dsl import compile_architecture
+from tach.errors import TachConfigError
+from tach.parsing.config import parse_project_config_file
+
+ARCHITECTURE = """\
+source_roots: src
+layers: web -> service -> data
+layer web: myapp.api
+layer service: myapp.orders, myapp.billing
+layer data: myapp.db
+
+# Each group of modules depends on the group to its right
+myapp.api -> myapp.orders, myapp.billing -> myapp.db
+domain myapp.payments: api -> models
+utility: myapp.utils
+forbid: *.api -> *.db unless *.repository
+"""
+
+
+def test_compile_architecture(tmp_path):
+    (tmp_path / "arch.dsl").write_text(ARCHITECTURE)
+    config_path = tmp_path / "tach.toml"
+    config_path.write_text('exclude = ["**/tests"]\nsource_roots = ["."]\n')
+
+    compiled = compile_architecture(tmp_path, tmp_path / "arch.dsl", config_path)
+
+    assert compiled.layers == ["web", "service", "data"]
+    assert compiled.modules == [
+        "myapp.api",
+        "myapp.billing",
+        "myapp.db",
+        "myapp.orders",
+        "myapp.payments.api",
+        "myapp.payments.models",
+        "myapp.utils",
+    ]
+    assert compiled.config_toml.startswith("# Modules, layers and forbid rules")
+
+    config_path.write_text(compiled.config_toml)
+    project_config = parse_project_config_file(config_path)
+    # Settings which are not generated are kept
+    assert project_config.exclude == ["**/tests"]
+    assert [str(root) for root in project_config.source_roots] == ["src"]
+    modules = {module.path: module for module in project_config.all_modules()}
+    assert modules["myapp.api"].layer == "web"
+    assert [dependency.path for dependency in modules["myapp.api"].depends_on] == [
+        "myapp.billing",
+        "myapp.orders",
+    ]
+    assert [dependency.path for dependency in modules["myapp.orders"].depends_on] == [
+        "myapp.db"
+    ]
+    assert [
+        dependency.path for dependency in modules["myapp.payments.api"].depends_on
+    ] == ["myapp.payments.models"]
+    assert modules["myapp.db"].depends_on == []
+    assert modules["myapp.utils"].utility
+    assert len(project_config.forbid) == 1
+    assert project_config.forbid[0].unless_through == "*.repository"
+
+
+@pytest.mark.parametrize(
+    "architecture,error",
+    [
+        ("layer web: myapp.api\n", "arch.dsl:1: Layer 'web' is not declared"),
+        ("myapp.api -> my-app.db\n", "arch.dsl:1: 'my-app.db' is not a valid"),
+        ("layers: a\nlayers: b\n", "arch.dsl:2: 'layers' may only be declared"),
+        ("modules: myapp.api\n", "arch.dsl:1: Unknown statement 'modules'"),
+    ],
+)
+def test_compile_architecture_errors(tmp_path, architecture, error):
+    (tmp_path / "arch.dsl").write_text(architecture)
+    with pytest.raises(TachConfigError, match=error):
+        compile_architecture(tmp_path, tmp_path / "arch.dsl", tmp_path / "tach.toml")
+
+
+def test_compile_command(tmp_path, monkeypatch, capsys):
+    monkeypatch.chdir(tmp_path)
+    (tmp_path / "arch.dsl").write_text(ARCHITECTURE)
+
+    # The config does not exist yet, so it is out of date
+    with pytest.raises(SystemExit):
+        cli.main(["compile", "arch.dsl", "--check"])
+    cli.main(["compile", "arch.dsl"])
+    assert "Wrote 7 module(s), 3 layer(s) and 1 forbid rule(s)" in (
+        capsys.readouterr().out
+    )
+    cli.main(["compile", "arch.dsl", "--check"])
+
+    (tmp_path / "arch.dsl").write_text(ARCHITECTURE + "myapp.cli -> myapp.api\n")
+    with pytest.raises(SystemExit):
+        cli.main(["compile", "arch.dsl", "--check"])
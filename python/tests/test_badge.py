@@ -0,0 +1,42 @@
+from __future__ import annotations
+
+import json
+import xml.etree.ElementTree as ET
+
+import pytest
+
+from tach.badge import Badge
+
+
+@pytest.mark.parametrize(
+    "violations,message,color",
+    [
+        (0, "passing", "brightgreen"),
+        (1, "1 violation", "red"),
+        (12, "12 violations", "red"),
+    ],
+)
+def test_badge_from_violation_count(violations, message, color):
+    badge = Badge.from_violation_count(violations)
+    assert badge == Badge(label="architecture", message=message, color=color)
+
+
+def test_badge_json():
+    badge = Badge.from_violation_count(0, label="boundaries")
+    assert json.loads(badge.to_json()) == {
+        "schemaVersion": 1,
+        "label": "boundaries",
+        "message": "passing",
+        "color": "brightgreen",
+    }
+
+
+def test_badge_svg():
+    badge = Badge(label="a<b", message="2 violations", color="red")
+    svg = ET.fromstring(badge.to_svg())
+    namespace = "{http://www.w3.org/2000/svg}"
+    assert svg.find(f"{namespace}title").text == "a<b: 2 violations"
+    texts = [text.text for text in svg.iter(f"{namespace}text")]
+    assert texts == ["a<b", "a<b", "2 violations", "2 violations"]
+    fills = [rect.get("fill") for rect in svg.iter(f"{namespace}rect")]
+    assert "#e05d44" in fills
@@ -1,13 +1,16 @@
 from __future__ import annotations
 
 import json
+import shutil
+from pathlib import Path
 from unittest.mock import NonCallableMagicMock
 
 import pytest
 
+from tach import extension
 from tach.cli import tach_check, tach_check_external
 from tach.errors import TachCircularDependencyError, TachVisibilityError
-from tach.extension import Diagnostic
+from tach.extension import Diagnostic, ProjectConfig
 from tach.icons import FAIL, SUCCESS, WARNING
 from tach.parsing.config import parse_project_config
 
@@ -342,6 +345,533 @@ def test_many_features_example_dir(example_dir, capfd):
     _check_expected_messages_unordered(dependencies_section, expected_dependencies)
 
 
+def _load_example(example_dir: Path, name: str) -> tuple[Path, ProjectConfig]:
+    project_root = example_dir / name
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+    return project_root, project_config
+
+
+def _check(
+    project_root: Path, project_config: ProjectConfig, **kwargs
+) -> list[Diagnostic]:
+    return extension.check(
+        project_root=project_root,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+        **kwargs,
+    )
+
+
+def test_sharded_check_matches_full_check(example_dir):
+    project_root, project_config = _load_example(example_dir, "many_features")
+
+    def as_json(diagnostics):
+        return sorted(
+            json.dumps(diagnostic, sort_keys=True)
+            for diagnostic in json.loads(
+                extension.serialize_diagnostics_json(diagnostics)
+            )
+        )
+
+    shard_outputs = [
+        extension.serialize_diagnostics_json(
+            _check(project_root, project_config, shard=f"{index}/3")
+        )
+        for index in range(1, 4)
+    ]
+    merged = extension.merge_check_shards(
+        project_root=project_root,
+        project_config=project_config,
+        outputs=shard_outputs,
+    )
+    assert as_json(merged) == as_json(_check(project_root, project_config))
+
+
+def test_check_order_is_stable(example_dir):
+    project_root, project_config = _load_example(example_dir, "many_features")
+
+    diagnostics = _check(project_root, project_config)
+    full_output = extension.serialize_diagnostics_json(diagnostics)
+    for _ in range(3):
+        assert (
+            extension.serialize_diagnostics_json(_check(project_root, project_config))
+            == full_output
+        )
+
+    shard_outputs = [
+        extension.serialize_diagnostics_json(
+            _check(project_root, project_config, shard=f"{index}/3")
+        )
+        for index in range(1, 4)
+    ]
+    for outputs in (shard_outputs, shard_outputs[::-1]):
+        merged = extension.merge_check_shards(
+            project_root=project_root,
+            project_config=project_config,
+            outputs=outputs,
+        )
+        assert extension.serialize_diagnostics_json(merged) == full_output
+
+    # Located diagnostics are sorted by the components of their path, then line
+    located = [
+        (Path(diagnostic.pyfile_path()).parts, diagnostic.pyline_number())
+        for diagnostic in diagnostics
+        if diagnostic.pyfile_path() is not None
+    ]
+    assert located == sorted(located)
+
+
+def test_check_json_schema_versions(example_dir):
+    project_root, project_config = _load_example(example_dir, "many_features")
+
+    diagnostics = _check(project_root, project_config)
+    current = json.loads(
+        extension.serialize_diagnostics_json(
+            diagnostics, schema_version=extension.OUTPUT_SCHEMA_VERSION
+        )
+    )
+    legacy = json.loads(
+        extension.serialize_diagnostics_json(diagnostics, schema_version=1)
+    )
+    assert current["schema_version"] == extension.OUTPUT_SCHEMA_VERSION
+    assert current["diagnostics"] == legacy
+
+    # Shards written in either layout can be merged
+    def merge(output):
+        return extension.serialize_diagnostics_json(
+            extension.merge_check_shards(
+                project_root=project_root,
+                project_config=project_config,
+                outputs=[json.dumps(output)],
+            )
+        )
+
+    assert merge(current) == merge(legacy)
+
+    with pytest.raises(ValueError):
+        extension.serialize_diagnostics_json(
+            diagnostics, schema_version=extension.OUTPUT_SCHEMA_VERSION + 1
+        )
+
+
+def test_check_invalid_shard(example_dir):
+    project_root, project_config = _load_example(example_dir, "valid")
+
+    with pytest.raises(ValueError):
+        _check(project_root, project_config, shard="4/3")
+
+
+def test_check_reports_progress(example_dir):
+    project_root, project_config = _load_example(example_dir, "many_features")
+
+    updates = []
+    diagnostics = _check(project_root, project_config, progress=updates.append)
+
+    assert diagnostics
+    # The final update is always reported, once every file has been checked
+    assert updates[-1].total_files > 0
+    assert updates[-1].checked_files == updates[-1].total_files
+
+
+def test_check_cancelled_by_progress_callback(example_dir):
+    project_root, project_config = _load_example(example_dir, "many_features")
+
+    def cancel(_progress):
+        raise RuntimeError("cancelled")
+
+    with pytest.raises(RuntimeError, match="cancelled"):
+        _check(project_root, project_config, progress=cancel)
+
+
+def test_import_style_rules(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[rules]
+wildcard_imports = "error"
+relative_imports = "warn"
+
+[[modules]]
+path = "a"
+depends_on = []
+
+[[modules]]
+path = "legacy"
+depends_on = []
+rules = { wildcard_imports = "off", conditional_imports = "error" }
+"""
+    )
+    for module in ("a", "legacy"):
+        (tmp_path / module).mkdir()
+        (tmp_path / module / "__init__.py").write_text(
+            "from os.path import *\nfrom . import helpers\n\nif DEBUG:\n    import pdb\n"
+        )
+        (tmp_path / module / "helpers.py").write_text("")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    found = sorted(
+        (
+            diagnostic["Located"]["file_path"].split("/")[0],
+            next(iter(diagnostic["Located"]["details"]["Code"])),
+            diagnostic["Located"]["severity"],
+        )
+        for diagnostic in diagnostics
+        if "Located" in diagnostic
+    )
+    assert found == [
+        ("a", "RelativeImport", "Warning"),
+        ("a", "WildcardImport", "Error"),
+        ("legacy", "ConditionalImport", "Error"),
+        ("legacy", "RelativeImport", "Warning"),
+    ]
+
+
+def test_platform_imports(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "app"
+depends_on = ["winsupport"]
+
+[[modules]]
+path = "winsupport"
+depends_on = []
+platforms = ["win32"]
+"""
+    )
+    (tmp_path / "app").mkdir()
+    (tmp_path / "app" / "__init__.py").write_text(
+        "import sys\n"
+        "import winsupport\n\n"
+        "if sys.platform == 'win32':\n"
+        "    from winsupport import registry\n"
+        "elif sys.platform == 'linux':\n"
+        "    from winsupport import compat\n"
+    )
+    (tmp_path / "winsupport").mkdir()
+    (tmp_path / "winsupport" / "__init__.py").write_text("registry = compat = None\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    assert sorted(
+        diagnostic.pyline_number()
+        for diagnostic in diagnostics
+        if "is only available on 'win32'" in diagnostic.to_string()
+    ) == [2, 7]
+
+
+
+def test_domain_boundaries(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "app"
+depends_on = ["billing", "billing.api", "billing.ledger"]
+"""
+    )
+    (tmp_path / "app").mkdir()
+    (tmp_path / "app" / "__init__.py").write_text(
+        "from billing.api import charge\nfrom billing.ledger import entries\n"
+    )
+    (tmp_path / "billing" / "api").mkdir(parents=True)
+    (tmp_path / "billing" / "ledger").mkdir()
+    (tmp_path / "billing" / "__init__.py").write_text("")
+    (tmp_path / "billing" / "api" / "__init__.py").write_text(
+        "from billing.ledger import entries\ncharge = None\n"
+    )
+    (tmp_path / "billing" / "ledger" / "__init__.py").write_text("entries = None\n")
+    (tmp_path / "billing" / "tach.domain.toml").write_text(
+        """
+name = "Billing"
+interface_modules = ["api"]
+
+[root]
+depends_on = []
+
+[[modules]]
+path = "api"
+depends_on = ["ledger"]
+
+[[modules]]
+path = "ledger"
+depends_on = []
+"""
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    # Imports within the domain, and of its interface modules, are allowed
+    violations = [
+        diagnostic
+        for diagnostic in diagnostics
+        if "is internal to domain 'Billing'" in diagnostic.to_string()
+    ]
+    assert len(violations) == 1
+    assert violations[0].pyfile_path() == "app/__init__.py"
+    assert violations[0].pyline_number() == 2
+    assert "may only import 'billing.api'" in violations[0].to_string()
+
+
+def test_declarative_rules(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "app.api"
+depends_on = ["app.db", "app.services", "app.repository"]
+
+[[modules]]
+path = "app.services"
+depends_on = ["app.db"]
+
+[[modules]]
+path = "app.repository"
+depends_on = ["app.db"]
+
+[[modules]]
+path = "app.db"
+depends_on = []
+
+[[modules]]
+path = "app.Legacy"
+depends_on = []
+
+[[forbid]]
+from = "*.api"
+to = "*.db"
+unless_through = "*.repository"
+
+[[naming]]
+modules = "app.*"
+pattern = "^[a-z_]+$"
+severity = "warn"
+"""
+    )
+    (tmp_path / "app").mkdir()
+    (tmp_path / "app" / "__init__.py").write_text("")
+    for module in ["services", "repository", "db", "Legacy"]:
+        (tmp_path / "app" / module).mkdir()
+    (tmp_path / "app" / "api.py").write_text(
+        "from app.db import session\n"
+        "from app.services import orders\n"
+        "from app.repository import users\n"
+    )
+    (tmp_path / "app" / "services" / "__init__.py").write_text(
+        "from app.db import session\norders = None\n"
+    )
+    (tmp_path / "app" / "repository" / "__init__.py").write_text(
+        "from app.db import session\nusers = None\n"
+    )
+    (tmp_path / "app" / "db" / "__init__.py").write_text("session = None\n")
+    (tmp_path / "app" / "Legacy" / "__init__.py").write_text("")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    # The direct dependency, and the one which bypasses the repository, are forbidden
+    located = sorted(
+        (diagnostic.pyline_number(), diagnostic.to_string())
+        for diagnostic in diagnostics
+        if diagnostic.pyfile_path() == "app/api.py"
+    )
+    assert [line for line, _ in located] == [1, 2]
+    assert "(forbid '*.api' -> '*.db' unless through '*.repository')" in located[0][1]
+    assert "'app.api' -> 'app.services' -> 'app.db'" in located[1][1]
+
+    naming = [
+        diagnostic
+        for diagnostic in diagnostics
+        if "does not match the naming pattern" in diagnostic.to_string()
+    ]
+    assert len(naming) == 1
+    assert "'app.Legacy'" in naming[0].to_string()
+    assert not naming[0].is_error()
+
+
+def test_custom_plugin(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["src"]
+
+[[modules]]
+path = "app"
+depends_on = ["legacy"]
+
+[[modules]]
+path = "legacy"
+depends_on = []
+
+[[plugins.custom]]
+name = "no-legacy"
+path = "tach_plugins:NoLegacy"
+severity = "warn"
+options = { banned = ["legacy"] }
+"""
+    )
+    (tmp_path / "tach_plugins.py").write_text(
+        """
+class NoLegacy:
+    def __init__(self, options):
+        self.banned = options["banned"]
+        self.files = set()
+
+    def on_import(self, file_path, module_path, import_path, line_number):
+        if import_path.split(".")[0] in self.banned:
+            self.files.add(file_path)
+            return f"Do not import '{import_path}' from '{module_path}'."
+
+    def on_finish(self):
+        return [f"Legacy code is imported in {len(self.files)} file(s)."]
+"""
+    )
+    (tmp_path / "src" / "app").mkdir(parents=True)
+    (tmp_path / "src" / "legacy").mkdir()
+    (tmp_path / "src" / "app" / "__init__.py").write_text(
+        "import os\nfrom legacy import db\n"
+    )
+    (tmp_path / "src" / "legacy" / "__init__.py").write_text("db = None\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    violations = [
+        diagnostic
+        for diagnostic in diagnostics
+        if diagnostic.to_string().startswith("[no-legacy]")
+    ]
+    assert [violation.to_string() for violation in violations] == [
+        "[no-legacy] Do not import 'legacy.db' from 'app'.",
+        "[no-legacy] Legacy code is imported in 1 file(s).",
+    ]
+    assert violations[0].pyfile_path() == "src/app/__init__.py"
+    assert violations[0].pyline_number() == 2
+    assert all(not violation.is_error() for violation in violations)
+
+
+
+def test_check_with_max_memory(example_dir, tmp_path):
+    # Parsed imports are written to '.tach' in the project
+    project_root = tmp_path / "many_features"
+    shutil.copytree(example_dir / "many_features", project_root)
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    def check(**kwargs):
+        return sorted(
+            (diagnostic.pyfile_path() or "", diagnostic.to_string())
+            for diagnostic in _check(project_root, project_config, **kwargs)
+        )
+
+    # The smallest budget checks one file at a time
+    assert check(max_memory=1) == check()
+
+
+def test_check_records_history(example_dir, tmp_path, capfd):
+    project_root = tmp_path / "many_features"
+    shutil.copytree(example_dir / "many_features", project_root)
+    config_path = project_root / "tach.toml"
+    config_path.write_text("record_history = true\n" + config_path.read_text())
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+    assert project_config.record_history
+
+    for _ in range(2):
+        with pytest.raises(SystemExit):
+            tach_check(project_root=project_root, project_config=project_config)
+    capfd.readouterr()
+
+    runs = extension.load_check_history(project_root)
+    assert len(runs) == 2
+    assert runs[0].errors > 0
+    assert runs[0].findings == runs[1].findings
+
+    # Identical runs have no new or fixed findings
+    diff = extension.diff_check_history(project_root)
+    assert diff.base.id == runs[0].id
+    assert diff.head.id == runs[1].id
+    assert diff.new == []
+    assert diff.fixed == []
+
+    with pytest.raises(ValueError):
+        extension.find_check_history_run(project_root, "~2")
+
+
+def test_check_pr_comment(example_dir, tmp_path, capfd):
+    project_root = tmp_path / "many_features"
+    shutil.copytree(example_dir / "many_features", project_root)
+    config_path = project_root / "tach.toml"
+    config_path.write_text("record_history = true\n" + config_path.read_text())
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    with pytest.raises(SystemExit):
+        tach_check(project_root=project_root, project_config=project_config)
+    capfd.readouterr()
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_check(
+            project_root=project_root,
+            project_config=project_config,
+            output_format="pr-comment",
+        )
+    assert exc_info.value.code == 1
+    comment = capfd.readouterr().out
+    assert comment.startswith("<!-- tach-pr-comment -->")
+    assert ":white_check_mark: **No new violations**" in comment
+
+    # The compared run is recorded afterwards, and becomes the base of the next comparison
+    runs = extension.load_check_history(project_root)
+    assert len(runs) == 2
+    assert runs[1].suppressions is not None
+    assert f"Compared with run `{runs[0].id}`" in comment
+
+
 def test_many_features_example_dir__external(example_dir, capfd):
     project_root = example_dir / "many_features"
     project_config = parse_project_config(root=project_root)
@@ -386,3 +916,505 @@ def test_many_features_example_dir__external(example_dir, capfd):
 
     _check_expected_messages_unordered(general_section, expected_general)
     _check_expected_messages_unordered(external_section, expected_external)
+
+
+def test_main_block_imports(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[rules]
+main_block_imports = "warn"
+cli_patterns = ["scripts/*.py"]
+
+[[modules]]
+path = "app"
+depends_on = []
+
+[[modules]]
+path = "billing"
+depends_on = []
+
+[[modules]]
+path = "legacy"
+depends_on = []
+rules = { main_block_imports = "off" }
+
+[[modules]]
+path = "scripts"
+depends_on = []
+"""
+    )
+    for module in ("app", "billing", "scripts"):
+        (tmp_path / module).mkdir()
+        (tmp_path / module / "__init__.py").write_text("")
+    main_block = 'from billing import charge\n\nif __name__ == "__main__":\n    from billing import refund\n'
+    (tmp_path / "app" / "main.py").write_text(main_block)
+    (tmp_path / "legacy").mkdir()
+    (tmp_path / "legacy" / "__init__.py").write_text(main_block)
+    (tmp_path / "scripts" / "manage.py").write_text("from billing import charge\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    # Imports under '__main__' and in files matching 'cli_patterns' use 'main_block_imports',
+    # which 'legacy' turns off for itself
+    found = sorted(
+        (
+            diagnostic["Located"]["file_path"],
+            diagnostic["Located"]["line_number"],
+            diagnostic["Located"]["severity"],
+        )
+        for diagnostic in diagnostics
+        if "Located" in diagnostic
+    )
+    assert found == [
+        ("app/main.py", 1, "Error"),
+        ("app/main.py", 4, "Warning"),
+        ("legacy/__init__.py", 1, "Error"),
+        ("scripts/manage.py", 1, "Warning"),
+    ]
+
+
+def test_partly_constant_string_imports(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+include_string_imports = true
+
+[rules]
+function_imports = "warn"
+
+[[modules]]
+path = "app"
+depends_on = []
+
+[[modules]]
+path = "pkg"
+depends_on = []
+"""
+    )
+    for module in ("app", "pkg"):
+        (tmp_path / module).mkdir()
+        (tmp_path / module / "__init__.py").write_text("")
+    (tmp_path / "pkg" / "plugin.py").write_text("")
+    (tmp_path / "app" / "main.py").write_text(
+        "import importlib\n"
+        "\n"
+        'importlib.import_module(f"pkg.{name}")\n'
+        "\n"
+        "\n"
+        "def load(name):\n"
+        '    return importlib.import_module(f"pkg.{name}")\n'
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    # The constant prefix names 'pkg', which is checked like an import of it,
+    # and the import within a function uses 'function_imports'
+    found = sorted(
+        (
+            diagnostic.pyfile_path(),
+            diagnostic.pyline_number(),
+            diagnostic.is_error(),
+            diagnostic.definition_module(),
+        )
+        for diagnostic in _check(tmp_path, project_config)
+        if diagnostic.pyfile_path() is not None
+    )
+    assert found == [
+        ("app/main.py", 3, True, "pkg"),
+        ("app/main.py", 7, False, "pkg"),
+    ]
+
+
+def test_check_dependency_members(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "a"
+depends_on = [{ path = "b", members = ["allowed"] }]
+
+[[modules]]
+path = "b"
+depends_on = []
+"""
+    )
+    (tmp_path / "a").mkdir()
+    (tmp_path / "a" / "__init__.py").write_text(
+        "from b import allowed\nfrom b import forbidden\n"
+    )
+    (tmp_path / "b").mkdir()
+    (tmp_path / "b" / "__init__.py").write_text("allowed = 1\nforbidden = 2\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    assert len(diagnostics) == 1
+    details = diagnostics[0]["Located"]["details"]
+    assert details["Code"]["RestrictedMemberDependency"] == {
+        "dependency": "b.forbidden",
+        "usage_module": "a",
+        "definition_module": "b",
+        "allowed_members": "'allowed'",
+    }
+
+
+def test_interface_conformance(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "core"
+depends_on = []
+
+[[modules]]
+path = "adapters.stripe"
+depends_on = ["core"]
+implements = "core.ports.PaymentPort"
+
+[[modules]]
+path = "adapters.paypal"
+depends_on = ["core"]
+implements = "core.ports.PaymentPort"
+"""
+    )
+    (tmp_path / "core").mkdir()
+    (tmp_path / "core" / "__init__.py").write_text("")
+    (tmp_path / "core" / "ports.py").write_text(
+        "class PaymentPort:\n"
+        "    def charge(self, amount, customer_id): ...\n"
+        "    def refund(self, charge_id): ...\n"
+    )
+    (tmp_path / "adapters").mkdir()
+    (tmp_path / "adapters" / "__init__.py").write_text("")
+    (tmp_path / "adapters" / "stripe.py").write_text(
+        "def charge(amount, customer_id, idempotency_key=None): ...\n"
+        "def refund(charge_id): ...\n"
+    )
+    (tmp_path / "adapters" / "paypal.py").write_text(
+        "import core\n\ndef charge(amount): ...\n"
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    messages = [
+        diagnostic.to_string()
+        for diagnostic in diagnostics
+        if "core.ports.PaymentPort" in diagnostic.to_string()
+    ]
+    assert len(messages) == 2
+    assert any(
+        "'charge' in module 'adapters.paypal'" in message
+        and "expected parameters (amount, customer_id), found (amount)" in message
+        for message in messages
+    )
+    assert any(
+        "Module 'adapters.paypal' implements 'core.ports.PaymentPort', but does not "
+        "define 'refund'" in message
+        for message in messages
+    )
+
+
+def test_root_module_treatment(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+root_module = "dependenciesonly"
+
+[[modules]]
+path = "app"
+depends_on = []
+"""
+    )
+    (tmp_path / "app").mkdir()
+    (tmp_path / "app" / "__init__.py").write_text("from scripts import run\n")
+    (tmp_path / "scripts.py").write_text("from app import main\nrun = None\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    details = sorted(
+        (
+            diagnostic["Located"]["file_path"],
+            next(iter(diagnostic["Located"]["details"]["Code"])),
+        )
+        for diagnostic in diagnostics
+        if "Located" in diagnostic
+    )
+    # The root module may declare its own dependencies, but no module may depend on it
+    assert details == [
+        ("app/__init__.py", "ForbiddenRootModuleDependency"),
+        ("scripts.py", "UndeclaredDependency"),
+    ]
+
+
+def test_check_deprecated_members(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "a"
+depends_on = [{ path = "b", deprecated_members = ["old_client"] }]
+
+[[modules]]
+path = "b"
+depends_on = []
+"""
+    )
+    (tmp_path / "a").mkdir()
+    (tmp_path / "a" / "__init__.py").write_text(
+        "from b import new_client\nfrom b import old_client\n"
+    )
+    (tmp_path / "b").mkdir()
+    (tmp_path / "b" / "__init__.py").write_text("new_client = 1\nold_client = 2\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = extension.check(
+        project_root=tmp_path,
+        project_config=project_config,
+        dependencies=True,
+        interfaces=True,
+    )
+
+    assert len(diagnostics) == 1
+    assert diagnostics[0].is_warning()
+    assert diagnostics[0].is_deprecated()
+    assert diagnostics[0].deprecated_member() == "b.old_client"
+
+
+def test_utility_dependencies(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "app"
+depends_on = []
+
+[[modules]]
+path = "utils"
+depends_on = []
+utility = true
+
+[[modules]]
+path = "helpers"
+depends_on = []
+utility = true
+"""
+    )
+    (tmp_path / "app.py").write_text("from utils import slugify\n")
+    (tmp_path / "utils.py").write_text(
+        "from helpers import strip\nfrom app import main\nslugify = None\n"
+    )
+    (tmp_path / "helpers.py").write_text("strip = None\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    # Utilities may be used without being declared, but may only use other utilities
+    details = [
+        next(iter(diagnostic["Located"]["details"]["Code"].items()))
+        for diagnostic in diagnostics
+        if "Located" in diagnostic
+    ]
+    assert (
+        "UtilityDependency",
+        {
+            "dependency": "app.main",
+            "usage_module": "utils",
+            "definition_module": "app",
+        },
+    ) in details
+    assert all(
+        value.get("usage_module") != "app"
+        and value.get("definition_module") != "helpers"
+        for _, value in details
+    )
+
+
+def test_conftest_imports(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[rules]
+conftest_imports = "warn"
+
+[[modules]]
+path = "app"
+depends_on = []
+
+[[modules]]
+path = "billing"
+depends_on = []
+
+[[modules]]
+path = "tests"
+depends_on = []
+"""
+    )
+    for module in ("app", "billing"):
+        (tmp_path / module).mkdir()
+        (tmp_path / module / "__init__.py").write_text("")
+    (tmp_path / "app" / "main.py").write_text("from billing import charge\n")
+    (tmp_path / "tests").mkdir()
+    (tmp_path / "tests" / "__init__.py").write_text("")
+    (tmp_path / "tests" / "conftest.py").write_text(
+        "from app import main\nfrom billing import charge\n"
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    diagnostics = json.loads(
+        extension.serialize_diagnostics_json(
+            extension.check(
+                project_root=tmp_path,
+                project_config=project_config,
+                dependencies=True,
+                interfaces=True,
+            )
+        )
+    )
+
+    # Imports in conftest.py use 'conftest_imports', while other files are unaffected
+    found = sorted(
+        (
+            diagnostic["Located"]["file_path"],
+            diagnostic["Located"]["severity"],
+        )
+        for diagnostic in diagnostics
+        if "Located" in diagnostic
+    )
+    assert found == [
+        ("app/main.py", "Error"),
+        ("tests/conftest.py", "Warning"),
+        ("tests/conftest.py", "Warning"),
+    ]
+
+
+def test_check_config_with_unsaved_edits(example_dir):
+    project_root = example_dir / "valid"
+    config_path = project_root / "tach.toml"
+    original = config_path.read_text()
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    def dependency_errors():
+        return [
+            diagnostic
+            for diagnostic in extension.check_config(
+                project_root=project_root, project_config=project_config
+            )
+            if diagnostic.is_dependency_error() and diagnostic.is_error()
+        ]
+
+    assert dependency_errors() == []
+
+    # The edit is only enqueued, but it is still applied to the config which is checked
+    project_config.remove_dependency("domain_two", "domain_three")
+    errors = dependency_errors()
+    assert errors
+    assert all(error.usage_module() == "domain_two" for error in errors)
+    assert config_path.read_text() == original
+
+    project_config.add_dependency("domain_two", "domain_three")
+    assert dependency_errors() == []
+
+
+def test_check_file(example_dir):
+    project_root = example_dir / "valid"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+    file_path = project_root / "domain_three" / "__init__.py"
+
+    assert (
+        extension.check_file(
+            project_root, project_config, file_path, file_path.read_text()
+        )
+        == []
+    )
+
+    # 'domain_three' has no dependencies, so the unsaved import is reported
+    diagnostics = extension.check_file(
+        project_root, project_config, file_path, "from domain_two import x\n"
+    )
+    assert [
+        (diagnostic.pyfile_path(), diagnostic.pyline_number())
+        for diagnostic in diagnostics
+        if diagnostic.is_error()
+    ] == [("domain_three/__init__.py", 1)]
+
+
+def test_check_file_reports_columns(example_dir):
+    project_root = example_dir / "valid"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+    file_path = project_root / "domain_three" / "__init__.py"
+
+    diagnostics = extension.check_file(
+        project_root, project_config, file_path, "from domain_two import x\n"
+    )
+    # The span covers the imported name 'x'
+    assert [
+        (
+            diagnostic.pyline_number(),
+            diagnostic.column(),
+            diagnostic.end_line_number(),
+            diagnostic.end_column(),
+        )
+        for diagnostic in diagnostics
+        if diagnostic.is_error()
+    ] == [(1, 24, 1, 25)]
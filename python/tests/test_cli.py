@@ -1,5 +1,6 @@
 from __future__ import annotations
 
+import json
 import pathlib
 from pathlib import Path
 from unittest.mock import Mock
@@ -7,7 +8,9 @@ from unittest.mock import Mock
 import pytest
 
 from tach import cli
+from tach import filesystem as fs
 from tach.extension import ProjectConfig
+from tach.parsing import parse_project_config_file
 
 _VALID_TACH_TOML = pathlib.Path(__file__).parent / "example" / "valid" / "tach.toml"
 
@@ -21,10 +24,10 @@ def mock_check(mocker) -> Mock:
 
 @pytest.fixture
 def mock_project_config(mocker) -> ProjectConfig:
-    def _mock_project_config(root: str = "") -> ProjectConfig:
+    def _mock_project_config(file_path: Path | None = None) -> ProjectConfig:
         return ProjectConfig()
 
-    mocker.patch("tach.cli.parse_project_config", _mock_project_config)
+    mocker.patch("tach.cli.parse_project_config_file", _mock_project_config)
     return _mock_project_config()
 
 
@@ -86,3 +89,169 @@ def test_tach_server_with_config(tmp_path, mocker):
     # Verify server was run with the custom config.
     mock_run_server.assert_called_once()
     assert "domain_four.py" in mock_run_server.call_args[0][1].exclude
+
+
+def test_timings(capfd):
+    with pytest.raises(SystemExit):
+        cli.main(["check", "--config", str(_VALID_TACH_TOML), "--quiet", "--timings"])
+    captured = capfd.readouterr()
+    assert "Timings" in captured.err
+    assert "build_module_tree" in captured.err
+    assert "check_file" in captured.err
+
+
+def test_profile(tmp_path):
+    profile_path = tmp_path / "profile.json"
+    with pytest.raises(SystemExit):
+        cli.main(
+            [
+                "check",
+                "--config",
+                str(_VALID_TACH_TOML),
+                "--quiet",
+                "--profile",
+                str(profile_path),
+            ]
+        )
+    profile = json.loads(profile_path.read_text())
+    check_file_events = [
+        event for event in profile["traceEvents"] if event["name"] == "check_file"
+    ]
+    assert check_file_events
+    assert all(event["ph"] == "X" for event in check_file_events)
+    assert all("path" in event["args"] for event in check_file_events)
+
+
+def test_resolve_project_location(tmp_path, monkeypatch):
+    project_root = tmp_path / "project"
+    (project_root / "src").mkdir(parents=True)
+    config_path = project_root / "tach.toml"
+    config_path.write_text("")
+    monkeypatch.chdir(project_root / "src")
+    monkeypatch.delenv(fs.CONFIG_PATH_ENV_VAR, raising=False)
+    monkeypatch.delenv(fs.PROJECT_ROOT_ENV_VAR, raising=False)
+
+    # Searches upwards from the current directory
+    location = fs.resolve_project_location()
+    assert location.root == project_root.resolve()
+    assert location.config_path == config_path.resolve()
+
+    # Environment variables take precedence over searching
+    custom_config_path = tmp_path / "ci" / "tach-ci.toml"
+    custom_config_path.parent.mkdir()
+    custom_config_path.write_text("")
+    monkeypatch.setenv(fs.CONFIG_PATH_ENV_VAR, str(custom_config_path))
+    location = fs.resolve_project_location()
+    assert location.root == custom_config_path.parent.resolve()
+    assert location.config_path == custom_config_path.resolve()
+
+    monkeypatch.setenv(fs.PROJECT_ROOT_ENV_VAR, str(project_root))
+    location = fs.resolve_project_location()
+    assert location.root == project_root.resolve()
+    assert location.config_path == custom_config_path.resolve()
+
+    # Explicit paths take precedence over environment variables
+    location = fs.resolve_project_location(root=project_root)
+    assert location.root == project_root.resolve()
+    assert location.config_path == config_path.resolve()
+
+
+@pytest.mark.parametrize(
+    "argv",
+    [
+        ["check"],
+        ["show"],
+        ["server"],
+        ["config", "validate"],
+        ["init"],
+    ],
+)
+def test_project_arguments(argv):
+    args, _ = cli.parse_arguments(
+        [*argv, "--config", "ci/tach.toml", "--root", "project"]
+    )
+    assert args.config == Path("ci/tach.toml")
+    assert args.root == Path("project")
+
+
+def test_tach_init_auto(tmp_path, capfd):
+    for package in ["api", "core"]:
+        (tmp_path / "src" / package).mkdir(parents=True)
+    (tmp_path / "src" / "api" / "__init__.py").write_text("from core import models\n")
+    (tmp_path / "src" / "core" / "__init__.py").write_text("")
+    (tmp_path / "src" / "core" / "models.py").write_text("")
+
+    cli.tach_init(tmp_path, auto=True)
+    captured = capfd.readouterr()
+    assert "2 module(s)" in captured.out
+
+    project_config = parse_project_config_file(tmp_path / "tach.toml")
+    assert project_config.source_roots == [Path("src")]
+    dependencies = {
+        module.path: [dependency.path for dependency in module.depends_on or []]
+        for module in project_config.modules
+    }
+    assert dependencies == {"api": ["core"], "core": []}
+
+    with pytest.raises(SystemExit) as sys_exit:
+        cli.tach_init(tmp_path, auto=True)
+    assert sys_exit.value.code == 1
+    assert "already initialized" in capfd.readouterr().err
+
+
+def test_explain(capfd):
+    cli.main(["explain", "TACH001"])
+    captured = capfd.readouterr()
+    assert "undeclared-dependency" in captured.out
+    assert "How to fix" in captured.out
+
+    with pytest.raises(SystemExit) as sys_exit:
+        cli.main(["explain", "TACH999"])
+    assert sys_exit.value.code == 1
+    assert "Unknown rule code" in capfd.readouterr().out
+
+
+def test_tach_diff_config(tmp_path, capfd):
+    project_root = tmp_path / "project"
+    for package in ["api", "core"]:
+        (project_root / package).mkdir(parents=True)
+    (project_root / "api" / "__init__.py").write_text("from core import models\n")
+    (project_root / "core" / "__init__.py").write_text("")
+    (project_root / "core" / "models.py").write_text("")
+    modules = '[[modules]]\npath = "core"\ndepends_on = []\n\n'
+    (project_root / "tach.toml").write_text(
+        modules + '[[modules]]\npath = "api"\ndepends_on = []\n'
+    )
+    # The old config lives outside the project, next to an unrelated domain config
+    # which must not be picked up
+    elsewhere = tmp_path / "elsewhere"
+    (elsewhere / "other").mkdir(parents=True)
+    (elsewhere / "other" / "tach.domain.toml").write_text("not = [valid")
+    (elsewhere / "old.toml").write_text(
+        modules + '[[modules]]\npath = "api"\ndepends_on = ["core"]\n'
+    )
+
+    with pytest.raises(SystemExit) as sys_exit:
+        cli.tach_diff_config(
+            project_root,
+            project_root / "tach.toml",
+            old=str(elsewhere / "old.toml"),
+            output_format="json",
+        )
+    assert sys_exit.value.code == 1
+    diff = json.loads(capfd.readouterr().out)
+    assert diff["dependencies_removed"] == [{"module": "api", "dependency": "core"}]
+    assert [finding["file"] for finding in diff["new"]] == ["api/__init__.py"]
+    assert diff["fixed"] == []
+
+    # Comparing in the other direction fixes the violation
+    cli.tach_diff_config(
+        project_root,
+        project_root / "tach.toml",
+        old=str(project_root / "tach.toml"),
+        new=str(elsewhere / "old.toml"),
+        output_format="json",
+    )
+    diff = json.loads(capfd.readouterr().out)
+    assert diff["new"] == []
+    assert len(diff["fixed"]) == 1
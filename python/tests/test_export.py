@@ -0,0 +1,48 @@
+from __future__ import annotations
+
+import json
+
+import pytest
+
+from tach.cli import tach_export
+from tach.extension import export_bundle
+from tach.parsing.config import parse_project_config
+
+
+def test_export_bundle(example_dir, tmp_path):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    summary = export_bundle(project_root, project_config, tmp_path / "bundle")
+
+    assert summary.schema_version == 1
+    assert sorted(path.name for path in (tmp_path / "bundle").iterdir()) == sorted(
+        summary.artifacts
+    )
+    for artifact in summary.artifacts:
+        contents = json.loads((tmp_path / "bundle" / artifact).read_text())
+        assert contents["schema_version"] == summary.schema_version
+
+    modules = json.loads((tmp_path / "bundle" / "modules.json").read_text())
+    assert "project.top_level" in [module["path"] for module in modules["modules"]]
+    edges = json.loads((tmp_path / "bundle" / "edges.json").read_text())
+    assert {"source": "project.top_level", "target": "project.module_one"} in [
+        {"source": edge["source"], "target": edge["target"]} for edge in edges["edges"]
+    ]
+
+
+def test_export_bundle_rejects_output(example_dir, tmp_path):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_export(
+            project_config,
+            project_root,
+            output_path=tmp_path / "report.json",
+            bundle=tmp_path / "bundle",
+        )
+    assert exc_info.value.code == 1
+    assert not (tmp_path / "bundle").exists()
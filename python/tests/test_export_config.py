@@ -0,0 +1,145 @@
+from __future__ import annotations
+
+import configparser
+import re
+
+import pytest
+import tomli
+
+from tach import cli
+from tach.export_config import (
+    _gitignore_pattern_to_regex,
+    export_to_deptry,
+    export_to_import_linter,
+)
+from tach.import_config import import_from_import_linter
+from tach.parsing.config import parse_project_config_file
+
+TACH_CONFIG = """\
+source_roots = ["src"]
+layers = ["api", "domain"]
+exclude = ["tests", "/build/", "**/*_pb2.py", "!tests/conftest.py"]
+
+[[modules]]
+path = "myapp.api"
+layer = "api"
+
+[[modules]]
+path = "myapp.billing"
+layer = "domain"
+depends_on = ["myapp.core"]
+
+[[modules]]
+path = "myapp.shipping"
+layer = "domain"
+depends_on = []
+
+[[modules]]
+path = "myapp.core"
+utility = true
+
+[external]
+exclude = ["pytest"]
+rename = ["PIL:pillow"]
+"""
+
+
+@pytest.fixture
+def tach_project(tmp_path):
+    for package in ["api", "billing", "shipping", "core"]:
+        package_path = tmp_path / "src" / "myapp" / package
+        package_path.mkdir(parents=True)
+        (package_path / "__init__.py").write_text("")
+    (tmp_path / "src" / "myapp" / "__init__.py").write_text("")
+    (tmp_path / "tach.toml").write_text(TACH_CONFIG)
+    return tmp_path
+
+
+def test_export_to_import_linter(tach_project):
+    project_config = parse_project_config_file(tach_project / "tach.toml")
+    exported = export_to_import_linter(project_config)
+
+    parser = configparser.ConfigParser()
+    parser.read_string(exported.contents)
+    assert parser["importlinter"]["root_packages"].split() == ["myapp"]
+    # Higher layers and utility modules are not forbidden
+    assert "importlinter:contract:tach-myapp.api" not in parser
+    assert "importlinter:contract:tach-myapp.core" not in parser
+    billing = parser["importlinter:contract:tach-myapp.billing"]
+    assert billing["type"] == "forbidden"
+    assert billing["forbidden_modules"].split() == ["myapp.api", "myapp.shipping"]
+    shipping = parser["importlinter:contract:tach-myapp.shipping"]
+    assert shipping["forbidden_modules"].split() == ["myapp.api", "myapp.billing"]
+    assert not exported.warnings
+
+    # The contracts can be imported again
+    (tach_project / ".importlinter").write_text(exported.contents)
+    imported = import_from_import_linter(tach_project, tach_project / ".importlinter")
+    assert imported.modules == ["myapp.api", "myapp.billing", "myapp.shipping"]
+
+
+def test_export_to_import_linter_allowed_submodule(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """\
+[[modules]]
+path = "a"
+depends_on = ["b.c"]
+
+[[modules]]
+path = "b"
+depends_on = []
+
+[[modules]]
+path = "b.c"
+depends_on = []
+"""
+    )
+    project_config = parse_project_config_file(tmp_path / "tach.toml")
+    exported = export_to_import_linter(project_config)
+
+    parser = configparser.ConfigParser()
+    parser.read_string(exported.contents)
+    assert "importlinter:contract:tach-a" not in parser
+    assert len(exported.warnings) == 1
+    assert "'b.c'" in exported.warnings[0]
+
+
+def test_export_to_deptry(tach_project):
+    project_config = parse_project_config_file(tach_project / "tach.toml")
+    exported = export_to_deptry(tach_project, project_config)
+
+    settings = tomli.loads(exported.contents)["tool"]["deptry"]
+    assert settings["known_first_party"] == ["myapp"]
+    assert settings["per_rule_ignores"] == {"DEP001": ["pytest"], "DEP003": ["pytest"]}
+    assert settings["package_module_name_map"] == {"pillow": "PIL"}
+    assert len(settings["extend_exclude"]) == 3
+    assert "use_gitignore" not in settings
+    assert len(exported.warnings) == 1
+    assert "!tests/conftest.py" in exported.warnings[0]
+
+
+@pytest.mark.parametrize(
+    "pattern,path,matches",
+    [
+        ("tests", "tests/test_a.py", True),
+        ("tests", "src/tests/test_a.py", True),
+        ("tests", "src/tests_a.py", False),
+        ("/build/", "build/lib/a.py", True),
+        ("/build/", "src/build/a.py", False),
+        ("src/*/migrations", "src/app/migrations/0001.py", True),
+        ("src/*/migrations", "src/app/sub/migrations/0001.py", False),
+        ("**/*_pb2.py", "src/app/api_pb2.py", True),
+        ("docs/**/*.py", "docs/a/b/conf.py", True),
+    ],
+)
+def test_gitignore_pattern_to_regex(pattern, path, matches):
+    assert bool(re.match(_gitignore_pattern_to_regex(pattern), path)) is matches
+
+
+def test_export_config_command(tach_project, monkeypatch, capsys):
+    monkeypatch.chdir(tach_project)
+    cli.main(["export-config", "--to", "importlinter"])
+    assert "[importlinter:contract:tach-myapp.billing]" in capsys.readouterr().out
+
+    cli.main(["export-config", "--to", "deptry", "-o", "deptry.toml"])
+    assert "[tool.deptry]" in (tach_project / "deptry.toml").read_text()
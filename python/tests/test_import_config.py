@@ -0,0 +1,132 @@
+from __future__ import annotations
+
+import pytest
+
+from tach import cli
+from tach.errors import TachConfigError
+from tach.import_config import import_from_import_linter
+from tach.parsing.config import parse_project_config_file
+
+IMPORT_LINTER_CONFIG = """\
+[importlinter]
+root_package = myapp
+
+[importlinter:contract:layers]
+name = Layered architecture
+type = layers
+containers =
+    myapp
+layers =
+    api
+    (cli)
+    billing | shipping
+    domain
+    data
+
+[importlinter:contract:forbidden]
+name = Data does not use the API
+type = forbidden
+source_modules =
+    myapp.data
+forbidden_modules =
+    myapp.api
+    django
+
+[importlinter:contract:acyclic]
+name = Acyclic
+type = acyclic_siblings
+"""
+
+
+@pytest.fixture
+def import_linter_project(tmp_path):
+    for package in ["api", "billing", "shipping", "domain", "data"]:
+        package_path = tmp_path / "src" / "myapp" / package
+        package_path.mkdir(parents=True)
+        (package_path / "__init__.py").write_text("")
+    (tmp_path / "setup.cfg").write_text(IMPORT_LINTER_CONFIG)
+    return tmp_path
+
+
+def test_import_from_import_linter(import_linter_project):
+    imported = import_from_import_linter(
+        import_linter_project, import_linter_project / "setup.cfg"
+    )
+
+    assert imported.layers == ["api", "billing | shipping", "domain", "data"]
+    assert imported.modules == [
+        "myapp.api",
+        "myapp.billing",
+        "myapp.data",
+        "myapp.domain",
+        "myapp.shipping",
+    ]
+    # The optional 'cli' layer is skipped quietly
+    assert len(imported.warnings) == 2
+    assert "acyclic_siblings" in imported.warnings[0]
+    assert "django" in imported.warnings[1]
+
+    config_path = import_linter_project / "tach.toml"
+    config_path.write_text(imported.config_toml)
+    project_config = parse_project_config_file(config_path)
+    assert [str(root) for root in project_config.source_roots] == ["src"]
+    modules = {module.path: module for module in project_config.all_modules()}
+    assert modules["myapp.api"].layer == "api"
+    assert modules["myapp.api"].depends_on is None
+    # Independent siblings and forbidden modules are left out of 'depends_on'
+    assert [dependency.path for dependency in modules["myapp.billing"].depends_on] == [
+        "myapp.api",
+        "myapp.data",
+        "myapp.domain",
+    ]
+    assert [dependency.path for dependency in modules["myapp.data"].depends_on] == [
+        "myapp.billing",
+        "myapp.domain",
+        "myapp.shipping",
+    ]
+
+
+def test_import_from_import_linter_pyproject(tmp_path):
+    (tmp_path / "myapp" / "a").mkdir(parents=True)
+    (tmp_path / "myapp" / "b.py").write_text("")
+    (tmp_path / "pyproject.toml").write_text(
+        """\
+[tool.importlinter]
+root_packages = ["myapp"]
+
+[[tool.importlinter.contracts]]
+name = "Independent"
+type = "independence"
+modules = ["myapp.a", "myapp.b"]
+"""
+    )
+
+    imported = import_from_import_linter(tmp_path, tmp_path / "pyproject.toml")
+
+    assert imported.modules == ["myapp.a", "myapp.b"]
+    assert imported.layers == []
+    assert not imported.warnings
+
+    config_path = tmp_path / "tach.toml"
+    config_path.write_text(imported.config_toml)
+    project_config = parse_project_config_file(config_path)
+    assert [str(root) for root in project_config.source_roots] == ["."]
+    assert all(module.depends_on == [] for module in project_config.all_modules())
+
+
+def test_import_from_import_linter_without_section(tmp_path):
+    (tmp_path / "setup.cfg").write_text("[metadata]\nname = myapp\n")
+    with pytest.raises(TachConfigError):
+        import_from_import_linter(tmp_path, tmp_path / "setup.cfg")
+
+
+def test_import_config_command(import_linter_project, monkeypatch, capsys):
+    monkeypatch.chdir(import_linter_project)
+    cli.main(["import-config", "--from", "importlinter", "setup.cfg"])
+    assert (import_linter_project / "tach.toml").exists()
+    assert "Wrote 5 module(s) and 4 layer(s)" in capsys.readouterr().out
+
+    # An existing config is only replaced with --force
+    with pytest.raises(SystemExit):
+        cli.main(["import-config", "--from", "importlinter", "setup.cfg"])
+    cli.main(["import-config", "--from", "importlinter", "setup.cfg", "--force"])
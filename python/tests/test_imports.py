@@ -292,3 +292,29 @@ from local.m.n import k, l
         ("local.m.n.l", 9),  # only 'k' is ignored
     ]
     assert result == expected
+
+
+def test_imports_from_overlay(temp_project, mock_project_config):
+    file_path = temp_project / "file1.py"
+    overlay = {file_path: "import os\nfrom a.b import c\n"}
+    result = _get_project_imports(
+        temp_project,
+        [temp_project],
+        file_path,
+        mock_project_config,
+        overlay=overlay,
+    )
+    # The unsaved contents are used instead of the file on disk
+    assert result == [("a.b.c", 2)]
+
+
+def test_imports_from_overlay_for_unsaved_file(temp_project, mock_project_config):
+    file_path = temp_project / "new_file.py"
+    result = _get_project_imports(
+        temp_project,
+        [temp_project],
+        file_path,
+        mock_project_config,
+        overlay={file_path: "from d.e import f\n"},
+    )
+    assert result == [("d.e.f", 1)]
@@ -2,6 +2,7 @@ from __future__ import annotations
 
 import pytest
 
+from tach.extension import ConfigEdit
 from tach.parsing import parse_project_config
 
 
@@ -13,3 +14,114 @@ def test_invalid_project_config(example_dir):
 def test_empty_project_config(example_dir):
     with pytest.raises(ValueError):
         parse_project_config(example_dir / "invalid" / "empty")
+
+
+def test_otlp_config(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+[otlp]
+enabled = true
+endpoint = "http://localhost:4318/v1/traces"
+
+[otlp.resource_attributes]
+team = "platform"
+"""
+    )
+    project_config = parse_project_config(tmp_path)
+    assert project_config is not None
+    assert project_config.otlp.enabled
+    assert project_config.otlp.endpoint == "http://localhost:4318/v1/traces"
+    assert project_config.otlp.resource_attributes == {"team": "platform"}
+
+
+def test_rules_environment(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+[rules]
+unused_ignore_directives = "off"
+
+[rules.ci]
+unused_ignore_directives = "error"
+deprecated_dependencies = "error"
+"""
+    )
+    project_config = parse_project_config(tmp_path)
+    assert project_config is not None
+    assert project_config.rules.unused_ignore_directives == "off"
+
+    assert not project_config.apply_rules_environment("local")
+    assert project_config.rules.unused_ignore_directives == "off"
+
+    assert project_config.apply_rules_environment("ci")
+    assert project_config.rules.unused_ignore_directives == "error"
+    assert project_config.rules.deprecated_dependencies == "error"
+    assert project_config.rules.interface_violations == "error"
+
+
+def test_config_edits(tmp_path):
+    config = """# Layers are listed from highest to lowest
+layers = ["api", "core"]
+
+[[modules]]
+path = "a"
+depends_on = []  # a depends on nothing yet
+
+[[modules]]
+path = "b"
+depends_on = []
+"""
+    (tmp_path / "tach.toml").write_text(config)
+    project_config = parse_project_config(tmp_path)
+    assert project_config is not None
+
+    project_config.enqueue_edit(ConfigEdit.AddDependency(path="a", dependency="b"))
+    project_config.enqueue_edit(ConfigEdit.SetModuleLayer(path="b", layer="core"))
+    assert project_config.pending_edits() == [
+        ConfigEdit.AddDependency(path="a", dependency="b"),
+        ConfigEdit.SetModuleLayer(path="b", layer="core"),
+    ]
+
+    [preview] = project_config.preview_edits()
+    assert preview.path == tmp_path / "tach.toml"
+    assert preview.original == config
+    assert '+depends_on = ["b"]  # a depends on nothing yet' in preview.diff()
+    assert '+layer = "core"' in preview.diff()
+    # Nothing is written until the edits are saved
+    assert (tmp_path / "tach.toml").read_text() == config
+
+    project_config.save_edits()
+    assert (tmp_path / "tach.toml").read_text() == preview.edited
+    assert project_config.pending_edits() == []
+
+
+def test_set_module_strict(tmp_path):
+    config = """source_roots = ["."]
+
+[[modules]]
+path = "a"
+depends_on = []
+"""
+    (tmp_path / "tach.toml").write_text(config)
+    (tmp_path / "a").mkdir()
+    (tmp_path / "a" / "__init__.py").write_text('__all__ = ["run"]\n')
+    project_config = parse_project_config(tmp_path)
+    assert project_config is not None
+
+    # A strict module is written as an interface exposing its '__all__'
+    project_config.set_module_strict("a", True)
+    assert project_config.pending_edits() == [
+        ConfigEdit.AddInterface(path="a", expose=["run"])
+    ]
+    project_config.save_edits()
+    edited = (tmp_path / "tach.toml").read_text()
+    assert "strict" not in edited
+    assert 'expose = ["run"]\nfrom = ["a"]' in edited
+
+    project_config = parse_project_config(tmp_path)
+    assert project_config is not None
+    project_config.set_module_strict("a", True)
+    assert project_config.pending_edits() == []
+
+    project_config.set_module_strict("a", False)
+    project_config.save_edits()
+    assert "from = " not in (tmp_path / "tach.toml").read_text()
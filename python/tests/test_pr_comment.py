@@ -0,0 +1,90 @@
+from __future__ import annotations
+
+from pathlib import Path
+from types import SimpleNamespace
+
+from tach.pr_comment import COMMENT_MARKER, github_link_base, render_pr_comment
+
+
+def finding(file_path: str | None, line_number: int | None, message: str):
+    return SimpleNamespace(
+        fingerprint=message,
+        severity="error",
+        file_path=file_path,
+        line_number=line_number,
+        message=message,
+    )
+
+
+def run(id: str, commit: str | None, errors: int, suppressions, module_coverage):
+    return SimpleNamespace(
+        id=id,
+        timestamp=0,
+        commit=commit,
+        errors=errors,
+        warnings=0,
+        findings=[],
+        suppressions=suppressions,
+        module_coverage=module_coverage,
+    )
+
+
+def test_render_pr_comment():
+    diff = SimpleNamespace(
+        base=run("aaaa1111", "0123456789abcdef", 1, 3, 0.5),
+        head=run("bbbb2222", "fedcba9876543210", 2, 4, 0.625),
+        new=[
+            finding("api/views.py", 3, "Cannot use 'core.db'."),
+            finding(None, None, "Module 'legacy' not found."),
+        ],
+        fixed=[finding("cli.py", 7, "Cannot use 'core._private'.")],
+    )
+    comment = render_pr_comment(
+        diff, head_link_base="https://example.com/blob/head"
+    ).splitlines()
+
+    assert comment[0] == COMMENT_MARKER
+    assert ":x: **2 new violations**, 1 fixed" in comment
+    assert "Compared with run `aaaa1111` (commit `0123456789`)." in comment
+    assert "| Errors | 1 | 2 | +1 |" in comment
+    assert "| Suppressions | 3 | 4 | +1 |" in comment
+    assert "| Module coverage | 50.0% | 62.5% | +12.5 pp |" in comment
+    assert (
+        "- **error** [`api/views.py:3`](https://example.com/blob/head/api/views.py#L3): "
+        "Cannot use 'core.db'." in comment
+    )
+    assert "- **error**: Module 'legacy' not found." in comment
+    # Without a link base, fixed findings are not linked
+    assert "- **error** `cli.py:7`: Cannot use 'core.\\_private'." in comment
+
+
+def test_render_pr_comment_truncates_long_lists():
+    diff = SimpleNamespace(
+        base=run("aaaa1111", None, 0, None, None),
+        head=run("bbbb2222", None, 60, 0, 1.0),
+        new=[finding("a.py", line, f"Error {line}") for line in range(60)],
+        fixed=[],
+    )
+    comment = render_pr_comment(diff).splitlines()
+    assert "### New violations (60)" in comment
+    assert "- ...and 10 more" in comment
+    assert "| Suppressions | - | 0 |  |" in comment
+    assert not any("Fixed violations" in line for line in comment)
+
+
+def test_github_link_base(monkeypatch, tmp_path):
+    monkeypatch.delenv("GITHUB_SERVER_URL", raising=False)
+    assert github_link_base("abc123", tmp_path) is None
+
+    monkeypatch.setenv("GITHUB_SERVER_URL", "https://github.com")
+    monkeypatch.setenv("GITHUB_REPOSITORY", "org/repo")
+    monkeypatch.setenv("GITHUB_WORKSPACE", str(tmp_path))
+    assert (
+        github_link_base("abc123", tmp_path / "backend")
+        == "https://github.com/org/repo/blob/abc123/backend"
+    )
+    assert github_link_base(None, tmp_path) is None
+    assert (
+        github_link_base("abc123", Path("/elsewhere"))
+        == "https://github.com/org/repo/blob/abc123"
+    )
@@ -0,0 +1,145 @@
+from __future__ import annotations
+
+import json
+
+import pytest
+
+from tach.cli import tach_query, tach_why
+from tach.extension import explain_dependencies, query_dependencies, query_paths
+from tach.parsing.config import parse_project_config
+
+
+def test_query_dependencies(example_dir):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    assert query_dependencies(project_root, project_config, "project.top_level") == [
+        "project.module_one",
+        "project.module_two",
+    ]
+    assert query_dependencies(
+        project_root, project_config, "project.module_two", dependents=True
+    ) == ["project.module_one", "project.top_level"]
+
+    with pytest.raises(ValueError):
+        query_dependencies(project_root, project_config, "project.missing")
+
+
+def test_query_paths(example_dir, capfd):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    paths = query_paths(
+        project_root, project_config, "project.top_level", "project.module_two"
+    )
+    assert [[step.target for step in path.steps] for path in paths] == [
+        ["project.module_two"],
+        ["project.module_one", "project.module_two"],
+    ]
+    assert paths[0].steps[0].examples[0].line == 2
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_query(
+            project_config,
+            project_root,
+            query_command="paths",
+            source="project.top_level",
+            target="project.module_two",
+            max_paths=1,
+            output_format="json",
+        )
+    assert exc_info.value.code == 0
+    output = json.loads(capfd.readouterr().out)
+    assert len(output["paths"]) == 1
+    assert output["paths"][0][0]["evidence"] == 1
+
+
+def test_why(example_dir, capfd):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    [explanation] = explain_dependencies(
+        project_root, project_config, "project.top_level", "project.module_two"
+    )
+    assert len(explanation.paths) == 1
+    assert [step.target for step in explanation.paths[0].steps] == [
+        "project.module_two"
+    ]
+    assert (
+        explain_dependencies(
+            project_root, project_config, "project.module_two", "project.top_level"
+        )
+        == []
+    )
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_why(
+            project_config,
+            project_root,
+            source="project.top_level",
+            target="project.module_two",
+            output_format="json",
+        )
+    assert exc_info.value.code == 0
+    output = json.loads(capfd.readouterr().out)
+    [pair] = output["pairs"]
+    assert pair["source"] == "project.top_level"
+    imports = pair["paths"][0][0]["imports"]
+    assert imports[0]["line"] == 2
+    assert "module_two" in imports[0]["code"]
+    assert output["schema_version"] == 2
+
+    with pytest.raises(SystemExit):
+        tach_why(
+            project_config,
+            project_root,
+            source="project.top_level",
+            target="project.module_two",
+            output_format="json",
+            schema_version=1,
+        )
+    legacy_output = json.loads(capfd.readouterr().out)
+    assert legacy_output == {
+        key: value for key, value in output.items() if key != "schema_version"
+    }
+
+
+def test_why_glob(example_dir, capfd):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    explanations = explain_dependencies(
+        project_root, project_config, "project.*", "project.module_two"
+    )
+    assert [(item.source, item.target) for item in explanations] == [
+        ("project.module_one", "project.module_two"),
+        ("project.top_level", "project.module_two"),
+    ]
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_why(
+            project_config,
+            project_root,
+            source="project.*",
+            target="project.module_two",
+            all_shortest=True,
+            output_format="json",
+        )
+    assert exc_info.value.code == 0
+    output = json.loads(capfd.readouterr().out)
+    assert [len(pair["paths"]) for pair in output["pairs"]] == [1, 1]
+
+    with pytest.raises(SystemExit) as exc_info:
+        tach_why(
+            project_config,
+            project_root,
+            source="billing.*",
+            target="project.module_two",
+            output_format="json",
+        )
+    assert exc_info.value.code == 1
+    assert "billing.*" in json.loads(capfd.readouterr().out)["error"]
@@ -5,10 +5,22 @@ from pathlib import Path
 
 import pytest
 
+from tach import cli
 from tach.errors import TachError
-from tach.extension import ProjectConfig
+from tach.extension import ProjectConfig, strictify_module
 from tach.parsing.config import parse_project_config
-from tach.report import report
+from tach.report import (
+    combined_report,
+    dependency_matrix,
+    dependency_records,
+    render_dependency_matrix,
+    render_dependency_matrix_csv,
+    render_dependency_matrix_html,
+    render_dependency_records_csv,
+    report,
+    unused_interface_members_report,
+    unused_modules_report,
+)
 
 
 @pytest.fixture
@@ -126,3 +138,206 @@ domain_two
 # Module Usages
 ."""
     )
+
+
+def test_report_records(example_valid_dir):
+    project_config = parse_project_config(example_valid_dir)
+    records = dependency_records(
+        project_root=example_valid_dir,
+        path=Path("domain_one"),
+        project_config=project_config,
+    )
+    dependencies = [record for record in records if record.kind == "dependency"]
+    usages = [record for record in records if record.kind == "usage"]
+    assert dependencies and usages
+    assert records == dependencies + usages
+    assert {record.target_module for record in dependencies} == {"domain_two"}
+    assert {record.target_module for record in usages} == {"domain_one"}
+    assert all(record.import_path.startswith("domain_one") for record in usages)
+
+    csv_output = render_dependency_records_csv(dependencies)
+    header, first_row = csv_output.splitlines()[:2]
+    assert header == "kind,file,line,source_module,target_module,import_path"
+    assert first_row == (
+        f"dependency,{dependencies[0].file_path.as_posix()},"
+        f"{dependencies[0].line_number},domain_one,domain_two,"
+        f"{dependencies[0].import_path}"
+    )
+
+
+def test_combined_report(example_valid_dir):
+    project_config = parse_project_config(example_valid_dir)
+    paths = [Path("domain_one"), Path("domain_two")]
+
+    # Each section matches the report of a single path
+    raw = combined_report(
+        project_root=example_valid_dir,
+        paths=paths,
+        project_config=project_config,
+        raw=True,
+    )
+    assert raw == "\n".join(
+        f"# Report for '{path}'\n"
+        + report(
+            project_root=example_valid_dir,
+            path=path,
+            project_config=project_config,
+            raw=True,
+        )
+        for path in paths
+    )
+
+    result = combined_report(
+        project_root=example_valid_dir,
+        paths=paths,
+        project_config=project_config,
+        skip_usages=True,
+    )
+    assert "Dependency Report for 'domain_one'" in result
+    assert "Dependency Report for 'domain_two'" in result
+    imports_of_domain_two = len(
+        dependency_records(
+            project_root=example_valid_dir,
+            path=Path("domain_one"),
+            project_config=project_config,
+            include_dependency_modules=["domain_two"],
+            skip_usages=True,
+        )
+    )
+    matrix = result.split("[ Cross-references ]\n")[1].splitlines()
+    assert matrix[2].split("|") == [
+        "domain_one ",
+        "          - ",
+        f" {imports_of_domain_two:>10}",
+    ]
+    assert matrix[3].split("|") == ["domain_two ", "          0 ", "          -"]
+
+
+def test_unused_modules_report(example_dir):
+    project_root = example_dir / "distributed_config"
+    project_config = parse_project_config(project_root)
+    assert project_config is not None
+
+    result = unused_modules_report(
+        project_root=project_root,
+        path=project_root,
+        project_config=project_config,
+        raw=True,
+    )
+    assert result.strip() == "# Unused Modules\nproject.top_level"
+
+    result = unused_modules_report(
+        project_root=project_root,
+        path=project_root / "project" / "module_two",
+        project_config=project_config,
+        raw=True,
+    )
+    assert result == ""
+
+
+def test_unused_interface_members_report(example_dir):
+    project_root = example_dir / "monorepo"
+    project_config = parse_project_config(project_root)
+    assert project_config is not None
+
+    result = unused_interface_members_report(
+        project_root=project_root,
+        path=project_root,
+        project_config=project_config,
+        raw=True,
+    )
+    assert result.strip() == "# Unused Interface Members\nmod1 x"
+
+    result = unused_interface_members_report(
+        project_root=project_root,
+        path=project_root / "backend" / "mod2",
+        project_config=project_config,
+        raw=True,
+    )
+    assert result == ""
+
+
+def test_strictify(tmp_project, capsys):
+    (tmp_project / "tach.toml").write_text(
+        'source_roots = ["."]\n\n'
+        '[[modules]]\npath = "payments"\ndepends_on = []\n\n'
+        '[[modules]]\npath = "orders"\ndepends_on = ["payments"]\n'
+    )
+    (tmp_project / "payments").mkdir()
+    (tmp_project / "payments" / "__init__.py").write_text(
+        '__all__ = ["charge"]\n\ndef charge(): ...\n\ndef refund(): ...\n'
+    )
+    (tmp_project / "payments" / "models.py").write_text("class Invoice: ...\n")
+    (tmp_project / "orders").mkdir()
+    (tmp_project / "orders" / "__init__.py").write_text(
+        "from payments import charge\n"
+        "from payments import refund\n"
+        "from payments.models import Invoice\n"
+    )
+
+    strictify_report = strictify_module(
+        project_root=tmp_project,
+        project_config=parse_project_config(tmp_project),
+        path=Path("payments"),
+    )
+    assert strictify_report.covered_usages == 1
+    assert [
+        (str(blocking_import.file_path), blocking_import.line_number)
+        for blocking_import in strictify_report.blocking_imports
+    ] == [
+        (os.path.join("orders", "__init__.py"), 2),
+        (os.path.join("orders", "__init__.py"), 3),
+    ]
+    assert strictify_report.expose == ["charge", "models", "refund"]
+
+    cli.main(["strictify", "payments", "--apply"])
+    assert "Added an interface exposing 3 member(s)" in capsys.readouterr().out
+    project_config = parse_project_config(tmp_project)
+    assert [
+        (interface.expose, interface.from_modules)
+        for interface in project_config.all_interfaces()
+    ] == [(["charge", "models", "refund"], ["payments"])]
+
+    cli.main(["strictify", "payments"])
+    assert "goes through its interface" in capsys.readouterr().out
+
+
+def test_dependency_matrix(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+source_roots = ["."]
+
+[[modules]]
+path = "api"
+display_name = "Public API"
+depends_on = ["core"]
+
+[[modules]]
+path = "core"
+depends_on = []
+"""
+    )
+    (tmp_path / "api.py").write_text("from core import a\nfrom core import b\n")
+    (tmp_path / "core.py").write_text("from api import c\na = b = None\n")
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    matrix = dependency_matrix(
+        project_root=tmp_path, path=tmp_path, project_config=project_config
+    )
+    assert matrix.modules == ["api", "core"]
+    assert matrix.labels == ["Public API", "core"]
+    assert matrix.counts == [[0, 2], [1, 0]]
+    assert matrix.violations == [[False, False], [True, False]]
+
+    # Display names are shown in the grids, while the CSV keeps the module paths
+    assert "\n1 Public API | " in render_dependency_matrix(matrix, tmp_path)
+    assert render_dependency_matrix_csv(matrix) == (
+        "module,api,core\napi,-,2\ncore,1!,-\n"
+    )
+    matrix_html = render_dependency_matrix_html(matrix, tmp_path)
+    assert '<th class="module">1 Public API</th>' in matrix_html
+    assert (
+        '<td class="violation" title="core -&gt; Public API: 1 imports">1!</td>'
+        in matrix_html
+    )
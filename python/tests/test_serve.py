@@ -0,0 +1,24 @@
+from __future__ import annotations
+
+from tach.parsing.config import parse_project_config
+from tach.serve import build_graph, module_details
+
+
+def test_build_graph_many_features_example_dir(example_dir):
+    project_root = example_dir / "many_features"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    graph = build_graph(project_root=project_root, project_config=project_config)
+    node_ids = {node["id"] for node in graph["nodes"]}
+    assert node_ids
+    assert all(
+        edge["source"] in node_ids and edge["target"] in node_ids
+        for edge in graph["edges"]
+    )
+
+    module_path = graph["nodes"][0]["id"]
+    details = module_details(graph, module_path)
+    assert details is not None
+    assert all(edge["source"] == module_path for edge in details["dependencies"])
+    assert module_details(graph, "does.not.exist") is None
@@ -1,7 +1,19 @@
 from __future__ import annotations
 
+import json
+from dataclasses import asdict
+
+from tach.modularity import build_modules
 from tach.parsing.config import parse_project_config
-from tach.show import generate_show_report
+from tach.show import (
+    build_graph,
+    collapse_module_path,
+    generate_module_graph_d2,
+    generate_module_graph_dot_file,
+    generate_module_graph_mermaid,
+    generate_module_graph_plantuml,
+    generate_show_report,
+)
 
 
 # right now this is just a smoke test
@@ -15,3 +27,175 @@ def test_many_features_example_dir(example_dir, capfd):
         project_root=project_root, project_config=project_config, included_paths=[]
     )
     assert report is not None
+
+
+def test_graphs_use_display_names(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        """
+[[modules]]
+path = "app.billing"
+display_name = "Billing"
+depends_on = ["app.core"]
+
+[[modules]]
+path = "app.core"
+depends_on = []
+"""
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    mermaid_filepath = tmp_path / "graph.mmd"
+    generate_module_graph_mermaid(
+        project_config, output_filepath=mermaid_filepath, included_paths=[]
+    )
+    assert '    app.billing["Billing"] --> app.core' in mermaid_filepath.read_text()
+
+    dot_filepath = tmp_path / "graph.dot"
+    generate_module_graph_dot_file(
+        project_config, output_filepath=dot_filepath, included_paths=[]
+    )
+    assert 'label="Billing"' in dot_filepath.read_text()
+
+    # The exported module graph keeps the real module paths
+    exported = json.dumps([asdict(module) for module in build_modules(project_config)])
+    assert "app.billing" in exported
+    assert "Billing" not in exported
+
+
+def test_mermaid_graph_highlights_import_side_effects(example_dir, tmp_path):
+    project_root = example_dir / "many_features"
+    project_config = parse_project_config(root=project_root)
+    assert project_config is not None
+
+    output_filepath = tmp_path / "graph.mmd"
+    generate_module_graph_mermaid(
+        project_config,
+        output_filepath=output_filepath,
+        included_paths=[],
+        import_side_effects={"module2": ["network"]},
+    )
+    assert output_filepath.read_text().endswith(
+        "\n    class module2 importSideEffects"
+    )
+
+
+NESTED_PROJECT_CONFIG = """
+[[modules]]
+path = "app.api.views"
+depends_on = ["app.core.models", { path = "app.api.utils", deprecated = true }]
+
+[[modules]]
+path = "app.api.utils"
+depends_on = []
+
+[[modules]]
+path = "app.core.models"
+depends_on = []
+"""
+
+
+def test_collapse_module_path():
+    assert collapse_module_path("app.api.views", None) == "app.api.views"
+    assert collapse_module_path("app.api.views", 2) == "app.api"
+    assert collapse_module_path("app", 2) == "app"
+    assert collapse_module_path("<root>", 1) == "<root>"
+
+
+def test_d2_graph(tmp_path):
+    (tmp_path / "tach.toml").write_text(NESTED_PROJECT_CONFIG)
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    output_filepath = tmp_path / "graph.d2"
+    generate_module_graph_d2(
+        project_config,
+        output_filepath=output_filepath,
+        included_paths=[],
+        import_side_effects={"app.core.models": ["network"]},
+    )
+    d2_graph = output_filepath.read_text()
+    assert '"app.api.views" -> "app.core.models"\n' in d2_graph
+    assert (
+        '"app.api.views" -> "app.api.utils": deprecated {style.stroke-dash: 3}'
+        in d2_graph
+    )
+    assert '  tooltip: "Import-time side effects: network"' in d2_graph
+
+
+def test_plantuml_graph_collapses_modules(tmp_path):
+    (tmp_path / "tach.toml").write_text(NESTED_PROJECT_CONFIG)
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    output_filepath = tmp_path / "graph.puml"
+    generate_module_graph_plantuml(
+        project_config,
+        output_filepath=output_filepath,
+        included_paths=[],
+        depth=2,
+    )
+    assert output_filepath.read_text().splitlines() == [
+        "@startuml",
+        'component "app.api" as app_api',
+        'component "app.core" as app_core',
+        "app_api --> app_core",
+        "@enduml",
+    ]
+
+
+def test_graph_labels_include_stability(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        NESTED_PROJECT_CONFIG.replace(
+            'path = "app.core.models"',
+            'path = "app.core.models"\nstability = "experimental"',
+        )
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    output_filepath = tmp_path / "graph.puml"
+    generate_module_graph_plantuml(
+        project_config, output_filepath=output_filepath, included_paths=[]
+    )
+    assert (
+        'component "app.core.models [experimental]" as app_core_models'
+        in output_filepath.read_text()
+    )
+
+
+def test_focused_graph(tmp_path):
+    (tmp_path / "tach.toml").write_text(
+        NESTED_PROJECT_CONFIG
+        + """
+[[modules]]
+path = "app.core.jobs"
+depends_on = ["app.core.models"]
+"""
+    )
+    project_config = parse_project_config(root=tmp_path)
+    assert project_config is not None
+
+    graph = build_graph(
+        tmp_path, project_config, included_paths=[], focus="app.api.utils"
+    )
+    assert sorted(graph.nodes) == ["app.api.utils", "app.api.views"]
+
+    graph = build_graph(
+        tmp_path,
+        project_config,
+        included_paths=[],
+        focus="app.api.utils",
+        focus_depth=2,
+    )
+    assert sorted(graph.nodes) == ["app.api.utils", "app.api.views", "app.core.models"]
+
+    output_filepath = tmp_path / "graph.puml"
+    generate_module_graph_plantuml(
+        project_config,
+        output_filepath=output_filepath,
+        included_paths=[],
+        graph=graph,
+    )
+    assert "app_core_jobs" not in output_filepath.read_text()
+    assert "app_api_views ..> app_api_utils" in output_filepath.read_text()
@@ -1,5 +1,6 @@
 from __future__ import annotations
 
+import json
 import shutil
 import tempfile
 from pathlib import Path
@@ -7,6 +8,7 @@ from pathlib import Path
 import pytest
 
 from tach.cli import tach_sync
+from tach.extension import latest_config_backup
 from tach.parsing.config import parse_project_config
 
 
@@ -122,3 +124,67 @@ def test_many_features_example_dir(example_dir, capfd):
                 temp_project_root / "real_src" / "module3" / "tach.domain.toml"
             ).read_text()
         )
+
+
+def test_dry_run_json_plan(example_dir, capfd):
+    project_root = example_dir / "distributed_config"
+
+    with tempfile.TemporaryDirectory() as temp_dir:
+        temp_project_root = Path(temp_dir) / "distributed_config"
+        shutil.copytree(project_root, temp_project_root)
+        config_path = temp_project_root / "tach.toml"
+        original_config = config_path.read_text()
+
+        project_config = parse_project_config(root=temp_project_root)
+        assert project_config is not None
+
+        with pytest.raises(SystemExit) as exc_info:
+            tach_sync(
+                project_root=temp_project_root,
+                project_config=project_config,
+                dry_run=True,
+                output_format="json",
+            )
+
+        assert exc_info.value.code == 0
+        plan = json.loads(capfd.readouterr().out)
+        added = next(
+            change
+            for change in plan["changes"]
+            if change["action"] == "add"
+            and change["path"] == "project.top_level"
+            and change["dependency"] == "project.module_one"
+        )
+        assert added["evidence"] >= 1
+        assert added["examples"]
+        assert config_path.read_text() == original_config
+
+
+@pytest.mark.parametrize("backup", [True, False])
+def test_sync_backs_up_config(example_dir, backup):
+    project_root = example_dir / "distributed_config"
+
+    with tempfile.TemporaryDirectory() as temp_dir:
+        temp_project_root = Path(temp_dir) / "distributed_config"
+        shutil.copytree(project_root, temp_project_root)
+        config_path = temp_project_root / "tach.toml"
+        original_config = config_path.read_text()
+
+        project_config = parse_project_config(root=temp_project_root)
+        assert project_config is not None
+        project_config.backup_edits = backup
+
+        with pytest.raises(SystemExit) as exc_info:
+            tach_sync(
+                project_root=temp_project_root,
+                project_config=project_config,
+            )
+
+        assert exc_info.value.code == 0
+        assert config_path.read_text() != original_config
+        backup_dir = latest_config_backup(temp_project_root)
+        if backup:
+            assert backup_dir is not None
+            assert (backup_dir / "tach.toml").read_text() == original_config
+        else:
+            assert backup_dir is None
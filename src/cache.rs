@@ -1,14 +1,20 @@
 use cached::stores::DiskCacheBuildError;
 use cached::{DiskCache, DiskCacheError, IOCached};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use parking_lot::Mutex;
+use redis::Commands;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use std::{env, fs};
 use thiserror::Error;
 use toml::Value;
 
+use crate::config::{CacheBackend, CacheConfig, RedisCacheConfig, RemoteCacheConfig};
 use crate::exclusion::PathExclusions;
-use crate::filesystem::{self, walk_pyfiles};
+use crate::filesystem::{self, walk_pyfiles_with_overlay, FileOverlay, WalkOptions};
+use crate::hash::fnv1a;
 
 #[derive(Error, Debug)]
 pub enum CacheError {
@@ -16,6 +22,16 @@ pub enum CacheError {
     DiskCache(#[from] DiskCacheError),
     #[error("Disk cache build error: {0}")]
     DiskCacheBuild(#[from] DiskCacheBuildError),
+    #[error("Remote cache error: {0}")]
+    Remote(String),
+    #[error("Remote cache is not configured. Set 'cache.remote.url' in tach.toml.")]
+    RemoteNotConfigured,
+    #[error("Redis cache error: {0}")]
+    Redis(#[from] redis::RedisError),
+    #[error("Redis cache is not configured. Set 'cache.redis.url' in tach.toml.")]
+    RedisNotConfigured,
+    #[error("Failed to serialize cache value: {0}")]
+    Serialization(#[from] serde_json::Error),
 }
 
 pub type Result<T> = std::result::Result<T, CacheError>;
@@ -25,14 +41,12 @@ struct CacheKey {
     hash: String,
 }
 
+// Keys are shared between machines through remote caches, so the hash must be stable
 impl FromIterator<u8> for CacheKey {
     fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
-        let mut hasher = DefaultHasher::new();
-        for item in iter {
-            item.hash(&mut hasher);
+        CacheKey {
+            hash: format!("{:016X}", fnv1a(iter)),
         }
-        let hash = format!("{:016X}", hasher.finish());
-        CacheKey { hash }
     }
 }
 
@@ -126,6 +140,7 @@ fn read_env_dependencies(env_dependencies: Vec<String>) -> impl Iterator<Item =
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_computation_cache_key(
     project_root: &PathBuf,
     source_roots: &[PathBuf],
@@ -134,12 +149,25 @@ pub fn create_computation_cache_key(
     file_dependencies: Vec<String>,
     env_dependencies: Vec<String>,
     _backend: String,
+    overlay: &FileOverlay,
 ) -> String {
-    // Exclusions are not applied when building cache keys
-    let exclusions = PathExclusions::new(project_root, &[], false).unwrap();
+    // Exclusions are not applied when building cache keys, and every Python file is walked
+    let exclusions = PathExclusions::none(project_root);
     let source_pyfiles = source_roots.iter().flat_map(|root| {
-        walk_pyfiles(root.to_str().unwrap(), &exclusions)
-            .flat_map(move |path| fs::read(root.join(path)).unwrap())
+        walk_pyfiles_with_overlay(
+            root.to_str().unwrap(),
+            &exclusions,
+            overlay,
+            WalkOptions::all(),
+        )
+        .flat_map(move |path| {
+            let file_path = root.join(path);
+            // Overlay contents replace the contents on disk, so unsaved buffers produce distinct keys
+            match overlay.get(&file_path) {
+                Some(content) => content.clone().into_bytes(),
+                None => fs::read(file_path).unwrap(),
+            }
+        })
     });
     let env_dependencies = read_env_dependencies(env_dependencies).flat_map(|d| d.into_bytes());
     let project_dependencies =
@@ -157,21 +185,558 @@ pub fn create_computation_cache_key(
     .hash
 }
 
+/// A cache shared across machines, keyed by the content hash of a computation.
+pub trait RemoteCache {
+    fn get(&self, cache_key: &str) -> Result<Option<ComputationCacheValue>>;
+    fn set(&self, cache_key: &str, value: &ComputationCacheValue) -> Result<()>;
+}
+
+/// A content-addressed store reachable over HTTP(S).
+/// Entries are fetched with `GET <url>/<key>` and stored with `PUT <url>/<key>`,
+/// which is compatible with simple cache servers as well as S3/GCS buckets behind an HTTP gateway.
+/// Per-file results are stored under `<url>/<namespace>/<key>`. Once any request fails,
+/// the cache is disabled for the rest of the process, as with Redis.
+pub struct HttpCache {
+    base_url: String,
+    token: Option<String>,
+    agent: ureq::Agent,
+    unavailable: AtomicBool,
+}
+
+const REMOTE_CACHE_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl HttpCache {
+    pub fn new(config: &RemoteCacheConfig) -> Self {
+        Self {
+            base_url: config.url.trim_end_matches('/').to_string(),
+            token: config.token_env.as_ref().and_then(|var| env::var(var).ok()),
+            agent: ureq::AgentBuilder::new()
+                .timeout(REMOTE_CACHE_TIMEOUT)
+                .build(),
+            unavailable: AtomicBool::new(false),
+        }
+    }
+
+    fn entry_url(&self, cache_key: &str) -> String {
+        format!("{}/{}", self.base_url, cache_key)
+    }
+
+    fn namespaced_url(&self, namespace: &str, cache_key: &str) -> String {
+        format!("{}/{}/{}", self.base_url, namespace, cache_key)
+    }
+
+    fn authorize(&self, request: ureq::Request) -> ureq::Request {
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.unavailable.load(Ordering::Relaxed)
+    }
+
+    /// Send a request, where a missing entry is `None` rather than an error
+    fn send(
+        &self,
+        f: impl FnOnce() -> std::result::Result<ureq::Response, ureq::Error>,
+    ) -> Result<Option<ureq::Response>> {
+        if self.unavailable.load(Ordering::Relaxed) {
+            return Err(CacheError::Remote(
+                "Remote cache is unavailable".to_string(),
+            ));
+        }
+        match f() {
+            Ok(response) => Ok(Some(response)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(err) => {
+                if !self.unavailable.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(
+                        "Remote cache is unavailable, continuing without it: {}",
+                        err
+                    );
+                }
+                Err(CacheError::Remote(err.to_string()))
+            }
+        }
+    }
+
+    fn get_url(&self, url: &str) -> Result<Option<String>> {
+        match self.send(|| self.authorize(self.agent.get(url)).call())? {
+            Some(response) => Ok(Some(
+                response
+                    .into_string()
+                    .map_err(|e| CacheError::Remote(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn put_url(&self, url: &str, body: &str) -> Result<()> {
+        self.send(|| {
+            self.authorize(self.agent.put(url))
+                .set("Content-Type", "application/json")
+                .send_string(body)
+        })?;
+        Ok(())
+    }
+
+    pub fn get_raw(&self, namespace: &str, cache_key: &str) -> Result<Option<String>> {
+        self.get_url(&self.namespaced_url(namespace, cache_key))
+    }
+
+    pub fn set_raw(&self, namespace: &str, cache_key: &str, value: &str) -> Result<()> {
+        self.put_url(&self.namespaced_url(namespace, cache_key), value)
+    }
+
+    /// Returns whether an entry was removed
+    pub fn delete_raw(&self, namespace: &str, cache_key: &str) -> Result<bool> {
+        let url = self.namespaced_url(namespace, cache_key);
+        Ok(self
+            .send(|| self.authorize(self.agent.delete(&url)).call())?
+            .is_some())
+    }
+}
+
+impl RemoteCache for HttpCache {
+    fn get(&self, cache_key: &str) -> Result<Option<ComputationCacheValue>> {
+        match self.get_url(&self.entry_url(cache_key))? {
+            Some(body) => Ok(Some(serde_json::from_str(&body)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&self, cache_key: &str, value: &ComputationCacheValue) -> Result<()> {
+        self.put_url(&self.entry_url(cache_key), &serde_json::to_string(value)?)
+    }
+}
+
+/// A Redis server shared by a team, for those who would rather not run object storage.
+/// Connections are opened lazily and kept in a small pool, so that lookups from several threads
+/// do not wait on each other. Once any request fails, the cache is disabled for the rest
+/// of the process so that an unreachable server does not stall every lookup.
+pub struct RedisCache {
+    client: redis::Client,
+    // Idle connections, there are at most as many as threads which used the cache at once
+    connections: Mutex<Vec<redis::Connection>>,
+    unavailable: AtomicBool,
+    key_prefix: String,
+    ttl_seconds: Option<u64>,
+}
+
+const COMPUTATION_NAMESPACE: &str = "computation";
+
+impl RedisCache {
+    pub fn new(config: &RedisCacheConfig) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(config.url.as_str())?,
+            connections: Mutex::new(Vec::new()),
+            unavailable: AtomicBool::new(false),
+            key_prefix: config.key_prefix.clone(),
+            ttl_seconds: config.ttl_seconds,
+        })
+    }
+
+    fn namespaced_key(&self, namespace: &str, cache_key: &str) -> String {
+        format!("{}:{}:{}", self.key_prefix, namespace, cache_key)
+    }
+
+    fn connect(&self) -> redis::RedisResult<redis::Connection> {
+        let connection = self
+            .client
+            .get_connection_with_timeout(REMOTE_CACHE_TIMEOUT)?;
+        connection.set_read_timeout(Some(REMOTE_CACHE_TIMEOUT))?;
+        connection.set_write_timeout(Some(REMOTE_CACHE_TIMEOUT))?;
+        Ok(connection)
+    }
+
+    fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<T>,
+    ) -> Result<T> {
+        if self.unavailable.load(Ordering::Relaxed) {
+            return Err(CacheError::Remote(
+                "Redis server is unavailable".to_string(),
+            ));
+        }
+        let idle_connection = self.connections.lock().pop();
+        let result = match idle_connection {
+            Some(connection) => Ok(connection),
+            None => self.connect(),
+        }
+        .and_then(|mut connection| f(&mut connection).map(|value| (connection, value)));
+        match result {
+            Ok((connection, value)) => {
+                self.connections.lock().push(connection);
+                Ok(value)
+            }
+            Err(err) => {
+                // The failed connection is dropped rather than returned to the pool (e.g. after a timeout
+                // or a broken pipe), and the remaining lookups miss instead of each waiting for the timeout
+                if !self.unavailable.swap(true, Ordering::Relaxed) {
+                    self.connections.lock().clear();
+                    tracing::warn!("Redis cache is unavailable, continuing without it: {}", err);
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.unavailable.load(Ordering::Relaxed)
+    }
+
+    pub fn get_raw(&self, namespace: &str, cache_key: &str) -> Result<Option<String>> {
+        let key = self.namespaced_key(namespace, cache_key);
+        self.with_connection(|connection| connection.get(key))
+    }
+
+    /// Returns whether an entry was removed
+    pub fn delete_raw(&self, namespace: &str, cache_key: &str) -> Result<bool> {
+        let key = self.namespaced_key(namespace, cache_key);
+        self.with_connection(|connection| connection.del::<_, usize>(key))
+            .map(|removed| removed > 0)
+    }
+
+    pub fn set_raw(&self, namespace: &str, cache_key: &str, value: &str) -> Result<()> {
+        let key = self.namespaced_key(namespace, cache_key);
+        self.with_connection(|connection| match self.ttl_seconds {
+            Some(ttl_seconds) => connection.set_ex(key, value, ttl_seconds),
+            None => connection.set(key, value),
+        })
+    }
+}
+
+impl RemoteCache for RedisCache {
+    fn get(&self, cache_key: &str) -> Result<Option<ComputationCacheValue>> {
+        match self.get_raw(COMPUTATION_NAMESPACE, cache_key)? {
+            Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&self, cache_key: &str, value: &ComputationCacheValue) -> Result<()> {
+        self.set_raw(
+            COMPUTATION_NAMESPACE,
+            cache_key,
+            &serde_json::to_string(value)?,
+        )
+    }
+}
+
+fn build_remote_cache(cache_config: &CacheConfig) -> Result<Option<Box<dyn RemoteCache>>> {
+    match cache_config.backend {
+        CacheBackend::Disk => Ok(None),
+        CacheBackend::Http => {
+            let remote_config = cache_config
+                .remote
+                .as_ref()
+                .ok_or(CacheError::RemoteNotConfigured)?;
+            Ok(Some(Box::new(HttpCache::new(remote_config))))
+        }
+        CacheBackend::Redis => {
+            let redis_config = cache_config
+                .redis
+                .as_ref()
+                .ok_or(CacheError::RedisNotConfigured)?;
+            Ok(Some(Box::new(RedisCache::new(redis_config)?)))
+        }
+    }
+}
+
+pub const IMPORTS_NAMESPACE: &str = "imports";
+pub const CHECK_NAMESPACE: &str = "check";
+
+enum FileResultStore {
+    Http(HttpCache),
+    Redis(RedisCache),
+    // Used for parsed imports when no remote backend is configured
+    Disk(DiskCache<String, String>),
+}
+
+/// Per-file results (parsed imports, check outcomes) shared through a remote cache, or kept on local disk.
+/// Entries are keyed by the content hash of the file, combined with a hash of the
+/// context the result was computed in (configuration, enabled checks, tach version).
+/// Lookups never fail, any error is treated as a cache miss.
+pub struct FileResultCache {
+    store: FileResultStore,
+    namespace: &'static str,
+    context: String,
+}
+
+impl std::fmt::Debug for FileResultCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileResultCache")
+            .field("namespace", &self.namespace)
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileResultCache {
+    pub fn new(
+        cache_config: &CacheConfig,
+        namespace: &'static str,
+        context: impl IntoIterator<Item = u8>,
+    ) -> Result<Option<Self>> {
+        let store = match cache_config.backend {
+            CacheBackend::Disk => return Ok(None),
+            CacheBackend::Http => FileResultStore::Http(HttpCache::new(
+                cache_config
+                    .remote
+                    .as_ref()
+                    .ok_or(CacheError::RemoteNotConfigured)?,
+            )),
+            CacheBackend::Redis => FileResultStore::Redis(RedisCache::new(
+                cache_config
+                    .redis
+                    .as_ref()
+                    .ok_or(CacheError::RedisNotConfigured)?,
+            )?),
+        };
+        Ok(Some(Self {
+            store,
+            namespace,
+            context: CacheKey::from_iter(env!("CARGO_PKG_VERSION").bytes().chain(context)).hash,
+        }))
+    }
+
+    /// A cache in the '.tach' directory of the project, regardless of the configured backend
+    pub fn on_disk(
+        project_root: &Path,
+        namespace: &'static str,
+        context: impl IntoIterator<Item = u8>,
+    ) -> Result<Self> {
+        let disk_cache = DiskCache::<String, String>::new(&format!("{}-results", namespace))
+            .set_disk_directory(project_root.join(CACHE_DIR).join("file-results"))
+            .build()?;
+        Ok(Self {
+            store: FileResultStore::Disk(disk_cache),
+            namespace,
+            context: CacheKey::from_iter(env!("CARGO_PKG_VERSION").bytes().chain(context)).hash,
+        })
+    }
+
+    fn entry_key(&self, file_key: &str, contents: &str) -> String {
+        CacheKey::from_iter(
+            self.context
+                .bytes()
+                .chain(file_key.bytes())
+                .chain(contents.bytes()),
+        )
+        .hash
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, file_key: &str, contents: &str) -> Option<T> {
+        let entry_key = self.entry_key(file_key, contents);
+        let value = match &self.store {
+            FileResultStore::Http(http) => http.get_raw(self.namespace, &entry_key).ok(),
+            FileResultStore::Redis(redis) => redis.get_raw(self.namespace, &entry_key).ok(),
+            FileResultStore::Disk(disk_cache) => disk_cache.cache_get(&entry_key).ok(),
+        };
+        value
+            .flatten()
+            .and_then(|value| serde_json::from_str(&value).ok())
+    }
+
+    /// Remove the entry for the file with the given contents. Unlike lookups, errors are returned.
+    pub fn invalidate(&self, file_key: &str, contents: &str) -> Result<bool> {
+        let entry_key = self.entry_key(file_key, contents);
+        match &self.store {
+            FileResultStore::Http(http) => http.delete_raw(self.namespace, &entry_key),
+            FileResultStore::Redis(redis) => redis.delete_raw(self.namespace, &entry_key),
+            FileResultStore::Disk(disk_cache) => Ok(disk_cache.cache_remove(&entry_key)?.is_some()),
+        }
+    }
+
+    pub fn set<T: Serialize>(&self, file_key: &str, contents: &str, value: &T) {
+        if let Ok(value) = serde_json::to_string(value) {
+            let entry_key = self.entry_key(file_key, contents);
+            let _ = match &self.store {
+                FileResultStore::Http(http) => http.set_raw(self.namespace, &entry_key, &value),
+                FileResultStore::Redis(redis) => redis.set_raw(self.namespace, &entry_key, &value),
+                FileResultStore::Disk(disk_cache) => disk_cache
+                    .cache_set(entry_key, value)
+                    .map(|_| ())
+                    .map_err(CacheError::from),
+            };
+        }
+    }
+}
+
 pub fn check_computation_cache(
     project_root: &PathBuf,
     cache_key: String,
+    cache_config: &CacheConfig,
 ) -> Result<Option<ComputationCacheValue>> {
     let cache = build_computation_cache(project_root)?;
 
-    Ok(cache.cache_get(&cache_key)?)
+    if let Some(value) = cache.cache_get(&cache_key)? {
+        return Ok(Some(value));
+    }
+
+    // The local cache always takes precedence, remote failures are treated as a cache miss
+    if let Some(remote_cache) = build_remote_cache(cache_config)? {
+        match remote_cache.get(&cache_key) {
+            Ok(Some(value)) => {
+                cache.cache_set(cache_key, value.clone())?;
+                return Ok(Some(value));
+            }
+            // Failed requests are reported once, when the remote cache is disabled
+            Ok(None) | Err(_) => {}
+        }
+    }
+
+    Ok(None)
 }
 
 pub fn update_computation_cache(
     project_root: &PathBuf,
     cache_key: String,
     value: ComputationCacheValue,
+    cache_config: &CacheConfig,
 ) -> Result<Option<ComputationCacheValue>> {
     let cache = build_computation_cache(project_root)?;
 
+    if let Some(remote_cache) = build_remote_cache(cache_config)? {
+        // Failures are reported when the remote cache is disabled, and do not prevent caching locally
+        let _ = remote_cache.set(&cache_key, &value);
+    }
+
     Ok(cache.cache_set(cache_key, value)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    /// Serve GET, PUT and DELETE of entries held in memory, one request per connection
+    fn serve_http_store() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cache", listener.local_addr().unwrap());
+        let store: Arc<Mutex<HashMap<String, String>>> = Arc::default();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap().to_string());
+                let mut store = store.lock();
+                let (status, body) = match method {
+                    "GET" => match store.get(&path) {
+                        Some(value) => ("200 OK", value.clone()),
+                        None => ("404 Not Found", String::new()),
+                    },
+                    "PUT" => {
+                        store.insert(path, String::from_utf8(body).unwrap());
+                        ("200 OK", String::new())
+                    }
+                    "DELETE" => match store.remove(&path) {
+                        Some(_) => ("200 OK", String::new()),
+                        None => ("404 Not Found", String::new()),
+                    },
+                    _ => ("405 Method Not Allowed", String::new()),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    fn http_cache_config(url: String) -> CacheConfig {
+        CacheConfig {
+            backend: CacheBackend::Http,
+            remote: Some(RemoteCacheConfig {
+                url,
+                token_env: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_file_results_through_http() {
+        let cache = FileResultCache::new(
+            &http_cache_config(serve_http_store()),
+            IMPORTS_NAMESPACE,
+            *b"",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(cache.get::<Vec<String>>("a.py", "import b"), None);
+        cache.set("a.py", "import b", &vec!["b".to_string()]);
+        assert_eq!(
+            cache.get::<Vec<String>>("a.py", "import b"),
+            Some(vec!["b".to_string()])
+        );
+        // Entries are keyed by contents
+        assert_eq!(cache.get::<Vec<String>>("a.py", "import c"), None);
+        assert!(cache.invalidate("a.py", "import b").unwrap());
+        assert!(!cache.invalidate("a.py", "import b").unwrap());
+        assert_eq!(cache.get::<Vec<String>>("a.py", "import b"), None);
+    }
+
+    #[test]
+    fn test_unreachable_http_is_disabled() {
+        let http = HttpCache::new(&RemoteCacheConfig {
+            url: "http://127.0.0.1:1".to_string(),
+            token_env: None,
+        });
+        assert!(http.get_raw(IMPORTS_NAMESPACE, "key").is_err());
+        assert!(!http.is_available());
+        assert!(http.set_raw(IMPORTS_NAMESPACE, "key", "value").is_err());
+    }
+
+    #[test]
+    fn test_cache_key_is_stable() {
+        // Keys must not change between builds, since they are shared between machines
+        assert_eq!(CacheKey::from_iter(*b"foobar").hash, "85944171F73967E8");
+    }
+
+    #[test]
+    fn test_unreachable_redis_is_disabled() {
+        let redis = RedisCache::new(&RedisCacheConfig {
+            url: "redis://127.0.0.1:1".to_string(),
+            key_prefix: "tach".to_string(),
+            ttl_seconds: None,
+        })
+        .unwrap();
+        assert!(redis.get_raw(IMPORTS_NAMESPACE, "key").is_err());
+        assert!(!redis.is_available());
+
+        // Later requests fail without trying to connect again
+        let start = Instant::now();
+        assert!(redis.set_raw(IMPORTS_NAMESPACE, "key", "value").is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use ruff_python_ast::{Expr, Mod, Parameters, Stmt};
+use ruff_source_file::LineIndex;
+use ruff_text_size::Ranged;
+
+use crate::config::{ModuleConfig, RuleSetting};
+use crate::diagnostics::{CodeDiagnostic, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails};
+use crate::filesystem::{
+    module_to_file_path, read_file_content_with_overlay, relative_to, FileOverlay,
+};
+use crate::python::parsing::parse_python_source;
+
+/// The parameters of a function, excluding '*args' and '**kwargs'
+#[derive(Debug, Clone, PartialEq)]
+struct Signature {
+    // (name, has_default)
+    parameters: Vec<(String, bool)>,
+}
+
+impl Signature {
+    fn new(parameters: &Parameters) -> Self {
+        Self {
+            parameters: parameters
+                .iter_non_variadic_params()
+                .map(|p| (p.parameter.name.to_string(), p.default.is_some()))
+                .collect(),
+        }
+    }
+
+    /// The signature of a method as called on an instance, without 'self' or 'cls'
+    fn unbound(mut self) -> Self {
+        if self
+            .parameters
+            .first()
+            .is_some_and(|(name, _)| name == "self" || name == "cls")
+        {
+            self.parameters.remove(0);
+        }
+        self
+    }
+
+    /// Whether this signature can stand in for `expected`: it must take the same parameters in
+    /// the same order, and any extra parameters must have defaults
+    fn conforms_to(&self, expected: &Signature) -> bool {
+        let parameters: Vec<&str> = self
+            .parameters
+            .iter()
+            .filter(|(name, has_default)| {
+                !has_default || expected.parameters.iter().any(|(other, _)| other == name)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        parameters
+            .iter()
+            .eq(expected.parameters.iter().map(|(name, _)| name.as_str()))
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parameters: Vec<&str> = self
+            .parameters
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        write!(f, "({})", parameters.join(", "))
+    }
+}
+
+#[derive(Debug)]
+enum Member {
+    Function(Signature),
+    Other,
+}
+
+/// The names a module defines at the top level, with the line each is defined on
+struct ModuleNamespace {
+    file_path: PathBuf,
+    members: HashMap<String, (Member, usize)>,
+}
+
+fn parse_module(file_path: &Path, overlay: &FileOverlay) -> Option<(Vec<Stmt>, String)> {
+    let source = read_file_content_with_overlay(file_path, overlay).ok()?;
+    let Ok(Mod::Module(ast)) = parse_python_source(&source) else {
+        return None;
+    };
+    Some((ast.body, source))
+}
+
+fn assigned_names(stmt: &Stmt) -> Vec<String> {
+    let targets: Vec<&Expr> = match stmt {
+        Stmt::Assign(node) => node.targets.iter().collect(),
+        Stmt::AnnAssign(node) => vec![node.target.as_ref()],
+        _ => vec![],
+    };
+    targets
+        .into_iter()
+        .filter_map(|target| match target {
+            Expr::Name(name) => Some(name.id.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names exported by the interface: a module's `__all__`, or else its public top-level
+/// definitions. For a class (such as a `Protocol`), its public methods and annotated attributes.
+fn required_members(body: &[Stmt], class_name: Option<&str>) -> Option<Vec<(String, Member)>> {
+    let Some(class_name) = class_name else {
+        let exported: Option<Vec<String>> = body.iter().find_map(|stmt| match stmt {
+            Stmt::Assign(node) if assigned_names(stmt).iter().any(|name| name == "__all__") => {
+                match node.value.as_ref() {
+                    Expr::List(list) => Some(
+                        list.elts
+                            .iter()
+                            .filter_map(|element| match element {
+                                Expr::StringLiteral(s) => Some(s.value.to_string()),
+                                _ => None,
+                            })
+                            .collect(),
+                    ),
+                    _ => None,
+                }
+            }
+            _ => None,
+        });
+        let mut members = vec![];
+        for stmt in body {
+            let defined = match stmt {
+                Stmt::FunctionDef(node) => {
+                    vec![(
+                        node.name.to_string(),
+                        Member::Function(Signature::new(&node.parameters)),
+                    )]
+                }
+                Stmt::ClassDef(node) => vec![(node.name.to_string(), Member::Other)],
+                _ => assigned_names(stmt)
+                    .into_iter()
+                    .map(|name| (name, Member::Other))
+                    .collect(),
+            };
+            members.extend(defined.into_iter().filter(|(name, _)| match &exported {
+                Some(exported) => exported.contains(name),
+                None => !name.starts_with('_'),
+            }));
+        }
+        return Some(members);
+    };
+
+    let class = body.iter().find_map(|stmt| match stmt {
+        Stmt::ClassDef(node) if node.name.as_str() == class_name => Some(node),
+        _ => None,
+    })?;
+    let mut members = vec![];
+    for stmt in &class.body {
+        match stmt {
+            Stmt::FunctionDef(node) if !node.name.as_str().starts_with('_') => members.push((
+                node.name.to_string(),
+                Member::Function(Signature::new(&node.parameters).unbound()),
+            )),
+            Stmt::AnnAssign(_) => members.extend(
+                assigned_names(stmt)
+                    .into_iter()
+                    .filter(|name| !name.starts_with('_'))
+                    .map(|name| (name, Member::Other)),
+            ),
+            _ => {}
+        }
+    }
+    Some(members)
+}
+
+fn module_namespace(file_path: PathBuf, overlay: &FileOverlay) -> Option<ModuleNamespace> {
+    let (body, source) = parse_module(&file_path, overlay)?;
+    let line_index = LineIndex::from_source_text(&source);
+    let mut members = HashMap::new();
+    for stmt in &body {
+        let line_number = line_index.line_index(stmt.start()).get();
+        let defined: Vec<(String, Member)> = match stmt {
+            Stmt::FunctionDef(node) => vec![(
+                node.name.to_string(),
+                Member::Function(Signature::new(&node.parameters)),
+            )],
+            Stmt::ClassDef(node) => vec![(node.name.to_string(), Member::Other)],
+            // Re-exported names are accepted without checking their signatures
+            Stmt::Import(node) => node
+                .names
+                .iter()
+                .map(|alias| {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name);
+                    let name = name.as_str().split('.').next().unwrap_or_default();
+                    (name.to_string(), Member::Other)
+                })
+                .collect(),
+            Stmt::ImportFrom(node) => node
+                .names
+                .iter()
+                .map(|alias| {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name);
+                    (name.to_string(), Member::Other)
+                })
+                .collect(),
+            _ => assigned_names(stmt)
+                .into_iter()
+                .map(|name| (name, Member::Other))
+                .collect(),
+        };
+        for (name, member) in defined {
+            members.insert(name, (member, line_number));
+        }
+    }
+    Some(ModuleNamespace { file_path, members })
+}
+
+/// Report modules which declare that they implement an interface (with `implements`), but which
+/// do not define each of its members, or define functions whose parameters do not match
+pub fn check_conformance(
+    project_root: &Path,
+    source_roots: &[PathBuf],
+    modules: &[ModuleConfig],
+    overlay: &FileOverlay,
+    setting: &RuleSetting,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for module in modules {
+        let Some(interface) = &module.implements else {
+            continue;
+        };
+        let required = module_to_file_path(source_roots, interface, true).and_then(|resolved| {
+            let (body, _) = parse_module(&resolved.file_path, overlay)?;
+            required_members(&body, resolved.member_name.as_deref())
+        });
+        let Some(required) = required else {
+            diagnostics.extend(
+                Diagnostic::new_global_error(DiagnosticDetails::Configuration(
+                    ConfigurationDiagnostic::UnknownInterface {
+                        module_path: module.path.clone(),
+                        interface: interface.clone(),
+                    },
+                ))
+                .with_rule_setting(setting),
+            );
+            continue;
+        };
+        // Unresolved modules are reported when validating the project's modules
+        let Some(namespace) = module_to_file_path(source_roots, &module.path, false)
+            .and_then(|resolved| module_namespace(resolved.file_path, overlay))
+        else {
+            continue;
+        };
+        let file_path = relative_to(&namespace.file_path, project_root)
+            .unwrap_or_else(|_| namespace.file_path.clone());
+
+        for (member, expected) in required {
+            let (line_number, details) = match (namespace.members.get(&member), &expected) {
+                (None, _) => (
+                    1,
+                    CodeDiagnostic::MissingInterfaceMember {
+                        module_path: module.path.clone(),
+                        interface: interface.clone(),
+                        member,
+                    },
+                ),
+                (Some((Member::Function(found), line_number)), Member::Function(expected))
+                    if !found.conforms_to(expected) =>
+                {
+                    (
+                        *line_number,
+                        CodeDiagnostic::InterfaceSignatureMismatch {
+                            module_path: module.path.clone(),
+                            interface: interface.clone(),
+                            member,
+                            expected: expected.to_string(),
+                            found: found.to_string(),
+                        },
+                    )
+                }
+                _ => continue,
+            };
+            diagnostics.extend(
+                Diagnostic::new_located_error(
+                    file_path.clone(),
+                    line_number,
+                    None,
+                    DiagnosticDetails::Code(details),
+                )
+                .with_rule_setting(setting),
+            );
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn check(files: &[(&str, &str)], implements: &str) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        for (path, contents) in files {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let module = ModuleConfig {
+            implements: Some(implements.to_string()),
+            ..ModuleConfig::new("adapters.stripe", false)
+        };
+        check_conformance(
+            temp_dir.path(),
+            &[temp_dir.path().to_path_buf()],
+            &[module],
+            &FileOverlay::new(),
+            &RuleSetting::Error,
+        )
+        .iter()
+        .map(Diagnostic::message)
+        .collect()
+    }
+
+    const PORT: &str = r#"
+from typing import Protocol
+
+class PaymentPort(Protocol):
+    currency: str
+
+    def charge(self, amount, customer_id): ...
+    def refund(self, charge_id, reason=None): ...
+    def _audit(self): ...
+"#;
+
+    #[test]
+    fn test_conforming_module() {
+        let adapter = r#"
+from adapters.config import currency
+
+def charge(amount, customer_id, idempotency_key=None):
+    pass
+
+def refund(charge_id, reason=None):
+    pass
+"#;
+        assert!(check(
+            &[("core/ports.py", PORT), ("adapters/stripe.py", adapter)],
+            "core.ports.PaymentPort",
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_nonconforming_module() {
+        let adapter = r#"
+currency = "usd"
+
+def charge(customer_id, amount):
+    pass
+"#;
+        assert_eq!(
+            check(
+                &[("core/ports.py", PORT), ("adapters/stripe.py", adapter)],
+                "core.ports.PaymentPort",
+            ),
+            vec![
+                "'charge' in module 'adapters.stripe' does not match 'core.ports.PaymentPort': expected parameters (amount, customer_id), found (customer_id, amount).",
+                "Module 'adapters.stripe' implements 'core.ports.PaymentPort', but does not define 'refund'.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_interface() {
+        let port = r#"
+__all__ = ["charge"]
+
+def charge(amount): ...
+def helper(): ...
+"#;
+        assert_eq!(
+            check(
+                &[
+                    ("core/ports.py", port),
+                    ("adapters/stripe.py", "def helper(): ...\n")
+                ],
+                "core.ports",
+            ),
+            vec!["Module 'adapters.stripe' implements 'core.ports', but does not define 'charge'."]
+        );
+    }
+
+    #[test]
+    fn test_unknown_interface() {
+        assert_eq!(
+            check(
+                &[("core/ports.py", PORT), ("adapters/stripe.py", "")],
+                "core.ports.RefundPort",
+            ),
+            vec!["Module 'adapters.stripe' implements 'core.ports.RefundPort', which could not be found."]
+        );
+    }
+}
@@ -1,4 +1,7 @@
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::config::ProjectConfig;
 
 use crate::dependencies::import::{with_distribution_names, ExternalImportWithDistributionNames};
 use crate::diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails};
@@ -6,21 +9,31 @@ use crate::diagnostics::{FileChecker, Result as DiagnosticResult};
 use crate::external::parsing::ProjectInfo;
 use crate::processors::file_module::FileModule;
 
+/// Standard library module names for each Python version targeted by the project.
+/// The entry for `None` holds the modules of the running interpreter.
+pub type StdlibModules = HashMap<Option<String>, HashSet<String>>;
+
 pub struct ExternalDependencyChecker<'a> {
+    project_root: &'a Path,
+    project_config: &'a ProjectConfig,
     project_info: &'a ProjectInfo,
     module_mappings: &'a HashMap<String, Vec<String>>,
-    stdlib_modules: &'a HashSet<String>,
+    stdlib_modules: &'a StdlibModules,
     excluded_external_modules: &'a HashSet<String>,
 }
 
 impl<'a> ExternalDependencyChecker<'a> {
     pub fn new(
+        project_root: &'a Path,
+        project_config: &'a ProjectConfig,
         project_info: &'a ProjectInfo,
         module_mappings: &'a HashMap<String, Vec<String>>,
-        stdlib_modules: &'a HashSet<String>,
+        stdlib_modules: &'a StdlibModules,
         excluded_external_modules: &'a HashSet<String>,
     ) -> Self {
         Self {
+            project_root,
+            project_config,
             project_info,
             module_mappings,
             stdlib_modules,
@@ -28,18 +41,30 @@ impl<'a> ExternalDependencyChecker<'a> {
         }
     }
 
+    /// The standard library of the Python version targeted by the file
+    fn stdlib_modules_for(&self, processed_file: &FileModule<'a>) -> Option<&'a HashSet<String>> {
+        let python_version = self
+            .project_config
+            .python_version_for_file(self.project_root, processed_file.file_path())
+            .map(|version| version.to_string());
+        self.stdlib_modules
+            .get(&python_version)
+            .or_else(|| self.stdlib_modules.get(&None))
+    }
+
     fn check_import(
         &'a self,
         import: ExternalImportWithDistributionNames<'a>,
         processed_file: &FileModule<'a>,
+        stdlib_modules: Option<&HashSet<String>>,
     ) -> Option<Diagnostic> {
         if import
             .distribution_names
             .iter()
             .any(|dist_name| self.excluded_external_modules.contains(dist_name))
-            || self
-                .stdlib_modules
-                .contains(&import.import.top_level_module_name().to_string())
+            || stdlib_modules.is_some_and(|stdlib_modules| {
+                stdlib_modules.contains(import.import.top_level_module_name())
+            })
         {
             return None;
         }
@@ -50,14 +75,17 @@ impl<'a> ExternalDependencyChecker<'a> {
             .any(|dist_name| self.project_info.dependencies.contains(dist_name));
 
         if !is_declared {
-            Some(Diagnostic::new_located_error(
-                processed_file.relative_file_path().to_path_buf(),
-                processed_file.line_number(import.import.alias_offset),
-                Some(processed_file.line_number(import.import.import_offset)),
-                DiagnosticDetails::Code(CodeDiagnostic::UndeclaredExternalDependency {
-                    dependency: import.import.top_level_module_name().to_string(),
-                }),
-            ))
+            Some(
+                Diagnostic::new_located_error(
+                    processed_file.relative_file_path().to_path_buf(),
+                    processed_file.line_number(import.import.alias_offset),
+                    Some(processed_file.line_number(import.import.import_offset)),
+                    DiagnosticDetails::Code(CodeDiagnostic::UndeclaredExternalDependency {
+                        dependency: import.import.top_level_module_name().to_string(),
+                    }),
+                )
+                .with_span(processed_file.source_span(import.import.alias_range())),
+            )
         } else {
             None
         }
@@ -70,8 +98,9 @@ impl<'a> FileChecker<'a> for ExternalDependencyChecker<'a> {
 
     fn check(&'a self, processed_file: &Self::ProcessedFile) -> DiagnosticResult<Self::Output> {
         let mut diagnostics = Vec::new();
+        let stdlib_modules = self.stdlib_modules_for(processed_file);
         for import in with_distribution_names(processed_file.imports(), self.module_mappings) {
-            if let Some(diagnostic) = self.check_import(import, processed_file) {
+            if let Some(diagnostic) = self.check_import(import, processed_file, stdlib_modules) {
                 diagnostics.push(diagnostic);
             }
         }
@@ -0,0 +1,112 @@
+use crate::config::{RuleSetting, RulesConfig};
+use crate::dependencies::Dependency;
+use crate::diagnostics::{Diagnostic, Severity};
+
+/// Apply the rule for the context of a dependency (e.g. an import inside a function) to the diagnostics it caused.
+/// Configuration diagnostics are always kept as they are.
+pub fn apply_import_context_rule(
+    rules: &RulesConfig,
+    dependency: &Dependency,
+    diagnostics: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    match rules.import_context_setting(dependency.context()) {
+        RuleSetting::Error => diagnostics,
+        RuleSetting::Warn => diagnostics
+            .into_iter()
+            .map(|mut diagnostic| {
+                if diagnostic.is_code() {
+                    diagnostic.set_severity(Severity::Warning);
+                }
+                diagnostic
+            })
+            .collect(),
+        RuleSetting::Off => diagnostics
+            .into_iter()
+            .filter(|diagnostic| !diagnostic.is_code())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ruff_text_size::TextSize;
+
+    use super::*;
+    use crate::dependencies::{ImportContext, NormalizedImport};
+    use crate::diagnostics::{CodeDiagnostic, ConfigurationDiagnostic, DiagnosticDetails};
+
+    fn import_in(context: ImportContext) -> Dependency {
+        Dependency::Import(NormalizedImport {
+            module_path: "b.member".to_string(),
+            alias_path: None,
+            import_offset: TextSize::default(),
+            alias_offset: TextSize::default(),
+            alias_end: TextSize::default(),
+            is_absolute: true,
+            context,
+            platforms: None,
+        })
+    }
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::new_located_error(
+                PathBuf::from("a.py"),
+                1,
+                None,
+                DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                    dependency: "b.member".to_string(),
+                    usage_module: "a".to_string(),
+                    definition_module: "b".to_string(),
+                }),
+            ),
+            Diagnostic::new_global_error(DiagnosticDetails::Configuration(
+                ConfigurationDiagnostic::ModuleConfigNotFound {
+                    module_path: "b.member".to_string(),
+                },
+            )),
+        ]
+    }
+
+    #[test]
+    fn test_import_context_rules() {
+        let rules = RulesConfig {
+            function_imports: RuleSetting::Warn,
+            try_except_imports: RuleSetting::Off,
+            ..RulesConfig::default()
+        };
+
+        let top_level =
+            apply_import_context_rule(&rules, &import_in(ImportContext::TopLevel), diagnostics());
+        assert!(top_level.iter().all(|diagnostic| diagnostic.is_error()));
+
+        let function =
+            apply_import_context_rule(&rules, &import_in(ImportContext::Function), diagnostics());
+        assert!(function[0].is_warning() && function[1].is_error());
+
+        let try_except =
+            apply_import_context_rule(&rules, &import_in(ImportContext::TryExcept), diagnostics());
+        assert_eq!(try_except.len(), 1);
+        assert!(try_except[0].is_configuration());
+    }
+
+    #[test]
+    fn test_conftest_import_context() {
+        let rules = RulesConfig {
+            conftest_imports: RuleSetting::Warn,
+            type_checking_imports: RuleSetting::Off,
+            ..RulesConfig::default()
+        };
+
+        let conftest =
+            apply_import_context_rule(&rules, &import_in(ImportContext::Conftest), diagnostics());
+        assert!(conftest[0].is_warning() && conftest[1].is_error());
+
+        // Imports under 'if TYPE_CHECKING:' in a conftest file still use 'type_checking_imports'
+        let context = ImportContext::TypeChecking.max(ImportContext::Conftest);
+        let type_checking = apply_import_context_rule(&rules, &import_in(context), diagnostics());
+        assert_eq!(type_checking.len(), 1);
+    }
+}
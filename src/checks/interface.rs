@@ -1,18 +1,22 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use crate::config::root_module::RootModuleTreatment;
-use crate::config::{ModuleConfig, ProjectConfig};
+use crate::config::{ModuleConfig, ProjectConfig, RulesConfig};
 use crate::dependencies::Dependency;
 use crate::diagnostics::{
     CodeDiagnostic, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails, FileChecker,
     Result as DiagnosticResult,
 };
+use crate::filesystem::FileOverlay;
 use crate::interfaces::compiled::CompiledInterfaces;
 use crate::interfaces::data_types::{TypeCheckCache, TypeCheckResult};
 use crate::interfaces::error::InterfaceError;
 use crate::modules::ModuleTree;
 use crate::processors::file_module::FileModule;
 
+use super::apply_import_context_rule;
+
 #[derive(Debug)]
 pub enum InterfaceCheckResult {
     Exposed { type_check_result: TypeCheckResult },
@@ -25,7 +29,7 @@ pub struct InterfaceChecker<'a> {
     project_config: &'a ProjectConfig,
     module_tree: &'a ModuleTree,
     interfaces: CompiledInterfaces,
-    type_check_cache: Option<TypeCheckCache>,
+    type_check_cache: Option<Arc<TypeCheckCache>>,
 }
 
 impl<'a> InterfaceChecker<'a> {
@@ -44,12 +48,20 @@ impl<'a> InterfaceChecker<'a> {
         mut self,
         modules: &[ModuleConfig],
         source_roots: &[PathBuf],
+        overlay: &FileOverlay,
     ) -> Result<Self, InterfaceError> {
-        let type_check_cache = TypeCheckCache::build(&self.interfaces, modules, source_roots)?;
-        self.type_check_cache = Some(type_check_cache);
+        let type_check_cache =
+            TypeCheckCache::build(&self.interfaces, modules, source_roots, overlay)?;
+        self.type_check_cache = Some(Arc::new(type_check_cache));
         Ok(self)
     }
 
+    /// Reuse a type check cache which was built for the same interfaces and modules
+    pub fn with_shared_type_check_cache(mut self, type_check_cache: Arc<TypeCheckCache>) -> Self {
+        self.type_check_cache = Some(type_check_cache);
+        self
+    }
+
     fn check_member(&self, member: &str, module_path: &str) -> InterfaceCheckResult {
         if member.is_empty() {
             return InterfaceCheckResult::TopLevelModule;
@@ -81,10 +93,50 @@ impl<'a> InterfaceChecker<'a> {
         }
     }
 
+    /// Members exposed by an experimental interface cannot be used by stable modules.
+    /// This is only reported when the module itself is stable enough, to avoid reporting the same import twice.
+    fn check_stability(
+        &self,
+        dependency: &Dependency,
+        file_module: &FileModule,
+        dependency_module_config: &ModuleConfig,
+        import_member: &str,
+        rules: &RulesConfig,
+    ) -> Option<Diagnostic> {
+        let usage_stability = file_module.module_config().stability?;
+        let member_stability = self
+            .interfaces
+            .get_stability(&dependency_module_config.path, import_member);
+        if !usage_stability.permits(dependency_module_config.stability)
+            || usage_stability.permits(member_stability)
+        {
+            return None;
+        }
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::UnstableDependency {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module.module_config().path.to_string(),
+                usage_stability: usage_stability.as_str().to_string(),
+                definition_module: dependency_module_config.path.to_string(),
+                definition_stability: member_stability
+                    .map(|stability| stability.as_str().to_string())
+                    .unwrap_or_default(),
+            }),
+        )
+        .with_rule_setting(&rules.unstable_dependencies)
+    }
+
     fn check_interfaces(
         &self,
         dependency: &Dependency,
         file_module: &FileModule,
+        rules: &RulesConfig,
     ) -> DiagnosticResult<Vec<Diagnostic>> {
         if let Some(dependency_module_config) = self
             .module_tree
@@ -108,8 +160,8 @@ impl<'a> InterfaceChecker<'a> {
                 .and_then(|s| s.strip_prefix('.'))
                 .unwrap_or("");
             let check_result = self.check_member(import_member, &dependency_module_config.path);
-            match check_result {
-                InterfaceCheckResult::NotExposed => Ok(vec![Diagnostic::new_located_error(
+            let mut diagnostics: Vec<Diagnostic> = match check_result {
+                InterfaceCheckResult::NotExposed => Diagnostic::new_located_error(
                     file_module.relative_file_path().to_path_buf(),
                     file_module.line_number(dependency.offset()),
                     dependency
@@ -120,10 +172,13 @@ impl<'a> InterfaceChecker<'a> {
                         usage_module: file_module.module_config().path.to_string(),
                         definition_module: dependency_module_config.path.to_string(),
                     }),
-                )]),
+                )
+                .with_rule_setting(&rules.interface_violations)
+                .into_iter()
+                .collect(),
                 InterfaceCheckResult::Exposed {
                     type_check_result: TypeCheckResult::DidNotMatchInterface { expected },
-                } => Ok(vec![Diagnostic::new_located_error(
+                } => Diagnostic::new_located_error(
                     file_module.relative_file_path().to_path_buf(),
                     file_module.line_number(dependency.offset()),
                     dependency
@@ -135,7 +190,10 @@ impl<'a> InterfaceChecker<'a> {
                         definition_module: dependency_module_config.path.to_string(),
                         expected_data_type: expected.to_string(),
                     }),
-                )]),
+                )
+                .with_rule_setting(&rules.interface_violations)
+                .into_iter()
+                .collect(),
                 InterfaceCheckResult::Exposed {
                     type_check_result: TypeCheckResult::MatchedInterface { .. },
                 }
@@ -143,8 +201,16 @@ impl<'a> InterfaceChecker<'a> {
                     type_check_result: TypeCheckResult::Unknown,
                 }
                 | InterfaceCheckResult::NoInterfaces
-                | InterfaceCheckResult::TopLevelModule => Ok(vec![]),
-            }
+                | InterfaceCheckResult::TopLevelModule => vec![],
+            };
+            diagnostics.extend(self.check_stability(
+                dependency,
+                file_module,
+                dependency_module_config,
+                import_member,
+                rules,
+            ));
+            Ok(diagnostics)
         } else {
             Ok(vec![Diagnostic::new_global_error(
                 DiagnosticDetails::Configuration(ConfigurationDiagnostic::ModuleConfigNotFound {
@@ -160,9 +226,19 @@ impl<'a> FileChecker<'a> for InterfaceChecker<'a> {
     type Output = Vec<Diagnostic>;
 
     fn check(&'a self, input: &Self::ProcessedFile) -> DiagnosticResult<Self::Output> {
+        let rules = self.project_config.rules.for_module(input.module_config());
         let mut diagnostics = vec![];
         for dependency in input.dependencies.iter() {
-            diagnostics.extend(self.check_interfaces(dependency, input)?);
+            let span = input.source_span(dependency.range());
+            diagnostics.extend(
+                apply_import_context_rule(
+                    &rules,
+                    dependency,
+                    self.check_interfaces(dependency, input, &rules)?,
+                )
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_span(span)),
+            );
         }
 
         Ok(diagnostics)
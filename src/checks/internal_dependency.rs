@@ -1,14 +1,26 @@
 use crate::{
-    config::{root_module::RootModuleTreatment, DependencyConfig, ModuleConfig, ProjectConfig},
-    dependencies::Dependency,
+    config::{
+        modules::current_date, root_module::RootModuleTreatment, LocatedDomainConfig, ModuleConfig,
+        ProjectConfig, ReexportResolution, RulesConfig,
+    },
+    dependencies::{
+        Dependency, ImportContext, ImportStyle, ImportStyleUsage, UnresolvedRelativeImport,
+    },
     diagnostics::{
         CodeDiagnostic, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails, FileChecker,
         Result as DiagnosticResult,
     },
+    filesystem::{self as fs, FileOverlay},
     modules::ModuleTree,
-    processors::FileModule,
+    processors::{FileModule, ReexportResolver},
 };
-use std::path::Path;
+use ruff_text_size::TextRange;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::apply_import_context_rule;
+use super::rule_engine::{ModuleEdge, RuleEngine};
 
 #[derive(Debug)]
 enum LayerCheckResult {
@@ -22,6 +34,13 @@ enum LayerCheckResult {
 pub struct InternalDependencyChecker<'a> {
     project_config: &'a ProjectConfig,
     module_tree: &'a ModuleTree,
+    reexport_resolver: Option<ReexportResolver<'a>>,
+    module_domains: HashMap<&'a str, &'a LocatedDomainConfig>,
+    rule_engine: Option<&'a RuleEngine<'a>>,
+    // Every dependency between modules, when a rule needs to follow indirect dependencies
+    module_edges: Option<Mutex<Vec<ModuleEdge>>>,
+    // Deprecated dependencies become errors once their grace period has ended
+    today: String,
 }
 
 impl<'a> InternalDependencyChecker<'a> {
@@ -29,7 +48,40 @@ impl<'a> InternalDependencyChecker<'a> {
         Self {
             project_config,
             module_tree,
+            reexport_resolver: None,
+            module_domains: project_config.module_domains(),
+            rule_engine: None,
+            module_edges: None,
+            today: current_date(),
+        }
+    }
+
+    pub fn with_rule_engine(mut self, rule_engine: &'a RuleEngine<'a>) -> Self {
+        self.module_edges = rule_engine.has_mediation_rules().then(Mutex::default);
+        self.rule_engine = Some(rule_engine);
+        self
+    }
+
+    pub fn take_module_edges(&self) -> Vec<ModuleEdge> {
+        self.module_edges
+            .as_ref()
+            .map(|module_edges| std::mem::take(&mut *module_edges.lock().unwrap()))
+            .unwrap_or_default()
+    }
+
+    pub fn with_reexport_resolution(
+        mut self,
+        source_roots: &'a [PathBuf],
+        overlay: &'a FileOverlay,
+    ) -> Self {
+        if self.project_config.rules.reexport_resolution == ReexportResolution::Follow {
+            self.reexport_resolver = Some(ReexportResolver::new(
+                source_roots,
+                self.project_config.rules.max_reexport_depth,
+                overlay,
+            ));
         }
+        self
     }
 
     fn check_layers(
@@ -95,16 +147,261 @@ impl<'a> InternalDependencyChecker<'a> {
         }
     }
 
+    fn check_internal_structure(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        module_config: &ModuleConfig,
+    ) -> Option<Diagnostic> {
+        if module_config.internal_structure.is_empty() {
+            return None;
+        }
+
+        let file_mod_path = fs::normalized_file_to_module_path(
+            &[file_module.file.source_root.to_path_buf()],
+            file_module.file_path(),
+            &self.project_config.module_path_normalizers,
+        )
+        .ok()?;
+        // Files and imports outside of every entry are not restricted
+        let usage_entry = module_config.internal_structure_entry(&file_mod_path)?;
+        let definition_entry = module_config.internal_structure_entry(dependency.module_path())?;
+        if usage_entry == definition_entry
+            || module_config.internal_structure[usage_entry]
+                .iter()
+                .any(|allowed| allowed == definition_entry)
+        {
+            return None;
+        }
+
+        Some(Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::InternalStructureViolation {
+                dependency: dependency.module_path().to_string(),
+                module: module_config.path.clone(),
+                usage_path: format!("{}.{}", module_config.path, usage_entry),
+                definition_path: format!("{}.{}", module_config.path, definition_entry),
+            }),
+        ))
+    }
+
+    fn check_stability(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        dependency_module_config: &ModuleConfig,
+        rules: &RulesConfig,
+    ) -> Option<Diagnostic> {
+        let file_module_config = file_module.module_config();
+        let usage_stability = file_module_config.stability?;
+        if usage_stability.permits(dependency_module_config.stability) {
+            return None;
+        }
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::UnstableDependency {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                usage_stability: usage_stability.as_str().to_string(),
+                definition_module: dependency_module_config.path.clone(),
+                definition_stability: dependency_module_config
+                    .stability
+                    .map(|stability| stability.as_str().to_string())
+                    .unwrap_or_default(),
+            }),
+        )
+        .with_rule_setting(&rules.unstable_dependencies)
+    }
+
+    fn check_utility(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        dependency_module_config: &ModuleConfig,
+        rules: &RulesConfig,
+    ) -> Option<Diagnostic> {
+        let file_module_config = file_module.module_config();
+        // Utility modules may only depend on other utilities (code in the root module is covered by 'root_module')
+        if !file_module_config.utility
+            || dependency_module_config.utility
+            || dependency_module_config.is_root()
+            || dependency_module_config == file_module_config
+        {
+            return None;
+        }
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::UtilityDependency {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                definition_module: dependency_module_config.path.clone(),
+            }),
+        )
+        .with_rule_setting(&rules.utility_dependencies)
+    }
+
+    fn check_platforms(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        dependency_module_config: &ModuleConfig,
+        rules: &RulesConfig,
+    ) -> Option<Diagnostic> {
+        let file_module_config = file_module.module_config();
+        // Imports under 'if TYPE_CHECKING:' never run
+        if dependency_module_config.platforms.is_empty()
+            || dependency_module_config == file_module_config
+            || dependency.context() == ImportContext::TypeChecking
+        {
+            return None;
+        }
+        // An import which is not guarded is limited to the platforms of the importing module
+        let import_platforms = dependency
+            .platforms()
+            .unwrap_or(&file_module_config.platforms);
+        let is_covered = |platform: &String| {
+            dependency_module_config
+                .platforms
+                .iter()
+                .any(|module_platform| platform.starts_with(module_platform.as_str()))
+        };
+        if !import_platforms.is_empty() && import_platforms.iter().all(is_covered) {
+            return None;
+        }
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::PlatformDependency {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                definition_module: dependency_module_config.path.clone(),
+                platforms: dependency_module_config
+                    .platforms
+                    .iter()
+                    .map(|platform| format!("'{}'", platform))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        )
+        .with_rule_setting(&rules.platform_imports)
+    }
+
+    fn check_domain_boundaries(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        dependency_module_config: &ModuleConfig,
+        rules: &RulesConfig,
+    ) -> Option<Diagnostic> {
+        let domain = self
+            .module_domains
+            .get(dependency_module_config.path.as_str())?;
+        let interface_modules = domain.interface_modules()?;
+        let file_module_config = file_module.module_config();
+        let is_same_domain = self
+            .module_domains
+            .get(file_module_config.path.as_str())
+            .is_some_and(|usage_domain| usage_domain.location == domain.location);
+        if is_same_domain || interface_modules.contains(&dependency_module_config.path) {
+            return None;
+        }
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::DomainBoundaryViolation {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                definition_module: dependency_module_config.path.clone(),
+                domain: domain.name().to_string(),
+                interface_modules: if interface_modules.is_empty() {
+                    "none of its modules".to_string()
+                } else {
+                    interface_modules
+                        .iter()
+                        .map(|path| format!("'{}'", path))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                },
+            }),
+        )
+        .with_rule_setting(&rules.domain_boundaries)
+    }
+
+    fn check_forbidden_dependency(
+        &self,
+        file_module: &FileModule,
+        dependency: &Dependency,
+        dependency_module_config: &ModuleConfig,
+    ) -> Option<Diagnostic> {
+        let file_module_config = file_module.module_config();
+        if dependency_module_config == file_module_config {
+            return None;
+        }
+        if let Some(module_edges) = &self.module_edges {
+            module_edges.lock().unwrap().push(ModuleEdge {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                definition_module: dependency_module_config.path.clone(),
+                file_path: file_module.relative_file_path().to_path_buf(),
+                line_number: file_module.line_number(dependency.offset()),
+            });
+        }
+        let rule = self
+            .rule_engine?
+            .forbidden_dependency(&file_module_config.path, &dependency_module_config.path)?;
+
+        Diagnostic::new_located_error(
+            file_module.relative_file_path().to_path_buf(),
+            file_module.line_number(dependency.offset()),
+            dependency
+                .original_line_offset()
+                .map(|offset| file_module.line_number(offset)),
+            DiagnosticDetails::Code(CodeDiagnostic::ForbiddenDependency {
+                dependency: dependency.module_path().to_string(),
+                usage_module: file_module_config.path.clone(),
+                definition_module: dependency_module_config.path.clone(),
+                rule: rule.describe(),
+            }),
+        )
+        .with_rule_setting(&rule.severity)
+    }
+
     fn check_dependency_rules(
         &self,
         file_module: &FileModule,
         dependency: &Dependency,
         dependency_module_config: &ModuleConfig,
         layers: &[String],
+        rules: &RulesConfig,
     ) -> DiagnosticResult<Vec<Diagnostic>> {
         let file_module_config = file_module.module_config();
         if dependency_module_config == file_module_config {
-            return Ok(vec![]);
+            return Ok(self
+                .check_internal_structure(file_module, dependency, file_module_config)
+                .into_iter()
+                .collect());
         }
 
         let relative_file_path = file_module.relative_file_path();
@@ -135,37 +432,137 @@ impl<'a> InternalDependencyChecker<'a> {
         let file_nearest_module_path = &file_module_config.path;
         let dependency_nearest_module_path = &dependency_module_config.path;
 
+        let member = dependency.member_of(dependency_nearest_module_path);
         match file_module_config
             .dependencies_iter()
             .find(|dep| &dep.path == dependency_nearest_module_path)
         {
-            Some(DependencyConfig {
-                deprecated: true, ..
-            }) => Ok(vec![Diagnostic::new_located_warning(
-                relative_file_path.to_path_buf(),
-                file_module.line_number(dependency.offset()),
-                dependency
-                    .original_line_offset()
-                    .map(|offset| file_module.line_number(offset)),
-                DiagnosticDetails::Code(CodeDiagnostic::DeprecatedDependency {
-                    dependency: dependency.module_path().to_string(),
-                    usage_module: file_nearest_module_path.to_string(),
-                    definition_module: dependency_nearest_module_path.to_string(),
-                }),
-            )]),
+            Some(dependency_config)
+                if dependency_config.is_past_deprecation(&self.today)
+                    && dependency_config.deprecates_member(member) =>
+            {
+                // The grace period has ended, so the dependency is treated as undeclared
+                Ok(Diagnostic::new_located_error(
+                    relative_file_path.to_path_buf(),
+                    file_module.line_number(dependency.offset()),
+                    dependency
+                        .original_line_offset()
+                        .map(|offset| file_module.line_number(offset)),
+                    DiagnosticDetails::Code(CodeDiagnostic::ExpiredDeprecatedDependency {
+                        dependency: dependency.module_path().to_string(),
+                        usage_module: file_nearest_module_path.to_string(),
+                        definition_module: dependency_nearest_module_path.to_string(),
+                        deprecated_until: dependency_config
+                            .deprecated_until
+                            .clone()
+                            .unwrap_or_default(),
+                    }),
+                )
+                .with_rule_setting(&rules.undeclared_dependencies)
+                .into_iter()
+                .collect())
+            }
+            Some(dependency_config) if !dependency_config.allows_member(member) => {
+                Ok(Diagnostic::new_located_error(
+                    relative_file_path.to_path_buf(),
+                    file_module.line_number(dependency.offset()),
+                    dependency
+                        .original_line_offset()
+                        .map(|offset| file_module.line_number(offset)),
+                    DiagnosticDetails::Code(CodeDiagnostic::RestrictedMemberDependency {
+                        dependency: dependency.module_path().to_string(),
+                        usage_module: file_nearest_module_path.to_string(),
+                        definition_module: dependency_nearest_module_path.to_string(),
+                        allowed_members: dependency_config
+                            .members
+                            .iter()
+                            .flatten()
+                            .map(|member| format!("'{}'", member))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    }),
+                )
+                .with_rule_setting(&rules.undeclared_dependencies)
+                .into_iter()
+                .collect())
+            }
+            Some(dependency_config) if dependency_config.deprecates_member(member) => {
+                let details = match member {
+                    Some(member) if !dependency_config.is_deprecated() => {
+                        CodeDiagnostic::DeprecatedMemberDependency {
+                            dependency: dependency.module_path().to_string(),
+                            usage_module: file_nearest_module_path.to_string(),
+                            definition_module: dependency_nearest_module_path.to_string(),
+                            member: member.to_string(),
+                        }
+                    }
+                    _ => CodeDiagnostic::DeprecatedDependency {
+                        dependency: dependency.module_path().to_string(),
+                        usage_module: file_nearest_module_path.to_string(),
+                        definition_module: dependency_nearest_module_path.to_string(),
+                    },
+                };
+                Ok(Diagnostic::new_located_warning(
+                    relative_file_path.to_path_buf(),
+                    file_module.line_number(dependency.offset()),
+                    dependency
+                        .original_line_offset()
+                        .map(|offset| file_module.line_number(offset)),
+                    DiagnosticDetails::Code(details),
+                )
+                .with_rule_setting(&rules.deprecated_dependencies)
+                .into_iter()
+                .collect())
+            }
             Some(_) => Ok(vec![]),
-            None => Ok(vec![Diagnostic::new_located_error(
-                relative_file_path.to_path_buf(),
-                file_module.line_number(dependency.offset()),
-                dependency
-                    .original_line_offset()
-                    .map(|offset| file_module.line_number(offset)),
-                DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
-                    dependency: dependency.module_path().to_string(),
-                    usage_module: file_nearest_module_path.to_string(),
-                    definition_module: dependency_nearest_module_path.to_string(),
-                }),
-            )]),
+            None => {
+                let rule_setting =
+                    if file_module_config.is_root() || dependency_module_config.is_root() {
+                        &rules.root_module_usage
+                    } else {
+                        &rules.undeclared_dependencies
+                    };
+                let dependency_path = dependency.module_path().to_string();
+                let usage_module = file_nearest_module_path.to_string();
+                let definition_module = dependency_nearest_module_path.to_string();
+                // These usages of the root module cannot be declared, so they are reported as such
+                let details = match &self.project_config.root_module {
+                    RootModuleTreatment::Forbid if file_module_config.is_root() => {
+                        CodeDiagnostic::ForbiddenRootModuleUsage {
+                            dependency: dependency_path,
+                            usage_module,
+                            definition_module,
+                        }
+                    }
+                    treatment @ (RootModuleTreatment::Forbid
+                    | RootModuleTreatment::DependenciesOnly)
+                        if dependency_module_config.is_root() =>
+                    {
+                        CodeDiagnostic::ForbiddenRootModuleDependency {
+                            dependency: dependency_path,
+                            usage_module,
+                            definition_module,
+                            treatment: treatment.to_string(),
+                        }
+                    }
+                    _ => CodeDiagnostic::UndeclaredDependency {
+                        dependency: dependency_path,
+                        usage_module,
+                        definition_module,
+                    },
+                };
+                Ok(Diagnostic::new_located_error(
+                    relative_file_path.to_path_buf(),
+                    file_module.line_number(dependency.offset()),
+                    dependency
+                        .original_line_offset()
+                        .map(|offset| file_module.line_number(offset)),
+                    DiagnosticDetails::Code(details),
+                )
+                .with_rule_setting(rule_setting)
+                .into_iter()
+                .collect())
+            }
         }
     }
 
@@ -173,10 +570,17 @@ impl<'a> InternalDependencyChecker<'a> {
         &self,
         dependency: &Dependency,
         file_module: &FileModule,
+        rules: &RulesConfig,
     ) -> DiagnosticResult<Vec<Diagnostic>> {
+        // The dependency is attributed to the module which actually provides it,
+        // but diagnostics still refer to the path as it was written
+        let provider_path = match &self.reexport_resolver {
+            Some(resolver) => resolver.resolve(dependency.module_path()),
+            None => dependency.module_path().to_string(),
+        };
         if let Some(dependency_module_config) = self
             .module_tree
-            .find_nearest(dependency.module_path())
+            .find_nearest(&provider_path)
             .as_ref()
             .and_then(|module| module.config.as_ref())
         {
@@ -186,12 +590,44 @@ impl<'a> InternalDependencyChecker<'a> {
                 return Ok(vec![]);
             }
 
-            self.check_dependency_rules(
+            // Stability, utilities, platforms and domain boundaries apply regardless of whether the dependency is declared
+            let mut diagnostics = self.check_dependency_rules(
                 file_module,
                 dependency,
                 dependency_module_config,
                 &self.project_config.layers,
-            )
+                rules,
+            )?;
+            diagnostics.extend(self.check_stability(
+                file_module,
+                dependency,
+                dependency_module_config,
+                rules,
+            ));
+            diagnostics.extend(self.check_utility(
+                file_module,
+                dependency,
+                dependency_module_config,
+                rules,
+            ));
+            diagnostics.extend(self.check_platforms(
+                file_module,
+                dependency,
+                dependency_module_config,
+                rules,
+            ));
+            diagnostics.extend(self.check_domain_boundaries(
+                file_module,
+                dependency,
+                dependency_module_config,
+                rules,
+            ));
+            diagnostics.extend(self.check_forbidden_dependency(
+                file_module,
+                dependency,
+                dependency_module_config,
+            ));
+            Ok(diagnostics)
         } else {
             Ok(vec![Diagnostic::new_global_error(
                 DiagnosticDetails::Configuration(ConfigurationDiagnostic::ModuleConfigNotFound {
@@ -202,15 +638,102 @@ impl<'a> InternalDependencyChecker<'a> {
     }
 }
 
+/// Relative imports which cannot be resolved are reported as warnings, since they do not affect dependency rules
+fn unresolved_import_diagnostic(
+    unresolved_import: &UnresolvedRelativeImport,
+    file_module: &FileModule,
+) -> Diagnostic {
+    let details = match unresolved_import {
+        UnresolvedRelativeImport::BeyondSourceRoot { import, .. } => {
+            CodeDiagnostic::RelativeImportBeyondSourceRoot {
+                import: import.clone(),
+            }
+        }
+        UnresolvedRelativeImport::NotFound { resolution, .. } => {
+            CodeDiagnostic::UnresolvedRelativeImport {
+                dependency: resolution.clone(),
+            }
+        }
+    };
+    Diagnostic::new_located_warning(
+        file_module.relative_file_path().to_path_buf(),
+        file_module.line_number(unresolved_import.import_offset()),
+        None,
+        DiagnosticDetails::Code(details),
+    )
+}
+
+fn import_style_diagnostic(
+    usage: &ImportStyleUsage,
+    file_module: &FileModule,
+    rules: &RulesConfig,
+) -> Option<Diagnostic> {
+    let import = usage.import.clone();
+    let details = match usage.style {
+        ImportStyle::Wildcard => CodeDiagnostic::WildcardImport { import },
+        ImportStyle::Relative => CodeDiagnostic::RelativeImport { import },
+        ImportStyle::Conditional => CodeDiagnostic::ConditionalImport { import },
+    };
+    Diagnostic::new_located_error(
+        file_module.relative_file_path().to_path_buf(),
+        file_module.line_number(usage.import_offset),
+        None,
+        DiagnosticDetails::Code(details),
+    )
+    .with_rule_setting(rules.import_style_setting(usage.style))
+}
+
 impl<'a> FileChecker<'a> for InternalDependencyChecker<'a> {
     type ProcessedFile = FileModule<'a>;
     type Output = Vec<Diagnostic>;
 
     fn check(&'a self, processed_file: &Self::ProcessedFile) -> DiagnosticResult<Self::Output> {
+        let rules = self
+            .project_config
+            .rules
+            .for_module(processed_file.module_config());
         let mut diagnostics = Vec::new();
         for dependency in processed_file.dependencies.iter() {
-            diagnostics.extend(self.check_dependency(dependency, processed_file)?);
+            let span = processed_file.source_span(dependency.range());
+            diagnostics.extend(
+                apply_import_context_rule(
+                    &rules,
+                    dependency,
+                    self.check_dependency(dependency, processed_file, &rules)?,
+                )
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_span(span)),
+            );
         }
+        diagnostics.extend(processed_file.unresolved_relative_imports.iter().map(
+            |unresolved_import| unresolved_import_diagnostic(unresolved_import, processed_file),
+        ));
+        diagnostics.extend(
+            processed_file
+                .unresolved_imports
+                .iter()
+                .filter_map(|import| {
+                    Diagnostic::new_located_error(
+                        processed_file.relative_file_path().to_path_buf(),
+                        processed_file.line_number(import.import_offset),
+                        None,
+                        DiagnosticDetails::Code(CodeDiagnostic::UnresolvedImport {
+                            dependency: import.module_path.clone(),
+                        }),
+                    )
+                    .with_span(processed_file.source_span(TextRange::new(
+                        import.import_offset,
+                        import.alias_range().end(),
+                    )))
+                    .with_rule_setting(&self.project_config.rules.unresolved_imports)
+                }),
+        );
+        diagnostics.extend(
+            processed_file
+                .import_styles
+                .iter()
+                .filter_map(|usage| import_style_diagnostic(usage, processed_file, &rules)),
+        );
 
         Ok(diagnostics)
     }
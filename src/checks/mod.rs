@@ -1,9 +1,23 @@
+pub mod conformance;
 pub mod external_dependency;
 pub mod ignore_directive;
+pub mod import_context;
 pub mod interface;
 pub mod internal_dependency;
+pub mod plugin;
+pub mod rule_engine;
+pub mod violation_budget;
+#[cfg(feature = "wasm")]
+pub mod wasm_plugin;
 
-pub use external_dependency::ExternalDependencyChecker;
+pub use conformance::check_conformance;
+pub use external_dependency::{ExternalDependencyChecker, StdlibModules};
 pub use ignore_directive::IgnoreDirectivePostProcessor;
+pub use import_context::apply_import_context_rule;
 pub use interface::InterfaceChecker;
 pub use internal_dependency::InternalDependencyChecker;
+pub use plugin::{CheckPlugin, CheckPlugins, PluginFile, PluginViolation};
+pub use rule_engine::{ModuleEdge, RuleEngine};
+pub use violation_budget::ViolationBudgetPostProcessor;
+#[cfg(feature = "wasm")]
+pub use wasm_plugin::WasmCheckPlugin;
@@ -0,0 +1,421 @@
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(feature = "wasm")]
+use super::wasm_plugin::WasmCheckPlugin;
+use crate::config::plugins::CustomPluginConfig;
+use crate::config::ProjectConfig;
+use crate::diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails, Severity};
+use crate::processors::FileModule;
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("Invalid path '{path}' for plugin '{plugin}', expected 'package.module:attribute'.")]
+    InvalidPath { plugin: String, path: String },
+    #[error("Failed to load plugin '{plugin}'.\n{source}")]
+    Load { plugin: String, source: PyErr },
+    #[error("Plugin '{plugin}' failed in '{hook}'.\n{source}")]
+    Hook {
+        plugin: String,
+        hook: &'static str,
+        source: PyErr,
+    },
+    #[error("WebAssembly plugin '{plugin}' failed.\n{reason}")]
+    Wasm { plugin: String, reason: String },
+    #[error("WebAssembly plugin '{0}' cannot be run, since this build of tach does not support WebAssembly plugins. Rebuild it with the 'wasm' feature to run them.")]
+    WasmUnsupported(String),
+}
+
+pub type Result<T> = std::result::Result<T, PluginError>;
+
+/// An import seen by a plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginImport {
+    pub module_path: String,
+    pub line_number: usize,
+}
+
+/// A checked file, as seen by a plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginFile {
+    // Relative to the project root
+    pub file_path: PathBuf,
+    // The module which contains the file
+    pub module_path: String,
+    pub imports: Vec<PluginImport>,
+}
+
+impl From<&FileModule<'_>> for PluginFile {
+    fn from(file_module: &FileModule) -> Self {
+        Self {
+            file_path: file_module.relative_file_path().to_path_buf(),
+            module_path: file_module.module.full_path.clone(),
+            imports: file_module
+                .imports()
+                .map(|import| PluginImport {
+                    module_path: import.module_path.clone(),
+                    line_number: file_module.line_number(import.import_offset),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A violation reported by a plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PluginViolation {
+    pub message: String,
+    // Relative to the project root. Violations without a file are reported for the whole project.
+    #[serde(default)]
+    pub file_path: Option<PathBuf>,
+    #[serde(default)]
+    pub line_number: Option<usize>,
+}
+
+impl PluginViolation {
+    fn located(message: String, file: &PluginFile, line_number: usize) -> Self {
+        Self {
+            message,
+            file_path: Some(file.file_path.clone()),
+            line_number: Some(line_number),
+        }
+    }
+
+    fn global(message: String) -> Self {
+        Self {
+            message,
+            file_path: None,
+            line_number: None,
+        }
+    }
+}
+
+/// A custom check which runs after the built-in checks.
+/// Each hook returns the messages of the violations it found.
+pub trait CheckPlugin {
+    fn name(&self) -> &str;
+
+    /// Check every file at once. By default, this calls each hook in turn.
+    fn check(&mut self, files: &[PluginFile]) -> Result<Vec<PluginViolation>> {
+        let mut violations = vec![];
+        for file in files {
+            for message in self.on_module(file)? {
+                violations.push(PluginViolation::located(message, file, 1));
+            }
+            for import in &file.imports {
+                for message in self.on_import(file, import)? {
+                    violations.push(PluginViolation::located(message, file, import.line_number));
+                }
+            }
+        }
+        for message in self.on_finish()? {
+            violations.push(PluginViolation::global(message));
+        }
+        Ok(violations)
+    }
+
+    /// Called once for each checked file
+    fn on_module(&mut self, _file: &PluginFile) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// Called for each first-party import in a checked file
+    fn on_import(&mut self, _file: &PluginFile, _import: &PluginImport) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// Called once after every file has been checked
+    fn on_finish(&mut self) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+}
+
+fn toml_to_py(py: Python, value: &toml::Value) -> PyObject {
+    match value {
+        toml::Value::String(value) => value.to_object(py),
+        toml::Value::Integer(value) => value.to_object(py),
+        toml::Value::Float(value) => value.to_object(py),
+        toml::Value::Boolean(value) => value.to_object(py),
+        toml::Value::Datetime(value) => value.to_string().to_object(py),
+        toml::Value::Array(values) => {
+            PyList::new_bound(py, values.iter().map(|value| toml_to_py(py, value))).to_object(py)
+        }
+        toml::Value::Table(table) => table_to_py(py, table),
+    }
+}
+
+fn table_to_py(py: Python, table: &toml::Table) -> PyObject {
+    let dict = PyDict::new_bound(py);
+    for (key, value) in table {
+        // Setting an item with a string key cannot fail
+        let _ = dict.set_item(key, toml_to_py(py, value));
+    }
+    dict.to_object(py)
+}
+
+/// A plugin implemented by a Python object.
+/// Hooks which the object does not define are skipped, and each hook may return
+/// None, a message, or a list of messages.
+pub struct PyCheckPlugin {
+    name: String,
+    object: PyObject,
+}
+
+impl PyCheckPlugin {
+    /// Import the object at the configured path and call it with the plugin's options.
+    /// The project root is importable, so plugins may live alongside the code they check.
+    pub fn load(project_root: &Path, config: &CustomPluginConfig) -> Result<Self> {
+        let (module, attribute) =
+            config
+                .path
+                .split_once(':')
+                .ok_or_else(|| PluginError::InvalidPath {
+                    plugin: config.name.clone(),
+                    path: config.path.clone(),
+                })?;
+        Python::with_gil(|py| {
+            let sys_path = py.import_bound("sys")?.getattr("path")?;
+            let project_root = project_root.display().to_string();
+            if !sys_path.contains(&project_root)? {
+                sys_path.call_method1("insert", (0, project_root))?;
+            }
+            let object = py
+                .import_bound(module)?
+                .getattr(attribute)?
+                .call1((table_to_py(py, &config.options),))?;
+            Ok(Self {
+                name: config.name.clone(),
+                object: object.unbind(),
+            })
+        })
+        .map_err(|source| PluginError::Load {
+            plugin: config.name.clone(),
+            source,
+        })
+    }
+
+    fn call_hook(
+        &self,
+        hook: &'static str,
+        args: impl IntoPy<Py<pyo3::types::PyTuple>>,
+    ) -> Result<Vec<String>> {
+        Python::with_gil(|py| {
+            let object = self.object.bind(py);
+            if !object.hasattr(hook)? {
+                return Ok(vec![]);
+            }
+            let result = object.call_method1(hook, args)?;
+            if result.is_none() {
+                Ok(vec![])
+            } else if result.is_instance_of::<PyString>() {
+                Ok(vec![result.extract()?])
+            } else {
+                result.extract()
+            }
+        })
+        .map_err(|source| PluginError::Hook {
+            plugin: self.name.clone(),
+            hook,
+            source,
+        })
+    }
+}
+
+impl CheckPlugin for PyCheckPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_module(&mut self, file: &PluginFile) -> Result<Vec<String>> {
+        self.call_hook(
+            "on_module",
+            (
+                file.file_path.display().to_string(),
+                file.module_path.clone(),
+            ),
+        )
+    }
+
+    fn on_import(&mut self, file: &PluginFile, import: &PluginImport) -> Result<Vec<String>> {
+        self.call_hook(
+            "on_import",
+            (
+                file.file_path.display().to_string(),
+                file.module_path.clone(),
+                import.module_path.clone(),
+                import.line_number,
+            ),
+        )
+    }
+
+    fn on_finish(&mut self) -> Result<Vec<String>> {
+        self.call_hook("on_finish", ())
+    }
+}
+
+/// The configured plugins, along with the severity of the violations they report
+pub struct CheckPlugins {
+    plugins: Vec<(Box<dyn CheckPlugin>, Severity)>,
+}
+
+impl CheckPlugins {
+    pub fn new(plugins: Vec<(Box<dyn CheckPlugin>, Severity)>) -> Self {
+        Self { plugins }
+    }
+
+    /// Load every configured plugin which is not turned off, Python plugins first
+    pub fn load(project_root: &Path, project_config: &ProjectConfig) -> Result<Self> {
+        let mut plugins: Vec<(Box<dyn CheckPlugin>, Severity)> = vec![];
+        for config in &project_config.plugins.custom {
+            // Plugins which are turned off are not loaded
+            let Ok(severity) = Severity::try_from(&config.severity) else {
+                continue;
+            };
+            plugins.push((
+                Box::new(PyCheckPlugin::load(project_root, config)?),
+                severity,
+            ));
+        }
+        for config in &project_config.plugins.wasm {
+            let Ok(severity) = Severity::try_from(&config.severity) else {
+                continue;
+            };
+            #[cfg(feature = "wasm")]
+            plugins.push((
+                Box::new(WasmCheckPlugin::load(project_root, config, project_config)?),
+                severity,
+            ));
+            #[cfg(not(feature = "wasm"))]
+            {
+                let _ = severity;
+                return Err(PluginError::WasmUnsupported(config.name.clone()));
+            }
+        }
+        Ok(Self::new(plugins))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Run each plugin over `files`, in order of their paths
+    pub fn run(&mut self, mut files: Vec<PluginFile>) -> Result<Vec<Diagnostic>> {
+        files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        let mut diagnostics = vec![];
+        for (plugin, severity) in &mut self.plugins {
+            for violation in plugin.check(&files)? {
+                let details = DiagnosticDetails::Code(CodeDiagnostic::PluginViolation {
+                    plugin: plugin.name().to_string(),
+                    message: violation.message,
+                });
+                diagnostics.push(match violation.file_path {
+                    Some(file_path) => Diagnostic::new_located(
+                        *severity,
+                        details,
+                        file_path,
+                        violation.line_number.unwrap_or(1),
+                    ),
+                    None => Diagnostic::new_global(*severity, details),
+                });
+            }
+        }
+        Ok(diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports every import of 'legacy', and the number of files it was seen in
+    #[derive(Default)]
+    struct NoLegacy {
+        files: usize,
+    }
+
+    impl CheckPlugin for NoLegacy {
+        fn name(&self) -> &str {
+            "no-legacy"
+        }
+
+        fn on_import(&mut self, _file: &PluginFile, import: &PluginImport) -> Result<Vec<String>> {
+            if import.module_path.starts_with("legacy") {
+                self.files += 1;
+                Ok(vec![format!("Do not import '{}'.", import.module_path)])
+            } else {
+                Ok(vec![])
+            }
+        }
+
+        fn on_finish(&mut self) -> Result<Vec<String>> {
+            Ok(vec![format!(
+                "'legacy' is imported in {} files.",
+                self.files
+            )])
+        }
+    }
+
+    #[test]
+    fn test_run_plugins() {
+        let file = |path: &str, imports: &[(&str, usize)]| PluginFile {
+            file_path: PathBuf::from(path),
+            module_path: "app".to_string(),
+            imports: imports
+                .iter()
+                .map(|(module_path, line_number)| PluginImport {
+                    module_path: module_path.to_string(),
+                    line_number: *line_number,
+                })
+                .collect(),
+        };
+        let mut plugins = CheckPlugins::new(vec![(
+            Box::new(NoLegacy::default()) as Box<dyn CheckPlugin>,
+            Severity::Warning,
+        )]);
+
+        let diagnostics = plugins
+            .run(vec![
+                file("app/b.py", &[("legacy.db", 3)]),
+                file("app/a.py", &[("core", 1), ("legacy", 2)]),
+            ])
+            .unwrap();
+
+        let located: Vec<(Option<&Path>, Option<usize>, String)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.file_path().map(PathBuf::as_path),
+                    diagnostic.line_number(),
+                    diagnostic.message(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                (
+                    Some(Path::new("app/a.py")),
+                    Some(2),
+                    "[no-legacy] Do not import 'legacy'.".to_string()
+                ),
+                (
+                    Some(Path::new("app/b.py")),
+                    Some(3),
+                    "[no-legacy] Do not import 'legacy.db'.".to_string()
+                ),
+                (
+                    None,
+                    None,
+                    "[no-legacy] 'legacy' is imported in 2 files.".to_string()
+                ),
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity() == Severity::Warning));
+    }
+}
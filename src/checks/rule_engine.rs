@@ -0,0 +1,353 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+
+use glob::Pattern;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::{ForbidRuleConfig, ModuleConfig, NamingRuleConfig, ProjectConfig, RuleSetting};
+use crate::diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails};
+
+#[derive(Error, Debug)]
+pub enum RuleEngineError {
+    #[error("Invalid module pattern '{pattern}' in '{rule}': {source}")]
+    InvalidModulePattern {
+        rule: &'static str,
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[error("Invalid naming pattern '{pattern}': {source}")]
+    InvalidNamingPattern {
+        pattern: String,
+        source: regex::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, RuleEngineError>;
+
+fn module_pattern(rule: &'static str, pattern: &str) -> Result<Pattern> {
+    Pattern::new(pattern).map_err(|source| RuleEngineError::InvalidModulePattern {
+        rule,
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+/// A dependency between two modules, from an import in a checked file
+#[derive(Debug, Clone)]
+pub struct ModuleEdge {
+    pub dependency: String,
+    pub usage_module: String,
+    pub definition_module: String,
+    // Relative to the project root
+    pub file_path: PathBuf,
+    pub line_number: usize,
+}
+
+struct ForbidRule<'a> {
+    config: &'a ForbidRuleConfig,
+    from: Pattern,
+    to: Pattern,
+    unless_through: Option<Pattern>,
+}
+
+struct NamingRule<'a> {
+    config: &'a NamingRuleConfig,
+    modules: Pattern,
+    pattern: Regex,
+}
+
+/// Evaluates the '[[forbid]]' and '[[naming]]' rules declared in the project configuration
+pub struct RuleEngine<'a> {
+    forbid: Vec<ForbidRule<'a>>,
+    naming: Vec<NamingRule<'a>>,
+}
+
+impl<'a> RuleEngine<'a> {
+    /// Compile the rules which are not turned off
+    pub fn new(project_config: &'a ProjectConfig) -> Result<Self> {
+        let forbid = project_config
+            .forbid
+            .iter()
+            .filter(|config| config.severity != RuleSetting::Off)
+            .map(|config| {
+                Ok(ForbidRule {
+                    config,
+                    from: module_pattern("forbid", &config.from)?,
+                    to: module_pattern("forbid", &config.to)?,
+                    unless_through: config
+                        .unless_through
+                        .as_deref()
+                        .map(|pattern| module_pattern("forbid", pattern))
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let naming = project_config
+            .naming
+            .iter()
+            .filter(|config| config.severity != RuleSetting::Off)
+            .map(|config| {
+                Ok(NamingRule {
+                    config,
+                    modules: module_pattern("naming", &config.modules)?,
+                    pattern: Regex::new(&config.pattern).map_err(|source| {
+                        RuleEngineError::InvalidNamingPattern {
+                            pattern: config.pattern.clone(),
+                            source,
+                        }
+                    })?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { forbid, naming })
+    }
+
+    /// Whether any rule needs to see every dependency in the project (see `check_mediation`)
+    pub fn has_mediation_rules(&self) -> bool {
+        self.forbid.iter().any(|rule| rule.unless_through.is_some())
+    }
+
+    /// The first rule which forbids `usage_module` from directly depending on `definition_module`
+    pub fn forbidden_dependency(
+        &self,
+        usage_module: &str,
+        definition_module: &str,
+    ) -> Option<&ForbidRuleConfig> {
+        self.forbid
+            .iter()
+            .find(|rule| rule.from.matches(usage_module) && rule.to.matches(definition_module))
+            .map(|rule| rule.config)
+    }
+
+    /// Report modules whose name (the last component of their path) does not match a naming rule
+    pub fn check_naming<'m>(
+        &self,
+        modules: impl IntoIterator<Item = &'m ModuleConfig>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for module in modules {
+            if module.path == ROOT_MODULE_SENTINEL_TAG {
+                continue;
+            }
+            let name = module.path.rsplit('.').next().unwrap_or(&module.path);
+            for rule in &self.naming {
+                if rule.modules.matches(&module.path) && !rule.pattern.is_match(name) {
+                    diagnostics.extend(
+                        Diagnostic::new_global_error(DiagnosticDetails::Code(
+                            CodeDiagnostic::ModuleNamingViolation {
+                                module_path: module.path.clone(),
+                                pattern: rule.config.pattern.clone(),
+                                reason: rule
+                                    .config
+                                    .reason
+                                    .as_ref()
+                                    .map(|reason| format!(": {}", reason))
+                                    .unwrap_or_default(),
+                            },
+                        ))
+                        .with_rule_setting(&rule.config.severity),
+                    );
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Report dependencies which lead from a module matching 'from' to a module matching 'to',
+    /// without passing through a module matching 'unless_through'. Direct dependencies are
+    /// reported by `forbidden_dependency` instead, so only indirect paths are considered here.
+    pub fn check_mediation(&self, edges: &[ModuleEdge]) -> Vec<Diagnostic> {
+        let mut graph: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for edge in edges {
+            if edge.usage_module != edge.definition_module {
+                graph
+                    .entry(edge.usage_module.as_str())
+                    .or_default()
+                    .insert(edge.definition_module.as_str());
+            }
+        }
+
+        let mut diagnostics = vec![];
+        for rule in &self.forbid {
+            let Some(through) = &rule.unless_through else {
+                continue;
+            };
+            // The shortest unmediated path from each module to a module matching 'to'
+            let mut paths: HashMap<&str, Option<Vec<&str>>> = HashMap::new();
+            for edge in edges {
+                if edge.usage_module == edge.definition_module
+                    || !rule.from.matches(&edge.usage_module)
+                    || rule.to.matches(&edge.definition_module)
+                    || through.matches(&edge.definition_module)
+                {
+                    continue;
+                }
+                let path = paths
+                    .entry(edge.definition_module.as_str())
+                    .or_insert_with(|| unmediated_path(&graph, &edge.definition_module, rule))
+                    .as_ref();
+                let Some(path) = path else {
+                    continue;
+                };
+                diagnostics.extend(
+                    Diagnostic::new_located_error(
+                        edge.file_path.clone(),
+                        edge.line_number,
+                        None,
+                        DiagnosticDetails::Code(CodeDiagnostic::UnmediatedDependency {
+                            dependency: edge.dependency.clone(),
+                            usage_module: edge.usage_module.clone(),
+                            definition_module: path.last().unwrap_or(&"").to_string(),
+                            path: std::iter::once(edge.usage_module.as_str())
+                                .chain(path.iter().copied())
+                                .map(|module| format!("'{}'", module))
+                                .collect::<Vec<_>>()
+                                .join(" -> "),
+                            through: through.as_str().to_string(),
+                        }),
+                    )
+                    .with_rule_setting(&rule.config.severity),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// The shortest path from `start` to a module matching the rule's 'to' pattern,
+/// which does not pass through a module matching its 'unless_through' pattern
+fn unmediated_path<'g>(
+    graph: &BTreeMap<&'g str, BTreeSet<&'g str>>,
+    start: &'g str,
+    rule: &ForbidRule,
+) -> Option<Vec<&'g str>> {
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(module) = queue.pop_front() {
+        for &next in graph.get(module).into_iter().flatten() {
+            if next == start
+                || previous.contains_key(next)
+                || rule
+                    .unless_through
+                    .as_ref()
+                    .is_some_and(|through| through.matches(next))
+            {
+                continue;
+            }
+            previous.insert(next, module);
+            if rule.to.matches(next) {
+                let mut path = vec![next];
+                let mut current = next;
+                while let Some(&module) = previous.get(current) {
+                    path.push(module);
+                    current = module;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(usage_module: &str, definition_module: &str) -> ModuleEdge {
+        ModuleEdge {
+            dependency: format!("{}.member", definition_module),
+            usage_module: usage_module.to_string(),
+            definition_module: definition_module.to_string(),
+            file_path: PathBuf::from(format!("{}.py", usage_module.replace('.', "/"))),
+            line_number: 1,
+        }
+    }
+
+    fn project_config() -> ProjectConfig {
+        ProjectConfig {
+            forbid: vec![ForbidRuleConfig {
+                from: "*.api".to_string(),
+                to: "*.db".to_string(),
+                unless_through: Some("*.repository".to_string()),
+                reason: None,
+                severity: RuleSetting::Error,
+            }],
+            naming: vec![NamingRuleConfig {
+                modules: "app.*".to_string(),
+                pattern: "^[a-z_]+$".to_string(),
+                reason: Some("Use snake_case".to_string()),
+                severity: RuleSetting::Warn,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_forbidden_dependency() {
+        let project_config = project_config();
+        let engine = RuleEngine::new(&project_config).unwrap();
+        assert!(engine.forbidden_dependency("app.api", "app.db").is_some());
+        assert!(engine
+            .forbidden_dependency("app.api", "app.repository")
+            .is_none());
+        assert!(engine.forbidden_dependency("app.db", "app.api").is_none());
+    }
+
+    #[test]
+    fn test_check_mediation() {
+        let project_config = project_config();
+        let engine = RuleEngine::new(&project_config).unwrap();
+        let diagnostics = engine.check_mediation(&[
+            edge("app.api", "app.service"),
+            edge("app.api", "app.repository"),
+            edge("app.service", "app.utils"),
+            edge("app.utils", "app.db"),
+            edge("app.repository", "app.db"),
+        ]);
+
+        let messages: Vec<String> = diagnostics.iter().map(Diagnostic::message).collect();
+        assert_eq!(
+            messages,
+            vec!["Cannot use 'app.service.member'. Module 'app.api' reaches 'app.db' ('app.api' -> 'app.service' -> 'app.utils' -> 'app.db') without going through a module matching '*.repository'."]
+        );
+    }
+
+    #[test]
+    fn test_check_naming() {
+        let project_config = project_config();
+        let engine = RuleEngine::new(&project_config).unwrap();
+        let module = |path: &str| ModuleConfig::new(path, false);
+        let diagnostics = engine.check_naming(&[
+            module("app.orders"),
+            module("app.OrderHistory"),
+            module("other.Thing"),
+        ]);
+
+        let messages: Vec<String> = diagnostics.iter().map(Diagnostic::message).collect();
+        assert_eq!(
+            messages,
+            vec!["Module 'app.OrderHistory' does not match the naming pattern '^[a-z_]+$': Use snake_case."]
+        );
+    }
+
+    #[test]
+    fn test_invalid_naming_pattern() {
+        let project_config = ProjectConfig {
+            naming: vec![NamingRuleConfig {
+                modules: "*".to_string(),
+                pattern: "(".to_string(),
+                reason: None,
+                severity: RuleSetting::Error,
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            RuleEngine::new(&project_config),
+            Err(RuleEngineError::InvalidNamingPattern { .. })
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use crate::config::ModuleConfig;
+use crate::diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails, Severity};
+
+/// Applies the `max_violations` budget of each module, and of the project as a whole,
+/// to the diagnostics from a full check.
+/// While a module is within its budget, its dependency and interface errors are downgraded to warnings,
+/// and the remaining headroom is reported. Once the budget is exceeded, all of its errors are kept.
+/// The project budget covers the errors of every module which does not have a budget of its own.
+pub struct ViolationBudgetPostProcessor<'a> {
+    budgets: HashMap<&'a str, usize>,
+    project_budget: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BudgetScope<'a> {
+    Module(&'a str),
+    Project,
+}
+
+impl<'a> ViolationBudgetPostProcessor<'a> {
+    pub fn new(
+        modules: impl IntoIterator<Item = &'a ModuleConfig>,
+        project_budget: Option<usize>,
+    ) -> Self {
+        Self {
+            budgets: modules
+                .into_iter()
+                .filter_map(|module| {
+                    module
+                        .max_violations
+                        .map(|max_violations| (module.path.as_str(), max_violations))
+                })
+                .collect(),
+            project_budget,
+        }
+    }
+
+    fn budget_scope(&self, diagnostic: &Diagnostic) -> Option<BudgetScope<'a>> {
+        if !diagnostic.is_error()
+            || !(diagnostic.is_dependency_error() || diagnostic.is_interface_error())
+        {
+            return None;
+        }
+        match diagnostic
+            .usage_module()
+            .and_then(|module_path| self.budgets.get_key_value(module_path))
+        {
+            Some((module_path, _)) => Some(BudgetScope::Module(*module_path)),
+            None => self.project_budget.map(|_| BudgetScope::Project),
+        }
+    }
+
+    fn max_violations(&self, scope: BudgetScope) -> usize {
+        match scope {
+            BudgetScope::Module(module_path) => self.budgets[module_path],
+            BudgetScope::Project => self.project_budget.unwrap_or_default(),
+        }
+    }
+
+    pub fn process_diagnostics(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.budgets.is_empty() && self.project_budget.is_none() {
+            return;
+        }
+
+        let mut violations: HashMap<BudgetScope, usize> = HashMap::new();
+        for diagnostic in diagnostics.iter() {
+            if let Some(scope) = self.budget_scope(diagnostic) {
+                *violations.entry(scope).or_default() += 1;
+            }
+        }
+
+        for diagnostic in diagnostics.iter_mut() {
+            if let Some(scope) = self.budget_scope(diagnostic) {
+                if violations[&scope] <= self.max_violations(scope) {
+                    diagnostic.set_severity(Severity::Warning);
+                }
+            }
+        }
+
+        let mut budgets: Vec<(&str, usize)> = self
+            .budgets
+            .iter()
+            .map(|(module_path, max_violations)| (*module_path, *max_violations))
+            .collect();
+        budgets.sort();
+        for (module_path, max_violations) in budgets {
+            let violations = violations
+                .get(&BudgetScope::Module(module_path))
+                .copied()
+                .unwrap_or_default();
+            if violations > max_violations {
+                diagnostics.push(Diagnostic::new_global_error(DiagnosticDetails::Code(
+                    CodeDiagnostic::ViolationBudgetExceeded {
+                        module_path: module_path.to_string(),
+                        violations,
+                        max_violations,
+                    },
+                )));
+            } else {
+                diagnostics.push(Diagnostic::new_global_warning(DiagnosticDetails::Code(
+                    CodeDiagnostic::WithinViolationBudget {
+                        module_path: module_path.to_string(),
+                        violations,
+                        max_violations,
+                        remaining: max_violations - violations,
+                    },
+                )));
+            }
+        }
+
+        if let Some(max_violations) = self.project_budget {
+            let violations = violations
+                .get(&BudgetScope::Project)
+                .copied()
+                .unwrap_or_default();
+            if violations > max_violations {
+                diagnostics.push(Diagnostic::new_global_error(DiagnosticDetails::Code(
+                    CodeDiagnostic::ProjectViolationBudgetExceeded {
+                        violations,
+                        max_violations,
+                    },
+                )));
+            } else {
+                diagnostics.push(Diagnostic::new_global_warning(DiagnosticDetails::Code(
+                    CodeDiagnostic::WithinProjectViolationBudget {
+                        violations,
+                        max_violations,
+                        remaining: max_violations - violations,
+                    },
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn undeclared_dependency(usage_module: &str) -> Diagnostic {
+        Diagnostic::new_located_error(
+            PathBuf::from("a.py"),
+            1,
+            None,
+            DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                dependency: "b.member".to_string(),
+                usage_module: usage_module.to_string(),
+                definition_module: "b".to_string(),
+            }),
+        )
+    }
+
+    fn module_with_budget(path: &str, max_violations: usize) -> ModuleConfig {
+        ModuleConfig {
+            max_violations: Some(max_violations),
+            ..ModuleConfig::new(path, false)
+        }
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let modules = vec![module_with_budget("a", 3)];
+        let mut diagnostics = vec![undeclared_dependency("a"), undeclared_dependency("a")];
+        ViolationBudgetPostProcessor::new(&modules, None).process_diagnostics(&mut diagnostics);
+
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_warning()));
+        assert_eq!(
+            diagnostics.last().unwrap().details(),
+            &DiagnosticDetails::Code(CodeDiagnostic::WithinViolationBudget {
+                module_path: "a".to_string(),
+                violations: 2,
+                max_violations: 3,
+                remaining: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_budget_exceeded() {
+        let modules = vec![module_with_budget("a", 1)];
+        let mut diagnostics = vec![
+            undeclared_dependency("a"),
+            undeclared_dependency("a"),
+            undeclared_dependency("c"),
+        ];
+        ViolationBudgetPostProcessor::new(&modules, None).process_diagnostics(&mut diagnostics);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.is_error())
+                .count(),
+            4
+        );
+        assert_eq!(
+            diagnostics.last().unwrap().details(),
+            &DiagnosticDetails::Code(CodeDiagnostic::ViolationBudgetExceeded {
+                module_path: "a".to_string(),
+                violations: 2,
+                max_violations: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_project_budget() {
+        let modules = vec![module_with_budget("a", 1)];
+        let mut diagnostics = vec![
+            undeclared_dependency("a"),
+            undeclared_dependency("c"),
+            undeclared_dependency("d"),
+        ];
+        ViolationBudgetPostProcessor::new(&modules, Some(2)).process_diagnostics(&mut diagnostics);
+
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_warning()));
+        assert_eq!(
+            diagnostics.last().unwrap().details(),
+            &DiagnosticDetails::Code(CodeDiagnostic::WithinProjectViolationBudget {
+                violations: 2,
+                max_violations: 2,
+                remaining: 0,
+            })
+        );
+
+        // Violations in modules with a budget of their own do not count against the project
+        let mut diagnostics = vec![
+            undeclared_dependency("a"),
+            undeclared_dependency("c"),
+            undeclared_dependency("d"),
+            undeclared_dependency("e"),
+        ];
+        ViolationBudgetPostProcessor::new(&modules, Some(2)).process_diagnostics(&mut diagnostics);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.is_error())
+                .count(),
+            4
+        );
+        assert_eq!(
+            diagnostics.last().unwrap().details(),
+            &DiagnosticDetails::Code(CodeDiagnostic::ProjectViolationBudgetExceeded {
+                violations: 3,
+                max_violations: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::path::Path;
+
+use serde::Serialize;
+use wasmtime::{
+    Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+use super::plugin::{CheckPlugin, PluginError, PluginFile, PluginViolation, Result};
+use crate::config::plugins::WasmPluginConfig;
+use crate::config::ProjectConfig;
+
+/// The version of the rule ABI which plugins must export from 'tach_abi_version'
+pub const ABI_VERSION: i32 = 1;
+
+/// Plugins may not grow their memory beyond this size
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Roughly the number of instructions a plugin may execute in one run
+const MAX_FUEL: u64 = 10_000_000_000;
+
+/// A module as seen by a WebAssembly plugin
+#[derive(Debug, Serialize)]
+struct WasmModule {
+    path: String,
+    layer: Option<String>,
+    utility: bool,
+    // None when the module may depend on anything
+    depends_on: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct WasmInput<'a> {
+    abi_version: i32,
+    options: &'a toml::Table,
+    modules: &'a [WasmModule],
+    files: &'a [PluginFile],
+}
+
+fn wasm_error(plugin: &str, context: &str) -> impl FnOnce(wasmtime::Error) -> PluginError {
+    let plugin = plugin.to_string();
+    let context = context.to_string();
+    move |err| PluginError::Wasm {
+        plugin,
+        reason: format!("{}: {:#}", context, err),
+    }
+}
+
+/// A plugin compiled to WebAssembly, which implements the rule ABI:
+///
+/// - `memory`: the plugin's exported linear memory
+/// - `tach_abi_version() -> i32`: returns `ABI_VERSION`
+/// - `tach_alloc(len: i32) -> i32`: returns a buffer of `len` bytes for the input
+/// - `tach_check(ptr: i32, len: i32) -> i64`: reads the input as JSON, and returns the location of
+///   its JSON output (a list of violations) packed as `(ptr << 32) | len`
+///
+/// Plugins are given no imports, so they cannot access the filesystem, the network or the clock,
+/// and each run is bounded in memory and fuel.
+pub struct WasmCheckPlugin {
+    name: String,
+    options: toml::Table,
+    modules: Vec<WasmModule>,
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    check: TypedFunc<(i32, i32), i64>,
+}
+
+impl WasmCheckPlugin {
+    pub fn load(
+        project_root: &Path,
+        config: &WasmPluginConfig,
+        project_config: &ProjectConfig,
+    ) -> Result<Self> {
+        let name = config.name.as_str();
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config).map_err(wasm_error(name, "Failed to start"))?;
+        let module = Module::from_file(&engine, project_root.join(&config.path)).map_err(
+            wasm_error(name, &format!("Failed to load '{}'", config.path)),
+        )?;
+
+        let mut store = Store::new(
+            &engine,
+            StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_BYTES)
+                .build(),
+        );
+        store.limiter(|limits| limits);
+        let instance = Instance::new(&mut store, &module, &[])
+            .map_err(wasm_error(name, "Failed to instantiate"))?;
+        let memory =
+            instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| PluginError::Wasm {
+                    plugin: config.name.clone(),
+                    reason: "The plugin does not export 'memory'".to_string(),
+                })?;
+
+        store
+            .set_fuel(MAX_FUEL)
+            .map_err(wasm_error(name, "Failed to set fuel"))?;
+        let abi_version = instance
+            .get_typed_func::<(), i32>(&mut store, "tach_abi_version")
+            .and_then(|abi_version| abi_version.call(&mut store, ()))
+            .map_err(wasm_error(name, "Failed to read the ABI version"))?;
+        if abi_version != ABI_VERSION {
+            return Err(PluginError::Wasm {
+                plugin: config.name.clone(),
+                reason: format!(
+                    "The plugin implements ABI version {}, but only version {} is supported",
+                    abi_version, ABI_VERSION
+                ),
+            });
+        }
+        let alloc = instance
+            .get_typed_func(&mut store, "tach_alloc")
+            .map_err(wasm_error(name, "Missing export 'tach_alloc'"))?;
+        let check = instance
+            .get_typed_func(&mut store, "tach_check")
+            .map_err(wasm_error(name, "Missing export 'tach_check'"))?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            options: config.options.clone(),
+            modules: project_config
+                .all_modules()
+                .map(|module| WasmModule {
+                    path: module.path.clone(),
+                    layer: module.layer.clone(),
+                    utility: module.utility,
+                    depends_on: module.depends_on.as_ref().map(|dependencies| {
+                        dependencies
+                            .iter()
+                            .map(|dependency| dependency.path.clone())
+                            .collect()
+                    }),
+                })
+                .collect(),
+            store,
+            memory,
+            alloc,
+            check,
+        })
+    }
+}
+
+impl CheckPlugin for WasmCheckPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check(&mut self, files: &[PluginFile]) -> Result<Vec<PluginViolation>> {
+        let name = self.name.clone();
+        let input = serde_json::to_vec(&WasmInput {
+            abi_version: ABI_VERSION,
+            options: &self.options,
+            modules: &self.modules,
+            files,
+        })
+        .map_err(|err| wasm_error(&name, "Failed to serialize the input")(err.into()))?;
+        let len = i32::try_from(input.len())
+            .map_err(|err| wasm_error(&name, "The input is too large")(err.into()))?;
+
+        self.store
+            .set_fuel(MAX_FUEL)
+            .map_err(wasm_error(&name, "Failed to set fuel"))?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(wasm_error(&name, "'tach_alloc' failed"))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, &input)
+            .map_err(|err| wasm_error(&name, "Failed to write the input")(err.into()))?;
+        let output = self
+            .check
+            .call(&mut self.store, (ptr, len))
+            .map_err(wasm_error(&name, "'tach_check' failed"))? as u64;
+
+        let mut buffer = vec![0; (output & 0xffff_ffff) as usize];
+        self.memory
+            .read(&self.store, (output >> 32) as usize, &mut buffer)
+            .map_err(|err| wasm_error(&name, "Failed to read the output")(err.into()))?;
+        serde_json::from_slice(&buffer)
+            .map_err(|err| wasm_error(&name, "Invalid output")(err.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// A plugin which ignores its input and reports `output`
+    fn plugin_wat(abi_version: i32, output: &str) -> String {
+        format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 16) "{data}")
+                (func (export "tach_abi_version") (result i32) (i32.const {abi_version}))
+                (func (export "tach_alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "tach_check") (param i32 i32) (result i64)
+                    (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const {len}))))"#,
+            data = output.replace('"', "\\\""),
+            len = output.len(),
+        )
+    }
+
+    fn load(wat: &str) -> Result<WasmCheckPlugin> {
+        let temp_dir = TempDir::new().unwrap();
+        // Modules may also be written in the WebAssembly text format
+        std::fs::write(temp_dir.path().join("plugin.wat"), wat).unwrap();
+        WasmCheckPlugin::load(
+            temp_dir.path(),
+            &WasmPluginConfig {
+                name: "no-legacy".to_string(),
+                path: "plugin.wat".to_string(),
+                severity: crate::config::RuleSetting::Error,
+                options: toml::Table::new(),
+            },
+            &ProjectConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_wasm_plugin_violations() {
+        let mut plugin = load(&plugin_wat(
+            ABI_VERSION,
+            r#"[{"message":"Do not import 'legacy'.","file_path":"app/a.py","line_number":2},{"message":"Done."}]"#,
+        ))
+        .unwrap();
+
+        let violations = plugin
+            .check(&[PluginFile {
+                file_path: PathBuf::from("app/a.py"),
+                module_path: "app".to_string(),
+                imports: vec![],
+            }])
+            .unwrap();
+        assert_eq!(
+            violations,
+            vec![
+                PluginViolation {
+                    message: "Do not import 'legacy'.".to_string(),
+                    file_path: Some(PathBuf::from("app/a.py")),
+                    line_number: Some(2),
+                },
+                PluginViolation {
+                    message: "Done.".to_string(),
+                    file_path: None,
+                    line_number: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wasm_plugin_abi_version() {
+        assert!(matches!(
+            load(&plugin_wat(ABI_VERSION + 1, "[]")),
+            Err(PluginError::Wasm { .. })
+        ));
+    }
+}
@@ -26,15 +26,29 @@ fn detect_environment() -> TerminalEnvironment {
 }
 
 pub fn create_clickable_link(file_path: &Path, abs_path: &Path, line: &usize) -> String {
+    create_clickable_link_with_column(file_path, abs_path, line, None)
+}
+
+/// A clickable link which also moves the cursor to the given column, when the editor supports it
+pub fn create_clickable_link_with_column(
+    file_path: &Path,
+    abs_path: &Path,
+    line: &usize,
+    column: Option<usize>,
+) -> String {
     let terminal_env = detect_environment();
     let file_path_str = file_path.to_string_lossy().to_string();
     let abs_path_str = abs_path.to_string_lossy().to_string();
+    let position = match column {
+        Some(column) => format!("{}:{}", line, column),
+        None => line.to_string(),
+    };
     let link = match terminal_env {
         TerminalEnvironment::JetBrains => {
-            format!("file://{}:{}", abs_path_str, line)
+            format!("file://{}:{}", abs_path_str, position)
         }
         TerminalEnvironment::VSCode => {
-            format!("vscode://file/{}:{}", abs_path_str, line)
+            format!("vscode://file/{}:{}", abs_path_str, position)
         }
         TerminalEnvironment::Unknown => {
             format!("file://{}", abs_path_str)
@@ -1,6 +1,6 @@
-use crate::checks::{ExternalDependencyChecker, IgnoreDirectivePostProcessor};
+use crate::checks::{ExternalDependencyChecker, IgnoreDirectivePostProcessor, StdlibModules};
 use crate::commands::check;
-use crate::config::{self, ProjectConfig};
+use crate::config::{self, ProjectConfig, SymlinkPolicy};
 use crate::dependencies::import::with_distribution_names;
 use crate::diagnostics::{
     CodeDiagnostic, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails, DiagnosticError,
@@ -8,12 +8,15 @@ use crate::diagnostics::{
 };
 use crate::exclusion::PathExclusions;
 use crate::external::parsing::{parse_pyproject_toml, ProjectInfo};
-use crate::filesystem::{walk_pyfiles, walk_pyprojects, ProjectFile};
+use crate::filesystem::{
+    check_symlink_cycles, walk_pyfiles, walk_pyprojects, FileSystemError, ProjectFile, WalkOptions,
+};
 use crate::interrupt::check_interrupt;
 use crate::processors::file_module::FileModule;
 use crate::processors::ExternalDependencyExtractor;
 use pyo3::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::iter;
 use std::path::{Path, PathBuf};
 
 use dashmap::DashSet;
@@ -34,11 +37,12 @@ struct CheckExternalPipeline<'a> {
 
 impl<'a> CheckExternalPipeline<'a> {
     pub fn new(
+        project_root: &'a Path,
         source_roots: &'a [PathBuf],
         project_config: &'a ProjectConfig,
         project_info: &'a ProjectInfo,
         module_mappings: &'a HashMap<String, Vec<String>>,
-        stdlib_modules: &'a HashSet<String>,
+        stdlib_modules: &'a StdlibModules,
         excluded_external_modules: &'a HashSet<String>,
         exclusions: &'a PathExclusions,
     ) -> Self {
@@ -52,6 +56,8 @@ impl<'a> CheckExternalPipeline<'a> {
                 exclusions,
             ),
             dependency_checker: ExternalDependencyChecker::new(
+                project_root,
+                project_config,
                 project_info,
                 module_mappings,
                 stdlib_modules,
@@ -104,7 +110,8 @@ impl<'a> FileChecker<'a> for CheckExternalPipeline<'a> {
 
 struct CheckExternalMetadata {
     module_mappings: HashMap<String, Vec<String>>,
-    stdlib_modules: Vec<String>,
+    // Keyed by Python version, where None is the version of the running interpreter
+    stdlib_modules: HashMap<Option<String>, Vec<String>>,
 }
 
 /// Get metadata for checking external dependencies.
@@ -121,13 +128,20 @@ fn get_check_external_metadata(
             .expect("Failed to call get_module_mappings")
             .extract()
             .expect("Failed to extract module_mappings");
-        let stdlib_modules: Vec<String> = external_utils
+        let get_stdlib_modules = external_utils
             .getattr("get_stdlib_modules")
-            .expect("Failed to get stdlib_modules")
-            .call0()
-            .expect("Failed to call get_stdlib_modules")
-            .extract()
-            .expect("Failed to extract stdlib_modules");
+            .expect("Failed to get stdlib_modules");
+        let stdlib_modules: HashMap<Option<String>, Vec<String>> = iter::once(None)
+            .chain(project_config.python_versions().into_iter().map(Some))
+            .map(|python_version| {
+                let modules: Vec<String> = get_stdlib_modules
+                    .call1((python_version,))
+                    .expect("Failed to call get_stdlib_modules")
+                    .extract()
+                    .expect("Failed to extract stdlib_modules");
+                (python_version.map(|version| version.to_string()), modules)
+            })
+            .collect();
 
         if !project_config.external.rename.is_empty() {
             for rename_pair in project_config.external.rename.iter() {
@@ -162,17 +176,31 @@ fn check_with_modules(
     project_root: &Path,
     project_config: &ProjectConfig,
     module_mappings: &HashMap<String, Vec<String>>,
-    stdlib_modules: &[String],
+    stdlib_modules: &HashMap<Option<String>, Vec<String>>,
 ) -> Result<Vec<Diagnostic>> {
-    let stdlib_modules: HashSet<String> = stdlib_modules.iter().cloned().collect();
+    let stdlib_modules: StdlibModules = stdlib_modules
+        .iter()
+        .map(|(python_version, modules)| {
+            (python_version.clone(), modules.iter().cloned().collect())
+        })
+        .collect();
     let excluded_external_modules: HashSet<String> =
         project_config.external.exclude.iter().cloned().collect();
     let source_roots: Vec<PathBuf> = project_config.prepend_roots(project_root);
     let exclusions = PathExclusions::new(
         project_root,
         &project_config.exclude,
-        project_config.use_regex_matching,
+        &project_config.ignore,
     )?;
+    if project_config.symlinks == SymlinkPolicy::ErrorOnCycle {
+        for source_root in &source_roots {
+            check_symlink_cycles(
+                &source_root.display().to_string(),
+                &exclusions,
+                WalkOptions::from(project_config),
+            )?;
+        }
+    }
 
     let diagnostics = walk_pyprojects(project_root.to_string_lossy().as_ref(), &exclusions)
         .par_bridge()
@@ -190,6 +218,7 @@ fn check_with_modules(
                 }
             };
             let pipeline = CheckExternalPipeline::new(
+                project_root,
                 &source_roots,
                 project_config,
                 &project_info,
@@ -202,31 +231,46 @@ fn check_with_modules(
                 .source_paths
                 .par_iter()
                 .flat_map(|source_root| {
-                    walk_pyfiles(&source_root.display().to_string(), &exclusions)
-                        .par_bridge()
-                        .flat_map(|file_path| {
-                            if check_interrupt().is_err() {
-                                // Since files are being processed in parallel,
-                                // this will essentially short-circuit all remaining files.
-                                // Then, we check for an interrupt right after, and return the Err if it is set
-                                return vec![];
-                            }
-
-                            let project_file =
-                                match ProjectFile::try_new(project_root, source_root, &file_path) {
-                                    Ok(project_file) => project_file,
-                                    Err(_) => {
-                                        return vec![Diagnostic::new_global_warning(
-                                            DiagnosticDetails::Configuration(
-                                                ConfigurationDiagnostic::SkippedFileIoError {
-                                                    file_path: file_path.display().to_string(),
-                                                },
-                                            ),
-                                        )]
-                                    }
-                                };
-
-                            match pipeline.diagnostics(project_file) {
+                    walk_pyfiles(
+                        &source_root.display().to_string(),
+                        &exclusions,
+                        WalkOptions::from(project_config),
+                    )
+                    .par_bridge()
+                    .flat_map(|file_path| {
+                        if check_interrupt().is_err() {
+                            // Since files are being processed in parallel,
+                            // this will essentially short-circuit all remaining files.
+                            // Then, we check for an interrupt right after, and return the Err if it is set
+                            return vec![];
+                        }
+
+                        let project_files =
+                            match ProjectFile::try_new_all(project_root, source_root, &file_path) {
+                                Ok(project_files) => project_files,
+                                Err(FileSystemError::Notebook(_)) => {
+                                    return vec![Diagnostic::new_global_warning(
+                                        DiagnosticDetails::Configuration(
+                                            ConfigurationDiagnostic::SkippedFileSyntaxError {
+                                                file_path: file_path.display().to_string(),
+                                            },
+                                        ),
+                                    )]
+                                }
+                                Err(_) => {
+                                    return vec![Diagnostic::new_global_warning(
+                                        DiagnosticDetails::Configuration(
+                                            ConfigurationDiagnostic::SkippedFileIoError {
+                                                file_path: file_path.display().to_string(),
+                                            },
+                                        ),
+                                    )]
+                                }
+                            };
+
+                        project_files
+                            .into_iter()
+                            .flat_map(|project_file| match pipeline.diagnostics(project_file) {
                                 Ok(diagnostics) => diagnostics,
                                 Err(DiagnosticError::Io(_))
                                 | Err(DiagnosticError::Filesystem(_)) => {
@@ -254,8 +298,9 @@ fn check_with_modules(
                                         },
                                     ),
                                 )],
-                            }
-                        })
+                            })
+                            .collect::<Vec<_>>()
+                    })
                 })
                 .collect();
 
@@ -296,6 +341,7 @@ mod tests {
     use crate::diagnostics::Severity;
     use crate::tests::fixtures::example_dir;
     use rstest::*;
+    use std::collections::BTreeMap;
 
     #[fixture]
     fn project_config() -> ProjectConfig {
@@ -329,14 +375,20 @@ mod tests {
         module_mapping: HashMap<String, Vec<String>>,
     ) {
         let project_root = example_dir.join("multi_package");
-        let result =
-            check_with_modules(&project_root, &project_config, &module_mapping, &[]).unwrap();
+        let result = check_with_modules(
+            &project_root,
+            &project_config,
+            &module_mapping,
+            &HashMap::new(),
+        )
+        .unwrap();
         assert_eq!(result.len(), 1);
         assert!(matches!(
             result[0],
             Diagnostic::Global {
                 severity: Severity::Error,
-                details: DiagnosticDetails::Code(CodeDiagnostic::UnusedExternalDependency { .. })
+                details: DiagnosticDetails::Code(CodeDiagnostic::UnusedExternalDependency { .. }),
+                ..
             }
         ));
         assert_eq!(
@@ -353,8 +405,13 @@ mod tests {
         project_config: ProjectConfig,
     ) {
         let project_root = example_dir.join("multi_package");
-        let result =
-            check_with_modules(&project_root, &project_config, &HashMap::new(), &[]).unwrap();
+        let result = check_with_modules(
+            &project_root,
+            &project_config,
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
         assert_eq!(result.len(), 3);
         assert!(result.iter().any(|d| d.details()
             == &DiagnosticDetails::Code(CodeDiagnostic::UndeclaredExternalDependency {
@@ -369,4 +426,35 @@ mod tests {
                 package_module_name: "unused".to_string()
             })));
     }
+
+    #[rstest]
+    fn check_external_dependencies_per_root_python_version(
+        example_dir: PathBuf,
+        project_config: ProjectConfig,
+    ) {
+        let project_root = example_dir.join("multi_package");
+        let project_config = ProjectConfig {
+            source_root_python_versions: BTreeMap::from([(
+                PathBuf::from("src/pack-a/src"),
+                "3.8".to_string(),
+            )]),
+            ..project_config
+        };
+        // 'git' is only part of the standard library for the version targeted by 'pack-a'
+        let stdlib_modules = HashMap::from([
+            (None, vec![]),
+            (Some("3.8".to_string()), vec!["git".to_string()]),
+        ]);
+        let result = check_with_modules(
+            &project_root,
+            &project_config,
+            &HashMap::new(),
+            &stdlib_modules,
+        )
+        .unwrap();
+        assert!(!result.iter().any(|d| d.details()
+            == &DiagnosticDetails::Code(CodeDiagnostic::UndeclaredExternalDependency {
+                dependency: "git".to_string()
+            })));
+    }
 }
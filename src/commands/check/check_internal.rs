@@ -1,33 +1,70 @@
 use std::{
-    path::PathBuf,
-    sync::atomic::{AtomicBool, Ordering},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
+use crossbeam_channel::{bounded, RecvTimeoutError};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
 use rayon::prelude::*;
 
 use super::error::CheckError;
+use super::memory::MemoryBudget;
+use super::shard::Shard;
 use crate::{
-    checks::{IgnoreDirectivePostProcessor, InterfaceChecker, InternalDependencyChecker},
-    config::ProjectConfig,
+    cache::{FileResultCache, CHECK_NAMESPACE, IMPORTS_NAMESPACE},
+    checks::{
+        check_conformance, CheckPlugins, IgnoreDirectivePostProcessor, InterfaceChecker,
+        InternalDependencyChecker, PluginFile, RuleEngine, ViolationBudgetPostProcessor,
+    },
+    config::{
+        modules::current_date, CacheBackend, InterfaceDataTypes, ModuleConfig, ProjectConfig,
+        ReexportResolution, RuleSetting, SymlinkPolicy,
+    },
     diagnostics::{
-        ConfigurationDiagnostic, Diagnostic, DiagnosticDetails, DiagnosticError,
-        DiagnosticPipeline, FileChecker, FileProcessor, Result as DiagnosticResult,
+        aggregate_diagnostics, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails,
+        DiagnosticError, DiagnosticPipeline, FileChecker, FileProcessor,
+        Result as DiagnosticResult,
     },
     exclusion::PathExclusions,
-    filesystem::{self as fs, ProjectFile},
+    filesystem::{self as fs, FileOverlay, FileSystemError, ProjectFile, WalkOptions},
+    interfaces::{compiled::CompiledInterfaces, TypeCheckCache},
     interrupt::check_interrupt,
-    modules::{build_module_tree, ModuleTree},
+    modules::{build_module_tree, parsing::find_modules_with_cycles, ModuleTree},
+    ownership::CodeOwners,
     processors::{FileModule, InternalDependencyExtractor},
 };
 
 pub type Result<T> = std::result::Result<T, CheckError>;
 
+/// How often progress is reported while files are being checked
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The number of files checked so far, out of every file being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct CheckProgress {
+    pub checked_files: usize,
+    pub total_files: usize,
+}
+
+/// Called periodically from the thread which started the check, and once more when every file
+/// has been checked. Returning an error cancels the check, which then returns that error.
+pub type ProgressCallback<'a> = dyn FnMut(CheckProgress) -> Result<()> + 'a;
+
 struct CheckInternalPipeline<'a> {
     found_imports: &'a AtomicBool,
     dependency_extractor: InternalDependencyExtractor<'a>,
     dependency_checker: Option<InternalDependencyChecker<'a>>,
     interface_checker: Option<InterfaceChecker<'a>>,
     ignore_directive_post_processor: IgnoreDirectivePostProcessor<'a>,
+    check_cache: Option<&'a FileResultCache>,
+    // The files seen by plugins, which run once every file has been checked
+    plugin_files: Option<Mutex<Vec<PluginFile>>>,
 }
 
 impl<'a> CheckInternalPipeline<'a> {
@@ -49,9 +86,19 @@ impl<'a> CheckInternalPipeline<'a> {
             dependency_checker: None,
             interface_checker: None,
             ignore_directive_post_processor: IgnoreDirectivePostProcessor::new(project_config),
+            check_cache: None,
+            plugin_files: None,
         }
     }
 
+    pub fn with_file_caches(mut self, file_caches: &'a FileCaches) -> Self {
+        self.dependency_extractor = self
+            .dependency_extractor
+            .with_import_cache(file_caches.imports.as_ref());
+        self.check_cache = file_caches.check.as_ref();
+        self
+    }
+
     pub fn with_dependency_checker(
         mut self,
         dependency_checker: Option<InternalDependencyChecker<'a>>,
@@ -67,6 +114,236 @@ impl<'a> CheckInternalPipeline<'a> {
         self.interface_checker = interface_checker;
         self
     }
+
+    pub fn with_plugins(mut self, plugins: &CheckPlugins) -> Self {
+        self.plugin_files = (!plugins.is_empty()).then(Mutex::default);
+        self
+    }
+
+    pub fn take_plugin_files(&self) -> Vec<PluginFile> {
+        self.plugin_files
+            .as_ref()
+            .map(|plugin_files| std::mem::take(&mut *plugin_files.lock().unwrap()))
+            .unwrap_or_default()
+    }
+}
+
+impl<'a> CheckInternalPipeline<'a> {
+    /// Same as `diagnostics`, but consults the shared check cache first.
+    /// Cached entries also record whether the file had imports, so that a fully cached run
+    /// does not report that no first-party imports were found.
+    pub fn cached_diagnostics(
+        &'a self,
+        project_file: ProjectFile<'a>,
+    ) -> DiagnosticResult<Vec<Diagnostic>> {
+        let Some(check_cache) = self.check_cache else {
+            return self.diagnostics(project_file);
+        };
+
+        let file_key = project_file.relative_file_path.display().to_string();
+        let contents = project_file.contents.clone();
+        if let Some((has_imports, diagnostics)) =
+            check_cache.get::<(bool, Vec<Diagnostic>)>(&file_key, &contents)
+        {
+            if has_imports {
+                self.found_imports.store(true, Ordering::Relaxed);
+            }
+            return Ok(diagnostics);
+        }
+
+        let file_module = self.process(project_file)?;
+        let has_imports = file_module.imports().next().is_some();
+        let diagnostics = self.check(&file_module)?;
+        check_cache.set(&file_key, &contents, &(has_imports, &diagnostics));
+        Ok(diagnostics)
+    }
+}
+
+/// The context which the imports of a file are parsed in, or None if they cannot be cached.
+/// String imports and attribute accesses depend on which files exist, not only on the contents of the importing file.
+fn imports_context(project_config: &ProjectConfig) -> Option<String> {
+    if project_config.include_string_imports || project_config.analyze_attribute_access {
+        None
+    } else {
+        Some(format!(
+            "{:?}{}",
+            project_config.source_roots, project_config.ignore_type_checking_imports
+        ))
+    }
+}
+
+/// Per-file results shared through a remote cache backend, or parsed imports kept on disk.
+#[derive(Debug, Default)]
+pub struct FileCaches {
+    imports: Option<FileResultCache>,
+    check: Option<FileResultCache>,
+}
+
+/// Data type checks on interfaces and followed re-exports depend on the contents of other
+/// modules, and plugins and 'unless_through' rules need to see every file, so the outcome
+/// of checking a file cannot be cached by its contents alone
+fn check_reads_other_files(project_config: &ProjectConfig, interfaces: bool) -> bool {
+    (interfaces
+        && project_config
+            .interfaces
+            .iter()
+            .any(|interface| interface.data_types != InterfaceDataTypes::All))
+        || project_config.rules.reexport_resolution == ReexportResolution::Follow
+        || project_config.plugins.has_check_plugins()
+        || project_config
+            .forbid
+            .iter()
+            .any(|rule| rule.unless_through.is_some())
+}
+
+/// Deprecated dependencies become errors once their 'deprecated_until' date has passed,
+/// so the outcome of a check is only valid on the day it was cached
+fn check_date(project_config: &ProjectConfig) -> String {
+    if project_config
+        .all_modules()
+        .flat_map(|module| module.dependencies_iter())
+        .any(|dependency| dependency.deprecated_until.is_some())
+    {
+        current_date()
+    } else {
+        String::new()
+    }
+}
+
+impl FileCaches {
+    #[allow(clippy::too_many_arguments)]
+    pub fn build(
+        project_root: &Path,
+        project_config: &ProjectConfig,
+        source_roots: &[PathBuf],
+        valid_modules: &[ModuleConfig],
+        exclusions: &PathExclusions,
+        overlay: &FileOverlay,
+        dependencies: bool,
+        interfaces: bool,
+    ) -> Result<Self> {
+        if project_config.cache.backend == CacheBackend::Disk {
+            return Ok(Self::default());
+        }
+
+        let imports = match imports_context(project_config) {
+            Some(context) => FileResultCache::new(
+                &project_config.cache,
+                IMPORTS_NAMESPACE,
+                context.into_bytes(),
+            )?,
+            None => None,
+        };
+
+        let check = if check_reads_other_files(project_config, interfaces) {
+            None
+        } else {
+            // Check outcomes depend on the full configuration and on which files exist in the project
+            let mut project_files: Vec<PathBuf> = source_roots
+                .iter()
+                .flat_map(|source_root| {
+                    fs::walk_pyfiles_with_overlay(
+                        &source_root.display().to_string(),
+                        exclusions,
+                        overlay,
+                        WalkOptions::from(project_config),
+                    )
+                    .map(move |file_path| source_root.join(file_path))
+                })
+                .filter_map(|file_path| fs::relative_to(file_path, project_root).ok())
+                .collect();
+            project_files.sort();
+            let context = format!(
+                "{}{}{:?}{}{}{}",
+                serde_json::to_string(project_config).unwrap_or_default(),
+                serde_json::to_string(valid_modules).unwrap_or_default(),
+                project_files,
+                dependencies,
+                interfaces,
+                check_date(project_config)
+            );
+            FileResultCache::new(&project_config.cache, CHECK_NAMESPACE, context.into_bytes())?
+        };
+
+        Ok(Self { imports, check })
+    }
+
+    /// Keep parsed imports in the '.tach' directory when they are not shared through a remote cache,
+    /// so that files which have not changed are not parsed again
+    pub fn with_imports_on_disk(
+        mut self,
+        project_root: &Path,
+        project_config: &ProjectConfig,
+    ) -> Result<Self> {
+        if self.imports.is_none() && project_config.cache.backend == CacheBackend::Disk {
+            if let Some(context) = imports_context(project_config) {
+                self.imports = Some(FileResultCache::on_disk(
+                    project_root,
+                    IMPORTS_NAMESPACE,
+                    context.into_bytes(),
+                )?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// The caches of every combination of enabled checks (dependencies, interfaces) which
+    /// 'tach check' runs with, since each combination caches check results separately.
+    /// Parsed imports do not depend on the enabled checks, so only the first includes them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_all(
+        project_root: &Path,
+        project_config: &ProjectConfig,
+        source_roots: &[PathBuf],
+        valid_modules: &[ModuleConfig],
+        exclusions: &PathExclusions,
+        overlay: &FileOverlay,
+    ) -> Result<Vec<Self>> {
+        [(true, true), (true, false), (false, true)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (dependencies, interfaces))| {
+                let caches = Self::build(
+                    project_root,
+                    project_config,
+                    source_roots,
+                    valid_modules,
+                    exclusions,
+                    overlay,
+                    dependencies,
+                    interfaces,
+                )?;
+                if index == 0 {
+                    caches.with_imports_on_disk(project_root, project_config)
+                } else {
+                    Ok(Self {
+                        imports: None,
+                        ..caches
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the outcome of checking each file is cached
+    pub fn caches_checks(&self) -> bool {
+        self.check.is_some()
+    }
+
+    pub fn imports(&self) -> Option<&FileResultCache> {
+        self.imports.as_ref()
+    }
+
+    /// Remove the entries for the file with the given contents, returning how many were removed
+    pub fn invalidate(&self, file_key: &str, contents: &str) -> Result<usize> {
+        let mut removed = 0;
+        for cache in self.imports.iter().chain(self.check.iter()) {
+            if cache.invalidate(file_key, contents)? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
 }
 
 impl<'a> FileProcessor<'a, ProjectFile<'a>> for CheckInternalPipeline<'a> {
@@ -111,6 +388,27 @@ impl<'a> FileChecker<'a> for CheckInternalPipeline<'a> {
             processed_file.relative_file_path(),
         );
 
+        // The rest of the file was checked, but imports near the syntax error may have been missed
+        if let Some(syntax_error) = processed_file.syntax_errors.first() {
+            diagnostics.push(Diagnostic::new_located_warning(
+                processed_file.relative_file_path().to_path_buf(),
+                processed_file.line_number(syntax_error.start()),
+                None,
+                DiagnosticDetails::Configuration(
+                    ConfigurationDiagnostic::PartiallySkippedFileSyntaxError {
+                        file_path: processed_file.relative_file_path().display().to_string(),
+                    },
+                ),
+            ));
+        }
+
+        if let Some(plugin_files) = &self.plugin_files {
+            plugin_files
+                .lock()
+                .unwrap()
+                .push(PluginFile::from(processed_file));
+        }
+
         Ok(diagnostics)
     }
 }
@@ -120,6 +418,270 @@ pub fn check(
     project_config: &ProjectConfig,
     dependencies: bool,
     interfaces: bool,
+) -> Result<Vec<Diagnostic>> {
+    check_with_overlay(
+        project_root,
+        project_config,
+        dependencies,
+        interfaces,
+        &FileOverlay::new(),
+    )
+}
+
+/// Check the project with the contents of any files in `overlay` taking precedence over
+/// the contents on disk. This allows checking unsaved editor buffers.
+pub fn check_with_overlay(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    dependencies: bool,
+    interfaces: bool,
+    overlay: &FileOverlay,
+) -> Result<Vec<Diagnostic>> {
+    check_files(
+        project_root,
+        project_config,
+        dependencies,
+        interfaces,
+        overlay,
+        None,
+        None,
+        None,
+    )
+}
+
+/// Check only the files which belong to `shard`, while still building the full module tree.
+/// Violation budgets are not applied, since they depend on the violations in every shard
+/// (see `shard::merge_shards`).
+pub fn check_shard(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    dependencies: bool,
+    interfaces: bool,
+    shard: Shard,
+) -> Result<Vec<Diagnostic>> {
+    check_files(
+        project_root,
+        project_config,
+        dependencies,
+        interfaces,
+        &FileOverlay::new(),
+        Some(shard),
+        None,
+        None,
+    )
+}
+
+/// Check the project (or one shard of it), reporting progress to `progress` as files are checked
+#[allow(clippy::too_many_arguments)]
+pub fn check_with_progress(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    dependencies: bool,
+    interfaces: bool,
+    overlay: &FileOverlay,
+    shard: Option<Shard>,
+    memory_budget: Option<MemoryBudget>,
+    progress: &mut ProgressCallback,
+) -> Result<Vec<Diagnostic>> {
+    check_files(
+        project_root,
+        project_config,
+        dependencies,
+        interfaces,
+        overlay,
+        shard,
+        memory_budget,
+        Some(progress),
+    )
+}
+
+/// The module tree and interface type checks of a project, along with the config they were built from
+type FileCheckState = Option<(String, Arc<ModuleTree>, Arc<TypeCheckCache>)>;
+
+/// The state of the project most recently checked by `check_file`. Building the module tree and
+/// type checking interface members takes much longer than checking a single file, so they are
+/// reused until the modules change.
+static FILE_CHECK_MODULE_TREE: Lazy<Mutex<FileCheckState>> = Lazy::new(Mutex::default);
+
+/// Interface members are type checked against the files on disk, since the cached results
+/// are shared by every file which is checked.
+fn cached_module_tree(
+    cache: &Mutex<FileCheckState>,
+    project_config: &ProjectConfig,
+    source_roots: &[PathBuf],
+    valid_modules: &[ModuleConfig],
+) -> Result<(Arc<ModuleTree>, Arc<TypeCheckCache>)> {
+    let key = format!(
+        "{:?}{}{}",
+        source_roots,
+        serde_json::to_string(project_config).unwrap_or_default(),
+        serde_json::to_string(valid_modules).unwrap_or_default()
+    );
+    let mut cached = cache.lock().unwrap();
+    if let Some((cached_key, module_tree, type_check_cache)) = cached.as_ref() {
+        if *cached_key == key {
+            return Ok((module_tree.clone(), type_check_cache.clone()));
+        }
+    }
+    let module_tree = Arc::new(build_module_tree(
+        source_roots,
+        valid_modules,
+        project_config.forbids_circular_dependencies(),
+        project_config.circular_domain_groups().as_ref(),
+        project_config.root_module.clone(),
+    )?);
+    let type_check_cache = Arc::new(TypeCheckCache::build(
+        &CompiledInterfaces::build(project_config.all_interfaces()),
+        valid_modules,
+        source_roots,
+        &FileOverlay::new(),
+    )?);
+    *cached = Some((key, module_tree.clone(), type_check_cache.clone()));
+    Ok((module_tree, type_check_cache))
+}
+
+/// Check a single file, with `contents` in place of the file on disk (such as an unsaved editor buffer).
+/// Only the diagnostics for this file are reported: checks which depend on the whole project, such as
+/// violation budgets, naming rules and plugins, are left to a full check.
+/// Files outside of the source roots, or which are excluded, have no diagnostics.
+pub fn check_file(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    file_path: &Path,
+    contents: String,
+) -> Result<Vec<Diagnostic>> {
+    if !project_root.is_dir() {
+        return Err(CheckError::InvalidDirectory(
+            project_root.display().to_string(),
+        ));
+    }
+
+    let file_path = project_root.join(file_path);
+    let source_roots: Vec<PathBuf> = project_config.prepend_roots(project_root);
+    // When source roots are nested, the most specific root wins
+    let Some(source_root) = source_roots
+        .iter()
+        .filter(|source_root| file_path.starts_with(source_root))
+        .max_by_key(|source_root| source_root.components().count())
+    else {
+        return Ok(vec![]);
+    };
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    if exclusions.is_path_excluded(&file_path) {
+        return Ok(vec![]);
+    }
+
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    let (module_tree, type_check_cache) = cached_module_tree(
+        &FILE_CHECK_MODULE_TREE,
+        project_config,
+        &source_roots,
+        &valid_modules,
+    )?;
+    let relative_file_path = fs::relative_to(&file_path, source_root)?;
+    let overlay = FileOverlay::from([(file_path.clone(), contents)]);
+    let found_imports = AtomicBool::new(false);
+    let rule_engine = RuleEngine::new(project_config)?;
+    let pipeline = CheckInternalPipeline::new(
+        project_config,
+        &source_roots,
+        &module_tree,
+        &exclusions,
+        &found_imports,
+    )
+    .with_dependency_checker(Some(
+        InternalDependencyChecker::new(project_config, &module_tree)
+            .with_reexport_resolution(&source_roots, &overlay)
+            .with_rule_engine(&rule_engine),
+    ))
+    .with_interface_checker(Some(
+        InterfaceChecker::new(project_config, &module_tree)
+            .with_shared_type_check_cache(type_check_cache),
+    ));
+
+    let project_files = match ProjectFile::try_new_all_with_overlay(
+        project_root,
+        source_root,
+        &relative_file_path,
+        &overlay,
+    ) {
+        Ok(project_files) => project_files,
+        Err(FileSystemError::Notebook(_)) => {
+            return Ok(vec![Diagnostic::new_global_warning(
+                DiagnosticDetails::Configuration(ConfigurationDiagnostic::SkippedFileSyntaxError {
+                    file_path: relative_file_path.display().to_string(),
+                }),
+            )])
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let mut diagnostics: Vec<Diagnostic> = project_files
+        .into_iter()
+        .flat_map(|project_file| match pipeline.diagnostics(project_file) {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => vec![skipped_file_warning(&relative_file_path, &err)],
+        })
+        .collect();
+    if let Some(code_owners) = CodeOwners::discover(project_root) {
+        code_owners.annotate(&mut diagnostics);
+    }
+    Ok(aggregate_diagnostics(diagnostics))
+}
+
+/// The warning reported for a file which could not be checked
+fn skipped_file_warning(file_path: &Path, error: &DiagnosticError) -> Diagnostic {
+    let file_path = file_path.display().to_string();
+    Diagnostic::new_global_warning(DiagnosticDetails::Configuration(match error {
+        DiagnosticError::Io(_) | DiagnosticError::Filesystem(_) => {
+            ConfigurationDiagnostic::SkippedFileIoError { file_path }
+        }
+        DiagnosticError::ImportParse(_) => {
+            ConfigurationDiagnostic::SkippedFileSyntaxError { file_path }
+        }
+        _ => ConfigurationDiagnostic::SkippedUnknownError { file_path },
+    }))
+}
+
+/// The files within `source_root` which are checked, limited to those in `shard` if any
+fn walk_source_root<'a>(
+    project_root: &'a Path,
+    source_root: &'a Path,
+    exclusions: &'a PathExclusions,
+    overlay: &'a FileOverlay,
+    options: WalkOptions,
+    shard: Option<Shard>,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    fs::walk_pyfiles_with_overlay(
+        &source_root.display().to_string(),
+        exclusions,
+        overlay,
+        options,
+    )
+    .filter(move |file_path| {
+        shard.is_none_or(|shard| {
+            let absolute_file_path = source_root.join(file_path);
+            shard.contains(
+                &fs::relative_to(&absolute_file_path, project_root).unwrap_or(absolute_file_path),
+            )
+        })
+    })
+}
+
+#[tracing::instrument(level = "info", name = "check", skip_all, fields(dependencies, interfaces, shard = ?shard, memory_budget = ?memory_budget, files = tracing::field::Empty, errors = tracing::field::Empty, warnings = tracing::field::Empty))]
+#[allow(clippy::too_many_arguments)]
+fn check_files(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    dependencies: bool,
+    interfaces: bool,
+    overlay: &FileOverlay,
+    shard: Option<Shard>,
+    memory_budget: Option<MemoryBudget>,
+    progress: Option<&mut ProgressCallback>,
 ) -> Result<Vec<Diagnostic>> {
     if !dependencies && !interfaces {
         return Err(CheckError::NoChecksEnabled());
@@ -134,10 +696,7 @@ pub fn check(
     let mut warnings = Vec::new();
     let found_imports = AtomicBool::new(false);
     let source_roots: Vec<PathBuf> = project_config.prepend_roots(&project_root);
-    let (valid_modules, invalid_modules) = fs::validate_project_modules(
-        &source_roots,
-        project_config.all_modules().cloned().collect(),
-    );
+    let (valid_modules, invalid_modules) = project_config.validate_modules(&source_roots);
 
     for module in &invalid_modules {
         warnings.push(Diagnostic::new_global_warning(
@@ -151,12 +710,18 @@ pub fn check(
     let module_tree = build_module_tree(
         &source_roots,
         &valid_modules,
-        project_config.forbid_circular_dependencies,
+        project_config.forbids_circular_dependencies(),
+        project_config.circular_domain_groups().as_ref(),
         project_config.root_module.clone(),
     )?;
 
+    let rule_engine = RuleEngine::new(project_config)?;
     let dependency_checker = if dependencies {
-        Some(InternalDependencyChecker::new(project_config, &module_tree))
+        Some(
+            InternalDependencyChecker::new(project_config, &module_tree)
+                .with_reexport_resolution(&source_roots, overlay)
+                .with_rule_engine(&rule_engine),
+        )
     } else {
         None
     };
@@ -164,7 +729,8 @@ pub fn check(
     let interface_checker = if interfaces {
         let interface_checker = InterfaceChecker::new(project_config, &module_tree);
         // This is expensive
-        Some(interface_checker.with_type_check_cache(&valid_modules, &source_roots)?)
+        let _span = tracing::debug_span!("build_type_check_cache").entered();
+        Some(interface_checker.with_type_check_cache(&valid_modules, &source_roots, overlay)?)
     } else {
         None
     };
@@ -172,8 +738,32 @@ pub fn check(
     let exclusions = PathExclusions::new(
         &project_root,
         &project_config.exclude,
-        project_config.use_regex_matching,
+        &project_config.ignore,
     )?;
+    if project_config.symlinks == SymlinkPolicy::ErrorOnCycle {
+        for source_root in &source_roots {
+            fs::check_symlink_cycles(
+                &source_root.display().to_string(),
+                &exclusions,
+                WalkOptions::from(project_config),
+            )?;
+        }
+    }
+    let mut plugins = CheckPlugins::load(&project_root, project_config)?;
+    let file_caches = FileCaches::build(
+        &project_root,
+        project_config,
+        &source_roots,
+        &valid_modules,
+        &exclusions,
+        overlay,
+        dependencies,
+        interfaces,
+    )?;
+    let file_caches = match memory_budget {
+        Some(_) => file_caches.with_imports_on_disk(&project_root, project_config)?,
+        None => file_caches,
+    };
     let pipeline = CheckInternalPipeline::new(
         project_config,
         &source_roots,
@@ -182,74 +772,336 @@ pub fn check(
         &found_imports,
     )
     .with_dependency_checker(dependency_checker)
-    .with_interface_checker(interface_checker);
-
-    let diagnostics = source_roots.par_iter().flat_map(|source_root| {
-        fs::walk_pyfiles(&source_root.display().to_string(), &exclusions)
-            .par_bridge()
-            .flat_map(|file_path| {
-                if check_interrupt().is_err() {
-                    // Since files are being processed in parallel,
-                    // this will essentially short-circuit all remaining files.
-                    // Then, we check for an interrupt right after, and return the Err if it is set
-                    return vec![];
-                }
+    .with_interface_checker(interface_checker)
+    .with_plugins(&plugins)
+    .with_file_caches(&file_caches);
 
-                let project_file =
-                    match ProjectFile::try_new(&project_root, source_root, &file_path) {
-                        Ok(project_file) => project_file,
-                        Err(_) => {
-                            return vec![Diagnostic::new_global_warning(
-                                DiagnosticDetails::Configuration(
-                                    ConfigurationDiagnostic::SkippedFileIoError {
-                                        file_path: file_path.display().to_string(),
-                                    },
-                                ),
-                            )]
-                        }
-                    };
+    // Files are counted in a first pass rather than listed, so that progress can be reported
+    // against the total without keeping the path of every file in memory
+    let total_files = if progress.is_some() {
+        let _span = tracing::debug_span!("count_files").entered();
+        let total_files: usize = source_roots
+            .iter()
+            .map(|source_root| {
+                walk_source_root(
+                    &project_root,
+                    source_root,
+                    &exclusions,
+                    overlay,
+                    WalkOptions::from(project_config),
+                    shard,
+                )
+                .count()
+            })
+            .sum();
+        tracing::debug!(total_files, "found files to check");
+        total_files
+    } else {
+        0
+    };
+    let checked_files = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
 
-                match pipeline.diagnostics(project_file) {
+    let check_one = |source_root: &PathBuf, file_path: &Path| -> Vec<Diagnostic> {
+        let _span = tracing::trace_span!("check_file", path = %file_path.display()).entered();
+        // Notebooks are checked one code cell at a time
+        let project_files = match ProjectFile::try_new_all_with_overlay(
+            &project_root,
+            source_root,
+            file_path,
+            overlay,
+        ) {
+            Ok(project_files) => project_files,
+            Err(FileSystemError::Notebook(_)) => {
+                return vec![Diagnostic::new_global_warning(
+                    DiagnosticDetails::Configuration(
+                        ConfigurationDiagnostic::SkippedFileSyntaxError {
+                            file_path: file_path.display().to_string(),
+                        },
+                    ),
+                )]
+            }
+            Err(_) => {
+                return vec![Diagnostic::new_global_warning(
+                    DiagnosticDetails::Configuration(ConfigurationDiagnostic::SkippedFileIoError {
+                        file_path: file_path.display().to_string(),
+                    }),
+                )]
+            }
+        };
+
+        project_files
+            .into_iter()
+            .flat_map(
+                |project_file| match pipeline.cached_diagnostics(project_file) {
                     Ok(diagnostics) => diagnostics,
-                    Err(DiagnosticError::Io(_)) | Err(DiagnosticError::Filesystem(_)) => {
-                        vec![Diagnostic::new_global_warning(
-                            DiagnosticDetails::Configuration(
-                                ConfigurationDiagnostic::SkippedFileIoError {
-                                    file_path: file_path.display().to_string(),
-                                },
-                            ),
-                        )]
-                    }
-                    Err(DiagnosticError::ImportParse(_)) => {
-                        vec![Diagnostic::new_global_warning(
-                            DiagnosticDetails::Configuration(
-                                ConfigurationDiagnostic::SkippedFileSyntaxError {
-                                    file_path: file_path.display().to_string(),
-                                },
-                            ),
-                        )]
+                    Err(err) => vec![skipped_file_warning(file_path, &err)],
+                },
+            )
+            .collect::<Vec<_>>()
+    };
+    let check_next_file = |source_root: &PathBuf, file_path: &Path| -> Vec<Diagnostic> {
+        if cancelled.load(Ordering::Relaxed) || check_interrupt().is_err() {
+            // Since files are being processed in parallel,
+            // this will essentially short-circuit all remaining files.
+            // Then, we check for an interrupt right after, and return the Err if it is set
+            return vec![];
+        }
+        let diagnostics = check_one(source_root, file_path);
+        checked_files.fetch_add(1, Ordering::Relaxed);
+        diagnostics
+    };
+    let check_source_root = |source_root: &PathBuf| -> Vec<Diagnostic> {
+        walk_source_root(
+            &project_root,
+            source_root,
+            &exclusions,
+            overlay,
+            WalkOptions::from(project_config),
+            shard,
+        )
+        .par_bridge()
+        .flat_map(|file_path| check_next_file(source_root, file_path.as_path()))
+        .collect()
+    };
+
+    let thread_pool = memory_budget
+        .map(|memory_budget| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(memory_budget.threads())
+                .build()
+        })
+        .transpose()?;
+    let check_all_files = || -> Vec<Diagnostic> {
+        match &thread_pool {
+            None => source_roots
+                .par_iter()
+                .flat_map(check_source_root)
+                .collect(),
+            // One source root at a time, releasing the modules resolved for each of them
+            Some(thread_pool) => thread_pool.install(|| {
+                source_roots
+                    .iter()
+                    .flat_map(|source_root| {
+                        let diagnostics = check_source_root(source_root);
+                        fs::clear_module_resolution_cache();
+                        diagnostics
+                    })
+                    .collect()
+            }),
+        }
+    };
+
+    let diagnostics = match progress {
+        None => check_all_files(),
+        Some(progress) => std::thread::scope(|scope| {
+            let (sender, receiver) = bounded(1);
+            scope.spawn(|| {
+                let _ = sender.send(check_all_files());
+            });
+            let mut progress_error = None;
+            let diagnostics = loop {
+                match receiver.recv_timeout(PROGRESS_INTERVAL) {
+                    Ok(diagnostics) => break diagnostics,
+                    Err(RecvTimeoutError::Timeout) if progress_error.is_none() => {
+                        let current = CheckProgress {
+                            checked_files: checked_files.load(Ordering::Relaxed),
+                            total_files,
+                        };
+                        if let Err(err) = progress(current) {
+                            // Stop checking the remaining files
+                            cancelled.store(true, Ordering::Relaxed);
+                            progress_error = Some(err);
+                        }
                     }
-                    Err(_) => vec![Diagnostic::new_global_warning(
-                        DiagnosticDetails::Configuration(
-                            ConfigurationDiagnostic::SkippedUnknownError {
-                                file_path: file_path.display().to_string(),
-                            },
-                        ),
-                    )],
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // The check panicked, which is propagated when the scope ends
+                    Err(RecvTimeoutError::Disconnected) => break vec![],
                 }
-            })
-    });
+            };
+            match progress_error {
+                Some(err) => Err(err),
+                None => {
+                    progress(CheckProgress {
+                        checked_files: checked_files.load(Ordering::Relaxed),
+                        total_files,
+                    })?;
+                    Ok(diagnostics)
+                }
+            }
+        })?,
+    };
 
     if check_interrupt().is_err() {
         return Err(CheckError::Interrupt);
     }
 
-    let mut final_diagnostics: Vec<Diagnostic> = diagnostics.collect();
+    let mut final_diagnostics: Vec<Diagnostic> = diagnostics;
+    if let Some(dependency_checker) = &pipeline.dependency_checker {
+        final_diagnostics
+            .extend(rule_engine.check_mediation(&dependency_checker.take_module_edges()));
+    }
+    final_diagnostics.extend(rule_engine.check_naming(&valid_modules));
+    if interfaces {
+        final_diagnostics.extend(check_conformance(
+            &project_root,
+            &source_roots,
+            &valid_modules,
+            overlay,
+            &project_config.rules.interface_conformance,
+        ));
+    }
+    final_diagnostics.extend(plugins.run(pipeline.take_plugin_files())?);
+    if project_config.forbid_circular_dependencies
+        && project_config.rules.circular_dependencies == RuleSetting::Warn
+    {
+        final_diagnostics.extend(find_modules_with_cycles(&valid_modules).into_iter().map(
+            |module_path| {
+                Diagnostic::new_global_warning(DiagnosticDetails::Configuration(
+                    ConfigurationDiagnostic::CircularDependency {
+                        module_path: module_path.clone(),
+                    },
+                ))
+            },
+        ));
+    }
+    if shard.is_none() {
+        ViolationBudgetPostProcessor::new(&valid_modules, project_config.max_violations)
+            .process_diagnostics(&mut final_diagnostics);
+    }
     if !found_imports.load(Ordering::Relaxed) {
         final_diagnostics.push(Diagnostic::new_global_warning(
             DiagnosticDetails::Configuration(ConfigurationDiagnostic::NoFirstPartyImportsFound()),
         ));
     }
+    if let Some(code_owners) = CodeOwners::discover(&project_root) {
+        code_owners.annotate(&mut final_diagnostics);
+    }
+    let final_diagnostics = aggregate_diagnostics(final_diagnostics);
 
+    // Result counts, for profiles and OTLP export
+    let span = tracing::Span::current();
+    span.record("files", checked_files.load(Ordering::Relaxed));
+    span.record(
+        "errors",
+        final_diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .count(),
+    );
+    span.record(
+        "warnings",
+        final_diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_warning())
+            .count(),
+    );
     Ok(final_diagnostics)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::config::DependencyConfig;
+
+    #[test]
+    fn test_check_file_uses_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        for (path, contents) in [
+            ("a/__init__.py", "from b import x\n"),
+            ("b/__init__.py", "x = 1\n"),
+            ("c/__init__.py", ""),
+        ] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let project_config = ProjectConfig {
+            modules: vec![
+                ModuleConfig {
+                    depends_on: Some(vec![DependencyConfig::from_path("b")]),
+                    ..ModuleConfig::new("a", false)
+                },
+                ModuleConfig::new("b", false),
+                ModuleConfig::new("c", false),
+            ],
+            ..Default::default()
+        };
+        let check = |contents: &str| {
+            check_file(
+                temp_dir.path(),
+                &project_config,
+                Path::new("a/__init__.py"),
+                contents.to_string(),
+            )
+            .unwrap()
+        };
+
+        assert!(check("from b import x\n").is_empty());
+        // The contents on disk are not checked
+        let diagnostics = check("from b import x\nfrom c import y\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].line_number(), Some(2));
+    }
+
+    #[test]
+    fn test_check_file_reuses_cached_module_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        for path in ["a/__init__.py", "b/__init__.py"] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let mut project_config = ProjectConfig {
+            modules: vec![ModuleConfig::new("a", false), ModuleConfig::new("b", false)],
+            ..Default::default()
+        };
+        let source_roots = project_config.prepend_roots(temp_dir.path());
+        let cache = Mutex::default();
+        let cached = |project_config: &ProjectConfig| {
+            let (valid_modules, _) = project_config.validate_modules(&source_roots);
+            cached_module_tree(&cache, project_config, &source_roots, &valid_modules).unwrap()
+        };
+
+        let (module_tree, type_check_cache) = cached(&project_config);
+        let (reused_tree, reused_type_check_cache) = cached(&project_config);
+        assert!(Arc::ptr_eq(&module_tree, &reused_tree));
+        assert!(Arc::ptr_eq(&type_check_cache, &reused_type_check_cache));
+
+        // Both are rebuilt once the modules change
+        project_config.modules.pop();
+        let (rebuilt_tree, rebuilt_type_check_cache) = cached(&project_config);
+        assert!(!Arc::ptr_eq(&module_tree, &rebuilt_tree));
+        assert!(!Arc::ptr_eq(&type_check_cache, &rebuilt_type_check_cache));
+    }
+
+    #[test]
+    fn test_followed_reexports_are_not_cached_per_file() {
+        let mut project_config = ProjectConfig::default();
+        assert!(!check_reads_other_files(&project_config, true));
+        project_config.rules.reexport_resolution = ReexportResolution::Follow;
+        assert!(check_reads_other_files(&project_config, true));
+    }
+
+    #[test]
+    fn test_check_date_only_with_deprecation_deadlines() {
+        let mut project_config = ProjectConfig {
+            modules: vec![ModuleConfig {
+                depends_on: Some(vec![DependencyConfig::from_path("b")]),
+                ..ModuleConfig::new("a", false)
+            }],
+            ..Default::default()
+        };
+        assert_eq!(check_date(&project_config), "");
+        project_config.modules[0].depends_on = Some(vec![DependencyConfig {
+            deprecated: true,
+            deprecated_until: Some("2020-01-01".to_string()),
+            ..DependencyConfig::from_path("b")
+        }]);
+        assert_eq!(check_date(&project_config), current_date());
+    }
+}
@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::cache::CacheError;
+use crate::checks::plugin::PluginError;
+use crate::checks::rule_engine::RuleEngineError;
 use crate::diagnostics::DiagnosticError;
 use crate::exclusion;
 use crate::filesystem as fs;
@@ -26,4 +29,16 @@ pub enum CheckError {
     Diagnostic(#[from] DiagnosticError),
     #[error("Config error: {0}")]
     ConfigError(String),
+    #[error("Cache error: {0}")]
+    Cache(#[from] CacheError),
+    #[error("Invalid shard '{0}', expected INDEX/COUNT with 1 <= INDEX <= COUNT (e.g. '3/8').")]
+    InvalidShard(String),
+    #[error("{0}")]
+    Plugin(#[from] PluginError),
+    #[error("{0}")]
+    RuleEngine(#[from] RuleEngineError),
+    #[error("Failed to start the threads which check files: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+    #[error("Failed to parse the output of a check shard: {0}")]
+    ShardOutput(#[from] serde_json::Error),
 }
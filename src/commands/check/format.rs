@@ -1,8 +1,11 @@
 use crate::{
-    cli::{create_clickable_link, fail, warning},
+    cli::{create_clickable_link_with_column, fail, warning},
     diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails, Severity},
 };
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use console::style;
 use itertools::Itertools;
@@ -22,8 +25,23 @@ impl From<&DiagnosticDetails> for DiagnosticGroupKind {
             DiagnosticDetails::Configuration(..) => Self::Configuration,
             DiagnosticDetails::Code(code_diagnostic_details) => match code_diagnostic_details {
                 CodeDiagnostic::UndeclaredDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::ForbiddenRootModuleUsage { .. } => Self::InternalDependency,
+                CodeDiagnostic::ForbiddenRootModuleDependency { .. } => Self::InternalDependency,
                 CodeDiagnostic::DeprecatedDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::DeprecatedMemberDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::ExpiredDeprecatedDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::RestrictedMemberDependency { .. } => Self::InternalDependency,
                 CodeDiagnostic::LayerViolation { .. } => Self::InternalDependency,
+                CodeDiagnostic::UnstableDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::UtilityDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::PlatformDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::DomainBoundaryViolation { .. } => Self::InternalDependency,
+                CodeDiagnostic::InternalStructureViolation { .. } => Self::InternalDependency,
+                CodeDiagnostic::ForbiddenDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::UnmediatedDependency { .. } => Self::InternalDependency,
+                CodeDiagnostic::ModuleNamingViolation { .. } => Self::Other,
+                CodeDiagnostic::MissingInterfaceMember { .. } => Self::Interface,
+                CodeDiagnostic::InterfaceSignatureMismatch { .. } => Self::Interface,
                 CodeDiagnostic::PrivateDependency { .. } => Self::Interface,
                 CodeDiagnostic::InvalidDataTypeExport { .. } => Self::Interface,
                 CodeDiagnostic::UndeclaredExternalDependency { .. } => Self::ExternalDependency,
@@ -31,6 +49,17 @@ impl From<&DiagnosticDetails> for DiagnosticGroupKind {
                 CodeDiagnostic::UnnecessarilyIgnoredDependency { .. } => Self::Other,
                 CodeDiagnostic::UnusedIgnoreDirective() => Self::Other,
                 CodeDiagnostic::MissingIgnoreDirectiveReason() => Self::Other,
+                CodeDiagnostic::RelativeImportBeyondSourceRoot { .. } => Self::Other,
+                CodeDiagnostic::UnresolvedRelativeImport { .. } => Self::Other,
+                CodeDiagnostic::UnresolvedImport { .. } => Self::Other,
+                CodeDiagnostic::WildcardImport { .. } => Self::Other,
+                CodeDiagnostic::RelativeImport { .. } => Self::Other,
+                CodeDiagnostic::ConditionalImport { .. } => Self::Other,
+                CodeDiagnostic::PluginViolation { .. } => Self::Other,
+                CodeDiagnostic::ViolationBudgetExceeded { .. } => Self::Other,
+                CodeDiagnostic::WithinViolationBudget { .. } => Self::Other,
+                CodeDiagnostic::ProjectViolationBudgetExceeded { .. } => Self::Other,
+                CodeDiagnostic::WithinProjectViolationBudget { .. } => Self::Other,
             },
         }
     }
@@ -82,6 +111,20 @@ impl<'a> DiagnosticGroup<'a> {
         }
     }
 
+    fn new_owner(severity: Severity, owner: Option<&str>) -> Self {
+        let header = match owner {
+            Some(owner) => style(owner).bold(),
+            None => style("Unowned").bold(),
+        };
+        Self {
+            kind: DiagnosticGroupKind::Other,
+            severity,
+            header: header.to_string(),
+            diagnostics: vec![],
+            footer: None,
+        }
+    }
+
     fn add_diagnostic(&mut self, diagnostic: &'a Diagnostic) {
         self.diagnostics.push(diagnostic);
     }
@@ -94,13 +137,14 @@ impl<'a> DiagnosticGroup<'a> {
                 return severity_order;
             }
 
-            // Then sort by file path (None first)
+            // Then sort by file path (None first), and line
             match (a.file_path(), b.file_path()) {
                 (None, None) => std::cmp::Ordering::Equal,
                 (None, Some(_)) => std::cmp::Ordering::Less,
                 (Some(_), None) => std::cmp::Ordering::Greater,
                 (Some(a_path), Some(b_path)) => a_path.cmp(b_path),
             }
+            .then_with(|| a.line_number().cmp(&b.line_number()))
         });
     }
 }
@@ -120,14 +164,23 @@ impl DiagnosticFormatter {
         let error_location = match local_error_path {
             Some(path) => {
                 let absolute_error_path = self.project_root.join(path);
-                create_clickable_link(
+                create_clickable_link_with_column(
                     path,
                     &absolute_error_path,
                     &diagnostic.line_number().unwrap(),
+                    diagnostic.column(),
                 )
             }
             None => diagnostic.severity().to_string(),
         };
+        let message = match diagnostic.occurrences() {
+            1 => diagnostic.message(),
+            occurrences => format!("{} (found {} times)", diagnostic.message(), occurrences),
+        };
+        let message = match diagnostic.rule_id() {
+            Some(rule_id) => format!("{} {}", rule_id, message),
+            None => message,
+        };
 
         match diagnostic.severity() {
             Severity::Error => format!(
@@ -135,14 +188,14 @@ impl DiagnosticFormatter {
                 fail(),
                 style(error_location).red().bold(),
                 style(":").yellow().bold(),
-                style(diagnostic.message()).yellow(),
+                style(&message).yellow(),
             ),
             Severity::Warning => format!(
                 "{} {}{} {}",
                 warning(),
                 style(error_location).yellow().bold(),
                 style(":").yellow().bold(),
-                style(diagnostic.message()).yellow(),
+                style(&message).yellow(),
             ),
         }
     }
@@ -187,4 +240,36 @@ impl DiagnosticFormatter {
 
         formatted_diagnostics.join("\n\n")
     }
+
+    /// Group diagnostics by the owners of their files, so that they can be routed to the responsible teams.
+    /// Diagnostics with several owners appear under each of them, and those without owners are listed last.
+    pub fn format_diagnostics_by_owner(&self, diagnostics: &[Diagnostic]) -> String {
+        let mut groups: BTreeMap<Option<String>, DiagnosticGroup> = BTreeMap::new();
+
+        for diagnostic in diagnostics {
+            let owners = diagnostic.owners();
+            let owners = if owners.is_empty() {
+                vec![None]
+            } else {
+                owners.into_iter().map(Some).collect()
+            };
+            for owner in owners {
+                let group = groups.entry(owner.clone()).or_insert_with(|| {
+                    DiagnosticGroup::new_owner(diagnostic.severity(), owner.as_deref())
+                });
+                // The header is highlighted as an error if any diagnostic in the group is an error
+                if diagnostic.is_error() {
+                    group.severity = Severity::Error;
+                }
+                group.add_diagnostic(diagnostic);
+            }
+        }
+
+        groups
+            .into_iter()
+            .sorted_by_key(|(owner, _)| (owner.is_none(), owner.clone()))
+            .map(|(_, mut group)| self.format_diagnostic_group(&mut group))
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
 }
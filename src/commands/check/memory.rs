@@ -0,0 +1,45 @@
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// The assumed peak memory of checking a single file: its contents, syntax tree and imports
+const MEMORY_PER_FILE_MB: usize = 32;
+/// Memory set aside for the state shared by every file, such as the module tree
+const BASE_MEMORY_MB: usize = 128;
+
+/// A limit on the memory used by 'tach check --max-memory'.
+/// Files are checked one source root at a time, with only as many files in flight at once
+/// as fit within the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+    max_memory_mb: usize,
+}
+
+impl MemoryBudget {
+    pub fn new(max_memory_mb: usize) -> Self {
+        Self { max_memory_mb }
+    }
+
+    /// The number of files which are checked at once, which is never more than
+    /// the available parallelism and never less than one
+    pub fn threads(&self) -> usize {
+        let available = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        (self.max_memory_mb.saturating_sub(BASE_MEMORY_MB) / MEMORY_PER_FILE_MB).clamp(1, available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threads() {
+        let available = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        assert_eq!(MemoryBudget::new(0).threads(), 1);
+        assert_eq!(MemoryBudget::new(BASE_MEMORY_MB + 10).threads(), 1);
+        assert_eq!(
+            MemoryBudget::new(BASE_MEMORY_MB + 2 * MEMORY_PER_FILE_MB).threads(),
+            2.min(available)
+        );
+        assert_eq!(MemoryBudget::new(usize::MAX).threads(), available);
+    }
+}
@@ -2,7 +2,13 @@ pub mod check_external;
 pub mod check_internal;
 pub mod error;
 pub mod format;
+pub mod memory;
+pub mod shard;
 
 pub use check_external::check as check_external;
 pub use check_internal::check as check_internal;
+pub use check_internal::check_file;
+pub use check_internal::check_with_overlay as check_internal_with_overlay;
 pub use error::CheckError;
+pub use memory::MemoryBudget;
+pub use shard::Shard;
@@ -0,0 +1,183 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use super::error::CheckError;
+use crate::checks::ViolationBudgetPostProcessor;
+use crate::config::ProjectConfig;
+use crate::diagnostics::{
+    aggregate_diagnostics, ConfigurationDiagnostic, Diagnostic, DiagnosticDetails,
+    DiagnosticsOutput,
+};
+use crate::hash::fnv1a;
+
+/// One of `count` deterministic partitions of the files in a project (e.g. '3/8').
+/// `index` starts at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = CheckError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CheckError::InvalidShard(value.to_string());
+        let (index, count) = value.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(invalid());
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl Shard {
+    /// Whether the file (relative to the project root) belongs to this shard
+    pub fn contains(&self, relative_file_path: &Path) -> bool {
+        let key = relative_file_path.to_string_lossy().replace('\\', "/");
+        // A stable hash, since the assignment of files to shards must not change between builds
+        fnv1a(key.bytes()) % self.count as u64 == (self.index - 1) as u64
+    }
+}
+
+fn is_no_imports_found(diagnostic: &Diagnostic) -> bool {
+    matches!(
+        diagnostic.details(),
+        DiagnosticDetails::Configuration(ConfigurationDiagnostic::NoFirstPartyImportsFound())
+    )
+}
+
+/// Combine the diagnostics from each shard of a check into the diagnostics of a full check.
+/// Global diagnostics are reported by every shard, so they are deduplicated (keeping the largest count
+/// of occurrences), and violation budgets are applied to the combined violations.
+pub fn merge_shards(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    shards: Vec<Vec<Diagnostic>>,
+) -> Vec<Diagnostic> {
+    let shard_count = shards.len();
+    let mut shards_without_imports = 0;
+    let mut merged: Vec<Diagnostic> = Vec::new();
+    for diagnostics in shards {
+        if diagnostics.iter().any(is_no_imports_found) {
+            shards_without_imports += 1;
+        }
+        for diagnostic in diagnostics {
+            if is_no_imports_found(&diagnostic) {
+                continue;
+            }
+            if diagnostic.file_path().is_none() {
+                if let Some(existing) = merged.iter_mut().find(|existing| {
+                    existing.file_path().is_none()
+                        && existing.severity() == diagnostic.severity()
+                        && existing.details() == diagnostic.details()
+                }) {
+                    existing.add_occurrences(
+                        diagnostic
+                            .occurrences()
+                            .saturating_sub(existing.occurrences()),
+                    );
+                    continue;
+                }
+            }
+            merged.push(diagnostic);
+        }
+    }
+
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    ViolationBudgetPostProcessor::new(&valid_modules, project_config.max_violations)
+        .process_diagnostics(&mut merged);
+    if shard_count > 0 && shards_without_imports == shard_count {
+        merged.push(Diagnostic::new_global_warning(
+            DiagnosticDetails::Configuration(ConfigurationDiagnostic::NoFirstPartyImportsFound()),
+        ));
+    }
+    aggregate_diagnostics(merged)
+}
+
+/// Parse and merge the JSON output of `tach check --output json` from each shard
+pub fn merge_shard_outputs(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    outputs: &[String],
+) -> Result<Vec<Diagnostic>, CheckError> {
+    let shards = outputs
+        .iter()
+        .map(|output| {
+            serde_json::from_str::<DiagnosticsOutput>(output)
+                .map(DiagnosticsOutput::into_diagnostics)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(merge_shards(project_root, project_config, shards))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::diagnostics::CodeDiagnostic;
+
+    #[test]
+    fn test_parse_shard() {
+        assert_eq!(
+            "3/8".parse::<Shard>().unwrap(),
+            Shard { index: 3, count: 8 }
+        );
+        assert!("0/8".parse::<Shard>().is_err());
+        assert!("9/8".parse::<Shard>().is_err());
+        assert!("3".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_every_file_in_one_shard() {
+        let shards: Vec<Shard> = (1..=4).map(|index| Shard { index, count: 4 }).collect();
+        for file in ["a.py", "a/b.py", "a/b/c.py", "d/__init__.py", "e.ipynb"] {
+            let path = PathBuf::from(file);
+            assert_eq!(
+                shards.iter().filter(|shard| shard.contains(&path)).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_shards_deduplicates_global_diagnostics() {
+        let global = Diagnostic::new_global_warning(DiagnosticDetails::Configuration(
+            ConfigurationDiagnostic::ModuleNotFound {
+                file_mod_path: "missing".to_string(),
+            },
+        ));
+        let located = |file: &str| {
+            Diagnostic::new_located_error(
+                PathBuf::from(file),
+                1,
+                None,
+                DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                    dependency: "b.member".to_string(),
+                    usage_module: "a".to_string(),
+                    definition_module: "b".to_string(),
+                }),
+            )
+        };
+        let no_imports = Diagnostic::new_global_warning(DiagnosticDetails::Configuration(
+            ConfigurationDiagnostic::NoFirstPartyImportsFound(),
+        ));
+
+        let merged = merge_shards(
+            Path::new("/project"),
+            &ProjectConfig::default(),
+            vec![
+                vec![global.clone(), located("a/one.py"), no_imports.clone()],
+                vec![global.clone(), located("a/two.py")],
+            ],
+        );
+        assert_eq!(
+            merged,
+            vec![global, located("a/one.py"), located("a/two.py")]
+        );
+    }
+}
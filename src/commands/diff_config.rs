@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use pyo3::prelude::*;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::commands::check::{check_internal, CheckError};
+use crate::config::yaml::is_yaml_path;
+use crate::config::ProjectConfig;
+use crate::filesystem::read_file_content;
+use crate::history::{diff_findings, to_findings, HistoryFinding};
+use crate::parsing::config::parse_project_config_content;
+use crate::parsing::error::ParsingError;
+
+#[derive(Error, Debug)]
+pub enum DiffConfigError {
+    #[error("Failed to check the project.\n{0}")]
+    CheckError(#[from] CheckError),
+    #[error("Failed to parse project configuration.\n{0}")]
+    Parsing(#[from] ParsingError),
+    #[error("Failed to read '{path}' at revision '{revision}': {message}")]
+    Revision {
+        revision: String,
+        path: String,
+        message: String,
+    },
+}
+
+/// A dependency which was declared or removed for a module present in both configs
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct DependencyChange {
+    pub module_path: String,
+    pub dependency_path: String,
+}
+
+/// A project-wide rule whose setting differs, where 'default' means the setting is not declared
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct RuleChange {
+    pub rule: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The differences between two project configs, along with the findings which
+/// a full check would report under the new config but not the old one, and vice versa
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ConfigDiff {
+    pub modules_added: Vec<String>,
+    pub modules_removed: Vec<String>,
+    pub dependencies_added: Vec<DependencyChange>,
+    pub dependencies_removed: Vec<DependencyChange>,
+    pub rules_changed: Vec<RuleChange>,
+    pub new: Vec<HistoryFinding>,
+    pub fixed: Vec<HistoryFinding>,
+}
+
+fn module_dependencies(config: &ProjectConfig) -> BTreeMap<String, BTreeSet<String>> {
+    config
+        .all_modules()
+        .map(|module| {
+            (
+                module.path.clone(),
+                module
+                    .dependencies_iter()
+                    .map(|dependency| dependency.path.clone())
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// Dependencies of the modules declared in both configs which are only in `from`
+fn dependency_changes(
+    from: &BTreeMap<String, BTreeSet<String>>,
+    to: &BTreeMap<String, BTreeSet<String>>,
+) -> Vec<DependencyChange> {
+    from.iter()
+        .filter_map(|(module_path, dependencies)| {
+            to.get(module_path)
+                .map(|other| (module_path, dependencies.difference(other)))
+        })
+        .flat_map(|(module_path, dependencies)| {
+            dependencies.map(|dependency_path| DependencyChange {
+                module_path: module_path.clone(),
+                dependency_path: dependency_path.clone(),
+            })
+        })
+        .collect()
+}
+
+fn rule_values(config: &ProjectConfig) -> BTreeMap<String, String> {
+    // Settings which match the default are left out when serializing
+    match serde_json::to_value(&config.rules) {
+        Ok(Value::Object(rules)) => rules
+            .into_iter()
+            .map(|(rule, value)| match value {
+                Value::String(value) => (rule, value),
+                value => (rule, value.to_string()),
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+fn rule_changes(old: &ProjectConfig, new: &ProjectConfig) -> Vec<RuleChange> {
+    let old_rules = rule_values(old);
+    let new_rules = rule_values(new);
+    let default = || "default".to_string();
+    old_rules
+        .keys()
+        .chain(new_rules.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|rule| old_rules.get(*rule) != new_rules.get(*rule))
+        .map(|rule| RuleChange {
+            rule: rule.clone(),
+            old: old_rules.get(rule).cloned().unwrap_or_else(default),
+            new: new_rules.get(rule).cloned().unwrap_or_else(default),
+        })
+        .collect()
+}
+
+/// Compare the modules, dependencies and rules of two configs, without checking the project
+fn diff_declarations(old: &ProjectConfig, new: &ProjectConfig) -> ConfigDiff {
+    let old_dependencies = module_dependencies(old);
+    let new_dependencies = module_dependencies(new);
+    ConfigDiff {
+        modules_added: new_dependencies
+            .keys()
+            .filter(|path| !old_dependencies.contains_key(*path))
+            .cloned()
+            .collect(),
+        modules_removed: old_dependencies
+            .keys()
+            .filter(|path| !new_dependencies.contains_key(*path))
+            .cloned()
+            .collect(),
+        dependencies_added: dependency_changes(&new_dependencies, &old_dependencies),
+        dependencies_removed: dependency_changes(&old_dependencies, &new_dependencies),
+        rules_changed: rule_changes(old, new),
+        new: vec![],
+        fixed: vec![],
+    }
+}
+
+/// Compare two configs, and check the project under each of them to find the
+/// violations which would appear or disappear by moving from `old` to `new`
+pub fn diff_configs(
+    project_root: PathBuf,
+    old: &ProjectConfig,
+    new: &ProjectConfig,
+) -> Result<ConfigDiff, DiffConfigError> {
+    let old_findings = to_findings(&check_internal(project_root.clone(), old, true, true)?);
+    let new_findings = to_findings(&check_internal(project_root, new, true, true)?);
+    let (new_violations, fixed) = diff_findings(&old_findings, &new_findings);
+    Ok(ConfigDiff {
+        new: new_violations,
+        fixed,
+        ..diff_declarations(old, new)
+    })
+}
+
+/// Parse the config file at `path` as if it were the project config at `config_path`, so that
+/// domain configs, implicit modules and inline configs are found in the project rather than next to `path`
+pub fn parse_config_file_as(
+    path: &Path,
+    config_path: &Path,
+) -> Result<ProjectConfig, DiffConfigError> {
+    let content = read_file_content(path).map_err(ParsingError::from)?;
+    // The format still follows the file which is read
+    let location = if is_yaml_path(path) == is_yaml_path(config_path) {
+        config_path.to_path_buf()
+    } else {
+        config_path.with_extension(path.extension().unwrap_or_default())
+    };
+    let (config, _) = parse_project_config_content(&content, location)?;
+    Ok(config)
+}
+
+/// Parse the project config at `config_path` as it was at a git revision (e.g. 'main' or 'git:main').
+/// Domain configs are still read from the working tree.
+pub fn parse_config_at_revision(
+    config_path: &Path,
+    revision: &str,
+) -> Result<ProjectConfig, DiffConfigError> {
+    let revision = revision.strip_prefix("git:").unwrap_or(revision);
+    let file_name = config_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let revision_error = |message: String| DiffConfigError::Revision {
+        revision: revision.to_string(),
+        path: config_path.display().to_string(),
+        message,
+    };
+    let output = Command::new("git")
+        .args(["show", &format!("{}:./{}", revision, file_name)])
+        .current_dir(config_path.parent().unwrap_or(Path::new(".")))
+        .output()
+        .map_err(|err| revision_error(err.to_string()))?;
+    if !output.status.success() {
+        return Err(revision_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let content =
+        String::from_utf8(output.stdout).map_err(|err| revision_error(err.to_string()))?;
+    let (config, _) = parse_project_config_content(&content, config_path)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ProjectConfig {
+        parse_project_config_content(content, "/nonexistent/tach.toml")
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_diff_declarations() {
+        let old = parse(
+            r#"
+[[modules]]
+path = "a"
+depends_on = ["b"]
+
+[[modules]]
+path = "b"
+depends_on = []
+
+[[modules]]
+path = "c"
+depends_on = []
+"#,
+        );
+        let new = parse(
+            r#"
+[[modules]]
+path = "a"
+depends_on = ["d"]
+
+[[modules]]
+path = "b"
+depends_on = []
+
+[[modules]]
+path = "d"
+depends_on = ["b"]
+
+[rules]
+unused_ignore_directives = "error"
+"#,
+        );
+        let diff = diff_declarations(&old, &new);
+        assert_eq!(diff.modules_added, vec!["d".to_string()]);
+        assert_eq!(diff.modules_removed, vec!["c".to_string()]);
+        assert_eq!(
+            diff.dependencies_added,
+            vec![DependencyChange {
+                module_path: "a".to_string(),
+                dependency_path: "d".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.dependencies_removed,
+            vec![DependencyChange {
+                module_path: "a".to_string(),
+                dependency_path: "b".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.rules_changed,
+            vec![RuleChange {
+                rule: "unused_ignore_directives".to_string(),
+                old: "default".to_string(),
+                new: "error".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use petgraph::algo::kosaraju_scc;
+use petgraph::graphmap::DiGraphMap;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::commands::check::{check_internal, CheckError};
+use crate::commands::sync::{detect_project_dependencies, example_locations, ImportLocation};
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::{HealthConfig, InterfaceConfig, ProjectConfig};
+use crate::diagnostics::Diagnostic;
+use crate::exclusion::{PathExclusionError, PathExclusions};
+use crate::filesystem::{self as tach_fs, FileSystemError, WalkOptions};
+use crate::processors::ignore_directive::get_ignore_directives;
+use crate::stats::{count_source_files, StatsError};
+
+/// The version of the layout of an export bundle.
+/// Incremented whenever a field is removed or changes meaning; new fields may be added at any time.
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Failed to check the project:\n{0}")]
+    Check(#[from] CheckError),
+    #[error("Failed to write the export bundle: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize the export bundle: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Failed to count source files: {0}")]
+    Stats(#[from] StatsError),
+    #[error("Failed to read source files: {0}")]
+    Filesystem(#[from] FileSystemError),
+    #[error("Path exclusion error: {0}")]
+    PathExclusion(#[from] PathExclusionError),
+}
+
+pub type Result<T> = std::result::Result<T, ExportError>;
+
+#[derive(Debug, Serialize)]
+struct Artifact<T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    contents: T,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedDependency {
+    path: String,
+    deprecated: bool,
+    deprecated_until: Option<String>,
+    reason: Option<String>,
+    ticket: Option<String>,
+    deprecated_members: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedModule {
+    path: String,
+    display_name: Option<String>,
+    domain: Option<String>,
+    layer: Option<String>,
+    stability: Option<&'static str>,
+    utility: bool,
+    unchecked: bool,
+    visibility: Vec<String>,
+    max_violations: Option<usize>,
+    has_interface: bool,
+    interface_expose: Vec<String>,
+    depends_on: Vec<ExportedDependency>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedDomain {
+    path: String,
+    config_file: PathBuf,
+    modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedEdge {
+    source: String,
+    target: String,
+    imports: usize,
+    declared: bool,
+    deprecated: bool,
+    examples: Vec<ImportLocation>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ModuleMetrics {
+    fan_in: usize,
+    fan_out: usize,
+    errors: usize,
+    warnings: usize,
+    instability: f64,
+    in_cycle: bool,
+    interface_coverage: f64,
+    suppressions: usize,
+    health: f64,
+}
+
+impl ModuleMetrics {
+    /// A score from 0 to 100, which drops with each penalty according to its weight
+    fn health_score(&self, weights: &HealthConfig) -> f64 {
+        let penalty = weights.violations * self.errors as f64
+            + if self.in_cycle { weights.cycles } else { 0.0 }
+            + weights.instability * self.instability
+            + weights.interface_coverage * (1.0 - self.interface_coverage)
+            + weights.suppressions * self.suppressions as f64;
+        round_score((100.0 - penalty).max(0.0))
+    }
+}
+
+fn round_score(score: f64) -> f64 {
+    (score * 10.0).round() / 10.0
+}
+
+#[derive(Debug, Serialize)]
+struct Metrics {
+    files: usize,
+    modules: usize,
+    domains: usize,
+    edges: usize,
+    undeclared_edges: usize,
+    deprecated_edges: usize,
+    // The number of usages of each deprecated member, to track its migration
+    deprecated_members: BTreeMap<String, usize>,
+    imports: usize,
+    errors: usize,
+    warnings: usize,
+    // The average health score of the modules
+    health: f64,
+    per_module: BTreeMap<String, ModuleMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+struct Metadata {
+    tach_version: &'static str,
+    generated_at: u64,
+    artifacts: Vec<&'static str>,
+}
+
+/// Summary of a written export bundle
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ExportSummary {
+    pub schema_version: u32,
+    pub directory: PathBuf,
+    pub artifacts: Vec<String>,
+}
+
+const ARTIFACTS: [&str; 6] = [
+    "metadata.json",
+    "modules.json",
+    "domains.json",
+    "edges.json",
+    "diagnostics.json",
+    "metrics.json",
+];
+
+fn write_artifact<T: Serialize>(directory: &Path, name: &str, contents: T) -> Result<()> {
+    let artifact = Artifact {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        contents,
+    };
+    fs::write(
+        directory.join(name),
+        serde_json::to_string_pretty(&artifact)?,
+    )?;
+    Ok(())
+}
+
+fn interface_matches_module(interface: &InterfaceConfig, module_path: &str) -> bool {
+    interface.from_modules.iter().any(|pattern| {
+        Regex::new(&format!("^{}$", pattern)).is_ok_and(|regex| regex.is_match(module_path))
+    })
+}
+
+fn export_modules(project_config: &ProjectConfig) -> Vec<ExportedModule> {
+    let domains: HashMap<&str, &str> = project_config
+        .domains
+        .iter()
+        .flat_map(|domain| {
+            domain
+                .modules()
+                .map(|module| (module.path.as_str(), domain.location.mod_path.as_str()))
+        })
+        .collect();
+    let mut modules: Vec<ExportedModule> = project_config
+        .all_modules()
+        .filter(|module| module.path != ROOT_MODULE_SENTINEL_TAG)
+        .map(|module| {
+            let interfaces: Vec<&InterfaceConfig> = project_config
+                .all_interfaces()
+                .filter(|interface| interface_matches_module(interface, &module.path))
+                .collect();
+            ExportedModule {
+                path: module.path.clone(),
+                display_name: module.display_name.clone(),
+                domain: domains
+                    .get(module.path.as_str())
+                    .map(|path| path.to_string()),
+                layer: module.layer.clone(),
+                stability: module
+                    .stability
+                    .as_ref()
+                    .map(|stability| stability.as_str()),
+                utility: module.utility,
+                unchecked: module.unchecked,
+                visibility: module.visibility.clone(),
+                max_violations: module.max_violations,
+                has_interface: !interfaces.is_empty(),
+                interface_expose: interfaces
+                    .iter()
+                    .flat_map(|interface| interface.expose.iter().cloned())
+                    .collect(),
+                depends_on: module
+                    .depends_on
+                    .iter()
+                    .flatten()
+                    .map(|dependency| ExportedDependency {
+                        path: dependency.path.clone(),
+                        deprecated: dependency.is_deprecated(),
+                        deprecated_until: dependency.deprecated_until.clone(),
+                        reason: dependency.reason.clone(),
+                        ticket: dependency.ticket.clone(),
+                        deprecated_members: dependency
+                            .deprecated_members
+                            .clone()
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    modules.sort_by(|left, right| left.path.cmp(&right.path));
+    modules
+}
+
+fn export_domains(project_root: &Path, project_config: &ProjectConfig) -> Vec<ExportedDomain> {
+    let mut domains: Vec<ExportedDomain> = project_config
+        .domains
+        .iter()
+        .map(|domain| ExportedDomain {
+            path: domain.location.mod_path.clone(),
+            config_file: tach_fs::relative_to(&domain.location.path, project_root)
+                .unwrap_or_else(|_| domain.location.path.clone()),
+            modules: domain.modules().map(|module| module.path.clone()).collect(),
+        })
+        .collect();
+    domains.sort_by(|left, right| left.path.cmp(&right.path));
+    domains
+}
+
+fn export_edges(
+    project_config: &ProjectConfig,
+    dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>>,
+) -> Vec<ExportedEdge> {
+    let mut edges: Vec<ExportedEdge> = dependencies
+        .into_iter()
+        .flat_map(|(source, targets)| {
+            let declared = project_config.dependencies_for_module(&source);
+            targets
+                .into_iter()
+                .filter(|(target, _)| *target != source)
+                .map(|(target, usages)| {
+                    let dependency = declared
+                        .and_then(|declared| declared.iter().find(|dep| dep.path == target));
+                    ExportedEdge {
+                        source: source.clone(),
+                        target,
+                        imports: usages.len(),
+                        declared: dependency.is_some(),
+                        deprecated: dependency.is_some_and(|dep| dep.is_deprecated()),
+                        examples: usages,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for edge in &mut edges {
+        edge.examples = example_locations(&edge.examples);
+    }
+    edges.sort_by(|left, right| (&left.source, &left.target).cmp(&(&right.source, &right.target)));
+    edges
+}
+
+/// The module which contains `module_path`, if any
+fn containing_module<'a>(modules: &'a [ExportedModule], module_path: &str) -> Option<&'a str> {
+    modules
+        .iter()
+        .map(|module| module.path.as_str())
+        .filter(|path| {
+            module_path == *path
+                || module_path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|path| path.len())
+}
+
+/// The number of ignore directives in the files of each module
+fn count_suppressions(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    modules: &[ExportedModule],
+) -> Result<HashMap<String, usize>> {
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let source_roots = project_config.prepend_roots(project_root);
+    let mut suppressions: HashMap<String, usize> = HashMap::new();
+    for source_root in &source_roots {
+        for file_path in tach_fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            // Notebooks are only supported by 'tach check'
+            WalkOptions {
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        ) {
+            let file_path = source_root.join(file_path);
+            let module_path = tach_fs::normalized_file_to_module_path(
+                &source_roots,
+                &file_path,
+                &project_config.module_path_normalizers,
+            )?;
+            let Some(module) = containing_module(modules, &module_path) else {
+                continue;
+            };
+            let directives = get_ignore_directives(&tach_fs::read_file_content(&file_path)?);
+            if !directives.is_empty() {
+                *suppressions.entry(module.to_string()).or_default() += directives.len();
+            }
+        }
+    }
+    Ok(suppressions)
+}
+
+/// The modules which take part in a cycle of imports
+fn modules_in_cycles(edges: &[ExportedEdge]) -> Vec<&str> {
+    let mut graph = DiGraphMap::new();
+    for edge in edges {
+        graph.add_edge(edge.source.as_str(), edge.target.as_str(), ());
+    }
+    kosaraju_scc(&graph)
+        .into_iter()
+        .filter(|scc| scc.len() > 1)
+        .flatten()
+        .collect()
+}
+
+fn export_metrics(
+    files: usize,
+    modules: &[ExportedModule],
+    domains: &[ExportedDomain],
+    edges: &[ExportedEdge],
+    diagnostics: &[Diagnostic],
+    suppressions: &HashMap<String, usize>,
+    weights: &HealthConfig,
+) -> Metrics {
+    let mut per_module: BTreeMap<String, ModuleMetrics> = modules
+        .iter()
+        .map(|module| (module.path.clone(), ModuleMetrics::default()))
+        .collect();
+    // Imports from other modules into each module, and how many of them bypass its interface
+    let mut incoming_imports: HashMap<&str, usize> = HashMap::new();
+    let mut interface_errors: HashMap<&str, usize> = HashMap::new();
+    for edge in edges {
+        if let Some(metrics) = per_module.get_mut(&edge.source) {
+            metrics.fan_out += 1;
+        }
+        if let Some(metrics) = per_module.get_mut(&edge.target) {
+            metrics.fan_in += 1;
+        }
+        *incoming_imports.entry(edge.target.as_str()).or_default() += edge.imports;
+    }
+    for module in modules_in_cycles(edges) {
+        if let Some(metrics) = per_module.get_mut(module) {
+            metrics.in_cycle = true;
+        }
+    }
+    for diagnostic in diagnostics {
+        let Some(metrics) = diagnostic
+            .usage_module()
+            .and_then(|module| per_module.get_mut(module))
+        else {
+            continue;
+        };
+        if diagnostic.is_error() {
+            metrics.errors += 1;
+        } else if diagnostic.is_warning() {
+            metrics.warnings += 1;
+        }
+    }
+    for diagnostic in diagnostics {
+        if let Some(module) = diagnostic
+            .definition_module()
+            .filter(|_| diagnostic.is_interface_error())
+        {
+            *interface_errors.entry(module).or_default() += 1;
+        }
+    }
+    for module in modules {
+        let Some(metrics) = per_module.get_mut(&module.path) else {
+            continue;
+        };
+        if metrics.fan_in + metrics.fan_out > 0 {
+            metrics.instability =
+                metrics.fan_out as f64 / (metrics.fan_in + metrics.fan_out) as f64;
+        }
+        let imports = incoming_imports
+            .get(module.path.as_str())
+            .copied()
+            .unwrap_or_default();
+        metrics.interface_coverage = if imports == 0 {
+            1.0
+        } else if module.has_interface {
+            let errors = interface_errors
+                .get(module.path.as_str())
+                .copied()
+                .unwrap_or_default();
+            1.0 - errors.min(imports) as f64 / imports as f64
+        } else {
+            0.0
+        };
+        metrics.suppressions = suppressions.get(&module.path).copied().unwrap_or_default();
+        metrics.health = metrics.health_score(weights);
+    }
+    let health = if per_module.is_empty() {
+        100.0
+    } else {
+        round_score(
+            per_module
+                .values()
+                .map(|metrics| metrics.health)
+                .sum::<f64>()
+                / per_module.len() as f64,
+        )
+    };
+    let mut deprecated_members: BTreeMap<String, usize> = BTreeMap::new();
+    for member in diagnostics.iter().filter_map(Diagnostic::deprecated_member) {
+        *deprecated_members.entry(member).or_default() += 1;
+    }
+    Metrics {
+        files,
+        modules: modules.len(),
+        domains: domains.len(),
+        edges: edges.len(),
+        undeclared_edges: edges.iter().filter(|edge| !edge.declared).count(),
+        deprecated_edges: edges.iter().filter(|edge| edge.deprecated).count(),
+        deprecated_members,
+        imports: edges.iter().map(|edge| edge.imports).sum(),
+        errors: diagnostics.iter().filter(|d| d.is_error()).count(),
+        warnings: diagnostics.iter().filter(|d| d.is_warning()).count(),
+        health,
+        per_module,
+    }
+}
+
+/// Write a bundle of JSON artifacts describing the project's modules, domains, import edges,
+/// diagnostics and metrics into `directory`, for consumption by dashboards.
+pub fn export_bundle(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    directory: &Path,
+) -> Result<ExportSummary> {
+    let diagnostics = check_internal(project_root.to_path_buf(), project_config, true, true)?;
+    let dependencies = detect_project_dependencies(project_root.to_path_buf(), project_config)?;
+    let files = count_source_files(project_root, project_config)?;
+
+    let modules = export_modules(project_config);
+    let domains = export_domains(project_root, project_config);
+    let edges = export_edges(project_config, dependencies);
+    let suppressions = count_suppressions(project_root, project_config, &modules)?;
+    let metrics = export_metrics(
+        files,
+        &modules,
+        &domains,
+        &edges,
+        &diagnostics,
+        &suppressions,
+        &project_config.health,
+    );
+
+    fs::create_dir_all(directory)?;
+    write_artifact(
+        directory,
+        "metadata.json",
+        Metadata {
+            tach_version: env!("CARGO_PKG_VERSION"),
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            artifacts: ARTIFACTS.to_vec(),
+        },
+    )?;
+    write_artifact(
+        directory,
+        "modules.json",
+        BTreeMap::from([("modules", modules)]),
+    )?;
+    write_artifact(
+        directory,
+        "domains.json",
+        BTreeMap::from([("domains", domains)]),
+    )?;
+    write_artifact(directory, "edges.json", BTreeMap::from([("edges", edges)]))?;
+    write_artifact(
+        directory,
+        "diagnostics.json",
+        BTreeMap::from([("diagnostics", diagnostics)]),
+    )?;
+    write_artifact(directory, "metrics.json", metrics)?;
+
+    Ok(ExportSummary {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        directory: directory.to_path_buf(),
+        artifacts: ARTIFACTS.iter().map(|name| name.to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DependencyConfig, ModuleConfig};
+
+    fn location(file: &str, line: usize) -> ImportLocation {
+        ImportLocation {
+            file: file.to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_export_edges_and_metrics() {
+        let project_config = ProjectConfig {
+            modules: vec![
+                ModuleConfig {
+                    depends_on: Some(vec![DependencyConfig::from_deprecated_path("core")]),
+                    ..ModuleConfig::new("api", false)
+                },
+                ModuleConfig::new("core", false),
+            ],
+            ..Default::default()
+        };
+        let dependencies = HashMap::from([
+            (
+                "api".to_string(),
+                HashMap::from([
+                    (
+                        "core".to_string(),
+                        vec![location("api/b.py", 1), location("api/a.py", 3)],
+                    ),
+                    ("api".to_string(), vec![location("api/a.py", 1)]),
+                ]),
+            ),
+            (
+                "core".to_string(),
+                HashMap::from([("api".to_string(), vec![location("core/a.py", 2)])]),
+            ),
+        ]);
+
+        let edges = export_edges(&project_config, dependencies);
+        assert_eq!(
+            edges
+                .iter()
+                .map(|edge| (
+                    edge.source.as_str(),
+                    edge.target.as_str(),
+                    edge.imports,
+                    edge.declared,
+                    edge.deprecated
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("api", "core", 2, true, true),
+                ("core", "api", 1, false, false)
+            ]
+        );
+        assert_eq!(edges[0].examples[0], location("api/a.py", 3));
+
+        let modules = export_modules(&project_config);
+        let suppressions = HashMap::from([("api".to_string(), 2)]);
+        let metrics = export_metrics(
+            4,
+            &modules,
+            &[],
+            &edges,
+            &[],
+            &suppressions,
+            &HealthConfig::default(),
+        );
+        assert_eq!(metrics.edges, 2);
+        assert_eq!(metrics.undeclared_edges, 1);
+        assert_eq!(metrics.deprecated_edges, 1);
+        assert_eq!(metrics.imports, 3);
+        assert_eq!(metrics.per_module["api"].fan_out, 1);
+        assert_eq!(metrics.per_module["core"].fan_in, 1);
+
+        // Both modules import each other without an interface: 100 - 15 (cycle) - 5 (instability) - 15 (coverage)
+        let core = &metrics.per_module["core"];
+        assert!(core.in_cycle);
+        assert_eq!(core.instability, 0.5);
+        assert_eq!(core.interface_coverage, 0.0);
+        assert_eq!(core.health, 65.0);
+        // Less 2 points for each ignore directive
+        assert_eq!(metrics.per_module["api"].health, 61.0);
+        assert_eq!(metrics.health, 63.0);
+    }
+
+    #[test]
+    fn test_health_score_weights() {
+        let metrics = ModuleMetrics {
+            errors: 30,
+            interface_coverage: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(metrics.health_score(&HealthConfig::default()), 0.0);
+        let weights = HealthConfig {
+            violations: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(metrics.health_score(&weights), 70.0);
+    }
+}
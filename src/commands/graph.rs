@@ -0,0 +1,360 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::{ConfigError, ProjectConfig};
+
+#[derive(Error, Debug)]
+pub enum GraphError {
+    #[error("Module '{0}' is not in the module graph.")]
+    ModuleNotFound(String),
+    #[error("Failed to build the module graph.\n{0}")]
+    Config(#[from] ConfigError),
+}
+
+pub type Result<T> = std::result::Result<T, GraphError>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    // Undeclared edges only come from violations
+    pub declared: bool,
+    // Only set when every declared dependency merged into this edge is deprecated
+    pub deprecated: bool,
+    pub violations: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ModuleGraph {
+    pub nodes: Vec<String>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Options to reduce the module graph to a digestible subgraph
+#[derive(Debug, Default)]
+pub struct GraphOptions {
+    /// Only keep modules within `focus_depth` dependencies (in either direction) of this module
+    pub focus: Option<String>,
+    pub focus_depth: usize,
+    /// Collapse modules into their ancestor with at most this many segments
+    pub depth: Option<usize>,
+    /// Only keep dependencies with violations
+    pub only_violations: bool,
+    pub exclude_utilities: bool,
+}
+
+#[derive(Debug, Clone)]
+struct EdgeWeight {
+    declared: bool,
+    deprecated: bool,
+    violations: usize,
+}
+
+impl EdgeWeight {
+    fn merge(&mut self, other: &EdgeWeight) {
+        self.deprecated = match (self.declared, other.declared) {
+            (true, true) => self.deprecated && other.deprecated,
+            (true, false) => self.deprecated,
+            (false, _) => other.deprecated,
+        };
+        self.declared |= other.declared;
+        self.violations += other.violations;
+    }
+}
+
+/// Collapse a module path to at most `depth` segments (e.g. 'a.b.c' -> 'a.b' at depth 2)
+pub fn collapse_module_path(path: &str, depth: Option<usize>) -> String {
+    match depth {
+        Some(depth) if path != ROOT_MODULE_SENTINEL_TAG => {
+            path.split('.').take(depth).collect::<Vec<_>>().join(".")
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[derive(Debug, Default)]
+struct GraphBuilder {
+    graph: StableDiGraph<String, EdgeWeight>,
+    indices: HashMap<String, NodeIndex>,
+}
+
+impl GraphBuilder {
+    fn node(&mut self, path: &str) -> NodeIndex {
+        if let Some(index) = self.indices.get(path) {
+            return *index;
+        }
+        let index = self.graph.add_node(path.to_string());
+        self.indices.insert(path.to_string(), index);
+        index
+    }
+
+    /// Add an edge, merging it into any existing edge between the same modules
+    fn add_edge(&mut self, source: &str, target: &str, weight: EdgeWeight) {
+        if source == target {
+            return;
+        }
+        let source = self.node(source);
+        let target = self.node(target);
+        match self.graph.find_edge(source, target) {
+            Some(edge) => self.graph[edge].merge(&weight),
+            None => {
+                self.graph.add_edge(source, target, weight);
+            }
+        }
+    }
+
+    fn retain_nodes(&mut self, keep: impl Fn(&str) -> bool) {
+        self.graph.retain_nodes(|graph, node| keep(&graph[node]));
+        let graph = &self.graph;
+        self.indices.retain(|_, index| graph.contains_node(*index));
+    }
+
+    fn retain_violations(&mut self) {
+        self.graph
+            .retain_edges(|graph, edge| graph[edge].violations > 0);
+        self.graph
+            .retain_nodes(|graph, node| graph.neighbors_undirected(node).next().is_some());
+        let graph = &self.graph;
+        self.indices.retain(|_, index| graph.contains_node(*index));
+    }
+
+    fn collapse(self, depth: usize) -> Self {
+        let mut collapsed = Self::default();
+        for node in self.graph.node_indices() {
+            collapsed.node(&collapse_module_path(&self.graph[node], Some(depth)));
+        }
+        for edge in self.graph.edge_indices() {
+            let (source, target) = self.graph.edge_endpoints(edge).unwrap();
+            collapsed.add_edge(
+                &collapse_module_path(&self.graph[source], Some(depth)),
+                &collapse_module_path(&self.graph[target], Some(depth)),
+                self.graph[edge].clone(),
+            );
+        }
+        collapsed
+    }
+
+    fn focus(&mut self, module_path: &str, depth: usize) -> Result<()> {
+        let start = *self
+            .indices
+            .get(module_path)
+            .ok_or_else(|| GraphError::ModuleNotFound(module_path.to_string()))?;
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node];
+            if distance == depth {
+                continue;
+            }
+            for neighbor in self.graph.neighbors_undirected(node) {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        self.graph
+            .retain_nodes(|_, node| distances.contains_key(&node));
+        let graph = &self.graph;
+        self.indices.retain(|_, index| graph.contains_node(*index));
+        Ok(())
+    }
+
+    fn into_module_graph(self) -> ModuleGraph {
+        let graph = self.graph;
+        ModuleGraph {
+            nodes: graph
+                .node_indices()
+                .map(|node| graph[node].clone())
+                .collect(),
+            edges: graph
+                .edge_indices()
+                .map(|edge| {
+                    let (source, target) = graph.edge_endpoints(edge).unwrap();
+                    let weight = &graph[edge];
+                    GraphEdge {
+                        source: graph[source].clone(),
+                        target: graph[target].clone(),
+                        declared: weight.declared,
+                        deprecated: weight.deprecated,
+                        violations: weight.violations,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Build the graph of declared dependencies between modules, along with any `violations`
+/// (pairs of usage and definition modules), reduced according to `options`.
+pub fn build_module_graph(
+    project_config: &ProjectConfig,
+    included_paths: Vec<PathBuf>,
+    violations: &[(String, String)],
+    options: &GraphOptions,
+) -> Result<ModuleGraph> {
+    let modules = project_config.filtered_modules(included_paths)?;
+    let mut builder = GraphBuilder::default();
+    for module in &modules {
+        builder.node(&module.path);
+        for dependency in module.dependencies_iter() {
+            builder.add_edge(
+                &module.path,
+                &dependency.path,
+                EdgeWeight {
+                    declared: true,
+                    deprecated: dependency.is_deprecated(),
+                    violations: 0,
+                },
+            );
+        }
+    }
+    // Violations are only drawn between modules which are already part of the graph
+    for (usage_module, definition_module) in violations {
+        if builder.indices.contains_key(usage_module)
+            && builder.indices.contains_key(definition_module)
+        {
+            builder.add_edge(
+                usage_module,
+                definition_module,
+                EdgeWeight {
+                    declared: false,
+                    deprecated: false,
+                    violations: 1,
+                },
+            );
+        }
+    }
+
+    if options.exclude_utilities {
+        let utilities: HashSet<&str> = project_config
+            .all_modules()
+            .filter(|module| module.utility)
+            .map(|module| module.path.as_str())
+            .collect();
+        builder.retain_nodes(|path| !utilities.contains(path));
+    }
+    if options.only_violations {
+        builder.retain_violations();
+    }
+    if let Some(depth) = options.depth {
+        builder = builder.collapse(depth);
+    }
+    // The focus may be given as a full module path, even when modules are collapsed
+    if let Some(focus) = &options.focus {
+        builder.focus(
+            &collapse_module_path(focus, options.depth),
+            options.focus_depth,
+        )?;
+    }
+    Ok(builder.into_module_graph())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DependencyConfig, ModuleConfig};
+
+    fn module(path: &str, depends_on: &[&str]) -> ModuleConfig {
+        ModuleConfig {
+            path: path.to_string(),
+            depends_on: Some(
+                depends_on
+                    .iter()
+                    .map(|dependency| DependencyConfig::from_path(*dependency))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn project_config() -> ProjectConfig {
+        let mut project_config = ProjectConfig {
+            modules: vec![
+                module("app.api.views", &["app.api.utils", "app.core.models"]),
+                module("app.api.utils", &["shared"]),
+                module("app.core.models", &["shared"]),
+                module("app.core.jobs", &["app.core.models"]),
+                ModuleConfig {
+                    utility: true,
+                    ..module("shared", &[])
+                },
+            ],
+            ..Default::default()
+        };
+        project_config.set_location(PathBuf::from("/project/tach.toml"));
+        project_config
+    }
+
+    fn edges(graph: &ModuleGraph) -> Vec<(&str, &str)> {
+        graph
+            .edges
+            .iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_focus() {
+        let options = GraphOptions {
+            focus: Some("app.api.views".to_string()),
+            focus_depth: 1,
+            exclude_utilities: true,
+            ..Default::default()
+        };
+        let graph = build_module_graph(&project_config(), vec![], &[], &options).unwrap();
+        assert_eq!(
+            graph.nodes,
+            vec!["app.api.views", "app.api.utils", "app.core.models"]
+        );
+        assert_eq!(
+            edges(&graph),
+            vec![
+                ("app.api.views", "app.api.utils"),
+                ("app.api.views", "app.core.models")
+            ]
+        );
+
+        let options = GraphOptions {
+            focus: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_module_graph(&project_config(), vec![], &[], &options),
+            Err(GraphError::ModuleNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_collapse_and_violations() {
+        let violations = vec![
+            ("app.core.jobs".to_string(), "app.api.utils".to_string()),
+            ("app.core.jobs".to_string(), "app.api.views".to_string()),
+        ];
+        let options = GraphOptions {
+            depth: Some(2),
+            only_violations: true,
+            ..Default::default()
+        };
+        let graph = build_module_graph(&project_config(), vec![], &violations, &options).unwrap();
+        assert_eq!(graph.nodes, vec!["app.api", "app.core"]);
+        assert_eq!(
+            graph.edges,
+            vec![GraphEdge {
+                source: "app.core".to_string(),
+                target: "app.api".to_string(),
+                declared: false,
+                deprecated: false,
+                violations: 2,
+            }]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use ruff_linter::Locator;
 use crate::config::ProjectConfig;
 use crate::dependencies::import::LocatedImport;
 use crate::exclusion::PathExclusions;
-use crate::filesystem;
+use crate::filesystem::{self, FileOverlay};
 use crate::processors::ignore_directive::get_ignore_directives;
 use crate::processors::import::{get_normalized_imports, Result};
 
@@ -32,7 +32,24 @@ pub fn get_located_project_imports<P: AsRef<Path>>(
     file_path: P,
     project_config: &ProjectConfig,
 ) -> Result<Vec<LocatedImport>> {
-    let file_contents = filesystem::read_file_content(file_path.as_ref())?;
+    get_located_project_imports_with_overlay(
+        project_root,
+        source_roots,
+        file_path,
+        project_config,
+        &FileOverlay::new(),
+    )
+}
+
+/// Same as `get_located_project_imports`, but reads the file from `overlay` when it has unsaved contents
+pub fn get_located_project_imports_with_overlay<P: AsRef<Path>>(
+    project_root: &PathBuf,
+    source_roots: &[PathBuf],
+    file_path: P,
+    project_config: &ProjectConfig,
+    overlay: &FileOverlay,
+) -> Result<Vec<LocatedImport>> {
+    let file_contents = filesystem::read_file_content_with_overlay(file_path.as_ref(), overlay)?;
     let line_index = Locator::new(&file_contents).to_index().clone();
     let normalized_imports = get_normalized_imports(
         source_roots,
@@ -40,13 +57,15 @@ pub fn get_located_project_imports<P: AsRef<Path>>(
         &file_contents,
         project_config.ignore_type_checking_imports,
         project_config.include_string_imports,
-    )?;
+        project_config.analyze_attribute_access,
+    )?
+    .imports;
     let ignore_directives = get_ignore_directives(&file_contents);
 
     let exclusions = PathExclusions::new(
         project_root,
         &project_config.exclude,
-        project_config.use_regex_matching,
+        &project_config.ignore,
     )?;
 
     Ok(normalized_imports
@@ -60,7 +79,8 @@ pub fn get_located_project_imports<P: AsRef<Path>>(
         })
         .filter(|import| {
             !ignore_directives.is_ignored(import)
-                && filesystem::is_project_import(source_roots, import.module_path(), &exclusions)
+                && (filesystem::is_project_import(source_roots, import.module_path(), &exclusions)
+                    || project_config.is_mapped_module(import.module_path()))
         })
         .collect())
 }
@@ -71,7 +91,24 @@ pub fn get_located_external_imports<P: AsRef<Path>>(
     file_path: P,
     project_config: &ProjectConfig,
 ) -> Result<Vec<LocatedImport>> {
-    let file_contents = filesystem::read_file_content(file_path.as_ref())?;
+    get_located_external_imports_with_overlay(
+        project_root,
+        source_roots,
+        file_path,
+        project_config,
+        &FileOverlay::new(),
+    )
+}
+
+/// Same as `get_located_external_imports`, but reads the file from `overlay` when it has unsaved contents
+pub fn get_located_external_imports_with_overlay<P: AsRef<Path>>(
+    project_root: &PathBuf,
+    source_roots: &[PathBuf],
+    file_path: P,
+    project_config: &ProjectConfig,
+    overlay: &FileOverlay,
+) -> Result<Vec<LocatedImport>> {
+    let file_contents = filesystem::read_file_content_with_overlay(file_path.as_ref(), overlay)?;
     let line_index = Locator::new(&file_contents).to_index().clone();
     let normalized_imports = get_normalized_imports(
         source_roots,
@@ -79,12 +116,14 @@ pub fn get_located_external_imports<P: AsRef<Path>>(
         &file_contents,
         project_config.ignore_type_checking_imports,
         false,
-    )?;
+        false,
+    )?
+    .imports;
     let ignore_directives = get_ignore_directives(&file_contents);
     let exclusions = PathExclusions::new(
         project_root,
         &project_config.exclude,
-        project_config.use_regex_matching,
+        &project_config.ignore,
     )?;
     Ok(normalized_imports
         .into_iter()
@@ -98,6 +137,7 @@ pub fn get_located_external_imports<P: AsRef<Path>>(
         .filter(|import| {
             !ignore_directives.is_ignored(import)
                 && !filesystem::is_project_import(source_roots, import.module_path(), &exclusions)
+                && !project_config.is_mapped_module(import.module_path())
         })
         .collect())
 }
@@ -1,3 +1,6 @@
 pub mod import;
 
-pub use import::{get_located_external_imports, get_located_project_imports};
+pub use import::{
+    get_located_external_imports, get_located_external_imports_with_overlay,
+    get_located_project_imports, get_located_project_imports_with_overlay,
+};
@@ -0,0 +1,217 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::commands::sync::{sync_project, SyncError};
+use crate::config::write::write_atomic;
+use crate::config::{ModuleConfig, ProjectConfig};
+use crate::exclusion::{PathExclusionError, PathExclusions};
+use crate::filesystem::{top_level_packages, walk_domain_config_files, PROJECT_CONFIG_FILE_NAMES};
+use crate::parsing::config::{dump_project_config_to_toml, parse_project_config};
+use crate::parsing::error::ParsingError;
+
+#[derive(Error, Debug)]
+pub enum InitError {
+    #[error("Project already initialized: '{}' exists. Use 'tach init --force' to reinitialize.", .0.display())]
+    AlreadyInitialized(PathBuf),
+    #[error("No Python packages found in the project.")]
+    NoPackagesFound,
+    #[error("Failed to write project configuration to file.\n{0}")]
+    FileWrite(#[from] io::Error),
+    #[error("Failed to serialize project configuration to TOML.\n{0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+    #[error("Path exclusion error: {0}")]
+    PathExclusion(#[from] PathExclusionError),
+    #[error("{0}")]
+    Parsing(#[from] ParsingError),
+    #[error("{0}")]
+    Sync(#[from] SyncError),
+}
+
+pub type Result<T> = std::result::Result<T, InitError>;
+
+/// Summary of a newly written project configuration
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct InitSummary {
+    pub config_path: PathBuf,
+    pub source_roots: Vec<PathBuf>,
+    pub modules: Vec<String>,
+    // The number of dependencies added by the initial sync
+    pub dependencies: usize,
+}
+
+/// Source roots which are commonly used instead of the project root
+const SOURCE_ROOT_CANDIDATES: [&str; 2] = ["src", "lib"];
+
+/// The top-level packages of `source_root`, preferring regular packages.
+/// Namespace packages are only used when there are no regular packages,
+/// since directories such as 'scripts' would otherwise become modules.
+fn find_packages(source_root: &Path, exclusions: &PathExclusions) -> Vec<String> {
+    let packages = top_level_packages(source_root, exclusions, false);
+    if packages.is_empty() {
+        top_level_packages(source_root, exclusions, true)
+    } else {
+        packages
+    }
+}
+
+/// Detect the source roots of the project, relative to `project_root`,
+/// along with the module paths of the packages in each of them.
+pub fn detect_source_roots(
+    project_root: &Path,
+    exclusions: &PathExclusions,
+) -> Vec<(PathBuf, Vec<String>)> {
+    let mut source_roots: Vec<(PathBuf, Vec<String>)> = SOURCE_ROOT_CANDIDATES
+        .iter()
+        .map(PathBuf::from)
+        .filter(|source_root| project_root.join(source_root).is_dir())
+        .map(|source_root| {
+            let packages = find_packages(&project_root.join(&source_root), exclusions);
+            (source_root, packages)
+        })
+        .filter(|(_, packages)| !packages.is_empty())
+        .collect();
+    // Packages directly in the project root are only used alongside other source roots
+    // if they are regular packages, since the other source roots would look like namespace packages
+    let root_packages: Vec<String> = if source_roots.is_empty() {
+        find_packages(project_root, exclusions)
+    } else {
+        top_level_packages(project_root, exclusions, false)
+    };
+    if !root_packages.is_empty() {
+        source_roots.insert(0, (PathBuf::from("."), root_packages));
+    }
+    source_roots
+}
+
+fn existing_config_files(project_root: &Path) -> Vec<PathBuf> {
+    PROJECT_CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| project_root.join(name))
+        .filter(|path| path.is_file())
+        .chain(walk_domain_config_files(
+            &project_root.display().to_string(),
+        ))
+        .collect()
+}
+
+/// Write a new project configuration to 'tach.toml' in `project_root`,
+/// with a module for each top-level package in the detected source roots.
+/// When `sync` is set, the dependencies of each module are then filled in from its imports.
+pub fn init_project(project_root: &Path, force: bool, sync: bool) -> Result<InitSummary> {
+    let existing_files = existing_config_files(project_root);
+    if let Some(existing_file) = existing_files.first() {
+        if !force {
+            return Err(InitError::AlreadyInitialized(existing_file.clone()));
+        }
+    }
+
+    let mut project_config = ProjectConfig::default();
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let source_roots = detect_source_roots(project_root, &exclusions);
+    if source_roots.is_empty() {
+        return Err(InitError::NoPackagesFound);
+    }
+    project_config.source_roots = source_roots.iter().map(|(root, _)| root.clone()).collect();
+    project_config.modules = source_roots
+        .iter()
+        .flat_map(|(_, packages)| packages.iter())
+        .map(|package| ModuleConfig::new(package, false))
+        .collect();
+    let modules: Vec<String> = project_config
+        .modules
+        .iter()
+        .map(|module| module.path.clone())
+        .collect();
+
+    for existing_file in &existing_files {
+        fs::remove_file(existing_file)?;
+    }
+    let config_path = project_root.join(PROJECT_CONFIG_FILE_NAMES[0]);
+    write_atomic(
+        &config_path,
+        &dump_project_config_to_toml(&mut project_config)?,
+    )?;
+
+    let dependencies = if sync {
+        let (project_config, _) = parse_project_config(&config_path)?;
+        sync_project(project_root.to_path_buf(), project_config, true, false)?
+            .changes
+            .len()
+    } else {
+        0
+    };
+
+    Ok(InitSummary {
+        config_path,
+        source_roots: source_roots.into_iter().map(|(root, _)| root).collect(),
+        modules,
+        dependencies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_package(root: &Path, path: &str) {
+        fs::create_dir_all(root.join(path)).unwrap();
+        fs::write(root.join(path).join("__init__.py"), "").unwrap();
+    }
+
+    #[test]
+    fn test_detect_source_roots() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_package(root, "src/api");
+        write_package(root, "src/core");
+        write_package(root, "tools");
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("scripts/release.py"), "").unwrap();
+        write_package(root, "tests");
+        let project_config = ProjectConfig::default();
+        let exclusions =
+            PathExclusions::new(root, &project_config.exclude, &project_config.ignore).unwrap();
+
+        let mut source_roots = detect_source_roots(root, &exclusions);
+        for (_, packages) in &mut source_roots {
+            packages.sort();
+        }
+        assert_eq!(
+            source_roots,
+            vec![
+                (PathBuf::from("."), vec!["tools".to_string()]),
+                (
+                    PathBuf::from("src"),
+                    vec!["api".to_string(), "core".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_init_project_refuses_existing_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write_package(root, "api");
+        fs::write(root.join("tach.toml"), "").unwrap();
+
+        assert!(matches!(
+            init_project(root, false, false),
+            Err(InitError::AlreadyInitialized(_))
+        ));
+        let summary = init_project(root, true, false).unwrap();
+        assert_eq!(summary.source_roots, vec![PathBuf::from(".")]);
+        assert_eq!(summary.modules, vec!["api".to_string()]);
+        let (project_config, _) = parse_project_config(&summary.config_path).unwrap();
+        assert_eq!(project_config.module_paths(), vec!["api".to_string()]);
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use ruff_python_ast::{Expr, Mod, Stmt};
+
+use crate::config::root_module::{RootModuleTreatment, ROOT_MODULE_SENTINEL_TAG};
+use crate::config::ProjectConfig;
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{self as fs, WalkOptions};
+use crate::interfaces::compiled::{CompiledInterface, CompiledInterfaces};
+use crate::interrupt::check_interrupt;
+use crate::modules::{build_module_tree, error::ModuleTreeError, ModuleTree};
+use crate::python::parsing::{parse_interface_members, parse_python_source};
+
+use super::helpers::import::get_located_project_imports;
+use super::report::{is_module_prefix, ReportCreationError, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct InterfaceMemberUsage {
+    pub member: String,
+    // Number of imports of this member from outside the module
+    pub usages: usize,
+    pub usage_modules: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct InterfaceUsageReport {
+    pub module_path: String,
+    // Sorted by usages (most used first), so members which are never used come last
+    pub members: Vec<InterfaceMemberUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct UnusedInterfaceMembers {
+    pub module_path: String,
+    // Exposed members which no other module imports, sorted by name
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct BlockingImport {
+    // Relative to the project root
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    pub member: String,
+    pub usage_module: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct StrictifyReport {
+    pub module_path: String,
+    pub has_interface: bool,
+    // Imports from other modules of members which are in '__all__' or already exposed
+    pub covered_usages: usize,
+    // Imports which strict mode would reject, ordered by file and line
+    pub blocking_imports: Vec<BlockingImport>,
+    // The members which a new interface must expose for every import to be allowed, sorted by name
+    pub expose: Vec<String>,
+}
+
+fn top_level_names(body: &[Stmt]) -> Vec<String> {
+    let mut names = Vec::new();
+    for stmt in body {
+        match stmt {
+            Stmt::FunctionDef(node) => names.push(node.name.to_string()),
+            Stmt::ClassDef(node) => names.push(node.name.to_string()),
+            Stmt::Assign(node) => {
+                names.extend(node.targets.iter().filter_map(|target| match target {
+                    Expr::Name(name) => Some(name.id.to_string()),
+                    _ => None,
+                }))
+            }
+            Stmt::AnnAssign(node) => {
+                if let Expr::Name(name) = node.target.as_ref() {
+                    names.push(name.id.to_string());
+                }
+            }
+            Stmt::Import(node) => names.extend(node.names.iter().map(|alias| {
+                alias
+                    .asname
+                    .as_ref()
+                    .unwrap_or(&alias.name)
+                    .as_str()
+                    .split('.')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })),
+            Stmt::ImportFrom(node) => names.extend(
+                node.names
+                    .iter()
+                    .filter(|alias| alias.name.as_str() != "*")
+                    .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name).to_string()),
+            ),
+            _ => (),
+        }
+    }
+    names
+}
+
+/// Names listed in a literal `__all__` at the top level of the module, if it has one
+fn dunder_all_names(body: &[Stmt]) -> Vec<String> {
+    let mut names = Vec::new();
+    for stmt in body {
+        let (targets, value): (Vec<&Expr>, Option<&Expr>) = match stmt {
+            Stmt::Assign(node) => (node.targets.iter().collect(), Some(node.value.as_ref())),
+            Stmt::AnnAssign(node) => (vec![node.target.as_ref()], node.value.as_deref()),
+            Stmt::AugAssign(node) => (vec![node.target.as_ref()], Some(node.value.as_ref())),
+            _ => continue,
+        };
+        let is_dunder_all = targets
+            .iter()
+            .any(|target| matches!(target, Expr::Name(name) if name.id.as_str() == "__all__"));
+        if !is_dunder_all {
+            continue;
+        }
+        let elements = match value {
+            Some(Expr::List(list)) => &list.elts,
+            Some(Expr::Tuple(tuple)) => &tuple.elts,
+            _ => continue,
+        };
+        names.extend(elements.iter().filter_map(|element| match element {
+            Expr::StringLiteral(literal) => Some(literal.value.to_str().to_string()),
+            _ => None,
+        }));
+    }
+    names
+}
+
+/// Names defined at the top level of the module's source or listed in its `__all__`
+/// which are exposed by one of its interfaces
+fn declared_members(
+    source_roots: &[PathBuf],
+    module_path: &str,
+    interfaces: &[&CompiledInterface],
+) -> Vec<String> {
+    let Some(resolved) = fs::module_to_file_path(source_roots, module_path, false) else {
+        return vec![];
+    };
+    let Ok(contents) = fs::read_file_content(&resolved.file_path) else {
+        return vec![];
+    };
+    let Ok(Mod::Module(module)) = parse_python_source(&contents) else {
+        return vec![];
+    };
+    let names: BTreeSet<String> = top_level_names(&module.body)
+        .into_iter()
+        .chain(dunder_all_names(&module.body))
+        .filter(|name| name != "__all__")
+        .collect();
+    names
+        .into_iter()
+        .filter(|name| {
+            interfaces
+                .iter()
+                .any(|interface| interface.matches_member(name))
+        })
+        .collect()
+}
+
+fn aggregate_usages(
+    declared: Vec<String>,
+    usages: impl IntoIterator<Item = (String, String)>,
+) -> Vec<InterfaceMemberUsage> {
+    let mut members: BTreeMap<String, (usize, BTreeSet<String>)> = declared
+        .into_iter()
+        .map(|member| (member, (0, BTreeSet::new())))
+        .collect();
+    for (member, usage_module) in usages {
+        let entry = members.entry(member).or_default();
+        entry.0 += 1;
+        entry.1.insert(usage_module);
+    }
+
+    let mut members: Vec<InterfaceMemberUsage> = members
+        .into_iter()
+        .map(|(member, (usages, usage_modules))| InterfaceMemberUsage {
+            member,
+            usages,
+            usage_modules: usage_modules.into_iter().collect(),
+        })
+        .collect();
+    // BTreeMap iteration is already ordered by name, and the sort is stable
+    members.sort_by(|left, right| right.usages.cmp(&left.usages));
+    members
+}
+
+struct MemberUsage {
+    module_path: String,
+    member: String,
+    usage_module: String,
+    // Relative to the project root
+    file_path: PathBuf,
+    line_number: usize,
+}
+
+impl MemberUsage {
+    fn top_level_member(&self) -> &str {
+        self.member.split('.').next().unwrap_or(&self.member)
+    }
+
+    /// Whether one of the interfaces exposes the member, or the member it is nested under
+    fn is_exposed(&self, interfaces: &[&CompiledInterface]) -> bool {
+        interfaces.iter().any(|interface| {
+            interface.matches_member(&self.member)
+                || interface.matches_member(self.top_level_member())
+        })
+    }
+}
+
+/// Find the imports of members of each of the 'targets' from modules other than
+/// the target itself, whether or not they are exposed by an interface.
+/// Imports are attributed to the most deeply nested target which contains them.
+fn member_usages(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    source_roots: &[PathBuf],
+    module_tree: &ModuleTree,
+    targets: &[&str],
+) -> Result<Vec<MemberUsage>> {
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let project_root = project_root.to_path_buf();
+
+    let mut usages: Vec<MemberUsage> = Vec::new();
+    for source_root in source_roots {
+        check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+        let source_root_usages: Vec<MemberUsage> = fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            // Notebooks are only supported by 'tach check'
+            WalkOptions {
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        )
+        .par_bridge()
+        .filter_map(|pyfile| {
+            if check_interrupt().is_err() {
+                return None;
+            }
+
+            let absolute_pyfile = source_root.join(&pyfile);
+            let relative_pyfile = fs::relative_to(&absolute_pyfile, &project_root).ok()?;
+            let file_module_path = fs::file_to_module_path(source_roots, &absolute_pyfile).ok()?;
+            let file_module = module_tree.find_nearest(&file_module_path)?;
+            let project_imports = get_located_project_imports(
+                &project_root,
+                source_roots,
+                &absolute_pyfile,
+                project_config,
+            )
+            .ok()?;
+
+            Some(
+                project_imports
+                    .into_iter()
+                    .filter_map(|import| {
+                        let module_path = targets
+                            .iter()
+                            .filter(|module_path| {
+                                is_module_prefix(module_path, import.module_path())
+                            })
+                            .max_by_key(|module_path| module_path.len())?;
+                        if file_module.full_path == *module_path {
+                            return None;
+                        }
+                        let member = import
+                            .module_path()
+                            .strip_prefix(module_path)
+                            .and_then(|member| member.strip_prefix('.'))?;
+                        Some(MemberUsage {
+                            module_path: module_path.to_string(),
+                            member: member.to_string(),
+                            usage_module: file_module.full_path.clone(),
+                            file_path: relative_pyfile.clone(),
+                            line_number: import.alias_line_number(),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect();
+        usages.extend(source_root_usages);
+    }
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+    Ok(usages)
+}
+
+/// Count the imports of each exposed member of the module containing 'path'
+/// from elsewhere in the project, including members which are never imported.
+pub fn create_interface_usage_report(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    path: &Path,
+) -> Result<InterfaceUsageReport> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        false,
+        None,
+        RootModuleTreatment::Allow,
+    )?;
+
+    let absolute_path = project_root.join(path);
+    let module_path = fs::file_to_module_path(&source_roots, &absolute_path)?;
+    let target_module = module_tree.find_nearest(&module_path).ok_or_else(|| {
+        ReportCreationError::ModuleTree(ModuleTreeError::ModuleNotFound(module_path.clone()))
+    })?;
+
+    let compiled_interfaces = CompiledInterfaces::build(project_config.all_interfaces());
+    let interfaces = compiled_interfaces.get_interfaces(&target_module.full_path);
+    if interfaces.is_empty() {
+        return Err(ReportCreationError::NoInterface(
+            target_module.full_path.clone(),
+        ));
+    }
+    let declared = declared_members(&source_roots, &target_module.full_path, &interfaces);
+
+    let usages: Vec<(String, String)> = member_usages(
+        project_root,
+        project_config,
+        &source_roots,
+        &module_tree,
+        &[target_module.full_path.as_str()],
+    )?
+    .into_iter()
+    .filter(|usage| {
+        interfaces
+            .iter()
+            .any(|interface| interface.matches_member(&usage.member))
+    })
+    .map(|usage| (usage.member, usage.usage_module))
+    .collect();
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+    Ok(InterfaceUsageReport {
+        module_path: target_module.full_path.clone(),
+        members: aggregate_usages(declared, usages),
+    })
+}
+
+/// Exposed members which are never imported from another module, for each module within 'path'
+/// which has an interface. Importing a path nested under a member (e.g. `utils.format`) counts as
+/// a use of the member.
+pub fn find_unused_interface_members(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    path: &Path,
+) -> Result<Vec<UnusedInterfaceMembers>> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        false,
+        None,
+        RootModuleTreatment::Allow,
+    )?;
+    // When 'path' is not within a module (e.g. the project root), every module is included
+    let path_module = fs::file_to_module_path(&source_roots, &project_root.join(path)).ok();
+
+    let compiled_interfaces = CompiledInterfaces::build(project_config.all_interfaces());
+    let module_interfaces: Vec<(String, Vec<&CompiledInterface>)> = valid_modules
+        .iter()
+        .map(|module| module.path.as_str())
+        .filter(|module_path| {
+            *module_path != ROOT_MODULE_SENTINEL_TAG
+                && path_module.as_ref().is_none_or(|path_module| {
+                    is_module_prefix(path_module, module_path)
+                        || is_module_prefix(module_path, path_module)
+                })
+        })
+        .map(|module_path| {
+            (
+                module_path.to_string(),
+                compiled_interfaces.get_interfaces(module_path),
+            )
+        })
+        .filter(|(_, interfaces)| !interfaces.is_empty())
+        .collect();
+    let targets: Vec<&str> = module_interfaces
+        .iter()
+        .map(|(module_path, _)| module_path.as_str())
+        .collect();
+
+    let usages = member_usages(
+        project_root,
+        project_config,
+        &source_roots,
+        &module_tree,
+        &targets,
+    )?;
+
+    let mut unused: Vec<UnusedInterfaceMembers> = module_interfaces
+        .iter()
+        .filter_map(|(module_path, interfaces)| {
+            let members = unused_members(
+                declared_members(&source_roots, module_path, interfaces),
+                usages
+                    .iter()
+                    .filter(|usage| {
+                        usage.module_path == *module_path && usage.is_exposed(interfaces)
+                    })
+                    .map(|usage| usage.member.as_str()),
+            );
+            (!members.is_empty()).then(|| UnusedInterfaceMembers {
+                module_path: module_path.clone(),
+                members,
+            })
+        })
+        .collect();
+    unused.sort_by(|left, right| left.module_path.cmp(&right.module_path));
+    Ok(unused)
+}
+
+fn unused_members<'a>(
+    declared: Vec<String>,
+    used: impl Iterator<Item = &'a str> + Clone,
+) -> Vec<String> {
+    let mut members: Vec<String> = declared
+        .into_iter()
+        .filter(|member| !used.clone().any(|used| is_module_prefix(member, used)))
+        .collect();
+    members.sort();
+    members
+}
+
+fn strictify_usages(
+    module_path: String,
+    dunder_all: &[String],
+    interfaces: &[&CompiledInterface],
+    usages: Vec<MemberUsage>,
+) -> StrictifyReport {
+    let mut covered_usages = 0;
+    let mut blocking_imports = Vec::new();
+    let mut expose = BTreeSet::new();
+    for usage in usages {
+        let is_exposed = usage.is_exposed(interfaces);
+        if !is_exposed {
+            expose.insert(usage.top_level_member().to_string());
+        }
+        if is_exposed
+            || dunder_all
+                .iter()
+                .any(|name| name == usage.top_level_member())
+        {
+            covered_usages += 1;
+        } else {
+            blocking_imports.push(BlockingImport {
+                file_path: usage.file_path,
+                line_number: usage.line_number,
+                member: usage.member,
+                usage_module: usage.usage_module,
+            });
+        }
+    }
+    blocking_imports.sort_by(|left, right| {
+        (&left.file_path, left.line_number).cmp(&(&right.file_path, right.line_number))
+    });
+
+    StrictifyReport {
+        module_path,
+        has_interface: !interfaces.is_empty(),
+        covered_usages,
+        blocking_imports,
+        expose: expose.into_iter().collect(),
+    }
+}
+
+/// Find the imports of the module containing 'path' from elsewhere in the project which
+/// strict mode would reject, along with the members an interface must expose to allow them all.
+/// Members in the module's `__all__`, or exposed by an existing interface, are already allowed.
+pub fn strictify_module(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    path: &Path,
+) -> Result<StrictifyReport> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        false,
+        None,
+        RootModuleTreatment::Allow,
+    )?;
+
+    let absolute_path = project_root.join(path);
+    let module_path = fs::file_to_module_path(&source_roots, &absolute_path)?;
+    let target_module = module_tree.find_nearest(&module_path).ok_or_else(|| {
+        ReportCreationError::ModuleTree(ModuleTreeError::ModuleNotFound(module_path.clone()))
+    })?;
+
+    let compiled_interfaces = CompiledInterfaces::build(project_config.all_interfaces());
+    let interfaces = compiled_interfaces.get_interfaces(&target_module.full_path);
+    let dunder_all =
+        parse_interface_members(&source_roots, &target_module.full_path).unwrap_or_default();
+    let usages = member_usages(
+        project_root,
+        project_config,
+        &source_roots,
+        &module_tree,
+        &[target_module.full_path.as_str()],
+    )?;
+
+    Ok(strictify_usages(
+        target_module.full_path.clone(),
+        &dunder_all,
+        &interfaces,
+        usages,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_usages_includes_unused_members() {
+        let members = aggregate_usages(
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            vec![
+                ("c".to_string(), "other".to_string()),
+                ("c".to_string(), "another".to_string()),
+                ("a".to_string(), "other".to_string()),
+                ("c".to_string(), "other".to_string()),
+            ],
+        );
+
+        assert_eq!(
+            members,
+            vec![
+                InterfaceMemberUsage {
+                    member: "c".to_string(),
+                    usages: 3,
+                    usage_modules: vec!["another".to_string(), "other".to_string()],
+                },
+                InterfaceMemberUsage {
+                    member: "a".to_string(),
+                    usages: 1,
+                    usage_modules: vec!["other".to_string()],
+                },
+                InterfaceMemberUsage {
+                    member: "b".to_string(),
+                    usages: 0,
+                    usage_modules: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dunder_all_names() {
+        let source = "__all__ = ['Client', 'connect']\n__all__ += ('reexported',)\n__all__.append('dynamic')\n";
+        let Mod::Module(module) = parse_python_source(source).unwrap() else {
+            panic!("Expected a module");
+        };
+
+        assert_eq!(
+            dunder_all_names(&module.body),
+            vec!["Client", "connect", "reexported"]
+        );
+    }
+
+    #[test]
+    fn test_unused_members() {
+        let declared = vec![
+            "connect".to_string(),
+            "Client".to_string(),
+            "Config".to_string(),
+        ];
+
+        assert_eq!(
+            unused_members(declared, ["Client.create", "ConfigLoader"].into_iter()),
+            vec!["Config", "connect"]
+        );
+    }
+
+    #[test]
+    fn test_strictify_usages() {
+        let usage = |member: &str, file_path: &str, line_number: usize| MemberUsage {
+            module_path: "payments".to_string(),
+            member: member.to_string(),
+            usage_module: "orders".to_string(),
+            file_path: PathBuf::from(file_path),
+            line_number,
+        };
+        let report = strictify_usages(
+            "payments".to_string(),
+            &["charge".to_string()],
+            &[],
+            vec![
+                usage("models.Invoice", "orders/b.py", 3),
+                usage("charge", "orders/a.py", 1),
+                usage("refund", "orders/a.py", 2),
+            ],
+        );
+
+        assert_eq!(report.covered_usages, 1);
+        assert!(!report.has_interface);
+        assert_eq!(
+            report
+                .blocking_imports
+                .iter()
+                .map(|import| (import.file_path.to_str().unwrap(), import.line_number))
+                .collect::<Vec<_>>(),
+            vec![("orders/a.py", 2), ("orders/b.py", 3)]
+        );
+        assert_eq!(report.expose, vec!["charge", "models", "refund"]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+
+use crate::commands::check::check_internal::FileCaches;
+use crate::commands::check::CheckError;
+use crate::config::ProjectConfig;
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{self as fs, FileOverlay, ProjectFile, WalkOptions};
+use crate::interrupt::check_interrupt;
+
+pub type Result<T> = std::result::Result<T, CheckError>;
+
+#[derive(Debug, Clone, Default)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct InvalidateSummary {
+    // Files whose cache entries were looked up
+    pub files: usize,
+    // Cache entries which were removed
+    pub entries: usize,
+}
+
+/// Each target is a path relative to the project root, or a module path.
+/// Directories and packages expand to every Python file they contain.
+fn resolve_target_files(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    source_roots: &[PathBuf],
+    exclusions: &PathExclusions,
+    target: &str,
+) -> Result<Vec<PathBuf>> {
+    let target_path = project_root.join(target);
+    let target_path = if target_path.exists() {
+        target_path
+    } else {
+        fs::module_to_pyfile_or_dir_path(source_roots, target).ok_or_else(|| {
+            CheckError::ConfigError(format!(
+                "'{}' is not a path or module in the project.",
+                target
+            ))
+        })?
+    };
+
+    if target_path.is_dir() {
+        Ok(fs::walk_pyfiles(
+            &target_path.display().to_string(),
+            exclusions,
+            WalkOptions::from(project_config),
+        )
+        .map(|file_path| target_path.join(file_path))
+        .collect())
+    } else {
+        Ok(vec![target_path])
+    }
+}
+
+/// Remove the per-file cache entries (parsed imports and check results) for the given targets,
+/// from the remote cache or the '.tach' directory, so that the next run recomputes them.
+/// Entries are keyed by file contents, so only the entries matching the current contents
+/// of each file are removed.
+pub fn invalidate(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    targets: &[String],
+) -> Result<InvalidateSummary> {
+    if !project_root.is_dir() {
+        return Err(CheckError::InvalidDirectory(
+            project_root.display().to_string(),
+        ));
+    }
+
+    let source_roots: Vec<PathBuf> = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let overlay = FileOverlay::new();
+    let file_caches = FileCaches::build_all(
+        project_root,
+        project_config,
+        &source_roots,
+        &valid_modules,
+        &exclusions,
+        &overlay,
+    )?;
+
+    let mut summary = InvalidateSummary::default();
+    for target in targets {
+        for file_path in resolve_target_files(
+            project_root,
+            project_config,
+            &source_roots,
+            &exclusions,
+            target,
+        )? {
+            check_interrupt().map_err(|_| CheckError::Interrupt)?;
+
+            // The most specific source root contains the file
+            let Some(source_root) = source_roots
+                .iter()
+                .filter(|source_root| file_path.starts_with(source_root))
+                .max_by_key(|source_root| source_root.components().count())
+            else {
+                continue;
+            };
+            let Ok(project_files) = ProjectFile::try_new_all(project_root, source_root, &file_path)
+            else {
+                continue;
+            };
+            summary.files += 1;
+            for project_file in project_files {
+                let file_key = project_file.relative_file_path.display().to_string();
+                for caches in &file_caches {
+                    summary.entries += caches.invalidate(&file_key, &project_file.contents)?;
+                }
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::commands::warm::warm;
+    use crate::config::ModuleConfig;
+
+    #[test]
+    fn test_invalidate_local_import_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        for (path, contents) in [
+            ("a/__init__.py", "import b\n"),
+            ("a/api.py", "import b\n"),
+            ("b/__init__.py", ""),
+        ] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let project_config = ProjectConfig {
+            modules: vec![ModuleConfig::new("a", false), ModuleConfig::new("b", false)],
+            ..Default::default()
+        };
+        warm(temp_dir.path(), &project_config, false).unwrap();
+
+        // Modules expand to every file they contain
+        let summary = invalidate(temp_dir.path(), &project_config, &["a".to_string()]).unwrap();
+        assert_eq!((summary.files, summary.entries), (2, 2));
+        // The entries are gone, and other files are untouched
+        let summary = invalidate(
+            temp_dir.path(),
+            &project_config,
+            &["a/api.py".to_string(), "b".to_string()],
+        )
+        .unwrap();
+        assert_eq!((summary.files, summary.entries), (2, 1));
+    }
+
+    #[test]
+    fn test_unknown_target() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(matches!(
+            invalidate(
+                temp_dir.path(),
+                &ProjectConfig::default(),
+                &["missing".to_string()]
+            ),
+            Err(CheckError::ConfigError(_))
+        ));
+    }
+}
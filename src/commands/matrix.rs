@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use pyo3::prelude::*;
+
+use crate::config::{ModuleConfig, ProjectConfig};
+use crate::filesystem as fs;
+
+use super::check::check_internal;
+use super::report::{is_module_prefix, Result};
+use super::sync::{detect_project_dependencies, ImportLocation};
+
+/// A design structure matrix (DSM) of the imports between modules
+#[derive(Debug, Clone, Default, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct DependencyMatrix {
+    pub modules: Vec<String>,
+    // The display name of each module, or its path when it has none
+    pub labels: Vec<String>,
+    // The number of imports by each module (rows) of each other module (columns)
+    pub counts: Vec<Vec<usize>>,
+    // Whether the imports in each cell violate the declared dependencies
+    pub violations: Vec<Vec<bool>>,
+}
+
+fn dependency_matrix(
+    mut module_configs: Vec<ModuleConfig>,
+    dependencies: &HashMap<String, HashMap<String, Vec<ImportLocation>>>,
+    violations: &HashSet<(&str, &str)>,
+) -> DependencyMatrix {
+    module_configs.sort_by(|a, b| a.path.cmp(&b.path));
+    let labels: Vec<String> = module_configs.iter().map(ModuleConfig::label).collect();
+    let modules: Vec<String> = module_configs
+        .into_iter()
+        .map(|module| module.path)
+        .collect();
+    let mut counts = vec![vec![0; modules.len()]; modules.len()];
+    let mut cell_violations = vec![vec![false; modules.len()]; modules.len()];
+    for (row, source) in modules.iter().enumerate() {
+        for (column, target) in modules.iter().enumerate() {
+            if row == column {
+                continue;
+            }
+            counts[row][column] = dependencies
+                .get(source)
+                .and_then(|targets| targets.get(target))
+                .map_or(0, |usages| usages.len());
+            let cell = (source.as_str(), target.as_str());
+            cell_violations[row][column] = violations.contains(&cell);
+        }
+    }
+    DependencyMatrix {
+        modules,
+        labels,
+        counts,
+        violations: cell_violations,
+    }
+}
+
+/// Build the matrix of imports between the modules within 'path', with the cells whose imports
+/// are reported as errors by 'tach check' marked as violations.
+pub fn create_dependency_matrix(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    path: &Path,
+) -> Result<DependencyMatrix> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    // When 'path' is not within a module (e.g. the project root), every module is included
+    let path_module = fs::file_to_module_path(&source_roots, &project_root.join(path)).ok();
+    let modules: Vec<ModuleConfig> = valid_modules
+        .into_iter()
+        .filter(|module| {
+            path_module
+                .as_ref()
+                .is_none_or(|path_module| is_module_prefix(path_module, &module.path))
+        })
+        .collect();
+
+    let dependencies = detect_project_dependencies(project_root.to_path_buf(), project_config)?;
+    let diagnostics = check_internal(project_root.to_path_buf(), project_config, true, false)?;
+    let violations: HashSet<(&str, &str)> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_dependency_error() && diagnostic.is_error())
+        .filter_map(|diagnostic| {
+            Some((diagnostic.usage_module()?, diagnostic.definition_module()?))
+        })
+        .collect();
+    Ok(dependency_matrix(modules, &dependencies, &violations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_matrix() {
+        let mut dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>> =
+            HashMap::new();
+        for (source, target, imports) in [("api", "core", 2), ("core", "api", 1), ("api", "api", 4)]
+        {
+            dependencies.entry(source.to_string()).or_default().insert(
+                target.to_string(),
+                (1..=imports)
+                    .map(|line| ImportLocation {
+                        file: format!("{}.py", source),
+                        line,
+                    })
+                    .collect(),
+            );
+        }
+        let violations = HashSet::from([("core", "api")]);
+
+        let matrix = dependency_matrix(
+            vec![
+                ModuleConfig::new("core", false),
+                ModuleConfig {
+                    display_name: Some("Public API".to_string()),
+                    ..ModuleConfig::new("api", false)
+                },
+                ModuleConfig::new("jobs", false),
+            ],
+            &dependencies,
+            &violations,
+        );
+        assert_eq!(matrix.modules, vec!["api", "core", "jobs"]);
+        assert_eq!(matrix.labels, vec!["Public API", "core", "jobs"]);
+        assert_eq!(
+            matrix.counts,
+            vec![vec![0, 2, 0], vec![1, 0, 0], vec![0, 0, 0]]
+        );
+        assert_eq!(
+            matrix.violations,
+            vec![
+                vec![false, false, false],
+                vec![true, false, false],
+                vec![false, false, false]
+            ]
+        );
+    }
+}
@@ -1,6 +1,20 @@
 pub mod check;
+pub mod diff_config;
+pub mod export;
+pub mod graph;
 pub mod helpers;
+pub mod init;
+pub mod interface_usage;
+pub mod invalidate;
+pub mod matrix;
+pub mod mod_edit;
+pub mod query;
+pub mod ratchet;
 pub mod report;
 pub mod server;
+pub mod side_effects;
 pub mod sync;
 pub mod test;
+pub mod unused_modules;
+pub mod validate;
+pub mod warm;
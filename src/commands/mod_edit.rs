@@ -0,0 +1,793 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use thiserror::Error;
+
+use crate::config::edit::{ConfigEditor, EditError};
+use crate::config::write::write_atomic;
+use crate::config::ProjectConfig;
+use crate::exclusion::{PathExclusionError, PathExclusions};
+use crate::filesystem::{self, FileSystemError};
+use crate::parsing::config::dump_project_config_to_toml;
+
+#[derive(Error, Debug)]
+pub enum ModEditError {
+    #[error("The path {0} is not a directory.")]
+    NotADirectory(String),
+    #[error("Terminal I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to edit project configuration: {0}")]
+    Edit(#[from] EditError),
+    #[error("Failed to serialize project configuration: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+    #[error("Exclusion error: {0}")]
+    Exclusion(#[from] PathExclusionError),
+    #[error("Filesystem error: {0}")]
+    Filesystem(#[from] FileSystemError),
+}
+
+pub type Result<T> = std::result::Result<T, ModEditError>;
+
+const CONFIG_FILE_NAME: &str = "tach.toml";
+
+/// Marks are exclusive, a path is at most one of a module, utility, or source root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeMark {
+    #[default]
+    Unmarked,
+    Module,
+    Utility,
+    SourceRoot,
+}
+
+#[derive(Debug)]
+struct FileNode {
+    path: PathBuf,
+    is_dir: bool,
+    expanded: bool,
+    mark: NodeMark,
+    parent: Option<usize>,
+    // Children are read from disk lazily, the first time a directory is expanded
+    children: Option<Vec<usize>>,
+}
+
+/// A lazily loaded view of the Python files and packages below the project root.
+#[derive(Debug)]
+pub struct FileTree<'a> {
+    nodes: Vec<FileNode>,
+    exclusions: &'a PathExclusions,
+}
+
+impl<'a> FileTree<'a> {
+    const ROOT: usize = 0;
+
+    pub fn new(project_root: &Path, exclusions: &'a PathExclusions, depth: usize) -> Self {
+        let mut tree = Self {
+            nodes: vec![FileNode {
+                path: project_root.to_path_buf(),
+                is_dir: true,
+                expanded: false,
+                mark: NodeMark::Unmarked,
+                parent: None,
+                children: None,
+            }],
+            exclusions,
+        };
+        tree.expand_to_depth(Self::ROOT, depth.max(1));
+        tree
+    }
+
+    fn expand_to_depth(&mut self, node: usize, depth: usize) {
+        self.expand(node);
+        if depth > 1 {
+            for child in self.children(node) {
+                if self.nodes[child].is_dir {
+                    self.expand_to_depth(child, depth - 1);
+                }
+            }
+        }
+    }
+
+    fn should_include(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        // Hidden files and directories are never shown
+        if file_name.starts_with('.') {
+            return false;
+        }
+        // Only Python files are of interest, and __init__.py does not have
+        // a module path distinct from its package, so it cannot be marked separately
+        if !is_dir && (!file_name.ends_with(".py") || file_name == "__init__.py") {
+            return false;
+        }
+        !self.exclusions.is_path_excluded(path)
+    }
+
+    fn load_children(&mut self, node: usize) -> Vec<usize> {
+        if let Some(children) = &self.nodes[node].children {
+            return children.clone();
+        }
+        let mut entries: Vec<(PathBuf, bool)> = match fs::read_dir(&self.nodes[node].path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                    (entry.path(), is_dir)
+                })
+                .filter(|(path, is_dir)| self.should_include(path, *is_dir))
+                .collect(),
+            // Directories which cannot be read are simply shown as empty
+            Err(_) => vec![],
+        };
+        entries.sort();
+
+        let mut children = Vec::with_capacity(entries.len());
+        for (path, is_dir) in entries {
+            children.push(self.nodes.len());
+            self.nodes.push(FileNode {
+                path,
+                is_dir,
+                expanded: false,
+                mark: NodeMark::Unmarked,
+                parent: Some(node),
+                children: None,
+            });
+        }
+        self.nodes[node].children = Some(children.clone());
+        children
+    }
+
+    fn children(&self, node: usize) -> Vec<usize> {
+        self.nodes[node].children.clone().unwrap_or_default()
+    }
+
+    pub fn expand(&mut self, node: usize) {
+        if self.nodes[node].is_dir {
+            self.load_children(node);
+            self.nodes[node].expanded = true;
+        }
+    }
+
+    pub fn collapse(&mut self, node: usize) {
+        self.nodes[node].expanded = false;
+    }
+
+    /// Find the node for an absolute path, loading and expanding its ancestors
+    pub fn find_path(&mut self, path: &Path) -> Option<usize> {
+        let relative_path = path.strip_prefix(&self.nodes[Self::ROOT].path).ok()?;
+        let mut current = Self::ROOT;
+        for component in relative_path.components() {
+            self.expand(current);
+            current = self
+                .children(current)
+                .into_iter()
+                .find(|child| self.nodes[*child].path.file_name() == Some(component.as_os_str()))?;
+        }
+        Some(current)
+    }
+
+    pub fn mark(&self, node: usize) -> NodeMark {
+        self.nodes[node].mark
+    }
+
+    pub fn toggle_mark(&mut self, node: usize, mark: NodeMark) {
+        // Only source roots make sense for the project root itself
+        if node == Self::ROOT && mark != NodeMark::SourceRoot {
+            return;
+        }
+        let current = &mut self.nodes[node].mark;
+        *current = if *current == mark {
+            NodeMark::Unmarked
+        } else {
+            mark
+        };
+    }
+
+    /// Mark all siblings as modules, or unmark them if they already are
+    pub fn toggle_siblings(&mut self, node: usize) {
+        let Some(parent) = self.nodes[node].parent else {
+            return;
+        };
+        let siblings = self.children(parent);
+        let all_modules = siblings
+            .iter()
+            .all(|sibling| self.nodes[*sibling].mark == NodeMark::Module);
+        for sibling in siblings {
+            self.nodes[sibling].mark = if all_modules {
+                NodeMark::Unmarked
+            } else {
+                NodeMark::Module
+            };
+        }
+    }
+
+    /// Depth-first list of visible nodes, with their depth below the root
+    pub fn visible(&self) -> Vec<(usize, usize)> {
+        let mut visible = vec![];
+        let mut stack = vec![(Self::ROOT, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            visible.push((node, depth));
+            if self.nodes[node].expanded {
+                stack.extend(
+                    self.children(node)
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, depth + 1)),
+                );
+            }
+        }
+        visible
+    }
+
+    pub fn selection(&self) -> ModuleSelection {
+        let mut selection = ModuleSelection::default();
+        for node in &self.nodes {
+            match node.mark {
+                NodeMark::Module => selection.module_paths.push(node.path.clone()),
+                NodeMark::Utility => selection.utility_paths.push(node.path.clone()),
+                NodeMark::SourceRoot => selection.source_roots.push(node.path.clone()),
+                NodeMark::Unmarked => {}
+            }
+        }
+        selection
+    }
+
+    fn initialize_marks(&mut self, project_root: &Path, project_config: &ProjectConfig) {
+        let source_roots = project_config.prepend_roots(project_root);
+        for module in project_config.all_modules() {
+            let Some(module_file_path) =
+                filesystem::module_to_pyfile_or_dir_path(&source_roots, &module.path)
+            else {
+                continue;
+            };
+            if let Some(node) = self.find_path(&module_file_path) {
+                self.nodes[node].mark = if module.utility {
+                    NodeMark::Utility
+                } else {
+                    NodeMark::Module
+                };
+            }
+        }
+        for source_root in &source_roots {
+            if let Some(node) = self.find_path(source_root) {
+                self.nodes[node].mark = NodeMark::SourceRoot;
+                self.expand(node);
+            }
+        }
+    }
+}
+
+/// The absolute paths chosen in the editor
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ModuleSelection {
+    pub source_roots: Vec<PathBuf>,
+    pub module_paths: Vec<PathBuf>,
+    pub utility_paths: Vec<PathBuf>,
+}
+
+pub fn validate_selection(selection: &ModuleSelection) -> Vec<String> {
+    selection
+        .module_paths
+        .iter()
+        .chain(&selection.utility_paths)
+        .filter(|module_path| {
+            !selection.source_roots.iter().any(|source_root| {
+                module_path.starts_with(source_root) && *module_path != source_root
+            })
+        })
+        .map(|module_path| {
+            format!(
+                "Module '{}' is not contained within any source root: {:?}",
+                module_path.display(),
+                selection
+                    .source_roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+            )
+        })
+        .collect()
+}
+
+/// Commit a selection to the project configuration through `ConfigEditor`,
+/// creating the config file first if it does not exist.
+pub fn apply_selection(
+    project_root: &Path,
+    project_config: &mut ProjectConfig,
+    selection: &ModuleSelection,
+) -> Result<()> {
+    // An existing config (which may be YAML) is edited in place
+    if project_config.location.is_none() {
+        let config_path = project_root.join(CONFIG_FILE_NAME);
+        if !config_path.exists() {
+            write_atomic(&config_path, &dump_project_config_to_toml(project_config)?)?;
+        }
+        project_config.set_location(config_path);
+    }
+
+    let selected_source_roots: HashSet<PathBuf> = selection
+        .source_roots
+        .iter()
+        .map(|source_root| {
+            let relative = filesystem::relative_to(source_root, project_root)?;
+            Ok(if relative.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                relative
+            })
+        })
+        .collect::<Result<_>>()?;
+    let existing_source_roots: HashSet<PathBuf> =
+        project_config.source_roots.iter().cloned().collect();
+    for source_root in selected_source_roots.difference(&existing_source_roots) {
+        project_config.add_source_root(source_root.clone())?;
+    }
+    for source_root in existing_source_roots.difference(&selected_source_roots) {
+        project_config.remove_source_root(source_root.clone())?;
+    }
+
+    let to_module_paths = |paths: &[PathBuf]| -> Result<HashSet<String>> {
+        paths
+            .iter()
+            .map(|path| {
+                Ok(filesystem::file_to_module_path(
+                    &selection.source_roots,
+                    path,
+                )?)
+            })
+            .collect()
+    };
+    let selected_utilities = to_module_paths(&selection.utility_paths)?;
+    let selected_modules: HashSet<String> = to_module_paths(&selection.module_paths)?
+        .union(&selected_utilities)
+        .cloned()
+        .collect();
+    let existing_modules: HashSet<String> = project_config.module_paths().into_iter().collect();
+    let existing_utilities: HashSet<String> = project_config
+        .all_modules()
+        .filter(|module| module.utility)
+        .map(|module| module.path.clone())
+        .collect();
+
+    for module in selected_modules.difference(&existing_modules) {
+        project_config.create_module(module.clone())?;
+    }
+    for module in existing_modules.difference(&selected_modules) {
+        project_config.delete_module(module.clone())?;
+    }
+    for utility in selected_utilities.difference(&existing_utilities) {
+        project_config.mark_module_as_utility(utility.clone())?;
+    }
+    for utility in existing_utilities.difference(&selected_utilities) {
+        // Utilities which were deleted entirely do not need to be unmarked
+        if selected_modules.contains(utility) {
+            project_config.unmark_module_as_utility(utility.clone())?;
+        }
+    }
+
+    Ok(project_config.apply_edits()?)
+}
+
+enum EditorAction {
+    Continue,
+    Save,
+    Quit,
+}
+
+const KEY_LEGEND: [&[(&str, &str)]; 3] = [
+    &[
+        ("Up/Down", "Navigate"),
+        ("Right", "Expand"),
+        ("Left", "Collapse/Jump to parent"),
+    ],
+    &[
+        ("Enter", "Mark/unmark Module"),
+        ("s", "Mark/unmark Source Root"),
+        ("u", "Mark/unmark Utility"),
+    ],
+    &[
+        ("Ctrl + s", "Exit and save"),
+        ("Ctrl + c", "Exit without saving"),
+        ("Ctrl + a", "Mark/unmark all"),
+    ],
+];
+
+struct ModEditor<'a> {
+    tree: FileTree<'a>,
+    list_state: ListState,
+}
+
+impl<'a> ModEditor<'a> {
+    fn new(tree: FileTree<'a>) -> Self {
+        Self {
+            tree,
+            list_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn selected_node(&self, visible: &[(usize, usize)]) -> usize {
+        let index = self.list_state.selected().unwrap_or_default();
+        visible
+            .get(index)
+            .map(|(node, _)| *node)
+            .unwrap_or(FileTree::ROOT)
+    }
+
+    fn select_node(&mut self, node: usize) {
+        let position = self
+            .tree
+            .visible()
+            .iter()
+            .position(|(visible_node, _)| *visible_node == node);
+        self.list_state.select(position.or(Some(0)));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> EditorAction {
+        let visible = self.tree.visible();
+        let selected = self.selected_node(&visible);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c') if ctrl => return EditorAction::Quit,
+            KeyCode::Char('s') if ctrl => return EditorAction::Save,
+            KeyCode::Char('a') if ctrl => self.tree.toggle_siblings(selected),
+            KeyCode::Esc => return EditorAction::Quit,
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => {
+                if self.list_state.selected().unwrap_or_default() + 1 < visible.len() {
+                    self.list_state.select_next();
+                }
+            }
+            KeyCode::Right => self.tree.expand(selected),
+            KeyCode::Left => {
+                if self.tree.nodes[selected].expanded {
+                    self.tree.collapse(selected);
+                } else if let Some(parent) = self.tree.nodes[selected].parent {
+                    self.select_node(parent);
+                }
+            }
+            KeyCode::Enter => self.tree.toggle_mark(selected, NodeMark::Module),
+            KeyCode::Char('s') => self.tree.toggle_mark(selected, NodeMark::SourceRoot),
+            KeyCode::Char('u') => self.tree.toggle_mark(selected, NodeMark::Utility),
+            _ => {}
+        }
+        EditorAction::Continue
+    }
+
+    fn render_node(&self, node: usize, depth: usize, is_selected: bool) -> ListItem<'static> {
+        let file_node = &self.tree.nodes[node];
+        let name = if node == FileTree::ROOT {
+            file_node.path.display().to_string()
+        } else {
+            file_node
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+
+        let mut spans = vec![Span::raw("  ".repeat(depth))];
+        let label = match file_node.mark {
+            NodeMark::SourceRoot => Span::styled(
+                format!("[Source Root] {}", name),
+                Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            NodeMark::Module => Span::styled(
+                format!("[Module] {}", name),
+                Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            NodeMark::Utility => Span::styled(
+                format!("[Utility] {}", name),
+                Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            ),
+            NodeMark::Unmarked if is_selected => {
+                Span::styled(name, Style::new().add_modifier(Modifier::BOLD))
+            }
+            NodeMark::Unmarked => Span::raw(name),
+        };
+        spans.push(label);
+
+        let has_children = file_node
+            .children
+            .as_ref()
+            .map_or(file_node.is_dir, |children| !children.is_empty());
+        if has_children {
+            let indicator = if file_node.expanded { " ∨" } else { " >" };
+            spans.push(Span::styled(indicator, Style::new().fg(Color::Cyan)));
+        }
+        ListItem::new(Line::from(spans))
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let visible = self.tree.visible();
+        let selected = self.selected_node(&visible);
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|(node, depth)| self.render_node(*node, *depth, *node == selected))
+            .collect();
+
+        let [tree_area, footer_area] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(KEY_LEGEND.len() as u16),
+        ])
+        .areas(frame.area());
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Confirm Your Modules"),
+            )
+            .highlight_symbol("-> ")
+            .highlight_style(Style::new().fg(Color::Cyan));
+        frame.render_stateful_widget(list, tree_area, &mut self.list_state);
+
+        let footer: Vec<Line> = KEY_LEGEND
+            .iter()
+            .map(|bindings| {
+                Line::from(
+                    bindings
+                        .iter()
+                        .flat_map(|(key, description)| {
+                            [
+                                Span::styled(
+                                    *key,
+                                    Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                                ),
+                                Span::raw(format!(": {}  ", description)),
+                            ]
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .centered()
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<Option<ModuleSelection>> {
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match self.handle_key(key) {
+                    EditorAction::Continue => {}
+                    EditorAction::Save => return Ok(Some(self.tree.selection())),
+                    EditorAction::Quit => return Ok(None),
+                }
+            }
+        }
+    }
+}
+
+/// Run the interactive module editor, returning whether changes were saved
+/// along with any messages which should be shown to the user.
+pub fn mod_edit_interactive(
+    project_root: PathBuf,
+    mut project_config: ProjectConfig,
+    exclude_paths: Vec<String>,
+    depth: Option<usize>,
+) -> Result<(bool, Vec<String>)> {
+    if !project_root.is_dir() {
+        return Err(ModEditError::NotADirectory(
+            project_root.display().to_string(),
+        ));
+    }
+
+    let exclusions = PathExclusions::new(&project_root, &exclude_paths, &project_config.ignore)?;
+    let mut tree = FileTree::new(&project_root, &exclusions, depth.unwrap_or(1));
+    tree.initialize_marks(&project_root, &project_config);
+
+    let mut terminal = ratatui::init();
+    let result = ModEditor::new(tree).run(&mut terminal);
+    ratatui::restore();
+
+    save_selection(&project_root, &mut project_config, result?)
+}
+
+/// Validate and apply the selection made in the editor, if it was saved
+fn save_selection(
+    project_root: &Path,
+    project_config: &mut ProjectConfig,
+    selection: Option<ModuleSelection>,
+) -> Result<(bool, Vec<String>)> {
+    let Some(selection) = selection else {
+        return Ok((false, vec!["No changes saved.".to_string()]));
+    };
+
+    let errors = validate_selection(&selection);
+    if !errors.is_empty() {
+        return Ok((
+            false,
+            errors
+                .into_iter()
+                .map(|error| format!("Validation error: {}", error))
+                .collect(),
+        ));
+    }
+
+    apply_selection(project_root, project_config, &selection)?;
+    Ok((true, vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IgnoreConfig;
+    use crate::parsing::config::parse_project_config;
+    use rstest::*;
+    use tempfile::TempDir;
+
+    #[fixture]
+    fn project_root() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for file_name in ["dir1/__init__.py", "dir1/file1.py", "dir2/file2.py"] {
+            let file_path = temp_dir.path().join(file_name);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, "").unwrap();
+        }
+        temp_dir
+    }
+
+    fn saved_config(project_root: &Path) -> ProjectConfig {
+        parse_project_config(project_root.join(CONFIG_FILE_NAME))
+            .unwrap()
+            .0
+    }
+
+    #[rstest]
+    fn test_file_tree_lazy_loading(project_root: TempDir) {
+        let exclusions = PathExclusions::none(project_root.path());
+        let mut tree = FileTree::new(project_root.path(), &exclusions, 1);
+        // Only the root is expanded, '__init__.py' is never shown
+        assert_eq!(tree.visible().len(), 3);
+
+        let file1 = tree
+            .find_path(&project_root.path().join("dir1/file1.py"))
+            .unwrap();
+        assert!(tree
+            .find_path(&project_root.path().join("dir1/__init__.py"))
+            .is_none());
+        assert_eq!(tree.visible().len(), 4);
+
+        tree.toggle_mark(file1, NodeMark::Module);
+        assert_eq!(tree.mark(file1), NodeMark::Module);
+        tree.toggle_mark(file1, NodeMark::Utility);
+        assert_eq!(tree.mark(file1), NodeMark::Utility);
+    }
+
+    #[rstest]
+    fn test_file_tree_exclusions(project_root: TempDir) {
+        let root = project_root.path();
+        fs::write(root.join(".gitignore"), "dir2/\n").unwrap();
+        let exclusions = PathExclusions::new(
+            root,
+            &["dir1/file1.py".to_string()],
+            &IgnoreConfig::default(),
+        )
+        .unwrap();
+        let mut tree = FileTree::new(root, &exclusions, 2);
+        assert!(tree.find_path(&root.join("dir1")).is_some());
+        assert!(tree.find_path(&root.join("dir1/file1.py")).is_none());
+        assert!(tree.find_path(&root.join("dir2")).is_none());
+    }
+
+    fn utility_paths(project_config: &ProjectConfig) -> HashSet<String> {
+        project_config
+            .all_modules()
+            .filter(|module| module.utility)
+            .map(|module| module.path.clone())
+            .collect()
+    }
+
+    #[rstest]
+    fn test_save_new_configuration(project_root: TempDir) {
+        let root = project_root.path();
+        let selection = ModuleSelection {
+            source_roots: vec![root.join("src")],
+            module_paths: vec![root.join("src/module1.py"), root.join("src/module2.py")],
+            utility_paths: vec![root.join("src/utils.py")],
+        };
+        let result = save_selection(root, &mut ProjectConfig::default(), Some(selection));
+        assert_eq!(result.unwrap(), (true, vec![]));
+
+        let project_config = saved_config(root);
+        assert_eq!(project_config.source_roots, vec![PathBuf::from("src")]);
+        let modules: HashSet<String> = project_config.module_paths().into_iter().collect();
+        assert_eq!(
+            modules,
+            HashSet::from([
+                "module1".to_string(),
+                "module2".to_string(),
+                "utils".to_string()
+            ])
+        );
+        assert_eq!(
+            utility_paths(&project_config),
+            HashSet::from(["utils".to_string()])
+        );
+    }
+
+    #[rstest]
+    fn test_save_selection_outside_source_root(project_root: TempDir) {
+        let root = project_root.path();
+        let selection = ModuleSelection {
+            source_roots: vec![root.join("src")],
+            module_paths: vec![root.join("outside/module1.py")],
+            utility_paths: vec![],
+        };
+        let (saved, errors) =
+            save_selection(root, &mut ProjectConfig::default(), Some(selection)).unwrap();
+        assert!(!saved);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("not contained within any source root"));
+        assert!(!root.join(CONFIG_FILE_NAME).exists());
+    }
+
+    #[rstest]
+    fn test_save_cancelled(project_root: TempDir) {
+        let root = project_root.path();
+        let (saved, errors) = save_selection(root, &mut ProjectConfig::default(), None).unwrap();
+        assert!(!saved);
+        assert_eq!(errors, vec!["No changes saved.".to_string()]);
+        assert!(!root.join(CONFIG_FILE_NAME).exists());
+    }
+
+    #[rstest]
+    fn test_apply_selection_updates_existing(project_root: TempDir) {
+        let root = project_root.path();
+        let mut project_config = ProjectConfig::default();
+        let selection = ModuleSelection {
+            source_roots: vec![root.join("src"), root.join("tests")],
+            module_paths: vec![root.join("src/new_module.py")],
+            utility_paths: vec![root.join("src/new_utility.py")],
+        };
+        apply_selection(root, &mut project_config, &selection).unwrap();
+
+        let mut project_config = saved_config(root);
+        let source_roots: HashSet<PathBuf> = project_config.source_roots.iter().cloned().collect();
+        assert_eq!(
+            source_roots,
+            HashSet::from([PathBuf::from("src"), PathBuf::from("tests")])
+        );
+        let modules: HashSet<String> = project_config.module_paths().into_iter().collect();
+        assert_eq!(
+            modules,
+            HashSet::from(["new_module".to_string(), "new_utility".to_string()])
+        );
+        assert_eq!(
+            utility_paths(&project_config),
+            HashSet::from(["new_utility".to_string()])
+        );
+
+        let selection = ModuleSelection {
+            source_roots: vec![root.join("src")],
+            module_paths: vec![
+                root.join("src/new_module.py"),
+                root.join("src/another_module.py"),
+            ],
+            utility_paths: vec![],
+        };
+        apply_selection(root, &mut project_config, &selection).unwrap();
+
+        let project_config = saved_config(root);
+        assert_eq!(project_config.source_roots, vec![PathBuf::from("src")]);
+        let modules: HashSet<String> = project_config.module_paths().into_iter().collect();
+        assert_eq!(
+            modules,
+            HashSet::from(["new_module".to_string(), "another_module".to_string()])
+        );
+        assert!(utility_paths(&project_config).is_empty());
+    }
+}
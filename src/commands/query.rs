@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use petgraph::algo::{all_simple_paths, dijkstra};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{Bfs, Reversed};
+use petgraph::Direction;
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::commands::check::CheckError;
+use crate::commands::sync::{detect_project_dependencies, example_locations, ImportLocation};
+use crate::config::ProjectConfig;
+
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("Module '{0}' is not in the project configuration.")]
+    ModuleNotFound(String),
+    #[error("No module in the project configuration matches '{0}'.")]
+    NoMatchingModules(String),
+    #[error("Invalid module pattern '{pattern}': {source}")]
+    InvalidPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[error("Failed to query the module graph.\n{0}")]
+    CheckError(#[from] CheckError),
+}
+
+pub type Result<T> = std::result::Result<T, QueryError>;
+
+/// A single dependency along a path, along with the imports which use it
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct PathStep {
+    pub source: String,
+    pub target: String,
+    pub evidence: usize,
+    pub examples: Vec<ImportLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct DependencyPath {
+    pub steps: Vec<PathStep>,
+}
+
+impl DependencyPath {
+    pub fn modules(&self) -> Vec<&str> {
+        let mut modules: Vec<&str> = self.steps.iter().map(|step| step.source.as_str()).collect();
+        if let Some(last) = self.steps.last() {
+            modules.push(&last.target);
+        }
+        modules
+    }
+}
+
+/// The shortest paths through which one module depends on another, for a pair of modules matched by `tach why`
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct DependencyExplanation {
+    pub source: String,
+    pub target: String,
+    pub paths: Vec<DependencyPath>,
+}
+
+/// The graph of dependencies between modules which are used by imports, weighted by the imports
+#[derive(Debug, Default)]
+struct ImportGraph {
+    graph: DiGraph<String, Vec<ImportLocation>>,
+    indices: HashMap<String, NodeIndex>,
+}
+
+impl ImportGraph {
+    fn node(&mut self, path: &str) -> NodeIndex {
+        if let Some(index) = self.indices.get(path) {
+            return *index;
+        }
+        let index = self.graph.add_node(path.to_string());
+        self.indices.insert(path.to_string(), index);
+        index
+    }
+
+    fn from_dependencies(
+        dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>>,
+    ) -> Self {
+        let mut import_graph = Self::default();
+        // Sorted, so that query results are deterministic
+        let mut dependencies: Vec<_> = dependencies
+            .into_iter()
+            .flat_map(|(source, targets)| {
+                targets
+                    .into_iter()
+                    .map(move |(target, usages)| (source.clone(), target, usages))
+            })
+            .collect();
+        dependencies.sort_by(|left, right| (&left.0, &left.1).cmp(&(&right.0, &right.1)));
+        for (source, target, usages) in dependencies {
+            if source == target {
+                continue;
+            }
+            let source = import_graph.node(&source);
+            let target = import_graph.node(&target);
+            import_graph.graph.add_edge(source, target, usages);
+        }
+        import_graph
+    }
+
+    /// All modules reachable from `module`, following dependencies in the given direction
+    fn closure(&self, module: &str, direction: Direction) -> Vec<String> {
+        let Some(start) = self.indices.get(module) else {
+            return vec![];
+        };
+        let mut reachable = Vec::new();
+        match direction {
+            Direction::Outgoing => {
+                let mut bfs = Bfs::new(&self.graph, *start);
+                while let Some(node) = bfs.next(&self.graph) {
+                    reachable.push(self.graph[node].clone());
+                }
+            }
+            Direction::Incoming => {
+                let reversed = Reversed(&self.graph);
+                let mut bfs = Bfs::new(reversed, *start);
+                while let Some(node) = bfs.next(reversed) {
+                    reachable.push(self.graph[node].clone());
+                }
+            }
+        }
+        reachable.retain(|path| path != module);
+        reachable.sort();
+        reachable
+    }
+
+    /// Up to `max_paths` paths from `source` to `target`, shortest first
+    fn paths(&self, source: &str, target: &str, max_paths: usize) -> Vec<DependencyPath> {
+        let (Some(source), Some(target)) = (self.indices.get(source), self.indices.get(target))
+        else {
+            return vec![];
+        };
+        let mut paths = Vec::new();
+        // Enumerate by length, so that the shortest paths are found without listing every path
+        for intermediate_nodes in 0..self.graph.node_count() {
+            if paths.len() >= max_paths {
+                break;
+            }
+            let mut paths_of_length: Vec<Vec<NodeIndex>> = all_simple_paths(
+                &self.graph,
+                *source,
+                *target,
+                intermediate_nodes,
+                Some(intermediate_nodes),
+            )
+            .collect();
+            paths_of_length.sort_by_key(|path| {
+                path.iter()
+                    .map(|node| self.graph[*node].clone())
+                    .collect::<Vec<_>>()
+            });
+            paths.extend(
+                paths_of_length
+                    .into_iter()
+                    .take(max_paths - paths.len())
+                    .map(|path| self.dependency_path(&path)),
+            );
+        }
+        paths
+    }
+
+    /// Every shortest path from `source` to `target` (or only the first, without `all`),
+    /// along with every import in each step
+    fn shortest_paths(&self, source: &str, target: &str, all: bool) -> Vec<DependencyPath> {
+        let (Some(source), Some(target)) = (self.indices.get(source), self.indices.get(target))
+        else {
+            return vec![];
+        };
+        let Some(length) = dijkstra(&self.graph, *source, Some(*target), |_| 1_usize)
+            .get(target)
+            .copied()
+            .filter(|length| *length > 0)
+        else {
+            return vec![];
+        };
+        let mut paths: Vec<Vec<NodeIndex>> =
+            all_simple_paths(&self.graph, *source, *target, length - 1, Some(length - 1)).collect();
+        paths.sort_by_key(|path| {
+            path.iter()
+                .map(|node| self.graph[*node].clone())
+                .collect::<Vec<_>>()
+        });
+        if !all {
+            paths.truncate(1);
+        }
+        paths
+            .iter()
+            .map(|path| {
+                let mut path = self.dependency_path(path);
+                for step in path.steps.iter_mut() {
+                    let edge = self
+                        .graph
+                        .find_edge(self.indices[&step.source], self.indices[&step.target])
+                        .unwrap();
+                    let mut imports = self.graph[edge].clone();
+                    imports.sort_by(|left, right| {
+                        (&left.file, left.line).cmp(&(&right.file, right.line))
+                    });
+                    step.examples = imports;
+                }
+                path
+            })
+            .collect()
+    }
+
+    fn dependency_path(&self, nodes: &[NodeIndex]) -> DependencyPath {
+        DependencyPath {
+            steps: nodes
+                .windows(2)
+                .map(|pair| {
+                    let edge = self.graph.find_edge(pair[0], pair[1]).unwrap();
+                    let usages = &self.graph[edge];
+                    PathStep {
+                        source: self.graph[pair[0]].clone(),
+                        target: self.graph[pair[1]].clone(),
+                        evidence: usages.len(),
+                        examples: example_locations(usages),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+fn validate_module(project_config: &ProjectConfig, module: &str) -> Result<()> {
+    if project_config
+        .module_paths()
+        .iter()
+        .any(|path| path == module)
+    {
+        Ok(())
+    } else {
+        Err(QueryError::ModuleNotFound(module.to_string()))
+    }
+}
+
+/// The modules matching `pattern`, which is either a module path or a glob of module paths
+/// (where `*` matches any sequence of characters, including `.`)
+fn matching_modules(project_config: &ProjectConfig, pattern: &str) -> Result<Vec<String>> {
+    if !pattern.contains(['*', '?', '[']) {
+        validate_module(project_config, pattern)?;
+        return Ok(vec![pattern.to_string()]);
+    }
+    let glob = glob::Pattern::new(pattern).map_err(|source| QueryError::InvalidPattern {
+        pattern: pattern.to_string(),
+        source,
+    })?;
+    let mut modules: Vec<String> = project_config
+        .module_paths()
+        .into_iter()
+        .filter(|path| glob.matches(path))
+        .collect();
+    if modules.is_empty() {
+        return Err(QueryError::NoMatchingModules(pattern.to_string()));
+    }
+    modules.sort();
+    modules.dedup();
+    Ok(modules)
+}
+
+/// The modules which `module` transitively depends on through imports,
+/// or with `dependents`, the modules which transitively depend on `module`.
+pub fn transitive_dependencies(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    module: &str,
+    dependents: bool,
+) -> Result<Vec<String>> {
+    validate_module(project_config, module)?;
+    let import_graph =
+        ImportGraph::from_dependencies(detect_project_dependencies(project_root, project_config)?);
+    let direction = if dependents {
+        Direction::Incoming
+    } else {
+        Direction::Outgoing
+    };
+    Ok(import_graph.closure(module, direction))
+}
+
+/// The paths of imports through which `source` depends on `target`, shortest first
+pub fn dependency_paths(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    source: &str,
+    target: &str,
+    max_paths: usize,
+) -> Result<Vec<DependencyPath>> {
+    validate_module(project_config, source)?;
+    validate_module(project_config, target)?;
+    let import_graph =
+        ImportGraph::from_dependencies(detect_project_dependencies(project_root, project_config)?);
+    Ok(import_graph.paths(source, target, max_paths))
+}
+
+/// Why the modules matching `source` depend on the modules matching `target`: the shortest paths
+/// of imports for each pair of modules with any path between them. Without `all_shortest`,
+/// only the first shortest path is listed for each pair.
+pub fn explain_dependencies(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+    source: &str,
+    target: &str,
+    all_shortest: bool,
+) -> Result<Vec<DependencyExplanation>> {
+    let sources = matching_modules(project_config, source)?;
+    let targets = matching_modules(project_config, target)?;
+    let import_graph =
+        ImportGraph::from_dependencies(detect_project_dependencies(project_root, project_config)?);
+    Ok(sources
+        .iter()
+        .flat_map(|source| targets.iter().map(move |target| (source, target)))
+        .filter(|(source, target)| source != target)
+        .filter_map(|(source, target)| {
+            let paths = import_graph.shortest_paths(source, target, all_shortest);
+            (!paths.is_empty()).then(|| DependencyExplanation {
+                source: source.clone(),
+                target: target.clone(),
+                paths,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_graph(edges: &[(&str, &str, usize)]) -> ImportGraph {
+        let mut dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>> =
+            HashMap::new();
+        for (source, target, imports) in edges {
+            dependencies.entry(source.to_string()).or_default().insert(
+                target.to_string(),
+                (1..=*imports)
+                    .map(|line| ImportLocation {
+                        file: format!("{}.py", source.replace('.', "/")),
+                        line,
+                    })
+                    .collect(),
+            );
+        }
+        ImportGraph::from_dependencies(dependencies)
+    }
+
+    #[test]
+    fn test_closure() {
+        let graph = import_graph(&[
+            ("billing", "payments", 1),
+            ("payments", "auth", 1),
+            ("auth", "core", 1),
+            ("api", "billing", 1),
+        ]);
+        assert_eq!(
+            graph.closure("billing", Direction::Outgoing),
+            vec!["auth", "core", "payments"]
+        );
+        assert_eq!(
+            graph.closure("auth", Direction::Incoming),
+            vec!["api", "billing", "payments"]
+        );
+        assert!(graph.closure("unused", Direction::Outgoing).is_empty());
+    }
+
+    #[test]
+    fn test_paths_shortest_first() {
+        let graph = import_graph(&[
+            ("billing", "payments", 2),
+            ("payments", "auth", 5),
+            ("billing", "auth", 1),
+            ("billing", "core", 1),
+            ("core", "payments", 1),
+        ]);
+        let paths = graph.paths("billing", "auth", 10);
+        assert_eq!(
+            paths.iter().map(|path| path.modules()).collect::<Vec<_>>(),
+            vec![
+                vec!["billing", "auth"],
+                vec!["billing", "payments", "auth"],
+                vec!["billing", "core", "payments", "auth"],
+            ]
+        );
+        assert_eq!(paths[1].steps[1].evidence, 5);
+        assert_eq!(paths[1].steps[1].examples.len(), 3);
+
+        assert_eq!(graph.paths("billing", "auth", 2).len(), 2);
+        assert!(graph.paths("auth", "billing", 10).is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path_has_every_import() {
+        let graph = import_graph(&[
+            ("api", "billing", 4),
+            ("billing", "auth", 1),
+            ("api", "core", 1),
+            ("core", "payments", 1),
+            ("payments", "auth", 1),
+        ]);
+        let path = graph.shortest_paths("api", "auth", false).pop().unwrap();
+        assert_eq!(path.modules(), vec!["api", "billing", "auth"]);
+        assert_eq!(path.steps[0].evidence, 4);
+        assert_eq!(
+            path.steps[0]
+                .examples
+                .iter()
+                .map(|import| import.line)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert!(graph.shortest_paths("auth", "api", false).is_empty());
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let graph = import_graph(&[
+            ("api", "billing", 1),
+            ("api", "core", 2),
+            ("billing", "auth", 1),
+            ("core", "auth", 1),
+            ("api", "payments", 1),
+            ("payments", "billing", 1),
+        ]);
+        let paths = graph.shortest_paths("api", "auth", true);
+        assert_eq!(
+            paths.iter().map(|path| path.modules()).collect::<Vec<_>>(),
+            vec![vec!["api", "billing", "auth"], vec!["api", "core", "auth"]]
+        );
+        assert_eq!(paths[1].steps[0].evidence, 2);
+        assert_eq!(graph.shortest_paths("api", "auth", false), paths[..1]);
+        assert!(graph.shortest_paths("auth", "api", true).is_empty());
+    }
+
+    #[test]
+    fn test_matching_modules() {
+        let mut project_config = ProjectConfig::default();
+        for path in ["api.v1", "api.v2", "core.db"] {
+            project_config
+                .modules
+                .push(crate::config::ModuleConfig::new(path, false));
+        }
+        assert_eq!(
+            matching_modules(&project_config, "api.*").unwrap(),
+            vec!["api.v1", "api.v2"]
+        );
+        assert_eq!(
+            matching_modules(&project_config, "core.db").unwrap(),
+            vec!["core.db"]
+        );
+        assert!(matches!(
+            matching_modules(&project_config, "web.*"),
+            Err(QueryError::NoMatchingModules(_))
+        ));
+        assert!(matches!(
+            matching_modules(&project_config, "core"),
+            Err(QueryError::ModuleNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::commands::check::{check_internal, CheckError};
+use crate::config::edit::{ConfigEditor, EditError};
+use crate::config::ProjectConfig;
+use crate::diagnostics::{CodeDiagnostic, Diagnostic, DiagnosticDetails};
+
+#[derive(Error, Debug)]
+pub enum RatchetError {
+    #[error("Failed to ratchet violation budgets.\n{0}")]
+    CheckError(#[from] CheckError),
+    #[error("Failed to apply edits to project configuration.\n{0}")]
+    EditError(#[from] EditError),
+}
+
+/// The path of a budget update for the project-wide `max_violations`
+pub const PROJECT_BUDGET_PATH: &str = "<project>";
+
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct BudgetUpdate {
+    pub path: String,
+    pub previous: usize,
+    pub current: usize,
+}
+
+/// Budgets which can be lowered to the number of violations observed, in order of module path.
+/// Budgets which are exceeded are left alone, since a budget is never raised.
+fn detect_budget_updates(diagnostics: &[Diagnostic]) -> Vec<BudgetUpdate> {
+    let mut updates: Vec<BudgetUpdate> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| match diagnostic.details() {
+            DiagnosticDetails::Code(CodeDiagnostic::WithinViolationBudget {
+                module_path,
+                violations,
+                max_violations,
+                ..
+            }) if violations < max_violations => Some(BudgetUpdate {
+                path: module_path.clone(),
+                previous: *max_violations,
+                current: *violations,
+            }),
+            DiagnosticDetails::Code(CodeDiagnostic::WithinProjectViolationBudget {
+                violations,
+                max_violations,
+                ..
+            }) if violations < max_violations => Some(BudgetUpdate {
+                path: PROJECT_BUDGET_PATH.to_string(),
+                previous: *max_violations,
+                current: *violations,
+            }),
+            _ => None,
+        })
+        .collect();
+    updates.sort_by(|left, right| left.path.cmp(&right.path));
+    updates
+}
+
+/// Lower the budgets which the diagnostics of a full check show to be above the current number
+/// of violations. With `dry_run`, the config is left unchanged.
+pub fn apply_budget_updates(
+    project_config: &mut ProjectConfig,
+    diagnostics: &[Diagnostic],
+    dry_run: bool,
+) -> Result<Vec<BudgetUpdate>, RatchetError> {
+    let updates = detect_budget_updates(diagnostics);
+    if dry_run || updates.is_empty() {
+        return Ok(updates);
+    }
+
+    for update in &updates {
+        if update.path == PROJECT_BUDGET_PATH {
+            project_config.set_max_violations(Some(update.current))?;
+        } else {
+            project_config.set_module_max_violations(update.path.clone(), Some(update.current))?;
+        }
+    }
+    project_config.apply_edits()?;
+
+    Ok(updates)
+}
+
+/// Lower the `max_violations` budget of each module, and of the project, to its current number
+/// of violations, so that fixed violations cannot be reintroduced. With `dry_run`, the config is left unchanged.
+pub fn ratchet_budgets(
+    project_root: PathBuf,
+    mut project_config: ProjectConfig,
+    dry_run: bool,
+) -> Result<Vec<BudgetUpdate>, RatchetError> {
+    if project_config.max_violations.is_none()
+        && project_config
+            .all_modules()
+            .all(|module| module.max_violations.is_none())
+    {
+        return Ok(vec![]);
+    }
+
+    let diagnostics = check_internal(project_root, &project_config, true, true)?;
+    apply_budget_updates(&mut project_config, &diagnostics, dry_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget_diagnostic(
+        module_path: &str,
+        violations: usize,
+        max_violations: usize,
+    ) -> Diagnostic {
+        let details = if violations > max_violations {
+            CodeDiagnostic::ViolationBudgetExceeded {
+                module_path: module_path.to_string(),
+                violations,
+                max_violations,
+            }
+        } else {
+            CodeDiagnostic::WithinViolationBudget {
+                module_path: module_path.to_string(),
+                violations,
+                max_violations,
+                remaining: max_violations - violations,
+            }
+        };
+        Diagnostic::new_global_warning(DiagnosticDetails::Code(details))
+    }
+
+    #[test]
+    fn test_budgets_are_only_lowered() {
+        let diagnostics = vec![
+            budget_diagnostic("c", 2, 5),
+            budget_diagnostic("a", 0, 3),
+            budget_diagnostic("b", 4, 4),
+            budget_diagnostic("d", 6, 1),
+            Diagnostic::new_global_warning(DiagnosticDetails::Code(
+                CodeDiagnostic::WithinProjectViolationBudget {
+                    violations: 1,
+                    max_violations: 4,
+                    remaining: 3,
+                },
+            )),
+        ];
+        assert_eq!(
+            detect_budget_updates(&diagnostics),
+            vec![
+                BudgetUpdate {
+                    path: PROJECT_BUDGET_PATH.to_string(),
+                    previous: 4,
+                    current: 1,
+                },
+                BudgetUpdate {
+                    path: "a".to_string(),
+                    previous: 3,
+                    current: 0,
+                },
+                BudgetUpdate {
+                    path: "c".to_string(),
+                    previous: 5,
+                    current: 2,
+                },
+            ]
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use pyo3::prelude::*;
 use rayon::prelude::*;
 
 use thiserror::Error;
@@ -10,15 +13,16 @@ use thiserror::Error;
 use crate::colors::*;
 
 use crate::cli::create_clickable_link;
+use crate::commands::check::CheckError;
 use crate::config::root_module::RootModuleTreatment;
-use crate::config::ProjectConfig;
+use crate::config::{ModuleConfig, ProjectConfig};
 use crate::dependencies::LocatedImport;
 use crate::exclusion::{PathExclusionError, PathExclusions};
 use crate::filesystem::{
-    file_to_module_path, validate_project_modules, walk_pyfiles, FileSystemError,
+    normalized_file_to_module_path, walk_pyfiles, FileSystemError, WalkOptions,
 };
 use crate::interrupt::check_interrupt;
-use crate::modules::{build_module_tree, error::ModuleTreeError};
+use crate::modules::{build_module_tree, error::ModuleTreeError, ModuleNode};
 use crate::processors::import::ImportParseError;
 
 use super::helpers::import::get_located_project_imports;
@@ -31,6 +35,20 @@ struct Dependency {
     target_module: String,
 }
 
+/// An import found by a dependency report, for output as JSON or CSV
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct DependencyRecord {
+    // "dependency" for imports made by the path, or "usage" for imports of it from elsewhere
+    pub kind: String,
+    // Relative to the project root
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    pub source_module: String,
+    pub target_module: String,
+    pub import_path: String,
+}
+
 #[derive(Error, Debug)]
 pub enum ReportCreationError {
     #[error("I/O failure during report generation:\n{0}")]
@@ -47,6 +65,10 @@ pub enum ReportCreationError {
     Interrupted,
     #[error("Failed to build exclusion patterns: {0}")]
     PathExclusion(#[from] PathExclusionError),
+    #[error("Module '{0}' does not have an interface.")]
+    NoInterface(String),
+    #[error("Failed to find the imports between modules:\n{0}")]
+    Check(#[from] CheckError),
 }
 
 pub type Result<T> = std::result::Result<T, ReportCreationError>;
@@ -65,34 +87,63 @@ fn compare_dependencies(left: &Dependency, right: &Dependency) -> Ordering {
 
 struct DependencyReport {
     path: String,
+    // Display names for modules, keyed by module path (only used in human-readable output)
+    module_labels: HashMap<String, String>,
+    // Declared reason and ticket of dependencies, keyed by (module path, dependency path)
+    dependency_annotations: HashMap<(String, String), String>,
     pub dependencies: Vec<Dependency>,
     pub usages: Vec<Dependency>,
     pub warnings: Vec<String>,
 }
 
 impl DependencyReport {
-    fn new(path: String) -> Self {
+    fn new(
+        path: String,
+        module_labels: HashMap<String, String>,
+        dependency_annotations: HashMap<(String, String), String>,
+    ) -> Self {
         DependencyReport {
             path,
+            module_labels,
+            dependency_annotations,
             dependencies: vec![],
             usages: vec![],
             warnings: vec![],
         }
     }
 
-    fn render_dependency(&self, dependency: &Dependency) -> String {
+    fn render_module_label(&self, module_path: &str) -> String {
+        match self.module_labels.get(module_path) {
+            Some(label) => format!(" ({})", label),
+            None => String::new(),
+        }
+    }
+
+    fn render_annotation(&self, dependency: &Dependency) -> String {
+        match self.dependency_annotations.get(&(
+            dependency.source_module.clone(),
+            dependency.target_module.clone(),
+        )) {
+            Some(annotation) => format!(" - {}", annotation),
+            None => String::new(),
+        }
+    }
+
+    fn render_dependency(&self, dependency: &Dependency, labelled_module: &str) -> String {
         let clickable_link = create_clickable_link(
             &dependency.file_path,
             &dependency.absolute_path,
             &dependency.import.alias_line_number(),
         );
         format!(
-            "{green}{clickable_link}{end_color}: {cyan}Import '{import_mod_path}'{end_color}",
+            "{green}{clickable_link}{end_color}: {cyan}Import '{import_mod_path}'{module_label}{end_color}{annotation}",
             green = BColors::OKGREEN,
             clickable_link = clickable_link,
             end_color = BColors::ENDC,
             cyan = BColors::OKCYAN,
-            import_mod_path = dependency.import.module_path()
+            import_mod_path = dependency.import.module_path(),
+            module_label = self.render_module_label(labelled_module),
+            annotation = self.render_annotation(dependency),
         )
     }
 
@@ -151,7 +202,7 @@ impl DependencyReport {
                 _ => self
                     .dependencies
                     .iter()
-                    .map(|dep| self.render_dependency(dep))
+                    .map(|dep| self.render_dependency(dep, &dep.target_module))
                     .collect::<Vec<String>>()
                     .join("\n")
                     .to_string(),
@@ -177,7 +228,7 @@ impl DependencyReport {
                 _ => self
                     .usages
                     .iter()
-                    .map(|dep| self.render_dependency(dep))
+                    .map(|dep| self.render_dependency(dep, &dep.source_module))
                     .collect::<Vec<String>>()
                     .join("\n")
                     .to_string(),
@@ -205,13 +256,30 @@ impl DependencyReport {
     }
 }
 
-fn is_module_prefix(prefix: &str, full_path: &str) -> bool {
+pub(crate) fn is_module_prefix(prefix: &str, full_path: &str) -> bool {
     if !full_path.starts_with(prefix) {
         return false;
     }
     full_path.len() == prefix.len() || full_path[prefix.len()..].starts_with('.')
 }
 
+/// Labels of modules for the report, keyed by module path.
+/// Utility modules are labelled as such, since they may be imported without being declared.
+fn module_labels(modules: &[ModuleConfig]) -> HashMap<String, String> {
+    modules
+        .iter()
+        .filter_map(|module| {
+            let label = match (&module.display_name, module.utility) {
+                (Some(display_name), true) => format!("{}, utility", display_name),
+                (Some(display_name), false) => display_name.clone(),
+                (None, true) => "utility".to_string(),
+                (None, false) => return None,
+            };
+            Some((module.mod_path(), label))
+        })
+        .collect()
+}
+
 pub fn create_dependency_report(
     project_root: &PathBuf,
     project_config: &ProjectConfig,
@@ -222,15 +290,222 @@ pub fn create_dependency_report(
     skip_usages: bool,
     raw: bool,
 ) -> Result<String> {
+    let mut report = collect_dependency_report(
+        project_root,
+        project_config,
+        path,
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+    )?;
+    Ok(report.render_to_string(skip_dependencies, skip_usages, raw))
+}
+
+/// The imports found by the dependency report, as records ordered by kind, file and line
+pub fn create_dependency_records(
+    project_root: &PathBuf,
+    project_config: &ProjectConfig,
+    path: &PathBuf,
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+) -> Result<Vec<DependencyRecord>> {
+    let mut report = collect_dependency_report(
+        project_root,
+        project_config,
+        path,
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+    )?;
+    report.dependencies.sort_by(compare_dependencies);
+    report.usages.sort_by(compare_dependencies);
+
+    let to_record = |kind: &str, dependency: &Dependency| DependencyRecord {
+        kind: kind.to_string(),
+        file_path: dependency
+            .absolute_path
+            .strip_prefix(project_root)
+            .unwrap_or(&dependency.absolute_path)
+            .to_path_buf(),
+        line_number: dependency.import.alias_line_number(),
+        source_module: dependency.source_module.clone(),
+        target_module: dependency.target_module.clone(),
+        import_path: dependency.import.module_path().to_string(),
+    };
+    Ok(report
+        .dependencies
+        .iter()
+        .map(|dependency| to_record("dependency", dependency))
+        .chain(report.usages.iter().map(|usage| to_record("usage", usage)))
+        .collect())
+}
+
+/// Create a combined report of dependencies and usages of several paths,
+/// followed by a matrix of the imports between them
+#[allow(clippy::too_many_arguments)]
+pub fn create_combined_dependency_report(
+    project_root: &PathBuf,
+    project_config: &ProjectConfig,
+    paths: &[PathBuf],
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+    raw: bool,
+) -> Result<String> {
+    let DependencyReports {
+        mut reports,
+        cross_references,
+        warnings,
+    } = collect_dependency_reports(
+        project_root,
+        project_config,
+        paths,
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+    )?;
+
+    if raw {
+        return Ok(reports
+            .iter_mut()
+            .map(|report| {
+                format!(
+                    "# Report for '{path}'\n{section}",
+                    path = report.path,
+                    section = report.render_to_string(skip_dependencies, skip_usages, raw)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let mut result: String = reports
+        .iter_mut()
+        .map(|report| report.render_to_string(skip_dependencies, skip_usages, raw))
+        .collect();
+    let target_paths: Vec<&str> = reports.iter().map(|report| report.path.as_str()).collect();
+    result.push_str(&format!(
+        "[ Cross-references ]\n\
+        Imports by each path (rows) of each other path (columns)\n\
+        {matrix}\n\
+        -------------------------------\n",
+        matrix = render_cross_references(&target_paths, &cross_references),
+    ));
+
+    if !warnings.is_empty() {
+        result.push_str(&format!(
+            "[ Warnings ]\n\
+            {warning_color}{warnings}{end_color}",
+            warning_color = BColors::WARNING,
+            end_color = BColors::ENDC,
+            warnings = warnings.join("\n")
+        ));
+    }
+
+    Ok(result)
+}
+
+fn render_cross_references(paths: &[&str], cross_references: &[Vec<usize>]) -> String {
+    let label_width = paths
+        .iter()
+        .map(|path| path.len())
+        .max()
+        .unwrap_or_default();
+    let mut lines = vec![format!(
+        "{label:label_width$} | {columns}",
+        label = "",
+        columns = paths.join(" | "),
+    )];
+    for (row, (path, counts)) in paths.iter().zip(cross_references).enumerate() {
+        let cells: Vec<String> = counts
+            .iter()
+            .zip(paths)
+            .enumerate()
+            .map(|(column, (count, column_path))| {
+                let count = if row == column {
+                    "-".to_string()
+                } else {
+                    count.to_string()
+                };
+                format!("{count:>width$}", width = column_path.len())
+            })
+            .collect();
+        lines.push(format!("{path:label_width$} | {}", cells.join(" | ")));
+    }
+    lines.join("\n")
+}
+
+/// A path being reported on, and the module which contains it
+struct ReportTarget {
+    module_path: String,
+    module: Arc<ModuleNode>,
+}
+
+/// The dependency reports of several paths, which are found with a single walk of the project
+struct DependencyReports {
+    reports: Vec<DependencyReport>,
+    // The number of imports by code in each path (rows) of code in each other path (columns)
+    cross_references: Vec<Vec<usize>>,
+    warnings: Vec<String>,
+}
+
+// The dependencies and usages of each target found in a file, the cross-references between targets,
+// and a warning if the file could not be parsed
+type FileReport = (
+    Vec<(Vec<Dependency>, Vec<Dependency>)>,
+    Vec<(usize, usize)>,
+    Option<String>,
+);
+
+fn collect_dependency_report(
+    project_root: &PathBuf,
+    project_config: &ProjectConfig,
+    path: &PathBuf,
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+) -> Result<DependencyReport> {
+    let DependencyReports {
+        reports, warnings, ..
+    } = collect_dependency_reports(
+        project_root,
+        project_config,
+        std::slice::from_ref(path),
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+    )?;
+    let mut report = reports
+        .into_iter()
+        .next()
+        .expect("one report is collected for each path");
+    report.warnings = warnings;
+    Ok(report)
+}
+
+fn collect_dependency_reports(
+    project_root: &PathBuf,
+    project_config: &ProjectConfig,
+    paths: &[PathBuf],
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+) -> Result<DependencyReports> {
     if skip_dependencies && skip_usages {
         return Err(ReportCreationError::NothingToReport);
     }
 
     let source_roots = project_config.prepend_roots(project_root);
-    let (valid_modules, _) = validate_project_modules(
-        &source_roots,
-        project_config.all_modules().cloned().collect(),
-    );
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
 
     check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
 
@@ -238,141 +513,244 @@ pub fn create_dependency_report(
         &source_roots,
         &valid_modules,
         false,                      // skip circular dependency check in report
+        None,                       // skip circular domain dependency check in report
         RootModuleTreatment::Allow, // skip root module check in report
     )?;
-
-    let absolute_path = project_root.join(path);
-    let module_path = file_to_module_path(&source_roots, &absolute_path)?;
-    let target_module = module_tree.find_nearest(&module_path).ok_or_else(|| {
-        ReportCreationError::ModuleTree(ModuleTreeError::ModuleNotFound(module_path.clone()))
-    })?;
-
-    let mut report = DependencyReport::new(path.display().to_string());
+    // As in 'tach check', code in the root module is not reported on when it is ignored
+    let ignore_root_module = project_config.root_module == RootModuleTreatment::Ignore;
+
+    let targets = paths
+        .iter()
+        .map(|path| {
+            let module_path = normalized_file_to_module_path(
+                &source_roots,
+                &project_root.join(path),
+                &project_config.module_path_normalizers,
+            )?;
+            let module = module_tree.find_nearest(&module_path).ok_or_else(|| {
+                ReportCreationError::ModuleTree(ModuleTreeError::ModuleNotFound(
+                    module_path.clone(),
+                ))
+            })?;
+            Ok(ReportTarget {
+                module_path,
+                module,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let module_labels = module_labels(&valid_modules);
+    let dependency_annotations: HashMap<(String, String), String> = project_config
+        .all_modules()
+        .flat_map(|module| {
+            module.dependencies_iter().filter_map(|dependency| {
+                dependency
+                    .annotation()
+                    .map(|annotation| ((module.path.clone(), dependency.path.clone()), annotation))
+            })
+        })
+        .collect();
+    let mut reports = DependencyReports {
+        reports: paths
+            .iter()
+            .map(|path| {
+                DependencyReport::new(
+                    path.display().to_string(),
+                    module_labels.clone(),
+                    dependency_annotations.clone(),
+                )
+            })
+            .collect(),
+        cross_references: vec![vec![0; paths.len()]; paths.len()],
+        warnings: vec![],
+    };
 
     let exclusions = PathExclusions::new(
         project_root,
         &project_config.exclude,
-        project_config.use_regex_matching,
+        &project_config.ignore,
     )?;
 
     for source_root in &source_roots {
         check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
 
-        let source_root_results: Vec<_> =
-            walk_pyfiles(&source_root.display().to_string(), &exclusions)
-                .par_bridge()
-                .filter_map(|pyfile| {
-                    if check_interrupt().is_err() {
-                        return None;
-                    }
+        let source_root_results: Vec<FileReport> = walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            // Notebooks are only supported by 'tach check'
+            WalkOptions {
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        )
+        .par_bridge()
+        .filter_map(|pyfile| {
+            if check_interrupt().is_err() {
+                return None;
+            }
+
+            let absolute_pyfile = source_root.join(&pyfile);
+            let file_module_path = normalized_file_to_module_path(
+                &source_roots,
+                &absolute_pyfile,
+                &project_config.module_path_normalizers,
+            )
+            .ok()?;
+            let file_module = module_tree.find_nearest(&file_module_path);
+
+            // Each file is only parsed once, however many paths are reported on
+            let project_imports = match get_located_project_imports(
+                project_root,
+                &source_roots,
+                &absolute_pyfile,
+                project_config,
+            ) {
+                Ok(project_imports) => project_imports,
+                Err(err) => return Some((Vec::new(), Vec::new(), Some(err.to_string()))),
+            };
 
-                    let absolute_pyfile = source_root.join(&pyfile);
-                    let file_module_path =
-                        match file_to_module_path(&source_roots, &absolute_pyfile) {
-                            Ok(path) => path,
-                            Err(_) => return None,
-                        };
-                    let file_module = module_tree.find_nearest(&file_module_path);
-
-                    match get_located_project_imports(
-                        project_root,
-                        &source_roots,
-                        &absolute_pyfile,
-                        project_config,
-                    ) {
-                        Ok(project_imports) => {
-                            let is_in_target_path =
-                                is_module_prefix(&module_path, &file_module_path);
-                            let mut dependencies = Vec::new();
-                            let mut usages = Vec::new();
-
-                            if is_in_target_path && !skip_dependencies {
-                                // Add dependencies
-                                dependencies.extend(
-                                    project_imports
-                                        .iter()
-                                        .filter_map(|import| {
-                                            if let Some(import_module) =
-                                                module_tree.find_nearest(import.module_path())
-                                            {
-                                                if import_module == target_module {
-                                                    return None;
-                                                }
-                                                include_dependency_modules.as_ref().map_or(
-                                                    Some((import.clone(), import_module.clone())),
-                                                    |included_modules| {
-                                                        if included_modules
-                                                            .contains(&import_module.full_path)
-                                                        {
-                                                            Some((
-                                                                import.clone(),
-                                                                import_module.clone(),
-                                                            ))
-                                                        } else {
-                                                            None
-                                                        }
-                                                    },
-                                                )
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .map(|(import, import_module)| Dependency {
-                                            file_path: pyfile.clone(),
-                                            absolute_path: absolute_pyfile.clone(),
-                                            import,
-                                            source_module: target_module.full_path.clone(),
-                                            target_module: import_module.full_path.clone(),
-                                        }),
-                                );
-                            } else if !is_in_target_path && !skip_usages {
-                                // Add usages
-                                usages.extend(
-                                    project_imports
-                                        .iter()
-                                        .filter(|import| {
-                                            if !is_module_prefix(&module_path, import.module_path())
-                                            {
-                                                return false;
-                                            }
-                                            file_module.as_ref().is_some_and(|m| {
-                                                include_usage_modules.as_ref().is_none_or(
-                                                    |included_modules| {
-                                                        included_modules.contains(&m.full_path)
-                                                    },
-                                                )
-                                            })
-                                        })
-                                        .map(|import| Dependency {
-                                            file_path: pyfile.clone(),
-                                            absolute_path: absolute_pyfile.clone(),
-                                            import: import.clone(),
-                                            source_module: file_module
-                                                .as_ref()
-                                                .map_or(String::new(), |m| m.full_path.clone()),
-                                            target_module: target_module.full_path.clone(),
-                                        }),
-                                );
-                            }
-
-                            Some((dependencies, usages, None))
+            let mut target_results = Vec::with_capacity(targets.len());
+            let mut cross_references = Vec::new();
+            for (index, target) in targets.iter().enumerate() {
+                let is_in_target_path = is_module_prefix(&target.module_path, &file_module_path);
+                let mut dependencies = Vec::new();
+                let mut usages = Vec::new();
+
+                if is_in_target_path {
+                    for import in &project_imports {
+                        if is_module_prefix(&target.module_path, import.module_path()) {
+                            continue;
                         }
-                        Err(err) => Some((Vec::new(), Vec::new(), Some(err.to_string()))),
+                        cross_references.extend(
+                            targets
+                                .iter()
+                                .enumerate()
+                                .filter(|(other_index, other)| {
+                                    *other_index != index
+                                        && is_module_prefix(
+                                            &other.module_path,
+                                            import.module_path(),
+                                        )
+                                        && !is_module_prefix(&other.module_path, &file_module_path)
+                                })
+                                .map(|(other_index, _)| (index, other_index)),
+                        );
                     }
-                })
-                .collect();
+                }
+
+                if is_in_target_path && !skip_dependencies {
+                    // Add dependencies
+                    dependencies.extend(
+                        project_imports
+                            .iter()
+                            .filter_map(|import| {
+                                let import_module =
+                                    module_tree.find_nearest(import.module_path())?;
+                                let is_included = import_module != target.module
+                                    && !(ignore_root_module && import_module.is_root())
+                                    && include_dependency_modules.as_ref().is_none_or(
+                                        |included_modules| {
+                                            included_modules.contains(&import_module.full_path)
+                                        },
+                                    );
+                                is_included.then_some((import, import_module))
+                            })
+                            .map(|(import, import_module)| Dependency {
+                                file_path: pyfile.clone(),
+                                absolute_path: absolute_pyfile.clone(),
+                                import: import.clone(),
+                                source_module: target.module.full_path.clone(),
+                                target_module: import_module.full_path.clone(),
+                            }),
+                    );
+                } else if !is_in_target_path && !skip_usages {
+                    // Add usages
+                    usages.extend(
+                        project_imports
+                            .iter()
+                            .filter(|import| {
+                                if !is_module_prefix(&target.module_path, import.module_path()) {
+                                    return false;
+                                }
+                                file_module.as_ref().is_some_and(|m| {
+                                    !(ignore_root_module && m.is_root())
+                                        && include_usage_modules.as_ref().is_none_or(
+                                            |included_modules| {
+                                                included_modules.contains(&m.full_path)
+                                            },
+                                        )
+                                })
+                            })
+                            .map(|import| Dependency {
+                                file_path: pyfile.clone(),
+                                absolute_path: absolute_pyfile.clone(),
+                                import: import.clone(),
+                                source_module: file_module
+                                    .as_ref()
+                                    .map_or(String::new(), |m| m.full_path.clone()),
+                                target_module: target.module.full_path.clone(),
+                            }),
+                    );
+                }
+
+                target_results.push((dependencies, usages));
+            }
+
+            Some((target_results, cross_references, None))
+        })
+        .collect();
 
         check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
 
         // Combine results
-        for (dependencies, usages, warning) in source_root_results {
-            report.dependencies.extend(dependencies);
-            report.usages.extend(usages);
+        for (target_results, cross_references, warning) in source_root_results {
+            for (report, (dependencies, usages)) in reports.reports.iter_mut().zip(target_results) {
+                report.dependencies.extend(dependencies);
+                report.usages.extend(usages);
+            }
+            for (index, other_index) in cross_references {
+                reports.cross_references[index][other_index] += 1;
+            }
             if let Some(warning) = warning {
-                report.warnings.push(warning);
+                reports.warnings.push(warning);
             }
         }
     }
 
-    Ok(report.render_to_string(skip_dependencies, skip_usages, raw))
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_labels() {
+        let modules = vec![
+            ModuleConfig::new("billing", false),
+            ModuleConfig {
+                display_name: Some("Payments".to_string()),
+                ..ModuleConfig::new("payments", false)
+            },
+            ModuleConfig {
+                utility: true,
+                ..ModuleConfig::new("utils", false)
+            },
+            ModuleConfig {
+                display_name: Some("Shared".to_string()),
+                utility: true,
+                ..ModuleConfig::new("shared", false)
+            },
+        ];
+        let report = DependencyReport::new(
+            "payments".to_string(),
+            module_labels(&modules),
+            HashMap::new(),
+        );
+
+        assert_eq!(report.render_module_label("payments"), " (Payments)");
+        assert_eq!(report.render_module_label("billing"), "");
+        assert_eq!(report.render_module_label("utils"), " (utility)");
+        assert_eq!(report.render_module_label("shared"), " (Shared, utility)");
+    }
 }
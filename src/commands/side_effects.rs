@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use ruff_linter::Locator;
+use ruff_python_ast::Mod;
+
+use crate::config::root_module::RootModuleTreatment;
+use crate::config::ProjectConfig;
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{self as fs, WalkOptions};
+use crate::interrupt::check_interrupt;
+use crate::modules::build_module_tree;
+use crate::processors::side_effects::find_import_side_effects;
+use crate::python::parsing::parse_python_source;
+
+use super::report::{ReportCreationError, Result};
+
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ImportSideEffect {
+    // Relative to the project root
+    pub file_path: PathBuf,
+    pub line_number: usize,
+    // The nearest module containing the file, if any
+    pub module_path: Option<String>,
+    pub kind: String,
+    pub detail: String,
+}
+
+fn file_side_effects(absolute_path: &Path) -> Option<Vec<(usize, String, String)>> {
+    let contents = fs::read_file_content(absolute_path).ok()?;
+    let Ok(Mod::Module(module)) = parse_python_source(&contents) else {
+        return None;
+    };
+    let locator = Locator::new(&contents);
+    let line_index = locator.to_index();
+    Some(
+        find_import_side_effects(&module.body)
+            .into_iter()
+            .map(|side_effect| {
+                (
+                    line_index.line_index(side_effect.offset).get(),
+                    side_effect.kind.as_str().to_string(),
+                    side_effect.detail,
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Find top-level code across the project which is likely to have side effects, or to be slow, at import time.
+/// Files which cannot be read or parsed are skipped.
+pub fn detect_import_side_effects(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+) -> Result<Vec<ImportSideEffect>> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        false,
+        None,
+        RootModuleTreatment::Allow,
+    )?;
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+
+    let mut side_effects = Vec::new();
+    for source_root in &source_roots {
+        check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+        let source_root_results: Vec<ImportSideEffect> = fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            // Notebooks are only supported by 'tach check'
+            WalkOptions {
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        )
+        .par_bridge()
+        .filter_map(|pyfile| {
+            if check_interrupt().is_err() {
+                return None;
+            }
+            let absolute_pyfile = source_root.join(&pyfile);
+            let file_side_effects = file_side_effects(&absolute_pyfile)?;
+            let file_path = fs::relative_to(&absolute_pyfile, project_root).ok()?;
+            let module_path = fs::file_to_module_path(&source_roots, &absolute_pyfile)
+                .ok()
+                .and_then(|module_path| module_tree.find_nearest(&module_path))
+                .map(|module| module.full_path.clone());
+            Some(
+                file_side_effects
+                    .into_iter()
+                    .map(|(line_number, kind, detail)| ImportSideEffect {
+                        file_path: file_path.clone(),
+                        line_number,
+                        module_path: module_path.clone(),
+                        kind,
+                        detail,
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect();
+        side_effects.extend(source_root_results);
+    }
+    check_interrupt().map_err(|_| ReportCreationError::Interrupted)?;
+
+    side_effects.sort_by(|left, right| {
+        (&left.file_path, left.line_number).cmp(&(&right.file_path, right.line_number))
+    });
+    Ok(side_effects)
+}
@@ -1,10 +1,11 @@
 use thiserror::Error;
 
 use pyo3::prelude::*;
+use serde::Serialize;
 
 use crate::commands::check::{check_internal, CheckError};
 use crate::config::edit::{ConfigEditor, EditError};
-use crate::config::root_module::{RootModuleTreatment, ROOT_MODULE_SENTINEL_TAG};
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
 use crate::config::{DependencyConfig, ProjectConfig};
 use crate::diagnostics::Diagnostic;
 use crate::filesystem::validate_module_path;
@@ -19,60 +20,133 @@ pub enum SyncError {
     TomlSerialize(#[from] toml::ser::Error),
     #[error("Failed to sync project.\n{0}")]
     CheckError(#[from] CheckError),
-    #[error("Failed to sync project configuration due to root module violation.\n{0}")]
-    RootModuleViolation(String),
     #[error("Failed to apply edits to project configuration.\n{0}")]
     EditError(#[from] EditError),
 }
 
-fn handle_added_dependency(
-    module_path: &str,
-    dependency: &str,
-    project_config: &mut ProjectConfig,
-) -> Result<(), SyncError> {
-    let module_is_root = module_path == ROOT_MODULE_SENTINEL_TAG;
-    let dependency_is_root = dependency == ROOT_MODULE_SENTINEL_TAG;
+// The number of example import locations recorded for each dependency
+const MAX_EXAMPLES: usize = 3;
 
-    if !module_is_root && !dependency_is_root {
-        project_config.add_dependency(module_path.to_string(), dependency.to_string())?;
-        return Ok(());
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Add,
+    Remove,
+    Deprecate,
+}
 
-    match project_config.root_module {
-        RootModuleTreatment::Ignore => Ok(()),
-        RootModuleTreatment::Allow => {
-            project_config.add_dependency(module_path.to_string(), dependency.to_string())?;
-            Ok(())
-        }
-        RootModuleTreatment::Forbid => Err(SyncError::RootModuleViolation(format!(
-            "The root module is forbidden, but it was found that '{}' depends on '{}'.",
-            module_path, dependency
-        ))),
-        RootModuleTreatment::DependenciesOnly => {
-            if dependency_is_root {
-                return Err(SyncError::RootModuleViolation(format!("No module may depend on the root module, but it was found that '{}' depends on the root module.", module_path)));
-            }
-            project_config.add_dependency(module_path.to_string(), dependency.to_string())?;
-            Ok(())
+impl SyncAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Remove => "remove",
+            Self::Deprecate => "deprecate",
         }
     }
 }
 
-fn detect_dependencies(diagnostics: &[Diagnostic]) -> HashMap<String, Vec<String>> {
-    let mut dependencies = HashMap::new();
+impl IntoPy<PyObject> for SyncAction {
+    fn into_py(self, py: Python) -> PyObject {
+        self.as_str().to_object(py)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ImportLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+/// A change to a single dependency of a module, along with the imports which justify it
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct SyncChange {
+    pub path: String,
+    pub dependency: String,
+    pub action: SyncAction,
+    // The number of imports which use the dependency (only for added dependencies)
+    pub evidence: usize,
+    pub examples: Vec<ImportLocation>,
+}
+
+/// The changes which 'tach sync' makes to the project configuration
+#[derive(Debug, Clone, Default, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct SyncPlan {
+    pub changes: Vec<SyncChange>,
+    pub created_modules: Vec<String>,
+    pub deleted_modules: Vec<String>,
+}
+
+impl SyncPlan {
+    fn record(&mut self, path: &str, dependency: &str, action: SyncAction) {
+        self.changes.push(SyncChange {
+            path: path.to_string(),
+            dependency: dependency.to_string(),
+            action,
+            evidence: 0,
+            examples: vec![],
+        });
+    }
+
+    fn record_added(&mut self, path: &str, dependency: &str, usages: &[ImportLocation]) {
+        self.changes.push(SyncChange {
+            path: path.to_string(),
+            dependency: dependency.to_string(),
+            action: SyncAction::Add,
+            evidence: usages.len(),
+            examples: example_locations(usages),
+        });
+    }
+}
+
+/// The first few import locations, in order of file and line
+pub(crate) fn example_locations(usages: &[ImportLocation]) -> Vec<ImportLocation> {
+    let mut examples = usages.to_vec();
+    examples.sort_by(|left, right| (&left.file, left.line).cmp(&(&right.file, right.line)));
+    examples.truncate(MAX_EXAMPLES);
+    examples
+}
+
+/// Map each module to the modules it depends on, along with the location of each usage
+fn detect_dependencies(
+    diagnostics: &[Diagnostic],
+) -> HashMap<String, HashMap<String, Vec<ImportLocation>>> {
+    let mut dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>> = HashMap::new();
     for diagnostic in diagnostics {
-        if diagnostic.is_dependency_error() {
+        // Usages of the root module which its treatment forbids cannot be declared,
+        // so they are left for 'tach check' to report
+        if diagnostic.is_dependency_error() && !diagnostic.is_root_module_violation() {
             let source_path = diagnostic.usage_module().unwrap();
             let dep_path = diagnostic.definition_module().unwrap();
-            dependencies
+            let usages = dependencies
                 .entry(source_path.to_string())
-                .or_insert(vec![])
-                .push(dep_path.to_string());
+                .or_default()
+                .entry(dep_path.to_string())
+                .or_default();
+            if let (Some(file), Some(line)) = (diagnostic.file_path(), diagnostic.line_number()) {
+                usages.push(ImportLocation {
+                    file: file.display().to_string(),
+                    line,
+                });
+            }
         }
     }
     dependencies
 }
 
+/// Detect the dependencies between modules which are actually used by imports in the project
+pub(crate) fn detect_project_dependencies(
+    project_root: PathBuf,
+    project_config: &ProjectConfig,
+) -> Result<HashMap<String, HashMap<String, Vec<ImportLocation>>>, CheckError> {
+    // This is a shortcut to finding all cross-module dependencies
+    // TODO: dedicated function
+    let cleared_project_config = project_config.with_dependencies_removed();
+    let check_result = check_internal(project_root, &cleared_project_config, true, false)?;
+    Ok(detect_dependencies(&check_result))
+}
+
 #[derive(Default, Clone)]
 #[pyclass(get_all, module = "tach.extension")]
 pub struct UnusedDependencies {
@@ -84,22 +158,13 @@ pub fn detect_unused_dependencies(
     project_root: PathBuf,
     project_config: &mut ProjectConfig,
 ) -> Result<Vec<UnusedDependencies>, SyncError> {
-    // This is a shortcut to finding all cross-module dependencies
-    // TODO: dedicated function
-    let cleared_project_config = project_config.with_dependencies_removed();
-    let check_result = check_internal(project_root, &cleared_project_config, true, false)?;
-    let detected_dependencies = detect_dependencies(&check_result);
+    let detected_dependencies = detect_project_dependencies(project_root, project_config)?;
 
     let mut unused_dependencies: Vec<UnusedDependencies> = vec![];
     for module_path in project_config.module_paths() {
-        let module_detected_dependencies =
-            detected_dependencies
-                .get(&module_path)
-                .map_or(HashSet::new(), |deps| {
-                    deps.iter()
-                        .map(|dep| dep.to_string())
-                        .collect::<HashSet<_>>()
-                });
+        let module_detected_dependencies = detected_dependencies
+            .get(&module_path)
+            .map_or(HashSet::new(), |deps| deps.keys().cloned().collect());
         let module_current_dependencies = project_config
             .dependencies_for_module(&module_path)
             .map_or(HashSet::new(), |deps| {
@@ -124,16 +189,15 @@ pub fn detect_unused_dependencies(
         .collect())
 }
 
+/// Enqueue edits which sync the dependency constraints with the detected dependencies,
+/// and return the plan describing them.
 fn sync_dependency_constraints(
     project_root: PathBuf,
     project_config: &mut ProjectConfig,
     prune: bool,
-) -> Result<(), SyncError> {
-    // This is a shortcut to finding all cross-module dependencies
-    // TODO: dedicated function
-    let cleared_project_config = project_config.with_dependencies_removed();
-    let check_result = check_internal(project_root, &cleared_project_config, true, false)?;
-    let detected_dependencies = detect_dependencies(&check_result);
+) -> Result<SyncPlan, SyncError> {
+    let mut plan = SyncPlan::default();
+    let detected_dependencies = detect_project_dependencies(project_root, project_config)?;
 
     // Root module is a special case -- it may not be in module paths and still implicitly detect dependencies
     // If the root module is not in the module paths, but was detected, create it
@@ -143,44 +207,52 @@ fn sync_dependency_constraints(
         && (detected_dependencies.contains_key(ROOT_MODULE_SENTINEL_TAG)
             || detected_dependencies
                 .values()
-                .any(|deps| deps.contains(&ROOT_MODULE_SENTINEL_TAG.to_string())))
+                .any(|deps| deps.contains_key(ROOT_MODULE_SENTINEL_TAG)))
     {
         // This enqueues an edit to the TOML
         project_config.create_module(ROOT_MODULE_SENTINEL_TAG.to_string())?;
+        plan.created_modules
+            .push(ROOT_MODULE_SENTINEL_TAG.to_string());
         // This adds the root module to the module paths immediately
         project_config.add_root_module();
     }
 
     // Now diff with project config and apply edits
+    let empty_dependencies = HashMap::new();
     for module_path in project_config.module_paths() {
-        let module_detected_dependencies =
-            detected_dependencies
-                .get(&module_path)
-                .map_or(HashSet::new(), |deps| {
-                    deps.iter()
-                        .map(|dep| dep.to_string())
-                        .collect::<HashSet<_>>()
-                });
-        let module_current_dependencies = project_config
+        let module_detected_dependencies = detected_dependencies
+            .get(&module_path)
+            .unwrap_or(&empty_dependencies);
+        let module_current_dependencies: HashMap<String, DependencyConfig> = project_config
             .dependencies_for_module(&module_path)
-            .map_or(HashSet::new(), |deps| {
+            .map_or(HashMap::new(), |deps| {
                 deps.iter()
-                    .map(|dep| dep.path.clone())
-                    .collect::<HashSet<_>>()
+                    .map(|dep| (dep.path.clone(), dep.clone()))
+                    .collect()
             });
 
-        let dependencies_to_add =
-            module_detected_dependencies.difference(&module_current_dependencies);
-        for dep in dependencies_to_add {
-            // This handler will also handle root module treatment
-            handle_added_dependency(&module_path, dep, project_config)?;
+        for (dep, usages) in module_detected_dependencies {
+            if module_current_dependencies.contains_key(dep) {
+                continue;
+            }
+            project_config.add_dependency(module_path.to_string(), dep.to_string())?;
+            plan.record_added(&module_path, dep, usages);
         }
 
         if prune {
-            let dependencies_to_remove =
-                module_current_dependencies.difference(&module_detected_dependencies);
-            for dep in dependencies_to_remove {
-                project_config.remove_dependency(module_path.to_string(), dep.to_string())?;
+            for (dep, dependency_config) in &module_current_dependencies {
+                if module_detected_dependencies.contains_key(dep) {
+                    continue;
+                }
+                if dependency_config.annotation().is_none() {
+                    project_config.remove_dependency(module_path.to_string(), dep.to_string())?;
+                    plan.record(&module_path, dep, SyncAction::Remove);
+                } else if !dependency_config.is_deprecated() {
+                    // Annotated dependencies record intent, so they are deprecated instead of removed
+                    project_config
+                        .mark_dependency_deprecated(module_path.to_string(), dep.to_string())?;
+                    plan.record(&module_path, dep, SyncAction::Deprecate);
+                }
             }
         }
     }
@@ -195,26 +267,76 @@ fn sync_dependency_constraints(
                     module_path,
                 ) {
                     // Not clear what to do if enqueueing deletion fails
-                    let _ = project_config.delete_module(module_path.to_string());
+                    if project_config
+                        .delete_module(module_path.to_string())
+                        .is_ok()
+                    {
+                        plan.deleted_modules.push(module_path.to_string());
+                    }
                 }
             });
     }
 
-    Ok(())
+    plan.changes.sort_by(|left, right| {
+        (&left.path, &left.dependency).cmp(&(&right.path, &right.dependency))
+    });
+    Ok(plan)
 }
 
 /// Update project configuration with auto-detected dependency constraints.
 /// If prune is set to False, it will create dependencies to resolve existing errors,
-/// but will not remove any constraints.
+/// but will not remove any constraints. With `dry_run`, the config is left unchanged.
 pub fn sync_project(
     project_root: PathBuf,
     mut project_config: ProjectConfig,
     add: bool,
-) -> Result<(), SyncError> {
+    dry_run: bool,
+) -> Result<SyncPlan, SyncError> {
     // This may queue edits to the project config
-    sync_dependency_constraints(project_root, &mut project_config, !add)?;
+    let plan = sync_dependency_constraints(project_root, &mut project_config, !add)?;
 
-    project_config.apply_edits()?;
+    if !dry_run {
+        project_config.apply_edits()?;
+    }
 
-    Ok(())
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(file: &str, line: usize) -> ImportLocation {
+        ImportLocation {
+            file: file.to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_record_added_keeps_first_examples() {
+        let usages = vec![
+            location("b.py", 1),
+            location("a.py", 9),
+            location("a.py", 2),
+            location("c.py", 4),
+        ];
+        let mut plan = SyncPlan::default();
+        plan.record_added("app", "core", &usages);
+
+        assert_eq!(
+            plan.changes,
+            vec![SyncChange {
+                path: "app".to_string(),
+                dependency: "core".to_string(),
+                action: SyncAction::Add,
+                evidence: 4,
+                examples: vec![
+                    location("a.py", 2),
+                    location("a.py", 9),
+                    location("b.py", 1)
+                ],
+            }]
+        );
+    }
 }
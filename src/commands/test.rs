@@ -48,10 +48,7 @@ impl TachPytestPluginHandler {
         all_affected_modules: HashSet<PathBuf>,
     ) -> Self {
         let source_roots = project_config.prepend_roots(&project_root);
-        let (valid_modules, invalid_modules) = fs::validate_project_modules(
-            &source_roots,
-            project_config.all_modules().cloned().collect(),
-        );
+        let (valid_modules, invalid_modules) = project_config.validate_modules(&source_roots);
         for invalid_module in invalid_modules {
             eprintln!(
                 "Module '{}' not found. It will be ignored.",
@@ -63,7 +60,8 @@ impl TachPytestPluginHandler {
         let module_tree = build_module_tree(
             &source_roots,
             &valid_modules,
-            project_config.forbid_circular_dependencies,
+            project_config.forbids_circular_dependencies(),
+            project_config.circular_domain_groups().as_ref(),
             project_config.root_module.clone(),
         )
         .unwrap();
@@ -138,7 +136,13 @@ fn get_changed_module_paths(
             file.extension().unwrap_or_default() == "py"
                 && source_roots.iter().any(|root| file.starts_with(root))
         })
-        .map(|file| fs::file_to_module_path(&source_roots, &file))
+        .map(|file| {
+            fs::normalized_file_to_module_path(
+                &source_roots,
+                &file,
+                &project_config.module_path_normalizers,
+            )
+        })
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
     Ok(changed_module_paths)
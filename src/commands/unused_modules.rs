@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::ProjectConfig;
+use crate::filesystem as fs;
+use crate::pattern::PatternMatcher;
+
+use super::report::{is_module_prefix, Result};
+use super::sync::{detect_project_dependencies, ImportLocation};
+
+/// Modules which nothing outside of them imports. Imports between two modules nested
+/// within the same module do not count, since deleting the outer module deletes both.
+fn unused_module_paths(
+    module_paths: &[String],
+    dependencies: &HashMap<String, HashMap<String, Vec<ImportLocation>>>,
+) -> Vec<String> {
+    let mut unused: Vec<String> = module_paths
+        .iter()
+        .filter(|module_path| {
+            !dependencies.iter().any(|(source, targets)| {
+                !is_module_prefix(module_path, source)
+                    && targets
+                        .keys()
+                        .any(|target| is_module_prefix(module_path, target))
+            })
+        })
+        .cloned()
+        .collect();
+    unused.sort();
+    unused
+}
+
+/// Find the modules within 'path' which have no imports from elsewhere in the project,
+/// excluding the root module and modules matching the configured entrypoints.
+pub fn find_unused_modules(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    path: &Path,
+) -> Result<Vec<String>> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    let entrypoints = project_config
+        .entrypoints
+        .iter()
+        .map(|pattern| PatternMatcher::from_glob(pattern))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    // When 'path' is not within a module (e.g. the project root), every module is included
+    let path_module = fs::file_to_module_path(&source_roots, &project_root.join(path)).ok();
+
+    let module_paths: Vec<String> = valid_modules
+        .into_iter()
+        .map(|module| module.path)
+        .filter(|module_path| {
+            module_path != ROOT_MODULE_SENTINEL_TAG
+                && !entrypoints
+                    .iter()
+                    .any(|entrypoint| entrypoint.matches(module_path))
+                && path_module.as_ref().is_none_or(|path_module| {
+                    is_module_prefix(path_module, module_path)
+                        || is_module_prefix(module_path, path_module)
+                })
+        })
+        .collect();
+
+    let dependencies = detect_project_dependencies(project_root.to_path_buf(), project_config)?;
+    Ok(unused_module_paths(&module_paths, &dependencies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependencies(
+        edges: &[(&str, &str)],
+    ) -> HashMap<String, HashMap<String, Vec<ImportLocation>>> {
+        let mut dependencies: HashMap<String, HashMap<String, Vec<ImportLocation>>> =
+            HashMap::new();
+        for (source, target) in edges {
+            dependencies
+                .entry(source.to_string())
+                .or_default()
+                .insert(target.to_string(), vec![]);
+        }
+        dependencies
+    }
+
+    #[test]
+    fn test_unused_module_paths() {
+        let module_paths: Vec<String> = ["api", "billing", "billing.legacy", "old", "old.jobs"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+        let dependencies = dependencies(&[
+            ("api", "billing"),
+            ("<root>", "api"),
+            // Only used from within the module which contains it
+            ("old", "old.jobs"),
+            ("billing.legacy", "old.jobs"),
+        ]);
+
+        assert_eq!(
+            unused_module_paths(&module_paths, &dependencies),
+            vec!["billing.legacy"]
+        );
+    }
+}
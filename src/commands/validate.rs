@@ -0,0 +1,569 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+use crate::config::plugins::{django::DjangoConfig, CustomPluginConfig, WasmPluginConfig};
+use crate::config::project::default_source_roots;
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::yaml::is_yaml_path;
+use crate::config::{
+    CacheConfig, ConfigError, DependencyConfig, EnvironmentRulesConfig, ExternalDependencyConfig,
+    ForbidRuleConfig, HealthConfig, IgnoreConfig, InterfaceConfig, ModuleConfig, NamingRuleConfig,
+    OtlpConfig, PluginsConfig, ProjectConfig, RedisCacheConfig, RemoteCacheConfig,
+};
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{
+    module_to_pyfile_or_dir_path, top_level_packages, walk_domain_config_files,
+};
+use crate::parsing::config::parse_domain_config;
+
+/// A problem found while validating a config file, with the position it was found at (1-indexed).
+/// Positions are only available for TOML configs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocatedConfigError {
+    pub error: ConfigError,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ConfigDiagnostic {
+    pub kind: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl From<LocatedConfigError> for ConfigDiagnostic {
+    fn from(located: LocatedConfigError) -> Self {
+        let suggestion = match &located.error {
+            ConfigError::UnknownKey { suggestion, .. } => suggestion.clone(),
+            _ => None,
+        };
+        Self {
+            kind: located.error.kind().to_string(),
+            message: located.error.to_string(),
+            suggestion,
+            file: located.file,
+            line: located.line,
+            column: located.column,
+        }
+    }
+}
+
+/// Captures the field names which a derived `Deserialize` implementation accepts,
+/// so that the set of known keys always matches the config structs.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("only structs have field names"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("field names captured"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Stands in for the name of an environment in the path of a '[rules.<environment>]' table
+const RULES_ENVIRONMENT: &str = "<environment>";
+
+/// The keys accepted in the table at `path`, where array elements are not part of the path.
+/// Returns None for tables whose keys are not checked.
+fn known_keys(path: &[&str]) -> Option<Vec<&'static str>> {
+    let keys = match path {
+        [] => field_names::<ProjectConfig>().to_vec(),
+        // Modules may also be declared in bulk with 'paths'
+        ["modules"] => [field_names::<ModuleConfig>(), &["paths"]].concat(),
+        // Dependencies have a hand-written `Deserialize`, which also accepts plain strings
+        ["modules", "depends_on"] => DependencyConfig::FIELDS.to_vec(),
+        ["interfaces"] => field_names::<InterfaceConfig>().to_vec(),
+        ["cache"] => field_names::<CacheConfig>().to_vec(),
+        ["cache", "remote"] => field_names::<RemoteCacheConfig>().to_vec(),
+        ["cache", "redis"] => field_names::<RedisCacheConfig>().to_vec(),
+        ["external"] => field_names::<ExternalDependencyConfig>().to_vec(),
+        ["ignore"] => field_names::<IgnoreConfig>().to_vec(),
+        ["health"] => field_names::<HealthConfig>().to_vec(),
+        ["otlp"] => field_names::<OtlpConfig>().to_vec(),
+        ["forbid"] => field_names::<ForbidRuleConfig>().to_vec(),
+        ["naming"] => field_names::<NamingRuleConfig>().to_vec(),
+        // Any other table within 'rules' declares the settings for an environment
+        ["rules"] => [
+            field_names::<EnvironmentRulesConfig>(),
+            &["reexport_resolution", "max_reexport_depth", "cli_patterns"],
+        ]
+        .concat(),
+        ["rules", RULES_ENVIRONMENT] => field_names::<EnvironmentRulesConfig>().to_vec(),
+        ["plugins"] => field_names::<PluginsConfig>().to_vec(),
+        ["plugins", "django"] => field_names::<DjangoConfig>().to_vec(),
+        ["plugins", "custom"] => field_names::<CustomPluginConfig>().to_vec(),
+        ["plugins", "wasm"] => field_names::<WasmPluginConfig>().to_vec(),
+        _ => return None,
+    };
+    Some(keys)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn suggest<'a>(key: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (key.len() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// All tables held by an item, looking through arrays
+fn tables(item: &Item) -> Vec<&dyn TableLike> {
+    match item {
+        Item::Table(table) => vec![table as &dyn TableLike],
+        Item::Value(Value::InlineTable(table)) => vec![table as &dyn TableLike],
+        Item::ArrayOfTables(array) => array.iter().map(|t| t as &dyn TableLike).collect(),
+        Item::Value(Value::Array(array)) => array
+            .iter()
+            .filter_map(|value| value.as_inline_table())
+            .map(|t| t as &dyn TableLike)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+struct Validator<'a> {
+    file: &'a Path,
+    // Spans are only meaningful when the document was parsed from the file itself
+    content: Option<&'a str>,
+    errors: Vec<LocatedConfigError>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, error: ConfigError, span: Option<Range<usize>>) {
+        let position = span.zip(self.content).map(|(span, content)| {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .count()
+                + 1;
+            (line, column)
+        });
+        self.errors.push(LocatedConfigError {
+            error,
+            file: self.file.to_path_buf(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        });
+    }
+
+    fn check_keys(&mut self, table: &dyn TableLike, path: &mut Vec<&'static str>) {
+        let Some(known) = known_keys(path) else {
+            return;
+        };
+        for (key, item) in table.iter() {
+            match known.iter().find(|known_key| **known_key == key) {
+                Some(known_key) => {
+                    path.push(*known_key);
+                    for child in tables(item) {
+                        self.check_keys(child, path);
+                    }
+                    path.pop();
+                }
+                None if path.as_slice() == ["rules"] && item.is_table_like() => {
+                    path.push(RULES_ENVIRONMENT);
+                    for child in tables(item) {
+                        self.check_keys(child, path);
+                    }
+                    path.pop();
+                }
+                None => {
+                    let table_name = if path.is_empty() {
+                        "the project config".to_string()
+                    } else {
+                        format!("'{}'", path.join("."))
+                    };
+                    self.report(
+                        ConfigError::UnknownKey {
+                            table: table_name,
+                            key: key.to_string(),
+                            suggestion: suggest(key, &known).map(str::to_string),
+                        },
+                        table.key(key).and_then(|key| key.span()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_modules(&mut self, doc: &ImDocument<String>, project_root: &Path) {
+        let source_roots: Vec<PathBuf> = doc
+            .get("source_roots")
+            .and_then(Item::as_array)
+            .map(|roots| {
+                roots
+                    .iter()
+                    .filter_map(|root| root.as_str().map(PathBuf::from))
+                    .collect()
+            })
+            .unwrap_or_else(default_source_roots)
+            .into_iter()
+            .map(|root| project_root.join(root))
+            .collect();
+
+        // (module path, span of its declaration, declared dependencies with their spans)
+        type DependencySpans = Vec<(String, Option<Range<usize>>)>;
+        let mut modules: Vec<(String, Option<Range<usize>>, DependencySpans)> = vec![];
+        for table in doc.get("modules").map(tables).unwrap_or_default() {
+            let dependencies: DependencySpans = table
+                .get("depends_on")
+                .and_then(Item::as_array)
+                .map(|depends_on| {
+                    depends_on
+                        .iter()
+                        .filter_map(|value| {
+                            let path = match value {
+                                Value::InlineTable(dependency) => dependency.get("path")?,
+                                _ => value,
+                            };
+                            Some((path.as_str()?.to_string(), value.span()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let paths: Vec<&Value> = match (table.get("path"), table.get("paths")) {
+                (Some(Item::Value(path)), _) => vec![path],
+                (_, Some(Item::Value(Value::Array(paths)))) => paths.iter().collect(),
+                _ => vec![],
+            };
+            for path in paths {
+                if let Some(module_path) = path.as_str() {
+                    modules.push((module_path.to_string(), path.span(), dependencies.clone()));
+                }
+            }
+            if let Some(structure) = table
+                .get("internal_structure")
+                .and_then(Item::as_table_like)
+            {
+                let module_path = table.get("path").and_then(Item::as_str).unwrap_or_default();
+                for (_, allowed) in structure.iter() {
+                    for entry in allowed.as_array().into_iter().flatten() {
+                        if let Some(entry_path) = entry.as_str() {
+                            if !structure.contains_key(entry_path) {
+                                self.report(
+                                    ConfigError::UnknownInternalStructureEntry {
+                                        module: module_path.to_string(),
+                                        entry: entry_path.to_string(),
+                                    },
+                                    entry.span(),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut defined: HashSet<String> = HashSet::from([ROOT_MODULE_SENTINEL_TAG.to_string()]);
+        for (module_path, span, _) in &modules {
+            if !defined.insert(module_path.clone()) {
+                self.report(
+                    ConfigError::DuplicateModule(module_path.clone()),
+                    span.clone(),
+                );
+            }
+        }
+        // Modules declared in domain configs may be depended on from the project config
+        if let Some(root) = project_root.to_str() {
+            for domain_path in walk_domain_config_files(root) {
+                if let Ok(domain) = parse_domain_config(&source_roots, domain_path) {
+                    defined.extend(domain.modules().map(|module| module.path.clone()));
+                }
+            }
+        }
+        // With implicit modules, every top-level package is a module
+        if doc.get("implicit_modules").and_then(Item::as_bool) == Some(true) {
+            let namespace_packages = doc
+                .get("namespace_packages")
+                .and_then(Item::as_bool)
+                .unwrap_or(true);
+            // Excluded packages are still treated as defined, since this only avoids false positives
+            let exclusions = PathExclusions::none(project_root);
+            for source_root in &source_roots {
+                defined.extend(top_level_packages(
+                    source_root,
+                    &exclusions,
+                    namespace_packages,
+                ));
+            }
+        }
+
+        // Mapped modules have no Python source, so they are found through their mapped path instead
+        let mut mapped: HashSet<String> = HashSet::new();
+        if let Some(mappings) = doc.get("module_mappings").and_then(Item::as_table_like) {
+            for (module_path, item) in mappings.iter() {
+                mapped.insert(module_path.to_string());
+                if let Some(path) = item.as_str() {
+                    if !project_root.join(path).exists() {
+                        self.report(
+                            ConfigError::MissingModuleMapping {
+                                module: module_path.to_string(),
+                                path: path.to_string(),
+                            },
+                            item.span(),
+                        );
+                    }
+                }
+            }
+        }
+
+        for (module_path, span, dependencies) in &modules {
+            if module_path != ROOT_MODULE_SENTINEL_TAG
+                && !mapped.contains(module_path)
+                && module_to_pyfile_or_dir_path(&source_roots, module_path).is_none()
+            {
+                self.report(
+                    ConfigError::UnreachableModule(module_path.clone()),
+                    span.clone(),
+                );
+            }
+            for (dependency, dependency_span) in dependencies {
+                if dependency == module_path {
+                    self.report(
+                        ConfigError::SelfDependency(module_path.clone()),
+                        dependency_span.clone(),
+                    );
+                } else if !defined.contains(dependency) {
+                    self.report(
+                        ConfigError::UndefinedDependency {
+                            module: module_path.clone(),
+                            dependency: dependency.clone(),
+                        },
+                        dependency_span.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// WebAssembly plugins can only be run by builds with the 'wasm' feature
+    #[cfg(not(feature = "wasm"))]
+    fn check_wasm_plugins(&mut self, doc: &ImDocument<String>) {
+        let plugins = doc
+            .get("plugins")
+            .and_then(|plugins| plugins.get("wasm"))
+            .map(tables)
+            .unwrap_or_default();
+        for plugin in plugins {
+            // Plugins which are turned off are never loaded
+            if plugin.get("severity").and_then(Item::as_str) == Some("off") {
+                continue;
+            }
+            let name = plugin
+                .get("name")
+                .and_then(Item::as_str)
+                .unwrap_or_default();
+            self.report(
+                ConfigError::WasmUnsupported(name.to_string()),
+                plugin.get("name").and_then(|name| name.span()),
+            );
+        }
+    }
+}
+
+/// Validate the project config at `config_path`, reporting every problem found rather than only the first.
+pub fn validate_project_config(
+    project_root: &Path,
+    config_path: &Path,
+) -> Result<Vec<LocatedConfigError>, ConfigError> {
+    let content =
+        std::fs::read_to_string(config_path).map_err(|_| ConfigError::ConfigDoesNotExist)?;
+    let mut validator = Validator {
+        file: config_path,
+        content: None,
+        errors: vec![],
+    };
+
+    // YAML is checked through an equivalent TOML document, without positions
+    let toml_content = if is_yaml_path(config_path) {
+        let converted = serde_yaml::from_str::<toml::Table>(&content)
+            .map_err(|err| err.to_string())
+            .and_then(|table| toml::to_string(&table).map_err(|err| err.to_string()));
+        match converted {
+            Ok(toml_content) => toml_content,
+            Err(err) => {
+                validator.report(ConfigError::Parse(err), None);
+                return Ok(validator.errors);
+            }
+        }
+    } else {
+        validator.content = Some(&content);
+        content.clone()
+    };
+
+    let doc = match ImDocument::parse(toml_content) {
+        Ok(doc) => doc,
+        Err(err) => {
+            validator.report(ConfigError::Parse(err.message().to_string()), err.span());
+            return Ok(validator.errors);
+        }
+    };
+
+    validator.check_keys(doc.as_table(), &mut vec![]);
+    validator.check_modules(&doc, project_root);
+
+    // Type errors are only reported once the structure is otherwise valid, since unknown keys also fail to parse
+    if validator.errors.is_empty() {
+        if let Err(err) = toml::from_str::<ProjectConfig>(doc.raw()) {
+            validator.report(ConfigError::Parse(err.message().to_string()), err.span());
+        }
+    }
+    #[cfg(not(feature = "wasm"))]
+    validator.check_wasm_plugins(&doc);
+
+    Ok(validator.errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn validate(content: &str) -> Vec<ConfigError> {
+        let project_root = tempfile::tempdir().unwrap();
+        for module in ["a", "b"] {
+            std::fs::create_dir(project_root.path().join(module)).unwrap();
+            std::fs::write(project_root.path().join(module).join("__init__.py"), "").unwrap();
+        }
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(&config_path, content).unwrap();
+        validate_project_config(project_root.path(), &config_path)
+            .unwrap()
+            .into_iter()
+            .map(|located| located.error)
+            .collect()
+    }
+
+    #[rstest]
+    #[case("exclud = []\n", "exclude")]
+    #[case(
+        "[rules.ci]\nunused_ignore_directive = \"error\"\n",
+        "unused_ignore_directives"
+    )]
+    #[case("[[modules]]\npath = \"a\"\ndepend_on = []\n", "depends_on")]
+    fn test_unknown_key_suggestion(#[case] content: &str, #[case] expected: &str) {
+        let errors = validate(content);
+        assert!(matches!(
+            errors.as_slice(),
+            [ConfigError::UnknownKey { suggestion: Some(suggestion), .. }] if suggestion == expected
+        ));
+    }
+
+    #[test]
+    fn test_module_errors() {
+        let content = "[[modules]]\npath = \"a\"\ndepends_on = [\"a\", \"c\"]\n\n[[modules]]\npath = \"b\"\n\n[[modules]]\npath = \"b\"\n\n[[modules]]\npath = \"missing\"\n";
+        assert_eq!(
+            validate(content),
+            vec![
+                ConfigError::DuplicateModule("b".to_string()),
+                ConfigError::SelfDependency("a".to_string()),
+                ConfigError::UndefinedDependency {
+                    module: "a".to_string(),
+                    dependency: "c".to_string()
+                },
+                ConfigError::UnreachableModule("missing".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_keys() {
+        let content = "[[modules]]\npath = \"a\"\ndepends_on = [{ path = \"b\", deprecated = true, deprecated_members = [\"old\"], members = [\"old\", \"new\"] }]\n\n[[modules]]\npath = \"b\"\n";
+        assert_eq!(validate(content), vec![]);
+    }
+
+    #[test]
+    fn test_module_mappings() {
+        let content = "[module_mappings]\n\"a.fast\" = \"a/__init__.py\"\n\"b.missing\" = \"b/missing.so\"\n\n[[modules]]\npath = \"a.fast\"\n";
+        assert_eq!(
+            validate(content),
+            vec![ConfigError::MissingModuleMapping {
+                module: "b.missing".to_string(),
+                path: "b/missing.so".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_internal_structure() {
+        let content = "[[modules]]\npath = \"a\"\n\n[modules.internal_structure]\napi = [\"services\", \"models\"]\nservices = []\n";
+        assert_eq!(
+            validate(content),
+            vec![ConfigError::UnknownInternalStructureEntry {
+                module: "a".to_string(),
+                entry: "models".to_string()
+            }]
+        );
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_wasm_plugins_unsupported() {
+        let content = "[[plugins.wasm]]\nname = \"no-legacy\"\npath = \"no_legacy.wasm\"\n\n[[plugins.wasm]]\nname = \"disabled\"\npath = \"disabled.wasm\"\nseverity = \"off\"\n";
+        assert_eq!(
+            validate(content),
+            vec![ConfigError::WasmUnsupported("no-legacy".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let project_root = tempfile::tempdir().unwrap();
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(&config_path, "exclude = []\n\n[[modules]]\n  pth = \"a\"\n").unwrap();
+        let errors = validate_project_config(project_root.path(), &config_path).unwrap();
+        assert_eq!((errors[0].line, errors[0].column), (Some(4), Some(3)));
+    }
+}
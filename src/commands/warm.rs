@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::commands::check::check_internal::{self, FileCaches};
+use crate::commands::check::CheckError;
+use crate::config::{CacheBackend, ProjectConfig};
+use crate::diagnostics::FileProcessor;
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{self as fs, FileOverlay, ProjectFile, WalkOptions};
+use crate::interrupt::check_interrupt;
+use crate::modules::build_module_tree;
+use crate::processors::InternalDependencyExtractor;
+
+pub type Result<T> = std::result::Result<T, CheckError>;
+
+#[derive(Debug, Clone, Default)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct WarmSummary {
+    // Files whose imports were parsed (or were already cached)
+    pub files: usize,
+    // Files which could not be read or parsed
+    pub skipped: usize,
+    // Whether check results were also cached
+    pub checked: bool,
+    // Whether results were stored in a remote cache, rather than in the '.tach' directory
+    pub shared: bool,
+}
+
+/// Walk and parse the whole project to populate the per-file caches, without reporting anything.
+/// Without a remote cache backend, parsed imports are stored in the '.tach' directory.
+/// With `checks`, dependency and interface checks are also run so that their results are cached,
+/// but the diagnostics are discarded. Check results are only cached by remote backends.
+pub fn warm(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    checks: bool,
+) -> Result<WarmSummary> {
+    if !project_root.is_dir() {
+        return Err(CheckError::InvalidDirectory(
+            project_root.display().to_string(),
+        ));
+    }
+
+    let source_roots: Vec<PathBuf> = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    // Cycles do not prevent parsing, so they are left for 'tach check' to report
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        false,
+        None,
+        project_config.root_module.clone(),
+    )?;
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let overlay = FileOverlay::new();
+    let file_caches = FileCaches::build(
+        project_root,
+        project_config,
+        &source_roots,
+        &valid_modules,
+        &exclusions,
+        &overlay,
+        true,
+        true,
+    )?
+    .with_imports_on_disk(project_root, project_config)?;
+    let checked = checks && file_caches.caches_checks();
+    let extractor =
+        InternalDependencyExtractor::new(&source_roots, &module_tree, project_config, &exclusions)
+            .with_import_cache(file_caches.imports());
+
+    let files = AtomicUsize::new(0);
+    let skipped = AtomicUsize::new(0);
+    source_roots.par_iter().for_each(|source_root| {
+        fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            WalkOptions::from(project_config),
+        )
+        .par_bridge()
+        .for_each(|file_path| {
+            if check_interrupt().is_err() {
+                return;
+            }
+            let processed = ProjectFile::try_new_all(project_root, source_root, &file_path)
+                .map_err(|_| ())
+                .and_then(|project_files| {
+                    project_files.into_iter().try_for_each(|project_file| {
+                        extractor.process(project_file).map(|_| ()).map_err(|_| ())
+                    })
+                });
+            match processed {
+                Ok(_) => files.fetch_add(1, Ordering::Relaxed),
+                Err(_) => skipped.fetch_add(1, Ordering::Relaxed),
+            };
+        });
+    });
+    check_interrupt().map_err(|_| CheckError::Interrupt)?;
+
+    if checked {
+        // Matches the default 'tach check', which is what developer and PR runs look up
+        check_internal::check(project_root.to_path_buf(), project_config, true, true)?;
+    }
+
+    Ok(WarmSummary {
+        files: files.into_inner(),
+        skipped: skipped.into_inner(),
+        checked,
+        shared: project_config.cache.backend != CacheBackend::Disk,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    use crate::config::{DependencyConfig, ModuleConfig};
+
+    #[test]
+    fn test_warm_local_import_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        for (path, contents) in [("a/__init__.py", "import b\n"), ("b/__init__.py", "")] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let project_config = ProjectConfig {
+            modules: vec![
+                ModuleConfig {
+                    depends_on: Some(vec![DependencyConfig::from_path("b")]),
+                    ..ModuleConfig::new("a", false)
+                },
+                ModuleConfig::new("b", false),
+            ],
+            ..Default::default()
+        };
+
+        let summary = warm(temp_dir.path(), &project_config, true).unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.skipped, 0);
+        // Only remote backends cache check results
+        assert!(!summary.checked);
+        assert!(!summary.shared);
+
+        let source_roots = project_config.prepend_roots(temp_dir.path());
+        let (valid_modules, _) = project_config.validate_modules(&source_roots);
+        let exclusions = PathExclusions::new(
+            temp_dir.path(),
+            &project_config.exclude,
+            &project_config.ignore,
+        )
+        .unwrap();
+        let file_caches = FileCaches::build(
+            temp_dir.path(),
+            &project_config,
+            &source_roots,
+            &valid_modules,
+            &exclusions,
+            &FileOverlay::new(),
+            true,
+            true,
+        )
+        .unwrap()
+        .with_imports_on_disk(temp_dir.path(), &project_config)
+        .unwrap();
+        let imports = file_caches.imports().unwrap();
+        assert!(imports
+            .get::<serde_json::Value>("a/__init__.py", "import b\n")
+            .is_some());
+    }
+}
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 pub enum CacheBackend {
     #[default]
     Disk,
+    Http,
+    Redis,
 }
 
 impl CacheBackend {
@@ -18,15 +20,57 @@ impl IntoPy<PyObject> for CacheBackend {
     fn into_py(self, py: Python) -> PyObject {
         match self {
             Self::Disk => "disk".to_object(py),
+            Self::Http => "http".to_object(py),
+            Self::Redis => "redis".to_object(py),
         }
     }
 }
 
+#[derive(Debug, Serialize, Default, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct RemoteCacheConfig {
+    // Base URL of a content-addressed store, entries are read/written at '<url>/<cache key>'
+    pub url: String,
+    // Name of an environment variable holding a bearer token for the store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+fn default_redis_key_prefix() -> String {
+    "tach".to_string()
+}
+
+fn is_default_redis_key_prefix(prefix: &str) -> bool {
+    prefix == default_redis_key_prefix()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct RedisCacheConfig {
+    // Connection URL, e.g. 'redis://localhost:6379/0'
+    pub url: String,
+    // Prefix applied to every key written by tach
+    #[serde(
+        default = "default_redis_key_prefix",
+        skip_serializing_if = "is_default_redis_key_prefix"
+    )]
+    pub key_prefix: String,
+    // Expiry for cache entries, entries never expire when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Default, Deserialize, Clone, PartialEq)]
 #[pyclass(get_all, module = "tach.extension")]
 pub struct CacheConfig {
     #[serde(default, skip_serializing_if = "CacheBackend::is_default")]
     pub backend: CacheBackend,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteCacheConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redis: Option<RedisCacheConfig>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub file_dependencies: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
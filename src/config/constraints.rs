@@ -0,0 +1,64 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::RuleSetting;
+
+/// Forbids dependencies between modules, declared with '[[forbid]]'
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(module = "tach.extension")]
+pub struct ForbidRuleConfig {
+    // Glob patterns of module paths (e.g. '*.api'). 'from' is a keyword in Python.
+    #[pyo3(get, name = "from_")]
+    pub from: String,
+    #[pyo3(get)]
+    pub to: String,
+    // When set, indirect dependencies from 'from' to 'to' must also pass through a module matching this pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[pyo3(get)]
+    pub unless_through: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[pyo3(get)]
+    pub reason: Option<String>,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    #[pyo3(get)]
+    pub severity: RuleSetting,
+}
+
+impl ForbidRuleConfig {
+    /// How the rule is referred to in diagnostics
+    pub fn describe(&self) -> String {
+        let rule = match &self.unless_through {
+            Some(through) => format!(
+                "forbid '{}' -> '{}' unless through '{}'",
+                self.from, self.to, through
+            ),
+            None => format!("forbid '{}' -> '{}'", self.from, self.to),
+        };
+        match &self.reason {
+            Some(reason) => format!("{}: {}", rule, reason),
+            None => rule,
+        }
+    }
+}
+
+/// Constrains the names of modules, declared with '[[naming]]'
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct NamingRuleConfig {
+    // Glob pattern of the module paths which the rule applies to
+    pub modules: String,
+    // Regex which the last component of each matching module path must match
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub severity: RuleSetting,
+}
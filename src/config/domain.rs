@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::iter;
 use std::path::{Path, PathBuf};
 
@@ -6,13 +7,20 @@ use serde::{Deserialize, Serialize};
 
 use crate::filesystem::file_to_module_path;
 
-use super::edit::{ConfigEdit, ConfigEditor, EditError};
+use super::edit::{
+    apply_attribute_edit, apply_module_edit, dependency_path, ConfigEdit, ConfigEditPreview,
+    ConfigEditor, EditError,
+};
 use super::interfaces::InterfaceConfig;
 use super::modules::{
     default_visibility, deserialize_modules, is_default_visibility, serialize_modules,
     DependencyConfig, ModuleConfig,
 };
+use super::rules::ModuleRulesConfig;
+use super::stability::Stability;
+use super::toml_layout;
 use super::utils::*;
+use super::write::write_atomic;
 use crate::parsing::error::ParsingError;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
@@ -22,6 +30,10 @@ pub struct DomainRootConfig {
     pub depends_on: Option<Vec<DependencyConfig>>,
     #[serde(default)]
     pub layer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<Stability>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
     #[serde(
         default = "default_visibility",
         skip_serializing_if = "is_default_visibility"
@@ -31,6 +43,16 @@ pub struct DomainRootConfig {
     pub utility: bool,
     #[serde(default, skip_serializing_if = "is_false")]
     pub unchecked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_violations: Option<usize>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub internal_structure: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<ModuleRulesConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implements: Option<String>,
 }
 
 impl DomainRootConfig {
@@ -45,6 +67,12 @@ impl DomainRootConfig {
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct DomainConfig {
+    // Shown in place of the domain's module path in diagnostics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // When set, modules outside of the domain may only import these modules of the domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface_modules: Option<Vec<String>>,
     #[serde(default)]
     pub root: Option<DomainRootConfig>,
     #[serde(
@@ -88,11 +116,18 @@ impl DomainConfig {
             .iter()
             .map(|interface| interface.resolve(&location))
             .collect();
+        let resolved_interface_modules = self.interface_modules.as_ref().map(|paths| {
+            paths
+                .iter()
+                .map(|path| resolve_module_path(path, &location))
+                .collect()
+        });
         LocatedDomainConfig {
             config: self,
             location,
             resolved_modules,
             resolved_interfaces,
+            resolved_interface_modules,
             pending_edits: Default::default(),
         }
     }
@@ -100,6 +135,18 @@ impl DomainConfig {
 
 pub const DOMAIN_ROOT_SENTINEL: &str = "<domain_root>";
 
+/// Resolve a module path within a domain config, which may be absolute ('//'),
+/// the domain root, or relative to the domain root
+fn resolve_module_path(path: &str, location: &ConfigLocation) -> String {
+    if let Some(absolute_path) = path.strip_prefix("//") {
+        absolute_path.to_string()
+    } else if path == DOMAIN_ROOT_SENTINEL {
+        location.mod_path.clone()
+    } else {
+        format!("{}.{}", location.mod_path, path)
+    }
+}
+
 trait Resolvable<T> {
     fn resolve(&self, location: &ConfigLocation) -> T;
 }
@@ -110,19 +157,19 @@ impl Resolvable<DependencyConfig> for DependencyConfig {
             // Absolute path does not need to be prefixed with the module path
             DependencyConfig {
                 path: self.path[2..].to_string(),
-                deprecated: self.deprecated,
+                ..self.clone()
             }
         } else {
             match self.path.as_str() {
                 // Special case for the domain root sentinel, use the module path
                 DOMAIN_ROOT_SENTINEL => DependencyConfig {
                     path: location.mod_path.clone(),
-                    deprecated: self.deprecated,
+                    ..self.clone()
                 },
                 // Relative path needs to be prefixed with the module path
                 _ => DependencyConfig {
                     path: format!("{}.{}", location.mod_path, self.path),
-                    deprecated: self.deprecated,
+                    ..self.clone()
                 },
             }
         }
@@ -142,10 +189,20 @@ impl Resolvable<ModuleConfig> for DomainRootConfig {
             path: location.mod_path.clone(),
             depends_on: self.depends_on.clone().map(|deps| deps.resolve(location)),
             layer: self.layer.clone(),
+            stability: self.stability,
+            platforms: self.platforms.clone(),
             visibility: self.visibility.clone(),
             utility: self.utility,
             strict: false,
             unchecked: self.unchecked,
+            display_name: self.display_name.clone(),
+            max_violations: self.max_violations,
+            internal_structure: self.internal_structure.clone(),
+            rules: self.rules.clone(),
+            implements: self
+                .implements
+                .as_ref()
+                .map(|implements| resolve_module_path(implements, location)),
             group_id: None,
         }
     }
@@ -157,10 +214,20 @@ impl Resolvable<ModuleConfig> for ModuleConfig {
             path: format!("{}.{}", location.mod_path, self.path),
             depends_on: self.depends_on.clone().map(|deps| deps.resolve(location)),
             layer: self.layer.clone(),
+            stability: self.stability,
+            platforms: self.platforms.clone(),
             visibility: self.visibility.clone(),
             utility: self.utility,
             strict: false,
             unchecked: self.unchecked,
+            display_name: self.display_name.clone(),
+            max_violations: self.max_violations,
+            internal_structure: self.internal_structure.clone(),
+            rules: self.rules.clone(),
+            implements: self
+                .implements
+                .as_ref()
+                .map(|implements| resolve_module_path(implements, location)),
             group_id: None,
         }
     }
@@ -179,6 +246,7 @@ impl Resolvable<InterfaceConfig> for InterfaceConfig {
                 })
                 .collect(),
             data_types: self.data_types.clone(),
+            stability: self.stability,
         }
     }
 }
@@ -189,10 +257,19 @@ pub struct LocatedDomainConfig {
     pub location: ConfigLocation,
     resolved_modules: Vec<ModuleConfig>,
     resolved_interfaces: Vec<InterfaceConfig>,
+    resolved_interface_modules: Option<Vec<String>>,
     pending_edits: Vec<ConfigEdit>,
 }
 
 impl LocatedDomainConfig {
+    pub fn has_pending_edits(&self) -> bool {
+        !self.pending_edits.is_empty()
+    }
+
+    pub fn pending_edits(&self) -> &[ConfigEdit] {
+        &self.pending_edits
+    }
+
     pub fn modules(&self) -> impl Iterator<Item = &ModuleConfig> {
         self.resolved_modules.iter()
     }
@@ -201,12 +278,34 @@ impl LocatedDomainConfig {
         self.resolved_interfaces.iter()
     }
 
+    /// The name of the domain, which defaults to its module path
+    pub fn name(&self) -> &str {
+        self.config
+            .name
+            .as_deref()
+            .unwrap_or(&self.location.mod_path)
+    }
+
+    /// The only modules of the domain which other domains may import, if restricted
+    pub fn interface_modules(&self) -> Option<&[String]> {
+        self.resolved_interface_modules.as_deref()
+    }
+
     pub fn with_dependencies_removed(&self) -> Self {
         self.config
             .with_dependencies_removed()
             .with_location(self.location.clone())
     }
 
+    /// A copy of this domain with its pending edits applied to its modules, rather than written to disk
+    pub fn with_edits_applied(&self) -> Self {
+        let mut domain = self.clone();
+        for edit in domain.pending_edits.drain(..) {
+            apply_module_edit(&mut domain.resolved_modules, &edit);
+        }
+        domain
+    }
+
     pub fn normalize_module_path(&self, module_path: &str) -> String {
         if module_path == self.location.mod_path {
             DOMAIN_ROOT_SENTINEL.to_string()
@@ -230,7 +329,11 @@ impl ConfigEditor for LocatedDomainConfig {
             | ConfigEdit::MarkModuleAsUtility { path }
             | ConfigEdit::UnmarkModuleAsUtility { path }
             | ConfigEdit::AddDependency { path, .. }
-            | ConfigEdit::RemoveDependency { path, .. } => {
+            | ConfigEdit::RemoveDependency { path, .. }
+            | ConfigEdit::MarkDependencyDeprecated { path, .. }
+            | ConfigEdit::UnmarkDependencyDeprecated { path, .. }
+            | ConfigEdit::SetModuleLayer { path, .. }
+            | ConfigEdit::SetModuleMaxViolations { path, .. } => {
                 if path.starts_with(&self.location.mod_path) {
                     // If this module path appears to belong to this domain, enqueue the edit
                     self.pending_edits.push(edit.clone());
@@ -239,10 +342,20 @@ impl ConfigEditor for LocatedDomainConfig {
                     Err(EditError::NotApplicable)
                 }
             }
-            ConfigEdit::AddSourceRoot { .. } | ConfigEdit::RemoveSourceRoot { .. } => {
-                Err(EditError::NotApplicable)
-            }
+            // Interfaces, including those of strict modules, are declared in the project config
+            ConfigEdit::SetModuleStrict { .. }
+            | ConfigEdit::AddSourceRoot { .. }
+            | ConfigEdit::RemoveSourceRoot { .. }
+            | ConfigEdit::AddInterface { .. }
+            | ConfigEdit::SetMaxViolations { .. } => Err(EditError::NotApplicable),
+        }
+    }
+
+    fn preview_edits(&self) -> Result<Vec<ConfigEditPreview>, EditError> {
+        if self.pending_edits.is_empty() {
+            return Ok(vec![]);
         }
+        Ok(vec![self.render_edits()?])
     }
 
     fn apply_edits(&mut self) -> Result<(), EditError> {
@@ -250,6 +363,16 @@ impl ConfigEditor for LocatedDomainConfig {
             return Ok(());
         }
 
+        let preview = self.render_edits()?;
+        write_atomic(&preview.path, &preview.edited).map_err(|_| EditError::DiskWriteFailed)?;
+
+        self.pending_edits.clear();
+        Ok(())
+    }
+}
+
+impl LocatedDomainConfig {
+    fn render_edits(&self) -> Result<ConfigEditPreview, EditError> {
         let toml_str = std::fs::read_to_string(&self.location.path)
             .map_err(|_| EditError::ConfigDoesNotExist)?;
         let mut doc = toml_str
@@ -275,6 +398,8 @@ impl ConfigEditor for LocatedDomainConfig {
                     let mut module_table = toml_edit::Table::new();
                     module_table.insert("path", toml_edit::value(relative_path));
                     module_table.insert("depends_on", toml_edit::value(toml_edit::Array::new()));
+                    // Separate the new module from the preceding table
+                    module_table.decor_mut().set_prefix("\n");
 
                     if let Some(root) = &self.config.root {
                         if let Some(layer) = &root.layer {
@@ -315,10 +440,9 @@ impl ConfigEditor for LocatedDomainConfig {
                                 .map(|p| p.as_array().is_some())
                                 .unwrap_or(false)
                             {
-                                table["paths"]
-                                    .as_array_mut()
-                                    .unwrap()
-                                    .retain(|p| p.as_str().unwrap() != relative_path);
+                                toml_layout::retain(table["paths"].as_array_mut().unwrap(), |p| {
+                                    p.as_str().unwrap() != relative_path
+                                });
                                 if table["paths"].as_array().unwrap().is_empty() {
                                     module_index = Some(i);
                                 }
@@ -392,7 +516,10 @@ impl ConfigEditor for LocatedDomainConfig {
                                         array,
                                     ))) = root.get_mut("depends_on")
                                     {
-                                        array.push(self.normalize_module_path(dependency));
+                                        toml_layout::push(
+                                            array,
+                                            self.normalize_module_path(dependency),
+                                        );
                                     } else {
                                         root.insert(
                                             "depends_on",
@@ -406,12 +533,9 @@ impl ConfigEditor for LocatedDomainConfig {
                                     if let toml_edit::Item::Value(toml_edit::Value::Array(array)) =
                                         &mut root["depends_on"]
                                     {
-                                        array.retain(|dep| {
-                                            dep.as_str()
-                                                .map(|d| {
-                                                    d != self.normalize_module_path(dependency)
-                                                })
-                                                .unwrap_or(true)
+                                        let dependency = self.normalize_module_path(dependency);
+                                        toml_layout::retain(array, |dep| {
+                                            dependency_path(dep) != Some(&dependency)
                                         });
                                     }
                                 }
@@ -448,7 +572,10 @@ impl ConfigEditor for LocatedDomainConfig {
                                             toml_edit::Value::Array(array),
                                         )) = table.get_mut("depends_on")
                                         {
-                                            array.push(self.normalize_module_path(dependency));
+                                            toml_layout::push(
+                                                array,
+                                                self.normalize_module_path(dependency),
+                                            );
                                         } else {
                                             table.insert(
                                                 "depends_on",
@@ -465,12 +592,9 @@ impl ConfigEditor for LocatedDomainConfig {
                                             array,
                                         )) = &mut table["depends_on"]
                                         {
-                                            array.retain(|dep| {
-                                                dep.as_str()
-                                                    .map(|d| {
-                                                        d != self.normalize_module_path(dependency)
-                                                    })
-                                                    .unwrap_or(true)
+                                            let dependency = self.normalize_module_path(dependency);
+                                            toml_layout::retain(array, |dep| {
+                                                dependency_path(dep) != Some(&dependency)
                                             });
                                         }
                                     }
@@ -480,17 +604,63 @@ impl ConfigEditor for LocatedDomainConfig {
                         }
                     }
                 }
-                ConfigEdit::AddSourceRoot { .. } | ConfigEdit::RemoveSourceRoot { .. } => {
+                ConfigEdit::MarkDependencyDeprecated { path, .. }
+                | ConfigEdit::UnmarkDependencyDeprecated { path, .. }
+                | ConfigEdit::SetModuleLayer { path, .. }
+                | ConfigEdit::SetModuleMaxViolations { path, .. } => {
+                    let dependency = edit
+                        .dependency()
+                        .map(|dependency| self.normalize_module_path(dependency));
+
+                    // Check if this is a root module
+                    if path == &self.location.mod_path {
+                        if let Some(toml_edit::Item::Table(root)) = doc.get_mut("root") {
+                            apply_attribute_edit(root, edit, dependency.as_deref());
+                        }
+                        continue;
+                    }
+
+                    let relative_path = path
+                        .strip_prefix(&self.location.mod_path)
+                        .map(|p| p.trim_start_matches('.'))
+                        .unwrap_or(path);
+
+                    if let toml_edit::Item::ArrayOfTables(modules) = &mut doc["modules"] {
+                        for table in modules.iter_mut() {
+                            let is_target_module = table
+                                .get("path")
+                                .map(|p| p.as_str() == Some(relative_path))
+                                .unwrap_or(false)
+                                || table
+                                    .get("paths")
+                                    .map(|p| {
+                                        p.as_array().is_some_and(|p| {
+                                            p.iter().any(|p| p.as_str() == Some(relative_path))
+                                        })
+                                    })
+                                    .unwrap_or(false);
+
+                            if is_target_module {
+                                apply_attribute_edit(table, edit, dependency.as_deref());
+                            }
+                        }
+                    }
+                }
+                ConfigEdit::SetModuleStrict { .. }
+                | ConfigEdit::AddSourceRoot { .. }
+                | ConfigEdit::RemoveSourceRoot { .. }
+                | ConfigEdit::AddInterface { .. }
+                | ConfigEdit::SetMaxViolations { .. } => {
                     return Err(EditError::NotApplicable);
                 }
             }
         }
 
-        std::fs::write(&self.location.path, doc.to_string())
-            .map_err(|_| EditError::DiskWriteFailed)?;
-
-        self.pending_edits.clear();
-        Ok(())
+        Ok(ConfigEditPreview {
+            path: self.location.path.clone(),
+            original: toml_str,
+            edited: doc.to_string(),
+        })
     }
 }
 
@@ -512,3 +682,38 @@ impl ConfigLocation {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_names_are_resolved() {
+        let domain = DomainConfig {
+            root: Some(DomainRootConfig {
+                display_name: Some("Billing".to_string()),
+                ..Default::default()
+            }),
+            modules: vec![ModuleConfig {
+                display_name: Some("Invoicing".to_string()),
+                ..ModuleConfig::new("invoices", false)
+            }],
+            ..Default::default()
+        };
+        let located = domain.with_location(ConfigLocation {
+            path: PathBuf::from("billing/tach.domain.toml"),
+            mod_path: "billing".to_string(),
+        });
+
+        assert_eq!(
+            located
+                .modules()
+                .map(|module| (module.path.as_str(), module.label()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("billing.invoices", "Invoicing".to_string()),
+                ("billing", "Billing".to_string()),
+            ]
+        );
+    }
+}
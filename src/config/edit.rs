@@ -1,17 +1,102 @@
 use std::path::PathBuf;
 
+use pyo3::prelude::*;
 use thiserror::Error;
 
+use super::modules::{DependencyConfig, ModuleConfig};
+
 #[derive(Debug, Clone, PartialEq)]
+#[pyclass(eq, module = "tach.extension")]
 pub enum ConfigEdit {
-    CreateModule { path: String },
-    DeleteModule { path: String },
-    MarkModuleAsUtility { path: String },
-    UnmarkModuleAsUtility { path: String },
-    AddDependency { path: String, dependency: String },
-    RemoveDependency { path: String, dependency: String },
-    AddSourceRoot { filepath: PathBuf },
-    RemoveSourceRoot { filepath: PathBuf },
+    CreateModule {
+        path: String,
+    },
+    DeleteModule {
+        path: String,
+    },
+    MarkModuleAsUtility {
+        path: String,
+    },
+    UnmarkModuleAsUtility {
+        path: String,
+    },
+    AddDependency {
+        path: String,
+        dependency: String,
+    },
+    RemoveDependency {
+        path: String,
+        dependency: String,
+    },
+    MarkDependencyDeprecated {
+        path: String,
+        dependency: String,
+    },
+    UnmarkDependencyDeprecated {
+        path: String,
+        dependency: String,
+    },
+    SetModuleStrict {
+        path: String,
+        strict: bool,
+    },
+    SetModuleLayer {
+        path: String,
+        layer: Option<String>,
+    },
+    SetModuleMaxViolations {
+        path: String,
+        max_violations: Option<usize>,
+    },
+    AddSourceRoot {
+        filepath: PathBuf,
+    },
+    RemoveSourceRoot {
+        filepath: PathBuf,
+    },
+    // Adds an interface for the module to the project config, rather than editing the module
+    AddInterface {
+        path: String,
+        expose: Vec<String>,
+    },
+    // Sets the project-wide violation budget
+    SetMaxViolations {
+        max_violations: Option<usize>,
+    },
+}
+
+impl ConfigEdit {
+    /// The module path targeted by this edit, if any
+    pub fn module_path(&self) -> Option<&str> {
+        match self {
+            ConfigEdit::CreateModule { path }
+            | ConfigEdit::DeleteModule { path }
+            | ConfigEdit::MarkModuleAsUtility { path }
+            | ConfigEdit::UnmarkModuleAsUtility { path }
+            | ConfigEdit::AddDependency { path, .. }
+            | ConfigEdit::RemoveDependency { path, .. }
+            | ConfigEdit::MarkDependencyDeprecated { path, .. }
+            | ConfigEdit::UnmarkDependencyDeprecated { path, .. }
+            | ConfigEdit::SetModuleStrict { path, .. }
+            | ConfigEdit::SetModuleLayer { path, .. }
+            | ConfigEdit::SetModuleMaxViolations { path, .. } => Some(path),
+            ConfigEdit::AddSourceRoot { .. }
+            | ConfigEdit::RemoveSourceRoot { .. }
+            | ConfigEdit::AddInterface { .. }
+            | ConfigEdit::SetMaxViolations { .. } => None,
+        }
+    }
+
+    /// The dependency path targeted by this edit, if any
+    pub fn dependency(&self) -> Option<&str> {
+        match self {
+            ConfigEdit::AddDependency { dependency, .. }
+            | ConfigEdit::RemoveDependency { dependency, .. }
+            | ConfigEdit::MarkDependencyDeprecated { dependency, .. }
+            | ConfigEdit::UnmarkDependencyDeprecated { dependency, .. } => Some(dependency),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Error)]
@@ -30,9 +115,329 @@ pub enum EditError {
     ConfigDoesNotExist,
     #[error("Edit not implemented: {0}")]
     NotImplemented(String),
+    #[error("Module '{0}' is declared with '__tach__' in its '__init__.py', which must be edited by hand")]
+    DeclaredInline(String),
+    #[error("Failed to back up config files before editing them: {0}")]
+    BackupFailed(String),
+    #[error("Failed to write every config file, so the configuration may be partially edited. The files as they were before this edit are backed up in '{}'.", .0.display())]
+    WriteInterrupted(PathBuf),
 }
 
 pub trait ConfigEditor {
     fn enqueue_edit(&mut self, edit: &ConfigEdit) -> Result<(), EditError>;
+    /// Each config file which the pending edits would change, without writing anything to disk
+    fn preview_edits(&self) -> Result<Vec<ConfigEditPreview>, EditError>;
     fn apply_edits(&mut self) -> Result<(), EditError>;
 }
+
+/// A config file as it is on disk, and as it will be once the pending edits are applied
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ConfigEditPreview {
+    pub path: PathBuf,
+    pub original: String,
+    pub edited: String,
+}
+
+#[pymethods]
+impl ConfigEditPreview {
+    /// A unified diff from the original to the edited contents
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        similar::TextDiff::from_lines(&self.original, &self.edited)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string()
+    }
+}
+
+/// The path of a `depends_on` entry, which is either a string or an inline table with a `path` key
+pub(crate) fn dependency_path(value: &toml_edit::Value) -> Option<&str> {
+    match value {
+        toml_edit::Value::String(path) => Some(path.value()),
+        toml_edit::Value::InlineTable(table) => table.get("path").and_then(|p| p.as_str()),
+        _ => None,
+    }
+}
+
+/// Apply an edit which changes an attribute of a module or one of its dependencies
+/// to the TOML table for that module. `dependency` is the dependency path as written in this config file.
+pub(crate) fn apply_attribute_edit(
+    table: &mut dyn toml_edit::TableLike,
+    edit: &ConfigEdit,
+    dependency: Option<&str>,
+) {
+    match edit {
+        ConfigEdit::MarkDependencyDeprecated { .. }
+        | ConfigEdit::UnmarkDependencyDeprecated { .. } => {
+            let Some(dependency) = dependency else {
+                return;
+            };
+            if let Some(toml_edit::Item::Value(toml_edit::Value::Array(array))) =
+                table.get_mut("depends_on")
+            {
+                set_dependency_deprecated(
+                    array,
+                    dependency,
+                    matches!(edit, ConfigEdit::MarkDependencyDeprecated { .. }),
+                );
+            }
+        }
+        ConfigEdit::SetModuleLayer { layer, .. } => match layer {
+            Some(layer) => {
+                table.insert("layer", toml_edit::value(layer));
+            }
+            None => {
+                table.remove("layer");
+            }
+        },
+        ConfigEdit::SetModuleMaxViolations { max_violations, .. } => match max_violations {
+            Some(max_violations) => {
+                table.insert("max_violations", toml_edit::value(*max_violations as i64));
+            }
+            None => {
+                table.remove("max_violations");
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Apply an edit which targets a module to the modules declared in a single config file, in memory.
+/// This has the same effect on the modules as writing the edit to the file and parsing it again.
+pub(crate) fn apply_module_edit(modules: &mut Vec<ModuleConfig>, edit: &ConfigEdit) {
+    match edit {
+        ConfigEdit::CreateModule { path } => {
+            if modules.iter().all(|module| module.path != *path) {
+                modules.push(ModuleConfig::new(path, false));
+            }
+        }
+        ConfigEdit::DeleteModule { path } => modules.retain(|module| module.path != *path),
+        _ => {
+            let Some(path) = edit.module_path() else {
+                return;
+            };
+            for module in modules.iter_mut().filter(|module| module.path == path) {
+                match edit {
+                    ConfigEdit::MarkModuleAsUtility { .. } => module.utility = true,
+                    ConfigEdit::UnmarkModuleAsUtility { .. } => module.utility = false,
+                    ConfigEdit::AddDependency { dependency, .. } => {
+                        let depends_on = module.depends_on.get_or_insert_with(Vec::new);
+                        if depends_on.iter().all(|dep| dep.path != *dependency) {
+                            depends_on.push(DependencyConfig::from_path(dependency));
+                        }
+                    }
+                    ConfigEdit::RemoveDependency { dependency, .. } => {
+                        if let Some(depends_on) = &mut module.depends_on {
+                            depends_on.retain(|dep| dep.path != *dependency);
+                        }
+                    }
+                    ConfigEdit::MarkDependencyDeprecated { dependency, .. }
+                    | ConfigEdit::UnmarkDependencyDeprecated { dependency, .. } => {
+                        let deprecated =
+                            matches!(edit, ConfigEdit::MarkDependencyDeprecated { .. });
+                        for dep in module
+                            .depends_on
+                            .iter_mut()
+                            .flatten()
+                            .filter(|dep| dep.path == *dependency)
+                        {
+                            dep.deprecated = deprecated;
+                            if !deprecated {
+                                dep.deprecated_until = None;
+                            }
+                        }
+                    }
+                    ConfigEdit::SetModuleLayer { layer, .. } => module.layer = layer.clone(),
+                    ConfigEdit::SetModuleMaxViolations { max_violations, .. } => {
+                        module.max_violations = *max_violations
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Deprecated dependencies are written as inline tables (`{ path = "...", deprecated = true }`),
+/// while dependencies without any other attributes are written as plain strings.
+fn set_dependency_deprecated(
+    depends_on: &mut toml_edit::Array,
+    dependency: &str,
+    deprecated: bool,
+) {
+    for value in depends_on.iter_mut() {
+        let replacement = match value {
+            toml_edit::Value::String(path) if path.value() == dependency && deprecated => {
+                let mut table = toml_edit::InlineTable::new();
+                table.insert("path", dependency.into());
+                table.insert("deprecated", true.into());
+                Some(toml_edit::Value::InlineTable(table))
+            }
+            toml_edit::Value::InlineTable(table)
+                if table.get("path").and_then(|p| p.as_str()) == Some(dependency) =>
+            {
+                if deprecated {
+                    table.insert("deprecated", true.into());
+                    None
+                } else {
+                    // Removing the deprecation also removes its grace period
+                    table.remove("deprecated");
+                    table.remove("deprecated_until");
+                    if table.len() == 1 {
+                        Some(dependency.into())
+                    } else {
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        if let Some(mut replacement) = replacement {
+            // Keep any surrounding whitespace and comments
+            *replacement.decor_mut() = value.decor().clone();
+            *value = replacement;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit_module(source: &str, edit: ConfigEdit, dependency: Option<&str>) -> String {
+        let mut doc = source.parse::<toml_edit::DocumentMut>().unwrap();
+        apply_attribute_edit(doc.as_table_mut(), &edit, dependency);
+        doc.to_string()
+    }
+
+    #[test]
+    fn test_mark_dependency_deprecated() {
+        let source = "path = \"a\"\ndepends_on = [\"b\", \"c\"]\n";
+        let edit = ConfigEdit::MarkDependencyDeprecated {
+            path: "a".to_string(),
+            dependency: "b".to_string(),
+        };
+        let edited = edit_module(source, edit, Some("b"));
+        assert_eq!(
+            edited,
+            "path = \"a\"\ndepends_on = [{ path = \"b\", deprecated = true }, \"c\"]\n"
+        );
+
+        let edit = ConfigEdit::UnmarkDependencyDeprecated {
+            path: "a".to_string(),
+            dependency: "b".to_string(),
+        };
+        assert_eq!(edit_module(&edited, edit, Some("b")), source);
+    }
+
+    #[test]
+    fn test_unmark_dependency_deprecated_keeps_annotations() {
+        let source = "path = \"a\"\ndepends_on = [{ path = \"b\", deprecated = true, reason = \"Legacy sync\", ticket = \"PROJ-1\" }]\n";
+        let edit = ConfigEdit::UnmarkDependencyDeprecated {
+            path: "a".to_string(),
+            dependency: "b".to_string(),
+        };
+        assert_eq!(
+            edit_module(source, edit, Some("b")),
+            "path = \"a\"\ndepends_on = [{ path = \"b\", reason = \"Legacy sync\", ticket = \"PROJ-1\" }]\n"
+        );
+    }
+
+    #[test]
+    fn test_set_module_layer_and_max_violations() {
+        let source = "path = \"a\"\n";
+        let edited = edit_module(
+            source,
+            ConfigEdit::SetModuleLayer {
+                path: "a".to_string(),
+                layer: Some("core".to_string()),
+            },
+            None,
+        );
+        let edited = edit_module(
+            &edited,
+            ConfigEdit::SetModuleMaxViolations {
+                path: "a".to_string(),
+                max_violations: Some(3),
+            },
+            None,
+        );
+        assert_eq!(
+            edited,
+            "path = \"a\"\nlayer = \"core\"\nmax_violations = 3\n"
+        );
+
+        let edited = edit_module(
+            &edited,
+            ConfigEdit::SetModuleLayer {
+                path: "a".to_string(),
+                layer: None,
+            },
+            None,
+        );
+        assert_eq!(edited, "path = \"a\"\nmax_violations = 3\n");
+    }
+
+    #[test]
+    fn test_apply_module_edits_in_memory() {
+        let mut modules = vec![ModuleConfig::new("a", false)];
+        for edit in [
+            ConfigEdit::CreateModule {
+                path: "b".to_string(),
+            },
+            ConfigEdit::AddDependency {
+                path: "a".to_string(),
+                dependency: "b".to_string(),
+            },
+            ConfigEdit::AddDependency {
+                path: "a".to_string(),
+                dependency: "b".to_string(),
+            },
+            ConfigEdit::MarkDependencyDeprecated {
+                path: "a".to_string(),
+                dependency: "b".to_string(),
+            },
+            ConfigEdit::SetModuleLayer {
+                path: "b".to_string(),
+                layer: Some("core".to_string()),
+            },
+        ] {
+            apply_module_edit(&mut modules, &edit);
+        }
+        assert_eq!(modules.len(), 2);
+        assert_eq!(
+            modules[0].depends_on,
+            Some(vec![DependencyConfig::from_deprecated_path("b")])
+        );
+        assert_eq!(modules[1].layer.as_deref(), Some("core"));
+
+        apply_module_edit(
+            &mut modules,
+            &ConfigEdit::DeleteModule {
+                path: "b".to_string(),
+            },
+        );
+        apply_module_edit(
+            &mut modules,
+            &ConfigEdit::RemoveDependency {
+                path: "a".to_string(),
+                dependency: "b".to_string(),
+            },
+        );
+        assert_eq!(modules, vec![ModuleConfig::new("a", false)]);
+    }
+
+    #[test]
+    fn test_preview_diff() {
+        let preview = ConfigEditPreview {
+            path: PathBuf::from("tach.toml"),
+            original: "path = \"a\"\nlayer = \"core\"\n".to_string(),
+            edited: "path = \"a\"\nmax_violations = 3\n".to_string(),
+        };
+        assert_eq!(
+            preview.diff(),
+            "--- tach.toml\n+++ tach.toml\n@@ -1,2 +1,2 @@\n path = \"a\"\n-layer = \"core\"\n+max_violations = 3\n"
+        );
+    }
+}
@@ -1,7 +1,46 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum ConfigError {
     #[error("Config file does not exist")]
     ConfigDoesNotExist,
+    #[error("Failed to parse config: {0}")]
+    Parse(String),
+    #[error("Unknown key '{key}' in {table}")]
+    UnknownKey {
+        table: String,
+        key: String,
+        suggestion: Option<String>,
+    },
+    #[error("Module '{module}' depends on '{dependency}', which is not defined")]
+    UndefinedDependency { module: String, dependency: String },
+    #[error("Module '{0}' is defined more than once")]
+    DuplicateModule(String),
+    #[error("Module '{0}' depends on itself")]
+    SelfDependency(String),
+    #[error("Module '{0}' could not be found in any source root")]
+    UnreachableModule(String),
+    #[error("Module '{module}' is mapped to '{path}', which does not exist")]
+    MissingModuleMapping { module: String, path: String },
+    #[error("Module '{module}' allows imports of '{entry}' in its internal structure, but '{entry}' is not an entry")]
+    UnknownInternalStructureEntry { module: String, entry: String },
+    #[error("WebAssembly plugin '{0}' is declared, but this build of tach does not support WebAssembly plugins. Rebuild it with the 'wasm' feature to run them.")]
+    WasmUnsupported(String),
+}
+
+impl ConfigError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConfigError::ConfigDoesNotExist => "config_does_not_exist",
+            ConfigError::Parse(_) => "parse",
+            ConfigError::UnknownKey { .. } => "unknown_key",
+            ConfigError::UndefinedDependency { .. } => "undefined_dependency",
+            ConfigError::DuplicateModule(_) => "duplicate_module",
+            ConfigError::SelfDependency(_) => "self_dependency",
+            ConfigError::UnreachableModule(_) => "unreachable_module",
+            ConfigError::MissingModuleMapping { .. } => "missing_module_mapping",
+            ConfigError::UnknownInternalStructureEntry { .. } => "unknown_internal_structure_entry",
+            ConfigError::WasmUnsupported(_) => "wasm_unsupported",
+        }
+    }
 }
@@ -0,0 +1,39 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Weights of the penalties which make up the health score of each module.
+/// A module starts with a score of 100, and loses the weight of each penalty
+/// multiplied by the size of that penalty (down to a score of 0).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+#[pyclass(get_all, set_all, module = "tach.extension")]
+pub struct HealthConfig {
+    // Per error reported in the module
+    pub violations: f64,
+    // When the module takes part in a cycle of imports
+    pub cycles: f64,
+    // Multiplied by the instability of the module (fan-out / (fan-in + fan-out))
+    pub instability: f64,
+    // Multiplied by the share of imports from other modules which bypass the module's interface
+    pub interface_coverage: f64,
+    // Per ignore directive in the module
+    pub suppressions: f64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            violations: 5.0,
+            cycles: 15.0,
+            instability: 10.0,
+            interface_coverage: 15.0,
+            suppressions: 2.0,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
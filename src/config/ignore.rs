@@ -0,0 +1,29 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::utils::{default_true, is_true};
+
+/// How paths are excluded from checks, alongside the gitignore-style patterns in 'exclude'
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, set_all, module = "tach.extension")]
+pub struct IgnoreConfig {
+    // Whether paths ignored by '.gitignore' files (and '.git/info/exclude') are excluded.
+    // '.tachignore' files are always read.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub respect_gitignore: bool,
+}
+
+impl Default for IgnoreConfig {
+    fn default() -> Self {
+        Self {
+            respect_gitignore: true,
+        }
+    }
+}
+
+impl IgnoreConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -1,23 +1,42 @@
 pub mod cache;
+pub mod constraints;
 pub mod domain;
 pub mod edit;
 pub mod error;
 pub mod external;
+pub mod health;
+pub mod ignore;
 pub mod interfaces;
 pub mod modules;
+pub mod normalizers;
+pub mod otlp;
 pub mod plugins;
 pub mod project;
 pub mod root_module;
 pub mod rules;
+pub mod stability;
+pub mod symlinks;
+pub mod toml_layout;
 pub mod utils;
+pub mod write;
+pub mod yaml;
 
-pub use cache::{CacheBackend, CacheConfig};
+pub use cache::{CacheBackend, CacheConfig, RedisCacheConfig, RemoteCacheConfig};
+pub use constraints::{ForbidRuleConfig, NamingRuleConfig};
 pub use domain::{ConfigLocation, DomainConfig, LocatedDomainConfig};
 pub use edit::ConfigEdit;
 pub use error::ConfigError;
 pub use external::ExternalDependencyConfig;
+pub use health::HealthConfig;
+pub use ignore::IgnoreConfig;
 pub use interfaces::{InterfaceConfig, InterfaceDataTypes};
 pub use modules::{serialize_modules_json, DependencyConfig, ModuleConfig};
+pub use normalizers::ModulePathNormalizer;
+pub use otlp::OtlpConfig;
 pub use plugins::PluginsConfig;
 pub use project::ProjectConfig;
-pub use rules::{RuleSetting, RulesConfig};
+pub use rules::{
+    EnvironmentRulesConfig, ModuleRulesConfig, ReexportResolution, RuleSetting, RulesConfig,
+};
+pub use stability::Stability;
+pub use symlinks::SymlinkPolicy;
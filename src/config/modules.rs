@@ -1,13 +1,15 @@
 use crate::filesystem::module_path_is_included_in_paths;
 
 use super::root_module::ROOT_MODULE_SENTINEL_TAG;
+use super::rules::ModuleRulesConfig;
+use super::stability::Stability;
 use super::utils::*;
 use pyo3::prelude::*;
 use serde::ser::{Error, SerializeSeq, SerializeStruct};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::path::PathBuf;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
 };
 
@@ -16,6 +18,15 @@ use std::{
 pub struct DependencyConfig {
     pub path: String,
     pub deprecated: bool,
+    // ISO date (YYYY-MM-DD) from which the deprecated dependency is no longer allowed
+    pub deprecated_until: Option<String>,
+    // Why the dependency exists, and a tracking ticket (e.g. for removing it)
+    pub reason: Option<String>,
+    pub ticket: Option<String>,
+    // When present, only these top-level members of the dependency may be imported
+    pub members: Option<Vec<String>>,
+    // When present, only these top-level members of the dependency are deprecated
+    pub deprecated_members: Option<Vec<String>>,
 }
 
 impl Serialize for DependencyConfig {
@@ -24,31 +35,169 @@ impl Serialize for DependencyConfig {
         S: serde::Serializer,
     {
         // Should actually express that all fields are default except for path
-        if !self.deprecated {
+        if !self.deprecated
+            && self.deprecated_until.is_none()
+            && self.reason.is_none()
+            && self.ticket.is_none()
+            && self.members.is_none()
+            && self.deprecated_members.is_none()
+        {
             serializer.serialize_str(&self.path)
         } else {
-            let mut state = serializer.serialize_struct("DependencyConfig", 2)?;
+            let mut state = serializer.serialize_struct("DependencyConfig", 7)?;
             state.serialize_field("path", &self.path)?;
-            state.serialize_field("deprecated", &self.deprecated)?;
+            if self.deprecated {
+                state.serialize_field("deprecated", &self.deprecated)?;
+            } else {
+                state.skip_field("deprecated")?;
+            }
+            if let Some(deprecated_until) = &self.deprecated_until {
+                state.serialize_field("deprecated_until", deprecated_until)?;
+            } else {
+                state.skip_field("deprecated_until")?;
+            }
+            if let Some(reason) = &self.reason {
+                state.serialize_field("reason", reason)?;
+            } else {
+                state.skip_field("reason")?;
+            }
+            if let Some(ticket) = &self.ticket {
+                state.serialize_field("ticket", ticket)?;
+            } else {
+                state.skip_field("ticket")?;
+            }
+            if let Some(members) = &self.members {
+                state.serialize_field("members", members)?;
+            } else {
+                state.skip_field("members")?;
+            }
+            if let Some(deprecated_members) = &self.deprecated_members {
+                state.serialize_field("deprecated_members", deprecated_members)?;
+            } else {
+                state.skip_field("deprecated_members")?;
+            }
             state.end()
         }
     }
 }
 
 impl DependencyConfig {
+    /// The keys accepted when a dependency is declared as a table
+    pub const FIELDS: &'static [&'static str] = &[
+        "path",
+        "deprecated",
+        "deprecated_until",
+        "reason",
+        "ticket",
+        "members",
+        "deprecated_members",
+    ];
+
     pub fn from_deprecated_path(path: impl Into<String>) -> Self {
         Self {
             path: path.into(),
             deprecated: true,
+            ..Default::default()
         }
     }
     pub fn from_path(path: impl Into<String>) -> Self {
         Self {
             path: path.into(),
-            deprecated: false,
+            ..Default::default()
+        }
+    }
+
+    /// A grace period implies that the dependency is deprecated,
+    /// unless it only applies to the 'deprecated_members'
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated || (self.deprecated_until.is_some() && self.deprecated_members.is_none())
+    }
+
+    /// Whether importing 'member' is deprecated, either with the whole dependency or on its own
+    pub fn deprecates_member(&self, member: Option<&str>) -> bool {
+        self.is_deprecated()
+            || self
+                .deprecated_members
+                .as_ref()
+                .is_some_and(|deprecated_members| {
+                    member.is_some_and(|member| deprecated_members.iter().any(|m| m == member))
+                })
+    }
+
+    /// Whether the grace period of the deprecated dependency has ended as of 'today' (YYYY-MM-DD).
+    /// Dates are validated when deserialized, so they can be compared as strings.
+    pub fn is_past_deprecation(&self, today: &str) -> bool {
+        self.deprecated_until
+            .as_deref()
+            .is_some_and(|deprecated_until| today >= deprecated_until)
+    }
+
+    /// The declared reason and ticket of the dependency, e.g. 'legacy billing sync (PROJ-123)'
+    pub fn annotation(&self) -> Option<String> {
+        match (&self.reason, &self.ticket) {
+            (Some(reason), Some(ticket)) => Some(format!("{} ({})", reason, ticket)),
+            (Some(reason), None) => Some(reason.clone()),
+            (None, Some(ticket)) => Some(ticket.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether the dependency allows importing 'member' (None when the module itself is imported)
+    pub fn allows_member(&self, member: Option<&str>) -> bool {
+        match &self.members {
+            None => true,
+            Some(members) => member.is_some_and(|member| members.iter().any(|m| m == member)),
         }
     }
 }
+
+fn is_iso_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    match (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) {
+        (Ok(_), Ok(month), Ok(day)) => (1..=12).contains(&month) && (1..=31).contains(&day),
+        _ => false,
+    }
+}
+
+/// The current UTC date as YYYY-MM-DD
+pub fn current_date() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / 86400)
+        .unwrap_or_default() as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Converts days since the Unix epoch into a proleptic Gregorian (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = (if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
 struct DependencyConfigVisitor;
 
 impl<'de> de::Visitor<'de> for DependencyConfigVisitor {
@@ -75,6 +224,11 @@ impl<'de> de::Visitor<'de> for DependencyConfigVisitor {
     {
         let mut path = None;
         let mut deprecated = false;
+        let mut deprecated_until: Option<String> = None;
+        let mut reason: Option<String> = None;
+        let mut ticket: Option<String> = None;
+        let mut members: Option<Vec<String>> = None;
+        let mut deprecated_members: Option<Vec<String>> = None;
 
         while let Some(key) = map.next_key::<String>()? {
             match key.as_str() {
@@ -92,15 +246,60 @@ impl<'de> de::Visitor<'de> for DependencyConfigVisitor {
                     }
                     deprecated = map.next_value()?;
                 }
+                "deprecated_until" => {
+                    if deprecated_until.is_some() {
+                        return Err(de::Error::duplicate_field("deprecated_until"));
+                    }
+                    let value: String = map.next_value()?;
+                    if !is_iso_date(&value) {
+                        return Err(de::Error::custom(format!(
+                            "invalid 'deprecated_until' date '{}', expected YYYY-MM-DD",
+                            value
+                        )));
+                    }
+                    deprecated_until = Some(value);
+                }
+                "reason" => {
+                    if reason.is_some() {
+                        return Err(de::Error::duplicate_field("reason"));
+                    }
+                    reason = Some(map.next_value()?);
+                }
+                "ticket" => {
+                    if ticket.is_some() {
+                        return Err(de::Error::duplicate_field("ticket"));
+                    }
+                    ticket = Some(map.next_value()?);
+                }
+                "members" => {
+                    if members.is_some() {
+                        return Err(de::Error::duplicate_field("members"));
+                    }
+                    members = Some(map.next_value()?);
+                }
+                "deprecated_members" => {
+                    if deprecated_members.is_some() {
+                        return Err(de::Error::duplicate_field("deprecated_members"));
+                    }
+                    deprecated_members = Some(map.next_value()?);
+                }
                 _ => {
-                    return Err(de::Error::unknown_field(&key, &["path", "deprecated"]));
+                    return Err(de::Error::unknown_field(&key, DependencyConfig::FIELDS));
                 }
             }
         }
 
         let path = path.ok_or_else(|| de::Error::missing_field("path"))?;
 
-        Ok(DependencyConfig { path, deprecated })
+        Ok(DependencyConfig {
+            path,
+            deprecated,
+            deprecated_until,
+            reason,
+            ticket,
+            members,
+            deprecated_members,
+        })
     }
 }
 
@@ -131,6 +330,13 @@ pub struct ModuleConfig {
     pub depends_on: Option<Vec<DependencyConfig>>,
     #[serde(default)]
     pub layer: Option<String>,
+    // Modules declared 'stable' cannot depend on 'experimental' modules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<Stability>,
+    // Values of 'sys.platform' (e.g. 'linux', 'win32') this module is limited to. Other modules
+    // must guard their imports of it with a matching check. Empty when it runs on every platform.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
     #[serde(
         default = "default_visibility",
         skip_serializing_if = "is_default_visibility"
@@ -146,6 +352,22 @@ pub struct ModuleConfig {
     pub strict: bool,
     #[serde(default, skip_serializing_if = "is_false")]
     pub unchecked: bool,
+    // Human-readable name used in diagrams and reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    // Number of dependency and interface errors tolerated before 'tach check' fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_violations: Option<usize>,
+    // Maps a submodule (relative to this module) to the submodules it may import.
+    // When present, imports between files within this module are checked against it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub internal_structure: BTreeMap<String, Vec<String>>,
+    // Overrides of the project's rules for imports made by this module
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<ModuleRulesConfig>,
+    // An interface module, or a class within one (e.g. a 'Protocol'), whose members this module must define
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implements: Option<String>,
     // Hidden field to track grouping
     // Unfortunately marked as public due to test fixtures constructing struct literals
     #[serde(skip)]
@@ -158,10 +380,17 @@ impl Default for ModuleConfig {
             path: Default::default(),
             depends_on: Some(vec![]),
             layer: Default::default(),
+            stability: Default::default(),
+            platforms: Default::default(),
             visibility: default_visibility(),
             utility: Default::default(),
             strict: Default::default(),
             unchecked: Default::default(),
+            display_name: Default::default(),
+            max_violations: Default::default(),
+            internal_structure: Default::default(),
+            rules: Default::default(),
+            implements: Default::default(),
             group_id: Default::default(),
         }
     }
@@ -174,14 +403,36 @@ impl ModuleConfig {
             path: path.to_string(),
             depends_on: Some(vec![]),
             layer: Some(layer.to_string()),
+            stability: None,
+            platforms: vec![],
             visibility: default_visibility(),
             utility: false,
             strict: false,
             unchecked: false,
+            display_name: None,
+            max_violations: None,
+            internal_structure: BTreeMap::new(),
+            rules: None,
+            implements: None,
             group_id: None,
         }
     }
 
+    /// The entry of `internal_structure` which contains the given module path, if any.
+    /// When entries are nested, the most specific one is used.
+    pub fn internal_structure_entry(&self, mod_path: &str) -> Option<&str> {
+        let relative_path = mod_path.strip_prefix(&self.path)?.strip_prefix('.')?;
+        self.internal_structure
+            .keys()
+            .filter(|entry| {
+                relative_path
+                    .strip_prefix(entry.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .max_by_key(|entry| entry.len())
+            .map(|entry| entry.as_str())
+    }
+
     pub fn dependencies_iter(&self) -> impl Iterator<Item = &DependencyConfig> {
         self.depends_on
             .as_ref()
@@ -244,10 +495,17 @@ impl ModuleConfig {
             path: path.to_string(),
             depends_on: Some(vec![]),
             layer: None,
+            stability: None,
+            platforms: vec![],
             visibility: default_visibility(),
             utility: false,
             strict,
             unchecked: false,
+            display_name: None,
+            max_violations: None,
+            internal_structure: BTreeMap::new(),
+            rules: None,
+            implements: None,
             group_id: None,
         }
     }
@@ -258,6 +516,14 @@ impl ModuleConfig {
         }
         self.path.clone()
     }
+
+    /// The name to show for this module in human-facing output (diagrams, reports).
+    /// Machine-readable formats should always use 'path' instead.
+    pub fn label(&self) -> String {
+        self.display_name
+            .clone()
+            .unwrap_or_else(|| self.path.clone())
+    }
 }
 
 pub fn serialize_modules_json(modules: &Vec<ModuleConfig>) -> String {
@@ -275,6 +541,10 @@ struct BulkModule {
     depends_on: Option<Vec<DependencyConfig>>,
     #[serde(default)]
     layer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stability: Option<Stability>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    platforms: Vec<String>,
     #[serde(
         default = "default_visibility",
         skip_serializing_if = "is_default_visibility"
@@ -299,6 +569,8 @@ impl TryFrom<&[&ModuleConfig]> for BulkModule {
             paths: modules.iter().map(|m| m.path.clone()).collect(),
             depends_on: None,
             layer: first.layer.clone(),
+            stability: first.stability,
+            platforms: first.platforms.clone(),
             visibility: first.visibility.clone(),
             utility: first.utility,
             unchecked: first.unchecked,
@@ -317,6 +589,18 @@ impl TryFrom<&[&ModuleConfig]> for BulkModule {
                     module.path
                 ));
             }
+            if module.stability != first.stability {
+                return Err(format!(
+                    "Inconsistent stability in bulk module group for path {}",
+                    module.path
+                ));
+            }
+            if module.platforms != first.platforms {
+                return Err(format!(
+                    "Inconsistent platforms in bulk module group for path {}",
+                    module.path
+                ));
+            }
             if module.visibility != first.visibility {
                 return Err(format!(
                     "Inconsistent visibility in bulk module group for path {}",
@@ -341,6 +625,36 @@ impl TryFrom<&[&ModuleConfig]> for BulkModule {
                     module.path
                 ));
             }
+            if module.display_name.is_some() {
+                return Err(format!(
+                    "Display names are not supported in bulk module groups (path {})",
+                    module.path
+                ));
+            }
+            if module.max_violations.is_some() {
+                return Err(format!(
+                    "Violation budgets are not supported in bulk module groups (path {})",
+                    module.path
+                ));
+            }
+            if !module.internal_structure.is_empty() {
+                return Err(format!(
+                    "Internal structure is not supported in bulk module groups (path {})",
+                    module.path
+                ));
+            }
+            if module.rules.is_some() {
+                return Err(format!(
+                    "Rule overrides are not supported in bulk module groups (path {})",
+                    module.path
+                ));
+            }
+            if module.implements.is_some() {
+                return Err(format!(
+                    "Implemented interfaces are not supported in bulk module groups (path {})",
+                    module.path
+                ));
+            }
         }
 
         if !unique_deps.is_empty() {
@@ -409,13 +723,148 @@ where
                     path,
                     depends_on: bulk.depends_on.clone(),
                     layer: bulk.layer.clone(),
+                    stability: bulk.stability,
+                    platforms: bulk.platforms.clone(),
                     visibility: bulk.visibility.clone(),
                     utility: bulk.utility,
                     strict: false,
                     unchecked: bulk.unchecked,
+                    display_name: None,
+                    max_violations: None,
+                    internal_structure: BTreeMap::new(),
+                    rules: None,
+                    implements: None,
                     group_id: Some(i),
                 })
                 .collect(),
         })
         .collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        let module = ModuleConfig::new("billing.invoices", false);
+        assert_eq!(module.label(), "billing.invoices");
+
+        let module = ModuleConfig {
+            display_name: Some("Invoicing".to_string()),
+            ..module
+        };
+        assert_eq!(module.label(), "Invoicing");
+        assert_eq!(module.mod_path(), "billing.invoices");
+    }
+
+    #[test]
+    fn test_internal_structure_entry() {
+        let module = ModuleConfig {
+            internal_structure: BTreeMap::from([
+                ("api".to_string(), vec!["services".to_string()]),
+                ("services".to_string(), vec![]),
+                ("services.payments".to_string(), vec![]),
+            ]),
+            ..ModuleConfig::new("billing", false)
+        };
+
+        assert_eq!(module.internal_structure_entry("billing.api"), Some("api"));
+        assert_eq!(
+            module.internal_structure_entry("billing.api.handler"),
+            Some("api")
+        );
+        assert_eq!(
+            module.internal_structure_entry("billing.services.payments.stripe"),
+            Some("services.payments")
+        );
+        assert_eq!(module.internal_structure_entry("billing.apis"), None);
+        assert_eq!(module.internal_structure_entry("billing"), None);
+        assert_eq!(module.internal_structure_entry("other.api"), None);
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20332), (2025, 9, 1));
+    }
+
+    #[test]
+    fn test_deprecated_until() {
+        let dependency: DependencyConfig = toml::from_str::<ModuleConfig>(
+            "path = \"a\"\ndepends_on = [{ path = \"b\", deprecated_until = \"2025-09-01\" }]",
+        )
+        .unwrap()
+        .depends_on
+        .unwrap()
+        .remove(0);
+
+        assert!(dependency.is_deprecated());
+        assert!(!dependency.is_past_deprecation("2025-08-31"));
+        assert!(dependency.is_past_deprecation("2025-09-01"));
+        assert!(!DependencyConfig::from_deprecated_path("b").is_past_deprecation("2025-09-01"));
+
+        assert!(toml::from_str::<ModuleConfig>(
+            "path = \"a\"\ndepends_on = [{ path = \"b\", deprecated_until = \"09/01/2025\" }]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_dependency_annotations() {
+        let source = "path = \"a\"\ndepends_on = [\"b\", { path = \"c\", reason = \"Shared billing models\", ticket = \"PROJ-123\" }]\n";
+        let module = toml::from_str::<ModuleConfig>(source).unwrap();
+        let depends_on = module.depends_on.as_ref().unwrap();
+
+        assert_eq!(depends_on[0].annotation(), None);
+        assert_eq!(
+            depends_on[1].annotation().as_deref(),
+            Some("Shared billing models (PROJ-123)")
+        );
+        assert!(!depends_on[1].is_deprecated());
+
+        // Annotated dependencies are serialized as tables, while plain ones stay strings
+        assert_eq!(serde_json::to_string(&depends_on[0]).unwrap(), "\"b\"");
+        assert_eq!(
+            serde_json::to_string(&depends_on[1]).unwrap(),
+            "{\"path\":\"c\",\"reason\":\"Shared billing models\",\"ticket\":\"PROJ-123\"}"
+        );
+    }
+    #[test]
+    fn test_dependency_members() {
+        let source =
+            "path = \"a\"\ndepends_on = [\"b\", { path = \"c\", members = [\"Client\"] }]\n";
+        let module = toml::from_str::<ModuleConfig>(source).unwrap();
+        let depends_on = module.depends_on.as_ref().unwrap();
+
+        assert!(depends_on[0].allows_member(Some("Anything")));
+        assert!(depends_on[0].allows_member(None));
+        assert!(depends_on[1].allows_member(Some("Client")));
+        assert!(!depends_on[1].allows_member(Some("Config")));
+        // Importing the module itself would give access to every member
+        assert!(!depends_on[1].allows_member(None));
+        assert_eq!(
+            serde_json::to_string(&depends_on[1]).unwrap(),
+            "{\"path\":\"c\",\"members\":[\"Client\"]}"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_members() {
+        let source = "path = \"a\"\ndepends_on = [{ path = \"legacy.api\", deprecated_members = [\"old_client\"], deprecated_until = \"2025-09-01\" }]\n";
+        let module = toml::from_str::<ModuleConfig>(source).unwrap();
+        let dependency = &module.depends_on.as_ref().unwrap()[0];
+
+        // The grace period only applies to the deprecated members
+        assert!(!dependency.is_deprecated());
+        assert!(dependency.deprecates_member(Some("old_client")));
+        assert!(!dependency.deprecates_member(Some("new_client")));
+        assert!(!dependency.deprecates_member(None));
+        assert!(DependencyConfig::from_deprecated_path("b").deprecates_member(None));
+        assert_eq!(
+            serde_json::to_string(dependency).unwrap(),
+            "{\"path\":\"legacy.api\",\"deprecated_until\":\"2025-09-01\",\"deprecated_members\":[\"old_client\"]}"
+        );
+    }
+}
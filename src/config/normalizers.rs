@@ -0,0 +1,84 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A built-in rewrite of the directory components of a file's module path,
+/// for projects whose layout does not match their import paths
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModulePathNormalizer {
+    /// Drop a leading 'src' directory (e.g. 'src.pkg.api' -> 'pkg.api')
+    StripSrc,
+    /// Drop version suffixes from directory names (e.g. 'pkg_v2.api' -> 'pkg.api')
+    StripVersionSuffix,
+    /// Attribute unit tests to the package they test (e.g. 'tests.unit.pkg.test_api' -> 'pkg.test_api')
+    UnitTests,
+}
+
+fn strip_version_suffix(component: &str) -> &str {
+    match component.rsplit_once("_v") {
+        Some((name, version))
+            if !name.is_empty()
+                && !version.is_empty()
+                && version.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            name
+        }
+        _ => component,
+    }
+}
+
+impl ModulePathNormalizer {
+    /// Rewrite the directory components of a module path.
+    /// The file's own name is never rewritten.
+    pub fn normalize(&self, components: &mut Vec<&str>) {
+        match self {
+            Self::StripSrc => {
+                if components.first() == Some(&"src") {
+                    components.remove(0);
+                }
+            }
+            Self::StripVersionSuffix => {
+                for component in components.iter_mut() {
+                    *component = strip_version_suffix(*component);
+                }
+            }
+            Self::UnitTests => {
+                if components.len() > 2 && components[..2] == ["tests", "unit"] {
+                    components.drain(..2);
+                }
+            }
+        }
+    }
+}
+
+impl IntoPy<PyObject> for ModulePathNormalizer {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Self::StripSrc => "strip-src".to_object(py),
+            Self::StripVersionSuffix => "strip-version-suffix".to_object(py),
+            Self::UnitTests => "unit-tests".to_object(py),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(ModulePathNormalizer::StripSrc, vec!["src", "pkg"], vec!["pkg"])]
+    #[case(ModulePathNormalizer::StripSrc, vec!["pkg", "src"], vec!["pkg", "src"])]
+    #[case(ModulePathNormalizer::StripVersionSuffix, vec!["pkg_v2", "api_v10"], vec!["pkg", "api"])]
+    #[case(ModulePathNormalizer::StripVersionSuffix, vec!["_v2", "pkg_vx", "pkg_v"], vec!["_v2", "pkg_vx", "pkg_v"])]
+    #[case(ModulePathNormalizer::UnitTests, vec!["tests", "unit", "pkg"], vec!["pkg"])]
+    #[case(ModulePathNormalizer::UnitTests, vec!["tests", "unit"], vec!["tests", "unit"])]
+    fn test_normalize(
+        #[case] normalizer: ModulePathNormalizer,
+        #[case] mut components: Vec<&str>,
+        #[case] expected: Vec<&str>,
+    ) {
+        normalizer.normalize(&mut components);
+        assert_eq!(components, expected);
+    }
+}
@@ -0,0 +1,26 @@
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Export of the spans of each analysis phase (e.g. 'check' and 'walk_files') to an
+/// OpenTelemetry collector. Requires tach to be built with the 'otel' feature.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct OtlpConfig {
+    pub enabled: bool,
+    // The OTLP/HTTP traces endpoint (e.g. "http://localhost:4318/v1/traces").
+    // When unset, the standard OTEL_EXPORTER_OTLP_* environment variables are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    // Added to every exported span (e.g. the repository or team), to aggregate across projects
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub resource_attributes: BTreeMap<String, String>,
+}
+
+impl OtlpConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -1,17 +1,28 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::custom::CustomPluginConfig;
 use super::django::DjangoConfig;
+use super::wasm::WasmPluginConfig;
 
 #[derive(Debug, Serialize, Default, Deserialize, Clone, PartialEq)]
 #[pyclass(get_all, module = "tach.extension")]
 pub struct PluginsConfig {
     #[serde(default)]
     pub django: Option<DjangoConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomPluginConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wasm: Vec<WasmPluginConfig>,
 }
 
 impl PluginsConfig {
     pub fn is_default(&self) -> bool {
         *self == Self::default()
     }
+
+    /// Whether any plugin adds checks to 'tach check'
+    pub fn has_check_plugins(&self) -> bool {
+        !self.custom.is_empty() || !self.wasm.is_empty()
+    }
 }
@@ -0,0 +1,25 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::RuleSetting;
+
+/// A check plugin implemented in Python, declared with '[[plugins.custom]]'
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(module = "tach.extension")]
+pub struct CustomPluginConfig {
+    #[pyo3(get)]
+    pub name: String,
+    // The object which implements the plugin, as 'package.module:attribute'
+    #[pyo3(get)]
+    pub path: String,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    #[pyo3(get)]
+    pub severity: RuleSetting,
+    // Passed to the plugin as a dict when it is created
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub options: toml::Table,
+}
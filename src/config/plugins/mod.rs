@@ -1,4 +1,8 @@
 mod all;
+pub mod custom;
 pub mod django;
+pub mod wasm;
 
 pub use all::PluginsConfig;
+pub use custom::CustomPluginConfig;
+pub use wasm::WasmPluginConfig;
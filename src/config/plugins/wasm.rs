@@ -0,0 +1,25 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::RuleSetting;
+
+/// A check plugin compiled to WebAssembly, declared with '[[plugins.wasm]]'
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(module = "tach.extension")]
+pub struct WasmPluginConfig {
+    #[pyo3(get)]
+    pub name: String,
+    // The '.wasm' file which implements the plugin, relative to the project root
+    #[pyo3(get)]
+    pub path: String,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    #[pyo3(get)]
+    pub severity: RuleSetting,
+    // Passed to the plugin alongside the checked files
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub options: toml::Table,
+}
@@ -1,26 +1,43 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::iter;
 use std::path::{Path, PathBuf};
 
-use crate::filesystem::module_path_is_included_in_paths;
+use crate::filesystem::{module_path_is_included_in_paths, validate_project_modules};
+use crate::python::parsing::parse_interface_members;
 
 use super::cache::CacheConfig;
+use super::constraints::{ForbidRuleConfig, NamingRuleConfig};
 use super::domain::LocatedDomainConfig;
-use super::edit::{ConfigEdit, ConfigEditor, EditError};
+use super::edit::{
+    apply_attribute_edit, apply_module_edit, dependency_path, ConfigEdit, ConfigEditPreview,
+    ConfigEditor, EditError,
+};
 use super::error::ConfigError;
 use super::external::ExternalDependencyConfig;
+use super::health::HealthConfig;
+use super::ignore::IgnoreConfig;
 use super::interfaces::InterfaceConfig;
 use super::modules::{deserialize_modules, serialize_modules, DependencyConfig, ModuleConfig};
+use super::normalizers::ModulePathNormalizer;
+use super::otlp::OtlpConfig;
 use super::plugins::PluginsConfig;
 use super::root_module::RootModuleTreatment;
-use super::rules::RulesConfig;
+use super::rules::{RuleSetting, RulesConfig};
+use super::symlinks::SymlinkPolicy;
+use super::toml_layout;
 use super::utils::*;
+use super::write::{backup_files, write_atomic};
+use super::yaml;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 #[pyclass(module = "tach.extension")]
 pub struct ProjectConfig {
+    #[serde(default = "legacy_schema_version")]
+    #[pyo3(get)]
+    pub schema_version: u32,
     #[serde(
         default,
         deserialize_with = "deserialize_modules",
@@ -40,18 +57,46 @@ pub struct ProjectConfig {
     #[serde(default, skip_serializing_if = "ExternalDependencyConfig::is_default")]
     #[pyo3(get)]
     pub external: ExternalDependencyConfig,
+    // Gitignore-style patterns of paths relative to the project root
     #[serde(default)]
     #[pyo3(get, set)]
     pub exclude: Vec<String>,
+    #[serde(default, skip_serializing_if = "IgnoreConfig::is_default")]
+    #[pyo3(get)]
+    pub ignore: IgnoreConfig,
+    #[serde(default, skip_serializing_if = "HealthConfig::is_default")]
+    #[pyo3(get)]
+    pub health: HealthConfig,
+    #[serde(default, skip_serializing_if = "OtlpConfig::is_default")]
+    #[pyo3(get)]
+    pub otlp: OtlpConfig,
     #[serde(default = "default_source_roots")]
     #[pyo3(get, set)]
     pub source_roots: Vec<PathBuf>,
+    // Python version targeted by the project's source code (e.g. "3.12"), which determines the standard library
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[pyo3(get)]
+    pub python_version: Option<String>,
+    // Source roots which target a different Python version than the rest of the project
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[pyo3(get)]
+    pub source_root_python_versions: BTreeMap<PathBuf, String>,
     #[serde(default, skip_serializing_if = "is_false")]
     #[pyo3(get)]
     pub exact: bool,
+    // Budget for the violations of modules which do not have a 'max_violations' of their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[pyo3(get)]
+    pub max_violations: Option<usize>,
     #[serde(default, skip_serializing_if = "is_false")]
     #[pyo3(get)]
     pub disable_logging: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub record_usage_stats: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub record_history: bool,
     #[serde(default = "default_true", skip_serializing_if = "is_true")]
     #[pyo3(get, set)]
     pub ignore_type_checking_imports: bool,
@@ -59,29 +104,95 @@ pub struct ProjectConfig {
     #[pyo3(get, set)]
     pub include_string_imports: bool,
     #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get, set)]
+    pub analyze_attribute_access: bool,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    #[pyo3(get)]
+    pub namespace_packages: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub include_stubs: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub include_notebooks: bool,
+    #[serde(default, skip_serializing_if = "SymlinkPolicy::is_default")]
+    #[pyo3(get)]
+    pub symlinks: SymlinkPolicy,
+    // Applied in order when attributing a file to a module
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[pyo3(get)]
+    pub module_path_normalizers: Vec<ModulePathNormalizer>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub implicit_modules: bool,
+    // Read module configs declared with '__tach__' in each package's '__init__.py'
+    #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub inline_config: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
     #[pyo3(get)]
     pub forbid_circular_dependencies: bool,
     #[serde(default, skip_serializing_if = "is_false")]
+    #[pyo3(get)]
+    pub forbid_circular_domain_dependencies: bool,
+    // Regex exclude patterns are no longer supported, this is only read to report an error when it is set
+    #[serde(default, skip_serializing_if = "is_false")]
     #[pyo3(get, set)]
     pub use_regex_matching: bool,
     #[serde(default, skip_serializing_if = "RootModuleTreatment::is_default")]
     #[pyo3(get)]
     pub root_module: RootModuleTreatment,
+    // Import paths of modules without Python source (e.g. compiled extensions), mapped to a file relative to the project root
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[pyo3(get)]
+    pub module_mappings: BTreeMap<String, PathBuf>,
+    // Glob patterns of module paths which are run directly instead of being imported (e.g. CLIs and scripts)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[pyo3(get)]
+    pub entrypoints: Vec<String>,
     #[serde(default, skip_serializing_if = "RulesConfig::is_default")]
     #[pyo3(get)]
     pub rules: RulesConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[pyo3(get)]
+    pub forbid: Vec<ForbidRuleConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[pyo3(get)]
+    pub naming: Vec<NamingRuleConfig>,
     #[serde(default, skip_serializing_if = "PluginsConfig::is_default")]
     #[pyo3(get)]
     pub plugins: PluginsConfig,
     #[serde(skip)]
     pub domains: Vec<LocatedDomainConfig>,
+    // Modules for top-level packages which are not declared explicitly, when 'implicit_modules' is set
+    #[serde(skip)]
+    pub inferred_modules: Vec<ModuleConfig>,
+    // Modules (and interfaces, for 'strict' modules) which are only declared with '__tach__'
+    #[serde(skip)]
+    pub inline_modules: Vec<ModuleConfig>,
+    #[serde(skip)]
+    pub inline_interfaces: Vec<InterfaceConfig>,
     #[serde(skip)]
     pub pending_edits: Vec<ConfigEdit>,
+    // Whether config files are backed up to '.tach/backups' before edits are written to them
+    #[serde(skip, default = "default_true")]
+    #[pyo3(get, set)]
+    pub backup_edits: bool,
     // If location is None, the config is not on disk
     #[serde(skip)]
     pub location: Option<PathBuf>,
 }
 
+/// The version of the config layout written by this version of tach.
+/// Run `tach config migrate` to upgrade configs with an older version.
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+/// Configs written before the schema was versioned
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+fn legacy_schema_version() -> u32 {
+    LEGACY_SCHEMA_VERSION
+}
+
 pub fn default_source_roots() -> Vec<PathBuf> {
     vec![PathBuf::from(".")]
 }
@@ -106,24 +217,50 @@ impl Default for ProjectConfig {
         Self {
             // special defaults
             exclude: default_excludes(),
+            ignore: Default::default(),
+            health: Default::default(),
+            otlp: Default::default(),
             source_roots: default_source_roots(),
             ignore_type_checking_imports: true,
+            namespace_packages: true,
+            schema_version: CONFIG_SCHEMA_VERSION,
             // normal defaults
             modules: Default::default(),
             interfaces: Default::default(),
             layers: Default::default(),
+            python_version: Default::default(),
+            source_root_python_versions: Default::default(),
             cache: Default::default(),
             external: Default::default(),
             exact: Default::default(),
             disable_logging: Default::default(),
+            record_usage_stats: Default::default(),
+            record_history: Default::default(),
+            max_violations: Default::default(),
             include_string_imports: Default::default(),
+            analyze_attribute_access: Default::default(),
             forbid_circular_dependencies: Default::default(),
+            forbid_circular_domain_dependencies: Default::default(),
             use_regex_matching: Default::default(),
             root_module: Default::default(),
+            module_mappings: Default::default(),
+            entrypoints: Default::default(),
             rules: Default::default(),
+            forbid: Default::default(),
+            naming: Default::default(),
             plugins: Default::default(),
             domains: Default::default(),
+            implicit_modules: Default::default(),
+            inline_config: Default::default(),
+            include_stubs: Default::default(),
+            include_notebooks: Default::default(),
+            symlinks: Default::default(),
+            module_path_normalizers: Default::default(),
+            inferred_modules: Default::default(),
+            inline_modules: Default::default(),
+            inline_interfaces: Default::default(),
             pending_edits: Default::default(),
+            backup_edits: true,
             location: Default::default(),
         }
     }
@@ -155,7 +292,42 @@ impl ProjectConfig {
             .collect())
     }
 
+    /// Whether circular dependencies between modules should be treated as a hard error
+    pub fn forbids_circular_dependencies(&self) -> bool {
+        self.forbid_circular_dependencies && self.rules.circular_dependencies == RuleSetting::Error
+    }
+
     // TODO: use absolute_source_roots
+    /// Maps each module declared in a domain config to the path of its domain,
+    /// if dependencies between domains must be acyclic.
+    pub fn circular_domain_groups(&self) -> Option<HashMap<String, String>> {
+        if !self.forbid_circular_domain_dependencies {
+            return None;
+        }
+        Some(
+            self.domains
+                .iter()
+                .flat_map(|domain| {
+                    domain
+                        .modules()
+                        .map(|module| (module.path.clone(), domain.location.mod_path.clone()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Maps each module declared in a domain config to its domain
+    pub fn module_domains(&self) -> HashMap<&str, &LocatedDomainConfig> {
+        self.domains
+            .iter()
+            .flat_map(|domain| {
+                domain
+                    .modules()
+                    .map(move |module| (module.path.as_str(), domain))
+            })
+            .collect()
+    }
+
     pub fn prepend_roots(&self, project_root: &Path) -> Vec<PathBuf> {
         // don't prepend if root is "."
         self.source_roots
@@ -170,6 +342,26 @@ impl ProjectConfig {
             .collect()
     }
 
+    /// The Python version targeted by the file at the (absolute) 'file_path'.
+    /// The most specific source root with its own version takes precedence over 'python_version'.
+    pub fn python_version_for_file(&self, project_root: &Path, file_path: &Path) -> Option<&str> {
+        self.source_root_python_versions
+            .iter()
+            .filter(|(source_root, _)| file_path.starts_with(project_root.join(source_root)))
+            .max_by_key(|(source_root, _)| source_root.components().count())
+            .map(|(_, version)| version.as_str())
+            .or(self.python_version.as_deref())
+    }
+
+    /// Every Python version targeted by part of the project
+    pub fn python_versions(&self) -> BTreeSet<&str> {
+        self.python_version
+            .iter()
+            .chain(self.source_root_python_versions.values())
+            .map(|version| version.as_str())
+            .collect()
+    }
+
     pub fn with_dependencies_removed(&self) -> Self {
         Self {
             modules: self
@@ -182,10 +374,58 @@ impl ProjectConfig {
                 .iter()
                 .map(|domain| domain.with_dependencies_removed())
                 .collect(),
+            inline_modules: self
+                .inline_modules
+                .iter()
+                .map(|module| module.with_dependencies_removed())
+                .collect(),
             ..self.clone()
         }
     }
 
+    /// A copy of this config with every pending edit applied in memory rather than written to disk,
+    /// so that a hypothetical config can be checked without touching any config file
+    pub fn with_edits_applied(&self) -> Self {
+        let mut config = Self {
+            domains: self
+                .domains
+                .iter()
+                .map(|domain| domain.with_edits_applied())
+                .collect(),
+            pending_edits: vec![],
+            ..self.clone()
+        };
+        for edit in &self.pending_edits {
+            match edit {
+                ConfigEdit::AddSourceRoot { filepath } => {
+                    if !config.source_roots.contains(filepath) {
+                        config.source_roots.push(filepath.clone());
+                    }
+                }
+                ConfigEdit::RemoveSourceRoot { filepath } => {
+                    config.source_roots.retain(|root| root != filepath);
+                }
+                ConfigEdit::AddInterface { path, expose } => {
+                    config.interfaces.push(InterfaceConfig {
+                        expose: expose.clone(),
+                        from_modules: vec![path.clone()],
+                        ..Default::default()
+                    });
+                }
+                ConfigEdit::SetModuleStrict { path, .. } => {
+                    config
+                        .interfaces
+                        .retain(|interface| interface.from_modules != [path.as_str()]);
+                }
+                ConfigEdit::SetMaxViolations { max_violations } => {
+                    config.max_violations = *max_violations;
+                }
+                _ => apply_module_edit(&mut config.modules, edit),
+            }
+        }
+        config
+    }
+
     pub fn add_domain(&mut self, domain: LocatedDomainConfig) {
         self.domains.push(domain);
     }
@@ -194,16 +434,72 @@ impl ProjectConfig {
         self.modules.push(ModuleConfig::new_root_config());
     }
 
+    pub fn add_inferred_module(&mut self, path: &str) {
+        if self.all_modules().all(|module| module.path != path) {
+            self.inferred_modules.push(ModuleConfig::new(path, false));
+        }
+    }
+
     pub fn all_modules(&self) -> impl Iterator<Item = &ModuleConfig> {
         self.modules
             .iter()
             .chain(self.domains.iter().flat_map(|domain| domain.modules()))
+            .chain(self.inferred_modules.iter())
+            .chain(self.inline_modules.iter())
+    }
+
+    /// An inferred module has no entry in the config file, so before it can be edited,
+    /// it is written out as an explicit module.
+    fn make_inferred_module_explicit(&mut self, path: &str) {
+        if let Some(index) = self
+            .inferred_modules
+            .iter()
+            .position(|module| module.path == path)
+        {
+            let module = self.inferred_modules.remove(index);
+            self.pending_edits.push(ConfigEdit::CreateModule {
+                path: module.path.clone(),
+            });
+            self.modules.push(module);
+        }
     }
 
     pub fn all_interfaces(&self) -> impl Iterator<Item = &InterfaceConfig> {
         self.interfaces
             .iter()
             .chain(self.domains.iter().flat_map(|domain| domain.interfaces()))
+            .chain(self.inline_interfaces.iter())
+    }
+
+    /// Whether an interface is declared for this module alone, as it is for a strict module
+    fn has_own_interface(&self, path: &str) -> bool {
+        self.all_interfaces()
+            .any(|interface| interface.from_modules == [path])
+    }
+
+    /// Whether the import path refers to a mapped module, or to a member within one
+    pub fn is_mapped_module(&self, module_path: &str) -> bool {
+        self.module_mappings.keys().any(|mapped_path| {
+            module_path == mapped_path
+                || module_path
+                    .strip_prefix(mapped_path.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+
+    /// Split all modules into those which can be found in the source roots (or are mapped explicitly),
+    /// and those which cannot
+    pub fn validate_modules(
+        &self,
+        source_roots: &[PathBuf],
+    ) -> (Vec<ModuleConfig>, Vec<ModuleConfig>) {
+        let (mut valid_modules, invalid_modules) =
+            validate_project_modules(source_roots, self.all_modules().cloned().collect());
+        let (mapped_modules, invalid_modules): (Vec<_>, Vec<_>) = invalid_modules
+            .into_iter()
+            .partition(|module| self.module_mappings.contains_key(&module.path));
+        valid_modules.extend(mapped_modules);
+        (valid_modules, invalid_modules)
     }
 }
 
@@ -222,17 +518,60 @@ impl ConfigEditor for ProjectConfig {
             | ConfigEdit::MarkModuleAsUtility { .. }
             | ConfigEdit::UnmarkModuleAsUtility { .. }
             | ConfigEdit::AddDependency { .. }
-            | ConfigEdit::RemoveDependency { .. } => {
-                if !domain_results.iter().any(|r| r.is_ok()) {
+            | ConfigEdit::RemoveDependency { .. }
+            | ConfigEdit::MarkDependencyDeprecated { .. }
+            | ConfigEdit::UnmarkDependencyDeprecated { .. }
+            | ConfigEdit::SetModuleLayer { .. }
+            | ConfigEdit::SetModuleMaxViolations { .. } => {
+                if let Some(path) = edit.module_path().filter(|path| {
+                    self.inline_modules
+                        .iter()
+                        .any(|module| module.path == *path)
+                }) {
+                    // Inline modules have no entry in any config file, so their '__tach__' is edited by hand
+                    Err(EditError::DeclaredInline(path.to_string()))
+                } else if !domain_results.iter().any(|r| r.is_ok()) {
                     // If no domain accepted the edit, enqueue the edit
+                    let creates_module = matches!(edit, ConfigEdit::CreateModule { .. });
+                    if let Some(path) = edit.module_path().filter(|_| !creates_module) {
+                        self.make_inferred_module_explicit(path);
+                    }
                     self.pending_edits.push(edit.clone());
                     Ok(())
                 } else {
                     Err(EditError::NotApplicable)
                 }
             }
-            ConfigEdit::AddSourceRoot { .. } | ConfigEdit::RemoveSourceRoot { .. } => {
-                // Source root edits are always applicable to project config
+            ConfigEdit::SetModuleStrict { path, strict } => {
+                if self
+                    .inline_modules
+                    .iter()
+                    .any(|module| module.path == *path)
+                {
+                    Err(EditError::DeclaredInline(path.to_string()))
+                } else {
+                    if !*strict {
+                        self.pending_edits.push(edit.clone());
+                    } else if !self.has_own_interface(path) {
+                        // A strict module is one with an interface exposing the members in its '__all__'
+                        let expose = self
+                            .absolute_source_roots()
+                            .ok()
+                            .and_then(|roots| parse_interface_members(&roots, path).ok())
+                            .unwrap_or_default();
+                        self.pending_edits.push(ConfigEdit::AddInterface {
+                            path: path.clone(),
+                            expose,
+                        });
+                    }
+                    Ok(())
+                }
+            }
+            ConfigEdit::AddSourceRoot { .. }
+            | ConfigEdit::RemoveSourceRoot { .. }
+            | ConfigEdit::AddInterface { .. }
+            | ConfigEdit::SetMaxViolations { .. } => {
+                // Project-level edits are always applicable to project config
                 self.pending_edits.push(edit.clone());
                 Ok(())
             }
@@ -251,7 +590,57 @@ impl ConfigEditor for ProjectConfig {
         }
     }
 
+    fn preview_edits(&self) -> Result<Vec<ConfigEditPreview>, EditError> {
+        let mut previews = vec![];
+        for domain in &self.domains {
+            previews.extend(domain.preview_edits()?);
+        }
+        if !self.pending_edits.is_empty() {
+            previews.push(self.render_edits()?);
+        }
+        Ok(previews)
+    }
+
     fn apply_edits(&mut self) -> Result<(), EditError> {
+        let mut edited_files: Vec<&Path> = self
+            .domains
+            .iter()
+            .filter(|domain| domain.has_pending_edits())
+            .map(|domain| domain.location.path.as_path())
+            .collect();
+        if !self.pending_edits.is_empty() {
+            edited_files.push(
+                self.location
+                    .as_deref()
+                    .ok_or(EditError::ConfigDoesNotExist)?,
+            );
+        }
+        let backup_dir = match (
+            &self.location,
+            self.backup_edits && !edited_files.is_empty(),
+        ) {
+            (Some(config_path), true) => Some(
+                backup_files(
+                    config_path.parent().unwrap_or(Path::new(".")),
+                    &edited_files,
+                )
+                .map_err(|err| EditError::BackupFailed(err.to_string()))?,
+            ),
+            _ => None,
+        };
+
+        // Each file is written atomically, but edits may span several files
+        self.write_edits().map_err(|err| match (err, backup_dir) {
+            (EditError::DiskWriteFailed, Some(backup_dir)) => {
+                EditError::WriteInterrupted(backup_dir)
+            }
+            (err, _) => err,
+        })
+    }
+}
+
+impl ProjectConfig {
+    fn write_edits(&mut self) -> Result<(), EditError> {
         for domain in &mut self.domains {
             domain.apply_edits()?;
         }
@@ -259,11 +648,24 @@ impl ConfigEditor for ProjectConfig {
         if self.pending_edits.is_empty() {
             return Ok(());
         }
+        let preview = self.render_edits()?;
+        write_atomic(&preview.path, &preview.edited).map_err(|_| EditError::DiskWriteFailed)?;
+
+        self.pending_edits.clear();
+        Ok(())
+    }
+
+    /// The project config file before and after applying its own pending edits
+    fn render_edits(&self) -> Result<ConfigEditPreview, EditError> {
         let config_path = self
             .location
             .as_ref()
             .ok_or(EditError::ConfigDoesNotExist)?;
 
+        if yaml::is_yaml_path(config_path) {
+            return yaml::render_edits(config_path, &self.pending_edits);
+        }
+
         let toml_str =
             std::fs::read_to_string(config_path).map_err(|_| EditError::ConfigDoesNotExist)?;
         let mut doc = toml_str
@@ -276,10 +678,12 @@ impl ConfigEditor for ProjectConfig {
                     let mut module_table = toml_edit::Table::new();
                     module_table.insert("path", toml_edit::value(path));
                     module_table.insert("depends_on", toml_edit::value(toml_edit::Array::new()));
+                    // Separate the new module from the preceding table
+                    module_table.decor_mut().set_prefix("\n");
 
                     match doc.get_mut("modules") {
                         // If modules is a regular array (modules = []) or doesn't exist, convert it to array of tables
-                        None | Some(toml_edit::Item::Value(toml_edit::Value::Array(_))) => {
+                        None => {
                             let mut array = toml_edit::ArrayOfTables::new();
                             array.push(module_table);
                             doc["modules"] = toml_edit::Item::ArrayOfTables(array);
@@ -307,10 +711,9 @@ impl ConfigEditor for ProjectConfig {
                                 .map(|p| p.as_array().is_some())
                                 .unwrap_or(false)
                             {
-                                table["paths"]
-                                    .as_array_mut()
-                                    .unwrap()
-                                    .retain(|p| p.as_str().unwrap() != path);
+                                toml_layout::retain(table["paths"].as_array_mut().unwrap(), |p| {
+                                    p.as_str().unwrap() != path
+                                });
                                 if table["paths"].as_array().unwrap().is_empty() {
                                     module_index = Some(i);
                                 }
@@ -394,7 +797,7 @@ impl ConfigEditor for ProjectConfig {
                                             });
 
                                             if !exists {
-                                                array.push(dependency);
+                                                toml_layout::push(array, dependency);
                                             }
                                         } else {
                                             table.insert(
@@ -410,10 +813,8 @@ impl ConfigEditor for ProjectConfig {
                                             array,
                                         )) = &mut table["depends_on"]
                                         {
-                                            array.retain(|dep| {
-                                                dep.as_str()
-                                                    .map(|d| d != dependency)
-                                                    .unwrap_or(true)
+                                            toml_layout::retain(array, |dep| {
+                                                dependency_path(dep) != Some(dependency)
                                             });
                                         }
                                     }
@@ -423,6 +824,31 @@ impl ConfigEditor for ProjectConfig {
                         }
                     }
                 }
+                ConfigEdit::MarkDependencyDeprecated { path, .. }
+                | ConfigEdit::UnmarkDependencyDeprecated { path, .. }
+                | ConfigEdit::SetModuleLayer { path, .. }
+                | ConfigEdit::SetModuleMaxViolations { path, .. } => {
+                    if let toml_edit::Item::ArrayOfTables(modules) = &mut doc["modules"] {
+                        for table in modules.iter_mut() {
+                            let is_target_module = table
+                                .get("path")
+                                .map(|p| p.as_str() == Some(path))
+                                .unwrap_or(false)
+                                || table
+                                    .get("paths")
+                                    .map(|p| {
+                                        p.as_array().is_some_and(|p| {
+                                            p.iter().any(|p| p.as_str() == Some(path))
+                                        })
+                                    })
+                                    .unwrap_or(false);
+
+                            if is_target_module {
+                                apply_attribute_edit(table, edit, edit.dependency());
+                            }
+                        }
+                    }
+                }
                 ConfigEdit::AddSourceRoot { filepath } => {
                     if let toml_edit::Item::Value(toml_edit::Value::Array(source_roots)) =
                         &mut doc["source_roots"]
@@ -430,7 +856,7 @@ impl ConfigEditor for ProjectConfig {
                         if !source_roots.iter().any(|root| {
                             root.as_str() == Some(filepath.as_os_str().to_str().unwrap())
                         }) {
-                            source_roots.push(filepath.display().to_string());
+                            toml_layout::push(source_roots, filepath.display().to_string());
                         }
                     }
                 }
@@ -438,20 +864,81 @@ impl ConfigEditor for ProjectConfig {
                     if let toml_edit::Item::Value(toml_edit::Value::Array(source_roots)) =
                         &mut doc["source_roots"]
                     {
-                        source_roots.retain(|root| {
+                        toml_layout::retain(source_roots, |root| {
                             root.as_str()
                                 .map(|s| s != filepath.as_os_str().to_str().unwrap())
                                 .unwrap_or(true)
                         });
                     }
                 }
+                ConfigEdit::SetMaxViolations { max_violations } => match max_violations {
+                    Some(max_violations) => {
+                        doc.insert("max_violations", toml_edit::value(*max_violations as i64));
+                    }
+                    None => {
+                        doc.remove("max_violations");
+                    }
+                },
+                ConfigEdit::SetModuleStrict { path, .. } => {
+                    // Making a module strict is enqueued as adding its interface
+                    let is_own_interface = |from: Option<&toml_edit::Value>| {
+                        from.and_then(|from| from.as_array()).is_some_and(|from| {
+                            from.len() == 1
+                                && from.get(0).and_then(|p| p.as_str()) == Some(path.as_str())
+                        })
+                    };
+                    match doc.get_mut("interfaces") {
+                        Some(toml_edit::Item::ArrayOfTables(interfaces)) => {
+                            interfaces.retain(|interface| {
+                                !is_own_interface(interface.get("from").and_then(|f| f.as_value()))
+                            });
+                        }
+                        Some(toml_edit::Item::Value(toml_edit::Value::Array(interfaces))) => {
+                            toml_layout::retain(interfaces, |interface| {
+                                !interface.as_inline_table().is_some_and(|interface| {
+                                    is_own_interface(interface.get("from"))
+                                })
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                ConfigEdit::AddInterface { path, expose } => {
+                    let mut interface_table = toml_edit::Table::new();
+                    interface_table.insert(
+                        "expose",
+                        toml_edit::value(expose.iter().collect::<toml_edit::Array>()),
+                    );
+                    interface_table.insert(
+                        "from",
+                        toml_edit::value(toml_edit::Array::from_iter([path])),
+                    );
+                    interface_table.decor_mut().set_prefix("\n");
+
+                    // An empty 'interfaces = []' is replaced by an array of tables
+                    let is_empty = match doc.get("interfaces") {
+                        None => true,
+                        Some(item) => item.as_array().is_some_and(|array| array.is_empty()),
+                    };
+                    if is_empty {
+                        doc["interfaces"] =
+                            toml_edit::Item::ArrayOfTables(toml_edit::ArrayOfTables::new());
+                    }
+                    match doc.get_mut("interfaces") {
+                        Some(toml_edit::Item::ArrayOfTables(array)) => {
+                            array.push(interface_table);
+                        }
+                        _ => return Err(EditError::ParsingFailed),
+                    }
+                }
             }
         }
 
-        std::fs::write(config_path, doc.to_string()).map_err(|_| EditError::DiskWriteFailed)?;
-
-        self.pending_edits.clear();
-        Ok(())
+        Ok(ConfigEditPreview {
+            path: config_path.clone(),
+            original: toml_str,
+            edited: doc.to_string(),
+        })
     }
 }
 
@@ -484,6 +971,12 @@ impl ProjectConfig {
         self.location = Some(location);
     }
 
+    /// Apply the rule settings for `environment` (e.g. `[rules.ci]`) over the project's rules.
+    /// Returns whether the environment is declared in the config.
+    pub fn apply_rules_environment(&mut self, environment: &str) -> bool {
+        self.rules.apply_environment(environment)
+    }
+
     pub fn has_no_modules(&self) -> bool {
         self.all_modules().next().is_none()
     }
@@ -567,6 +1060,52 @@ impl ProjectConfig {
         self.enqueue_edit(&ConfigEdit::RemoveDependency { path, dependency })
     }
 
+    pub fn mark_dependency_deprecated(
+        &mut self,
+        path: String,
+        dependency: String,
+    ) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::MarkDependencyDeprecated { path, dependency })
+    }
+
+    pub fn unmark_dependency_deprecated(
+        &mut self,
+        path: String,
+        dependency: String,
+    ) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::UnmarkDependencyDeprecated { path, dependency })
+    }
+
+    pub fn set_module_strict(&mut self, path: String, strict: bool) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::SetModuleStrict { path, strict })
+    }
+
+    #[pyo3(signature = (path, layer = None))]
+    pub fn set_module_layer(
+        &mut self,
+        path: String,
+        layer: Option<String>,
+    ) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::SetModuleLayer { path, layer })
+    }
+
+    #[pyo3(signature = (path, max_violations = None))]
+    pub fn set_module_max_violations(
+        &mut self,
+        path: String,
+        max_violations: Option<usize>,
+    ) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::SetModuleMaxViolations {
+            path,
+            max_violations,
+        })
+    }
+
+    #[pyo3(signature = (max_violations = None))]
+    pub fn set_max_violations(&mut self, max_violations: Option<usize>) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::SetMaxViolations { max_violations })
+    }
+
     pub fn add_source_root(&mut self, filepath: PathBuf) -> Result<(), EditError> {
         self.enqueue_edit(&ConfigEdit::AddSourceRoot { filepath })
     }
@@ -575,6 +1114,30 @@ impl ProjectConfig {
         self.enqueue_edit(&ConfigEdit::RemoveSourceRoot { filepath })
     }
 
+    pub fn add_interface(&mut self, path: String, expose: Vec<String>) -> Result<(), EditError> {
+        self.enqueue_edit(&ConfigEdit::AddInterface { path, expose })
+    }
+
+    #[pyo3(name = "enqueue_edit")]
+    pub fn enqueue_edit_py(&mut self, edit: ConfigEdit) -> Result<(), EditError> {
+        self.enqueue_edit(&edit)
+    }
+
+    /// Every edit which has been enqueued but not yet saved, across all config files
+    pub fn pending_edits(&self) -> Vec<ConfigEdit> {
+        self.domains
+            .iter()
+            .flat_map(|domain| domain.pending_edits())
+            .chain(&self.pending_edits)
+            .cloned()
+            .collect()
+    }
+
+    #[pyo3(name = "preview_edits")]
+    pub fn preview_edits_py(&self) -> Result<Vec<ConfigEditPreview>, EditError> {
+        self.preview_edits()
+    }
+
     pub fn save_edits(&mut self) -> Result<(), EditError> {
         self.apply_edits()
     }
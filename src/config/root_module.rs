@@ -19,13 +19,19 @@ impl RootModuleTreatment {
     }
 }
 
-impl IntoPy<PyObject> for RootModuleTreatment {
-    fn into_py(self, py: Python) -> PyObject {
+impl std::fmt::Display for RootModuleTreatment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Allow => "allow".to_object(py),
-            Self::Forbid => "forbid".to_object(py),
-            Self::Ignore => "ignore".to_object(py),
-            Self::DependenciesOnly => "dependenciesonly".to_object(py),
+            Self::Allow => write!(f, "allow"),
+            Self::Forbid => write!(f, "forbid"),
+            Self::Ignore => write!(f, "ignore"),
+            Self::DependenciesOnly => write!(f, "dependenciesonly"),
         }
     }
 }
+
+impl IntoPy<PyObject> for RootModuleTreatment {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
@@ -1,6 +1,12 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::modules::ModuleConfig;
+use crate::dependencies::{ImportContext, ImportStyle};
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum RuleSetting {
@@ -19,11 +25,11 @@ impl RuleSetting {
         *self == Self::Warn
     }
 
-    fn error() -> Self {
+    pub(crate) fn error() -> Self {
         Self::Error
     }
 
-    fn is_error(&self) -> bool {
+    pub(crate) fn is_error(&self) -> bool {
         *self == Self::Error
     }
 
@@ -46,24 +52,282 @@ impl IntoPy<PyObject> for RuleSetting {
     }
 }
 
+/// Settings for a single module which take precedence over the project's `rules`.
+/// Only rules about a module's own imports can be overridden.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ModuleRulesConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH001")]
+    pub undeclared_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH002")]
+    pub deprecated_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH003")]
+    pub interface_violations: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH004")]
+    pub root_module_usage: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH008")]
+    pub unstable_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub try_except_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_block_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conftest_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_checking_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH023")]
+    pub wildcard_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH024")]
+    pub relative_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH025")]
+    pub conditional_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH010")]
+    pub platform_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH011")]
+    pub domain_boundaries: Option<RuleSetting>,
+}
+
+/// Settings which take precedence over the project's `rules` in one environment (e.g. `[rules.ci]`),
+/// chosen with `--env` or `TACH_ENV`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct EnvironmentRulesConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH017")]
+    pub unused_ignore_directives: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH018")]
+    pub require_ignore_directive_reasons: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH020")]
+    pub unused_external_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub try_except_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_block_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conftest_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_checking_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH001")]
+    pub undeclared_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH002")]
+    pub deprecated_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH003")]
+    pub interface_violations: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circular_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH004")]
+    pub root_module_usage: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH008")]
+    pub unstable_dependencies: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH022")]
+    pub unresolved_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH023")]
+    pub wildcard_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH024")]
+    pub relative_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH025")]
+    pub conditional_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH010")]
+    pub platform_imports: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH011")]
+    pub domain_boundaries: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH016")]
+    pub interface_conformance: Option<RuleSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "TACH009")]
+    pub utility_dependencies: Option<RuleSetting>,
+}
+
+/// How an import of a member is attributed to a module
+#[derive(Debug, Serialize, Default, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReexportResolution {
+    /// Attribute the import to the nearest module containing the import path
+    #[default]
+    Nearest,
+    /// Follow re-exports to the module which actually provides the member
+    Follow,
+}
+
+impl ReexportResolution {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl IntoPy<PyObject> for ReexportResolution {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Self::Nearest => "nearest".to_object(py),
+            Self::Follow => "follow".to_object(py),
+        }
+    }
+}
+
+fn default_max_reexport_depth() -> usize {
+    3
+}
+
+fn is_default_max_reexport_depth(depth: &usize) -> bool {
+    *depth == default_max_reexport_depth()
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[pyclass(get_all, module = "tach.extension")]
 pub struct RulesConfig {
     #[serde(
         default = "RuleSetting::warn",
-        skip_serializing_if = "RuleSetting::is_warn"
+        skip_serializing_if = "RuleSetting::is_warn",
+        alias = "TACH017"
     )]
     pub unused_ignore_directives: RuleSetting,
     #[serde(
         default = "RuleSetting::off",
-        skip_serializing_if = "RuleSetting::is_off"
+        skip_serializing_if = "RuleSetting::is_off",
+        alias = "TACH018"
     )]
     pub require_ignore_directive_reasons: RuleSetting,
     #[serde(
         default = "RuleSetting::error",
-        skip_serializing_if = "RuleSetting::is_error"
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH020"
     )]
     pub unused_external_dependencies: RuleSetting,
+    #[serde(default, skip_serializing_if = "ReexportResolution::is_default")]
+    pub reexport_resolution: ReexportResolution,
+    #[serde(
+        default = "default_max_reexport_depth",
+        skip_serializing_if = "is_default_max_reexport_depth"
+    )]
+    pub max_reexport_depth: usize,
+    // How dependency and interface violations are reported for imports which do not run at module load time
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub function_imports: RuleSetting,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub try_except_imports: RuleSetting,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub main_block_imports: RuleSetting,
+    // How dependency and interface violations are reported for imports in pytest 'conftest.py' files
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub conftest_imports: RuleSetting,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub type_checking_imports: RuleSetting,
+    // Files (relative to the project root) whose imports are all treated as being in a main block, e.g. CLI entrypoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cli_patterns: Vec<String>,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH001"
+    )]
+    pub undeclared_dependencies: RuleSetting,
+    #[serde(
+        default = "RuleSetting::warn",
+        skip_serializing_if = "RuleSetting::is_warn",
+        alias = "TACH002"
+    )]
+    pub deprecated_dependencies: RuleSetting,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH003"
+    )]
+    pub interface_violations: RuleSetting,
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error"
+    )]
+    pub circular_dependencies: RuleSetting,
+    // Undeclared dependencies on (or from) the root module
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH004"
+    )]
+    pub root_module_usage: RuleSetting,
+    // Dependencies of 'stable' modules on 'experimental' modules or interfaces
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH008"
+    )]
+    pub unstable_dependencies: RuleSetting,
+    // Absolute imports which share a top-level package with the project, but do not resolve
+    #[serde(
+        default = "RuleSetting::off",
+        skip_serializing_if = "RuleSetting::is_off",
+        alias = "TACH022"
+    )]
+    pub unresolved_imports: RuleSetting,
+    // Import statements written in a restricted style, whatever they import
+    #[serde(
+        default = "RuleSetting::off",
+        skip_serializing_if = "RuleSetting::is_off",
+        alias = "TACH023"
+    )]
+    pub wildcard_imports: RuleSetting,
+    #[serde(
+        default = "RuleSetting::off",
+        skip_serializing_if = "RuleSetting::is_off",
+        alias = "TACH024"
+    )]
+    pub relative_imports: RuleSetting,
+    #[serde(
+        default = "RuleSetting::off",
+        skip_serializing_if = "RuleSetting::is_off",
+        alias = "TACH025"
+    )]
+    pub conditional_imports: RuleSetting,
+    // Imports of platform-specific modules which are not guarded by a matching 'sys.platform' check
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH010"
+    )]
+    pub platform_imports: RuleSetting,
+    // Imports from outside a domain of modules which are not among the domain's 'interface_modules'
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH011"
+    )]
+    pub domain_boundaries: RuleSetting,
+    // Modules which do not define the members of the interface they declare with 'implements'
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH016"
+    )]
+    pub interface_conformance: RuleSetting,
+    // Imports by a utility module of a module which is not a utility
+    #[serde(
+        default = "RuleSetting::error",
+        skip_serializing_if = "RuleSetting::is_error",
+        alias = "TACH009"
+    )]
+    pub utility_dependencies: RuleSetting,
+    // Settings for each environment, declared as tables within 'rules' (e.g. '[rules.ci]')
+    #[serde(flatten, default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, EnvironmentRulesConfig>,
 }
 
 impl Default for RulesConfig {
@@ -72,6 +336,29 @@ impl Default for RulesConfig {
             unused_ignore_directives: RuleSetting::warn(),
             require_ignore_directive_reasons: RuleSetting::off(),
             unused_external_dependencies: RuleSetting::error(),
+            reexport_resolution: Default::default(),
+            max_reexport_depth: default_max_reexport_depth(),
+            function_imports: RuleSetting::error(),
+            try_except_imports: RuleSetting::error(),
+            main_block_imports: RuleSetting::error(),
+            conftest_imports: RuleSetting::error(),
+            type_checking_imports: RuleSetting::error(),
+            cli_patterns: vec![],
+            undeclared_dependencies: RuleSetting::error(),
+            deprecated_dependencies: RuleSetting::warn(),
+            interface_violations: RuleSetting::error(),
+            circular_dependencies: RuleSetting::error(),
+            root_module_usage: RuleSetting::error(),
+            unstable_dependencies: RuleSetting::error(),
+            unresolved_imports: RuleSetting::off(),
+            wildcard_imports: RuleSetting::off(),
+            relative_imports: RuleSetting::off(),
+            conditional_imports: RuleSetting::off(),
+            platform_imports: RuleSetting::error(),
+            domain_boundaries: RuleSetting::error(),
+            interface_conformance: RuleSetting::error(),
+            utility_dependencies: RuleSetting::error(),
+            environments: BTreeMap::new(),
         }
     }
 }
@@ -80,4 +367,196 @@ impl RulesConfig {
     pub fn is_default(&self) -> bool {
         *self == Self::default()
     }
+
+    /// The rules which apply to imports made by the given module, with its overrides applied
+    pub fn for_module(&self, module_config: &ModuleConfig) -> Cow<'_, RulesConfig> {
+        let Some(overrides) = &module_config.rules else {
+            return Cow::Borrowed(self);
+        };
+        let mut rules = self.clone();
+        for (setting, module_setting) in [
+            (
+                &mut rules.undeclared_dependencies,
+                &overrides.undeclared_dependencies,
+            ),
+            (
+                &mut rules.deprecated_dependencies,
+                &overrides.deprecated_dependencies,
+            ),
+            (
+                &mut rules.interface_violations,
+                &overrides.interface_violations,
+            ),
+            (&mut rules.root_module_usage, &overrides.root_module_usage),
+            (
+                &mut rules.unstable_dependencies,
+                &overrides.unstable_dependencies,
+            ),
+            (&mut rules.function_imports, &overrides.function_imports),
+            (&mut rules.try_except_imports, &overrides.try_except_imports),
+            (&mut rules.main_block_imports, &overrides.main_block_imports),
+            (&mut rules.conftest_imports, &overrides.conftest_imports),
+            (
+                &mut rules.type_checking_imports,
+                &overrides.type_checking_imports,
+            ),
+            (&mut rules.wildcard_imports, &overrides.wildcard_imports),
+            (&mut rules.relative_imports, &overrides.relative_imports),
+            (
+                &mut rules.conditional_imports,
+                &overrides.conditional_imports,
+            ),
+            (&mut rules.platform_imports, &overrides.platform_imports),
+            (&mut rules.domain_boundaries, &overrides.domain_boundaries),
+        ] {
+            if let Some(module_setting) = module_setting {
+                *setting = module_setting.clone();
+            }
+        }
+        Cow::Owned(rules)
+    }
+
+    /// Apply the settings for `environment` over these rules, returning whether the environment is declared
+    pub fn apply_environment(&mut self, environment: &str) -> bool {
+        let Some(overrides) = self.environments.get(environment).cloned() else {
+            return false;
+        };
+        for (setting, environment_setting) in [
+            (
+                &mut self.unused_ignore_directives,
+                overrides.unused_ignore_directives,
+            ),
+            (
+                &mut self.require_ignore_directive_reasons,
+                overrides.require_ignore_directive_reasons,
+            ),
+            (
+                &mut self.unused_external_dependencies,
+                overrides.unused_external_dependencies,
+            ),
+            (&mut self.function_imports, overrides.function_imports),
+            (&mut self.try_except_imports, overrides.try_except_imports),
+            (&mut self.main_block_imports, overrides.main_block_imports),
+            (&mut self.conftest_imports, overrides.conftest_imports),
+            (
+                &mut self.type_checking_imports,
+                overrides.type_checking_imports,
+            ),
+            (
+                &mut self.undeclared_dependencies,
+                overrides.undeclared_dependencies,
+            ),
+            (
+                &mut self.deprecated_dependencies,
+                overrides.deprecated_dependencies,
+            ),
+            (
+                &mut self.interface_violations,
+                overrides.interface_violations,
+            ),
+            (
+                &mut self.circular_dependencies,
+                overrides.circular_dependencies,
+            ),
+            (&mut self.root_module_usage, overrides.root_module_usage),
+            (
+                &mut self.unstable_dependencies,
+                overrides.unstable_dependencies,
+            ),
+            (&mut self.unresolved_imports, overrides.unresolved_imports),
+            (&mut self.wildcard_imports, overrides.wildcard_imports),
+            (&mut self.relative_imports, overrides.relative_imports),
+            (&mut self.conditional_imports, overrides.conditional_imports),
+            (&mut self.platform_imports, overrides.platform_imports),
+            (&mut self.domain_boundaries, overrides.domain_boundaries),
+            (
+                &mut self.interface_conformance,
+                overrides.interface_conformance,
+            ),
+            (
+                &mut self.utility_dependencies,
+                overrides.utility_dependencies,
+            ),
+        ] {
+            if let Some(environment_setting) = environment_setting {
+                *setting = environment_setting;
+            }
+        }
+        true
+    }
+
+    /// The setting for dependency and interface violations caused by an import in the given context
+    pub fn import_context_setting(&self, context: ImportContext) -> &RuleSetting {
+        match context {
+            ImportContext::TopLevel => &RuleSetting::Error,
+            ImportContext::TryExcept => &self.try_except_imports,
+            ImportContext::Function => &self.function_imports,
+            ImportContext::MainBlock => &self.main_block_imports,
+            ImportContext::Conftest => &self.conftest_imports,
+            ImportContext::TypeChecking => &self.type_checking_imports,
+        }
+    }
+
+    /// The setting for import statements written in the given style
+    pub fn import_style_setting(&self, style: ImportStyle) -> &RuleSetting {
+        match style {
+            ImportStyle::Wildcard => &self.wildcard_imports,
+            ImportStyle::Relative => &self.relative_imports,
+            ImportStyle::Conditional => &self.conditional_imports,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_rule_overrides() {
+        let rules = RulesConfig {
+            function_imports: RuleSetting::Warn,
+            ..RulesConfig::default()
+        };
+        let module = ModuleConfig::new("legacy", false);
+        assert!(matches!(rules.for_module(&module), Cow::Borrowed(_)));
+
+        let module = ModuleConfig {
+            rules: Some(ModuleRulesConfig {
+                undeclared_dependencies: Some(RuleSetting::Warn),
+                ..Default::default()
+            }),
+            ..ModuleConfig::new("legacy", false)
+        };
+        let module_rules = rules.for_module(&module);
+        assert_eq!(module_rules.undeclared_dependencies, RuleSetting::Warn);
+        assert_eq!(module_rules.function_imports, RuleSetting::Warn);
+        assert_eq!(module_rules.interface_violations, RuleSetting::Error);
+    }
+
+    #[test]
+    fn test_environment_rules() {
+        let mut rules: RulesConfig = toml::from_str(
+            "unused_ignore_directives = \"off\"\n\n[ci]\nunused_ignore_directives = \"error\"\ndeprecated_dependencies = \"error\"\n\n[local]\ninterface_violations = \"warn\"\n",
+        )
+        .unwrap();
+        assert_eq!(rules.unused_ignore_directives, RuleSetting::Off);
+        assert_eq!(rules.environments.len(), 2);
+
+        assert!(!rules.apply_environment("staging"));
+        assert_eq!(rules.unused_ignore_directives, RuleSetting::Off);
+
+        assert!(rules.apply_environment("ci"));
+        assert_eq!(rules.unused_ignore_directives, RuleSetting::Error);
+        assert_eq!(rules.deprecated_dependencies, RuleSetting::Error);
+        assert_eq!(rules.interface_violations, RuleSetting::Error);
+    }
+
+    #[test]
+    fn test_rules_by_code() {
+        let mut rules: RulesConfig =
+            toml::from_str("TACH002 = \"error\"\n\n[ci]\nTACH001 = \"warn\"\n").unwrap();
+        assert_eq!(rules.deprecated_dependencies, RuleSetting::Error);
+        assert!(rules.apply_environment("ci"));
+        assert_eq!(rules.undeclared_dependencies, RuleSetting::Warn);
+    }
 }
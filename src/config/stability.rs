@@ -0,0 +1,48 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How stable the API of a module (or of the members exposed by an interface) is,
+/// ordered from most to least stable
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Stability {
+    Stable,
+    Beta,
+    Experimental,
+}
+
+impl Stability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+            Self::Experimental => "experimental",
+        }
+    }
+
+    /// Whether code with this stability may depend on code with the given stability.
+    /// Code without a declared stability is never restricted.
+    pub fn permits(&self, dependency: Option<Stability>) -> bool {
+        !matches!((self, dependency), (Self::Stable, Some(Self::Experimental)))
+    }
+}
+
+impl IntoPy<PyObject> for Stability {
+    fn into_py(self, py: Python) -> PyObject {
+        self.as_str().to_object(py)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits() {
+        assert!(!Stability::Stable.permits(Some(Stability::Experimental)));
+        assert!(Stability::Stable.permits(Some(Stability::Beta)));
+        assert!(Stability::Stable.permits(None));
+        assert!(Stability::Beta.permits(Some(Stability::Experimental)));
+        assert!(Stability::Experimental.permits(Some(Stability::Experimental)));
+    }
+}
@@ -0,0 +1,31 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How the filesystem walker treats symbolic links below a source root
+#[derive(Debug, Serialize, Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    /// Walk into symlinked files and directories, skipping links which point back to an ancestor
+    Follow,
+    /// Ignore symlinked files and directories entirely
+    #[default]
+    Skip,
+    /// Same as `Follow`, but fail when a link points back to an ancestor
+    ErrorOnCycle,
+}
+
+impl SymlinkPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl IntoPy<PyObject> for SymlinkPolicy {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Self::Follow => "follow".to_object(py),
+            Self::Skip => "skip".to_object(py),
+            Self::ErrorOnCycle => "error-on-cycle".to_object(py),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Array edits which preserve the layout of hand-written TOML.
+//!
+//! `toml_edit` keeps comments and whitespace as the decor of neighbouring values,
+//! and a comment at the end of a line belongs to the value on the *next* line.
+//! Naively pushing or removing values therefore moves or drops comments,
+//! and collapses multi-line arrays. These helpers keep each comment next to the value it describes.
+
+use toml_edit::{Array, Value};
+
+fn raw(decor: Option<&toml_edit::RawString>) -> String {
+    decor
+        .and_then(|decor| decor.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The part of some decor which is on the same line as the preceding value
+fn same_line(decor: &str) -> &str {
+    decor.split_once('\n').map_or(decor, |(head, _)| head)
+}
+
+/// The part of some decor from the first newline onwards
+fn following_lines(decor: &str) -> &str {
+    decor.find('\n').map_or("", |index| &decor[index..])
+}
+
+/// Push a value, laying it out like the existing values.
+/// In a multi-line array, the new value goes on its own line with the same indentation.
+pub fn push(array: &mut Array, value: impl Into<Value>) {
+    let indent = array
+        .iter()
+        .last()
+        .map(|last| raw(last.decor().prefix()))
+        .and_then(|prefix| prefix.rfind('\n').map(|index| prefix[index..].to_string()));
+
+    let Some(indent) = indent else {
+        array.push(value);
+        return;
+    };
+
+    if array.trailing_comma() {
+        // A comment after the last value is stored in the trailing decor
+        let trailing = array.trailing().as_str().unwrap_or_default().to_string();
+        array.push(value);
+        let last = array.len() - 1;
+        array.get_mut(last).unwrap().decor_mut().set_prefix(format!(
+            "{}{}",
+            same_line(&trailing),
+            indent
+        ));
+        array.set_trailing(following_lines(&trailing).to_string());
+    } else {
+        // Without a trailing comma, a comment after the last value is stored in its suffix
+        let previous = array.len() - 1;
+        let suffix = raw(array.get(previous).unwrap().decor().suffix());
+        array.get_mut(previous).unwrap().decor_mut().set_suffix("");
+        array.push(value);
+        let decor = array.get_mut(previous + 1).unwrap().decor_mut();
+        decor.set_prefix(format!("{}{}", same_line(&suffix), indent));
+        decor.set_suffix(following_lines(&suffix).to_string());
+    }
+}
+
+/// Remove the value at `index`, keeping comments which belong to the previous value
+fn remove(array: &mut Array, index: usize) {
+    let removed = array.remove(index);
+    let removed_prefix = raw(removed.decor().prefix());
+    let kept_comment = same_line(&removed_prefix);
+
+    if index < array.len() {
+        let next = array.get_mut(index).unwrap();
+        let next_prefix = raw(next.decor().prefix());
+        next.decor_mut()
+            .set_prefix(format!("{}{}", kept_comment, following_lines(&next_prefix)));
+    } else if array.trailing_comma() {
+        let trailing = array.trailing().as_str().unwrap_or_default().to_string();
+        array.set_trailing(format!("{}{}", kept_comment, following_lines(&trailing)));
+    } else if index > 0 {
+        let removed_suffix = raw(removed.decor().suffix());
+        let trailing = array.trailing().as_str().unwrap_or_default().to_string();
+        let previous = array.get_mut(index - 1).unwrap();
+        let previous_suffix = raw(previous.decor().suffix());
+        // Plain whitespace between values is dropped along with the comma
+        let kept_comment = if kept_comment.contains('#') {
+            kept_comment
+        } else {
+            ""
+        };
+        previous.decor_mut().set_suffix(format!(
+            "{}{}{}",
+            previous_suffix,
+            kept_comment,
+            following_lines(&removed_suffix)
+        ));
+        array.set_trailing(following_lines(&trailing).to_string());
+    }
+}
+
+/// Remove all values for which `keep` returns false
+pub fn retain(array: &mut Array, mut keep: impl FnMut(&Value) -> bool) {
+    let to_remove: Vec<usize> = array
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !keep(value))
+        .map(|(index, _)| index)
+        .collect();
+    // Removing from the back means each comment is carried to a value which is kept
+    for index in to_remove.into_iter().rev() {
+        remove(array, index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn edit_array(source: &str, edit: impl FnOnce(&mut Array)) -> String {
+        let mut doc = source.parse::<toml_edit::DocumentMut>().unwrap();
+        edit(doc["depends_on"].as_array_mut().unwrap());
+        doc.to_string()
+    }
+
+    #[rstest]
+    #[case(
+        "depends_on = [\"a\", \"b\"]\n",
+        "depends_on = [\"a\", \"b\", \"c\"]\n"
+    )]
+    #[case(
+        "depends_on = [\n    \"a\", # why a\n    \"b\", # why b\n]\n",
+        "depends_on = [\n    \"a\", # why a\n    \"b\", # why b\n    \"c\",\n]\n"
+    )]
+    fn test_push(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(edit_array(source, |array| push(array, "c")), expected);
+    }
+
+    #[rstest]
+    #[case(
+        "depends_on = [\"a\", \"b\", \"c\"]\n",
+        "b",
+        "depends_on = [\"a\", \"c\"]\n"
+    )]
+    #[case("depends_on = [\"a\", \"b\"]\n", "a", "depends_on = [\"b\"]\n")]
+    #[case(
+        "depends_on = [\n    \"a\", # why a\n    \"b\", # why b\n    \"c\", # why c\n]\n",
+        "b",
+        "depends_on = [\n    \"a\", # why a\n    \"c\", # why c\n]\n"
+    )]
+    #[case(
+        "depends_on = [\n    \"a\", # why a\n    \"b\", # why b\n]\n",
+        "b",
+        "depends_on = [\n    \"a\", # why a\n]\n"
+    )]
+    fn test_retain(#[case] source: &str, #[case] removed: &str, #[case] expected: &str) {
+        let edited = edit_array(source, |array| {
+            retain(array, |value| value.as_str() != Some(removed))
+        });
+        assert_eq!(edited, expected);
+    }
+}
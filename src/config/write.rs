@@ -0,0 +1,144 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tempfile::NamedTempFile;
+
+/// Backups of config files are kept in timestamped directories under this path in the project root
+pub const CONFIG_BACKUP_DIR: &str = ".tach/backups";
+/// Older backups are removed once there are more than this many
+const MAX_CONFIG_BACKUPS: usize = 10;
+
+/// Write 'contents' to 'path' by writing a temporary file in the same directory and renaming it
+/// over 'path', so that an interrupted write never leaves a partially written file behind.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = NamedTempFile::new_in(directory)?;
+    file.write_all(contents.as_bytes())?;
+    file.as_file().sync_all()?;
+    // Keep the permissions of the file being replaced
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(file.path(), metadata.permissions())?;
+    }
+    file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn backup_root(project_root: &Path) -> PathBuf {
+    project_root.join(CONFIG_BACKUP_DIR)
+}
+
+/// Backup directories, oldest first
+fn backup_dirs(project_root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(backup_root(project_root)) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    // Names are a zero-padded timestamp, optionally followed by a counter
+    dirs.sort();
+    dirs
+}
+
+/// The most recent backup directory, if any config files have been backed up
+pub fn latest_backup(project_root: &Path) -> Option<PathBuf> {
+    backup_dirs(project_root).pop()
+}
+
+/// Copy 'files' into a new timestamped directory under [`CONFIG_BACKUP_DIR`], keeping their paths
+/// relative to 'project_root', and remove all but the most recent backups.
+/// Returns the new backup directory.
+pub fn backup_files(project_root: &Path, files: &[&Path]) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let mut backup_dir = backup_root(project_root).join(format!("{:012}", timestamp));
+    let mut counter = 1;
+    while backup_dir.exists() {
+        backup_dir = backup_root(project_root).join(format!("{:012}-{:03}", timestamp, counter));
+        counter += 1;
+    }
+
+    // Backups should never be committed, even before the rest of '.tach' is set up
+    let gitignore_path = backup_root(project_root).join(".gitignore");
+    if !gitignore_path.exists() {
+        fs::create_dir_all(backup_root(project_root))?;
+        fs::write(gitignore_path, "*\n")?;
+    }
+
+    for file in files {
+        // Files outside of the project are backed up by name
+        let relative = file
+            .strip_prefix(project_root)
+            .ok()
+            .or_else(|| file.file_name().map(Path::new))
+            .unwrap_or(file);
+        let destination = backup_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(file, destination)?;
+    }
+
+    let dirs = backup_dirs(project_root);
+    for old_dir in dirs
+        .iter()
+        .take(dirs.len().saturating_sub(MAX_CONFIG_BACKUPS))
+    {
+        let _ = fs::remove_dir_all(old_dir);
+    }
+    Ok(backup_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_and_atomic_write() {
+        let project_root = TempDir::new().unwrap();
+        let config_path = project_root.path().join("tach.toml");
+        let domain_path = project_root.path().join("billing/tach.domain.toml");
+        fs::create_dir_all(domain_path.parent().unwrap()).unwrap();
+        fs::write(&config_path, "exact = true\n").unwrap();
+        fs::write(&domain_path, "[root]\n").unwrap();
+
+        let backup_dir = backup_files(project_root.path(), &[&config_path, &domain_path]).unwrap();
+        write_atomic(&config_path, "exact = false\n").unwrap();
+
+        assert_eq!(latest_backup(project_root.path()), Some(backup_dir.clone()));
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("tach.toml")).unwrap(),
+            "exact = true\n"
+        );
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("billing/tach.domain.toml")).unwrap(),
+            "[root]\n"
+        );
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "exact = false\n");
+        // Only the config files and the backups are left behind
+        assert_eq!(fs::read_dir(project_root.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_old_backups_are_removed() {
+        let project_root = TempDir::new().unwrap();
+        let config_path = project_root.path().join("tach.toml");
+        fs::write(&config_path, "").unwrap();
+
+        for _ in 0..MAX_CONFIG_BACKUPS + 2 {
+            backup_files(project_root.path(), &[&config_path]).unwrap();
+        }
+
+        assert_eq!(backup_dirs(project_root.path()).len(), MAX_CONFIG_BACKUPS);
+    }
+}
@@ -0,0 +1,382 @@
+//! Write-back support for project configuration written in YAML (`tach.yml` or `tach.yaml`).
+//!
+//! Edits are applied to the parsed YAML document, so key order is preserved,
+//! but unlike TOML configs, comments are not.
+
+use std::path::Path;
+
+use serde_yaml::{Mapping, Sequence, Value};
+
+use super::edit::{ConfigEdit, ConfigEditPreview, EditError};
+
+pub const YAML_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+pub fn is_yaml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| YAML_EXTENSIONS.contains(&ext))
+}
+
+fn module_matches(module: &Value, path: &str) -> bool {
+    module.get("path").and_then(Value::as_str) == Some(path)
+        || module
+            .get("paths")
+            .and_then(Value::as_sequence)
+            .is_some_and(|paths| paths.iter().any(|p| p.as_str() == Some(path)))
+}
+
+fn dependency_path(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(path) => Some(path),
+        Value::Mapping(dependency) => dependency.get("path").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+fn modules_mut(doc: &mut Value) -> Option<&mut Sequence> {
+    doc.get_mut("modules").and_then(Value::as_sequence_mut)
+}
+
+fn target_modules<'a>(doc: &'a mut Value, path: &'a str) -> impl Iterator<Item = &'a mut Mapping> {
+    modules_mut(doc)
+        .into_iter()
+        .flat_map(|modules| modules.iter_mut())
+        .filter(move |module| module_matches(module, path))
+        .filter_map(Value::as_mapping_mut)
+}
+
+fn set_flag(module: &mut Mapping, key: &str, value: bool) {
+    if value {
+        module.insert(key.into(), true.into());
+    } else {
+        module.shift_remove(key);
+    }
+}
+
+fn set_dependency_deprecated(module: &mut Mapping, dependency: &str, deprecated: bool) {
+    let Some(depends_on) = module
+        .get_mut("depends_on")
+        .and_then(Value::as_sequence_mut)
+    else {
+        return;
+    };
+    for value in depends_on
+        .iter_mut()
+        .filter(|value| dependency_path(value) == Some(dependency))
+    {
+        let mut entry = match value {
+            Value::Mapping(entry) => entry.clone(),
+            _ => Mapping::from_iter([("path".into(), dependency.into())]),
+        };
+        set_flag(&mut entry, "deprecated", deprecated);
+        if !deprecated {
+            entry.shift_remove("deprecated_until");
+        }
+        // Dependencies without any other attributes are written as plain strings
+        *value = if entry.len() == 1 {
+            dependency.into()
+        } else {
+            Value::Mapping(entry)
+        };
+    }
+}
+
+fn apply_edit(doc: &mut Value, edit: &ConfigEdit) -> Result<(), EditError> {
+    match edit {
+        ConfigEdit::CreateModule { path } => {
+            let module = Mapping::from_iter([
+                ("path".into(), path.as_str().into()),
+                ("depends_on".into(), Value::Sequence(vec![])),
+            ]);
+            let root = doc.as_mapping_mut().ok_or(EditError::ParsingFailed)?;
+            match root.get_mut("modules") {
+                Some(Value::Sequence(modules)) => modules.push(Value::Mapping(module)),
+                None | Some(Value::Null) => {
+                    root.insert(
+                        "modules".into(),
+                        Value::Sequence(vec![Value::Mapping(module)]),
+                    );
+                }
+                _ => return Err(EditError::ParsingFailed),
+            }
+        }
+        ConfigEdit::DeleteModule { path } => {
+            if let Some(modules) = modules_mut(doc) {
+                modules.retain_mut(|module| {
+                    if module.get("path").and_then(Value::as_str) == Some(path) {
+                        return false;
+                    }
+                    match module.get_mut("paths").and_then(Value::as_sequence_mut) {
+                        Some(paths) => {
+                            paths.retain(|p| p.as_str() != Some(path));
+                            !paths.is_empty()
+                        }
+                        None => true,
+                    }
+                });
+            }
+        }
+        ConfigEdit::MarkModuleAsUtility { path } | ConfigEdit::UnmarkModuleAsUtility { path } => {
+            let utility = matches!(edit, ConfigEdit::MarkModuleAsUtility { .. });
+            for module in target_modules(doc, path) {
+                set_flag(module, "utility", utility);
+            }
+        }
+        ConfigEdit::AddDependency { path, dependency } => {
+            for module in target_modules(doc, path) {
+                match module.get_mut("depends_on") {
+                    Some(Value::Sequence(depends_on)) => {
+                        if !depends_on
+                            .iter()
+                            .any(|dep| dependency_path(dep) == Some(dependency))
+                        {
+                            depends_on.push(dependency.as_str().into());
+                        }
+                    }
+                    _ => {
+                        module.insert(
+                            "depends_on".into(),
+                            Value::Sequence(vec![dependency.as_str().into()]),
+                        );
+                    }
+                }
+            }
+        }
+        ConfigEdit::RemoveDependency { path, dependency } => {
+            for module in target_modules(doc, path) {
+                if let Some(Value::Sequence(depends_on)) = module.get_mut("depends_on") {
+                    depends_on.retain(|dep| dependency_path(dep) != Some(dependency));
+                }
+            }
+        }
+        ConfigEdit::MarkDependencyDeprecated { path, dependency }
+        | ConfigEdit::UnmarkDependencyDeprecated { path, dependency } => {
+            let deprecated = matches!(edit, ConfigEdit::MarkDependencyDeprecated { .. });
+            for module in target_modules(doc, path) {
+                set_dependency_deprecated(module, dependency, deprecated);
+            }
+        }
+        ConfigEdit::SetModuleStrict { path, .. } => {
+            // Making a module strict is enqueued as adding its interface
+            if let Some(Value::Sequence(interfaces)) = doc.get_mut("interfaces") {
+                interfaces.retain(|interface| {
+                    interface
+                        .get("from")
+                        .and_then(Value::as_sequence)
+                        .map_or(true, |from| {
+                            from.len() != 1 || from[0].as_str() != Some(path.as_str())
+                        })
+                });
+            }
+        }
+        ConfigEdit::SetModuleLayer { path, layer } => {
+            for module in target_modules(doc, path) {
+                match layer {
+                    Some(layer) => {
+                        module.insert("layer".into(), layer.as_str().into());
+                    }
+                    None => {
+                        module.shift_remove("layer");
+                    }
+                }
+            }
+        }
+        ConfigEdit::SetModuleMaxViolations {
+            path,
+            max_violations,
+        } => {
+            for module in target_modules(doc, path) {
+                match max_violations {
+                    Some(max_violations) => {
+                        module.insert("max_violations".into(), (*max_violations as u64).into());
+                    }
+                    None => {
+                        module.shift_remove("max_violations");
+                    }
+                }
+            }
+        }
+        ConfigEdit::AddSourceRoot { filepath } => {
+            let filepath = filepath.display().to_string();
+            let root = doc.as_mapping_mut().ok_or(EditError::ParsingFailed)?;
+            match root.get_mut("source_roots") {
+                Some(Value::Sequence(source_roots)) => {
+                    if !source_roots
+                        .iter()
+                        .any(|root| root.as_str() == Some(&filepath))
+                    {
+                        source_roots.push(filepath.into());
+                    }
+                }
+                None | Some(Value::Null) => {
+                    // Without 'source_roots', the project root is the only source root
+                    let mut source_roots: Sequence = vec![".".into()];
+                    if filepath != "." {
+                        source_roots.push(filepath.into());
+                    }
+                    root.insert("source_roots".into(), Value::Sequence(source_roots));
+                }
+                _ => return Err(EditError::ParsingFailed),
+            }
+        }
+        ConfigEdit::RemoveSourceRoot { filepath } => {
+            if let Some(Value::Sequence(source_roots)) = doc.get_mut("source_roots") {
+                let filepath = filepath.display().to_string();
+                source_roots.retain(|root| root.as_str() != Some(&filepath));
+            }
+        }
+        ConfigEdit::SetMaxViolations { max_violations } => {
+            let root = doc.as_mapping_mut().ok_or(EditError::ParsingFailed)?;
+            match max_violations {
+                Some(max_violations) => {
+                    root.insert("max_violations".into(), (*max_violations as u64).into());
+                }
+                None => {
+                    root.shift_remove("max_violations");
+                }
+            }
+        }
+        ConfigEdit::AddInterface { path, expose } => {
+            let interface = Mapping::from_iter([
+                (
+                    "expose".into(),
+                    Value::Sequence(expose.iter().map(|member| member.as_str().into()).collect()),
+                ),
+                ("from".into(), Value::Sequence(vec![path.as_str().into()])),
+            ]);
+            let root = doc.as_mapping_mut().ok_or(EditError::ParsingFailed)?;
+            match root.get_mut("interfaces") {
+                Some(Value::Sequence(interfaces)) => interfaces.push(Value::Mapping(interface)),
+                None | Some(Value::Null) => {
+                    root.insert(
+                        "interfaces".into(),
+                        Value::Sequence(vec![Value::Mapping(interface)]),
+                    );
+                }
+                _ => return Err(EditError::ParsingFailed),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The YAML config at `config_path` before and after applying edits
+pub fn render_edits(
+    config_path: &Path,
+    edits: &[ConfigEdit],
+) -> Result<ConfigEditPreview, EditError> {
+    let original =
+        std::fs::read_to_string(config_path).map_err(|_| EditError::ConfigDoesNotExist)?;
+    let mut doc: Value = serde_yaml::from_str(&original).map_err(|_| EditError::ParsingFailed)?;
+    if doc.is_null() {
+        doc = Value::Mapping(Mapping::new());
+    }
+
+    for edit in edits {
+        apply_edit(&mut doc, edit)?;
+    }
+
+    Ok(ConfigEditPreview {
+        path: config_path.to_path_buf(),
+        original,
+        edited: serde_yaml::to_string(&doc).map_err(|_| EditError::ParsingFailed)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(source: &str, edits: &[ConfigEdit]) -> String {
+        let mut doc: Value = serde_yaml::from_str(source).unwrap();
+        for edit in edits {
+            apply_edit(&mut doc, edit).unwrap();
+        }
+        serde_yaml::to_string(&doc).unwrap()
+    }
+
+    #[test]
+    fn test_module_edits() {
+        let source = "modules:\n- path: a\n  depends_on:\n  - b\n- path: b\n  depends_on: []\n";
+        let edited = edit(
+            source,
+            &[
+                ConfigEdit::CreateModule {
+                    path: "c".to_string(),
+                },
+                ConfigEdit::AddDependency {
+                    path: "a".to_string(),
+                    dependency: "c".to_string(),
+                },
+                ConfigEdit::MarkDependencyDeprecated {
+                    path: "a".to_string(),
+                    dependency: "b".to_string(),
+                },
+                ConfigEdit::DeleteModule {
+                    path: "b".to_string(),
+                },
+            ],
+        );
+        assert_eq!(
+            edited,
+            "modules:\n- path: a\n  depends_on:\n  - path: b\n    deprecated: true\n  - c\n- path: c\n  depends_on: []\n"
+        );
+    }
+
+    #[test]
+    fn test_set_max_violations() {
+        let source = "modules: []\n";
+        let edited = edit(
+            source,
+            &[ConfigEdit::SetMaxViolations {
+                max_violations: Some(3),
+            }],
+        );
+        assert_eq!(edited, "modules: []\nmax_violations: 3\n");
+        let edited = edit(
+            &edited,
+            &[ConfigEdit::SetMaxViolations {
+                max_violations: None,
+            }],
+        );
+        assert_eq!(edited, source);
+    }
+
+    #[test]
+    fn test_add_source_root() {
+        let source = "modules: []\n";
+        let add = |filepath: &str| ConfigEdit::AddSourceRoot {
+            filepath: filepath.into(),
+        };
+        let edited = edit(source, &[add("src")]);
+        assert_eq!(edited, "modules: []\nsource_roots:\n- .\n- src\n");
+        assert_eq!(
+            edit(&edited, &[add("src"), add("lib")]),
+            "modules: []\nsource_roots:\n- .\n- src\n- lib\n"
+        );
+    }
+
+    #[test]
+    fn test_unset_module_strict() {
+        let source = "modules: []\ninterfaces:\n- expose:\n  - run\n  from:\n  - a\n- expose:\n  - run\n  from:\n  - a\n  - b\n";
+        let edited = edit(
+            source,
+            &[ConfigEdit::SetModuleStrict {
+                path: "a".to_string(),
+                strict: false,
+            }],
+        );
+        // Interfaces shared with other modules are kept
+        assert_eq!(
+            edited,
+            "modules: []\ninterfaces:\n- expose:\n  - run\n  from:\n  - a\n  - b\n"
+        );
+    }
+
+    #[test]
+    fn test_is_yaml_path() {
+        assert!(is_yaml_path(Path::new("project/tach.yml")));
+        assert!(is_yaml_path(Path::new("project/tach.yaml")));
+        assert!(!is_yaml_path(Path::new("project/tach.toml")));
+    }
+}
@@ -1,6 +1,6 @@
-use ruff_text_size::TextSize;
+use ruff_text_size::{TextRange, TextSize};
 
-use super::import::NormalizedImport;
+use super::import::{ImportContext, NormalizedImport};
 use super::reference::SourceCodeReference;
 
 #[derive(Debug)]
@@ -24,6 +24,42 @@ impl Dependency {
         }
     }
 
+    /// The source range of the dependency, which is empty when only its start is known
+    pub fn range(&self) -> TextRange {
+        match self {
+            Dependency::Import(import) => import.alias_range(),
+            Dependency::Reference(reference) => TextRange::empty(reference.offset),
+        }
+    }
+
+    /// References (e.g. Django foreign keys) are not imports, so they are treated as top-level
+    pub fn context(&self) -> ImportContext {
+        match self {
+            Dependency::Import(import) => import.context,
+            Dependency::Reference(_) => ImportContext::TopLevel,
+        }
+    }
+
+    /// The values of 'sys.platform' which an import is guarded by, if any
+    pub fn platforms(&self) -> Option<&[String]> {
+        match self {
+            Dependency::Import(import) => import.platforms.as_deref(),
+            Dependency::Reference(_) => None,
+        }
+    }
+
+    /// The top-level member of the module at 'module_path' which is used, e.g. 'Client' for
+    /// 'pkg.core.Client.create'. When the dependency is not within the module (it was re-exported
+    /// from elsewhere), this is the name which was imported. None when the module itself is used.
+    pub fn member_of(&self, module_path: &str) -> Option<&str> {
+        let dependency_path = self.module_path();
+        match dependency_path.strip_prefix(module_path) {
+            Some("") => None,
+            Some(member) if member.starts_with('.') => member[1..].split('.').next(),
+            _ => dependency_path.rsplit('.').next(),
+        }
+    }
+
     pub fn original_line_offset(&self) -> Option<TextSize> {
         match self {
             Dependency::Import(import) => Some(import.import_offset),
@@ -1,20 +1,142 @@
 use std::collections::HashMap;
 
-use ruff_text_size::TextSize;
+use ruff_text_size::{TextRange, TextSize};
+use serde::{Deserialize, Serialize};
 
 use crate::external::parsing::normalize_package_name;
 
+/// Where an import occurs, which determines whether it runs when the importing module is loaded.
+/// When contexts are nested, the one which defers the import the most is used
+/// (e.g. a function inside a 'try' block is 'function').
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportContext {
+    /// Runs when the module is loaded (including class bodies)
+    #[default]
+    TopLevel,
+    /// Inside 'try' with a handler for ImportError, so the import is optional at runtime
+    TryExcept,
+    /// Inside a function body, so the import is deferred until the function is called
+    Function,
+    /// Inside 'if __name__ == "__main__":' (or in a file matching `cli_patterns`),
+    /// so the import only runs when the module is executed as a script
+    MainBlock,
+    /// In a pytest 'conftest.py' file, whose fixtures are only loaded by the test runner
+    Conftest,
+    /// Inside 'if TYPE_CHECKING:', so the import never runs
+    TypeChecking,
+}
+
+impl ImportContext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportContext::TopLevel => "top_level",
+            ImportContext::TryExcept => "try_except",
+            ImportContext::Function => "function",
+            ImportContext::MainBlock => "main_block",
+            ImportContext::Conftest => "conftest",
+            ImportContext::TypeChecking => "type_checking",
+        }
+    }
+}
+
 /// An import with a normalized module path
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalizedImport {
     pub module_path: String,        // Global module path
     pub alias_path: Option<String>, // (for relative imports) alias path
     pub import_offset: TextSize,    // Source location of the import statement
     pub alias_offset: TextSize,     // Source location of the alias
+    #[serde(default)]
+    pub alias_end: TextSize, // Source location of the end of the alias
     pub is_absolute: bool,          // Whether the import is absolute
+    #[serde(default)]
+    pub context: ImportContext, // Where the import occurs
+    // The values of 'sys.platform' the import is guarded by (as prefixes), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+}
+
+/// A relative import which could not be resolved to a module in the project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnresolvedRelativeImport {
+    // The import (as written, e.g. '...utils') refers to a package above the top of the source root
+    BeyondSourceRoot {
+        import: String,
+        import_offset: TextSize,
+    },
+    // The import resolved to a module path which does not exist in any source root
+    NotFound {
+        resolution: String,
+        import_offset: TextSize,
+    },
+}
+
+impl UnresolvedRelativeImport {
+    pub fn import_offset(&self) -> TextSize {
+        match self {
+            UnresolvedRelativeImport::BeyondSourceRoot { import_offset, .. }
+            | UnresolvedRelativeImport::NotFound { import_offset, .. } => *import_offset,
+        }
+    }
+}
+
+/// A way of writing an import statement which can be forbidden by rules, regardless of what is imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStyle {
+    /// 'from x import *'
+    Wildcard,
+    /// 'from . import x' or 'from .x import y'
+    Relative,
+    /// Inside an 'if' block, other than 'if TYPE_CHECKING:' and 'if __name__ == "__main__":'
+    Conditional,
+}
+
+/// An import statement written in one of the restricted import styles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportStyleUsage {
+    pub style: ImportStyle,
+    pub import: String, // The imported module as written (e.g. '..utils')
+    pub import_offset: TextSize,
+}
+
+/// The imports found in a file, along with any relative imports which could not be resolved
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NormalizedImports {
+    pub imports: Vec<NormalizedImport>,
+    pub unresolved_relative_imports: Vec<UnresolvedRelativeImport>,
+    // Every import statement in a restricted style, including imports from outside the project
+    #[serde(default)]
+    pub import_styles: Vec<ImportStyleUsage>,
+    // The ranges of any syntax errors which were recovered from while collecting imports
+    #[serde(default)]
+    pub syntax_errors: Vec<TextRange>,
+}
+
+impl NormalizedImports {
+    /// Discard any imports which overlap a syntax error, since they may not be what was written,
+    /// and record the errors so that the partially-parsed file can be reported.
+    pub fn with_syntax_errors(mut self, syntax_errors: Vec<TextRange>) -> Self {
+        self.imports.retain(|import| {
+            let import_range = TextRange::new(import.import_offset, import.alias_range().end());
+            !syntax_errors
+                .iter()
+                .any(|error| error.intersect(import_range).is_some())
+        });
+        self.syntax_errors = syntax_errors;
+        self
+    }
 }
 
 impl NormalizedImport {
+    /// The source range of the alias, which is empty if its end is unknown
+    pub fn alias_range(&self) -> TextRange {
+        TextRange::new(self.alias_offset, self.alias_end.max(self.alias_offset))
+    }
+
     pub fn top_level_module_name(&self) -> &str {
         self.module_path
             .split('.')
@@ -3,5 +3,8 @@ pub mod import;
 pub mod reference;
 
 pub use dependency::Dependency;
-pub use import::{LocatedImport, NormalizedImport};
+pub use import::{
+    ImportContext, ImportStyle, ImportStyleUsage, LocatedImport, NormalizedImport,
+    NormalizedImports, UnresolvedRelativeImport,
+};
 pub use reference::SourceCodeReference;
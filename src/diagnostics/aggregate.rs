@@ -0,0 +1,97 @@
+use super::order::sort_diagnostics;
+use super::Diagnostic;
+
+/// Combine identical global diagnostics (e.g. a warning for each import of a missing module,
+/// or for each cell of a notebook which could not be read) into one, counting their occurrences.
+///
+/// Files are checked in parallel, so the result is sorted with `sort_diagnostics`.
+pub fn aggregate_diagnostics(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut global: Vec<Diagnostic> = Vec::new();
+    let mut located: Vec<Diagnostic> = Vec::new();
+    for diagnostic in diagnostics {
+        if diagnostic.file_path().is_some() {
+            located.push(diagnostic);
+            continue;
+        }
+        // There are only a few distinct global diagnostics, even when each occurs many times
+        match global.iter_mut().find(|existing| {
+            existing.severity() == diagnostic.severity()
+                && existing.details() == diagnostic.details()
+        }) {
+            Some(existing) => existing.add_occurrences(diagnostic.occurrences()),
+            None => global.push(diagnostic),
+        }
+    }
+    global.extend(located);
+    sort_diagnostics(&mut global);
+    global
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::diagnostics::{CodeDiagnostic, ConfigurationDiagnostic, DiagnosticDetails};
+
+    fn skipped_file(file_path: &str) -> Diagnostic {
+        Diagnostic::new_global_warning(DiagnosticDetails::Configuration(
+            ConfigurationDiagnostic::SkippedFileIoError {
+                file_path: file_path.to_string(),
+            },
+        ))
+    }
+
+    fn missing_module() -> Diagnostic {
+        Diagnostic::new_global_error(DiagnosticDetails::Configuration(
+            ConfigurationDiagnostic::ModuleConfigNotFound {
+                module_path: "missing".to_string(),
+            },
+        ))
+    }
+
+    #[test]
+    fn test_aggregate_diagnostics() {
+        let located = Diagnostic::new_located_error(
+            PathBuf::from("a.py"),
+            1,
+            None,
+            DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                dependency: "b.member".to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "b".to_string(),
+            }),
+        );
+        let diagnostics = vec![
+            skipped_file("c.ipynb"),
+            missing_module(),
+            located.clone(),
+            skipped_file("b.ipynb"),
+            skipped_file("c.ipynb"),
+            missing_module(),
+            missing_module(),
+        ];
+
+        let aggregated = aggregate_diagnostics(diagnostics);
+        assert_eq!(
+            aggregated
+                .iter()
+                .map(|diagnostic| (diagnostic.message(), diagnostic.occurrences()))
+                .collect::<Vec<_>>(),
+            vec![
+                (missing_module().message(), 3),
+                (skipped_file("b.ipynb").message(), 1),
+                (skipped_file("c.ipynb").message(), 2),
+                (located.message(), 1),
+            ]
+        );
+        // Aggregating again does not change the counts
+        assert_eq!(aggregate_diagnostics(aggregated.clone()), aggregated);
+
+        // The count is only serialized for diagnostics which occur more than once
+        let serialized = serde_json::to_string(&aggregated).unwrap();
+        assert_eq!(serialized.matches("\"occurrences\"").count(), 2);
+        let deserialized: Vec<Diagnostic> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, aggregated);
+    }
+}
@@ -1,12 +1,13 @@
 use std::{fmt::Display, path::PathBuf};
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::config::RuleSetting;
 
-#[derive(Debug, Copy, Clone, Eq, PartialOrd, Ord, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialOrd, Ord, Serialize, Deserialize, PartialEq)]
 #[pyclass(eq, eq_int, module = "tach.extension")]
 pub enum Severity {
     Error,
@@ -34,7 +35,7 @@ impl TryFrom<&RuleSetting> for Severity {
     }
 }
 
-#[derive(Error, Debug, Clone, Serialize, PartialEq)]
+#[derive(Error, Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[pyclass(module = "tach.extension")]
 pub enum ConfigurationDiagnostic {
     #[error("Module containing '{file_mod_path}' not found in project.")]
@@ -52,9 +53,21 @@ pub enum ConfigurationDiagnostic {
     #[error("Unexpected error: No checks were enabled.")]
     NoChecksEnabled(),
 
+    #[error("Circular dependency detected for module '{module_path}'.")]
+    CircularDependency { module_path: String },
+
+    #[error("Module '{module_path}' implements '{interface}', which could not be found.")]
+    UnknownInterface {
+        module_path: String,
+        interface: String,
+    },
+
     #[error("Skipped '{file_path}' due to a syntax error.")]
     SkippedFileSyntaxError { file_path: String },
 
+    #[error("Skipped part of '{file_path}' due to a syntax error. Imports elsewhere in the file were still checked.")]
+    PartiallySkippedFileSyntaxError { file_path: String },
+
     #[error("Skipped '{file_path}' due to an I/O error.")]
     SkippedFileIoError { file_path: String },
 
@@ -65,7 +78,7 @@ pub enum ConfigurationDiagnostic {
     SkippedUnknownError { file_path: String },
 }
 
-#[derive(Error, Debug, Clone, Serialize, PartialEq)]
+#[derive(Error, Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[pyclass(module = "tach.extension")]
 pub enum CodeDiagnostic {
     #[error(
@@ -92,6 +105,21 @@ pub enum CodeDiagnostic {
         definition_module: String,
     },
 
+    #[error("Cannot use '{dependency}'. The root module is forbidden, so code outside of any module cannot depend on '{definition_module}'.")]
+    ForbiddenRootModuleUsage {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+    },
+
+    #[error("Cannot use '{dependency}'. No module may depend on the root module (root_module = '{treatment}'), but module '{usage_module}' imports code outside of any module.")]
+    ForbiddenRootModuleDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        treatment: String,
+    },
+
     #[error("Dependency '{dependency}' is deprecated. Module '{usage_module}' should not depend on '{definition_module}'.")]
     DeprecatedDependency {
         dependency: String,
@@ -99,6 +127,30 @@ pub enum CodeDiagnostic {
         definition_module: String,
     },
 
+    #[error("Member '{member}' of '{definition_module}' is deprecated. Module '{usage_module}' should stop using '{dependency}'.")]
+    DeprecatedMemberDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        member: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Dependency of module '{usage_module}' on '{definition_module}' was deprecated until {deprecated_until}.")]
+    ExpiredDeprecatedDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        deprecated_until: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Module '{usage_module}' may only use {allowed_members} from '{definition_module}'.")]
+    RestrictedMemberDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        allowed_members: String,
+    },
+
     #[error("Cannot use '{dependency}'. Layer '{usage_layer}' ('{usage_module}') is lower than layer '{definition_layer}' ('{definition_module}').")]
     LayerViolation {
         dependency: String,
@@ -108,6 +160,88 @@ pub enum CodeDiagnostic {
         definition_layer: String,
     },
 
+    #[error("Cannot use '{dependency}'. Module '{usage_module}' is {usage_stability}, so it cannot depend on '{definition_module}', which is {definition_stability}.")]
+    UnstableDependency {
+        dependency: String,
+        usage_module: String,
+        usage_stability: String,
+        definition_module: String,
+        definition_stability: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Utility module '{usage_module}' may only depend on other utility modules, but '{definition_module}' is not a utility.")]
+    UtilityDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Module '{definition_module}' is only available on {platforms}, so '{usage_module}' must import it behind a matching 'sys.platform' check.")]
+    PlatformDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        platforms: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Module '{definition_module}' is internal to domain '{domain}', so modules outside of the domain may only import {interface_modules}.")]
+    DomainBoundaryViolation {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        domain: String,
+        interface_modules: String,
+    },
+
+    #[error("Cannot use '{dependency}'. '{usage_path}' cannot depend on '{definition_path}' within module '{module}'.")]
+    InternalStructureViolation {
+        dependency: String,
+        module: String,
+        usage_path: String,
+        definition_path: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Module '{usage_module}' cannot depend on '{definition_module}' ({rule}).")]
+    ForbiddenDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        rule: String,
+    },
+
+    #[error("Cannot use '{dependency}'. Module '{usage_module}' reaches '{definition_module}' ({path}) without going through a module matching '{through}'.")]
+    UnmediatedDependency {
+        dependency: String,
+        usage_module: String,
+        definition_module: String,
+        path: String,
+        through: String,
+    },
+
+    #[error("Module '{module_path}' does not match the naming pattern '{pattern}'{reason}.")]
+    ModuleNamingViolation {
+        module_path: String,
+        pattern: String,
+        // Empty, or the rule's reason prefixed with ': '
+        reason: String,
+    },
+
+    #[error("Module '{module_path}' implements '{interface}', but does not define '{member}'.")]
+    MissingInterfaceMember {
+        module_path: String,
+        interface: String,
+        member: String,
+    },
+
+    #[error("'{member}' in module '{module_path}' does not match '{interface}': expected parameters {expected}, found {found}.")]
+    InterfaceSignatureMismatch {
+        module_path: String,
+        interface: String,
+        member: String,
+        expected: String,
+        found: String,
+    },
+
     #[error("Dependency '{dependency}' is unnecessarily ignored by a directive.")]
     UnnecessarilyIgnoredDependency { dependency: String },
 
@@ -122,17 +256,132 @@ pub enum CodeDiagnostic {
 
     #[error("External package '{package_module_name}' is not used.")]
     UnusedExternalDependency { package_module_name: String },
+
+    #[error("Relative import '{import}' goes beyond the top of its source root.")]
+    RelativeImportBeyondSourceRoot { import: String },
+
+    #[error("Relative import of '{dependency}' could not be resolved. No such module was found in any source root.")]
+    UnresolvedRelativeImport { dependency: String },
+
+    #[error("Import of '{dependency}' could not be resolved. Its top-level package is part of the project, but no such module was found.")]
+    UnresolvedImport { dependency: String },
+
+    #[error(
+        "Wildcard import from '{import}' is not allowed. Import the names which are used instead."
+    )]
+    WildcardImport { import: String },
+
+    #[error("Relative import from '{import}' is not allowed. Use an absolute import instead.")]
+    RelativeImport { import: String },
+
+    #[error("Import of '{import}' inside an 'if' block is not allowed. Move it to the top level of the file.")]
+    ConditionalImport { import: String },
+
+    #[error("[{plugin}] {message}")]
+    PluginViolation { plugin: String, message: String },
+
+    #[error("Module '{module_path}' has {violations} violations, exceeding its budget of {max_violations}.")]
+    ViolationBudgetExceeded {
+        module_path: String,
+        violations: usize,
+        max_violations: usize,
+    },
+
+    #[error("Module '{module_path}' has {violations} of {max_violations} allowed violations ({remaining} remaining). Run 'tach ratchet' to lower 'max_violations' as violations are fixed.")]
+    WithinViolationBudget {
+        module_path: String,
+        violations: usize,
+        max_violations: usize,
+        remaining: usize,
+    },
+
+    #[error("The project has {violations} violations outside of modules with their own budget, exceeding its budget of {max_violations}.")]
+    ProjectViolationBudgetExceeded {
+        violations: usize,
+        max_violations: usize,
+    },
+
+    #[error("The project has {violations} of {max_violations} allowed violations ({remaining} remaining). Run 'tach ratchet' to lower 'max_violations' as violations are fixed.")]
+    WithinProjectViolationBudget {
+        violations: usize,
+        max_violations: usize,
+        remaining: usize,
+    },
 }
 
 impl CodeDiagnostic {
+    /// The stable code of the rule which produced this diagnostic (e.g. 'TACH001').
+    /// Codes are never reused or renumbered, since they appear in ignore directives and configuration.
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            CodeDiagnostic::UndeclaredDependency { .. } => "TACH001",
+            CodeDiagnostic::DeprecatedDependency { .. }
+            | CodeDiagnostic::DeprecatedMemberDependency { .. } => "TACH002",
+            CodeDiagnostic::PrivateDependency { .. }
+            | CodeDiagnostic::InvalidDataTypeExport { .. } => "TACH003",
+            CodeDiagnostic::ForbiddenRootModuleUsage { .. }
+            | CodeDiagnostic::ForbiddenRootModuleDependency { .. } => "TACH004",
+            CodeDiagnostic::ExpiredDeprecatedDependency { .. } => "TACH005",
+            CodeDiagnostic::RestrictedMemberDependency { .. } => "TACH006",
+            CodeDiagnostic::LayerViolation { .. } => "TACH007",
+            CodeDiagnostic::UnstableDependency { .. } => "TACH008",
+            CodeDiagnostic::UtilityDependency { .. } => "TACH009",
+            CodeDiagnostic::PlatformDependency { .. } => "TACH010",
+            CodeDiagnostic::DomainBoundaryViolation { .. } => "TACH011",
+            CodeDiagnostic::InternalStructureViolation { .. } => "TACH012",
+            CodeDiagnostic::ForbiddenDependency { .. } => "TACH013",
+            CodeDiagnostic::UnmediatedDependency { .. } => "TACH014",
+            CodeDiagnostic::ModuleNamingViolation { .. } => "TACH015",
+            CodeDiagnostic::MissingInterfaceMember { .. }
+            | CodeDiagnostic::InterfaceSignatureMismatch { .. } => "TACH016",
+            CodeDiagnostic::UnnecessarilyIgnoredDependency { .. }
+            | CodeDiagnostic::UnusedIgnoreDirective() => "TACH017",
+            CodeDiagnostic::MissingIgnoreDirectiveReason() => "TACH018",
+            CodeDiagnostic::UndeclaredExternalDependency { .. } => "TACH019",
+            CodeDiagnostic::UnusedExternalDependency { .. } => "TACH020",
+            CodeDiagnostic::RelativeImportBeyondSourceRoot { .. }
+            | CodeDiagnostic::UnresolvedRelativeImport { .. } => "TACH021",
+            CodeDiagnostic::UnresolvedImport { .. } => "TACH022",
+            CodeDiagnostic::WildcardImport { .. } => "TACH023",
+            CodeDiagnostic::RelativeImport { .. } => "TACH024",
+            CodeDiagnostic::ConditionalImport { .. } => "TACH025",
+            CodeDiagnostic::PluginViolation { .. } => "TACH026",
+            CodeDiagnostic::ViolationBudgetExceeded { .. }
+            | CodeDiagnostic::ProjectViolationBudgetExceeded { .. } => "TACH027",
+            CodeDiagnostic::WithinViolationBudget { .. }
+            | CodeDiagnostic::WithinProjectViolationBudget { .. } => "TACH028",
+        }
+    }
+
     pub fn dependency(&self) -> Option<&str> {
         match self {
             CodeDiagnostic::PrivateDependency { dependency, .. }
             | CodeDiagnostic::InvalidDataTypeExport { dependency, .. }
             | CodeDiagnostic::UndeclaredDependency { dependency, .. }
+            | CodeDiagnostic::ForbiddenRootModuleUsage { dependency, .. }
+            | CodeDiagnostic::ForbiddenRootModuleDependency { dependency, .. }
             | CodeDiagnostic::DeprecatedDependency { dependency, .. }
+            | CodeDiagnostic::DeprecatedMemberDependency { dependency, .. }
+            | CodeDiagnostic::ExpiredDeprecatedDependency { dependency, .. }
+            | CodeDiagnostic::RestrictedMemberDependency { dependency, .. }
             | CodeDiagnostic::LayerViolation { dependency, .. }
-            | CodeDiagnostic::UnnecessarilyIgnoredDependency { dependency, .. } => Some(dependency),
+            | CodeDiagnostic::UnstableDependency { dependency, .. }
+            | CodeDiagnostic::UtilityDependency { dependency, .. }
+            | CodeDiagnostic::PlatformDependency { dependency, .. }
+            | CodeDiagnostic::DomainBoundaryViolation { dependency, .. }
+            | CodeDiagnostic::InternalStructureViolation { dependency, .. }
+            | CodeDiagnostic::ForbiddenDependency { dependency, .. }
+            | CodeDiagnostic::UnmediatedDependency { dependency, .. }
+            | CodeDiagnostic::UnnecessarilyIgnoredDependency { dependency, .. }
+            | CodeDiagnostic::UnresolvedRelativeImport { dependency }
+            | CodeDiagnostic::UnresolvedImport { dependency } => Some(dependency),
+            CodeDiagnostic::RelativeImportBeyondSourceRoot { .. } => None,
+            CodeDiagnostic::ModuleNamingViolation { .. } => None,
+            CodeDiagnostic::MissingInterfaceMember { .. }
+            | CodeDiagnostic::InterfaceSignatureMismatch { .. } => None,
+            CodeDiagnostic::WildcardImport { .. }
+            | CodeDiagnostic::RelativeImport { .. }
+            | CodeDiagnostic::ConditionalImport { .. } => None,
             CodeDiagnostic::UnusedIgnoreDirective() => None,
             CodeDiagnostic::MissingIgnoreDirectiveReason() => None,
             CodeDiagnostic::UndeclaredExternalDependency { dependency, .. } => Some(dependency),
@@ -140,6 +389,11 @@ impl CodeDiagnostic {
                 package_module_name,
                 ..
             } => Some(package_module_name),
+            CodeDiagnostic::PluginViolation { .. } => None,
+            CodeDiagnostic::ViolationBudgetExceeded { .. } => None,
+            CodeDiagnostic::WithinViolationBudget { .. } => None,
+            CodeDiagnostic::ProjectViolationBudgetExceeded { .. } => None,
+            CodeDiagnostic::WithinProjectViolationBudget { .. } => None,
         }
     }
 
@@ -148,8 +402,23 @@ impl CodeDiagnostic {
             CodeDiagnostic::PrivateDependency { usage_module, .. }
             | CodeDiagnostic::InvalidDataTypeExport { usage_module, .. }
             | CodeDiagnostic::UndeclaredDependency { usage_module, .. }
+            | CodeDiagnostic::ForbiddenRootModuleUsage { usage_module, .. }
+            | CodeDiagnostic::ForbiddenRootModuleDependency { usage_module, .. }
             | CodeDiagnostic::DeprecatedDependency { usage_module, .. }
-            | CodeDiagnostic::LayerViolation { usage_module, .. } => Some(usage_module),
+            | CodeDiagnostic::DeprecatedMemberDependency { usage_module, .. }
+            | CodeDiagnostic::ExpiredDeprecatedDependency { usage_module, .. }
+            | CodeDiagnostic::RestrictedMemberDependency { usage_module, .. }
+            | CodeDiagnostic::LayerViolation { usage_module, .. }
+            | CodeDiagnostic::UnstableDependency { usage_module, .. }
+            | CodeDiagnostic::UtilityDependency { usage_module, .. }
+            | CodeDiagnostic::PlatformDependency { usage_module, .. }
+            | CodeDiagnostic::DomainBoundaryViolation { usage_module, .. }
+            | CodeDiagnostic::ForbiddenDependency { usage_module, .. }
+            | CodeDiagnostic::UnmediatedDependency { usage_module, .. }
+            | CodeDiagnostic::InternalStructureViolation {
+                module: usage_module,
+                ..
+            } => Some(usage_module),
             _ => None,
         }
     }
@@ -165,24 +434,71 @@ impl CodeDiagnostic {
             | CodeDiagnostic::UndeclaredDependency {
                 definition_module, ..
             }
+            | CodeDiagnostic::ForbiddenRootModuleUsage {
+                definition_module, ..
+            }
+            | CodeDiagnostic::ForbiddenRootModuleDependency {
+                definition_module, ..
+            }
             | CodeDiagnostic::DeprecatedDependency {
                 definition_module, ..
             }
+            | CodeDiagnostic::DeprecatedMemberDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::ExpiredDeprecatedDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::RestrictedMemberDependency {
+                definition_module, ..
+            }
             | CodeDiagnostic::LayerViolation {
                 definition_module, ..
+            }
+            | CodeDiagnostic::UnstableDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::UtilityDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::PlatformDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::DomainBoundaryViolation {
+                definition_module, ..
+            }
+            | CodeDiagnostic::ForbiddenDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::UnmediatedDependency {
+                definition_module, ..
+            }
+            | CodeDiagnostic::InternalStructureViolation {
+                module: definition_module,
+                ..
             } => Some(definition_module),
             _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[pyclass(module = "tach.extension")]
 pub enum DiagnosticDetails {
     Code(CodeDiagnostic),
     Configuration(ConfigurationDiagnostic),
 }
 
+impl DiagnosticDetails {
+    /// The code of the rule which produced this diagnostic. Configuration problems have no code.
+    pub fn rule_id(&self) -> Option<&'static str> {
+        match self {
+            DiagnosticDetails::Code(code) => Some(code.rule_id()),
+            DiagnosticDetails::Configuration(_) => None,
+        }
+    }
+}
+
 impl Display for DiagnosticDetails {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -192,25 +508,58 @@ impl Display for DiagnosticDetails {
     }
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+/// Where a located diagnostic starts within its line, and where it ends.
+/// Columns are 1-based character offsets, so that integrations can highlight the exact expression.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub column: usize,
+    pub end_line_number: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[pyclass(module = "tach.extension")]
 pub enum Diagnostic {
     Global {
         severity: Severity,
         details: DiagnosticDetails,
+        // How many times this diagnostic was found, once identical diagnostics are aggregated
+        #[serde(
+            default = "default_occurrences",
+            skip_serializing_if = "is_single_occurrence"
+        )]
+        occurrences: usize,
     },
     Located {
         file_path: PathBuf,
         line_number: usize, // Line number where the diagnostic should be attached
         original_line_number: Option<usize>, // Optional line number to point to the origin of the diagnostic
+        // The column and end position of the offending expression, when known
+        #[serde(flatten, default)]
+        span: Option<SourceSpan>,
         severity: Severity,
         details: DiagnosticDetails,
+        // Owners of the file, from the project's CODEOWNERS file
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        owners: Vec<String>,
     },
 }
 
+fn default_occurrences() -> usize {
+    1
+}
+
+fn is_single_occurrence(occurrences: &usize) -> bool {
+    *occurrences == 1
+}
+
 impl Diagnostic {
     pub fn new_global(severity: Severity, details: DiagnosticDetails) -> Self {
-        Self::Global { severity, details }
+        Self::Global {
+            severity,
+            details,
+            occurrences: 1,
+        }
     }
 
     pub fn new_located(
@@ -225,6 +574,8 @@ impl Diagnostic {
             file_path,
             line_number,
             original_line_number: None,
+            span: None,
+            owners: vec![],
         }
     }
 
@@ -238,8 +589,10 @@ impl Diagnostic {
             file_path,
             line_number,
             original_line_number,
+            span: None,
             severity: Severity::Error,
             details,
+            owners: vec![],
         }
     }
 
@@ -253,8 +606,10 @@ impl Diagnostic {
             file_path,
             line_number,
             original_line_number,
+            span: None,
             severity: Severity::Warning,
             details,
+            owners: vec![],
         }
     }
 
@@ -262,6 +617,7 @@ impl Diagnostic {
         Self::Global {
             severity: Severity::Error,
             details,
+            occurrences: 1,
         }
     }
 
@@ -269,6 +625,7 @@ impl Diagnostic {
         Self::Global {
             severity: Severity::Warning,
             details,
+            occurrences: 1,
         }
     }
 
@@ -290,6 +647,22 @@ impl Diagnostic {
         }
     }
 
+    pub fn set_severity(&mut self, new_severity: Severity) {
+        match self {
+            Self::Global { severity, .. } | Self::Located { severity, .. } => {
+                *severity = new_severity
+            }
+        }
+    }
+
+    /// Use the severity configured for the rule which produced this diagnostic,
+    /// or drop the diagnostic if the rule is turned off.
+    pub fn with_rule_setting(mut self, setting: &RuleSetting) -> Option<Self> {
+        let severity = Severity::try_from(setting).ok()?;
+        self.set_severity(severity);
+        Some(self)
+    }
+
     pub fn file_path(&self) -> Option<&PathBuf> {
         match self {
             Self::Global { .. } => None,
@@ -314,6 +687,34 @@ impl Diagnostic {
         }
     }
 
+    pub fn span(&self) -> Option<SourceSpan> {
+        match self {
+            Self::Global { .. } => None,
+            Self::Located { span, .. } => *span,
+        }
+    }
+
+    /// Attach the precise position of the offending expression to a located diagnostic
+    pub fn with_span(mut self, new_span: SourceSpan) -> Self {
+        if let Self::Located { span, .. } = &mut self {
+            *span = Some(new_span);
+        }
+        self
+    }
+
+    /// Count the occurrences of an identical global diagnostic as occurrences of this one
+    pub fn add_occurrences(&mut self, additional: usize) {
+        if let Self::Global { occurrences, .. } = self {
+            *occurrences += additional;
+        }
+    }
+
+    pub fn set_owners(&mut self, new_owners: Vec<String>) {
+        if let Self::Located { owners, .. } = self {
+            *owners = new_owners;
+        }
+    }
+
     pub fn dependency(&self) -> Option<&str> {
         match self.details() {
             DiagnosticDetails::Code(details) => details.dependency(),
@@ -350,8 +751,29 @@ impl Diagnostic {
         matches!(
             self.details(),
             DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::ForbiddenRootModuleUsage { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::ForbiddenRootModuleDependency { .. })
                 | DiagnosticDetails::Code(CodeDiagnostic::DeprecatedDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::DeprecatedMemberDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::ExpiredDeprecatedDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::RestrictedMemberDependency { .. })
                 | DiagnosticDetails::Code(CodeDiagnostic::LayerViolation { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::UnstableDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::UtilityDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::PlatformDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::DomainBoundaryViolation { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::InternalStructureViolation { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::ForbiddenDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::UnmediatedDependency { .. })
+        )
+    }
+
+    /// Whether this is a usage of the root module which the project's `root_module` treatment forbids
+    pub fn is_root_module_violation(&self) -> bool {
+        matches!(
+            self.details(),
+            DiagnosticDetails::Code(CodeDiagnostic::ForbiddenRootModuleUsage { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::ForbiddenRootModuleDependency { .. })
         )
     }
 
@@ -367,9 +789,22 @@ impl Diagnostic {
         matches!(
             self.details(),
             DiagnosticDetails::Code(CodeDiagnostic::DeprecatedDependency { .. })
+                | DiagnosticDetails::Code(CodeDiagnostic::DeprecatedMemberDependency { .. })
         )
     }
 
+    /// The deprecated member which this diagnostic reports a usage of, e.g. 'legacy.api.old_client'
+    pub fn deprecated_member(&self) -> Option<String> {
+        match self.details() {
+            DiagnosticDetails::Code(CodeDiagnostic::DeprecatedMemberDependency {
+                definition_module,
+                member,
+                ..
+            }) => Some(format!("{}.{}", definition_module, member)),
+            _ => None,
+        }
+    }
+
     pub fn is_error(&self) -> bool {
         matches!(self.severity(), Severity::Error)
     }
@@ -391,13 +826,180 @@ impl Diagnostic {
     pub fn pyline_number(&self) -> Option<usize> {
         self.line_number()
     }
+
+    /// The stable code of the rule which produced this diagnostic, e.g. 'TACH001'
+    pub fn rule_id(&self) -> Option<&'static str> {
+        self.details().rule_id()
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.span().map(|span| span.column)
+    }
+
+    pub fn end_line_number(&self) -> Option<usize> {
+        self.span().map(|span| span.end_line_number)
+    }
+
+    pub fn end_column(&self) -> Option<usize> {
+        self.span().map(|span| span.end_column)
+    }
+
+    pub fn owners(&self) -> Vec<String> {
+        match self {
+            Self::Global { .. } => vec![],
+            Self::Located { owners, .. } => owners.clone(),
+        }
+    }
+
+    /// How many times this diagnostic was found. Only global diagnostics are aggregated.
+    pub fn occurrences(&self) -> usize {
+        match self {
+            Self::Global { occurrences, .. } => *occurrences,
+            Self::Located { .. } => 1,
+        }
+    }
+}
+
+/// The current layout of the machine-readable output of commands (e.g. 'tach check --output json').
+/// Incremented whenever a field is removed or changes meaning; new fields may be added at any time.
+pub const OUTPUT_SCHEMA_VERSION: u32 = 2;
+/// The oldest layout which can still be requested with '--schema-version'
+pub const MIN_OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// The output of 'tach check --output json'.
+/// Before schema version 2, the output was only the list of diagnostics.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DiagnosticsOutput {
+    Versioned {
+        schema_version: u32,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Unversioned(Vec<Diagnostic>),
+}
+
+impl DiagnosticsOutput {
+    pub fn new(diagnostics: Vec<Diagnostic>, schema_version: u32) -> Self {
+        if schema_version < 2 {
+            Self::Unversioned(diagnostics)
+        } else {
+            Self::Versioned {
+                schema_version,
+                diagnostics,
+            }
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        match self {
+            Self::Versioned { diagnostics, .. } | Self::Unversioned(diagnostics) => diagnostics,
+        }
+    }
 }
 
-#[pyfunction(signature = (diagnostics, pretty_print = false))]
-pub fn serialize_diagnostics_json(diagnostics: Vec<Diagnostic>, pretty_print: bool) -> String {
-    if pretty_print {
-        serde_json::to_string_pretty(&diagnostics).unwrap()
+/// Serialize diagnostics in the layout of the given output schema version.
+/// Without a schema version, only the list of diagnostics is serialized.
+#[pyfunction(signature = (diagnostics, pretty_print = false, schema_version = None))]
+pub fn serialize_diagnostics_json(
+    diagnostics: Vec<Diagnostic>,
+    pretty_print: bool,
+    schema_version: Option<u32>,
+) -> PyResult<String> {
+    let output = match schema_version {
+        None => DiagnosticsOutput::Unversioned(diagnostics),
+        Some(version) if (MIN_OUTPUT_SCHEMA_VERSION..=OUTPUT_SCHEMA_VERSION).contains(&version) => {
+            DiagnosticsOutput::new(diagnostics, version)
+        }
+        Some(version) => {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported output schema version {}, expected a version from {} to {}",
+                version, MIN_OUTPUT_SCHEMA_VERSION, OUTPUT_SCHEMA_VERSION
+            )))
+        }
+    };
+    Ok(if pretty_print {
+        serde_json::to_string_pretty(&output).unwrap()
     } else {
-        serde_json::to_string(&diagnostics).unwrap()
+        serde_json::to_string(&output).unwrap()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deprecated_dependency() -> Diagnostic {
+        Diagnostic::new_located_warning(
+            PathBuf::from("a.py"),
+            1,
+            None,
+            DiagnosticDetails::Code(CodeDiagnostic::DeprecatedDependency {
+                dependency: "b.member".to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "b".to_string(),
+            }),
+        )
+    }
+
+    #[test]
+    fn test_with_rule_setting() {
+        assert!(deprecated_dependency()
+            .with_rule_setting(&RuleSetting::Error)
+            .is_some_and(|diagnostic| diagnostic.is_error()));
+        assert!(deprecated_dependency()
+            .with_rule_setting(&RuleSetting::Warn)
+            .is_some_and(|diagnostic| diagnostic.is_warning()));
+        assert!(deprecated_dependency()
+            .with_rule_setting(&RuleSetting::Off)
+            .is_none());
+    }
+
+    #[test]
+    fn test_diagnostics_output_versions() {
+        let versioned = serde_json::to_value(DiagnosticsOutput::new(
+            vec![deprecated_dependency()],
+            OUTPUT_SCHEMA_VERSION,
+        ))
+        .unwrap();
+        assert_eq!(versioned["schema_version"], OUTPUT_SCHEMA_VERSION);
+        assert_eq!(versioned["diagnostics"].as_array().unwrap().len(), 1);
+
+        let unversioned =
+            serde_json::to_value(DiagnosticsOutput::new(vec![deprecated_dependency()], 1)).unwrap();
+        assert!(unversioned.is_array());
+
+        // Both layouts can be read back
+        for output in [versioned, unversioned] {
+            let diagnostics = serde_json::from_value::<DiagnosticsOutput>(output)
+                .unwrap()
+                .into_diagnostics();
+            assert_eq!(diagnostics, vec![deprecated_dependency()]);
+        }
+    }
+
+    #[test]
+    fn test_span_in_json() {
+        let span = SourceSpan {
+            column: 8,
+            end_line_number: 1,
+            end_column: 16,
+        };
+        let diagnostic = deprecated_dependency().with_span(span);
+        let value = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(value["Located"]["column"], 8);
+        assert_eq!(value["Located"]["end_line_number"], 1);
+        assert_eq!(value["Located"]["end_column"], 16);
+        assert_eq!(
+            serde_json::from_value::<Diagnostic>(value).unwrap().span(),
+            Some(span)
+        );
+
+        // Diagnostics without a span omit the fields entirely
+        let value = serde_json::to_value(deprecated_dependency()).unwrap();
+        assert!(value["Located"].get("column").is_none());
+        assert_eq!(
+            serde_json::from_value::<Diagnostic>(value).unwrap().span(),
+            None
+        );
     }
 }
@@ -1,7 +1,13 @@
+pub mod aggregate;
 pub mod diagnostics;
 pub mod error;
+pub mod order;
 pub mod pipeline;
+pub mod rule_metadata;
 
+pub use aggregate::aggregate_diagnostics;
 pub use diagnostics::*;
 pub use error::DiagnosticError;
+pub use order::sort_diagnostics;
 pub use pipeline::{DiagnosticPipeline, FileChecker, FileProcessor, Result};
+pub use rule_metadata::{rule_metadata, RuleMetadata, RULES};
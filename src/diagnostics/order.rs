@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+use super::Diagnostic;
+
+type OrderKey = (
+    Option<PathBuf>,
+    Option<usize>,
+    Option<usize>,
+    Option<&'static str>,
+    String,
+);
+
+/// Global diagnostics come first, followed by located diagnostics by file path, line and column.
+/// Diagnostics at the same position are sorted by rule code, then message,
+/// so they are always listed in the same order.
+fn order_key(diagnostic: &Diagnostic) -> OrderKey {
+    (
+        diagnostic.file_path().cloned(),
+        diagnostic.line_number(),
+        diagnostic.column(),
+        diagnostic.rule_id(),
+        diagnostic.message(),
+    )
+}
+
+/// Sort diagnostics into an order which does not depend on how files were checked
+/// (in parallel, in shards, or one source root at a time), so that the output of
+/// two checks of the same code can be diffed line by line.
+pub fn sort_diagnostics(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by_cached_key(order_key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{
+        CodeDiagnostic, ConfigurationDiagnostic, DiagnosticDetails, SourceSpan,
+    };
+
+    fn undeclared(file_path: &str, line_number: usize, dependency: &str) -> Diagnostic {
+        Diagnostic::new_located_error(
+            PathBuf::from(file_path),
+            line_number,
+            None,
+            DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                dependency: dependency.to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "b".to_string(),
+            }),
+        )
+    }
+
+    fn at_column(diagnostic: Diagnostic, column: usize) -> Diagnostic {
+        let end_line_number = diagnostic.line_number().unwrap();
+        diagnostic.with_span(SourceSpan {
+            column,
+            end_line_number,
+            end_column: column + 3,
+        })
+    }
+
+    fn with_details(file_path: &str, line_number: usize, details: CodeDiagnostic) -> Diagnostic {
+        Diagnostic::new_located_error(
+            PathBuf::from(file_path),
+            line_number,
+            None,
+            DiagnosticDetails::Code(details),
+        )
+    }
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            undeclared("b.py", 3, "b.x"),
+            at_column(undeclared("a/z.py", 5, "b.x"), 12),
+            at_column(undeclared("a/z.py", 5, "b.y"), 1),
+            with_details(
+                "a/z.py",
+                7,
+                CodeDiagnostic::LayerViolation {
+                    dependency: "b.l".to_string(),
+                    usage_module: "a".to_string(),
+                    usage_layer: "low".to_string(),
+                    definition_module: "b".to_string(),
+                    definition_layer: "high".to_string(),
+                },
+            ),
+            with_details(
+                "a/z.py",
+                7,
+                CodeDiagnostic::DeprecatedDependency {
+                    dependency: "b.d".to_string(),
+                    usage_module: "a".to_string(),
+                    definition_module: "b".to_string(),
+                },
+            ),
+            undeclared("a/z.py", 10, "b.x"),
+            undeclared("a/z.py", 2, "b.y"),
+            undeclared("a/z.py", 2, "b.x"),
+            Diagnostic::new_located_warning(
+                PathBuf::from("a/z.py"),
+                2,
+                None,
+                DiagnosticDetails::Code(CodeDiagnostic::UnusedIgnoreDirective()),
+            ),
+            Diagnostic::new_global_warning(DiagnosticDetails::Configuration(
+                ConfigurationDiagnostic::NoFirstPartyImportsFound(),
+            )),
+        ]
+    }
+
+    #[test]
+    fn test_sort_diagnostics() {
+        let mut sorted = diagnostics();
+        sort_diagnostics(&mut sorted);
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.file_path().and_then(|path| path.to_str()),
+                    diagnostic.line_number(),
+                    diagnostic.dependency(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, None, None),
+                (Some("a/z.py"), Some(2), Some("b.x")),
+                (Some("a/z.py"), Some(2), Some("b.y")),
+                // TACH001 comes before TACH017
+                (Some("a/z.py"), Some(2), None),
+                // Then by column, before the message
+                (Some("a/z.py"), Some(5), Some("b.y")),
+                (Some("a/z.py"), Some(5), Some("b.x")),
+                // Then by rule code (TACH002 before TACH007), before the message
+                (Some("a/z.py"), Some(7), Some("b.d")),
+                (Some("a/z.py"), Some(7), Some("b.l")),
+                (Some("a/z.py"), Some(10), Some("b.x")),
+                (Some("b.py"), Some(3), Some("b.x")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_diagnostics_ignores_input_order() {
+        let mut expected = diagnostics();
+        sort_diagnostics(&mut expected);
+        for rotation in 0..expected.len() {
+            let mut shuffled = diagnostics();
+            shuffled.rotate_left(rotation);
+            sort_diagnostics(&mut shuffled);
+            assert_eq!(shuffled, expected);
+
+            shuffled.reverse();
+            sort_diagnostics(&mut shuffled);
+            assert_eq!(shuffled, expected);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use std::fmt::Write;
+
+/// What a rule checks, why, and how to fix its violations.
+/// This is the single source for 'tach explain', the CLI help, and editor hovers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuleMetadata {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+    // Python code which violates the rule
+    pub example: &'static str,
+    pub fix: &'static str,
+    // The entry in the 'rules' table which sets the rule's severity, if it can be configured
+    pub setting: Option<&'static str>,
+}
+
+pub static RULES: &[RuleMetadata] = &[
+    RuleMetadata {
+        code: "TACH001",
+        name: "undeclared-dependency",
+        summary: "Import of a module which is not listed in 'depends_on'",
+        explanation: "Every module declares the modules it may import with 'depends_on'. An import of any other module in the project is reported, since it adds a dependency which was never agreed on.",
+        example: "# In module 'api', which has depends_on = [\"core\"]\nfrom billing.invoices import create_invoice",
+        fix: "Import the code through a module which is already a dependency, move the code, or add the module to 'depends_on' (e.g. with 'tach sync') if the dependency is intended.",
+        setting: Some("undeclared_dependencies"),
+    },
+    RuleMetadata {
+        code: "TACH002",
+        name: "deprecated-dependency",
+        summary: "Import of a deprecated dependency or member",
+        explanation: "A 'depends_on' entry can be marked as deprecated, as can individual members of a module with 'deprecated_members'. Imports of them are still allowed, but reported so that they can be migrated away from.",
+        example: "# In module 'api', which has depends_on = [{ path = \"legacy\", deprecated = true }]\nfrom legacy.client import Client",
+        fix: "Replace the import with its successor, then remove the deprecated entry from 'depends_on' once no imports remain.",
+        setting: Some("deprecated_dependencies"),
+    },
+    RuleMetadata {
+        code: "TACH003",
+        name: "interface-violation",
+        summary: "Import of a member which is not part of a module's public interface",
+        explanation: "A module with an interface only exposes the members it lists in 'expose'. Importing anything else couples the caller to the module's internals, which can then no longer change freely. Members which do not match the interface's 'data_types' are also reported.",
+        example: "# 'core' only exposes 'core.api'\nfrom core.db.session import Session",
+        fix: "Import the member through the public interface, or add it to the interface's 'expose' list if it is meant to be public.",
+        setting: Some("interface_violations"),
+    },
+    RuleMetadata {
+        code: "TACH004",
+        name: "root-module-usage",
+        summary: "Dependency on, or from, code outside of any module",
+        explanation: "Code which is not part of any module belongs to the root module. Depending on the 'root_module' setting, modules may not import it, or it may not import modules.",
+        example: "# In module 'api', with root_module = \"dependenciesonly\"\nfrom scripts.helpers import load_fixture",
+        fix: "Move the code into a module, or change the 'root_module' setting.",
+        setting: Some("root_module_usage"),
+    },
+    RuleMetadata {
+        code: "TACH005",
+        name: "expired-deprecation",
+        summary: "Import of a dependency whose deprecation period has ended",
+        explanation: "A deprecated 'depends_on' entry can set 'deprecated_until'. After that date, imports of the dependency are errors rather than warnings.",
+        example: "# depends_on = [{ path = \"legacy\", deprecated_until = \"2024-01-01\" }]\nfrom legacy.client import Client",
+        fix: "Finish the migration and remove the dependency, or move 'deprecated_until' to a later date.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH006",
+        name: "restricted-member",
+        summary: "Import of a member which the dependency does not allow",
+        explanation: "A 'depends_on' entry can list the only members of the dependency which may be used. Imports of any other member are reported.",
+        example: "# depends_on = [{ path = \"core\", members = [\"Client\"] }]\nfrom core import Session",
+        fix: "Use one of the allowed members, or add the member to the dependency's 'members'.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH007",
+        name: "layer-violation",
+        summary: "Import of a module in a higher layer",
+        explanation: "Layers are ordered from highest to lowest, and modules may only import modules in the same or a lower layer. An import from a lower layer into a higher one inverts the architecture.",
+        example: "# 'core' is in layer 'domain', below 'api' in layer 'interface'\nfrom api.views import render",
+        fix: "Move the shared code down to a lower layer, or invert the dependency (e.g. by passing a callback).",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH008",
+        name: "unstable-dependency",
+        summary: "Import by a stable module of an experimental module",
+        explanation: "Modules and interfaces can be marked as 'stable' or 'experimental'. Stable code may not depend on experimental code, which can change at any time.",
+        example: "# 'api' is stable, and 'labs' is experimental\nfrom labs.ranking import score",
+        fix: "Stabilize the dependency, or keep the experimental code out of the stable module.",
+        setting: Some("unstable_dependencies"),
+    },
+    RuleMetadata {
+        code: "TACH009",
+        name: "utility-dependency",
+        summary: "Import by a utility module of a module which is not a utility",
+        explanation: "Utility modules can be used by every module, so they may only depend on other utilities. Otherwise every module would depend on the non-utility module indirectly.",
+        example: "# 'utils' is a utility, and 'billing' is not\nfrom billing.currency import format_price",
+        fix: "Move the code into a utility module, or stop marking the module as a utility.",
+        setting: Some("utility_dependencies"),
+    },
+    RuleMetadata {
+        code: "TACH010",
+        name: "platform-dependency",
+        summary: "Unguarded import of a platform-specific module",
+        explanation: "A module with 'platforms' only works on those platforms, so imports of it must be guarded by a matching 'sys.platform' check.",
+        example: "# 'winapi' has platforms = [\"win32\"]\nfrom winapi.registry import read_key",
+        fix: "Guard the import with 'if sys.platform == \"win32\":', or move it into code which only runs on that platform.",
+        setting: Some("platform_imports"),
+    },
+    RuleMetadata {
+        code: "TACH011",
+        name: "domain-boundary",
+        summary: "Import from outside a domain of one of its internal modules",
+        explanation: "A domain can declare 'interface_modules', which are the only modules that code outside of the domain may import.",
+        example: "# The 'payments' domain only allows imports of 'payments.api'\nfrom payments.ledger.entries import Entry",
+        fix: "Import the code through one of the domain's interface modules, or add the module to 'interface_modules'.",
+        setting: Some("domain_boundaries"),
+    },
+    RuleMetadata {
+        code: "TACH012",
+        name: "internal-structure",
+        summary: "Import between parts of a module which its internal structure forbids",
+        explanation: "A module can restrict how its own files and packages depend on each other. Imports which break those restrictions are reported.",
+        example: "# Within 'orders', 'orders.models' may not depend on 'orders.views'\nfrom orders.views import render_order",
+        fix: "Restructure the code so that it follows the module's internal structure, or update the structure.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH013",
+        name: "forbidden-dependency",
+        summary: "Import forbidden by a declarative rule",
+        explanation: "Declarative rules forbid dependencies between groups of modules, for example between every module matching a pattern and another module. The message names the rule which forbids the import.",
+        example: "# rule: modules matching 'plugins.*' may not depend on 'core.internal'\nfrom core.internal import registry",
+        fix: "Remove the dependency, or change the rule if the dependency should be allowed.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH014",
+        name: "unmediated-dependency",
+        summary: "Dependency which does not go through a required module",
+        explanation: "A declarative rule can require that a module only reaches another through a mediating module (such as an API layer). Direct imports which bypass it are reported.",
+        example: "# 'web' must reach 'db' through 'services'\nfrom db.models import User",
+        fix: "Go through the mediating module instead of importing the target directly.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH015",
+        name: "module-naming",
+        summary: "Module which does not match a naming pattern",
+        explanation: "A declarative rule can require module paths to match a pattern, so that the project's layout stays consistent.",
+        example: "# rule: modules under 'services' must match 'services.*_service'\n# module 'services.billing' is reported",
+        fix: "Rename the module, or change the pattern.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH016",
+        name: "interface-conformance",
+        summary: "Module which does not conform to the interface it implements",
+        explanation: "A module can declare the interface it implements with 'implements'. It must define every member of that interface, and its functions must take the same parameters.",
+        example: "# 'stripe' implements 'payments.Provider', which defines 'charge(amount, currency)'\ndef charge(amount): ...",
+        fix: "Define the missing members, or give the functions the interface's parameters.",
+        setting: Some("interface_conformance"),
+    },
+    RuleMetadata {
+        code: "TACH017",
+        name: "unused-ignore",
+        summary: "Ignore directive which does not suppress anything",
+        explanation: "A 'tach-ignore' comment which no longer suppresses an error or warning is reported, so that stale suppressions do not hide future problems.",
+        example: "from core.api import Client  # tach-ignore",
+        fix: "Remove the directive.",
+        setting: Some("unused_ignore_directives"),
+    },
+    RuleMetadata {
+        code: "TACH018",
+        name: "missing-ignore-reason",
+        summary: "Ignore directive without a reason",
+        explanation: "When reasons are required, every 'tach-ignore' comment must explain why the problem is suppressed.",
+        example: "from core.db import Session  # tach-ignore",
+        fix: "Add a reason, e.g. '# tach-ignore(Migrating to core.api by Q3)'.",
+        setting: Some("require_ignore_directive_reasons"),
+    },
+    RuleMetadata {
+        code: "TACH019",
+        name: "undeclared-external-dependency",
+        summary: "Import of a third-party package which is not declared",
+        explanation: "'tach check-external' reports imports of packages which are not listed in the project's dependencies (e.g. in 'pyproject.toml'), since they may not be installed.",
+        example: "import requests  # 'requests' is not in pyproject.toml",
+        fix: "Declare the package as a dependency, or add a module mapping if it is installed under a different name.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH020",
+        name: "unused-external-dependency",
+        summary: "Declared third-party package which is never imported",
+        explanation: "'tach check-external' reports packages which are declared as dependencies, but not imported anywhere in the project.",
+        example: "# pyproject.toml declares 'requests', but no file imports it",
+        fix: "Remove the dependency, or add it to 'external.exclude' if it is used without being imported (e.g. a plugin).",
+        setting: Some("unused_external_dependencies"),
+    },
+    RuleMetadata {
+        code: "TACH021",
+        name: "unresolved-relative-import",
+        summary: "Relative import which does not resolve to a module",
+        explanation: "A relative import which goes above the top of its source root, or which resolves to a module that does not exist, fails at runtime.",
+        example: "# In 'pkg/module.py', at the top of the source root\nfrom ...utils import helper",
+        fix: "Fix the import path, or use an absolute import.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH022",
+        name: "unresolved-import",
+        summary: "Absolute import of a project package which does not resolve",
+        explanation: "An import whose top-level package is part of the project, but which does not resolve to any module, is usually a typo or an import of a deleted module. Otherwise it would be treated as an external import.",
+        example: "from core.utills import slugify",
+        fix: "Fix the import path.",
+        setting: Some("unresolved_imports"),
+    },
+    RuleMetadata {
+        code: "TACH023",
+        name: "wildcard-import",
+        summary: "Wildcard import",
+        explanation: "'from x import *' hides which names are used, so dependencies on specific members cannot be checked.",
+        example: "from core.api import *",
+        fix: "Import the names which are used.",
+        setting: Some("wildcard_imports"),
+    },
+    RuleMetadata {
+        code: "TACH024",
+        name: "relative-import",
+        summary: "Relative import",
+        explanation: "When relative imports are forbidden, every import must spell out the full module path, which keeps dependencies easy to search for.",
+        example: "from ..utils import helper",
+        fix: "Use an absolute import.",
+        setting: Some("relative_imports"),
+    },
+    RuleMetadata {
+        code: "TACH025",
+        name: "conditional-import",
+        summary: "Import inside an 'if' block",
+        explanation: "Conditional imports make a module's dependencies depend on runtime state. Imports under 'if TYPE_CHECKING:' and 'if __name__ == \"__main__\":' are not affected.",
+        example: "if settings.USE_REDIS:\n    import redis",
+        fix: "Move the import to the top level of the file.",
+        setting: Some("conditional_imports"),
+    },
+    RuleMetadata {
+        code: "TACH026",
+        name: "plugin-violation",
+        summary: "Violation reported by a plugin",
+        explanation: "Plugins can report their own violations. The message is prefixed with the name of the plugin which reported it.",
+        example: "# Depends on the plugin",
+        fix: "See the documentation of the plugin.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH027",
+        name: "violation-budget-exceeded",
+        summary: "More violations than the budget allows",
+        explanation: "A module (or the project) can set 'max_violations' to tolerate existing violations while preventing new ones. Once the number of violations exceeds the budget, the check fails.",
+        example: "# 'legacy' has max_violations = 3, and a fourth violation was added",
+        fix: "Fix the new violations, or raise 'max_violations' if they are accepted.",
+        setting: None,
+    },
+    RuleMetadata {
+        code: "TACH028",
+        name: "within-violation-budget",
+        summary: "Violations which are still within the budget",
+        explanation: "Reports how many violations a module (or the project) has left before its 'max_violations' budget is exceeded.",
+        example: "# 'legacy' has 2 of its 3 allowed violations",
+        fix: "Fix violations, then run 'tach ratchet' to lower 'max_violations'.",
+        setting: None,
+    },
+];
+
+/// The metadata of the rule with the given code (e.g. 'TACH001'), ignoring case
+pub fn rule_metadata(code: &str) -> Option<&'static RuleMetadata> {
+    RULES
+        .iter()
+        .find(|rule| rule.code.eq_ignore_ascii_case(code))
+}
+
+impl RuleMetadata {
+    /// The full description of the rule, as printed by 'tach explain'
+    pub fn explain(&self) -> String {
+        let mut explanation = format!("{} ({})\n\n{}\n\n", self.code, self.name, self.summary);
+        writeln!(explanation, "{}\n", self.explanation).unwrap();
+        writeln!(explanation, "Example:\n").unwrap();
+        for line in self.example.lines() {
+            writeln!(explanation, "    {}", line).unwrap();
+        }
+        writeln!(explanation, "\nHow to fix:\n\n{}", self.fix).unwrap();
+        if let Some(setting) = self.setting {
+            writeln!(
+                explanation,
+                "\nSet the severity with '{} = \"error\" | \"warn\" | \"off\"' (or '{}') in the 'rules' table, or suppress a single violation with '# tach-ignore {}'.",
+                setting, self.code, self.code
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                explanation,
+                "\nSuppress a single violation with '# tach-ignore {}'.",
+                self.code
+            )
+            .unwrap();
+        }
+        explanation
+    }
+
+    /// A short Markdown description of the rule, for editor hovers
+    pub fn markdown(&self) -> String {
+        format!(
+            "**{}** ({}): {}\n\n{}\n\n**How to fix:** {}",
+            self.code, self.name, self.summary, self.explanation, self.fix
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_rule_codes_are_unique_and_ordered() {
+        let codes: Vec<&str> = RULES.iter().map(|rule| rule.code).collect();
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_rule_metadata() {
+        let rule = rule_metadata("tach002").unwrap();
+        assert_eq!(rule.name, "deprecated-dependency");
+        assert!(rule.explain().contains("deprecated_dependencies"));
+        assert!(rule_metadata("TACH999").is_none());
+    }
+}
@@ -1,8 +1,17 @@
-use itertools::Itertools;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use parking_lot::RwLock;
+use pyo3::prelude::*;
 use thiserror::Error;
 
-use crate::pattern::PatternMatcher;
+use crate::config::IgnoreConfig;
+
+const GITIGNORE_FILE_NAME: &str = ".gitignore";
+const TACHIGNORE_FILE_NAME: &str = ".tachignore";
 
 #[derive(Error, Debug)]
 pub enum PathExclusionError {
@@ -14,57 +23,316 @@ pub enum PathExclusionError {
         #[source]
         source: glob::PatternError,
     },
-    #[error("Failed to build regex pattern for excluded path:\n{exclude}\n{source}")]
-    RegexPatternError {
+    #[error("Invalid exclude pattern:\n{exclude}\n{source}")]
+    InvalidPattern {
         exclude: String,
         #[source]
-        source: regex::Error,
+        source: ignore::Error,
     },
 }
 
 pub type Result<T> = std::result::Result<T, PathExclusionError>;
 
+/// Decides which paths in the project are excluded, with the semantics of '.gitignore' files:
+/// patterns without a slash match at any depth, a leading slash anchors a pattern to the project root,
+/// a trailing slash only matches directories, and a leading '!' re-includes a path.
+///
+/// Patterns are read from 'exclude', and from the '.tachignore' and '.gitignore' files in each directory.
+/// Patterns in 'exclude' take precedence over these files, so they can also re-include ignored paths,
+/// and a '.tachignore' file takes precedence over the '.gitignore' file in the same directory.
 #[derive(Debug)]
+#[pyclass(module = "tach.extension")]
 pub struct PathExclusions {
     project_root: PathBuf,
-    patterns: Vec<PatternMatcher>,
+    exclude: Gitignore,
+    // The ignore files read in each directory, in increasing order of precedence
+    ignore_file_names: &'static [&'static str],
+    // '.gitignore' files above the project root (up to the root of the repository) and '.git/info/exclude'
+    outer_gitignores: Vec<Arc<Gitignore>>,
+    // The ignore files in each directory within the project, loaded as paths are checked
+    gitignores: RwLock<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+// Patterns in the files at 'paths' are relative to 'root', and later files take precedence
+fn load_gitignore(root: &Path, paths: &[PathBuf]) -> Option<Arc<Gitignore>> {
+    let mut builder = GitignoreBuilder::new(root);
+    let mut found = false;
+    for path in paths.iter().filter(|path| path.is_file()) {
+        // Invalid lines are skipped, as git does
+        let _ = builder.add(path);
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    builder.build().ok().map(Arc::new)
+}
+
+fn outer_gitignores(project_root: &Path) -> Vec<Arc<Gitignore>> {
+    let Some(repository_root) = project_root
+        .ancestors()
+        .find(|directory| directory.join(".git").exists())
+    else {
+        return vec![];
+    };
+    let mut gitignores: Vec<Arc<Gitignore>> = load_gitignore(
+        repository_root,
+        &[repository_root.join(".git/info/exclude")],
+    )
+    .into_iter()
+    .collect();
+    // Shallowest first, so that deeper files take precedence
+    let mut directories: Vec<&Path> = project_root
+        .ancestors()
+        .skip(1)
+        .take_while(|directory| directory.starts_with(repository_root))
+        .collect();
+    directories.reverse();
+    gitignores.extend(
+        directories.into_iter().filter_map(|directory| {
+            load_gitignore(directory, &[directory.join(GITIGNORE_FILE_NAME)])
+        }),
+    );
+    gitignores
 }
 
 impl PathExclusions {
     pub fn new<P: AsRef<Path>>(
         project_root: P,
         exclude_paths: &[String],
-        use_regex_matching: bool,
+        ignore: &IgnoreConfig,
     ) -> Result<Self> {
-        let mut patterns: Vec<PatternMatcher> = vec![];
+        let project_root = project_root.as_ref().to_path_buf();
+        let mut builder = GitignoreBuilder::new(&project_root);
         for pattern in exclude_paths.iter() {
-            patterns.push(if use_regex_matching {
-                PatternMatcher::from_regex(pattern)?
-            } else {
-                PatternMatcher::from_glob(pattern)?
-            });
+            builder.add_line(None, pattern).map_err(|source| {
+                PathExclusionError::InvalidPattern {
+                    exclude: pattern.to_string(),
+                    source,
+                }
+            })?;
         }
+        let exclude = builder
+            .build()
+            .map_err(|source| PathExclusionError::InvalidPattern {
+                exclude: exclude_paths.join(", "),
+                source,
+            })?;
         Ok(Self {
-            project_root: project_root.as_ref().to_path_buf(),
-            patterns,
+            outer_gitignores: if ignore.respect_gitignore {
+                outer_gitignores(&project_root)
+            } else {
+                vec![]
+            },
+            project_root,
+            exclude,
+            ignore_file_names: if ignore.respect_gitignore {
+                &[GITIGNORE_FILE_NAME, TACHIGNORE_FILE_NAME]
+            } else {
+                &[TACHIGNORE_FILE_NAME]
+            },
+            gitignores: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Exclusions which never exclude any path
+    pub fn none<P: AsRef<Path>>(project_root: P) -> Self {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            exclude: Gitignore::empty(),
+            ignore_file_names: &[],
+            outer_gitignores: vec![],
+            gitignores: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn gitignore_in(&self, directory: &Path) -> Option<Arc<Gitignore>> {
+        if let Some(gitignore) = self.gitignores.read().get(directory) {
+            return gitignore.clone();
+        }
+        let paths: Vec<PathBuf> = self
+            .ignore_file_names
+            .iter()
+            .map(|file_name| directory.join(file_name))
+            .collect();
+        let gitignore = load_gitignore(directory, &paths);
+        self.gitignores
+            .write()
+            .insert(directory.to_path_buf(), gitignore.clone());
+        gitignore
+    }
+
+    // Whether 'path' itself is excluded (Some(true)) or re-included (Some(false)) by the most specific pattern
+    fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        fn decision<T>(matched: Match<T>) -> Option<bool> {
+            match matched {
+                Match::None => None,
+                Match::Ignore(_) => Some(true),
+                Match::Whitelist(_) => Some(false),
+            }
+        }
+
+        if let Some(excluded) = decision(self.exclude.matched(path, is_dir)) {
+            return Some(excluded);
+        }
+        if self.ignore_file_names.is_empty() {
+            return None;
+        }
+        // Files in deeper directories take precedence
+        let mut directories: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|directory| directory.starts_with(&self.project_root))
+            .collect();
+        directories.reverse();
+        let gitignores: Vec<Arc<Gitignore>> = self
+            .outer_gitignores
+            .iter()
+            .cloned()
+            .chain(
+                directories
+                    .into_iter()
+                    .filter_map(|directory| self.gitignore_in(directory)),
+            )
+            .collect();
+        gitignores
+            .iter()
+            .rev()
+            .find_map(|gitignore| decision(gitignore.matched(path, is_dir)))
+    }
+
     // Input MUST be an absolute path within the project root
     pub fn is_path_excluded<P: AsRef<Path>>(&self, path: P) -> bool {
-        // This is for portability across OS
-        // Exclude patterns in 'tach.toml' are universally written with forward slashes,
-        // so we force our relative path to have forward slashes before checking for a match.
-        let path_with_forward_slashes: String = path
-            .as_ref()
-            .strip_prefix(&self.project_root)
-            .unwrap()
-            .components()
-            .map(|component| component.as_os_str().to_string_lossy())
-            .join("/");
-
-        self.patterns
-            .iter()
-            .any(|p| p.matches(&path_with_forward_slashes))
+        let path = path.as_ref();
+        let Ok(relative_path) = path.strip_prefix(&self.project_root) else {
+            return false;
+        };
+        // As in git, a path within an excluded directory is excluded, even if a pattern would re-include it
+        let component_count = relative_path.components().count();
+        let mut current = self.project_root.clone();
+        for (index, component) in relative_path.components().enumerate() {
+            current.push(component);
+            let is_dir = index + 1 < component_count || current.is_dir();
+            if self.matched(&current, is_dir) == Some(true) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[pymethods]
+impl PathExclusions {
+    #[new]
+    #[pyo3(signature = (project_root, exclude_paths, ignore = None))]
+    fn py_new(
+        project_root: PathBuf,
+        exclude_paths: Vec<String>,
+        ignore: Option<IgnoreConfig>,
+    ) -> Result<Self> {
+        Self::new(project_root, &exclude_paths, &ignore.unwrap_or_default())
+    }
+
+    #[pyo3(name = "is_path_excluded")]
+    fn py_is_path_excluded(&self, path: PathBuf) -> bool {
+        self.is_path_excluded(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn build_exclusions(
+        project_root: &Path,
+        exclude: &[&str],
+        respect_gitignore: bool,
+    ) -> PathExclusions {
+        let exclude: Vec<String> = exclude.iter().map(|pattern| pattern.to_string()).collect();
+        PathExclusions::new(project_root, &exclude, &IgnoreConfig { respect_gitignore }).unwrap()
+    }
+
+    #[test]
+    fn test_gitignore_style_patterns() {
+        let project_root = TempDir::new().unwrap();
+        let root = project_root.path();
+        for directory in ["tests", "src/app/tests", "src/app/migrations", "docs"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+
+        let exclusions = build_exclusions(
+            root,
+            &[
+                "tests",
+                "/docs/",
+                "src/*/migrations",
+                "*.pyi",
+                "!src/app/tests",
+            ],
+            false,
+        );
+
+        // Patterns without a slash match at any depth
+        assert!(exclusions.is_path_excluded(root.join("tests/test_a.py")));
+        assert!(exclusions.is_path_excluded(root.join("src/app/stubs.pyi")));
+        // Directory anchors
+        assert!(exclusions.is_path_excluded(root.join("docs/conf.py")));
+        assert!(!exclusions.is_path_excluded(root.join("src/docs.py")));
+        assert!(exclusions.is_path_excluded(root.join("src/app/migrations/0001.py")));
+        // Negation
+        assert!(!exclusions.is_path_excluded(root.join("src/app/tests/test_b.py")));
+        assert!(!exclusions.is_path_excluded(root.join("src/app/models.py")));
+    }
+
+    #[test]
+    fn test_respect_gitignore() {
+        let project_root = TempDir::new().unwrap();
+        let root = project_root.path();
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::write(root.join(".gitignore"), "build/\n*.gen.py\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "generated/\n!keep.gen.py\n").unwrap();
+        fs::write(root.join(".git/info/exclude"), "scratch.py\n").unwrap();
+
+        let exclusions = build_exclusions(root, &["!src/generated"], true);
+        assert!(exclusions.is_path_excluded(root.join("build/lib/a.py")));
+        assert!(exclusions.is_path_excluded(root.join("a.gen.py")));
+        assert!(exclusions.is_path_excluded(root.join("scratch.py")));
+        // Deeper '.gitignore' files take precedence
+        assert!(!exclusions.is_path_excluded(root.join("src/keep.gen.py")));
+        // Exclude patterns take precedence over '.gitignore' files
+        assert!(!exclusions.is_path_excluded(root.join("src/generated/models.py")));
+
+        let exclusions = build_exclusions(root, &[], false);
+        assert!(!exclusions.is_path_excluded(root.join("build/lib/a.py")));
+    }
+
+    #[test]
+    fn test_tachignore() {
+        let project_root = TempDir::new().unwrap();
+        let root = project_root.path();
+        fs::create_dir_all(root.join("src/vendor")).unwrap();
+        fs::write(root.join(".tachignore"), "*_pb2.py\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "local_settings.py\n").unwrap();
+        fs::write(
+            root.join("src/.tachignore"),
+            "vendor/\n!local_settings.py\n",
+        )
+        .unwrap();
+
+        let exclusions = build_exclusions(root, &[], true);
+        assert!(exclusions.is_path_excluded(root.join("src/api_pb2.py")));
+        assert!(exclusions.is_path_excluded(root.join("src/vendor/six.py")));
+        // '.tachignore' takes precedence over '.gitignore' in the same directory
+        assert!(!exclusions.is_path_excluded(root.join("src/local_settings.py")));
+
+        // '.tachignore' files are read even when '.gitignore' files are not
+        let exclusions = build_exclusions(root, &[], false);
+        assert!(exclusions.is_path_excluded(root.join("src/vendor/six.py")));
+
+        let exclusions = PathExclusions::none(root);
+        assert!(!exclusions.is_path_excluded(root.join("src/vendor/six.py")));
     }
 }
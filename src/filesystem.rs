@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::io::Read;
@@ -5,6 +6,7 @@ use std::path::StripPrefixError;
 use std::path::{Path, PathBuf, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 
 use cached::proc_macro::cached;
+use cached::Cached;
 use globset::Glob;
 use globset::GlobSetBuilder;
 use itertools::Itertools;
@@ -12,8 +14,9 @@ use thiserror::Error;
 use walkdir::{DirEntry, WalkDir};
 
 use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
-use crate::config::ModuleConfig;
+use crate::config::{ModuleConfig, ModulePathNormalizer, ProjectConfig, SymlinkPolicy};
 use crate::exclusion::PathExclusions;
+use crate::processors::notebook::{self, NotebookError};
 
 #[derive(Error, Debug)]
 pub enum FileSystemError {
@@ -22,20 +25,40 @@ pub enum FileSystemError {
     #[error("Path does not appear to be within project root.\n{0}")]
     StripPrefix(#[from] StripPrefixError),
     #[error("{0}")]
+    Notebook(#[from] NotebookError),
+    #[error("Symlink cycle detected: '{path}' points to its ancestor '{ancestor}'.\nSet 'symlinks' to 'follow' or 'skip' in your configuration to walk the project anyway.")]
+    SymlinkCycle { path: String, ancestor: String },
+    #[error("{0}")]
     Other(String),
 }
 pub type Result<T> = std::result::Result<T, FileSystemError>;
 
+/// In-memory file contents keyed by absolute path, such as unsaved editor buffers.
+/// Contents in the overlay take precedence over the contents on disk.
+pub type FileOverlay = HashMap<PathBuf, String>;
+
 pub fn relative_to<P: AsRef<Path>, R: AsRef<Path>>(path: P, root: R) -> Result<PathBuf> {
     let diff_path = path.as_ref().strip_prefix(root)?;
     Ok(diff_path.to_owned())
 }
 
 pub fn file_to_module_path(source_roots: &[PathBuf], file_path: &Path) -> Result<String> {
-    // Find the matching source root
+    normalized_file_to_module_path(source_roots, file_path, &[])
+}
+
+/// The module path of `file_path`, with `normalizers` applied in order to its directories.
+/// This is the path used to attribute the file to a module, which may not be importable.
+pub fn normalized_file_to_module_path(
+    source_roots: &[PathBuf],
+    file_path: &Path,
+    normalizers: &[ModulePathNormalizer],
+) -> Result<String> {
+    // Find the matching source root.
+    // When source roots are nested (e.g. '.' and 'src'), the most specific root wins.
     let matching_root = source_roots
         .iter()
-        .find(|&root| file_path.starts_with(root))
+        .filter(|&root| file_path.starts_with(root))
+        .max_by_key(|root| root.components().count())
         .ok_or(FileSystemError::Other(format!(
             "No matching source root found for filepath: {:?}",
             file_path
@@ -62,6 +85,9 @@ pub fn file_to_module_path(source_roots: &[PathBuf], file_path: &Path) -> Result
         .components()
         .filter_map(|component| component.as_os_str().to_str())
         .collect();
+    for normalizer in normalizers {
+        normalizer.normalize(&mut components);
+    }
 
     // Get the file name
     let file_name = relative_path
@@ -72,9 +98,9 @@ pub fn file_to_module_path(source_roots: &[PathBuf], file_path: &Path) -> Result
             relative_path
         )))?;
 
-    // If the file is not __init__.py, add its name (without extension) to the components
-    if file_name != "__init__.py" {
-        if let Some(stem) = Path::new(file_name).file_stem().and_then(|s| s.to_str()) {
+    // If the file is not __init__.py (or __init__.pyi), add its name (without extension) to the components
+    if let Some(stem) = Path::new(file_name).file_stem().and_then(|s| s.to_str()) {
+        if stem != "__init__" {
             components.push(stem);
         }
     }
@@ -106,6 +132,11 @@ fn is_potential_python_module_path(s: &str) -> bool {
         })
 }
 
+/// Release the memoized module resolutions, such as between source roots when memory is limited
+pub fn clear_module_resolution_cache() {
+    CACHED_MODULE_TO_FILE_PATH.lock().unwrap().cache_clear();
+}
+
 #[cached(
     key = "String",
     convert = r#"{
@@ -128,49 +159,48 @@ fn cached_module_to_file_path(
     }
 
     let mod_as_file_path = mod_path.replace('.', MAIN_SEPARATOR_STR);
+    // Check for [package with .pyi, .py] file or [.pyi, .py] file itself.
+    // Every root is checked before falling back to members, since a namespace package
+    // may be split across source roots (PEP 420).
     for root in roots {
-        let fs_path = root.join(&mod_as_file_path);
-
-        // Check for [package with .pyi, .py] file or [.pyi, .py] file itself
-        for path in &[
-            fs_path.join("__init__.pyi"),
-            fs_path.join("__init__.py"),
-            fs_path.with_extension("pyi"),
-            fs_path.with_extension("py"),
-        ] {
-            if path.exists() {
-                return Some(ResolvedModule {
-                    file_path: path.to_path_buf(),
-                    member_name: None,
-                });
-            }
-        }
-        // If the original file path does not contain a separator (e.g. 'os', 'ast')
-        // then we are done checking this root.
-        if !mod_as_file_path.contains(MAIN_SEPARATOR) || !check_members {
-            continue;
+        if let Some(file_path) = module_file_in_root(&root.join(&mod_as_file_path)) {
+            return Some(ResolvedModule {
+                file_path,
+                member_name: None,
+            });
         }
+    }
 
-        if let Some(last_sep_index) = mod_as_file_path.rfind(MAIN_SEPARATOR) {
-            let member_name = &mod_as_file_path[last_sep_index + 1..];
-            let base_fs_path = root.join(&mod_as_file_path[..last_sep_index]);
-
-            for path in &[
-                base_fs_path.join("__init__.pyi"),
-                base_fs_path.join("__init__.py"),
-                base_fs_path.with_extension("pyi"),
-                base_fs_path.with_extension("py"),
-            ] {
-                if path.exists() {
-                    return Some(ResolvedModule {
-                        file_path: path.to_path_buf(),
-                        member_name: Some(member_name.to_string()),
-                    });
-                }
-            }
-        }
+    // If the original file path does not contain a separator (e.g. 'os', 'ast')
+    // then there is no containing module to check for members.
+    if !check_members {
+        return None;
     }
-    None
+    let last_sep_index = mod_as_file_path.rfind(MAIN_SEPARATOR)?;
+    let member_name = &mod_as_file_path[last_sep_index + 1..];
+    roots.iter().find_map(|root| {
+        module_file_in_root(&root.join(&mod_as_file_path[..last_sep_index])).map(|file_path| {
+            ResolvedModule {
+                file_path,
+                member_name: Some(member_name.to_string()),
+            }
+        })
+    })
+}
+
+fn module_file_in_root(fs_path: &Path) -> Option<PathBuf> {
+    [
+        fs_path.join("__init__.pyi"),
+        fs_path.join("__init__.py"),
+        fs_path.with_extension("pyi"),
+        fs_path.with_extension("py"),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+fn is_regular_package(dir_path: &Path) -> bool {
+    dir_path.join("__init__.py").is_file() || dir_path.join("__init__.pyi").is_file()
 }
 
 pub fn module_to_file_path<P: AsRef<Path>>(
@@ -189,13 +219,18 @@ pub fn module_to_file_path<P: AsRef<Path>>(
     )
 }
 
-fn module_to_pyfile_or_dir_path<P: AsRef<Path>>(roots: &[P], mod_path: &str) -> Option<PathBuf> {
+pub fn module_to_pyfile_or_dir_path<P: AsRef<Path>>(
+    roots: &[P],
+    mod_path: &str,
+) -> Option<PathBuf> {
     if mod_path.is_empty() {
         return None;
     }
     let base_path = mod_path.replace('.', MAIN_SEPARATOR_STR);
 
-    // Iterate through each source root
+    // As in Python's import system, a regular package or module in any source root
+    // takes precedence over a namespace package directory found in an earlier root.
+    let mut namespace_dir: Option<PathBuf> = None;
     for source_root in roots {
         let source_root = source_root.as_ref();
 
@@ -205,14 +240,34 @@ fn module_to_pyfile_or_dir_path<P: AsRef<Path>>(roots: &[P], mod_path: &str) ->
         let pyfile_path = source_root.join(format!("{}.py", base_path));
 
         if dir_path.is_dir() {
-            return Some(dir_path);
+            if is_regular_package(&dir_path) {
+                return Some(dir_path);
+            }
+            namespace_dir.get_or_insert(dir_path);
         } else if pyinterface_path.exists() {
             return Some(pyinterface_path);
         } else if pyfile_path.exists() {
             return Some(pyfile_path);
         }
     }
-    None
+    namespace_dir
+}
+
+/// Returns the directories which make up the namespace package at `mod_path`,
+/// which may be spread across several source roots (PEP 420).
+/// Returns an empty list if `mod_path` is a regular package or module.
+pub fn namespace_package_portions<P: AsRef<Path>>(roots: &[P], mod_path: &str) -> Vec<PathBuf> {
+    match module_to_pyfile_or_dir_path(roots, mod_path) {
+        Some(path) if path.is_dir() && !is_regular_package(&path) => {
+            let base_path = mod_path.replace('.', MAIN_SEPARATOR_STR);
+            roots
+                .iter()
+                .map(|root| root.as_ref().join(&base_path))
+                .filter(|dir_path| dir_path.is_dir())
+                .collect()
+        }
+        _ => vec![],
+    }
 }
 
 pub fn module_path_is_included_in_paths(
@@ -220,11 +275,16 @@ pub fn module_path_is_included_in_paths(
     module_path: &str,
     included_paths: &[PathBuf],
 ) -> bool {
-    module_to_pyfile_or_dir_path(source_roots, module_path).is_some_and(|path| {
+    let is_included = |path: &PathBuf| {
         included_paths
             .iter()
             .any(|included_path| path.starts_with(included_path))
-    })
+    };
+    let portions = namespace_package_portions(source_roots, module_path);
+    if !portions.is_empty() {
+        return portions.iter().any(is_included);
+    }
+    module_to_pyfile_or_dir_path(source_roots, module_path).is_some_and(|path| is_included(&path))
 }
 
 pub fn is_project_import<P: AsRef<Path>>(
@@ -236,6 +296,16 @@ pub fn is_project_import<P: AsRef<Path>>(
     resolved_module.is_some_and(|module| !exclusions.is_path_excluded(&module.file_path))
 }
 
+/// Whether an import which does not resolve to any module looks like it was meant to refer to the project,
+/// because its top-level package (or module) is found in a source root.
+/// Namespace package directories are not considered, since any directory would match.
+pub fn is_unresolved_project_import<P: AsRef<Path>>(source_roots: &[P], mod_path: &str) -> bool {
+    let top_level_name = mod_path.split('.').next().unwrap_or_default();
+    let top_level_is_project = module_to_pyfile_or_dir_path(source_roots, top_level_name)
+        .is_some_and(|path| !path.is_dir() || is_regular_package(&path));
+    top_level_is_project && module_to_file_path(source_roots, mod_path, true).is_none()
+}
+
 pub fn read_file_content<P: AsRef<Path>>(path: P) -> Result<String> {
     let mut file = fs::File::open(path.as_ref())?;
     let mut content = String::new();
@@ -243,6 +313,16 @@ pub fn read_file_content<P: AsRef<Path>>(path: P) -> Result<String> {
     Ok(content)
 }
 
+pub fn read_file_content_with_overlay<P: AsRef<Path>>(
+    path: P,
+    overlay: &FileOverlay,
+) -> Result<String> {
+    match overlay.get(path.as_ref()) {
+        Some(content) => Ok(content.clone()),
+        None => read_file_content(path),
+    }
+}
+
 fn is_hidden(entry: &DirEntry) -> bool {
     entry
         .file_name()
@@ -255,20 +335,59 @@ fn direntry_is_excluded(entry: &DirEntry, exclusions: &PathExclusions) -> bool {
     exclusions.is_path_excluded(entry.path())
 }
 
+pub const PROJECT_CONFIG_FILE_NAMES: [&str; 3] = ["tach.toml", "tach.yml", "tach.yaml"];
+
 fn direntry_is_tach_project(entry: &DirEntry) -> bool {
-    entry.path().join("tach.toml").is_file()
+    PROJECT_CONFIG_FILE_NAMES
+        .iter()
+        .any(|name| entry.path().join(name).is_file())
 }
 
-fn is_pyfile_or_dir(entry: &DirEntry) -> bool {
-    if entry.file_type().is_dir() {
-        return true;
-    }
-    match entry.path().extension() {
-        Some(ext) => ext == "py",
+/// Returns the module paths of the packages directly below `source_root`.
+/// Directories without an `__init__.py` are only included when `namespace_packages` is set,
+/// and only if their name is a valid Python identifier.
+pub fn top_level_packages(
+    source_root: &Path,
+    exclusions: &PathExclusions,
+    namespace_packages: bool,
+) -> Vec<String> {
+    WalkDir::new(source_root)
+        .min_depth(1)
+        .max_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !is_hidden(e) && !direntry_is_excluded(e, exclusions))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+        .filter(|entry| namespace_packages || is_regular_package(entry.path()))
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| {
+            !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        })
+        .collect()
+}
+
+fn direntry_is_namespace_package(entry: &DirEntry) -> bool {
+    // The walk root is a source root, which never needs an '__init__.py'
+    entry.depth() > 0 && entry.file_type().is_dir() && !is_regular_package(entry.path())
+}
+
+fn is_walked_file(path: &Path, options: WalkOptions) -> bool {
+    match path.extension() {
+        Some(ext) => {
+            ext == "py"
+                || (options.include_stubs && ext == "pyi")
+                || (options.include_notebooks && ext == "ipynb")
+        }
         None => false,
     }
 }
 
+fn is_pyfile_or_dir(entry: &DirEntry, options: WalkOptions) -> bool {
+    entry.file_type().is_dir() || is_walked_file(entry.path(), options)
+}
+
 #[derive(Debug)]
 pub struct ProjectFile<'a> {
     pub project_root: &'a Path,
@@ -283,9 +402,18 @@ impl<'a> ProjectFile<'a> {
         project_root: &'a Path,
         source_root: &'a Path,
         file_path: &'a Path,
+    ) -> Result<Self> {
+        Self::try_new_with_overlay(project_root, source_root, file_path, &FileOverlay::new())
+    }
+
+    pub fn try_new_with_overlay(
+        project_root: &'a Path,
+        source_root: &'a Path,
+        file_path: &'a Path,
+        overlay: &FileOverlay,
     ) -> Result<Self> {
         let absolute_file_path = source_root.join(file_path);
-        let contents = read_file_content(&absolute_file_path)?;
+        let contents = read_file_content_with_overlay(&absolute_file_path, overlay)?;
         Ok(Self {
             project_root,
             source_root,
@@ -294,6 +422,45 @@ impl<'a> ProjectFile<'a> {
             contents,
         })
     }
+
+    pub fn try_new_all(
+        project_root: &'a Path,
+        source_root: &'a Path,
+        file_path: &'a Path,
+    ) -> Result<Vec<Self>> {
+        Self::try_new_all_with_overlay(project_root, source_root, file_path, &FileOverlay::new())
+    }
+
+    /// Same as `try_new_with_overlay`, but a notebook yields one file per code cell.
+    /// Cells keep the notebook as their `file_path`, and are identified by a pseudo `relative_file_path`.
+    pub fn try_new_all_with_overlay(
+        project_root: &'a Path,
+        source_root: &'a Path,
+        file_path: &'a Path,
+        overlay: &FileOverlay,
+    ) -> Result<Vec<Self>> {
+        if !notebook::is_notebook_path(file_path) {
+            return Ok(vec![Self::try_new_with_overlay(
+                project_root,
+                source_root,
+                file_path,
+                overlay,
+            )?]);
+        }
+        let absolute_file_path = source_root.join(file_path);
+        let relative_file_path = relative_to(&absolute_file_path, project_root)?;
+        let contents = read_file_content_with_overlay(&absolute_file_path, overlay)?;
+        Ok(notebook::notebook_code_cells(&contents)?
+            .into_iter()
+            .map(|cell| Self {
+                project_root,
+                source_root,
+                file_path: absolute_file_path.clone(),
+                relative_file_path: notebook::cell_file_path(&relative_file_path, cell.index),
+                contents: cell.source,
+            })
+            .collect())
+    }
 }
 
 impl AsRef<Path> for ProjectFile<'_> {
@@ -302,18 +469,83 @@ impl AsRef<Path> for ProjectFile<'_> {
     }
 }
 
+/// Determines which files are walked as part of the project
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+    /// Unless set, directories below a source root without an `__init__.py`
+    /// are not treated as packages, and their contents are skipped.
+    pub namespace_packages: bool,
+    /// Whether `.pyi` stub files are walked alongside `.py` files
+    pub include_stubs: bool,
+    /// Whether Jupyter notebooks (`.ipynb`) are walked alongside `.py` files
+    pub include_notebooks: bool,
+    /// Whether symlinked files and directories are walked
+    pub symlinks: SymlinkPolicy,
+}
+
+impl WalkOptions {
+    /// Walk every Python file, regardless of project configuration
+    pub fn all() -> Self {
+        Self {
+            namespace_packages: true,
+            include_stubs: true,
+            include_notebooks: true,
+            symlinks: SymlinkPolicy::default(),
+        }
+    }
+}
+
+impl From<&ProjectConfig> for WalkOptions {
+    fn from(project_config: &ProjectConfig) -> Self {
+        Self {
+            namespace_packages: project_config.namespace_packages,
+            include_stubs: project_config.include_stubs,
+            include_notebooks: project_config.include_notebooks,
+            symlinks: project_config.symlinks,
+        }
+    }
+}
+
+fn is_walked_entry(entry: &DirEntry, exclusions: &PathExclusions, options: WalkOptions) -> bool {
+    !is_hidden(entry)
+        && !direntry_is_excluded(entry, exclusions)
+        && is_pyfile_or_dir(entry, options)
+        && (options.namespace_packages || !direntry_is_namespace_package(entry))
+}
+
+fn pyfile_walker(root: &str, options: WalkOptions) -> WalkDir {
+    match options.symlinks {
+        SymlinkPolicy::Skip => WalkDir::new(root),
+        // Sorting keeps the first path to a file (and so the one which is yielded) stable
+        SymlinkPolicy::Follow | SymlinkPolicy::ErrorOnCycle => {
+            WalkDir::new(root).follow_links(true).sort_by_file_name()
+        }
+    }
+}
+
+/// Yields the Python files below `root`, relative to `root`.
+///
+/// Symlinks are handled according to `options.symlinks`.
+/// When they are followed, a file reachable through several paths is only yielded once,
+/// and links pointing back to one of their ancestors are not walked.
 pub fn walk_pyfiles<'a>(
     root: &str,
     exclusions: &'a PathExclusions,
+    options: WalkOptions,
 ) -> impl Iterator<Item = PathBuf> + 'a {
     let prefix_root = root.to_string();
-    WalkDir::new(root)
+    let mut walked_files = HashSet::new();
+    pyfile_walker(root, options)
         .into_iter()
-        .filter_entry(|e| {
-            !is_hidden(e) && !direntry_is_excluded(e, exclusions) && is_pyfile_or_dir(e)
-        })
-        .filter_map(|entry| entry.ok())
+        .filter_entry(move |e| is_walked_entry(e, exclusions, options))
+        .filter_map(|entry| entry.ok()) // symlink cycles surface as errors here
         .filter(|entry| entry.file_type().is_file()) // filter_entry would skip dirs if they were excluded earlier
+        .filter(move |entry| {
+            options.symlinks == SymlinkPolicy::Skip
+                || walked_files.insert(
+                    fs::canonicalize(entry.path()).unwrap_or_else(|_| entry.path().to_path_buf()),
+                )
+        })
         .map(move |entry| {
             entry
                 .path()
@@ -323,6 +555,48 @@ pub fn walk_pyfiles<'a>(
         })
 }
 
+/// Returns an error for the first symlink below `root` which points to one of its own ancestors.
+/// Only the directories which `walk_pyfiles` would walk are checked.
+pub fn check_symlink_cycles(
+    root: &str,
+    exclusions: &PathExclusions,
+    options: WalkOptions,
+) -> Result<()> {
+    let walker = WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| is_walked_entry(e, exclusions, options));
+    for entry in walker {
+        if let Err(error) = entry {
+            if let (Some(path), Some(ancestor)) = (error.path(), error.loop_ancestor()) {
+                return Err(FileSystemError::SymlinkCycle {
+                    path: path.display().to_string(),
+                    ancestor: ancestor.display().to_string(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Same as `walk_pyfiles`, but also yields Python files below `root`
+/// which only exist in the overlay (e.g. new files which have not been saved yet).
+pub fn walk_pyfiles_with_overlay<'a>(
+    root: &str,
+    exclusions: &'a PathExclusions,
+    overlay: &'a FileOverlay,
+    options: WalkOptions,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    let prefix_root = PathBuf::from(root);
+    let overlay_only_files = overlay
+        .keys()
+        .filter(move |path| {
+            !path.exists() && is_walked_file(path, options) && !exclusions.is_path_excluded(path)
+        })
+        .filter_map(move |path| path.strip_prefix(&prefix_root).ok().map(PathBuf::from));
+    walk_pyfiles(root, exclusions, options).chain(overlay_only_files)
+}
+
 pub fn walk_pyprojects<'a>(
     root: &str,
     exclusions: &'a PathExclusions,
@@ -406,6 +680,9 @@ mod tests {
     #[case(&["."], "domain_one/interface.py", "domain_one.interface")]
     #[case(&["source/root"], "source/root/domain.py", "domain")]
     #[case(&["src1", "src2"], "src1/core/lib/cat.py", "core.lib.cat")]
+    #[case(&[".", "source/root"], "source/root/domain.py", "domain")]
+    #[case(&["."], "domain_one/__init__.pyi", "domain_one")]
+    #[case(&["."], "domain_one/interface.pyi", "domain_one.interface")]
     fn test_file_to_mod_path(
         tests_dir: PathBuf,
         #[case] roots: &[&str],
@@ -425,4 +702,256 @@ mod tests {
             expected
         );
     }
+
+    #[rstest]
+    #[case("src/pkg/api.py", &[ModulePathNormalizer::StripSrc], "pkg.api")]
+    #[case("pkg_v2/__init__.py", &[ModulePathNormalizer::StripVersionSuffix], "pkg")]
+    #[case("tests/unit/pkg_v2/test_api_v2.py", &[ModulePathNormalizer::UnitTests, ModulePathNormalizer::StripVersionSuffix], "pkg.test_api_v2")]
+    #[case("tests/unit/pkg_v2/test_api.py", &[ModulePathNormalizer::StripVersionSuffix, ModulePathNormalizer::UnitTests], "pkg.test_api")]
+    #[case("src/tests/unit/pkg/test_api.py", &[ModulePathNormalizer::UnitTests, ModulePathNormalizer::StripSrc], "tests.unit.pkg.test_api")]
+    fn test_normalized_file_to_mod_path(
+        #[case] file_path: &str,
+        #[case] normalizers: &[ModulePathNormalizer],
+        #[case] expected: &str,
+    ) {
+        let root = PathBuf::from("/project");
+        assert_eq!(
+            normalized_file_to_module_path(&[root.clone()], &root.join(file_path), normalizers)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_namespace_packages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let roots = [temp_dir.path().join("src1"), temp_dir.path().join("src2")];
+        // 'ns' is a namespace package split across both roots, 'ns.regular' is a regular package
+        for file_path in [
+            "src1/ns/a.py",
+            "src2/ns/b.py",
+            "src2/ns/regular/__init__.py",
+        ] {
+            let file_path = temp_dir.path().join(file_path);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, "").unwrap();
+        }
+        fs::create_dir_all(roots[0].join("ns/regular")).unwrap();
+
+        let resolved = module_to_file_path(&roots, "ns.b.member", true).unwrap();
+        assert_eq!(resolved.file_path, roots[1].join("ns/b.py"));
+        assert_eq!(resolved.member_name.as_deref(), Some("member"));
+        // The regular package takes precedence over the namespace directory in the earlier root
+        assert_eq!(
+            module_to_pyfile_or_dir_path(&roots, "ns.regular"),
+            Some(roots[1].join("ns/regular"))
+        );
+        assert_eq!(
+            namespace_package_portions(&roots, "ns"),
+            vec![roots[0].join("ns"), roots[1].join("ns")]
+        );
+        assert!(module_path_is_included_in_paths(
+            &roots,
+            "ns",
+            &[roots[1].clone()]
+        ));
+
+        let exclusions = PathExclusions::none(temp_dir.path());
+        let root = roots[1].to_str().unwrap();
+        let options = WalkOptions {
+            namespace_packages: true,
+            include_stubs: false,
+            include_notebooks: false,
+            symlinks: SymlinkPolicy::Skip,
+        };
+        let mut files: Vec<PathBuf> = walk_pyfiles(root, &exclusions, options).collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("ns/b.py"),
+                PathBuf::from("ns/regular/__init__.py")
+            ]
+        );
+        let options = WalkOptions {
+            namespace_packages: false,
+            ..options
+        };
+        assert_eq!(walk_pyfiles(root, &exclusions, options).count(), 0);
+    }
+
+    #[rstest]
+    fn test_walk_stubs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("typed")).unwrap();
+        for file_name in ["typed/__init__.pyi", "typed/impl.py", "typed/impl.pyi"] {
+            fs::write(root.join(file_name), "").unwrap();
+        }
+        let exclusions = PathExclusions::none(root);
+        let options = WalkOptions {
+            namespace_packages: false,
+            include_stubs: true,
+            include_notebooks: false,
+            symlinks: SymlinkPolicy::Skip,
+        };
+
+        let mut files: Vec<PathBuf> =
+            walk_pyfiles(root.to_str().unwrap(), &exclusions, options).collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("typed/__init__.pyi"),
+                PathBuf::from("typed/impl.py"),
+                PathBuf::from("typed/impl.pyi")
+            ]
+        );
+        let options = WalkOptions {
+            include_stubs: false,
+            ..options
+        };
+        assert_eq!(
+            walk_pyfiles(root.to_str().unwrap(), &exclusions, options).collect::<Vec<_>>(),
+            vec![PathBuf::from("typed/impl.py")]
+        );
+    }
+
+    #[rstest]
+    fn test_notebook_project_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("notebooks")).unwrap();
+        fs::write(root.join("notebooks/__init__.py"), "").unwrap();
+        fs::write(
+            root.join("notebooks/analysis.ipynb"),
+            r##"{"cells": [{"cell_type": "markdown", "source": "# Title"}, {"cell_type": "code", "source": ["import core"]}]}"##,
+        )
+        .unwrap();
+        let exclusions = PathExclusions::none(root);
+        let options = WalkOptions {
+            namespace_packages: false,
+            include_stubs: false,
+            include_notebooks: true,
+            symlinks: SymlinkPolicy::Skip,
+        };
+
+        let mut files: Vec<PathBuf> =
+            walk_pyfiles(root.to_str().unwrap(), &exclusions, options).collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("notebooks/__init__.py"),
+                PathBuf::from("notebooks/analysis.ipynb")
+            ]
+        );
+
+        let notebook_path = PathBuf::from("notebooks/analysis.ipynb");
+        let cells =
+            ProjectFile::try_new_all_with_overlay(root, root, &notebook_path, &FileOverlay::new())
+                .unwrap();
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].file_path, root.join("notebooks/analysis.ipynb"));
+        assert_eq!(
+            cells[0].relative_file_path,
+            PathBuf::from("notebooks/analysis.ipynb#cell-2")
+        );
+        assert_eq!(cells[0].contents, "import core");
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    fn test_walk_symlinks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("vendor/lib")).unwrap();
+        fs::write(root.join("app/__init__.py"), "").unwrap();
+        fs::write(root.join("vendor/lib/__init__.py"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("vendor/lib"), root.join("app/lib")).unwrap();
+        let exclusions = PathExclusions::none(root);
+        let walk = |symlinks| {
+            let options = WalkOptions {
+                symlinks,
+                ..WalkOptions::all()
+            };
+            let mut files: Vec<PathBuf> =
+                walk_pyfiles(root.to_str().unwrap(), &exclusions, options).collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(
+            walk(SymlinkPolicy::Skip),
+            vec![
+                PathBuf::from("app/__init__.py"),
+                PathBuf::from("vendor/lib/__init__.py")
+            ]
+        );
+        // The vendored package is only yielded once, through the first path to it
+        let followed = vec![
+            PathBuf::from("app/__init__.py"),
+            PathBuf::from("app/lib/__init__.py"),
+        ];
+        assert_eq!(walk(SymlinkPolicy::Follow), followed);
+
+        std::os::unix::fs::symlink(root.join("app"), root.join("app/cycle")).unwrap();
+        assert_eq!(walk(SymlinkPolicy::Follow), followed);
+        let options = WalkOptions {
+            symlinks: SymlinkPolicy::ErrorOnCycle,
+            ..WalkOptions::all()
+        };
+        assert!(matches!(
+            check_symlink_cycles(root.to_str().unwrap(), &exclusions, options),
+            Err(FileSystemError::SymlinkCycle { .. })
+        ));
+    }
+
+    #[rstest]
+    fn test_walk_pyfiles_with_overlay() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("on_disk.py"), "import os").unwrap();
+        let overlay = FileOverlay::from([
+            (root.join("on_disk.py"), "import sys".to_string()),
+            (root.join("unsaved.py"), "".to_string()),
+            (root.join("notes.txt"), "".to_string()),
+        ]);
+        let exclusions = PathExclusions::none(root);
+
+        let mut files: Vec<PathBuf> = walk_pyfiles_with_overlay(
+            root.to_str().unwrap(),
+            &exclusions,
+            &overlay,
+            WalkOptions::all(),
+        )
+        .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("on_disk.py"), PathBuf::from("unsaved.py")]
+        );
+        assert_eq!(
+            read_file_content_with_overlay(root.join("on_disk.py"), &overlay).unwrap(),
+            "import sys"
+        );
+    }
+
+    #[rstest]
+    fn test_is_unresolved_project_import() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let roots = [temp_dir.path().to_path_buf()];
+        for file_name in ["app/__init__.py", "app/models.py", "scripts/run.py"] {
+            let file_path = temp_dir.path().join(file_name);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, "").unwrap();
+        }
+
+        assert!(is_unresolved_project_import(&roots, "app.modles.User"));
+        assert!(!is_unresolved_project_import(&roots, "app.models.User"));
+        // Namespace package directories and unknown packages are assumed to be external
+        assert!(!is_unresolved_project_import(&roots, "scripts.missing"));
+        assert!(!is_unresolved_project_import(&roots, "requests.get"));
+    }
 }
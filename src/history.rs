@@ -0,0 +1,547 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::root_module::ROOT_MODULE_SENTINEL_TAG;
+use crate::config::ProjectConfig;
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::exclusion::{PathExclusionError, PathExclusions};
+use crate::filesystem::{self as tach_fs, FileSystemError, WalkOptions};
+use crate::hash::fnv1a;
+use crate::processors::ignore_directive::get_ignore_directives;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Failed to access run history file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize run history: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("No recorded run matches '{0}'. Use 'tach history list' to see the recorded runs.")]
+    RunNotFound(String),
+    #[error("'{0}' matches more than one recorded run. Use more characters of the run ID.")]
+    AmbiguousRun(String),
+    #[error(
+        "Invalid duration '{0}', expected a number followed by 'm', 'h', 'd' or 'w' (e.g. '24h')."
+    )]
+    InvalidDuration(String),
+    #[error("Failed to read source files: {0}")]
+    Filesystem(#[from] FileSystemError),
+    #[error("Path exclusion error: {0}")]
+    PathExclusion(#[from] PathExclusionError),
+}
+
+pub type Result<T> = std::result::Result<T, HistoryError>;
+
+static HISTORY_DIR: &str = ".tach";
+static HISTORY_FILE: &str = "history.jsonl";
+
+/// Older runs are removed once more than this many have been recorded
+const MAX_RUNS: usize = 100;
+
+/// A diagnostic reported by a recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass(get_all, eq, module = "tach.extension")]
+pub struct HistoryFinding {
+    // Identifies the same finding across runs, even when it moves to another line
+    pub fingerprint: String,
+    // 'error' or 'warning'
+    pub severity: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+    pub message: String,
+}
+
+/// The summary and findings of a single recorded 'tach check'
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct HistoryRun {
+    pub id: String,
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+    // The git commit which was checked out, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub findings: Vec<HistoryFinding>,
+    // The number of ignore directives in the source files, missing in runs of older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressions: Option<usize>,
+    // The share of source files which belong to a declared module, between 0 and 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_coverage: Option<f64>,
+}
+
+/// The findings which appeared or disappeared between two recorded runs
+#[derive(Debug, Clone, PartialEq)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct HistoryDiff {
+    pub base: HistoryRun,
+    pub head: HistoryRun,
+    pub new: Vec<HistoryFinding>,
+    pub fixed: Vec<HistoryFinding>,
+}
+
+fn history_path(project_root: &Path) -> PathBuf {
+    project_root.join(HISTORY_DIR).join(HISTORY_FILE)
+}
+
+fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Fingerprints combine the file and message of a diagnostic, along with how many identical
+/// diagnostics came before it in the same file. Line numbers are left out, so that a finding
+/// keeps its fingerprint when unrelated code above it changes.
+pub(crate) fn to_findings(diagnostics: &[Diagnostic]) -> Vec<HistoryFinding> {
+    let mut occurrences: HashMap<(Option<String>, String), usize> = HashMap::new();
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let file_path = diagnostic
+                .file_path()
+                .map(|path| path.display().to_string());
+            let message = diagnostic.message();
+            let occurrence = occurrences
+                .entry((file_path.clone(), message.clone()))
+                .or_default();
+            *occurrence += 1;
+            let fingerprint = fnv1a(
+                file_path
+                    .iter()
+                    .flat_map(|path| path.bytes())
+                    .chain([0])
+                    .chain(message.bytes())
+                    .chain(occurrence.to_le_bytes()),
+            );
+            HistoryFinding {
+                fingerprint: format!("{:016x}", fingerprint),
+                severity: diagnostic.severity().to_string().to_lowercase(),
+                file_path,
+                line_number: diagnostic.line_number(),
+                message,
+            }
+        })
+        .collect()
+}
+
+fn current_commit(project_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(project_root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+pub fn load_runs(project_root: &Path) -> Result<Vec<HistoryRun>> {
+    let path = history_path(project_root);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    // Skip malformed lines rather than failing, the file may have been
+    // truncated by an interrupted write.
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The number of ignore directives in the source files, and the share of source files
+/// which belong to a declared module
+fn boundary_totals(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+) -> Result<(usize, Option<f64>)> {
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let source_roots = project_config.prepend_roots(project_root);
+    let module_paths: Vec<String> = project_config
+        .module_paths()
+        .into_iter()
+        .filter(|path| path != ROOT_MODULE_SENTINEL_TAG)
+        .collect();
+    let (mut suppressions, mut files, mut covered_files) = (0, 0, 0);
+    for source_root in &source_roots {
+        for file_path in tach_fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            WalkOptions {
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        ) {
+            let file_path = source_root.join(file_path);
+            let module_path = tach_fs::normalized_file_to_module_path(
+                &source_roots,
+                &file_path,
+                &project_config.module_path_normalizers,
+            )?;
+            files += 1;
+            if module_paths.iter().any(|path| {
+                module_path == *path
+                    || module_path
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            }) {
+                covered_files += 1;
+            }
+            suppressions += get_ignore_directives(&tach_fs::read_file_content(&file_path)?).len();
+        }
+    }
+    let coverage = (files > 0).then(|| covered_files as f64 / files as f64);
+    Ok((suppressions, coverage))
+}
+
+/// Summarize the result of a check as a run, without recording it
+fn snapshot_run(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    diagnostics: &[Diagnostic],
+) -> HistoryRun {
+    let timestamp = now_timestamp();
+    let findings = to_findings(diagnostics);
+    let id = fnv1a(
+        timestamp
+            .to_le_bytes()
+            .into_iter()
+            .chain(
+                findings
+                    .iter()
+                    .flat_map(|finding| finding.fingerprint.bytes()),
+            )
+            .chain(std::process::id().to_le_bytes()),
+    );
+    // The totals are informational, so a file which cannot be read should not prevent recording
+    let (suppressions, module_coverage) = match boundary_totals(project_root, project_config) {
+        Ok((suppressions, coverage)) => (Some(suppressions), coverage),
+        Err(_) => (None, None),
+    };
+    HistoryRun {
+        id: format!("{:016x}", id)[..8].to_string(),
+        timestamp,
+        commit: current_commit(project_root),
+        errors: diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+            .count(),
+        warnings: diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Warning)
+            .count(),
+        findings,
+        suppressions,
+        module_coverage,
+    }
+}
+
+/// Record the result of a check (no-op unless opted in), returning the new run
+pub fn record_run(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    diagnostics: &[Diagnostic],
+) -> Result<Option<HistoryRun>> {
+    if !project_config.record_history {
+        return Ok(None);
+    }
+
+    let run = snapshot_run(project_root, project_config, diagnostics);
+    let path = history_path(project_root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut runs = load_runs(project_root)?;
+    if runs.len() < MAX_RUNS {
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&run)?)?;
+    } else {
+        runs.drain(..=runs.len() - MAX_RUNS);
+        runs.push(run.clone());
+        let mut contents = String::new();
+        for run in &runs {
+            contents.push_str(&serde_json::to_string(run)?);
+            contents.push('\n');
+        }
+        fs::write(&path, contents)?;
+    }
+    Ok(Some(run))
+}
+
+/// Find a run by 'latest', '~N' (the Nth run before the latest), or a prefix of its ID
+pub fn resolve_run<'a>(runs: &'a [HistoryRun], reference: &str) -> Result<&'a HistoryRun> {
+    let not_found = || HistoryError::RunNotFound(reference.to_string());
+    let offset = match reference {
+        "latest" => Some(0),
+        _ => reference
+            .strip_prefix('~')
+            .map(|offset| offset.parse::<usize>().map_err(|_| not_found()))
+            .transpose()?,
+    };
+    if let Some(offset) = offset {
+        return runs.iter().rev().nth(offset).ok_or_else(not_found);
+    }
+
+    let mut matches = runs.iter().filter(|run| run.id.starts_with(reference));
+    match (matches.next(), matches.next()) {
+        (Some(run), None) => Ok(run),
+        (Some(_), Some(_)) => Err(HistoryError::AmbiguousRun(reference.to_string())),
+        _ => Err(not_found()),
+    }
+}
+
+/// Parse durations such as '30m', '24h', '7d' or '2w' into seconds
+pub fn parse_duration(duration: &str) -> Result<u64> {
+    let invalid = || HistoryError::InvalidDuration(duration.to_string());
+    let unit_index = duration
+        .len()
+        .checked_sub(1)
+        .filter(|index| duration.is_char_boundary(*index))
+        .ok_or_else(invalid)?;
+    let (amount, unit) = duration.split_at(unit_index);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(amount * unit_seconds)
+}
+
+/// The most recent run which was recorded at least `seconds` before `now`
+pub fn run_before(runs: &[HistoryRun], seconds: u64, now: u64) -> Option<&HistoryRun> {
+    let cutoff = now.saturating_sub(seconds);
+    runs.iter().rev().find(|run| run.timestamp <= cutoff)
+}
+
+/// The findings of `head` which are missing from `base`, and those of `base` which are missing from `head`
+pub(crate) fn diff_findings(
+    base: &[HistoryFinding],
+    head: &[HistoryFinding],
+) -> (Vec<HistoryFinding>, Vec<HistoryFinding>) {
+    let fingerprints = |findings: &[HistoryFinding]| -> HashSet<String> {
+        findings
+            .iter()
+            .map(|finding| finding.fingerprint.clone())
+            .collect()
+    };
+    let base_fingerprints = fingerprints(base);
+    let head_fingerprints = fingerprints(head);
+    (
+        head.iter()
+            .filter(|finding| !base_fingerprints.contains(&finding.fingerprint))
+            .cloned()
+            .collect(),
+        base.iter()
+            .filter(|finding| !head_fingerprints.contains(&finding.fingerprint))
+            .cloned()
+            .collect(),
+    )
+}
+
+pub fn diff_runs(base: &HistoryRun, head: &HistoryRun) -> HistoryDiff {
+    let (new, fixed) = diff_findings(&base.findings, &head.findings);
+    HistoryDiff {
+        new,
+        fixed,
+        base: base.clone(),
+        head: head.clone(),
+    }
+}
+
+/// Compare two recorded runs. By default, the latest run is compared against the run before it.
+/// With `since`, the base is instead the latest run recorded at least that long ago.
+pub fn diff_history(
+    project_root: &Path,
+    base: Option<&str>,
+    head: Option<&str>,
+    since: Option<&str>,
+) -> Result<HistoryDiff> {
+    let runs = load_runs(project_root)?;
+    let head = resolve_run(&runs, head.unwrap_or("latest"))?;
+    let base = match (base, since) {
+        (Some(base), _) => resolve_run(&runs, base)?,
+        (None, Some(since)) => run_before(&runs, parse_duration(since)?, now_timestamp())
+            .ok_or_else(|| HistoryError::RunNotFound(format!("{} ago", since)))?,
+        (None, None) => resolve_run(&runs, "~1")?,
+    };
+    Ok(diff_runs(base, head))
+}
+
+/// Compare the result of a check which has not been recorded yet against a recorded run,
+/// by default the latest one
+pub fn diff_against_history(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    diagnostics: &[Diagnostic],
+    base: Option<&str>,
+) -> Result<HistoryDiff> {
+    let runs = load_runs(project_root)?;
+    let base = resolve_run(&runs, base.unwrap_or("latest"))?;
+    let head = snapshot_run(project_root, project_config, diagnostics);
+    Ok(diff_runs(base, &head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::{CodeDiagnostic, DiagnosticDetails};
+    use rstest::rstest;
+
+    fn undeclared(file_path: &str, line_number: usize, dependency: &str) -> Diagnostic {
+        Diagnostic::new_located_error(
+            PathBuf::from(file_path),
+            line_number,
+            None,
+            DiagnosticDetails::Code(CodeDiagnostic::UndeclaredDependency {
+                dependency: dependency.to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "b".to_string(),
+            }),
+        )
+    }
+
+    fn run(id: &str, timestamp: u64, diagnostics: &[Diagnostic]) -> HistoryRun {
+        HistoryRun {
+            id: id.to_string(),
+            timestamp,
+            commit: None,
+            errors: diagnostics.len(),
+            warnings: 0,
+            findings: to_findings(diagnostics),
+            suppressions: None,
+            module_coverage: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_runs_ignores_moved_lines() {
+        let base = run(
+            "aaaa1111",
+            0,
+            &[undeclared("a.py", 1, "b.x"), undeclared("a.py", 2, "b.y")],
+        );
+        let head = run(
+            "bbbb2222",
+            10,
+            &[
+                undeclared("a.py", 5, "b.y"),
+                undeclared("a.py", 6, "b.z"),
+                undeclared("a.py", 7, "b.z"),
+            ],
+        );
+
+        let diff = diff_runs(&base, &head);
+        let lines = |findings: &[HistoryFinding]| -> Vec<Option<usize>> {
+            findings.iter().map(|finding| finding.line_number).collect()
+        };
+        assert_eq!(lines(&diff.new), vec![Some(6), Some(7)]);
+        assert_eq!(lines(&diff.fixed), vec![Some(1)]);
+    }
+
+    #[test]
+    fn test_resolve_run() {
+        let runs = vec![run("aaaa1111", 0, &[]), run("aabb2222", 10, &[])];
+        assert_eq!(resolve_run(&runs, "latest").unwrap().id, "aabb2222");
+        assert_eq!(resolve_run(&runs, "~1").unwrap().id, "aaaa1111");
+        assert_eq!(resolve_run(&runs, "aaaa").unwrap().id, "aaaa1111");
+        assert!(matches!(
+            resolve_run(&runs, "aa"),
+            Err(HistoryError::AmbiguousRun(_))
+        ));
+        assert!(matches!(
+            resolve_run(&runs, "~2"),
+            Err(HistoryError::RunNotFound(_))
+        ));
+        assert_eq!(run_before(&runs, 5, 12).unwrap().id, "aaaa1111");
+        assert!(run_before(&runs, 20, 12).is_none());
+    }
+
+    #[rstest]
+    #[case("30m", Some(30 * 60))]
+    #[case("24h", Some(24 * 60 * 60))]
+    #[case("2w", Some(14 * 24 * 60 * 60))]
+    #[case("h", None)]
+    #[case("10", None)]
+    #[case("", None)]
+    fn test_parse_duration(#[case] duration: &str, #[case] expected: Option<u64>) {
+        assert_eq!(parse_duration(duration).ok(), expected);
+    }
+
+    #[test]
+    fn test_record_run_keeps_latest_runs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project_config = ProjectConfig {
+            record_history: true,
+            ..Default::default()
+        };
+        for _ in 0..MAX_RUNS + 2 {
+            record_run(
+                temp_dir.path(),
+                &project_config,
+                &[undeclared("a.py", 1, "b.x")],
+            )
+            .unwrap();
+        }
+        let runs = load_runs(temp_dir.path()).unwrap();
+        assert_eq!(runs.len(), MAX_RUNS);
+        assert_eq!(runs[0].errors, 1);
+        assert_eq!(runs[0].findings, runs[MAX_RUNS - 1].findings);
+    }
+
+    #[test]
+    fn test_diff_against_history() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("a")).unwrap();
+        fs::write(
+            temp_dir.path().join("a/__init__.py"),
+            "import b.x  # tach-ignore\nimport b.y\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("b.py"), "x = 1\ny = 2\n").unwrap();
+        let project_config = ProjectConfig {
+            modules: vec![crate::config::ModuleConfig::new("a", false)],
+            record_history: true,
+            ..Default::default()
+        };
+        record_run(
+            temp_dir.path(),
+            &project_config,
+            &[undeclared("a/__init__.py", 1, "b.x")],
+        )
+        .unwrap();
+
+        let diff = diff_against_history(
+            temp_dir.path(),
+            &project_config,
+            &[undeclared("a/__init__.py", 2, "b.y")],
+            None,
+        )
+        .unwrap();
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.fixed.len(), 1);
+        assert_eq!(diff.head.suppressions, Some(1));
+        assert_eq!(diff.head.module_coverage, Some(0.5));
+        // The compared run is not recorded
+        assert_eq!(load_runs(temp_dir.path()).unwrap().len(), 1);
+    }
+}
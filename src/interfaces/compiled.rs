@@ -1,4 +1,4 @@
-use crate::config::{InterfaceConfig, InterfaceDataTypes};
+use crate::config::{InterfaceConfig, InterfaceDataTypes, Stability};
 use regex::Regex;
 
 #[derive(Debug, Clone)]
@@ -6,6 +6,7 @@ pub struct CompiledInterface {
     pub from_modules: Vec<Regex>,
     pub expose: Vec<Regex>,
     pub data_types: InterfaceDataTypes,
+    pub stability: Option<Stability>,
 }
 
 impl CompiledInterface {
@@ -35,6 +36,7 @@ impl<'a> CompiledInterfaces {
             .into_iter()
             .map(|interface| CompiledInterface {
                 data_types: interface.data_types.clone(),
+                stability: interface.stability,
                 from_modules: interface
                     .from_modules
                     .iter()
@@ -82,4 +84,13 @@ impl<'a> CompiledInterfaces {
             .map(|interface| &interface.data_types)
             .unwrap_or(&InterfaceDataTypes::All)
     }
+
+    /// The stability of a member, taken from the least stable interface which exposes it
+    pub fn get_stability(&self, module_path: &str, member_name: &str) -> Option<Stability> {
+        self.get_interfaces(module_path)
+            .iter()
+            .filter(|interface| interface.matches_member(member_name))
+            .filter_map(|interface| interface.stability)
+            .max()
+    }
 }
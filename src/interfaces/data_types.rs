@@ -1,7 +1,7 @@
 use super::compiled::{CompiledInterface, CompiledInterfaces};
 use super::error::InterfaceError;
 use crate::config::{InterfaceDataTypes, ModuleConfig};
-use crate::filesystem::module_to_file_path;
+use crate::filesystem::{module_to_file_path, read_file_content_with_overlay, FileOverlay};
 use crate::python::parsing::parse_python_source;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -25,6 +25,7 @@ impl TypeCheckCache {
         compiled_interfaces: &CompiledInterfaces,
         modules: &[ModuleConfig],
         source_roots: &[PathBuf],
+        overlay: &FileOverlay,
     ) -> Result<Self, InterfaceError> {
         let module_paths: Vec<&str> = modules
             .iter()
@@ -42,6 +43,7 @@ impl TypeCheckCache {
                 source_roots,
                 &module_paths,
                 compiled_interfaces,
+                overlay,
             )?,
         })
     }
@@ -286,18 +288,23 @@ pub fn type_check_all_interface_members(
     source_roots: &[PathBuf],
     module_paths: &[&str],
     interfaces: &CompiledInterfaces,
+    overlay: &FileOverlay,
 ) -> Result<HashMap<String, TypeCheckResult>, InterfaceError> {
     let mut member_status = HashMap::new();
 
     // for each module, parse the source files and use the visitor to extract the typed interface members
     let mut visitor = ModuleInterfaceVisitor::new(interfaces);
     for module_path in module_paths {
-        let resolved_mod = module_to_file_path(source_roots, module_path, false).unwrap();
+        // Mapped modules (e.g. compiled extensions) have no Python source to type check
+        let Some(resolved_mod) = module_to_file_path(source_roots, module_path, false) else {
+            continue;
+        };
         // first get this working for only the module file itself
-        let python_source = std::fs::read_to_string(resolved_mod.file_path).unwrap();
-        let ast = match parse_python_source(&python_source) {
-            Ok(Mod::Module(ast)) => ast,
-            _ => panic!("Expected ast::Mod variant"),
+        let python_source = read_file_content_with_overlay(&resolved_mod.file_path, overlay)?;
+        // An unsaved buffer may not parse while it is being edited, in which case
+        // the types of its members are unknown until it does
+        let Ok(Mod::Module(ast)) = parse_python_source(&python_source) else {
+            continue;
         };
         let interface_members = visitor.get_interface_members(module_path, "", &ast.body);
 
@@ -334,6 +341,7 @@ mod tests {
             expose: vec![".*".to_string()],
             from_modules: vec!["my_module".to_string()],
             data_types: InterfaceDataTypes::Primitive,
+            stability: None,
         }
     }
 
@@ -422,7 +430,9 @@ def custom_func(a: CustomType) -> CustomType:
         let interfaces = CompiledInterfaces::build(&[basic_interface]);
         let modules = vec![ModuleConfig::new("my_module", false)];
 
-        let cache = TypeCheckCache::build(&interfaces, &modules, &source_roots).unwrap();
+        let cache =
+            TypeCheckCache::build(&interfaces, &modules, &source_roots, &FileOverlay::new())
+                .unwrap();
 
         // Test primitive variable
         match cache.get_result("x") {
@@ -456,4 +466,22 @@ def custom_func(a: CustomType) -> CustomType:
             _ => panic!("Expected DidNotMatchInterface for custom_func"),
         }
     }
+
+    #[rstest]
+    fn test_type_check_cache_skips_invalid_overlay(
+        temp_dir: TempDir,
+        basic_interface: InterfaceConfig,
+    ) {
+        let source_roots = setup_test_files(&temp_dir, &[("my_module.py", "x: int = 1")]);
+        let interfaces = CompiledInterfaces::build(&[basic_interface]);
+        let modules = vec![ModuleConfig::new("my_module", false)];
+        let mut overlay = FileOverlay::new();
+        overlay.insert(
+            temp_dir.path().join("my_module.py"),
+            "x: int = 1\ndef func(a: int".to_string(),
+        );
+
+        let cache = TypeCheckCache::build(&interfaces, &modules, &source_roots, &overlay).unwrap();
+        assert!(matches!(cache.get_result("x"), TypeCheckResult::Unknown));
+    }
 }
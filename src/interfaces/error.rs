@@ -1,6 +1,7 @@
 use std::io;
 use thiserror::Error;
 
+use crate::filesystem::FileSystemError;
 use crate::python::error::ParsingError;
 
 #[derive(Error, Debug)]
@@ -9,4 +10,6 @@ pub enum InterfaceError {
     Io(#[from] io::Error),
     #[error("Parsing error: {0}")]
     Parsing(#[from] ParsingError),
+    #[error("Filesystem error: {0}")]
+    Filesystem(#[from] FileSystemError),
 }
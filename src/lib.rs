@@ -9,17 +9,26 @@ pub mod diagnostics;
 pub mod exclusion;
 pub mod external;
 pub mod filesystem;
+pub mod hash;
+pub mod history;
 pub mod interfaces;
 pub mod interrupt;
+pub mod logging;
 pub mod lsp;
 pub mod modularity;
 pub mod modules;
+pub mod otlp;
+pub mod ownership;
 pub mod parsing;
 pub mod pattern;
 pub mod processors;
 pub mod python;
+pub mod stats;
 pub mod tests;
-use commands::{check, report, server, sync, test};
+use commands::{
+    check, diff_config, export, graph, init, interface_usage, invalidate, matrix, mod_edit, query,
+    ratchet, report, server, side_effects, sync, test, unused_modules, validate, warm,
+};
 use diagnostics::serialize_diagnostics_json;
 use modularity::into_usage_errors;
 use pyo3::prelude::*;
@@ -29,6 +38,7 @@ use pyo3::exceptions::{PyKeyboardInterrupt, PyOSError, PySyntaxError, PyValueErr
 
 mod errors {
     pyo3::import_exception!(tach.errors, TachCircularDependencyError);
+    pyo3::import_exception!(tach.errors, TachCircularDomainDependencyError);
     pyo3::import_exception!(tach.errors, TachVisibilityError);
     pyo3::import_exception!(tach.errors, TachSetupError);
     pyo3::import_exception!(tach.errors, TachConfigError);
@@ -66,6 +76,39 @@ impl From<cache::CacheError> for PyErr {
     }
 }
 
+impl From<stats::StatsError> for PyErr {
+    fn from(err: stats::StatsError) -> Self {
+        match err {
+            stats::StatsError::Io(err) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<history::HistoryError> for PyErr {
+    fn from(err: history::HistoryError) -> Self {
+        match err {
+            history::HistoryError::Io(err) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<logging::LoggingError> for PyErr {
+    fn from(err: logging::LoggingError) -> Self {
+        match err {
+            logging::LoggingError::Io(_) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<otlp::OtlpError> for PyErr {
+    fn from(err: otlp::OtlpError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
 impl From<check::CheckError> for PyErr {
     fn from(err: check::CheckError) -> Self {
         match err {
@@ -73,6 +116,9 @@ impl From<check::CheckError> for PyErr {
             check::CheckError::ModuleTree(modules::error::ModuleTreeError::CircularDependency(
                 c,
             )) => errors::TachCircularDependencyError::new_err(c),
+            check::CheckError::ModuleTree(
+                modules::error::ModuleTreeError::CircularDomainDependency(c),
+            ) => errors::TachCircularDomainDependencyError::new_err(c),
             check::CheckError::ModuleTree(
                 modules::error::ModuleTreeError::VisibilityViolation(v),
             ) => errors::TachVisibilityError::new_err(v),
@@ -82,6 +128,23 @@ impl From<check::CheckError> for PyErr {
     }
 }
 
+impl From<modules::error::ModuleTreeError> for PyErr {
+    fn from(err: modules::error::ModuleTreeError) -> Self {
+        match err {
+            modules::error::ModuleTreeError::CircularDependency(c) => {
+                errors::TachCircularDependencyError::new_err(c)
+            }
+            modules::error::ModuleTreeError::CircularDomainDependency(c) => {
+                errors::TachCircularDomainDependencyError::new_err(c)
+            }
+            modules::error::ModuleTreeError::VisibilityViolation(v) => {
+                errors::TachVisibilityError::new_err(v)
+            }
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
 impl From<python::error::ParsingError> for PyErr {
     fn from(err: python::error::ParsingError) -> Self {
         match err {
@@ -101,23 +164,107 @@ impl From<parsing::error::ParsingError> for PyErr {
             parsing::error::ParsingError::Io(err) => PyOSError::new_err(err.to_string()),
             parsing::error::ParsingError::Filesystem(err) => PyOSError::new_err(err.to_string()),
             parsing::error::ParsingError::TomlParse(err) => PyValueError::new_err(err.to_string()),
+            parsing::error::ParsingError::YamlParse(err) => PyValueError::new_err(err.to_string()),
             parsing::error::ParsingError::MissingField(err) => PyValueError::new_err(err),
             parsing::error::ParsingError::ModulePath(err) => PyValueError::new_err(err),
+            parsing::error::ParsingError::PathExclusion(err) => {
+                PyValueError::new_err(err.to_string())
+            }
+            parsing::error::ParsingError::InlineConfig(_)
+            | parsing::error::ParsingError::RegexExcludes => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+impl From<parsing::migrations::MigrationError> for PyErr {
+    fn from(err: parsing::migrations::MigrationError) -> Self {
+        match err {
+            parsing::migrations::MigrationError::Io(err) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
         }
     }
 }
+
 impl From<sync::SyncError> for PyErr {
     fn from(err: sync::SyncError) -> Self {
         match err {
             sync::SyncError::FileWrite(err) => PyOSError::new_err(err.to_string()),
             sync::SyncError::TomlSerialize(err) => PyOSError::new_err(err.to_string()),
             sync::SyncError::CheckError(err) => err.into(),
-            sync::SyncError::RootModuleViolation(err) => PyValueError::new_err(err.to_string()),
             sync::SyncError::EditError(err) => PyValueError::new_err(err.to_string()),
         }
     }
 }
 
+impl From<init::InitError> for PyErr {
+    fn from(err: init::InitError) -> Self {
+        match err {
+            init::InitError::FileWrite(err) => PyOSError::new_err(err.to_string()),
+            init::InitError::Parsing(err) => err.into(),
+            init::InitError::Sync(err) => err.into(),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<ratchet::RatchetError> for PyErr {
+    fn from(err: ratchet::RatchetError) -> Self {
+        match err {
+            ratchet::RatchetError::CheckError(err) => err.into(),
+            ratchet::RatchetError::EditError(err) => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<diff_config::DiffConfigError> for PyErr {
+    fn from(err: diff_config::DiffConfigError) -> Self {
+        match err {
+            diff_config::DiffConfigError::CheckError(err) => err.into(),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<graph::GraphError> for PyErr {
+    fn from(err: graph::GraphError) -> Self {
+        match err {
+            graph::GraphError::ModuleNotFound(_) => PyValueError::new_err(err.to_string()),
+            graph::GraphError::Config(err) => err.into(),
+        }
+    }
+}
+
+impl From<export::ExportError> for PyErr {
+    fn from(err: export::ExportError) -> Self {
+        match err {
+            export::ExportError::Check(err) => err.into(),
+            export::ExportError::Io(err) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<query::QueryError> for PyErr {
+    fn from(err: query::QueryError) -> Self {
+        match err {
+            query::QueryError::CheckError(err) => err.into(),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl From<mod_edit::ModEditError> for PyErr {
+    fn from(err: mod_edit::ModEditError) -> Self {
+        match err {
+            mod_edit::ModEditError::NotADirectory(_) => {
+                errors::TachSetupError::new_err(err.to_string())
+            }
+            mod_edit::ModEditError::Io(err) => PyOSError::new_err(err.to_string()),
+            mod_edit::ModEditError::Filesystem(err) => PyOSError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
 impl From<lsp::error::ServerError> for PyErr {
     fn from(err: lsp::error::ServerError) -> Self {
         match err {
@@ -167,35 +314,49 @@ fn dump_project_config_to_toml(
     parsing::config::dump_project_config_to_toml(config).map_err(sync::SyncError::TomlSerialize)
 }
 
+#[pyfunction]
+#[pyo3(signature = (config))]
+fn dump_project_config_to_yaml(
+    config: &mut config::ProjectConfig,
+) -> parsing::config::Result<String> {
+    Ok(parsing::config::dump_project_config_to_yaml(config)?)
+}
+
 /// Get first-party imports from file_path
 #[pyfunction]
+#[pyo3(signature = (project_root, source_roots, file_path, project_config, overlay = None))]
 fn get_project_imports(
     project_root: PathBuf,
     source_roots: Vec<PathBuf>,
     file_path: PathBuf,
     project_config: config::ProjectConfig,
+    overlay: Option<filesystem::FileOverlay>,
 ) -> processors::import::Result<Vec<dependencies::LocatedImport>> {
-    commands::helpers::import::get_located_project_imports(
+    commands::helpers::import::get_located_project_imports_with_overlay(
         &project_root,
         &source_roots,
         &file_path,
         &project_config,
+        &overlay.unwrap_or_default(),
     )
 }
 
 /// Get third-party imports from file_path
 #[pyfunction]
+#[pyo3(signature = (project_root, source_roots, file_path, project_config, overlay = None))]
 fn get_external_imports(
     project_root: PathBuf,
     source_roots: Vec<PathBuf>,
     file_path: PathBuf,
     project_config: config::ProjectConfig,
+    overlay: Option<filesystem::FileOverlay>,
 ) -> processors::import::Result<Vec<dependencies::LocatedImport>> {
-    commands::helpers::import::get_located_external_imports(
+    commands::helpers::import::get_located_external_imports_with_overlay(
         &project_root,
         &source_roots,
         &file_path,
         &project_config,
+        &overlay.unwrap_or_default(),
     )
 }
 
@@ -233,7 +394,210 @@ fn create_dependency_report(
     )
 }
 
+/// Create a combined report of dependencies and usages of several paths, walking the project once
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, paths, include_dependency_modules, include_usage_modules, skip_dependencies, skip_usages, raw))]
+#[allow(clippy::too_many_arguments)]
+fn create_combined_dependency_report(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    paths: Vec<PathBuf>,
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+    raw: bool,
+) -> report::Result<String> {
+    report::create_combined_dependency_report(
+        &project_root,
+        project_config,
+        &paths,
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+        raw,
+    )
+}
+
+/// The imports found by a dependency report of a given path, as records for JSON or CSV output
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, path, include_dependency_modules, include_usage_modules, skip_dependencies, skip_usages))]
+fn create_dependency_records(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+    include_dependency_modules: Option<Vec<String>>,
+    include_usage_modules: Option<Vec<String>>,
+    skip_dependencies: bool,
+    skip_usages: bool,
+) -> report::Result<Vec<report::DependencyRecord>> {
+    report::create_dependency_records(
+        &project_root,
+        project_config,
+        &path,
+        include_dependency_modules,
+        include_usage_modules,
+        skip_dependencies,
+        skip_usages,
+    )
+}
+
+/// Build the module graph for 'tach show', reduced to a subgraph according to the given options
+#[pyfunction]
+#[pyo3(signature = (project_config, included_paths, violations = vec![], focus = None, focus_depth = 1, depth = None, only_violations = false, exclude_utilities = false))]
+#[allow(clippy::too_many_arguments)]
+fn build_module_graph(
+    project_config: &config::ProjectConfig,
+    included_paths: Vec<PathBuf>,
+    violations: Vec<(String, String)>,
+    focus: Option<String>,
+    focus_depth: usize,
+    depth: Option<usize>,
+    only_violations: bool,
+    exclude_utilities: bool,
+) -> graph::Result<graph::ModuleGraph> {
+    graph::build_module_graph(
+        project_config,
+        included_paths,
+        &violations,
+        &graph::GraphOptions {
+            focus,
+            focus_depth,
+            depth,
+            only_violations,
+            exclude_utilities,
+        },
+    )
+}
+
+/// The modules which a module transitively depends on through imports (or its dependents)
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, module, dependents = false))]
+fn query_dependencies(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    module: String,
+    dependents: bool,
+) -> query::Result<Vec<String>> {
+    query::transitive_dependencies(project_root, project_config, &module, dependents)
+}
+
+/// The paths of imports through which one module depends on another, shortest first
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, source, target, max_paths = 10))]
+fn query_paths(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    source: String,
+    target: String,
+    max_paths: usize,
+) -> query::Result<Vec<query::DependencyPath>> {
+    query::dependency_paths(project_root, project_config, &source, &target, max_paths)
+}
+
+/// The shortest paths of imports through which modules matching one pattern depend on
+/// modules matching another, with every import
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, source, target, all_shortest = false))]
+fn explain_dependencies(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    source: String,
+    target: String,
+    all_shortest: bool,
+) -> query::Result<Vec<query::DependencyExplanation>> {
+    query::explain_dependencies(project_root, project_config, &source, &target, all_shortest)
+}
+
+/// Find top-level code which is likely to have side effects when a module is imported
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config))]
+fn detect_import_side_effects(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+) -> report::Result<Vec<side_effects::ImportSideEffect>> {
+    side_effects::detect_import_side_effects(&project_root, project_config)
+}
+
+/// Count external imports of each exposed member of the module containing 'path'
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, path))]
+fn create_interface_usage_report(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+) -> report::Result<interface_usage::InterfaceUsageReport> {
+    interface_usage::create_interface_usage_report(&project_root, project_config, &path)
+}
+
+/// Write a bundle of JSON artifacts describing the project, for consumption by dashboards
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, directory))]
+fn export_bundle(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    directory: PathBuf,
+) -> export::Result<export::ExportSummary> {
+    export::export_bundle(&project_root, project_config, &directory)
+}
+
+/// Write a new 'tach.toml' with a module for each top-level package in the detected source roots,
+/// optionally syncing their dependencies from the imports in the project
+#[pyfunction]
+#[pyo3(signature = (project_root, force = false, sync = true))]
+fn init_project(project_root: PathBuf, force: bool, sync: bool) -> init::Result<init::InitSummary> {
+    init::init_project(&project_root, force, sync)
+}
+
+/// Find members exposed by interfaces within 'path' which no other module imports
 #[pyfunction]
+#[pyo3(signature = (project_root, project_config, path))]
+fn find_unused_interface_members(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+) -> report::Result<Vec<interface_usage::UnusedInterfaceMembers>> {
+    interface_usage::find_unused_interface_members(&project_root, project_config, &path)
+}
+
+/// Find the imports of the module containing 'path' which strict mode would reject,
+/// and the members an interface must expose to allow them
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, path))]
+fn strictify_module(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+) -> report::Result<interface_usage::StrictifyReport> {
+    interface_usage::strictify_module(&project_root, project_config, &path)
+}
+
+/// Find modules within 'path' which are not imported from anywhere else in the project
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, path))]
+fn find_unused_modules(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+) -> report::Result<Vec<String>> {
+    unused_modules::find_unused_modules(&project_root, project_config, &path)
+}
+
+/// Build the matrix of imports between the modules within 'path'
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, path))]
+fn create_dependency_matrix(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    path: PathBuf,
+) -> report::Result<matrix::DependencyMatrix> {
+    matrix::create_dependency_matrix(&project_root, project_config, &path)
+}
+
+#[pyfunction]
+#[pyo3(signature = (project_root, source_roots, action, py_interpreter_version, file_dependencies, env_dependencies, backend, overlay = None))]
+#[allow(clippy::too_many_arguments)]
 fn create_computation_cache_key(
     project_root: PathBuf,
     source_roots: Vec<PathBuf>,
@@ -242,6 +606,7 @@ fn create_computation_cache_key(
     file_dependencies: Vec<String>,
     env_dependencies: Vec<String>,
     backend: String,
+    overlay: Option<filesystem::FileOverlay>,
 ) -> String {
     cache::create_computation_cache_key(
         &project_root,
@@ -251,43 +616,289 @@ fn create_computation_cache_key(
         file_dependencies,
         env_dependencies,
         backend,
+        &overlay.unwrap_or_default(),
     )
 }
 
 #[pyfunction]
+#[pyo3(signature = (project_root, cache_key, cache_config = None))]
 fn check_computation_cache(
     project_root: PathBuf,
     cache_key: String,
+    cache_config: Option<config::CacheConfig>,
 ) -> cache::Result<Option<cache::ComputationCacheValue>> {
-    cache::check_computation_cache(&project_root, cache_key)
+    cache::check_computation_cache(&project_root, cache_key, &cache_config.unwrap_or_default())
 }
 
 #[pyfunction]
+#[pyo3(signature = (project_root, cache_key, value, cache_config = None))]
 fn update_computation_cache(
     project_root: PathBuf,
     cache_key: String,
     value: cache::ComputationCacheValue,
+    cache_config: Option<config::CacheConfig>,
 ) -> cache::Result<Option<cache::ComputationCacheValue>> {
-    cache::update_computation_cache(&project_root, cache_key, value)
+    cache::update_computation_cache(
+        &project_root,
+        cache_key,
+        value,
+        &cache_config.unwrap_or_default(),
+    )
+}
+
+/// Record a local usage stat for a command run (no-op unless opted in)
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, command, duration_ms, cache_hit = None))]
+fn record_usage_stats(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    command: String,
+    duration_ms: u64,
+    cache_hit: Option<bool>,
+) -> stats::Result<()> {
+    stats::record_usage(
+        &project_root,
+        project_config,
+        command,
+        duration_ms,
+        cache_hit,
+    )
+}
+
+/// The full description of the rule with the given code (e.g. 'TACH001')
+#[pyfunction]
+fn explain_rule(rule_id: &str) -> PyResult<String> {
+    diagnostics::rule_metadata(rule_id)
+        .map(|rule| rule.explain())
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown rule code '{}'.", rule_id)))
+}
+
+/// The code and summary of every rule, in order
+#[pyfunction]
+fn list_rules() -> Vec<(&'static str, &'static str)> {
+    diagnostics::RULES
+        .iter()
+        .map(|rule| (rule.code, rule.summary))
+        .collect()
 }
 
+/// Format a summary of locally recorded usage stats
 #[pyfunction]
-#[pyo3(name = "check")]
+fn format_usage_stats(project_root: PathBuf) -> stats::Result<String> {
+    stats::format_usage_stats(&project_root)
+}
+
+/// Record the diagnostics of a check in the local run history (no-op unless opted in)
+#[pyfunction]
+fn record_check_history(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    diagnostics: Vec<diagnostics::Diagnostic>,
+) -> history::Result<Option<history::HistoryRun>> {
+    history::record_run(&project_root, project_config, &diagnostics)
+}
+
+/// Every recorded run, oldest first
+#[pyfunction]
+fn load_check_history(project_root: PathBuf) -> history::Result<Vec<history::HistoryRun>> {
+    history::load_runs(&project_root)
+}
+
+/// Find a recorded run by 'latest', '~N' or a prefix of its ID
+#[pyfunction]
+fn find_check_history_run(
+    project_root: PathBuf,
+    reference: String,
+) -> history::Result<history::HistoryRun> {
+    let runs = history::load_runs(&project_root)?;
+    history::resolve_run(&runs, &reference).cloned()
+}
+
+/// Compare two recorded runs, defaulting to the latest run and the run before it
+#[pyfunction]
+#[pyo3(signature = (project_root, base = None, head = None, since = None))]
+fn diff_check_history(
+    project_root: PathBuf,
+    base: Option<String>,
+    head: Option<String>,
+    since: Option<String>,
+) -> history::Result<history::HistoryDiff> {
+    history::diff_history(
+        &project_root,
+        base.as_deref(),
+        head.as_deref(),
+        since.as_deref(),
+    )
+}
+
+/// Compare the diagnostics of a check which was not recorded against a recorded run,
+/// defaulting to the latest one
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, diagnostics, base = None))]
+fn diff_check_against_history(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    diagnostics: Vec<diagnostics::Diagnostic>,
+    base: Option<String>,
+) -> history::Result<history::HistoryDiff> {
+    history::diff_against_history(&project_root, project_config, &diagnostics, base.as_deref())
+}
+
+/// Send logs from the extension to stderr, and optionally record how long each step takes
+#[pyfunction]
+#[pyo3(signature = (verbosity = 0, timings = false, profile = false))]
+fn init_tracing(verbosity: u8, timings: bool, profile: bool) -> logging::Result<()> {
+    logging::init(verbosity, timings, profile)
+}
+
+/// The time spent in each step since tracing was initialized, slowest first
+#[pyfunction]
+fn take_timings() -> Vec<logging::SpanTiming> {
+    logging::take_timings()
+}
+
+/// Write every step recorded since tracing was initialized to 'path' as a Chrome trace
+#[pyfunction]
+fn write_profile(path: PathBuf) -> logging::Result<usize> {
+    logging::write_profile(&path)
+}
+
+/// Export the time spent in each analysis phase, and its results, to the project's OpenTelemetry collector
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config))]
+fn start_otlp_export(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+) -> otlp::Result<()> {
+    otlp::start(&project_root, &project_config.otlp)
+}
+
+/// Stop exporting to the OpenTelemetry collector, flushing any spans which have not been sent
+#[pyfunction]
+fn shutdown_otlp_export() {
+    otlp::shutdown()
+}
+
+#[pyfunction]
+#[pyo3(name = "check", signature = (project_root, project_config, dependencies, interfaces, overlay = None, shard = None, progress = None, max_memory = None))]
+#[allow(clippy::too_many_arguments)]
 fn check_internal(
+    py: Python<'_>,
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    dependencies: bool,
+    interfaces: bool,
+    overlay: Option<filesystem::FileOverlay>,
+    shard: Option<String>,
+    progress: Option<PyObject>,
+    max_memory: Option<usize>,
+) -> PyResult<Vec<diagnostics::Diagnostic>> {
+    // Python signal handlers (such as for Ctrl-C) run between progress updates.
+    // An exception raised by them or by the callback cancels the check, and is raised again here.
+    let mut callback_error = None;
+    let result = check::check_internal::check_with_progress(
+        project_root,
+        project_config,
+        dependencies,
+        interfaces,
+        &overlay.unwrap_or_default(),
+        shard.map(|shard| shard.parse()).transpose()?,
+        max_memory.map(check::MemoryBudget::new),
+        &mut |current| {
+            py.check_signals()
+                .and_then(|_| match &progress {
+                    Some(progress) => progress.call1(py, (current,)).map(|_| ()),
+                    None => Ok(()),
+                })
+                .map_err(|err| {
+                    callback_error = Some(err);
+                    check::CheckError::Interrupt
+                })
+        },
+    );
+    match callback_error {
+        Some(err) => Err(err),
+        None => Ok(result?),
+    }
+}
+
+/// Check the project against `project_config` as it is in memory, including any edits enqueued on it
+/// which have not been saved. This lets tooling check hypothetical configs without writing any files.
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, dependencies = true, interfaces = true, overlay = None))]
+fn check_config(
     project_root: PathBuf,
     project_config: &config::ProjectConfig,
     dependencies: bool,
     interfaces: bool,
+    overlay: Option<filesystem::FileOverlay>,
+) -> check::check_internal::Result<Vec<diagnostics::Diagnostic>> {
+    check::check_internal::check_with_overlay(
+        project_root,
+        &project_config.with_edits_applied(),
+        dependencies,
+        interfaces,
+        &overlay.unwrap_or_default(),
+    )
+}
+
+/// Check a single file with the given (possibly unsaved) contents, for editor integrations
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, file_path, contents))]
+fn check_file(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    file_path: PathBuf,
+    contents: String,
+) -> check::check_internal::Result<Vec<diagnostics::Diagnostic>> {
+    check::check_file(&project_root, project_config, &file_path, contents)
+}
+
+/// Merge the JSON output of each shard of 'tach check --shard' into a single set of diagnostics
+#[pyfunction]
+fn merge_check_shards(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    outputs: Vec<String>,
 ) -> check::check_internal::Result<Vec<diagnostics::Diagnostic>> {
-    check::check_internal(project_root, project_config, dependencies, interfaces)
+    check::shard::merge_shard_outputs(&project_root, project_config, &outputs)
+}
+
+/// Populate the shared per-file caches without reporting any diagnostics
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, checks = false))]
+fn warm_caches(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    checks: bool,
+) -> warm::Result<warm::WarmSummary> {
+    warm::warm(&project_root, project_config, checks)
+}
+
+/// Remove the shared per-file cache entries for the given paths or modules
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, targets))]
+fn invalidate_caches(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+    targets: Vec<String>,
+) -> invalidate::Result<invalidate::InvalidateSummary> {
+    invalidate::invalidate(&project_root, project_config, &targets)
 }
 
 #[pyfunction]
+#[pyo3(signature = (project_root, diagnostics, group_by_owner = false))]
 pub fn format_diagnostics(
     project_root: PathBuf,
     diagnostics: Vec<diagnostics::Diagnostic>,
+    group_by_owner: bool,
 ) -> String {
-    check::format::DiagnosticFormatter::new(project_root).format_diagnostics(&diagnostics)
+    let formatter = check::format::DiagnosticFormatter::new(project_root);
+    if group_by_owner {
+        formatter.format_diagnostics_by_owner(&diagnostics)
+    } else {
+        formatter.format_diagnostics(&diagnostics)
+    }
 }
 
 #[pyfunction]
@@ -299,13 +910,75 @@ fn detect_unused_dependencies(
 }
 
 #[pyfunction]
-#[pyo3(signature = (project_root, project_config, add = false))]
+#[pyo3(signature = (project_root, project_config, add = false, dry_run = false))]
 pub fn sync_project(
     project_root: PathBuf,
     project_config: config::ProjectConfig,
     add: bool,
-) -> Result<(), sync::SyncError> {
-    sync::sync_project(project_root, project_config, add)
+    dry_run: bool,
+) -> Result<sync::SyncPlan, sync::SyncError> {
+    sync::sync_project(project_root, project_config, add, dry_run)
+}
+
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, dry_run = false))]
+fn ratchet_budgets(
+    project_root: PathBuf,
+    project_config: config::ProjectConfig,
+    dry_run: bool,
+) -> Result<Vec<ratchet::BudgetUpdate>, ratchet::RatchetError> {
+    ratchet::ratchet_budgets(project_root, project_config, dry_run)
+}
+
+/// Lower the violation budgets to the violations found by a full check which already ran
+#[pyfunction]
+#[pyo3(signature = (project_config, diagnostics, dry_run = false))]
+fn update_violation_budgets(
+    mut project_config: config::ProjectConfig,
+    diagnostics: Vec<diagnostics::Diagnostic>,
+    dry_run: bool,
+) -> Result<Vec<ratchet::BudgetUpdate>, ratchet::RatchetError> {
+    ratchet::apply_budget_updates(&mut project_config, &diagnostics, dry_run)
+}
+
+/// Compare two project configs, and the violations a full check reports under each
+#[pyfunction]
+fn diff_project_configs(
+    project_root: PathBuf,
+    old_config: config::ProjectConfig,
+    new_config: config::ProjectConfig,
+) -> Result<diff_config::ConfigDiff, diff_config::DiffConfigError> {
+    diff_config::diff_configs(project_root, &old_config, &new_config)
+}
+
+/// Parse a config file in place of the project config at `config_path`
+#[pyfunction]
+fn parse_project_config_as(
+    path: PathBuf,
+    config_path: PathBuf,
+) -> Result<config::ProjectConfig, diff_config::DiffConfigError> {
+    diff_config::parse_config_file_as(&path, &config_path)
+}
+
+/// Parse the project config as it was at a git revision
+#[pyfunction]
+fn parse_project_config_at_revision(
+    config_path: PathBuf,
+    revision: String,
+) -> Result<config::ProjectConfig, diff_config::DiffConfigError> {
+    diff_config::parse_config_at_revision(&config_path, &revision)
+}
+
+/// Interactively edit modules and source roots, saving changes to the project config
+#[pyfunction]
+#[pyo3(signature = (project_root, project_config, exclude_paths, depth = Some(1)))]
+fn mod_edit_interactive(
+    project_root: PathBuf,
+    project_config: config::ProjectConfig,
+    exclude_paths: Vec<String>,
+    depth: Option<usize>,
+) -> mod_edit::Result<(bool, Vec<String>)> {
+    mod_edit::mod_edit_interactive(project_root, project_config, exclude_paths, depth)
 }
 
 #[pyfunction]
@@ -316,6 +989,47 @@ fn run_server(
     server::run_server(project_root, project_config)
 }
 
+/// Build the module tree for a project as a navigable object graph
+#[pyfunction]
+fn get_module_tree(
+    project_root: PathBuf,
+    project_config: &config::ProjectConfig,
+) -> Result<modules::ModuleTreeNode, modules::error::ModuleTreeError> {
+    modules::export::export_project_module_tree(&project_root, project_config)
+}
+
+/// Upgrade a project config file to the current schema version
+#[pyfunction]
+#[pyo3(signature = (config_path, dry_run = false, backup = true))]
+fn migrate_project_config(
+    config_path: PathBuf,
+    dry_run: bool,
+    backup: bool,
+) -> parsing::migrations::Result<parsing::migrations::MigrationResult> {
+    parsing::migrations::migrate_project_config(&config_path, dry_run, backup)
+}
+
+/// The most recent backup of the project's config files, if any
+#[pyfunction]
+#[pyo3(signature = (project_root))]
+fn latest_config_backup(project_root: PathBuf) -> Option<PathBuf> {
+    config::write::latest_backup(&project_root)
+}
+
+/// Validate a project config file, returning every problem found
+#[pyfunction]
+fn validate_project_config(
+    project_root: PathBuf,
+    config_path: PathBuf,
+) -> Result<Vec<validate::ConfigDiagnostic>, config::ConfigError> {
+    Ok(
+        validate::validate_project_config(&project_root, &config_path)?
+            .into_iter()
+            .map(validate::ConfigDiagnostic::from)
+            .collect(),
+    )
+}
+
 #[pyfunction]
 fn serialize_modules_json(modules: Vec<config::ModuleConfig>) -> String {
     config::serialize_modules_json(&modules)
@@ -325,29 +1039,121 @@ fn serialize_modules_json(modules: Vec<config::ModuleConfig>) -> String {
 fn extension(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     interrupt::setup_interrupt_handler();
     m.add_class::<config::ProjectConfig>()?;
+    m.add_class::<config::ConfigEdit>()?;
+    m.add_class::<config::edit::ConfigEditPreview>()?;
+    m.add_class::<check::check_internal::CheckProgress>()?;
+    m.add_class::<logging::SpanTiming>()?;
+    m.add_class::<history::HistoryFinding>()?;
+    m.add_class::<history::HistoryRun>()?;
+    m.add_class::<history::HistoryDiff>()?;
     m.add_class::<config::ModuleConfig>()?;
     m.add_class::<config::InterfaceConfig>()?;
     m.add_class::<config::RulesConfig>()?;
+    m.add_class::<config::ModuleRulesConfig>()?;
+    m.add_class::<config::EnvironmentRulesConfig>()?;
     m.add_class::<config::DependencyConfig>()?;
+    m.add_class::<config::IgnoreConfig>()?;
+    m.add_class::<config::HealthConfig>()?;
+    m.add_class::<config::OtlpConfig>()?;
+    m.add_class::<exclusion::PathExclusions>()?;
     m.add_class::<diagnostics::Diagnostic>()?;
     m.add_class::<test::TachPytestPluginHandler>()?;
     m.add_class::<modularity::UsageError>()?;
+    m.add_class::<modules::ModuleTreeNode>()?;
+    m.add_class::<validate::ConfigDiagnostic>()?;
+    m.add_class::<parsing::migrations::MigrationResult>()?;
+    m.add_class::<warm::WarmSummary>()?;
+    m.add_class::<invalidate::InvalidateSummary>()?;
+    m.add_class::<side_effects::ImportSideEffect>()?;
+    m.add_class::<report::DependencyRecord>()?;
+    m.add_class::<matrix::DependencyMatrix>()?;
+    m.add_class::<interface_usage::InterfaceMemberUsage>()?;
+    m.add_class::<interface_usage::InterfaceUsageReport>()?;
+    m.add_class::<interface_usage::UnusedInterfaceMembers>()?;
+    m.add_class::<interface_usage::BlockingImport>()?;
+    m.add_class::<interface_usage::StrictifyReport>()?;
+    m.add_class::<ratchet::BudgetUpdate>()?;
+    m.add_class::<diff_config::DependencyChange>()?;
+    m.add_class::<diff_config::RuleChange>()?;
+    m.add_class::<diff_config::ConfigDiff>()?;
+    m.add_class::<graph::ModuleGraph>()?;
+    m.add_class::<graph::GraphEdge>()?;
+    m.add_class::<sync::SyncPlan>()?;
+    m.add_class::<sync::SyncChange>()?;
+    m.add_class::<sync::ImportLocation>()?;
+    m.add_class::<export::ExportSummary>()?;
+    m.add_class::<init::InitSummary>()?;
+    m.add_class::<query::PathStep>()?;
+    m.add_class::<query::DependencyPath>()?;
+    m.add_class::<query::DependencyExplanation>()?;
     m.add_function(wrap_pyfunction_bound!(parse_project_config, m)?)?;
     m.add_function(wrap_pyfunction_bound!(get_project_imports, m)?)?;
     m.add_function(wrap_pyfunction_bound!(get_external_imports, m)?)?;
     m.add_function(wrap_pyfunction_bound!(check_external_dependencies, m)?)?;
     m.add_function(wrap_pyfunction_bound!(create_dependency_report, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(
+        create_combined_dependency_report,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction_bound!(create_dependency_records, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(detect_import_side_effects, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(build_module_graph, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(query_dependencies, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(query_paths, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(explain_dependencies, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(create_interface_usage_report, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(find_unused_modules, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(create_dependency_matrix, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(find_unused_interface_members, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(strictify_module, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(export_bundle, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(init_project, m)?)?;
     m.add_function(wrap_pyfunction_bound!(create_computation_cache_key, m)?)?;
     m.add_function(wrap_pyfunction_bound!(check_computation_cache, m)?)?;
     m.add_function(wrap_pyfunction_bound!(update_computation_cache, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(record_usage_stats, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(format_usage_stats, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(explain_rule, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(list_rules, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(record_check_history, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(load_check_history, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(find_check_history_run, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(diff_check_history, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(diff_check_against_history, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(init_tracing, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(take_timings, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(write_profile, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(start_otlp_export, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(shutdown_otlp_export, m)?)?;
     m.add_function(wrap_pyfunction_bound!(dump_project_config_to_toml, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(dump_project_config_to_yaml, m)?)?;
     m.add_function(wrap_pyfunction_bound!(check_internal, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(check_config, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(check_file, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(merge_check_shards, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(warm_caches, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(invalidate_caches, m)?)?;
     m.add_function(wrap_pyfunction_bound!(format_diagnostics, m)?)?;
     m.add_function(wrap_pyfunction_bound!(detect_unused_dependencies, m)?)?;
     m.add_function(wrap_pyfunction_bound!(sync_project, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(ratchet_budgets, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(diff_project_configs, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(parse_project_config_as, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(parse_project_config_at_revision, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(update_violation_budgets, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(mod_edit_interactive, m)?)?;
     m.add_function(wrap_pyfunction_bound!(run_server, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(get_module_tree, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(validate_project_config, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(migrate_project_config, m)?)?;
+    m.add_function(wrap_pyfunction_bound!(latest_config_backup, m)?)?;
     m.add_function(wrap_pyfunction_bound!(serialize_modules_json, m)?)?;
     m.add_function(wrap_pyfunction_bound!(serialize_diagnostics_json, m)?)?;
     m.add_function(wrap_pyfunction_bound!(into_usage_errors, m)?)?;
+    m.add("OUTPUT_SCHEMA_VERSION", diagnostics::OUTPUT_SCHEMA_VERSION)?;
+    m.add(
+        "MIN_OUTPUT_SCHEMA_VERSION",
+        diagnostics::MIN_OUTPUT_SCHEMA_VERSION,
+    )?;
     Ok(())
 }
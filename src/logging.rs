@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{
+    field::{Field, Visit},
+    span, Metadata, Subscriber,
+};
+use tracing_subscriber::{
+    filter::{dynamic_filter_fn, EnvFilter},
+    fmt::format::FmtSpan,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::config::write::write_atomic;
+
+/// Log filter directives (e.g. 'tach=debug,tach::processors=trace') which take precedence over '--verbose'
+pub const LOG_ENV_VAR: &str = "TACH_LOG";
+
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error("Invalid log filter in TACH_LOG: {0}")]
+    InvalidFilter(String),
+    #[error("Failed to write profile: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize profile: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, LoggingError>;
+
+static SUBSCRIBER_INSTALLED: OnceCell<()> = OnceCell::new();
+static TIMINGS_ENABLED: AtomicBool = AtomicBool::new(false);
+static PROFILE_ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Lazy<Mutex<HashMap<&'static str, SpanTotals>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static PROFILE_EVENTS: Lazy<Mutex<Vec<ProfileEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+// Timestamps in a profile are relative to this
+static PROFILE_EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // Small, stable ids for each thread, since 'std::thread::ThreadId' cannot be converted to a number
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SpanTotals {
+    calls: usize,
+    total: Duration,
+}
+
+/// The total time spent in every span with the same name.
+/// Spans which run in parallel (e.g. checking each file) add up to more than the wall-clock time.
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct SpanTiming {
+    pub name: String,
+    pub calls: usize,
+    pub total_seconds: f64,
+}
+
+/// A complete event ('ph': 'X') in the Chrome trace event format, which is read by chrome://tracing and Perfetto
+#[derive(Debug, Serialize)]
+struct ProfileEvent {
+    name: &'static str,
+    cat: &'static str,
+    ph: &'static str,
+    // Microseconds
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    args: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile<'a> {
+    trace_events: &'a [ProfileEvent],
+    display_time_unit: &'static str,
+}
+
+struct SpanStart {
+    start: Instant,
+    thread_id: u64,
+    // Only recorded while profiling
+    fields: BTreeMap<&'static str, String>,
+}
+
+struct FieldRecorder<'a>(&'a mut BTreeMap<&'static str, String>);
+
+impl Visit for FieldRecorder<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+}
+
+/// Records the lifetime of each span into [`TIMINGS`] and [`PROFILE_EVENTS`], when they are enabled
+struct SpanRecorder;
+
+impl<S> Layer<S> for SpanRecorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = BTreeMap::new();
+        if PROFILE_ENABLED.load(Ordering::Relaxed) {
+            attrs.record(&mut FieldRecorder(&mut fields));
+        }
+        span.extensions_mut().insert(SpanStart {
+            start: Instant::now(),
+            thread_id: THREAD_ID.with(|id| *id),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if !PROFILE_ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            if let Some(span_start) = span.extensions_mut().get_mut::<SpanStart>() {
+                values.record(&mut FieldRecorder(&mut span_start.fields));
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(span_start) = span.extensions_mut().remove::<SpanStart>() else {
+            return;
+        };
+        let elapsed = span_start.start.elapsed();
+
+        if TIMINGS_ENABLED.load(Ordering::Relaxed) {
+            let mut timings = TIMINGS.lock();
+            let totals = timings.entry(span.name()).or_default();
+            totals.calls += 1;
+            totals.total += elapsed;
+        }
+        if PROFILE_ENABLED.load(Ordering::Relaxed) {
+            let start = span_start.start.saturating_duration_since(*PROFILE_EPOCH);
+            PROFILE_EVENTS.lock().push(ProfileEvent {
+                name: span.name(),
+                cat: "tach",
+                ph: "X",
+                ts: start.as_secs_f64() * 1_000_000.0,
+                dur: elapsed.as_secs_f64() * 1_000_000.0,
+                pid: std::process::id(),
+                tid: span_start.thread_id,
+                args: span_start.fields,
+            });
+        }
+    }
+}
+
+// Checked for every span rather than cached per callsite, since '--timings' and '--profile' can change between calls
+fn is_recorded(metadata: &Metadata<'_>) -> bool {
+    (TIMINGS_ENABLED.load(Ordering::Relaxed) || PROFILE_ENABLED.load(Ordering::Relaxed))
+        && metadata.is_span()
+        && metadata.target().starts_with("tach")
+}
+
+fn verbosity_directives(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "warn",
+        1 => "warn,tach=debug",
+        _ => "warn,tach=trace",
+    }
+}
+
+/// Send logs to stderr, filtered by [`LOG_ENV_VAR`] when it is set and by 'verbosity' (the number of '-v' flags) otherwise.
+/// When 'timings' is set, the time spent in each span is recorded (see [`take_timings`]),
+/// and when 'profile' is set, every span is recorded as a profile event (see [`write_profile`]).
+/// Only the first call chooses the log filter, since the subscriber is global to the process.
+pub fn init(verbosity: u8, timings: bool, profile: bool) -> Result<()> {
+    TIMINGS_ENABLED.store(timings, Ordering::Relaxed);
+    if profile {
+        Lazy::force(&PROFILE_EPOCH);
+    }
+    PROFILE_ENABLED.store(profile, Ordering::Relaxed);
+    if SUBSCRIBER_INSTALLED.get().is_some() {
+        return Ok(());
+    }
+
+    let filter = match std::env::var(LOG_ENV_VAR) {
+        Ok(directives) if !directives.is_empty() => EnvFilter::try_new(&directives)
+            .map_err(|err| LoggingError::InvalidFilter(err.to_string()))?,
+        _ => EnvFilter::new(verbosity_directives(verbosity)),
+    };
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_filter(filter);
+    let recorder_layer =
+        SpanRecorder.with_filter(dynamic_filter_fn(|metadata, _| is_recorded(metadata)));
+
+    SUBSCRIBER_INSTALLED.get_or_init(|| {
+        // Fails only if another subscriber has already been installed (e.g. by an embedding application)
+        let _ = tracing_subscriber::registry()
+            .with(crate::otlp::layer())
+            .with(fmt_layer)
+            .with(recorder_layer)
+            .try_init();
+    });
+    Ok(())
+}
+
+/// The span timings recorded so far, slowest first. Recorded timings are cleared.
+pub fn take_timings() -> Vec<SpanTiming> {
+    let mut timings: Vec<SpanTiming> = TIMINGS
+        .lock()
+        .drain()
+        .map(|(name, totals)| SpanTiming {
+            name: name.to_string(),
+            calls: totals.calls,
+            total_seconds: totals.total.as_secs_f64(),
+        })
+        .collect();
+    timings.sort_by(|a, b| b.total_seconds.total_cmp(&a.total_seconds));
+    timings
+}
+
+/// Write the profile events recorded so far to 'path' in the Chrome trace event format,
+/// returning the number of events written. Recorded events are cleared.
+pub fn write_profile(path: &Path) -> Result<usize> {
+    let mut events = std::mem::take(&mut *PROFILE_EVENTS.lock());
+    events.sort_by(|a, b| a.ts.total_cmp(&b.ts));
+    let profile = Profile {
+        trace_events: &events,
+        display_time_unit: "ms",
+    };
+    write_atomic(path, &serde_json::to_string(&profile)?)?;
+    Ok(events.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    fn with_recorder(f: impl FnOnce()) {
+        let subscriber = tracing_subscriber::registry()
+            .with(SpanRecorder.with_filter(dynamic_filter_fn(|metadata, _| is_recorded(metadata))));
+        tracing::subscriber::with_default(subscriber, f);
+    }
+
+    #[test]
+    #[serial]
+    fn test_span_timings() {
+        TIMINGS_ENABLED.store(true, Ordering::Relaxed);
+        with_recorder(|| {
+            for _ in 0..3 {
+                let _span = tracing::debug_span!("test_span_timings").entered();
+            }
+        });
+        TIMINGS_ENABLED.store(false, Ordering::Relaxed);
+
+        let timing = take_timings()
+            .into_iter()
+            .find(|timing| timing.name == "test_span_timings")
+            .unwrap();
+        assert_eq!(timing.calls, 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_write_profile() {
+        PROFILE_ENABLED.store(true, Ordering::Relaxed);
+        with_recorder(|| {
+            let _span = tracing::trace_span!("test_write_profile", path = "a/b.py").entered();
+        });
+        PROFILE_ENABLED.store(false, Ordering::Relaxed);
+
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("profile.json");
+        assert!(write_profile(&path).unwrap() >= 1);
+
+        let profile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let event = profile["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|event| event["name"] == "test_write_profile")
+            .unwrap();
+        assert_eq!(event["ph"], "X");
+        assert_eq!(event["args"]["path"], "a/b.py");
+    }
+}
@@ -1,14 +1,18 @@
+use itertools::Itertools;
 use lsp_types::notification::Notification;
 use lsp_types::request::Request;
 use lsp_types::{InitializeParams, Uri};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::{PathBuf, MAIN_SEPARATOR_STR};
 use std::thread::JoinHandle;
 
-use lsp_server::{Connection, Message, Notification as NotificationMessage, RequestId};
+use lsp_server::{Connection, Message, Notification as NotificationMessage, RequestId, Response};
 
-use crate::commands::check::{check_external, check_internal};
+use crate::commands::check::{check_external, check_internal_with_overlay};
 use crate::config;
-use crate::diagnostics::{Diagnostic, Severity};
+use crate::diagnostics::{rule_metadata, Diagnostic, Severity};
+use crate::filesystem::FileOverlay;
 use crate::interrupt::{check_interrupt, get_interrupt_channel};
 
 use super::error::ServerError;
@@ -18,6 +22,10 @@ use crossbeam_channel::select;
 pub struct LSPServer {
     project_root: PathBuf,
     project_config: config::ProjectConfig,
+    // Contents of open documents, which may not have been saved yet
+    open_documents: RefCell<FileOverlay>,
+    // The diagnostics last published for each document, which hovers describe
+    published_diagnostics: RefCell<HashMap<PathBuf, Vec<lsp_types::Diagnostic>>>,
 }
 
 pub struct ServerHandle {
@@ -66,18 +74,36 @@ impl From<Diagnostic> for Option<lsp_types::Diagnostic> {
     fn from(diag: Diagnostic) -> Self {
         match diag {
             Diagnostic::Global { .. } => None,
-            Diagnostic::Located { line_number, .. } => Some(lsp_types::Diagnostic {
-                range: lsp_types::Range {
-                    start: lsp_types::Position {
-                        line: (line_number - 1) as u32,
-                        character: 0,
+            Diagnostic::Located {
+                line_number, span, ..
+            } => Some(lsp_types::Diagnostic {
+                // Without a span, the whole line is highlighted
+                range: match span {
+                    Some(span) => lsp_types::Range {
+                        start: lsp_types::Position {
+                            line: (line_number - 1) as u32,
+                            character: (span.column - 1) as u32,
+                        },
+                        end: lsp_types::Position {
+                            line: (span.end_line_number - 1) as u32,
+                            character: (span.end_column - 1) as u32,
+                        },
                     },
-                    end: lsp_types::Position {
-                        line: (line_number - 1) as u32,
-                        character: 99999,
+                    None => lsp_types::Range {
+                        start: lsp_types::Position {
+                            line: (line_number - 1) as u32,
+                            character: 0,
+                        },
+                        end: lsp_types::Position {
+                            line: (line_number - 1) as u32,
+                            character: 99999,
+                        },
                     },
                 },
                 severity: Some(diag.severity().into()),
+                code: diag
+                    .rule_id()
+                    .map(|rule_id| lsp_types::NumberOrString::String(rule_id.to_string())),
                 source: Some("tach".to_string()),
                 message: diag.details().to_string(),
                 ..Default::default()
@@ -91,6 +117,8 @@ impl LSPServer {
         Self {
             project_root,
             project_config,
+            open_documents: RefCell::new(FileOverlay::new()),
+            published_diagnostics: RefCell::new(HashMap::new()),
         }
     }
 
@@ -145,16 +173,45 @@ impl LSPServer {
             text_document_sync: Some(lsp_types::TextDocumentSyncCapability::Options(
                 lsp_types::TextDocumentSyncOptions {
                     open_close: Some(true),
-                    change: Some(lsp_types::TextDocumentSyncKind::INCREMENTAL),
+                    change: Some(lsp_types::TextDocumentSyncKind::FULL),
                     save: Some(lsp_types::TextDocumentSyncSaveOptions::Supported(true)),
                     will_save: Some(false),
                     will_save_wait_until: Some(false),
                 },
             )),
+            hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
             ..Default::default()
         }
     }
 
+    /// Describe the rules which produced the diagnostics at the given position
+    fn hover(&self, params: &lsp_types::TextDocumentPositionParams) -> Option<lsp_types::Hover> {
+        let published_diagnostics = self.published_diagnostics.borrow();
+        let diagnostics = published_diagnostics.get(&uri_to_path(&params.text_document.uri))?;
+        let descriptions: Vec<String> = diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                diagnostic.range.start <= params.position && params.position <= diagnostic.range.end
+            })
+            .filter_map(|diagnostic| match &diagnostic.code {
+                Some(lsp_types::NumberOrString::String(rule_id)) => rule_metadata(rule_id),
+                _ => None,
+            })
+            .unique()
+            .map(|rule| rule.markdown())
+            .collect();
+        if descriptions.is_empty() {
+            return None;
+        }
+        Some(lsp_types::Hover {
+            contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value: descriptions.join("\n\n---\n\n"),
+            }),
+            range: None,
+        })
+    }
+
     fn filter_diagnostics_results<'a>(
         &'a self,
         results: Vec<Diagnostic>,
@@ -178,8 +235,13 @@ impl LSPServer {
         eprintln!("Linting for diagnostics: {uri_pathbuf:?}");
         eprintln!("Project root: {}", self.project_root.display());
 
-        let check_result =
-            check_internal(self.project_root.clone(), &self.project_config, true, true)?;
+        let check_result = check_internal_with_overlay(
+            self.project_root.clone(),
+            &self.project_config,
+            true,
+            true,
+            &self.open_documents.borrow(),
+        )?;
         let check_external_result = check_external(&self.project_root, &self.project_config)?;
 
         let check_diagnostics = self.filter_diagnostics_results(check_result, &uri_pathbuf);
@@ -202,6 +264,9 @@ impl LSPServer {
         connection: &Connection,
         params: &lsp_types::PublishDiagnosticsParams,
     ) -> Result<(), ServerError> {
+        self.published_diagnostics
+            .borrow_mut()
+            .insert(uri_to_path(&params.uri), params.diagnostics.clone());
         connection
             .sender
             .send(Message::Notification(NotificationMessage {
@@ -239,6 +304,11 @@ impl LSPServer {
                                             let diagnostics = self.lint_for_diagnostics(data.text_document.uri.clone())?;
                                             self.publish_diagnostics(&connection, &diagnostics)?;
                                         }
+                                        lsp_types::request::HoverRequest::METHOD => {
+                                            let (id, data): (RequestId, lsp_types::HoverParams) = req.extract(lsp_types::request::HoverRequest::METHOD).unwrap();
+                                            let hover = self.hover(&data.text_document_position_params);
+                                            connection.sender.send(Message::Response(Response::new_ok(id, hover)))?;
+                                        }
                                         _ => {
                                             eprintln!("[Ignored] Received request: {:?}", req.method);
                                         }
@@ -253,6 +323,17 @@ impl LSPServer {
                                         lsp_types::notification::DidOpenTextDocument::METHOD => {
                                             eprintln!("Received DidOpen notification");
                                             let data: lsp_types::DidOpenTextDocumentParams = notification.extract(lsp_types::notification::DidOpenTextDocument::METHOD).unwrap();
+                                            self.open_documents.borrow_mut().insert(uri_to_path(&data.text_document.uri), data.text_document.text);
+                                            let diagnostics = self.lint_for_diagnostics(data.text_document.uri.clone())?;
+                                            self.publish_diagnostics(&connection, &diagnostics)?;
+                                        }
+                                        lsp_types::notification::DidChangeTextDocument::METHOD => {
+                                            eprintln!("Received DidChange notification");
+                                            let mut data: lsp_types::DidChangeTextDocumentParams = notification.extract(lsp_types::notification::DidChangeTextDocument::METHOD).unwrap();
+                                            // With full document sync, the last change holds the entire document
+                                            if let Some(change) = data.content_changes.pop() {
+                                                self.open_documents.borrow_mut().insert(uri_to_path(&data.text_document.uri), change.text);
+                                            }
                                             let diagnostics = self.lint_for_diagnostics(data.text_document.uri.clone())?;
                                             self.publish_diagnostics(&connection, &diagnostics)?;
                                         }
@@ -265,6 +346,7 @@ impl LSPServer {
                                         lsp_types::notification::DidCloseTextDocument::METHOD => {
                                             eprintln!("Received DidClose notification");
                                             let data: lsp_types::DidCloseTextDocumentParams = notification.extract(lsp_types::notification::DidCloseTextDocument::METHOD).unwrap();
+                                            self.open_documents.borrow_mut().remove(&uri_to_path(&data.text_document.uri));
                                             let diagnostics = lsp_types::PublishDiagnosticsParams {
                                                 uri: data.text_document.uri.clone(),
                                                 diagnostics: vec![],
@@ -19,6 +19,8 @@ pub enum ModuleTreeError {
     VisibilityViolation(Vec<VisibilityErrorInfo>),
     #[error("Circular dependency detected: {0:?}")]
     CircularDependency(Vec<String>),
+    #[error("Circular dependency detected between domains: {0:?}")]
+    CircularDomainDependency(Vec<Vec<String>>),
     #[error("Root module violation: {0:?}")]
     RootModuleViolation(String),
     #[error("Parsing Error while building module tree.\n{0}")]
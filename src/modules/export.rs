@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use pyo3::prelude::*;
+use regex::Regex;
+
+use super::error::ModuleTreeError;
+use super::{build_module_tree, ModuleNode, ModuleTree};
+use crate::config::{InterfaceConfig, ModuleConfig, ProjectConfig};
+
+/// A snapshot of a node in the module tree, for consumers outside of Rust.
+///
+/// Nodes which do not represent a module (e.g. the 'a' in a module 'a.b')
+/// have 'is_module' False and no config, but are kept so that the tree is navigable.
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct ModuleTreeNode {
+    pub name: String,
+    pub full_path: String,
+    pub is_module: bool,
+    pub config: Option<ModuleConfig>,
+    // Patterns from all interfaces which apply to this module
+    pub interface_members: Vec<String>,
+    // Declared dependencies which refer to a module in the tree
+    pub dependencies: Vec<String>,
+    pub children: Vec<ModuleTreeNode>,
+}
+
+#[pymethods]
+impl ModuleTreeNode {
+    /// Find a descendant module by its full path
+    pub fn find(&self, full_path: &str) -> Option<ModuleTreeNode> {
+        if self.is_module && self.full_path == full_path {
+            return Some(self.clone());
+        }
+        self.children.iter().find_map(|child| child.find(full_path))
+    }
+
+    /// All modules in this subtree, in depth-first order
+    pub fn modules(&self) -> Vec<ModuleTreeNode> {
+        let mut modules = vec![];
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.is_module {
+                modules.push(node.clone());
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        modules
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ModuleTreeNode(full_path={:?}, is_module={}, children={})",
+            self.full_path,
+            self.is_module,
+            self.children.len()
+        )
+    }
+}
+
+fn matching_interface_members(interfaces: &[InterfaceConfig], module_path: &str) -> Vec<String> {
+    interfaces
+        .iter()
+        .filter(|interface| {
+            interface.from_modules.iter().any(|pattern| {
+                Regex::new(&format!("^{}$", pattern)).is_ok_and(|regex| regex.is_match(module_path))
+            })
+        })
+        .flat_map(|interface| interface.expose.iter().cloned())
+        .collect()
+}
+
+fn export_node(
+    tree: &ModuleTree,
+    interfaces: &[InterfaceConfig],
+    name: &str,
+    node: &ModuleNode,
+) -> ModuleTreeNode {
+    let mut children: Vec<(&String, _)> = node.children.iter().collect();
+    children.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let (interface_members, dependencies) = match &node.config {
+        Some(config) if node.is_end_of_path => (
+            matching_interface_members(interfaces, &node.full_path),
+            config
+                .dependencies_iter()
+                .filter(|dependency| tree.get(&dependency.path).is_some())
+                .map(|dependency| dependency.path.clone())
+                .collect(),
+        ),
+        _ => (vec![], vec![]),
+    };
+
+    ModuleTreeNode {
+        name: name.to_string(),
+        full_path: node.full_path.clone(),
+        is_module: node.is_end_of_path,
+        config: node.config.clone(),
+        interface_members,
+        dependencies,
+        children: children
+            .into_iter()
+            .map(|(name, child)| export_node(tree, interfaces, name, child))
+            .collect(),
+    }
+}
+
+impl ModuleTree {
+    pub fn export(&self, interfaces: &[InterfaceConfig]) -> ModuleTreeNode {
+        export_node(self, interfaces, ".", &self.root)
+    }
+}
+
+/// Build the module tree for a project and export it, skipping modules which cannot be found on disk
+pub fn export_project_module_tree(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+) -> Result<ModuleTreeNode, ModuleTreeError> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let (valid_modules, _) = project_config.validate_modules(&source_roots);
+    let module_tree = build_module_tree(
+        &source_roots,
+        &valid_modules,
+        project_config.forbids_circular_dependencies(),
+        project_config.circular_domain_groups().as_ref(),
+        project_config.root_module.clone(),
+    )?;
+    let interfaces: Vec<InterfaceConfig> = project_config.all_interfaces().cloned().collect();
+    Ok(module_tree.export(&interfaces))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::tests::module::fixtures::module_tree;
+
+    #[rstest]
+    fn test_export_module_tree(module_tree: ModuleTree) {
+        let exported = module_tree.export(&[]);
+        assert_eq!(exported.full_path, ".");
+
+        let exported_paths: Vec<String> = exported
+            .modules()
+            .into_iter()
+            .map(|node| node.full_path)
+            .collect();
+        let mut tree_paths: Vec<String> = module_tree
+            .iter()
+            .map(|node| node.full_path.clone())
+            .collect();
+        tree_paths.sort();
+        let mut sorted_exported_paths = exported_paths.clone();
+        sorted_exported_paths.sort();
+        assert_eq!(sorted_exported_paths, tree_paths);
+
+        for path in exported_paths {
+            let node = exported.find(&path).unwrap();
+            assert!(node
+                .dependencies
+                .iter()
+                .all(|dependency| module_tree.get(dependency).is_some()));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 pub mod error;
+pub mod export;
 pub mod parsing;
 pub mod tree;
 
+pub use export::ModuleTreeNode;
 pub use parsing::build_module_tree;
 pub use tree::{ModuleNode, ModuleTree};
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 
 use crate::config::root_module::{RootModuleTreatment, ROOT_MODULE_SENTINEL_TAG};
@@ -109,6 +109,77 @@ pub fn find_modules_with_cycles(modules: &[ModuleConfig]) -> Vec<&String> {
     modules_with_cycles
 }
 
+fn domain_group<'a>(domain_groups: &'a HashMap<String, String>, path: &'a String) -> &'a String {
+    domain_groups.get(path).unwrap_or(path)
+}
+
+/// The shortest path from `start` back to itself through the nodes of a strongly connected component
+fn shortest_cycle<'a>(
+    graph: &DiGraphMap<&'a String, ()>,
+    scc: &HashSet<&'a String>,
+    start: &'a String,
+) -> Vec<String> {
+    let mut parents: HashMap<&String, &String> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for next in graph.neighbors(node) {
+            if next == start {
+                let mut path = vec![node.clone()];
+                let mut current = node;
+                while let Some(parent) = parents.get(current) {
+                    path.push((*parent).clone());
+                    current = *parent;
+                }
+                path.reverse();
+                path.push(start.clone());
+                return path;
+            }
+            if scc.contains(next) && !parents.contains_key(next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    vec![]
+}
+
+/// Find cycles in the dependency graph after condensing each domain into a single node.
+/// Modules which are not part of a domain remain individual nodes,
+/// and dependencies within a domain are ignored.
+/// Each cycle is returned as a path which starts and ends at the same domain.
+pub fn find_domain_cycles(
+    modules: &[ModuleConfig],
+    domain_groups: &HashMap<String, String>,
+) -> Vec<Vec<String>> {
+    let mut graph = DiGraphMap::new();
+
+    for module in modules {
+        graph.add_node(domain_group(domain_groups, &module.path));
+    }
+
+    for module in modules {
+        let source = domain_group(domain_groups, &module.path);
+        for dependency in module.dependencies_iter() {
+            let target = domain_group(domain_groups, &dependency.path);
+            if source != target {
+                graph.add_edge(source, target, ());
+            }
+        }
+    }
+
+    let domains: HashSet<&String> = domain_groups.values().collect();
+    let mut cycles: Vec<Vec<String>> = kosaraju_scc(&graph)
+        .into_iter()
+        .filter(|scc| scc.len() > 1)
+        .filter_map(|scc| {
+            let start = *scc.iter().filter(|node| domains.contains(*node)).min()?;
+            Some(shortest_cycle(&graph, &scc.into_iter().collect(), start))
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
+
 fn validate_root_module_treatment(
     root_module_treatment: RootModuleTreatment,
     modules: &[ModuleConfig],
@@ -167,10 +238,12 @@ fn validate_root_module_treatment(
     }
 }
 
+#[tracing::instrument(level = "debug", skip_all, fields(modules = modules.len()))]
 pub fn build_module_tree(
     _source_roots: &[PathBuf],
     modules: &[ModuleConfig],
     forbid_circular_dependencies: bool,
+    circular_domain_groups: Option<&HashMap<String, String>>,
     root_module_treatment: RootModuleTreatment,
 ) -> Result<ModuleTree, ModuleTreeError> {
     // Check for duplicate modules
@@ -200,6 +273,14 @@ pub fn build_module_tree(
         }
     }
 
+    // Check for circular dependencies between domains if forbidden
+    if let Some(domain_groups) = circular_domain_groups {
+        let domain_cycles = find_domain_cycles(modules, domain_groups);
+        if !domain_cycles.is_empty() {
+            return Err(ModuleTreeError::CircularDomainDependency(domain_cycles));
+        }
+    }
+
     // Construct the ModuleTree
     let mut tree = ModuleTree::new();
     for module in modules {
@@ -213,6 +294,7 @@ pub fn build_module_tree(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DependencyConfig;
     use crate::{parsing::config::parse_project_config, tests::fixtures::example_dir};
     use rstest::rstest;
     #[rstest]
@@ -234,4 +316,46 @@ mod tests {
         let module_paths = find_modules_with_cycles(&modules);
         assert_eq!(module_paths, ["domain_one", "domain_two", "domain_three"]);
     }
+
+    fn module(path: &str, depends_on: &[&str]) -> ModuleConfig {
+        ModuleConfig {
+            path: path.to_string(),
+            depends_on: Some(
+                depends_on
+                    .iter()
+                    .map(|dependency| DependencyConfig::from_path(*dependency))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_domain_cycles() {
+        let modules = vec![
+            // Cycles within a domain are allowed
+            module("billing.api", &["billing.core", "shared"]),
+            module("billing.core", &["billing.api"]),
+            module("shared", &["users.models"]),
+            module("users.models", &["users.views"]),
+            module("users.views", &["billing.api"]),
+        ];
+        let domain_groups: HashMap<String, String> = [
+            ("billing.api", "billing"),
+            ("billing.core", "billing"),
+            ("users.models", "users"),
+            ("users.views", "users"),
+        ]
+        .into_iter()
+        .map(|(module, domain)| (module.to_string(), domain.to_string()))
+        .collect();
+
+        assert_eq!(
+            find_domain_cycles(&modules, &domain_groups),
+            vec![vec!["billing", "shared", "users", "billing"]]
+        );
+
+        let acyclic_modules = &modules[..4];
+        assert!(find_domain_cycles(acyclic_modules, &domain_groups).is_empty());
+    }
 }
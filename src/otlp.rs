@@ -0,0 +1,176 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use thiserror::Error;
+use tracing::{Level, Metadata};
+use tracing_subscriber::{filter::dynamic_filter_fn, Layer, Registry};
+
+use crate::config::OtlpConfig;
+
+#[derive(Error, Debug)]
+pub enum OtlpError {
+    #[error("This build of tach does not support OTLP export. Rebuild it with the 'otel' feature to export spans.")]
+    Unsupported,
+    #[error("Tracing must be initialized before starting OTLP export.")]
+    NotInitialized,
+    #[error("Failed to start OTLP export: {0}")]
+    Exporter(String),
+}
+
+pub type Result<T> = std::result::Result<T, OtlpError>;
+
+static EXPORTING: AtomicBool = AtomicBool::new(false);
+
+// Only the phases of an analysis are exported, rather than each file (e.g. 'check_file'),
+// since every span is sent to the collector as soon as it closes
+fn is_exported(metadata: &Metadata<'_>) -> bool {
+    EXPORTING.load(Ordering::Relaxed)
+        && metadata.is_span()
+        && metadata.target().starts_with("tach")
+        && *metadata.level() <= Level::DEBUG
+}
+
+/// Resource attributes describing the project, which identify its spans among those of other projects
+#[cfg_attr(not(feature = "otel"), allow(dead_code))]
+fn resource_attributes(project_root: &Path, config: &OtlpConfig) -> Vec<(String, String)> {
+    let mut attributes = vec![
+        ("service.name".to_string(), "tach".to_string()),
+        (
+            "service.version".to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+        ),
+    ];
+    if let Some(name) = project_root.file_name() {
+        attributes.push((
+            "tach.project".to_string(),
+            name.to_string_lossy().to_string(),
+        ));
+    }
+    // Configured attributes take precedence, including over 'service.name'
+    attributes.retain(|(key, _)| !config.resource_attributes.contains_key(key));
+    attributes.extend(
+        config
+            .resource_attributes
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+    attributes
+}
+
+#[cfg(feature = "otel")]
+mod exporter {
+    use once_cell::sync::{Lazy, OnceCell};
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{trace::TracerProvider, Resource};
+    use parking_lot::Mutex;
+    use tracing_subscriber::reload;
+
+    use super::*;
+
+    pub(super) type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+    static HANDLE: OnceCell<reload::Handle<Option<BoxedLayer>, Registry>> = OnceCell::new();
+    static PROVIDER: Lazy<Mutex<Option<TracerProvider>>> = Lazy::new(|| Mutex::new(None));
+
+    pub(super) fn reloadable_layer() -> reload::Layer<Option<BoxedLayer>, Registry> {
+        let (layer, handle) = reload::Layer::new(None);
+        let _ = HANDLE.set(handle);
+        layer
+    }
+
+    pub(super) fn start(project_root: &Path, config: &OtlpConfig) -> Result<()> {
+        let handle = HANDLE.get().ok_or(OtlpError::NotInitialized)?;
+        let mut builder = opentelemetry_otlp::SpanExporter::builder().with_http();
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        let exporter = builder
+            .build()
+            .map_err(|err| OtlpError::Exporter(err.to_string()))?;
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .with_resource(Resource::new(
+                resource_attributes(project_root, config)
+                    .into_iter()
+                    .map(|(key, value)| KeyValue::new(key, value)),
+            ))
+            .build();
+        let layer: BoxedLayer =
+            Box::new(tracing_opentelemetry::layer().with_tracer(provider.tracer("tach")));
+        handle
+            .reload(Some(layer))
+            .map_err(|err| OtlpError::Exporter(err.to_string()))?;
+        *PROVIDER.lock() = Some(provider);
+        Ok(())
+    }
+
+    pub(super) fn shutdown() {
+        if let Some(provider) = PROVIDER.lock().take() {
+            // Export is best-effort, and a collector which cannot be reached must not fail the command
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+/// The layer which exports spans once [`start`] is called, installed with the global subscriber by [`crate::logging::init`]
+pub(crate) fn layer() -> impl Layer<Registry> {
+    #[cfg(feature = "otel")]
+    let layer = exporter::reloadable_layer();
+    #[cfg(not(feature = "otel"))]
+    let layer = tracing_subscriber::layer::Identity::new();
+    layer.with_filter(dynamic_filter_fn(|metadata, _| is_exported(metadata)))
+}
+
+/// Export the spans of each analysis phase, with the result counts recorded on them,
+/// to the OpenTelemetry collector configured by 'config'
+pub fn start(project_root: &Path, config: &OtlpConfig) -> Result<()> {
+    #[cfg(feature = "otel")]
+    {
+        exporter::start(project_root, config)?;
+        EXPORTING.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        let _ = (project_root, config);
+        Err(OtlpError::Unsupported)
+    }
+}
+
+/// Stop exporting spans, flushing any which have not been sent yet
+pub fn shutdown() {
+    EXPORTING.store(false, Ordering::Relaxed);
+    #[cfg(feature = "otel")]
+    exporter::shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_resource_attributes() {
+        let config = OtlpConfig {
+            enabled: true,
+            endpoint: None,
+            resource_attributes: BTreeMap::from([
+                ("service.name".to_string(), "tach-ci".to_string()),
+                ("team".to_string(), "platform".to_string()),
+            ]),
+        };
+
+        let attributes = resource_attributes(&PathBuf::from("/repos/payments"), &config);
+        assert!(attributes.contains(&("tach.project".to_string(), "payments".to_string())));
+        assert!(attributes.contains(&("team".to_string(), "platform".to_string())));
+        assert_eq!(
+            attributes
+                .iter()
+                .filter(|(key, _)| key == "service.name")
+                .collect::<Vec<_>>(),
+            vec![&("service.name".to_string(), "tach-ci".to_string())]
+        );
+    }
+}
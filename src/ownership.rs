@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::diagnostics::Diagnostic;
+use crate::filesystem::read_file_content;
+
+// The locations GitHub searches for a CODEOWNERS file, in order of precedence
+const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug)]
+struct OwnershipRule {
+    patterns: GlobSet,
+    owners: Vec<String>,
+}
+
+/// Owners of paths in the project, as declared in a CODEOWNERS file.
+/// As on GitHub, the last matching pattern takes precedence.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<OwnershipRule>,
+}
+
+/// Translates a CODEOWNERS (gitignore-style) pattern into globs relative to the project root
+fn pattern_to_globs(pattern: &str) -> Vec<String> {
+    let is_directory = pattern.ends_with('/');
+    let trimmed = pattern.trim_matches('/');
+    // Patterns without a slash (other than a trailing one) match at any depth
+    let base = if pattern.starts_with('/') || trimmed.contains('/') {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+    if is_directory {
+        vec![format!("{}/**", base)]
+    } else {
+        vec![format!("{}/**", base), base]
+    }
+}
+
+impl CodeOwners {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?;
+                let owners: Vec<String> = parts
+                    .take_while(|part| !part.starts_with('#'))
+                    .map(|owner| owner.to_string())
+                    .collect();
+
+                let mut builder = GlobSetBuilder::new();
+                for glob in pattern_to_globs(pattern) {
+                    // Invalid patterns are skipped, as GitHub does
+                    let glob = GlobBuilder::new(&glob)
+                        .literal_separator(true)
+                        .build()
+                        .ok()?;
+                    builder.add(glob);
+                }
+                Some(OwnershipRule {
+                    patterns: builder.build().ok()?,
+                    owners,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Reads the project's CODEOWNERS file, if there is one
+    pub fn discover(project_root: &Path) -> Option<Self> {
+        CODEOWNERS_PATHS
+            .iter()
+            .map(|path| project_root.join(path))
+            .find(|path| path.is_file())
+            .and_then(|path| read_file_content(path).ok())
+            .map(|contents| Self::parse(&contents))
+    }
+
+    /// The owners of a path relative to the project root.
+    /// A matching pattern without any owners means that the path is explicitly unowned.
+    pub fn owners_of(&self, path: &Path) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.patterns.is_match(path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn annotate(&self, diagnostics: &mut [Diagnostic]) {
+        for diagnostic in diagnostics.iter_mut() {
+            let owners = match diagnostic.file_path() {
+                Some(file_path) => self.owners_of(file_path).to_vec(),
+                None => continue,
+            };
+            diagnostic.set_owners(owners);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners_of() {
+        let code_owners = CodeOwners::parse(
+            "# Default owners\n\
+             * @org/platform\n\
+             \n\
+             *.pyi @org/typing\n\
+             /src/billing/ @org/payments @alice # inline comment\n\
+             docs/ @org/docs\n\
+             /src/billing/vendored\n",
+        );
+
+        let owners_of = |path: &str| code_owners.owners_of(Path::new(path)).to_vec();
+        assert_eq!(owners_of("src/core/main.py"), vec!["@org/platform"]);
+        assert_eq!(owners_of("src/core/types.pyi"), vec!["@org/typing"]);
+        assert_eq!(
+            owners_of("src/billing/api/handler.py"),
+            vec!["@org/payments", "@alice"]
+        );
+        assert_eq!(
+            owners_of("src/billing/api/types.pyi"),
+            vec!["@org/payments", "@alice"]
+        );
+        assert!(owners_of("src/billing/vendored/lib.py").is_empty());
+        assert_eq!(owners_of("docs/index.py"), vec!["@org/docs"]);
+    }
+}
@@ -8,20 +8,21 @@ use rayon::prelude::*;
 use crate::{
     colors::BColors,
     config::{
-        root_module::ROOT_MODULE_SENTINEL_TAG, ConfigLocation, DomainConfig, InterfaceConfig,
-        InterfaceDataTypes, LocatedDomainConfig, ProjectConfig,
+        project::CONFIG_SCHEMA_VERSION, root_module::ROOT_MODULE_SENTINEL_TAG, yaml::is_yaml_path,
+        ConfigLocation, DomainConfig, InterfaceConfig, InterfaceDataTypes, LocatedDomainConfig,
+        ProjectConfig,
     },
-    filesystem::{read_file_content, walk_domain_config_files},
+    exclusion::PathExclusions,
+    filesystem::{read_file_content, top_level_packages, walk_domain_config_files},
     python::parsing::parse_interface_members,
 };
 
 use super::error;
+use super::inline::{find_inline_configs, merge_inline_configs};
 
 pub type Result<T> = std::result::Result<T, error::ParsingError>;
 
-pub fn dump_project_config_to_toml(
-    config: &mut ProjectConfig,
-) -> std::result::Result<String, toml::ser::Error> {
+fn sort_project_config(config: &mut ProjectConfig) {
     config.modules.sort_by(|a, b| {
         if a.path == ROOT_MODULE_SENTINEL_TAG {
             Ordering::Less
@@ -40,10 +41,22 @@ pub fn dump_project_config_to_toml(
 
     config.exclude.sort();
     config.source_roots.sort();
+}
 
+pub fn dump_project_config_to_toml(
+    config: &mut ProjectConfig,
+) -> std::result::Result<String, toml::ser::Error> {
+    sort_project_config(config);
     toml::to_string(&config)
 }
 
+pub fn dump_project_config_to_yaml(
+    config: &mut ProjectConfig,
+) -> std::result::Result<String, serde_yaml::Error> {
+    sort_project_config(config);
+    serde_yaml::to_string(&config)
+}
+
 fn migrate_strict_mode_to_interfaces(filepath: &Path, config: &mut ProjectConfig) -> bool {
     if config.modules.iter().any(|m| m.strict) {
         println!(
@@ -70,6 +83,7 @@ fn migrate_strict_mode_to_interfaces(filepath: &Path, config: &mut ProjectConfig
                 expose: interface_members,
                 from_modules: vec![module.path.clone()],
                 data_types: InterfaceDataTypes::All,
+                stability: None,
             });
         }
     }
@@ -77,8 +91,47 @@ fn migrate_strict_mode_to_interfaces(filepath: &Path, config: &mut ProjectConfig
     true
 }
 
-const DEPRECATED_REGEX_EXCLUDE_PATHS: [&str; 2] = [".*__pycache__", ".*egg-info"];
-const REPLACEMENT_GLOB_EXCLUDE_PATHS: [&str; 2] = ["**/*__pycache__", "**/*egg-info"];
+/// Older versions of Tach wrote 'tach.yml' with a single 'source_root',
+/// and with cache backends which no longer exist
+fn migrate_legacy_yaml_config(content: &mut serde_yaml::Value) -> bool {
+    let Some(config) = content.as_mapping_mut() else {
+        return false;
+    };
+    let mut did_migrate = false;
+    if let Some(serde_yaml::Value::String(source_root)) = config.get("source_root").cloned() {
+        if !config.contains_key("source_roots") {
+            config.shift_remove("source_root");
+            config.insert(
+                "source_roots".into(),
+                serde_yaml::Value::Sequence(vec![source_root.into()]),
+            );
+            did_migrate = true;
+        }
+    }
+    if let Some(backend) = config
+        .get_mut("cache")
+        .and_then(|cache| cache.get_mut("backend"))
+    {
+        let is_known = backend
+            .as_str()
+            .is_some_and(|backend| ["disk", "http", "redis"].contains(&backend));
+        if !is_known {
+            *backend = "disk".into();
+            did_migrate = true;
+        }
+    }
+    if did_migrate {
+        println!(
+            "{}WARNING: Migrating deprecated YAML config to the current layout.{}",
+            BColors::WARNING,
+            BColors::ENDC
+        );
+    }
+    did_migrate
+}
+
+pub(crate) const DEPRECATED_REGEX_EXCLUDE_PATHS: [&str; 2] = [".*__pycache__", ".*egg-info"];
+pub(crate) const REPLACEMENT_GLOB_EXCLUDE_PATHS: [&str; 2] = ["**/*__pycache__", "**/*egg-info"];
 const EXPECTED_EXCLUDE_PATHS: [&str; 5] = [
     "tests",
     "docs",
@@ -88,10 +141,6 @@ const EXPECTED_EXCLUDE_PATHS: [&str; 5] = [
 ];
 
 fn migrate_deprecated_regex_exclude(config: &mut ProjectConfig) -> bool {
-    if config.use_regex_matching {
-        return false;
-    }
-
     let mut did_migrate = false;
     config.exclude.iter_mut().for_each(|exclude_path| {
         if let Some(index) = DEPRECATED_REGEX_EXCLUDE_PATHS
@@ -150,12 +199,38 @@ pub fn parse_domain_config<P: AsRef<Path>>(
     Ok(config.with_location(location))
 }
 
+#[tracing::instrument(level = "debug", skip_all, fields(path = %filepath.as_ref().display()))]
 pub fn parse_project_config<P: AsRef<Path>>(filepath: P) -> Result<(ProjectConfig, bool)> {
     let content = read_file_content(filepath.as_ref())?;
-    let mut config: ProjectConfig = toml::from_str(&content)?;
+    parse_project_config_content(&content, filepath)
+}
+
+/// Parse the contents of a project config as if they were read from `filepath`,
+/// which determines where domain configs and source roots are found
+pub fn parse_project_config_content<P: AsRef<Path>>(
+    content: &str,
+    filepath: P,
+) -> Result<(ProjectConfig, bool)> {
+    let mut migrated_legacy_yaml = false;
+    let mut config: ProjectConfig = if is_yaml_path(filepath.as_ref()) {
+        let mut content: serde_yaml::Value = serde_yaml::from_str(content)?;
+        migrated_legacy_yaml = migrate_legacy_yaml_config(&mut content);
+        serde_yaml::from_value(content)?
+    } else {
+        toml::from_str(content)?
+    };
+    if config.use_regex_matching {
+        return Err(error::ParsingError::RegexExcludes);
+    }
     config.set_location(filepath.as_ref().to_path_buf());
+    // Legacy YAML is migrated before parsing, and the other migrations still apply afterwards
     let did_migrate = migrate_strict_mode_to_interfaces(filepath.as_ref(), &mut config)
-        || migrate_deprecated_regex_exclude(&mut config);
+        || migrate_deprecated_regex_exclude(&mut config)
+        || migrated_legacy_yaml;
+    if did_migrate {
+        // The migrated config is written back in the current layout
+        config.schema_version = CONFIG_SCHEMA_VERSION;
+    }
     let root_dir = filepath.as_ref().parent().unwrap();
     let mut domain_configs = walk_domain_config_files(root_dir.as_os_str().to_str().unwrap())
         .par_bridge()
@@ -164,9 +239,28 @@ pub fn parse_project_config<P: AsRef<Path>>(filepath: P) -> Result<(ProjectConfi
     domain_configs.drain(..).for_each(|domain| {
         config.add_domain(domain);
     });
+    if config.implicit_modules {
+        add_implicit_modules(root_dir, &mut config)?;
+    }
+    if config.inline_config {
+        let inline_configs = find_inline_configs(root_dir, &config)?;
+        merge_inline_configs(root_dir, &mut config, inline_configs)?;
+    }
     Ok((config, did_migrate))
 }
 
+/// Add a module with the default configuration for each top-level package
+/// which is not already declared in the project config or a domain config.
+fn add_implicit_modules(project_root: &Path, config: &mut ProjectConfig) -> Result<()> {
+    let exclusions = PathExclusions::new(project_root, &config.exclude, &config.ignore)?;
+    for source_root in config.prepend_roots(project_root) {
+        for package in top_level_packages(&source_root, &exclusions, config.namespace_packages) {
+            config.add_inferred_module(&package);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -175,7 +269,8 @@ mod tests {
     use super::*;
     use crate::{
         config::{
-            project::DEFAULT_EXCLUDE_PATHS, root_module::ROOT_MODULE_SENTINEL_TAG, DependencyConfig,
+            cache::CacheBackend, project::DEFAULT_EXCLUDE_PATHS,
+            root_module::ROOT_MODULE_SENTINEL_TAG, ConfigEdit, DependencyConfig,
         },
         tests::fixtures::example_dir,
     };
@@ -315,4 +410,128 @@ mod tests {
             [].iter().collect::<HashSet<_>>()
         );
     }
+
+    #[test]
+    fn test_parse_yaml_project_config() {
+        let project_root = tempfile::tempdir().unwrap();
+        let config_path = project_root.path().join("tach.yml");
+        std::fs::write(
+            &config_path,
+            "source_roots:\n- src\nmodules:\n- path: a\n  depends_on:\n  - b\n- path: b\n  depends_on: []\n",
+        )
+        .unwrap();
+
+        let (config, did_migrate) = parse_project_config(&config_path).unwrap();
+        assert!(!did_migrate);
+        assert_eq!(config.location, Some(config_path));
+        assert_eq!(config.source_roots, vec![PathBuf::from("src")]);
+        assert_eq!(
+            config.dependencies_for_module("a").unwrap(),
+            &vec![DependencyConfig::from_path("b")]
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy_yaml_project_config() {
+        let project_root = tempfile::tempdir().unwrap();
+        let config_path = project_root.path().join("tach.yml");
+        std::fs::write(
+            &config_path,
+            "source_root: src\ncache:\n  backend: local\nmodules:\n- path: a\n  depends_on:\n  - b\n- path: b\n  depends_on: []\n",
+        )
+        .unwrap();
+
+        let (config, did_migrate) = parse_project_config(&config_path).unwrap();
+        assert!(did_migrate);
+        assert_eq!(config.source_roots, vec![PathBuf::from("src")]);
+        assert_eq!(config.cache.backend, CacheBackend::Disk);
+        assert_eq!(
+            config.dependencies_for_module("a").unwrap(),
+            &vec![DependencyConfig::from_path("b")]
+        );
+    }
+
+    #[test]
+    fn test_parse_implicit_modules() {
+        let project_root = tempfile::tempdir().unwrap();
+        for file_path in [
+            "a/__init__.py",
+            "b/__init__.py",
+            "c/__init__.py",
+            "scripts/run.py",
+        ] {
+            let file_path = project_root.path().join(file_path);
+            std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            std::fs::write(file_path, "").unwrap();
+        }
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(
+            &config_path,
+            "implicit_modules = true\nnamespace_packages = false\nexclude = [\"c\"]\n\n[[modules]]\npath = \"a\"\ndepends_on = [\"b\"]\n",
+        )
+        .unwrap();
+
+        let (mut config, _) = parse_project_config(&config_path).unwrap();
+        // Explicit modules override the inferred defaults, and excluded packages are skipped
+        assert_eq!(config.module_paths(), vec!["a", "b"]);
+        assert_eq!(config.dependencies_for_module("b").unwrap(), &vec![]);
+
+        // Editing an inferred module writes it out as an explicit module
+        config
+            .add_dependency("b".to_string(), "a".to_string())
+            .unwrap();
+        assert_eq!(
+            config.pending_edits,
+            vec![
+                ConfigEdit::CreateModule {
+                    path: "b".to_string()
+                },
+                ConfigEdit::AddDependency {
+                    path: "b".to_string(),
+                    dependency: "a".to_string()
+                },
+            ]
+        );
+        assert!(config.inferred_modules.is_empty());
+    }
+
+    #[test]
+    fn test_parse_inline_config() {
+        let project_root = tempfile::tempdir().unwrap();
+        for (file_path, contents) in [
+            ("a/__init__.py", "__tach__ = {\"depends_on\": [\"b\"]}\n"),
+            ("b/__init__.py", "__tach__ = {\"depends_on\": []}\n"),
+            ("c/__init__.py", ""),
+        ] {
+            let file_path = project_root.path().join(file_path);
+            std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            std::fs::write(file_path, contents).unwrap();
+        }
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(
+            &config_path,
+            "inline_config = true\n\n[[modules]]\npath = \"b\"\ndepends_on = []\n",
+        )
+        .unwrap();
+
+        let (mut config, _) = parse_project_config(&config_path).unwrap();
+        assert_eq!(config.module_paths(), vec!["b", "a"]);
+        assert_eq!(
+            config.dependencies_for_module("a").unwrap(),
+            &vec![DependencyConfig::from_path("b")]
+        );
+        // Inline modules can only be edited in their source
+        assert!(config
+            .add_dependency("a".to_string(), "c".to_string())
+            .is_err());
+
+        // The central config must agree with the inline config
+        std::fs::write(
+            &config_path,
+            "inline_config = true\n\n[[modules]]\npath = \"b\"\ndepends_on = [\"c\"]\n",
+        )
+        .unwrap();
+        let error = parse_project_config(&config_path).unwrap_err().to_string();
+        assert!(error.contains("'b' sets 'depends_on' to [] in '__tach__'"));
+    }
 }
@@ -1,6 +1,7 @@
 use std::io;
 use thiserror::Error;
 
+use crate::exclusion::PathExclusionError;
 use crate::filesystem::FileSystemError;
 
 #[derive(Error, Debug)]
@@ -11,8 +12,16 @@ pub enum ParsingError {
     Filesystem(#[from] FileSystemError),
     #[error("TOML parsing error: {0}")]
     TomlParse(#[from] toml::de::Error),
+    #[error("YAML parsing error: {0}")]
+    YamlParse(#[from] serde_yaml::Error),
     #[error("Missing field in TOML: {0}")]
     MissingField(String),
     #[error("Module path error: {0}")]
     ModulePath(String),
+    #[error("Exclusion error: {0}")]
+    PathExclusion(#[from] PathExclusionError),
+    #[error("Invalid inline module config:\n{0}")]
+    InlineConfig(String),
+    #[error("'use_regex_matching' is no longer supported. Rewrite the patterns in 'exclude' as gitignore-style patterns (e.g. '.*__pycache__' becomes '**/*__pycache__'), then remove 'use_regex_matching'.")]
+    RegexExcludes,
 }
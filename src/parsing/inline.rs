@@ -0,0 +1,399 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use ruff_python_ast::{Expr, Mod, Stmt};
+
+use crate::config::{
+    DependencyConfig, InterfaceConfig, InterfaceDataTypes, ModuleConfig, ProjectConfig,
+};
+use crate::exclusion::PathExclusions;
+use crate::filesystem::{self as fs, WalkOptions};
+use crate::interfaces::compiled::CompiledInterfaces;
+use crate::python::parsing::{parse_interface_members, parse_python_source};
+
+use super::error::ParsingError;
+
+const INLINE_CONFIG_NAME: &str = "__tach__";
+
+/// A module configuration declared in a package's `__init__.py`, e.g.
+/// `__tach__ = {"depends_on": ["core"], "strict": True}`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InlineModuleConfig {
+    pub depends_on: Option<Vec<DependencyConfig>>,
+    pub strict: Option<bool>,
+    pub utility: Option<bool>,
+    pub layer: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocatedInlineModuleConfig {
+    pub module_path: String,
+    // Relative to the project root
+    pub file_path: PathBuf,
+    pub config: InlineModuleConfig,
+}
+
+fn string_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::StringLiteral(string) => Some(string.value.to_string()),
+        _ => None,
+    }
+}
+
+fn bool_value(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::BooleanLiteral(boolean) => Some(boolean.value),
+        _ => None,
+    }
+}
+
+fn dependency_value(expr: &Expr) -> Result<DependencyConfig, String> {
+    if let Some(path) = string_value(expr) {
+        return Ok(DependencyConfig::from_path(&path));
+    }
+    let Expr::Dict(dict) = expr else {
+        return Err("each entry in 'depends_on' must be a string or a dict".to_string());
+    };
+    let mut dependency = DependencyConfig::default();
+    let mut path = None;
+    for item in &dict.items {
+        let key = item.key.as_ref().and_then(string_value);
+        match key.as_deref() {
+            Some("path") => path = string_value(&item.value),
+            Some("deprecated") => {
+                dependency.deprecated =
+                    bool_value(&item.value).ok_or("'deprecated' must be True or False")?
+            }
+            _ => {
+                return Err(format!(
+                    "unsupported key {} in a 'depends_on' entry",
+                    key.map_or("(non-string)".to_string(), |key| format!("'{}'", key))
+                ))
+            }
+        }
+    }
+    dependency.path = path.ok_or("each entry in 'depends_on' needs a string 'path'")?;
+    Ok(dependency)
+}
+
+fn inline_config_value(expr: &Expr) -> Result<InlineModuleConfig, String> {
+    let Expr::Dict(dict) = expr else {
+        return Err(format!("'{}' must be a dict literal", INLINE_CONFIG_NAME));
+    };
+    let mut config = InlineModuleConfig::default();
+    for item in &dict.items {
+        let key = item.key.as_ref().and_then(string_value);
+        match key.as_deref() {
+            Some("depends_on") => {
+                let (Expr::List(ruff_python_ast::ExprList { elts, .. })
+                | Expr::Tuple(ruff_python_ast::ExprTuple { elts, .. })) = &item.value
+                else {
+                    return Err("'depends_on' must be a list".to_string());
+                };
+                config.depends_on = Some(
+                    elts.iter()
+                        .map(dependency_value)
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            Some("strict") => {
+                config.strict =
+                    Some(bool_value(&item.value).ok_or("'strict' must be True or False")?)
+            }
+            Some("utility") => {
+                config.utility =
+                    Some(bool_value(&item.value).ok_or("'utility' must be True or False")?)
+            }
+            Some("layer") => {
+                config.layer = Some(string_value(&item.value).ok_or("'layer' must be a string")?)
+            }
+            _ => {
+                return Err(format!(
+                    "unsupported key {} (expected 'depends_on', 'strict', 'utility' or 'layer')",
+                    key.map_or("(non-string)".to_string(), |key| format!("'{}'", key))
+                ))
+            }
+        }
+    }
+    Ok(config)
+}
+
+/// Find a top-level `__tach__` assignment in Python source
+pub fn parse_inline_config(python_source: &str) -> Result<Option<InlineModuleConfig>, String> {
+    // Most packages have no inline config, so skip parsing them
+    if !python_source.contains(INLINE_CONFIG_NAME) {
+        return Ok(None);
+    }
+    let Ok(Mod::Module(module)) = parse_python_source(python_source) else {
+        return Ok(None);
+    };
+    for stmt in &module.body {
+        let (targets, value) = match stmt {
+            Stmt::Assign(node) => (node.targets.iter().collect::<Vec<_>>(), &node.value),
+            Stmt::AnnAssign(node) => match &node.value {
+                Some(value) => (vec![node.target.as_ref()], value),
+                None => continue,
+            },
+            _ => continue,
+        };
+        if targets
+            .iter()
+            .any(|target| matches!(target, Expr::Name(name) if name.id == INLINE_CONFIG_NAME))
+        {
+            return inline_config_value(value).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Find the inline configs in every package `__init__.py` in the project's source roots
+pub fn find_inline_configs(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+) -> Result<Vec<LocatedInlineModuleConfig>, ParsingError> {
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    let source_roots = project_config.prepend_roots(project_root);
+    let mut inline_configs = Vec::new();
+    let mut problems = Vec::new();
+    for source_root in &source_roots {
+        let results: Vec<_> = fs::walk_pyfiles(
+            &source_root.display().to_string(),
+            &exclusions,
+            WalkOptions {
+                include_stubs: false,
+                include_notebooks: false,
+                ..WalkOptions::from(project_config)
+            },
+        )
+        .filter(|file_path| {
+            file_path
+                .file_name()
+                .is_some_and(|name| name == "__init__.py")
+        })
+        .par_bridge()
+        .filter_map(|file_path| {
+            let absolute_path = source_root.join(&file_path);
+            let relative_path = fs::relative_to(&absolute_path, project_root)
+                .unwrap_or_else(|_| absolute_path.clone());
+            let contents = fs::read_file_content(&absolute_path).ok()?;
+            match parse_inline_config(&contents) {
+                Ok(Some(config)) => Some(Ok(LocatedInlineModuleConfig {
+                    module_path: fs::file_to_module_path(&source_roots, &absolute_path).ok()?,
+                    file_path: relative_path,
+                    config,
+                })),
+                Ok(None) => None,
+                Err(problem) => Some(Err(format!("{}: {}", relative_path.display(), problem))),
+            }
+        })
+        .collect();
+        for result in results {
+            match result {
+                Ok(inline_config) => inline_configs.push(inline_config),
+                Err(problem) => problems.push(problem),
+            }
+        }
+    }
+    if !problems.is_empty() {
+        problems.sort();
+        return Err(ParsingError::InlineConfig(problems.join("\n")));
+    }
+    inline_configs.sort_by(|left, right| left.module_path.cmp(&right.module_path));
+    // With nested source roots, the same package may be walked more than once
+    inline_configs.dedup_by(|left, right| left.file_path == right.file_path);
+    Ok(inline_configs)
+}
+
+fn dependency_paths(dependencies: &Option<Vec<DependencyConfig>>) -> Option<BTreeSet<&str>> {
+    dependencies.as_ref().map(|dependencies| {
+        dependencies
+            .iter()
+            .map(|dependency| dependency.path.as_str())
+            .collect()
+    })
+}
+
+fn describe_dependencies(paths: &Option<BTreeSet<&str>>) -> String {
+    match paths {
+        Some(paths) => format!(
+            "[{}]",
+            paths
+                .iter()
+                .map(|path| format!("'{}'", path))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => "unrestricted".to_string(),
+    }
+}
+
+/// Differences between an inline config and the module's entry in the central config
+fn find_conflicts(
+    inline: &LocatedInlineModuleConfig,
+    module: &ModuleConfig,
+    has_interface: bool,
+) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    let mut conflict = |field: &str, inline_value: String, central_value: String| {
+        conflicts.push(format!(
+            "{}: '{}' sets {} to {} in '{}', but the project configuration sets it to {}",
+            inline.file_path.display(),
+            inline.module_path,
+            field,
+            inline_value,
+            INLINE_CONFIG_NAME,
+            central_value
+        ))
+    };
+    if inline.config.depends_on.is_some() {
+        let inline_paths = dependency_paths(&inline.config.depends_on);
+        let central_paths = dependency_paths(&module.depends_on);
+        if inline_paths != central_paths {
+            conflict(
+                "'depends_on'",
+                describe_dependencies(&inline_paths),
+                describe_dependencies(&central_paths),
+            );
+        }
+    }
+    if let Some(utility) = inline
+        .config
+        .utility
+        .filter(|utility| *utility != module.utility)
+    {
+        conflict("'utility'", utility.to_string(), module.utility.to_string());
+    }
+    if let Some(layer) = inline
+        .config
+        .layer
+        .as_ref()
+        .filter(|layer| module.layer.as_ref() != Some(*layer))
+    {
+        conflict(
+            "'layer'",
+            format!("'{}'", layer),
+            module
+                .layer
+                .as_ref()
+                .map_or("nothing".to_string(), |layer| format!("'{}'", layer)),
+        );
+    }
+    if inline.config.strict == Some(true) && !has_interface {
+        conflict("'strict'", "true".to_string(), "no interface".to_string());
+    }
+    conflicts
+}
+
+/// Merge inline configs into the project config. Modules which are only declared inline are added,
+/// while modules which are also declared centrally are validated against their inline config.
+pub fn merge_inline_configs(
+    project_root: &Path,
+    project_config: &mut ProjectConfig,
+    inline_configs: Vec<LocatedInlineModuleConfig>,
+) -> Result<(), ParsingError> {
+    let source_roots = project_config.prepend_roots(project_root);
+    let mut conflicts = Vec::new();
+    for inline in inline_configs {
+        // Inline configs take precedence over the defaults of implicit modules
+        project_config
+            .inferred_modules
+            .retain(|module| module.path != inline.module_path);
+        if let Some(module) = project_config
+            .all_modules()
+            .find(|module| module.path == inline.module_path)
+        {
+            let compiled_interfaces = CompiledInterfaces::build(project_config.all_interfaces());
+            let has_interface = !compiled_interfaces
+                .get_interfaces(&inline.module_path)
+                .is_empty();
+            conflicts.extend(find_conflicts(&inline, module, has_interface));
+            continue;
+        }
+
+        if inline.config.strict == Some(true) {
+            project_config.inline_interfaces.push(InterfaceConfig {
+                expose: parse_interface_members(&source_roots, &inline.module_path)
+                    .unwrap_or_default(),
+                from_modules: vec![inline.module_path.clone()],
+                data_types: InterfaceDataTypes::All,
+                stability: None,
+            });
+        }
+        project_config.inline_modules.push(ModuleConfig {
+            depends_on: inline.config.depends_on.or(Some(vec![])),
+            utility: inline.config.utility.unwrap_or_default(),
+            layer: inline.config.layer,
+            ..ModuleConfig::new(&inline.module_path, false)
+        });
+    }
+    if !conflicts.is_empty() {
+        return Err(ParsingError::InlineConfig(conflicts.join("\n")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inline_config() {
+        let config = parse_inline_config(
+            "from .api import run\n\n__tach__ = {\"depends_on\": [\"core\", {\"path\": \"legacy\", \"deprecated\": True}], \"strict\": True}\n",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            config.depends_on,
+            Some(vec![
+                DependencyConfig::from_path("core"),
+                DependencyConfig::from_deprecated_path("legacy"),
+            ])
+        );
+        assert_eq!(config.strict, Some(true));
+        assert_eq!(config.utility, None);
+
+        assert_eq!(parse_inline_config("x = 1\n").unwrap(), None);
+        assert!(parse_inline_config("__tach__ = {\"depends\": []}\n").is_err());
+        assert!(parse_inline_config("__tach__ = dict(depends_on=[])\n").is_err());
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let inline = LocatedInlineModuleConfig {
+            module_path: "billing".to_string(),
+            file_path: PathBuf::from("billing/__init__.py"),
+            config: InlineModuleConfig {
+                depends_on: Some(vec![DependencyConfig::from_path("core")]),
+                utility: Some(false),
+                ..Default::default()
+            },
+        };
+        let module = ModuleConfig {
+            depends_on: Some(vec![DependencyConfig::from_deprecated_path("core")]),
+            ..ModuleConfig::new("billing", false)
+        };
+        assert!(find_conflicts(&inline, &module, false).is_empty());
+
+        let module = ModuleConfig {
+            depends_on: Some(vec![
+                DependencyConfig::from_path("core"),
+                DependencyConfig::from_path("auth"),
+            ]),
+            utility: true,
+            ..ModuleConfig::new("billing", false)
+        };
+        assert_eq!(
+            find_conflicts(&inline, &module, false),
+            vec![
+                "billing/__init__.py: 'billing' sets 'depends_on' to ['core'] in '__tach__', but the project configuration sets it to ['auth', 'core']",
+                "billing/__init__.py: 'billing' sets 'utility' to false in '__tach__', but the project configuration sets it to true",
+            ]
+        );
+    }
+}
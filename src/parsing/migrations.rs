@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::config::project::{CONFIG_SCHEMA_VERSION, LEGACY_SCHEMA_VERSION};
+use crate::config::write::{backup_files, write_atomic};
+use crate::config::yaml::is_yaml_path;
+use crate::python::parsing::parse_interface_members;
+
+use super::config::{DEPRECATED_REGEX_EXCLUDE_PATHS, REPLACEMENT_GLOB_EXCLUDE_PATHS};
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("Failed to read config: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse config: {0}")]
+    TomlParse(#[from] toml_edit::TomlError),
+    #[error("Config migration is only supported for TOML configs")]
+    UnsupportedFormat,
+    #[error("Config has schema version {found}, but this version of tach supports up to {supported}. Upgrade tach to use this config.")]
+    NewerSchemaVersion { found: i64, supported: u32 },
+}
+
+pub type Result<T> = std::result::Result<T, MigrationError>;
+
+/// A single upgrade of the config layout.
+/// `apply` returns whether the document was changed.
+struct Migration {
+    to_version: u32,
+    description: &'static str,
+    apply: fn(&mut DocumentMut, &Path) -> bool,
+}
+
+const MIGRATIONS: [Migration; 3] = [
+    Migration {
+        to_version: 2,
+        description: "Replaced 'source_root' with 'source_roots'",
+        apply: migrate_source_root,
+    },
+    Migration {
+        to_version: 2,
+        description: "Replaced default regex exclude patterns with glob patterns",
+        apply: migrate_regex_exclude,
+    },
+    Migration {
+        to_version: 2,
+        description: "Replaced 'strict' modules with interfaces",
+        apply: migrate_strict_modules,
+    },
+];
+
+#[derive(Debug, Clone)]
+#[pyclass(get_all, module = "tach.extension")]
+pub struct MigrationResult {
+    pub from_version: u32,
+    pub to_version: u32,
+    // Descriptions of the migrations which changed the config
+    pub applied: Vec<String>,
+    pub original: String,
+    pub migrated: String,
+    // Where the config was backed up before it was written
+    pub backup: Option<PathBuf>,
+}
+
+#[pymethods]
+impl MigrationResult {
+    pub fn has_changes(&self) -> bool {
+        self.original != self.migrated
+    }
+}
+
+fn migrate_source_root(doc: &mut DocumentMut, _project_root: &Path) -> bool {
+    let Some(source_root) = doc.remove("source_root") else {
+        return false;
+    };
+    if !doc.contains_key("source_roots") {
+        if let Some(source_root) = source_root.as_str() {
+            let mut source_roots = toml_edit::Array::new();
+            source_roots.push(source_root);
+            doc.insert("source_roots", toml_edit::value(source_roots));
+        }
+    }
+    true
+}
+
+fn migrate_regex_exclude(doc: &mut DocumentMut, _project_root: &Path) -> bool {
+    if doc
+        .get("use_regex_matching")
+        .and_then(Item::as_bool)
+        .unwrap_or(false)
+    {
+        return false;
+    }
+    let Some(exclude) = doc.get_mut("exclude").and_then(Item::as_array_mut) else {
+        return false;
+    };
+
+    let mut did_migrate = false;
+    for value in exclude.iter_mut() {
+        let replacement = DEPRECATED_REGEX_EXCLUDE_PATHS
+            .iter()
+            .position(|path| value.as_str() == Some(*path))
+            .map(|index| REPLACEMENT_GLOB_EXCLUDE_PATHS[index]);
+        if let Some(replacement) = replacement {
+            let decor = value.decor().clone();
+            *value = replacement.into();
+            *value.decor_mut() = decor;
+            did_migrate = true;
+        }
+    }
+    did_migrate
+}
+
+fn migrate_strict_modules(doc: &mut DocumentMut, project_root: &Path) -> bool {
+    let source_roots: Vec<PathBuf> = doc
+        .get("source_roots")
+        .and_then(Item::as_array)
+        .map(|roots| {
+            roots
+                .iter()
+                .filter_map(|root| root.as_str())
+                .map(|root| project_root.join(root))
+                .collect()
+        })
+        .unwrap_or_else(|| vec![project_root.to_path_buf()]);
+
+    let mut strict_modules: Vec<String> = vec![];
+    if let Some(modules) = doc
+        .get_mut("modules")
+        .and_then(Item::as_array_of_tables_mut)
+    {
+        for module in modules.iter_mut() {
+            if module.remove("strict").and_then(|strict| strict.as_bool()) == Some(true) {
+                if let Some(path) = module.get("path").and_then(Item::as_str) {
+                    strict_modules.push(path.to_string());
+                }
+            }
+        }
+    }
+    if strict_modules.is_empty() {
+        return false;
+    }
+
+    for module_path in strict_modules {
+        let mut expose = toml_edit::Array::new();
+        for member in parse_interface_members(&source_roots, &module_path).unwrap_or_default() {
+            expose.push(member);
+        }
+        let mut from = toml_edit::Array::new();
+        from.push(module_path);
+
+        let mut interface = toml_edit::Table::new();
+        interface.insert("expose", toml_edit::value(expose));
+        interface.insert("from", toml_edit::value(from));
+        interface.decor_mut().set_prefix("\n");
+
+        match doc.get_mut("interfaces") {
+            Some(Item::ArrayOfTables(interfaces)) => interfaces.push(interface),
+            Some(Item::Value(Value::Array(interfaces))) => {
+                interfaces.push(interface.into_inline_table());
+            }
+            _ => {
+                let mut interfaces = toml_edit::ArrayOfTables::new();
+                interfaces.push(interface);
+                doc.insert("interfaces", Item::ArrayOfTables(interfaces));
+            }
+        }
+    }
+    true
+}
+
+/// Upgrade the config at `config_path` to the current schema version.
+/// With `dry_run`, the migrated config is returned but not written.
+/// Otherwise, the config is backed up first when `backup` is set.
+pub fn migrate_project_config(
+    config_path: &Path,
+    dry_run: bool,
+    backup: bool,
+) -> Result<MigrationResult> {
+    if is_yaml_path(config_path) {
+        return Err(MigrationError::UnsupportedFormat);
+    }
+    let original = std::fs::read_to_string(config_path)?;
+    let mut doc = original.parse::<DocumentMut>()?;
+
+    let found = doc
+        .get("schema_version")
+        .and_then(Item::as_integer)
+        .unwrap_or(i64::from(LEGACY_SCHEMA_VERSION));
+    if found > i64::from(CONFIG_SCHEMA_VERSION) {
+        return Err(MigrationError::NewerSchemaVersion {
+            found,
+            supported: CONFIG_SCHEMA_VERSION,
+        });
+    }
+    let from_version = found.max(0) as u32;
+
+    let project_root = config_path.parent().unwrap_or(Path::new("."));
+    let applied: Vec<String> = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.to_version > from_version)
+        .filter(|migration| (migration.apply)(&mut doc, project_root))
+        .map(|migration| migration.description.to_string())
+        .collect();
+
+    if from_version < CONFIG_SCHEMA_VERSION {
+        doc.insert(
+            "schema_version",
+            toml_edit::value(i64::from(CONFIG_SCHEMA_VERSION)),
+        );
+    }
+
+    let migrated = doc.to_string();
+    let mut backup_dir = None;
+    if !dry_run && migrated != original {
+        if backup {
+            backup_dir = Some(backup_files(project_root, &[config_path])?);
+        }
+        write_atomic(config_path, &migrated)?;
+    }
+
+    Ok(MigrationResult {
+        from_version,
+        to_version: CONFIG_SCHEMA_VERSION,
+        applied,
+        original,
+        migrated,
+        backup: backup_dir,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrate(content: &str) -> MigrationResult {
+        let project_root = tempfile::tempdir().unwrap();
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(&config_path, content).unwrap();
+        let result = migrate_project_config(&config_path, true, true).unwrap();
+        // Dry runs never write the config
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), content);
+        result
+    }
+
+    #[test]
+    fn test_migrate_legacy_config() {
+        let result = migrate(
+            "source_root = \"src\"\nexclude = [\".*__pycache__\", \"docs\"]\n\n[[modules]]\npath = \"a\"\nstrict = true\ndepends_on = []\n",
+        );
+        assert_eq!(result.from_version, 1);
+        assert_eq!(result.applied.len(), 3);
+        assert_eq!(
+            result.migrated,
+            "exclude = [\"**/*__pycache__\", \"docs\"]\nsource_roots = [\"src\"]\nschema_version = 2\n\n[[modules]]\npath = \"a\"\ndepends_on = []\n\n[[interfaces]]\nexpose = []\nfrom = [\"a\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_migrate_current_config() {
+        let content = "schema_version = 2\nsource_roots = [\"src\"]\n";
+        let result = migrate(content);
+        assert!(result.applied.is_empty());
+        assert!(!result.has_changes());
+    }
+
+    #[test]
+    fn test_migrate_newer_config() {
+        let project_root = tempfile::tempdir().unwrap();
+        let config_path = project_root.path().join("tach.toml");
+        std::fs::write(&config_path, "schema_version = 99\n").unwrap();
+        assert!(matches!(
+            migrate_project_config(&config_path, true, true),
+            Err(MigrationError::NewerSchemaVersion { found: 99, .. })
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use itertools::Itertools;
+
+use crate::cache::FileResultCache;
 use crate::config::plugins::django::DjangoConfig;
 use crate::config::root_module::RootModuleTreatment;
 use crate::config::ProjectConfig;
@@ -9,12 +12,12 @@ use crate::exclusion::PathExclusions;
 use crate::filesystem::{self, ProjectFile};
 use crate::modules::error::ModuleTreeError;
 use crate::modules::{ModuleNode, ModuleTree};
-use crate::python::parsing::parse_python_source;
+use crate::python::parsing::parse_python_source_with_recovery;
 
 use super::django::fkey::{get_foreign_key_references, get_known_apps};
 use super::file_module::FileModule;
 use super::import::{get_normalized_imports, get_normalized_imports_from_ast};
-use crate::dependencies::Dependency;
+use crate::dependencies::{Dependency, ImportContext, NormalizedImports, UnresolvedRelativeImport};
 
 #[derive(Debug)]
 pub struct DjangoMetadata<'a> {
@@ -39,6 +42,8 @@ pub struct InternalDependencyExtractor<'a> {
     project_config: &'a ProjectConfig,
     exclusions: &'a PathExclusions,
     django_metadata: Option<DjangoMetadata<'a>>,
+    import_cache: Option<&'a FileResultCache>,
+    cli_patterns: Vec<glob::Pattern>,
 }
 
 impl<'a> InternalDependencyExtractor<'a> {
@@ -53,6 +58,13 @@ impl<'a> InternalDependencyExtractor<'a> {
             .django
             .as_ref()
             .map(|django_config| DjangoMetadata::new(source_roots, django_config));
+        // Invalid patterns never match
+        let cli_patterns = project_config
+            .rules
+            .cli_patterns
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .collect();
 
         Self {
             source_roots,
@@ -60,15 +72,50 @@ impl<'a> InternalDependencyExtractor<'a> {
             project_config,
             exclusions,
             django_metadata,
+            import_cache: None,
+            cli_patterns,
+        }
+    }
+
+    pub fn with_import_cache(mut self, import_cache: Option<&'a FileResultCache>) -> Self {
+        self.import_cache = import_cache;
+        self
+    }
+
+    /// Whether the file is CLI glue, whose imports are all treated as being in a main block
+    fn is_cli_file(&self, file_module: &FileModule) -> bool {
+        if self.cli_patterns.is_empty() {
+            return false;
         }
+        // Patterns are written with forward slashes on every platform
+        let relative_path = file_module
+            .relative_file_path()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .join("/");
+        self.cli_patterns
+            .iter()
+            .any(|pattern| pattern.matches(&relative_path))
     }
 }
 
+/// Whether the file is a pytest 'conftest.py', which often imports broadly to provide fixtures
+fn is_conftest_file(file_module: &FileModule) -> bool {
+    file_module
+        .relative_file_path()
+        .file_name()
+        .is_some_and(|name| name == "conftest.py")
+}
+
 impl<'a> FileProcessor<'a, ProjectFile<'a>> for InternalDependencyExtractor<'a> {
     type ProcessedFile = FileModule<'a>;
 
     fn process(&self, file_path: ProjectFile<'a>) -> DiagnosticResult<Self::ProcessedFile> {
-        let mod_path = filesystem::file_to_module_path(self.source_roots, file_path.as_ref())?;
+        let mod_path = filesystem::normalized_file_to_module_path(
+            self.source_roots,
+            file_path.as_ref(),
+            &self.project_config.module_path_normalizers,
+        )?;
         let module = self
             .module_tree
             .find_nearest(mod_path.as_ref())
@@ -84,24 +131,85 @@ impl<'a> FileProcessor<'a, ProjectFile<'a>> for InternalDependencyExtractor<'a>
 
         let mut file_module = FileModule::new(file_path, module);
         let mut dependencies: Vec<Dependency> = vec![];
-        let file_ast = parse_python_source(file_module.contents())?;
+        let file_key = file_module.relative_file_path().display().to_string();
+        let cached_imports: Option<NormalizedImports> = self
+            .import_cache
+            .and_then(|cache| cache.get(&file_key, file_module.contents()));
 
-        let project_imports = get_normalized_imports_from_ast(
-            self.source_roots,
-            file_module.file_path(),
-            &file_ast,
-            self.project_config.ignore_type_checking_imports,
-            self.project_config.include_string_imports,
-        )?
-        .into_iter()
-        .filter_map(|import| {
+        // Parsing is skipped entirely when imports are cached (unless Django references are needed)
+        let mut file_ast = None;
+        let normalized_imports = match cached_imports {
+            Some(imports) => imports,
+            None => {
+                let (ast, syntax_errors) =
+                    parse_python_source_with_recovery(file_module.contents());
+                let ast = file_ast.insert(ast);
+                let imports = get_normalized_imports_from_ast(
+                    self.source_roots,
+                    file_module.file_path(),
+                    ast,
+                    self.project_config.ignore_type_checking_imports,
+                    self.project_config.include_string_imports,
+                    self.project_config.analyze_attribute_access,
+                )?
+                .with_syntax_errors(syntax_errors);
+                if let Some(cache) = self.import_cache {
+                    cache.set(&file_key, file_module.contents(), &imports);
+                }
+                imports
+            }
+        };
+
+        let NormalizedImports {
+            mut imports,
+            mut unresolved_relative_imports,
+            import_styles,
+            syntax_errors,
+        } = normalized_imports;
+        if self.is_cli_file(&file_module) {
+            for import in imports.iter_mut() {
+                import.context = import.context.max(ImportContext::MainBlock);
+            }
+        }
+        if is_conftest_file(&file_module) {
+            for import in imports.iter_mut() {
+                import.context = import.context.max(ImportContext::Conftest);
+            }
+        }
+        let mut unresolved_imports = vec![];
+        let report_unresolved_imports = !self.project_config.rules.unresolved_imports.is_off();
+        let project_imports = imports.into_iter().filter_map(|import| {
             if filesystem::is_project_import(
                 self.source_roots,
                 &import.module_path,
                 self.exclusions,
-            ) {
+            ) || self.project_config.is_mapped_module(&import.module_path)
+            {
                 Some(Dependency::Import(import))
             } else {
+                // A relative import always refers to the project, so it is reported unless it was excluded.
+                // Ignore directives are kept, since they may suppress the warning.
+                if !import.is_absolute
+                    && filesystem::module_to_file_path(self.source_roots, &import.module_path, true)
+                        .is_none()
+                {
+                    unresolved_relative_imports.push(UnresolvedRelativeImport::NotFound {
+                        resolution: import.module_path,
+                        import_offset: import.import_offset,
+                    });
+                    return None;
+                }
+                // Likely a typo or a deleted module, which would otherwise be treated as external
+                if report_unresolved_imports
+                    && import.is_absolute
+                    && filesystem::is_unresolved_project_import(
+                        self.source_roots,
+                        &import.module_path,
+                    )
+                {
+                    unresolved_imports.push(import);
+                    return None;
+                }
                 // Remove directives that match irrelevant imports
                 file_module
                     .ignore_directives
@@ -116,6 +224,10 @@ impl<'a> FileProcessor<'a, ProjectFile<'a>> for InternalDependencyExtractor<'a>
         dependencies.extend(project_imports);
 
         if self.django_metadata.is_some() {
+            let file_ast = match file_ast {
+                Some(file_ast) => file_ast,
+                None => parse_python_source_with_recovery(file_module.contents()).0,
+            };
             dependencies.extend(
                 get_foreign_key_references(&file_ast)
                     .into_iter()
@@ -124,6 +236,10 @@ impl<'a> FileProcessor<'a, ProjectFile<'a>> for InternalDependencyExtractor<'a>
         }
 
         file_module.extend_dependencies(dependencies);
+        file_module.unresolved_relative_imports = unresolved_relative_imports;
+        file_module.unresolved_imports = unresolved_imports;
+        file_module.import_styles = import_styles;
+        file_module.syntax_errors = syntax_errors;
         Ok(file_module)
     }
 }
@@ -163,14 +279,17 @@ impl<'a> FileProcessor<'a, ProjectFile<'a>> for ExternalDependencyExtractor<'a>
             file_module.contents(),
             self.project_config.ignore_type_checking_imports,
             false,
+            false,
         )?
+        .imports
         .into_iter()
         .filter_map(|import| {
             if !filesystem::is_project_import(
                 self.source_roots,
                 &import.module_path,
                 self.exclusions,
-            ) {
+            ) && !self.project_config.is_mapped_module(&import.module_path)
+            {
                 Some(Dependency::Import(import))
             } else {
                 // Remove directives that match irrelevant imports
@@ -1,12 +1,15 @@
 use ruff_linter::Locator;
 use ruff_source_file::LineIndex;
-use ruff_text_size::TextSize;
+use ruff_text_size::{TextRange, TextSize};
 use std::{path::Path, sync::Arc};
 
+use crate::diagnostics::SourceSpan;
 use crate::filesystem::ProjectFile;
 use crate::{config::ModuleConfig, modules::ModuleNode};
 
-use crate::dependencies::{Dependency, NormalizedImport, SourceCodeReference};
+use crate::dependencies::{
+    Dependency, ImportStyleUsage, NormalizedImport, SourceCodeReference, UnresolvedRelativeImport,
+};
 use crate::processors::ignore_directive::{get_ignore_directives, IgnoreDirectives};
 
 #[derive(Debug)]
@@ -15,6 +18,13 @@ pub struct FileModule<'a> {
     pub module: Arc<ModuleNode>,
     pub ignore_directives: IgnoreDirectives,
     pub dependencies: Vec<Dependency>,
+    pub unresolved_relative_imports: Vec<UnresolvedRelativeImport>,
+    // Absolute imports which look like project imports, but do not resolve to any module
+    pub unresolved_imports: Vec<NormalizedImport>,
+    // Import statements written in a style which rules can forbid (e.g. wildcard imports)
+    pub import_styles: Vec<ImportStyleUsage>,
+    // Syntax errors which were recovered from, skipping any imports they overlap
+    pub syntax_errors: Vec<TextRange>,
     line_index: LineIndex,
 }
 
@@ -26,6 +36,10 @@ impl<'a> FileModule<'a> {
             file,
             module,
             dependencies: vec![],
+            unresolved_relative_imports: vec![],
+            unresolved_imports: vec![],
+            import_styles: vec![],
+            syntax_errors: vec![],
         }
     }
 
@@ -41,6 +55,21 @@ impl<'a> FileModule<'a> {
         self.line_index.line_index(offset).get()
     }
 
+    /// The position of the given source range, for highlighting it precisely
+    pub fn source_span(&self, range: TextRange) -> SourceSpan {
+        let start = self
+            .line_index
+            .source_location(range.start(), self.contents());
+        let end = self
+            .line_index
+            .source_location(range.end(), self.contents());
+        SourceSpan {
+            column: start.column.get(),
+            end_line_number: end.row.get(),
+            end_column: end.column.get(),
+        }
+    }
+
     pub fn module_config(&self) -> &ModuleConfig {
         self.module.config.as_ref().unwrap()
     }
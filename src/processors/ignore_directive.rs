@@ -11,6 +11,7 @@ use crate::diagnostics::Diagnostic;
 #[derive(Debug, Clone)]
 pub struct IgnoreDirective {
     pub modules: Vec<String>,
+    pub rule_ids: Vec<String>, // Rule codes (e.g. 'TACH001') which the directive is limited to
     pub reason: String,
     pub line_no: usize,         // Where is the directive literally written
     pub ignored_line_no: usize, // Where is the directive being applied
@@ -18,6 +19,10 @@ pub struct IgnoreDirective {
 
 impl IgnoreDirective {
     pub fn matches_located_import(&self, import: &LocatedImport) -> bool {
+        // A directive for specific rules only suppresses their diagnostics, not the import itself
+        if !self.rule_ids.is_empty() {
+            return false;
+        }
         if import.is_absolute() {
             self.modules.is_empty()
                 || self
@@ -41,6 +46,15 @@ impl IgnoreDirective {
             return false;
         }
 
+        // If the directive names rules, it only matches diagnostics from those rules
+        if !self.rule_ids.is_empty()
+            && !diagnostic
+                .rule_id()
+                .is_some_and(|rule_id| self.rule_ids.iter().any(|id| id == rule_id))
+        {
+            return false;
+        }
+
         // If the directive is a blanket ignore, it matches any diagnostic
         if self.modules.is_empty() {
             return true;
@@ -129,6 +143,8 @@ impl Extend<IgnoreDirectives> for IgnoreDirectives {
 static TACH_IGNORE_REGEX: Lazy<regex::Regex> =
     Lazy::new(|| Regex::new(r"# *tach-ignore(?:\(([^)]*)\))?((?:\s+[\w.]+)*)\s*$").unwrap());
 
+static RULE_ID_REGEX: Lazy<regex::Regex> = Lazy::new(|| Regex::new(r"^TACH\d{3}$").unwrap());
+
 pub fn get_ignore_directives(file_content: &str) -> IgnoreDirectives {
     if !file_content.contains("tach-ignore") {
         return IgnoreDirectives::default();
@@ -147,14 +163,11 @@ pub fn get_ignore_directives(file_content: &str) -> IgnoreDirectives {
                 .get(1)
                 .map_or("".to_string(), |m| m.as_str().to_string());
             let ignored_modules = captures.get(2).map_or("", |m| m.as_str());
-            let modules: Vec<String> = if ignored_modules.is_empty() {
-                Vec::new()
-            } else {
-                ignored_modules
-                    .split_whitespace()
-                    .map(|module| module.to_string())
-                    .collect()
-            };
+            // Rule codes and module paths may be mixed, e.g. 'tach-ignore TACH001 core.utils'
+            let (rule_ids, modules): (Vec<String>, Vec<String>) = ignored_modules
+                .split_whitespace()
+                .map(|module| module.to_string())
+                .partition(|module| RULE_ID_REGEX.is_match(module));
 
             let mut ignored_line_no = normal_lineno;
             if line.trim_start().starts_with('#') {
@@ -162,6 +175,7 @@ pub fn get_ignore_directives(file_content: &str) -> IgnoreDirectives {
             }
             let directive = IgnoreDirective {
                 modules,
+                rule_ids,
                 reason,
                 line_no: normal_lineno,
                 ignored_line_no,
@@ -230,4 +244,39 @@ mod tests {
         let directives = get_ignore_directives(content);
         assert!(directives.is_empty());
     }
+
+    #[test]
+    fn test_rule_id_directives() {
+        use crate::diagnostics::{CodeDiagnostic, DiagnosticDetails};
+        use std::path::PathBuf;
+
+        let directives = get_ignore_directives("from foo import bar  # tach-ignore TACH002 foo");
+        let directive = directives.get(&1).expect("Should have directive");
+        assert_eq!(directive.rule_ids, vec!["TACH002".to_string()]);
+        assert_eq!(directive.modules, vec!["foo".to_string()]);
+
+        let diagnostic = |details: CodeDiagnostic| {
+            Diagnostic::new_located_error(
+                PathBuf::from("a.py"),
+                1,
+                None,
+                DiagnosticDetails::Code(details),
+            )
+        };
+        // Only diagnostics of the named rule are ignored
+        assert!(
+            directive.matches_diagnostic(&diagnostic(CodeDiagnostic::DeprecatedDependency {
+                dependency: "foo.bar".to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "foo".to_string(),
+            }))
+        );
+        assert!(
+            !directive.matches_diagnostic(&diagnostic(CodeDiagnostic::UndeclaredDependency {
+                dependency: "foo.bar".to_string(),
+                usage_module: "a".to_string(),
+                definition_module: "foo".to_string(),
+            }))
+        );
+    }
 }
@@ -1,13 +1,23 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::iter;
 use std::path::{Path, PathBuf};
 
+use itertools::Itertools;
 use ruff_python_ast::statement_visitor::{walk_stmt, StatementVisitor};
-use ruff_python_ast::visitor::Visitor;
-use ruff_python_ast::{Expr, Mod, Stmt, StmtIf, StmtImport, StmtImportFrom};
+use ruff_python_ast::visitor::{self, walk_expr, Visitor};
+use ruff_python_ast::{
+    BoolOp, CmpOp, ExceptHandler, Expr, ExprCall, FStringElement, FStringPart, Mod, Operator, Stmt,
+    StmtIf, StmtImport, StmtImportFrom, StmtTry, UnaryOp,
+};
+use ruff_text_size::TextSize;
 use thiserror::Error;
 
-use crate::dependencies::import::NormalizedImport;
-use crate::python::{error::ParsingError, parsing::parse_python_source};
+use crate::dependencies::import::{
+    ImportContext, ImportStyle, ImportStyleUsage, NormalizedImport, NormalizedImports,
+    UnresolvedRelativeImport,
+};
+use crate::python::{error::ParsingError, parsing::parse_python_source_with_recovery};
 use crate::{exclusion, filesystem};
 
 #[derive(Error, Debug)]
@@ -30,7 +40,16 @@ pub struct ImportVisitor {
     file_mod_path: Option<String>,
     is_package: bool,
     ignore_type_checking_imports: bool,
+    context: ImportContext,
+    // Whether the current statement is inside an 'if' block (other than a TYPE_CHECKING or main block)
+    in_conditional: bool,
+    // The values of 'sys.platform' which the current statement is guarded by, if any
+    platforms: Option<Vec<String>>,
     pub normalized_imports: Vec<NormalizedImport>,
+    pub unresolved_relative_imports: Vec<UnresolvedRelativeImport>,
+    // The module path bound to each name by an import, along with the context of the import
+    pub bound_names: HashMap<String, (String, ImportContext)>,
+    pub import_styles: Vec<ImportStyleUsage>,
 }
 
 impl ImportVisitor {
@@ -43,7 +62,13 @@ impl ImportVisitor {
             file_mod_path,
             is_package,
             ignore_type_checking_imports,
+            context: Default::default(),
+            in_conditional: false,
+            platforms: None,
             normalized_imports: Default::default(),
+            unresolved_relative_imports: Default::default(),
+            bound_names: Default::default(),
+            import_styles: Default::default(),
         }
     }
 
@@ -54,12 +79,24 @@ impl ImportVisitor {
         let mut normalized_imports = vec![];
 
         for alias in &import_statement.names {
+            // 'import a.b' binds 'a', while 'import a.b as c' binds 'c' to 'a.b'
+            let (name, bound_path) = match &alias.asname {
+                Some(asname) => (asname.to_string(), alias.name.to_string()),
+                None => {
+                    let top_level = alias.name.split('.').next().unwrap_or_default();
+                    (top_level.to_string(), top_level.to_string())
+                }
+            };
+            self.bound_names.insert(name, (bound_path, self.context));
             let import = NormalizedImport {
                 module_path: alias.name.to_string(),
                 alias_path: None,
                 alias_offset: alias.range.start(),
+                alias_end: alias.range.end(),
                 import_offset: import_statement.range.start(),
                 is_absolute: true,
+                context: self.context,
+                platforms: self.platforms.clone(),
             };
             normalized_imports.push(import);
         }
@@ -90,6 +127,18 @@ impl ImportVisitor {
         };
 
         let base_path_parts: Vec<&str> = mod_path.split('.').collect();
+        // The import refers to a package above the top of the source root.
+        // Importing the source root itself ('from . import x' in a top-level module) is also not possible.
+        if num_paths_to_strip > base_path_parts.len()
+            || (num_paths_to_strip == base_path_parts.len() && import_statement.module.is_none())
+        {
+            self.unresolved_relative_imports
+                .push(UnresolvedRelativeImport::BeyondSourceRoot {
+                    import: relative_import_path(import_statement),
+                    import_offset: import_statement.range.start(),
+                });
+            return normalized_imports;
+        }
         let base_path_parts = if num_paths_to_strip > 0 {
             base_path_parts[..base_path_parts.len() - num_paths_to_strip].to_vec()
         } else {
@@ -115,21 +164,23 @@ impl ImportVisitor {
             // We are importing from the current package ('.') or a parent ('..' or more)
             // We have already stripped parts from the current file's mod path based on the import depth,
             // so we just need to join the remaining parts with a '.'
-            if base_path_parts.is_empty() {
-                // This means we are looking at a current package import outside of a source root
-                return normalized_imports;
-            }
             base_path_parts.join(".")
         };
 
         for name in &import_statement.names {
             let global_mod_path = format!("{}.{}", base_mod_path, name.name.as_str());
+            let alias_path = name.asname.as_ref().unwrap_or(&name.name).to_string();
+            self.bound_names
+                .insert(alias_path.clone(), (global_mod_path.clone(), self.context));
             let import = NormalizedImport {
                 module_path: global_mod_path,
-                alias_path: Some(name.asname.as_ref().unwrap_or(&name.name).to_string()),
+                alias_path: Some(alias_path),
                 alias_offset: name.range.start(),
+                alias_end: name.range.end(),
                 import_offset: import_statement.range.start(),
                 is_absolute: false,
+                context: self.context,
+                platforms: self.platforms.clone(),
             };
 
             normalized_imports.push(import);
@@ -138,38 +189,288 @@ impl ImportVisitor {
         normalized_imports
     }
 
-    fn should_ignore_if_statement(&mut self, node: &StmtIf) -> bool {
-        let id = match node.test.as_ref() {
-            Expr::Name(ref name) => Some(name.id.as_str()),
-            // This will match a single-level attribute access in cases like:
-            // import typing as t; if t.TYPE_CHECKING: ...
-            Expr::Attribute(ref attribute) => Some(attribute.attr.as_str()),
-            _ => None,
-        };
-        id.unwrap_or_default() == "TYPE_CHECKING" && self.ignore_type_checking_imports
+    /// Visit statements within a context, keeping any outer context which defers imports further
+    fn visit_in_context(&mut self, context: ImportContext, body: &[Stmt]) {
+        let outer_context = self.context;
+        self.context = outer_context.max(context);
+        self.visit_body(body);
+        self.context = outer_context;
+    }
+
+    /// Visit an 'if' statement, along with its 'elif' and 'else' branches,
+    /// keeping track of the platforms each branch is limited to by 'sys.platform' checks
+    fn visit_platform_branches(&mut self, node: &StmtIf) {
+        let branches = iter::once((Some(node.test.as_ref()), node.body.as_slice())).chain(
+            node.elif_else_clauses
+                .iter()
+                .map(|clause| (clause.test.as_ref(), clause.body.as_slice())),
+        );
+        // Once a branch which excludes some platforms is skipped, later branches only run on those platforms
+        let mut implied_platforms: Option<Vec<String>> = None;
+        for (test, body) in branches {
+            let guard = test.and_then(platform_guard);
+            let platforms = match &guard {
+                Some(PlatformGuard::Only(platforms)) => Some(platforms.clone()),
+                _ => implied_platforms.clone(),
+            };
+            if let (Some(PlatformGuard::Except(platforms)), None) = (&guard, &implied_platforms) {
+                implied_platforms = Some(platforms.clone());
+            }
+
+            let outer_platforms = self.platforms.clone();
+            if platforms.is_some() {
+                self.platforms = platforms;
+            }
+            self.visit_body(body);
+            self.platforms = outer_platforms;
+        }
+    }
+
+    fn record_import_style(&mut self, style: ImportStyle, import: String, import_offset: TextSize) {
+        self.import_styles.push(ImportStyleUsage {
+            style,
+            import,
+            import_offset,
+        });
     }
 
     fn visit_stmt_import(&mut self, node: &StmtImport) {
+        if self.in_conditional {
+            let import = node
+                .names
+                .iter()
+                .map(|alias| alias.name.as_str())
+                .join(", ");
+            self.record_import_style(ImportStyle::Conditional, import, node.range.start());
+        }
         let stmt_imports = self.normalize_absolute_import(node);
         self.normalized_imports.extend(stmt_imports);
     }
 
     fn visit_stmt_import_from(&mut self, node: &StmtImportFrom) {
+        let import = relative_import_path(node);
+        if node.names.iter().any(|alias| alias.name.as_str() == "*") {
+            self.record_import_style(ImportStyle::Wildcard, import.clone(), node.range.start());
+        }
+        if node.level > 0 {
+            self.record_import_style(ImportStyle::Relative, import.clone(), node.range.start());
+        }
+        if self.in_conditional {
+            self.record_import_style(ImportStyle::Conditional, import, node.range.start());
+        }
         let stmt_imports = self.normalize_import_from(node);
         self.normalized_imports.extend(stmt_imports);
     }
 }
 
+/// The module of an 'import from' statement as written, including any leading dots (e.g. '..utils')
+fn relative_import_path(import_statement: &StmtImportFrom) -> String {
+    format!(
+        "{}{}",
+        ".".repeat(import_statement.level as usize),
+        import_statement
+            .module
+            .as_ref()
+            .map(|module| module.as_str())
+            .unwrap_or_default()
+    )
+}
+
+/// Whether an 'if' statement only runs during type checking, with 'if TYPE_CHECKING:'
+fn is_type_checking_block(node: &StmtIf) -> bool {
+    let id = match node.test.as_ref() {
+        Expr::Name(ref name) => Some(name.id.as_str()),
+        // This will match a single-level attribute access in cases like:
+        // import typing as t; if t.TYPE_CHECKING: ...
+        Expr::Attribute(ref attribute) => Some(attribute.attr.as_str()),
+        _ => None,
+    };
+    id.unwrap_or_default() == "TYPE_CHECKING"
+}
+
+/// Whether an 'if' statement is a script entrypoint, with 'if __name__ == "__main__":' (in either order)
+fn is_main_block(node: &StmtIf) -> bool {
+    let Expr::Compare(compare) = node.test.as_ref() else {
+        return false;
+    };
+    let [CmpOp::Eq] = compare.ops.as_ref() else {
+        return false;
+    };
+    let [comparator] = compare.comparators.as_ref() else {
+        return false;
+    };
+    let is_name = |expr: &Expr| matches!(expr, Expr::Name(name) if name.id.as_str() == "__name__");
+    let is_main = |expr: &Expr| matches!(expr, Expr::StringLiteral(literal) if literal.value.to_str() == "__main__");
+    (is_name(&compare.left) && is_main(comparator))
+        || (is_main(&compare.left) && is_name(comparator))
+}
+
+/// The platforms which a condition limits its branch to, in terms of 'sys.platform'
+#[derive(Debug, PartialEq)]
+enum PlatformGuard {
+    /// The branch only runs on these platforms (e.g. 'sys.platform == "linux"')
+    Only(Vec<String>),
+    /// The branch runs on any platform except these (e.g. 'sys.platform != "win32"')
+    Except(Vec<String>),
+}
+
+impl PlatformGuard {
+    fn negate(self) -> Self {
+        match self {
+            PlatformGuard::Only(platforms) => PlatformGuard::Except(platforms),
+            PlatformGuard::Except(platforms) => PlatformGuard::Only(platforms),
+        }
+    }
+}
+
+fn is_sys_platform(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Attribute(attribute)
+            if attribute.attr.as_str() == "platform"
+                && matches!(attribute.value.as_ref(), Expr::Name(name) if name.id.as_str() == "sys")
+    )
+}
+
+/// String literals, either alone or in a tuple, list or set
+fn string_literals(expr: &Expr) -> Option<Vec<String>> {
+    let elements = match expr {
+        Expr::StringLiteral(literal) => return Some(vec![literal.value.to_str().to_string()]),
+        Expr::Tuple(tuple) => &tuple.elts,
+        Expr::List(list) => &list.elts,
+        Expr::Set(set) => &set.elts,
+        _ => return None,
+    };
+    elements
+        .iter()
+        .map(|element| match element {
+            Expr::StringLiteral(literal) => Some(literal.value.to_str().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Recognizes comparisons with 'sys.platform' (==, !=, in, not in), 'sys.platform.startswith(...)',
+/// and combinations of these with 'not', 'or' and 'and'. Platforms are matched as prefixes.
+fn platform_guard(test: &Expr) -> Option<PlatformGuard> {
+    match test {
+        Expr::Compare(compare) => {
+            let ([op], [comparator]) = (compare.ops.as_ref(), compare.comparators.as_ref()) else {
+                return None;
+            };
+            let platforms = if is_sys_platform(&compare.left) {
+                string_literals(comparator)?
+            } else if is_sys_platform(comparator) && matches!(op, CmpOp::Eq | CmpOp::NotEq) {
+                string_literals(&compare.left)?
+            } else {
+                return None;
+            };
+            match op {
+                CmpOp::Eq | CmpOp::In => Some(PlatformGuard::Only(platforms)),
+                CmpOp::NotEq | CmpOp::NotIn => Some(PlatformGuard::Except(platforms)),
+                _ => None,
+            }
+        }
+        Expr::Call(call) => {
+            let Expr::Attribute(attribute) = call.func.as_ref() else {
+                return None;
+            };
+            if attribute.attr.as_str() != "startswith" || !is_sys_platform(&attribute.value) {
+                return None;
+            }
+            let [prefixes] = call.arguments.args.as_ref() else {
+                return None;
+            };
+            Some(PlatformGuard::Only(string_literals(prefixes)?))
+        }
+        Expr::UnaryOp(unary) if unary.op == UnaryOp::Not => {
+            platform_guard(&unary.operand).map(PlatformGuard::negate)
+        }
+        Expr::BoolOp(bool_op) if bool_op.op == BoolOp::Or => {
+            let mut platforms = vec![];
+            for value in bool_op.values.iter() {
+                let Some(PlatformGuard::Only(value_platforms)) = platform_guard(value) else {
+                    return None;
+                };
+                platforms.extend(value_platforms);
+            }
+            Some(PlatformGuard::Only(platforms))
+        }
+        // Any other condition alongside a platform check only narrows the branch further
+        Expr::BoolOp(bool_op) if bool_op.op == BoolOp::And => {
+            bool_op
+                .values
+                .iter()
+                .find_map(|value| match platform_guard(value) {
+                    Some(PlatformGuard::Only(platforms)) => Some(PlatformGuard::Only(platforms)),
+                    _ => None,
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Whether a 'try' statement handles a failed import, with 'except ImportError', 'except ModuleNotFoundError' or a bare 'except'
+fn handles_import_error(node: &StmtTry) -> bool {
+    fn is_import_error(expr: &Expr) -> bool {
+        match expr {
+            Expr::Name(name) => matches!(name.id.as_str(), "ImportError" | "ModuleNotFoundError"),
+            Expr::Attribute(attribute) => {
+                matches!(
+                    attribute.attr.as_str(),
+                    "ImportError" | "ModuleNotFoundError"
+                )
+            }
+            Expr::Tuple(tuple) => tuple.elts.iter().any(is_import_error),
+            _ => false,
+        }
+    }
+
+    node.handlers.iter().any(|handler| {
+        let ExceptHandler::ExceptHandler(handler) = handler;
+        handler.type_.as_deref().is_none_or(is_import_error)
+    })
+}
+
 impl StatementVisitor<'_> for ImportVisitor {
     fn visit_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Import(statement) => self.visit_stmt_import(statement),
             Stmt::ImportFrom(statement) => self.visit_stmt_import_from(statement),
-            Stmt::If(statement) => {
-                if !self.should_ignore_if_statement(statement) {
-                    walk_stmt(self, stmt)
+            Stmt::If(statement) if is_type_checking_block(statement) => {
+                if !self.ignore_type_checking_imports {
+                    self.visit_in_context(ImportContext::TypeChecking, &statement.body);
+                    for clause in &statement.elif_else_clauses {
+                        self.visit_body(&clause.body);
+                    }
                 }
             }
+            // The 'else' branch of a main block runs on import, so only the body is relaxed
+            Stmt::If(statement) if is_main_block(statement) => {
+                self.visit_in_context(ImportContext::MainBlock, &statement.body);
+                for clause in &statement.elif_else_clauses {
+                    self.visit_body(&clause.body);
+                }
+            }
+            Stmt::FunctionDef(function) => {
+                self.visit_in_context(ImportContext::Function, &function.body)
+            }
+            // Fallback imports in the handlers are also optional
+            Stmt::Try(statement) if handles_import_error(statement) => {
+                self.visit_in_context(ImportContext::TryExcept, &statement.body);
+                for handler in &statement.handlers {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    self.visit_in_context(ImportContext::TryExcept, &handler.body);
+                }
+                self.visit_body(&statement.orelse);
+                self.visit_body(&statement.finalbody);
+            }
+            Stmt::If(statement) => {
+                let outer_conditional = self.in_conditional;
+                self.in_conditional = true;
+                self.visit_platform_branches(statement);
+                self.in_conditional = outer_conditional;
+            }
             _ => walk_stmt(self, stmt),
         }
     }
@@ -177,19 +478,166 @@ impl StatementVisitor<'_> for ImportVisitor {
 
 struct StringImportVisitor<'a> {
     source_roots: &'a [PathBuf],
+    ignore_type_checking_imports: bool,
+    context: ImportContext,
     pub normalized_imports: Vec<NormalizedImport>,
 }
 
 impl<'a> StringImportVisitor<'a> {
-    fn new(source_roots: &'a [PathBuf]) -> Self {
+    fn new(source_roots: &'a [PathBuf], ignore_type_checking_imports: bool) -> Self {
         StringImportVisitor {
             source_roots,
+            ignore_type_checking_imports,
+            context: Default::default(),
             normalized_imports: vec![],
         }
     }
+
+    /// Visit statements within a context, keeping any outer context which defers imports further
+    fn visit_in_context(&mut self, context: ImportContext, body: &[Stmt]) {
+        let outer_context = self.context;
+        self.context = outer_context.max(context);
+        self.visit_body(body);
+        self.context = outer_context;
+    }
+}
+
+/// The constant prefix of a string expression built from literals, f-strings and '+',
+/// along with whether the whole string is constant.
+fn constant_string_prefix(expr: &Expr) -> (String, bool) {
+    match expr {
+        Expr::StringLiteral(literal) => (literal.value.to_str().to_string(), true),
+        Expr::FString(fstring) => {
+            let mut prefix = String::new();
+            for part in fstring.value.iter() {
+                match part {
+                    FStringPart::Literal(literal) => prefix.push_str(&literal.value),
+                    FStringPart::FString(fstring) => {
+                        for element in fstring.elements.iter() {
+                            match element {
+                                FStringElement::Literal(literal) => prefix.push_str(&literal.value),
+                                FStringElement::Expression(_) => return (prefix, false),
+                            }
+                        }
+                    }
+                }
+            }
+            (prefix, true)
+        }
+        Expr::BinOp(binop) if binop.op == Operator::Add => {
+            let (mut prefix, is_constant) = constant_string_prefix(&binop.left);
+            if !is_constant {
+                return (prefix, false);
+            }
+            let (suffix, is_constant) = constant_string_prefix(&binop.right);
+            prefix.push_str(&suffix);
+            (prefix, is_constant)
+        }
+        _ => (String::new(), false),
+    }
+}
+
+/// The module imported by a call to `importlib.import_module` or `__import__`, as far as it
+/// can be determined statically. When the module name is only partly constant
+/// (e.g. `f"plugins.{name}"`), this is the package containing every module it could name.
+fn dynamic_import_module_path(call: &ExprCall) -> Option<String> {
+    let is_import_call = match call.func.as_ref() {
+        Expr::Name(name) => matches!(name.id.as_str(), "import_module" | "__import__"),
+        Expr::Attribute(attribute) => {
+            matches!(attribute.value.as_ref(), Expr::Name(name) if name.id.as_str() == "importlib")
+                && matches!(attribute.attr.as_str(), "import_module" | "__import__")
+        }
+        _ => false,
+    };
+    if !is_import_call {
+        return None;
+    }
+    let (prefix, is_constant) = constant_string_prefix(call.arguments.args.first()?);
+    // Relative module names depend on the 'package' argument
+    if prefix.starts_with('.') {
+        return None;
+    }
+    let module_path = if is_constant {
+        prefix.as_str()
+    } else {
+        // Only complete segments of the prefix name a package
+        prefix.rsplit_once('.')?.0
+    };
+    (!module_path.is_empty()).then(|| module_path.to_string())
 }
 
 impl Visitor<'_> for StringImportVisitor<'_> {
+    // String imports are deferred by the same blocks as import statements (see 'ImportVisitor')
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::If(statement) if is_type_checking_block(statement) => {
+                if !self.ignore_type_checking_imports {
+                    self.visit_in_context(ImportContext::TypeChecking, &statement.body);
+                    for clause in &statement.elif_else_clauses {
+                        self.visit_elif_else_clause(clause);
+                    }
+                }
+            }
+            Stmt::If(statement) if is_main_block(statement) => {
+                self.visit_in_context(ImportContext::MainBlock, &statement.body);
+                for clause in &statement.elif_else_clauses {
+                    self.visit_elif_else_clause(clause);
+                }
+            }
+            // Decorators, default values and annotations are evaluated when the function is defined
+            Stmt::FunctionDef(function) => {
+                for decorator in &function.decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                self.visit_parameters(&function.parameters);
+                if let Some(returns) = &function.returns {
+                    self.visit_annotation(returns);
+                }
+                self.visit_in_context(ImportContext::Function, &function.body);
+            }
+            Stmt::Try(statement) if handles_import_error(statement) => {
+                self.visit_in_context(ImportContext::TryExcept, &statement.body);
+                for handler in &statement.handlers {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    self.visit_in_context(ImportContext::TryExcept, &handler.body);
+                }
+                self.visit_body(&statement.orelse);
+                self.visit_body(&statement.finalbody);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        let Expr::Call(call) = expr else {
+            return walk_expr(self, expr);
+        };
+        let Some(module_path) = dynamic_import_module_path(call) else {
+            return walk_expr(self, expr);
+        };
+
+        if filesystem::module_to_pyfile_or_dir_path(self.source_roots, &module_path).is_some() {
+            self.normalized_imports.push(NormalizedImport {
+                module_path,
+                alias_path: None,
+                alias_offset: call.range.start(),
+                alias_end: call.range.end(),
+                import_offset: call.range.start(),
+                is_absolute: true,
+                context: self.context,
+                platforms: None,
+            });
+        }
+        // The module name has been handled, so it should not also be inferred as a string import
+        self.visit_expr(&call.func);
+        for arg in call.arguments.args.iter().skip(1) {
+            self.visit_expr(arg);
+        }
+        for keyword in call.arguments.keywords.iter() {
+            self.visit_expr(&keyword.value);
+        }
+    }
+
     fn visit_string_literal(&mut self, string_literal: &ruff_python_ast::StringLiteral) {
         // DEFAULT python-infer-string-imports-min-dots is 2
         if string_literal.value.chars().filter(|&c| c == '.').count() < 2 {
@@ -203,29 +651,141 @@ impl Visitor<'_> for StringImportVisitor<'_> {
                 module_path: string_literal.value.to_string(),
                 alias_path: None,
                 alias_offset: string_literal.range.start(),
+                alias_end: string_literal.range.end(),
                 import_offset: string_literal.range.start(),
                 is_absolute: true,
+                context: self.context,
+                platforms: None,
             });
         }
     }
 }
 
+/// Attributes accessed through an imported name (e.g. 'pkg.submodule.func()' after 'import pkg'),
+/// which reach a submodule of the imported module. These are recorded as imports of the submodule,
+/// so that importing a parent module does not hide which of its children are used.
+struct AttributeAccessVisitor<'a> {
+    source_roots: &'a [PathBuf],
+    bound_names: &'a HashMap<String, (String, ImportContext)>,
+    // Modules which are already imported directly, or through an earlier attribute access
+    seen: HashSet<String>,
+    pub normalized_imports: Vec<NormalizedImport>,
+}
+
+impl<'a> AttributeAccessVisitor<'a> {
+    fn new(
+        source_roots: &'a [PathBuf],
+        bound_names: &'a HashMap<String, (String, ImportContext)>,
+        imports: &[NormalizedImport],
+    ) -> Self {
+        AttributeAccessVisitor {
+            source_roots,
+            bound_names,
+            seen: imports
+                .iter()
+                .map(|import| import.module_path.clone())
+                .collect(),
+            normalized_imports: vec![],
+        }
+    }
+
+    /// The submodule reached by an attribute access, if it reaches deeper than the imported module,
+    /// along with the module path used by the access. This is the submodule followed by the member
+    /// accessed on it (if that can be resolved).
+    fn accessed_module_path(
+        &self,
+        base: &str,
+        attributes: &[&str],
+    ) -> Option<(String, String, ImportContext)> {
+        let (bound_path, context) = self.bound_names.get(base)?;
+        let bound_depth = bound_path.split('.').count();
+        let segments: Vec<&str> = bound_path
+            .split('.')
+            .chain(attributes.iter().copied())
+            .collect();
+        let module_depth = (bound_depth + 1..=segments.len()).rev().find(|&depth| {
+            filesystem::module_to_pyfile_or_dir_path(
+                self.source_roots,
+                &segments[..depth].join("."),
+            )
+            .is_some()
+        })?;
+        let submodule_path = segments[..module_depth].join(".");
+        let member_path = segments[..(module_depth + 1).min(segments.len())].join(".");
+        if filesystem::module_to_file_path(self.source_roots, &member_path, true).is_some() {
+            Some((submodule_path, member_path, *context))
+        } else {
+            Some((submodule_path.clone(), submodule_path, *context))
+        }
+    }
+}
+
+/// The name and attributes of a chain of attribute accesses on a name (e.g. 'pkg.submodule.func')
+fn attribute_chain(expr: &Expr) -> Option<(&str, Vec<&str>)> {
+    let mut attributes = vec![];
+    let mut current = expr;
+    loop {
+        match current {
+            Expr::Attribute(attribute) => {
+                attributes.push(attribute.attr.as_str());
+                current = &attribute.value;
+            }
+            Expr::Name(name) => {
+                attributes.reverse();
+                return Some((name.id.as_str(), attributes));
+            }
+            _ => return None,
+        }
+    }
+}
+
+impl Visitor<'_> for AttributeAccessVisitor<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let Expr::Attribute(attribute) = expr else {
+            return walk_expr(self, expr);
+        };
+        let Some((base, attributes)) = attribute_chain(expr) else {
+            return walk_expr(self, expr);
+        };
+
+        if let Some((submodule_path, module_path, context)) =
+            self.accessed_module_path(base, &attributes)
+        {
+            // Only the first access to each submodule is recorded
+            if self.seen.insert(submodule_path) {
+                self.normalized_imports.push(NormalizedImport {
+                    module_path,
+                    alias_path: None,
+                    alias_offset: attribute.range.start(),
+                    alias_end: attribute.range.end(),
+                    import_offset: attribute.range.start(),
+                    is_absolute: true,
+                    context,
+                    platforms: None,
+                });
+            }
+        }
+    }
+}
+
 pub fn get_normalized_imports_from_ast<P: AsRef<Path>>(
     source_roots: &[PathBuf],
     file_path: P,
     file_ast: &Mod,
     ignore_type_checking_imports: bool,
     include_string_imports: bool,
-) -> Result<Vec<NormalizedImport>> {
+    analyze_attribute_access: bool,
+) -> Result<NormalizedImports> {
     let is_package = file_path
         .as_ref()
-        .to_string_lossy()
-        .ends_with("__init__.py");
+        .file_stem()
+        .is_some_and(|stem| stem == "__init__");
     let file_mod_path: Option<String> =
         filesystem::file_to_module_path(source_roots, file_path.as_ref()).ok();
     let mut import_visitor =
         ImportVisitor::new(file_mod_path, is_package, ignore_type_checking_imports);
-    let mut string_import_visitor = StringImportVisitor::new(source_roots);
+    let mut string_import_visitor =
+        StringImportVisitor::new(source_roots, ignore_type_checking_imports);
 
     match file_ast {
         Mod::Module(ref module) => {
@@ -237,36 +797,396 @@ pub fn get_normalized_imports_from_ast<P: AsRef<Path>>(
         Mod::Expression(_) => (), // should error
     };
 
-    if include_string_imports {
+    let mut imports = if include_string_imports {
         let mut result_imports = Vec::with_capacity(
             import_visitor.normalized_imports.len()
                 + string_import_visitor.normalized_imports.len(),
         );
         result_imports.extend(import_visitor.normalized_imports);
         result_imports.extend(string_import_visitor.normalized_imports);
-
-        Ok(result_imports)
+        result_imports
     } else {
-        Ok(import_visitor.normalized_imports)
+        import_visitor.normalized_imports
+    };
+
+    if analyze_attribute_access {
+        if let Mod::Module(ref module) = file_ast {
+            let mut attribute_access_visitor =
+                AttributeAccessVisitor::new(source_roots, &import_visitor.bound_names, &imports);
+            attribute_access_visitor.visit_body(&module.body);
+            imports.extend(attribute_access_visitor.normalized_imports);
+        }
     }
+
+    Ok(NormalizedImports {
+        imports,
+        unresolved_relative_imports: import_visitor.unresolved_relative_imports,
+        import_styles: import_visitor.import_styles,
+        syntax_errors: vec![],
+    })
 }
 
+#[tracing::instrument(level = "trace", name = "extract_imports", skip_all)]
 pub fn get_normalized_imports<P: AsRef<Path>>(
     source_roots: &[PathBuf],
     file_path: P,
     file_contents: &str,
     ignore_type_checking_imports: bool,
     include_string_imports: bool,
-) -> Result<Vec<NormalizedImport>> {
-    let file_ast = parse_python_source(file_contents).map_err(|err| ImportParseError::Parsing {
-        file: file_path.as_ref().to_string_lossy().to_string(),
-        source: err,
-    })?;
-    get_normalized_imports_from_ast(
+    analyze_attribute_access: bool,
+) -> Result<NormalizedImports> {
+    // A localized syntax error (e.g. in an f-string) should not hide the rest of the file's imports
+    let (file_ast, syntax_errors) = parse_python_source_with_recovery(file_contents);
+    Ok(get_normalized_imports_from_ast(
         source_roots,
         file_path,
         &file_ast,
         ignore_type_checking_imports,
         include_string_imports,
-    )
+        analyze_attribute_access,
+    )?
+    .with_syntax_errors(syntax_errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::python::parsing::parse_python_source;
+    use ruff_text_size::{TextRange, TextSize};
+    use std::fs;
+
+    #[test]
+    fn test_import_context() {
+        let source_roots = vec![PathBuf::from("/project")];
+        let contents = r#"
+from typing import TYPE_CHECKING
+import a
+
+if TYPE_CHECKING:
+    import b
+
+try:
+    import c
+except ImportError:
+    import d
+else:
+    import e
+
+class Settings:
+    import f
+
+try:
+    def load():
+        import g
+except (ValueError, ModuleNotFoundError):
+    pass
+
+if __name__ == "__main__":
+    import h
+
+    def main():
+        import i
+else:
+    import j
+"#;
+        let imports = get_normalized_imports(
+            &source_roots,
+            "/project/module.py",
+            contents,
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imports
+                .imports
+                .iter()
+                .map(|import| (import.module_path.as_str(), import.context))
+                .collect::<Vec<_>>(),
+            vec![
+                ("typing.TYPE_CHECKING", ImportContext::TopLevel),
+                ("a", ImportContext::TopLevel),
+                ("b", ImportContext::TypeChecking),
+                ("c", ImportContext::TryExcept),
+                ("d", ImportContext::TryExcept),
+                ("e", ImportContext::TopLevel),
+                ("f", ImportContext::TopLevel),
+                ("g", ImportContext::Function),
+                ("h", ImportContext::MainBlock),
+                ("i", ImportContext::MainBlock),
+                ("j", ImportContext::TopLevel),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_styles() {
+        let source_roots = vec![PathBuf::from("/project")];
+        let contents = r#"
+from typing import TYPE_CHECKING
+from os.path import *
+from . import sibling
+
+if TYPE_CHECKING:
+    import a
+
+if sys.version_info >= (3, 11):
+    import tomllib
+else:
+    from .compat import *
+
+if __name__ == "__main__":
+    import b
+"#;
+        let imports = get_normalized_imports(
+            &source_roots,
+            "/project/package/module.py",
+            contents,
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imports
+                .import_styles
+                .iter()
+                .map(|usage| (usage.style, usage.import.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ImportStyle::Wildcard, "os.path"),
+                (ImportStyle::Relative, "."),
+                (ImportStyle::Conditional, "tomllib"),
+                (ImportStyle::Wildcard, ".compat"),
+                (ImportStyle::Relative, ".compat"),
+                (ImportStyle::Conditional, ".compat"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_platform_guards() {
+        let source_roots = vec![PathBuf::from("/project")];
+        let contents = r#"
+import sys
+import common
+
+if sys.platform == "win32":
+    import winreg
+elif sys.platform.startswith(("linux", "darwin")):
+    import posix_only
+else:
+    import other
+
+if sys.platform != "win32":
+    import fcntl
+else:
+    import msvcrt
+
+if DEBUG and sys.platform in ("linux",):
+    import linux_debug
+"#;
+        let imports = get_normalized_imports(
+            &source_roots,
+            "/project/package/module.py",
+            contents,
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+
+        let platforms = |module_path: &str| {
+            imports
+                .imports
+                .iter()
+                .find(|import| import.module_path == module_path)
+                .unwrap()
+                .platforms
+                .clone()
+        };
+        let strings = |platforms: &[&str]| {
+            Some(
+                platforms
+                    .iter()
+                    .map(|platform| platform.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(platforms("common"), None);
+        assert_eq!(platforms("winreg"), strings(&["win32"]));
+        assert_eq!(platforms("posix_only"), strings(&["linux", "darwin"]));
+        assert_eq!(platforms("other"), None);
+        assert_eq!(platforms("fcntl"), None);
+        assert_eq!(platforms("msvcrt"), strings(&["win32"]));
+        assert_eq!(platforms("linux_debug"), strings(&["linux"]));
+    }
+
+    #[test]
+    fn test_dynamic_import_module_path() {
+        let contents = r#"
+importlib.import_module("plugins.builtin")
+importlib.import_module(f"plugins.{name}")
+import_module("plugins.contrib." + name)
+__import__("plugins" + "." + name + ".hooks")
+importlib.import_module(f"plugins.loader_{name}")
+importlib.import_module(f"{base}.plugins")
+importlib.import_module(f"plugin_{name}")
+importlib.import_module(".local", package=__name__)
+other.import_module("plugins.builtin")
+"#;
+        let Mod::Module(module) = parse_python_source(contents).unwrap() else {
+            panic!("Expected a module");
+        };
+
+        assert_eq!(
+            module
+                .body
+                .iter()
+                .map(|stmt| match stmt {
+                    Stmt::Expr(stmt) => match stmt.value.as_ref() {
+                        Expr::Call(call) => dynamic_import_module_path(call),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                Some("plugins.builtin".to_string()),
+                Some("plugins".to_string()),
+                Some("plugins.contrib".to_string()),
+                Some("plugins".to_string()),
+                Some("plugins".to_string()),
+                None,
+                None,
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attribute_access() {
+        let project = tempfile::TempDir::new().unwrap();
+        let source_root = project.path().join("src");
+        fs::create_dir_all(source_root.join("pkg/other")).unwrap();
+        fs::write(source_root.join("pkg/__init__.py"), "").unwrap();
+        fs::write(source_root.join("pkg/sub.py"), "def func(): ...\n").unwrap();
+        fs::write(source_root.join("pkg/other/__init__.py"), "").unwrap();
+        fs::write(source_root.join("pkg/other/deep.py"), "").unwrap();
+        let source_roots = vec![source_root.clone()];
+        let contents = r#"
+import pkg
+import pkg.other
+from pkg import other as o
+
+def main():
+    pkg.sub.func()
+    pkg.sub.func()
+    pkg.helper()
+    pkg.other.value
+    o.deep.run()
+    unknown.sub.func()
+"#;
+
+        let imports = get_normalized_imports(
+            &source_roots,
+            source_root.join("main.py"),
+            contents,
+            true,
+            false,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imports
+                .imports
+                .iter()
+                .map(|import| (import.module_path.as_str(), import.context))
+                .collect::<Vec<_>>(),
+            vec![
+                ("pkg", ImportContext::TopLevel),
+                ("pkg.other", ImportContext::TopLevel),
+                ("pkg.other", ImportContext::TopLevel),
+                ("pkg.sub.func", ImportContext::TopLevel),
+                ("pkg.other.deep.run", ImportContext::TopLevel),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_import_beyond_source_root() {
+        let source_roots = vec![PathBuf::from("/project/src")];
+        let contents =
+            "from . import sibling\nfrom ..utils import helper\nfrom ...other import thing\n";
+        let imports = get_normalized_imports(
+            &source_roots,
+            "/project/src/pkg/module.py",
+            contents,
+            true,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imports
+                .imports
+                .iter()
+                .map(|import| import.module_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["pkg.sibling", "utils.helper"]
+        );
+        assert_eq!(
+            imports.unresolved_relative_imports,
+            vec![UnresolvedRelativeImport::BeyondSourceRoot {
+                import: "...other".to_string(),
+                import_offset: TextSize::from(49),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recover_from_syntax_error() {
+        let source_roots = vec![PathBuf::from("/project")];
+        let contents = "import a
+message = f\"{value!z}\"
+from b import c
+";
+        let imports = get_normalized_imports(
+            &source_roots,
+            "/project/pkg/module.py",
+            contents,
+            true,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imports
+                .imports
+                .iter()
+                .map(|import| (import.module_path.as_str(), import.alias_range()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a", TextRange::new(TextSize::from(7), TextSize::from(8))),
+                (
+                    "b.c",
+                    TextRange::new(TextSize::from(46), TextSize::from(47))
+                ),
+            ]
+        );
+        // The error is confined to the f-string on the second line
+        let second_line = TextRange::new(TextSize::from(9), TextSize::from(32));
+        assert!(!imports.syntax_errors.is_empty());
+        assert!(imports
+            .syntax_errors
+            .iter()
+            .all(|error| second_line.contains_range(*error)));
+    }
 }
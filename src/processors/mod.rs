@@ -3,6 +3,10 @@ pub mod django;
 pub mod file_module;
 pub mod ignore_directive;
 pub mod import;
+pub mod notebook;
+pub mod reexport;
+pub mod side_effects;
 
 pub use dependency::{ExternalDependencyExtractor, InternalDependencyExtractor};
 pub use file_module::FileModule;
+pub use reexport::ReexportResolver;
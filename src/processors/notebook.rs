@@ -0,0 +1,160 @@
+//! Extracts the Python source of Jupyter notebook (`.ipynb`) code cells.
+//!
+//! Each code cell is treated as its own file, identified by a pseudo path such as
+//! `notebooks/analysis.ipynb#cell-3` (using the 1-based position of the cell in the notebook),
+//! so that line numbers in diagnostics are relative to the cell.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NotebookError {
+    #[error("Failed to parse notebook: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, NotebookError>;
+
+/// Cell magics whose body is still Python code
+const PYTHON_CELL_MAGICS: &[&str] = &["time", "timeit", "capture", "prun", "debug"];
+
+#[derive(Deserialize)]
+struct RawNotebook {
+    #[serde(default)]
+    cells: Vec<RawCell>,
+}
+
+#[derive(Deserialize)]
+struct RawCell {
+    cell_type: String,
+    #[serde(default)]
+    source: CellSource,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CellSource {
+    Lines(Vec<String>),
+    Text(String),
+}
+
+impl Default for CellSource {
+    fn default() -> Self {
+        CellSource::Text(String::new())
+    }
+}
+
+impl CellSource {
+    fn into_text(self) -> String {
+        match self {
+            CellSource::Lines(lines) => lines.concat(),
+            CellSource::Text(text) => text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotebookCell {
+    // 1-based position of the cell in the notebook, counting all cell types
+    pub index: usize,
+    pub source: String,
+}
+
+pub fn is_notebook_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "ipynb")
+}
+
+/// The pseudo path used to report diagnostics within a notebook cell
+pub fn cell_file_path(notebook_path: &Path, index: usize) -> PathBuf {
+    PathBuf::from(format!("{}#cell-{}", notebook_path.display(), index))
+}
+
+/// Replace IPython line magics and shell escapes with `pass`, preserving indentation and line numbers.
+/// Returns None for cells which are not Python, such as `%%bash` cells.
+fn strip_magics(source: &str) -> Option<String> {
+    if let Some(cell_magic) = source.trim_start().strip_prefix("%%") {
+        let magic_name = cell_magic.split_whitespace().next().unwrap_or_default();
+        if !PYTHON_CELL_MAGICS.contains(&magic_name) {
+            return None;
+        }
+    }
+    Some(
+        source
+            .lines()
+            .map(|line| {
+                let code = line.trim_start();
+                if code.starts_with('%') || code.starts_with('!') {
+                    format!("{}pass", &line[..line.len() - code.len()])
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/// Extract the Python source of each code cell in the notebook
+pub fn notebook_code_cells(contents: &str) -> Result<Vec<NotebookCell>> {
+    let notebook: RawNotebook = serde_json::from_str(contents)?;
+    Ok(notebook
+        .cells
+        .into_iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.cell_type == "code")
+        .filter_map(|(position, cell)| {
+            strip_magics(&cell.source.into_text()).map(|source| NotebookCell {
+                index: position + 1,
+                source,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_code_cells() {
+        let contents = r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis"]},
+                {"cell_type": "code", "source": ["%matplotlib inline\n", "import pandas as pd\n", "from core import api"]},
+                {"cell_type": "code", "source": "for x in range(3):\n    !echo hi"},
+                {"cell_type": "code", "source": ["%%bash\n", "ls -la"]},
+                {"cell_type": "code", "source": ["%%time\n", "import core.models"]}
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        }"##;
+        assert_eq!(
+            notebook_code_cells(contents).unwrap(),
+            vec![
+                NotebookCell {
+                    index: 2,
+                    source: "pass\nimport pandas as pd\nfrom core import api".to_string(),
+                },
+                NotebookCell {
+                    index: 3,
+                    source: "for x in range(3):\n    pass".to_string(),
+                },
+                NotebookCell {
+                    index: 5,
+                    source: "pass\nimport core.models".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_file_path() {
+        assert_eq!(
+            cell_file_path(Path::new("notebooks/analysis.ipynb"), 3),
+            PathBuf::from("notebooks/analysis.ipynb#cell-3")
+        );
+    }
+}
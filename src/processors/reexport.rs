@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use dashmap::DashMap;
+
+use crate::filesystem::{self, FileOverlay};
+
+use super::import::get_normalized_imports;
+
+/// Follows re-export chains to find the module which actually provides a symbol.
+///
+/// When `a/__init__.py` contains `from b import symbol`, an import of `a.symbol`
+/// resolves to `b.symbol`. Chains are followed up to `max_depth` hops, and only
+/// through first-party modules.
+#[derive(Debug)]
+pub struct ReexportResolver<'a> {
+    source_roots: &'a [PathBuf],
+    max_depth: usize,
+    // Unsaved contents of files, which take precedence over the contents on disk
+    overlay: &'a FileOverlay,
+    // Maps a file to the names it binds via 'from ... import ...', and their global module paths
+    reexports: DashMap<PathBuf, HashMap<String, String>>,
+}
+
+impl<'a> ReexportResolver<'a> {
+    pub fn new(source_roots: &'a [PathBuf], max_depth: usize, overlay: &'a FileOverlay) -> Self {
+        Self {
+            source_roots,
+            max_depth,
+            overlay,
+            reexports: DashMap::new(),
+        }
+    }
+
+    pub fn resolve(&self, module_path: &str) -> String {
+        let mut current = module_path.to_string();
+        for _ in 0..self.max_depth {
+            match self.provider_of(&current) {
+                Some(provider) if provider != current => current = provider,
+                _ => break,
+            }
+        }
+        current
+    }
+
+    fn provider_of(&self, module_path: &str) -> Option<String> {
+        let resolved = filesystem::module_to_file_path(self.source_roots, module_path, true)?;
+        // Only members (not modules) can be re-exported
+        let member_name = resolved.member_name?;
+
+        let provider = match self.reexports.get(&resolved.file_path) {
+            Some(reexports) => reexports.get(&member_name).cloned(),
+            None => {
+                let reexports = self.parse_reexports(&resolved.file_path);
+                let provider = reexports.get(&member_name).cloned();
+                self.reexports.insert(resolved.file_path, reexports);
+                provider
+            }
+        }?;
+
+        // Stop at the boundary of the project
+        filesystem::module_to_file_path(self.source_roots, &provider, true)?;
+        Some(provider)
+    }
+
+    fn parse_reexports(&self, file_path: &Path) -> HashMap<String, String> {
+        let Ok(contents) = filesystem::read_file_content_with_overlay(file_path, self.overlay)
+        else {
+            return HashMap::new();
+        };
+        // Imports under TYPE_CHECKING are not available at runtime, so they are never re-exports
+        get_normalized_imports(self.source_roots, file_path, &contents, true, false, false)
+            .map(|imports| {
+                imports
+                    .imports
+                    .into_iter()
+                    .filter(|import| !import.is_absolute)
+                    .filter_map(|import| {
+                        import
+                            .alias_path
+                            .map(|bound_name| (bound_name, import.module_path))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[fixture]
+    fn source_root() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let files = [
+            (
+                "api/__init__.py",
+                "from .impl import helper\nfrom core import widget",
+            ),
+            ("api/impl.py", "from core.widgets import widget as helper"),
+            ("core/__init__.py", "from core.widgets import widget"),
+            ("core/widgets.py", "widget = object()"),
+        ];
+        for (file_name, content) in files {
+            let file_path = temp_dir.path().join(file_name);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, content).unwrap();
+        }
+        temp_dir
+    }
+
+    #[rstest]
+    #[case("api.helper", 3, "core.widgets.widget")]
+    #[case("api.helper", 1, "api.impl.helper")]
+    #[case("api.widget", 3, "core.widgets.widget")]
+    #[case("core.widgets.widget", 3, "core.widgets.widget")]
+    #[case("api.impl", 3, "api.impl")]
+    fn test_resolve_reexports(
+        source_root: TempDir,
+        #[case] module_path: &str,
+        #[case] max_depth: usize,
+        #[case] expected: &str,
+    ) {
+        let source_roots = vec![source_root.path().to_path_buf()];
+        let overlay = FileOverlay::new();
+        let resolver = ReexportResolver::new(&source_roots, max_depth, &overlay);
+        assert_eq!(resolver.resolve(module_path), expected);
+    }
+
+    #[rstest]
+    fn test_resolve_reexports_from_overlay(source_root: TempDir) {
+        let source_roots = vec![source_root.path().to_path_buf()];
+        // The unsaved contents of 'api/__init__.py' no longer re-export 'helper'
+        let overlay = FileOverlay::from([(
+            source_root.path().join("api/__init__.py"),
+            "from core import widget".to_string(),
+        )]);
+        let resolver = ReexportResolver::new(&source_roots, 3, &overlay);
+        assert_eq!(resolver.resolve("api.helper"), "api.helper");
+        assert_eq!(resolver.resolve("api.widget"), "core.widgets.widget");
+    }
+}
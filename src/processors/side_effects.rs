@@ -0,0 +1,282 @@
+//! Heuristic detection of code which runs when a module is imported.
+//!
+//! Only statements which execute at import time are considered: module-level code,
+//! class bodies and decorators. Function bodies, `if __name__ == "__main__":` blocks
+//! and `if TYPE_CHECKING:` blocks are skipped.
+
+use ruff_python_ast::visitor::{walk_expr, walk_stmt, Visitor};
+use ruff_python_ast::{CmpOp, Expr, Stmt, StmtIf};
+use ruff_text_size::{Ranged, TextSize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SideEffectKind {
+    Network,
+    Filesystem,
+    Database,
+    Subprocess,
+    HeavyImport,
+}
+
+impl SideEffectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SideEffectKind::Network => "network",
+            SideEffectKind::Filesystem => "filesystem",
+            SideEffectKind::Database => "database",
+            SideEffectKind::Subprocess => "subprocess",
+            SideEffectKind::HeavyImport => "heavy_import",
+        }
+    }
+}
+
+/// Calls which are assumed to have side effects.
+/// A pattern ending in '.' matches any call within that module (e.g. 'requests.get'),
+/// a pattern starting with '.' matches a method call on any object (e.g. 'path.read_text'),
+/// and a bare name also matches when accessed through a module (e.g. 'sqlalchemy.create_engine').
+const SIDE_EFFECT_CALLS: &[(&str, SideEffectKind)] = &[
+    ("requests.", SideEffectKind::Network),
+    ("httpx.", SideEffectKind::Network),
+    ("aiohttp.", SideEffectKind::Network),
+    ("urlopen", SideEffectKind::Network),
+    ("socket.create_connection", SideEffectKind::Network),
+    ("boto3.client", SideEffectKind::Network),
+    ("boto3.resource", SideEffectKind::Network),
+    ("insecure_channel", SideEffectKind::Network),
+    ("secure_channel", SideEffectKind::Network),
+    ("open", SideEffectKind::Filesystem),
+    ("os.makedirs", SideEffectKind::Filesystem),
+    ("os.mkdir", SideEffectKind::Filesystem),
+    ("os.remove", SideEffectKind::Filesystem),
+    ("os.listdir", SideEffectKind::Filesystem),
+    ("os.walk", SideEffectKind::Filesystem),
+    ("shutil.", SideEffectKind::Filesystem),
+    ("glob.glob", SideEffectKind::Filesystem),
+    (".read_text", SideEffectKind::Filesystem),
+    (".read_bytes", SideEffectKind::Filesystem),
+    (".write_text", SideEffectKind::Filesystem),
+    (".write_bytes", SideEffectKind::Filesystem),
+    ("sqlite3.connect", SideEffectKind::Database),
+    ("psycopg.connect", SideEffectKind::Database),
+    ("psycopg2.connect", SideEffectKind::Database),
+    ("pymysql.connect", SideEffectKind::Database),
+    ("create_engine", SideEffectKind::Database),
+    ("MongoClient", SideEffectKind::Database),
+    ("redis.Redis", SideEffectKind::Database),
+    ("redis.from_url", SideEffectKind::Database),
+    ("subprocess.", SideEffectKind::Subprocess),
+    ("os.system", SideEffectKind::Subprocess),
+    ("os.popen", SideEffectKind::Subprocess),
+];
+
+/// Packages which are slow to import
+const HEAVY_IMPORTS: &[&str] = &[
+    "pandas",
+    "numpy",
+    "scipy",
+    "sklearn",
+    "matplotlib",
+    "torch",
+    "tensorflow",
+    "keras",
+    "jax",
+    "transformers",
+    "cv2",
+    "pyspark",
+    "polars",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SideEffect {
+    pub kind: SideEffectKind,
+    // The call or import which was detected, e.g. 'requests.get' or 'pandas'
+    pub detail: String,
+    pub offset: TextSize,
+}
+
+/// The dotted name of a call target, e.g. 'os.path.join'.
+/// Method calls on other expressions are named by their attribute alone, e.g. '.read_text'.
+fn call_name(func: &Expr) -> Option<String> {
+    match func {
+        Expr::Name(name) => Some(name.id.as_str().to_string()),
+        Expr::Attribute(attribute) => Some(match call_name(&attribute.value) {
+            Some(base) if !base.starts_with('.') => format!("{}.{}", base, attribute.attr),
+            _ => format!(".{}", attribute.attr),
+        }),
+        _ => None,
+    }
+}
+
+fn classify_call(name: &str) -> Option<SideEffectKind> {
+    SIDE_EFFECT_CALLS
+        .iter()
+        .find(|(pattern, _)| {
+            if pattern.starts_with('.') {
+                name.ends_with(pattern)
+            } else if pattern.ends_with('.') {
+                name.starts_with(pattern)
+            } else if pattern.contains('.') {
+                name == *pattern
+            } else {
+                name == *pattern || name.ends_with(&format!(".{}", pattern))
+            }
+        })
+        .map(|(_, kind)| *kind)
+}
+
+fn is_heavy_import(module_path: &str) -> bool {
+    let package = module_path.split('.').next().unwrap_or_default();
+    HEAVY_IMPORTS.contains(&package)
+}
+
+fn is_main_guard(node: &StmtIf) -> bool {
+    let Expr::Compare(compare) = node.test.as_ref() else {
+        return false;
+    };
+    matches!(compare.ops.as_ref(), [CmpOp::Eq])
+        && matches!(compare.left.as_ref(), Expr::Name(name) if name.id.as_str() == "__name__")
+}
+
+fn is_type_checking_guard(node: &StmtIf) -> bool {
+    match node.test.as_ref() {
+        Expr::Name(name) => name.id.as_str() == "TYPE_CHECKING",
+        Expr::Attribute(attribute) => attribute.attr.as_str() == "TYPE_CHECKING",
+        _ => false,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SideEffectVisitor {
+    pub side_effects: Vec<SideEffect>,
+}
+
+impl SideEffectVisitor {
+    fn push(&mut self, kind: SideEffectKind, detail: String, offset: TextSize) {
+        self.side_effects.push(SideEffect {
+            kind,
+            detail,
+            offset,
+        });
+    }
+}
+
+impl<'a> Visitor<'a> for SideEffectVisitor {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            // Only the decorators of a function run at import time
+            Stmt::FunctionDef(function) => {
+                for decorator in &function.decorator_list {
+                    self.visit_decorator(decorator);
+                }
+            }
+            Stmt::Import(import) => {
+                for alias in import
+                    .names
+                    .iter()
+                    .filter(|alias| is_heavy_import(alias.name.as_str()))
+                {
+                    self.push(
+                        SideEffectKind::HeavyImport,
+                        alias.name.to_string(),
+                        import.start(),
+                    );
+                }
+            }
+            Stmt::ImportFrom(import) => {
+                if let Some(module) = import.module.as_ref().filter(|_| import.level == 0) {
+                    if is_heavy_import(module.as_str()) {
+                        self.push(
+                            SideEffectKind::HeavyImport,
+                            module.to_string(),
+                            import.start(),
+                        );
+                    }
+                }
+            }
+            Stmt::If(node) if is_main_guard(node) || is_type_checking_guard(node) => {}
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            // The body of a lambda does not run at import time
+            Expr::Lambda(_) => {}
+            Expr::Call(call) => {
+                if let Some(name) = call_name(&call.func) {
+                    if let Some(kind) = classify_call(&name) {
+                        self.push(kind, name, call.start());
+                    }
+                }
+                walk_expr(self, expr);
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// Find code in a module body which is likely to have side effects, or to be slow, when the module is imported
+pub fn find_import_side_effects(body: &[Stmt]) -> Vec<SideEffect> {
+    let mut visitor = SideEffectVisitor::default();
+    visitor.visit_body(body);
+    visitor.side_effects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::python::parsing::parse_python_source;
+    use ruff_python_ast::Mod;
+
+    fn side_effects(source: &str) -> Vec<(SideEffectKind, String)> {
+        let Mod::Module(module) = parse_python_source(source).unwrap() else {
+            panic!("Expected ast::Mod variant");
+        };
+        find_import_side_effects(&module.body)
+            .into_iter()
+            .map(|side_effect| (side_effect.kind, side_effect.detail))
+            .collect()
+    }
+
+    #[test]
+    fn test_top_level_side_effects() {
+        let source = r#"
+import pandas as pd
+from sqlalchemy import create_engine
+from pathlib import Path
+
+engine = create_engine("postgresql://")
+SCHEMA = Path("schema.json").read_text()
+
+class Settings:
+    token = requests.get("https://example.com").json()
+"#;
+        assert_eq!(
+            side_effects(source),
+            vec![
+                (SideEffectKind::HeavyImport, "pandas".to_string()),
+                (SideEffectKind::Database, "create_engine".to_string()),
+                (SideEffectKind::Filesystem, ".read_text".to_string()),
+                (SideEffectKind::Network, "requests.get".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deferred_code_is_ignored() {
+        let source = r#"
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    import numpy
+
+def load():
+    return open("data.csv").read()
+
+handler = lambda: subprocess.run(["ls"])
+
+if __name__ == "__main__":
+    os.system("echo hello")
+"#;
+        assert!(side_effects(source).is_empty());
+    }
+}
@@ -4,7 +4,8 @@ use ruff_python_ast::{
     statement_visitor::{walk_stmt, StatementVisitor},
     Expr, Mod, Stmt,
 };
-use ruff_python_parser::{parse, Mode};
+use ruff_python_parser::{parse, parse_unchecked, Mode};
+use ruff_text_size::TextRange;
 
 use super::error::ParsingError;
 use crate::filesystem::module_to_file_path;
@@ -12,10 +13,20 @@ use crate::filesystem::module_to_file_path;
 pub type Result<T> = std::result::Result<T, ParsingError>;
 
 /// Use the ruff-python-parser crate to parse a Python source file into an AST
+#[tracing::instrument(level = "trace", skip_all)]
 pub fn parse_python_source(python_source: &str) -> Result<Mod> {
     Ok(parse(python_source, Mode::Module)?.syntax().to_owned())
 }
 
+/// Parse a Python source file into an AST, recovering from syntax errors.
+/// Returns the (possibly partial) AST along with the source range of each syntax error.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn parse_python_source_with_recovery(python_source: &str) -> (Mod, Vec<TextRange>) {
+    let parsed = parse_unchecked(python_source, Mode::Module);
+    let syntax_errors = parsed.errors().iter().map(|error| error.location).collect();
+    (parsed.into_syntax(), syntax_errors)
+}
+
 struct InterfaceVisitor {
     members: Vec<String>,
 }
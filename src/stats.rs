@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::ProjectConfig;
+use crate::exclusion::{PathExclusionError, PathExclusions};
+use crate::filesystem::{walk_pyfiles, WalkOptions};
+
+#[derive(Error, Debug)]
+pub enum StatsError {
+    #[error("Failed to access usage stats file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize usage stats: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Path exclusion error: {0}")]
+    PathExclusion(#[from] PathExclusionError),
+}
+
+pub type Result<T> = std::result::Result<T, StatsError>;
+
+static STATS_DIR: &str = ".tach";
+static STATS_FILE: &str = "usage-stats.jsonl";
+
+/// Number of most recent runs compared against earlier runs when computing trends.
+const TREND_WINDOW: usize = 5;
+
+/// A single recorded invocation of a tach command.
+/// These records are only ever written to the local '.tach' directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub command: String,
+    pub timestamp: u64,
+    pub duration_ms: u64,
+    pub file_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+}
+
+fn stats_path(project_root: &Path) -> PathBuf {
+    project_root.join(STATS_DIR).join(STATS_FILE)
+}
+
+fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn count_source_files(project_root: &Path, project_config: &ProjectConfig) -> Result<usize> {
+    let exclusions = PathExclusions::new(
+        project_root,
+        &project_config.exclude,
+        &project_config.ignore,
+    )?;
+    Ok(project_config
+        .prepend_roots(project_root)
+        .iter()
+        .map(|source_root| {
+            walk_pyfiles(
+                &source_root.display().to_string(),
+                &exclusions,
+                WalkOptions::from(project_config),
+            )
+            .count()
+        })
+        .sum())
+}
+
+pub fn record_usage(
+    project_root: &Path,
+    project_config: &ProjectConfig,
+    command: String,
+    duration_ms: u64,
+    cache_hit: Option<bool>,
+) -> Result<()> {
+    if !project_config.record_usage_stats {
+        return Ok(());
+    }
+
+    let record = UsageRecord {
+        command,
+        timestamp: now_timestamp(),
+        duration_ms,
+        file_count: count_source_files(project_root, project_config)?,
+        cache_hit,
+    };
+
+    let path = stats_path(project_root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    Ok(())
+}
+
+pub fn load_usage(project_root: &Path) -> Result<Vec<UsageRecord>> {
+    let path = stats_path(project_root);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    // Skip malformed lines rather than failing, the file may have been
+    // truncated by an interrupted write.
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSummary {
+    pub command: String,
+    pub runs: usize,
+    pub average_duration_ms: u64,
+    pub last_duration_ms: u64,
+    pub average_file_count: usize,
+    pub cache_hit_rate: Option<f64>,
+    /// Percentage change of the recent average duration against earlier runs
+    pub duration_trend: Option<f64>,
+}
+
+fn average(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let (sum, count) = values.fold((0u64, 0u64), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count)
+    }
+}
+
+fn summarize_command(command: String, records: &[&UsageRecord]) -> CommandSummary {
+    let durations = || records.iter().map(|record| record.duration_ms);
+    let cache_results: Vec<bool> = records.iter().filter_map(|r| r.cache_hit).collect();
+    let cache_hit_rate = if cache_results.is_empty() {
+        None
+    } else {
+        Some(cache_results.iter().filter(|hit| **hit).count() as f64 / cache_results.len() as f64)
+    };
+
+    let duration_trend = if records.len() > TREND_WINDOW {
+        let (earlier, recent) = records.split_at(records.len() - TREND_WINDOW);
+        let earlier_avg = average(earlier.iter().map(|r| r.duration_ms)).unwrap_or_default();
+        let recent_avg = average(recent.iter().map(|r| r.duration_ms)).unwrap_or_default();
+        if earlier_avg == 0 {
+            None
+        } else {
+            Some((recent_avg as f64 - earlier_avg as f64) / earlier_avg as f64 * 100.0)
+        }
+    } else {
+        None
+    };
+
+    CommandSummary {
+        command,
+        runs: records.len(),
+        average_duration_ms: average(durations()).unwrap_or_default(),
+        last_duration_ms: records.last().map(|r| r.duration_ms).unwrap_or_default(),
+        average_file_count: average(records.iter().map(|r| r.file_count as u64)).unwrap_or_default()
+            as usize,
+        cache_hit_rate,
+        duration_trend,
+    }
+}
+
+pub fn summarize_usage(records: &[UsageRecord]) -> Vec<CommandSummary> {
+    let mut by_command: BTreeMap<&str, Vec<&UsageRecord>> = BTreeMap::new();
+    for record in records {
+        by_command
+            .entry(record.command.as_str())
+            .or_default()
+            .push(record);
+    }
+    by_command
+        .into_iter()
+        .map(|(command, records)| summarize_command(command.to_string(), &records))
+        .collect()
+}
+
+fn format_trend(trend: Option<f64>) -> String {
+    match trend {
+        Some(trend) if trend >= 0.0 => format!("+{:.1}%", trend),
+        Some(trend) => format!("{:.1}%", trend),
+        None => "-".to_string(),
+    }
+}
+
+pub fn format_usage_stats(project_root: &Path) -> Result<String> {
+    let records = load_usage(project_root)?;
+    if records.is_empty() {
+        return Ok(format!(
+            "No usage stats recorded. Set 'record_usage_stats = true' in tach.toml to start recording (stored in {}).",
+            Path::new(STATS_DIR).join(STATS_FILE).display()
+        ));
+    }
+
+    let mut lines = vec![format!(
+        "{:<16} {:>6} {:>10} {:>10} {:>8} {:>10} {:>8}",
+        "Command", "Runs", "Avg (ms)", "Last (ms)", "Files", "Cache hit", "Trend"
+    )];
+    for summary in summarize_usage(&records) {
+        lines.push(format!(
+            "{:<16} {:>6} {:>10} {:>10} {:>8} {:>10} {:>8}",
+            summary.command,
+            summary.runs,
+            summary.average_duration_ms,
+            summary.last_duration_ms,
+            summary.average_file_count,
+            summary
+                .cache_hit_rate
+                .map(|rate| format!("{:.0}%", rate * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+            format_trend(summary.duration_trend),
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn record(command: &str, duration_ms: u64, cache_hit: Option<bool>) -> UsageRecord {
+        UsageRecord {
+            command: command.to_string(),
+            timestamp: 0,
+            duration_ms,
+            file_count: 10,
+            cache_hit,
+        }
+    }
+
+    #[rstest]
+    fn test_summarize_usage_groups_by_command() {
+        let records = vec![
+            record("check", 100, None),
+            record("test", 400, Some(true)),
+            record("check", 300, None),
+            record("test", 200, Some(false)),
+        ];
+        let summaries = summarize_usage(&records);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].command, "check");
+        assert_eq!(summaries[0].runs, 2);
+        assert_eq!(summaries[0].average_duration_ms, 200);
+        assert_eq!(summaries[0].last_duration_ms, 300);
+        assert_eq!(summaries[0].cache_hit_rate, None);
+        assert_eq!(summaries[1].cache_hit_rate, Some(0.5));
+    }
+
+    #[rstest]
+    fn test_summarize_usage_trend() {
+        let mut records: Vec<_> = (0..5).map(|_| record("check", 100, None)).collect();
+        records.extend((0..5).map(|_| record("check", 50, None)));
+        let summaries = summarize_usage(&records);
+        assert_eq!(summaries[0].duration_trend, Some(-50.0));
+    }
+}